@@ -4,9 +4,15 @@
 use rust_decimal::Decimal;
 use rust_decimal::prelude::*;
 use serde::{Serialize, Deserialize};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
 use chrono::{DateTime, Utc, Duration};
 
+use crate::advanced_trading::value_at_risk::pairwise_correlations;
+use crate::errors::TradingResult;
+use crate::kline_store::{klines_to_price_data, KlineStore};
+use crate::models::PriceData;
+
 /// Kelly Criterion position sizer with safety adjustments
 #[derive(Debug, Clone)]
 pub struct KellyPositionSizer {
@@ -36,6 +42,11 @@ impl KellyPositionSizer {
             active_positions: Vec::new(),
         }
     }
+
+    /// Number of trade results currently tracked
+    pub fn trade_count(&self) -> usize {
+        self.win_history.len()
+    }
     
     /// Calculate optimal position size using Kelly Criterion
     pub fn calculate_position_size(
@@ -135,6 +146,12 @@ impl KellyPositionSizer {
     }
 }
 
+impl Default for KellyPositionSizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// ATR-based dynamic stop loss calculator
 #[derive(Debug, Clone)]
 pub struct DynamicStopLoss {
@@ -165,6 +182,32 @@ impl DynamicStopLoss {
             current_atr: 0.0,
         }
     }
+
+    /// Stop calculator with custom ATR multiples, stop bounds (percent) and trailing activation (percent profit)
+    pub fn with_params(
+        atr_period: usize,
+        atr_multiplier: f64,
+        min_stop_percent: f64,
+        max_stop_percent: f64,
+        trailing_activation: f64,
+        trailing_distance: f64,
+    ) -> Self {
+        Self {
+            atr_multiplier,
+            min_stop_percent,
+            max_stop_percent,
+            trailing_activation,
+            trailing_distance,
+            atr_period,
+            true_ranges: VecDeque::with_capacity(atr_period),
+            ..Self::new()
+        }
+    }
+
+    /// Average true range over the most recent `atr_period` bars
+    pub fn current_atr(&self) -> f64 {
+        self.current_atr
+    }
     
     /// Update ATR with new price data
     pub fn update_atr(&mut self, high: f64, low: f64, prev_close: f64) {
@@ -262,128 +305,477 @@ impl DynamicStopLoss {
     }
 }
 
-/// Portfolio heat tracker for overall risk management
+/// Portfolio heat and correlated exposure limits, in percent of equity
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatLimits {
+    /// Open risk (distance to stop times size) across every bot
+    pub max_portfolio_heat: f64,
+    /// Open risk within one correlation cluster
+    pub max_cluster_heat: f64,
+    /// Gross notional within one correlation cluster
+    pub max_correlation_exposure: f64,
+    /// Average pairwise return correlation at which symbols are clustered together
+    pub correlation_threshold: f64,
+    /// Daily returns in the rolling correlation window
+    pub correlation_window: usize,
+    /// Stop distance assumed for positions held without a protective stop
+    pub default_stop_percent: f64,
+    /// Entries that would have to shrink below this fraction of their size are blocked
+    pub min_entry_fraction: f64,
+}
+
+impl Default for HeatLimits {
+    fn default() -> Self {
+        Self {
+            max_portfolio_heat: 6.0,
+            max_cluster_heat: 3.0,
+            max_correlation_exposure: 60.0,
+            correlation_threshold: 0.7,
+            correlation_window: 90,
+            default_stop_percent: 5.0,
+            min_entry_fraction: 0.25,
+        }
+    }
+}
+
+impl HeatLimits {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_portfolio_heat <= 0.0 || self.max_portfolio_heat > 100.0 {
+            return Err("Max portfolio heat must be between 0 and 100 percent".to_string());
+        }
+        if self.max_cluster_heat <= 0.0 || self.max_cluster_heat > self.max_portfolio_heat {
+            return Err("Max cluster heat must be positive and no more than the portfolio heat".to_string());
+        }
+        if self.max_correlation_exposure <= 0.0 || self.max_correlation_exposure > 1000.0 {
+            return Err("Max correlation exposure must be between 0 and 1000 percent".to_string());
+        }
+        if !(0.0..1.0).contains(&self.correlation_threshold) || self.correlation_threshold == 0.0 {
+            return Err("Correlation threshold must be between 0 and 1".to_string());
+        }
+        if self.correlation_window < 20 {
+            return Err("Correlation window must be at least 20 daily returns".to_string());
+        }
+        if self.default_stop_percent <= 0.0 || self.default_stop_percent > 50.0 {
+            return Err("Default stop must be between 0 and 50 percent".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.min_entry_fraction) {
+            return Err("Min entry fraction must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Heat tracker shared by every bot placing entries
+pub type SharedHeatTracker = Arc<Mutex<PortfolioHeatTracker>>;
+
+/// Portfolio heat tracker for overall risk management; correlation groups are
+/// clusters of rolling daily return correlations of held and candidate symbols
 #[derive(Debug, Clone)]
 pub struct PortfolioHeatTracker {
     positions: Vec<PositionRisk>,
-    max_portfolio_heat: f64,
-    correlation_threshold: f64,
+    limits: HeatLimits,
+    price_history: HashMap<String, Vec<PriceData>>,
+    history_loaded_at: HashMap<String, DateTime<Utc>>,
+    correlations: HashMap<(String, String), f64>,
+    clusters: Vec<Vec<String>>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PositionRisk {
+    /// Bot or engine holding the position
+    pub owner: String,
     pub symbol: String,
     pub entry_price: Decimal,
     pub stop_loss: Decimal,
+    /// Notional value of the position
     pub position_size: Decimal,
     pub risk_amount: Decimal,
     pub risk_percent: f64,
     pub correlation_group: Option<String>,
 }
 
+impl PositionRisk {
+    pub fn new(
+        owner: &str,
+        symbol: &str,
+        entry_price: Decimal,
+        stop_loss: Decimal,
+        position_size: Decimal,
+        account_balance: Decimal,
+    ) -> Self {
+        let risk_amount = if entry_price > Decimal::ZERO {
+            (entry_price - stop_loss).abs() * position_size.abs() / entry_price
+        } else {
+            Decimal::ZERO
+        };
+        let risk_percent = if account_balance > Decimal::ZERO {
+            (risk_amount / account_balance * Decimal::from(100)).to_f64().unwrap_or(0.0)
+        } else {
+            0.0
+        };
+
+        Self {
+            owner: owner.to_string(),
+            symbol: symbol.to_uppercase(),
+            entry_price,
+            stop_loss,
+            position_size,
+            risk_amount,
+            risk_percent,
+            correlation_group: None,
+        }
+    }
+}
+
+/// Outcome of checking a new entry against the heat and correlated exposure limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeatCheck {
+    pub symbol: String,
+    pub cluster: String,
+    pub cluster_members: Vec<String>,
+    pub requested_notional: f64,
+    pub allowed_notional: f64,
+    /// Fraction of the requested size that fits within the limits
+    pub scale: f64,
+    pub blocked: bool,
+    /// Heat and exposure before the entry, in percent of equity
+    pub total_heat: f64,
+    pub cluster_heat: f64,
+    pub cluster_exposure: f64,
+    pub reasons: Vec<String>,
+}
+
+impl HeatCheck {
+    pub fn explanation(&self) -> String {
+        if self.reasons.is_empty() {
+            return format!("{} within heat limits (cluster {})", self.symbol, self.cluster);
+        }
+        format!("{} {} to {:.0}% of requested size: {}",
+            self.symbol,
+            if self.blocked { "blocked, only fits" } else { "down-sized" },
+            self.scale * 100.0,
+            self.reasons.join("; "))
+    }
+}
+
 impl PortfolioHeatTracker {
     pub fn new() -> Self {
+        Self::with_limits(HeatLimits::default())
+    }
+
+    pub fn with_limits(limits: HeatLimits) -> Self {
         Self {
             positions: Vec::new(),
-            max_portfolio_heat: 0.06,  // 6% maximum portfolio heat
-            correlation_threshold: 0.7,  // Correlation threshold for grouping
+            limits,
+            price_history: HashMap::new(),
+            history_loaded_at: HashMap::new(),
+            correlations: HashMap::new(),
+            clusters: Vec::new(),
         }
     }
-    
-    /// Add a new position to track
+
+    pub fn limits(&self) -> &HeatLimits {
+        &self.limits
+    }
+
+    pub fn set_limits(&mut self, limits: HeatLimits) -> Result<(), String> {
+        limits.validate()?;
+        self.limits = limits;
+        self.recluster();
+        Ok(())
+    }
+
+    /// Add a new position to track, replacing any the owner already holds in the symbol
     pub fn add_position(
         &mut self,
-        symbol: String,
+        owner: &str,
+        symbol: &str,
         entry_price: Decimal,
         stop_loss: Decimal,
         position_size: Decimal,
         account_balance: Decimal,
     ) {
-        let risk_amount = (entry_price - stop_loss).abs() * position_size / entry_price;
-        let risk_percent = (risk_amount / account_balance * Decimal::from(100))
-            .to_f64()
-            .unwrap_or(0.0);
-        
-        let position = PositionRisk {
-            symbol: symbol.clone(),
-            entry_price,
-            stop_loss,
-            position_size,
-            risk_amount,
-            risk_percent,
-            correlation_group: self.determine_correlation_group(&symbol),
-        };
-        
+        self.remove_position(owner, symbol);
+        let mut position = PositionRisk::new(owner, symbol, entry_price, stop_loss, position_size, account_balance);
+        position.correlation_group = Some(self.cluster_of(&position.symbol));
         self.positions.push(position);
     }
-    
+
     /// Remove a closed position
-    pub fn remove_position(&mut self, symbol: &str) {
-        self.positions.retain(|p| p.symbol != symbol);
+    pub fn remove_position(&mut self, owner: &str, symbol: &str) {
+        let symbol = symbol.to_uppercase();
+        self.positions.retain(|p| p.owner != owner || p.symbol != symbol);
     }
-    
-    /// Calculate total portfolio heat
-    pub fn calculate_total_heat(&self) -> f64 {
-        self.positions.iter().map(|p| p.risk_percent).sum()
+
+    /// Replace every position held by `owner`
+    pub fn sync_positions(&mut self, owner: &str, positions: Vec<PositionRisk>) {
+        self.positions.retain(|p| p.owner != owner);
+        for mut position in positions {
+            position.correlation_group = Some(self.cluster_of(&position.symbol));
+            self.positions.push(position);
+        }
     }
-    
-    /// Check if new position would exceed heat limit
-    pub fn can_add_position(&self, new_risk_percent: f64) -> bool {
-        self.calculate_total_heat() + new_risk_percent <= self.max_portfolio_heat
+
+    pub fn positions(&self) -> &[PositionRisk] {
+        &self.positions
     }
-    
+
+    /// Replace the daily price history of a symbol and recompute the clusters
+    pub fn update_price_history(&mut self, symbol: &str, prices: Vec<PriceData>) {
+        let symbol = symbol.to_uppercase();
+        self.history_loaded_at.insert(symbol.clone(), Utc::now());
+        self.price_history.insert(symbol, prices);
+        self.recluster();
+    }
+
+    /// Load stored daily klines for held and candidate symbols whose history is missing
+    /// or older than six hours, then recompute the clusters
+    pub fn refresh_from_store(&mut self, candidates: &[String]) -> TradingResult<()> {
+        let stale_before = Utc::now() - Duration::hours(6);
+        let mut symbols: Vec<String> = self.positions.iter()
+            .map(|p| p.symbol.clone())
+            .chain(candidates.iter().map(|s| s.to_uppercase()))
+            .filter(|s| self.history_loaded_at.get(s).is_none_or(|loaded| *loaded < stale_before))
+            .collect();
+        symbols.sort();
+        symbols.dedup();
+        if symbols.is_empty() {
+            return Ok(());
+        }
+
+        let store = KlineStore::open_default()?;
+        for symbol in symbols {
+            let klines = store.load(&symbol, "1d")?;
+            let prices = klines_to_price_data(&klines[klines.len().saturating_sub(self.limits.correlation_window + 1)..]);
+            self.history_loaded_at.insert(symbol.clone(), Utc::now());
+            self.price_history.insert(symbol, prices);
+        }
+        self.recluster();
+        Ok(())
+    }
+
+    /// Latest stored daily close of a symbol
+    pub fn last_close(&self, symbol: &str) -> Option<Decimal> {
+        self.price_history.get(&symbol.to_uppercase())?.last().map(|p| p.close)
+    }
+
+    /// Rolling return correlation of two symbols, if both have enough overlapping history
+    pub fn correlation(&self, first: &str, second: &str) -> Option<f64> {
+        let (first, second) = (first.to_uppercase(), second.to_uppercase());
+        if first == second {
+            return Some(1.0);
+        }
+        let key = if first < second { (first, second) } else { (second, first) };
+        self.correlations.get(&key).copied()
+    }
+
+    /// Current correlation clusters, largest first
+    pub fn clusters(&self) -> &[Vec<String>] {
+        &self.clusters
+    }
+
+    /// Cluster id of a symbol: its members joined by `+`, or the symbol itself when unclustered
+    pub fn cluster_of(&self, symbol: &str) -> String {
+        let symbol = symbol.to_uppercase();
+        self.clusters.iter()
+            .find(|members| members.contains(&symbol))
+            .map(|members| members.join("+"))
+            .unwrap_or(symbol)
+    }
+
+    /// Calculate total portfolio heat in percent of `equity`
+    pub fn calculate_total_heat(&self, equity: f64) -> f64 {
+        self.heat_of(self.positions.iter(), equity)
+    }
+
+    /// Check a new entry of `notional` risking `risk_amount` against the limits, returning
+    /// how much of it fits. Call `refresh_from_store` first so the candidate is clustered.
+    pub fn check_entry(&self, symbol: &str, notional: f64, risk_amount: f64, equity: f64) -> HeatCheck {
+        let symbol = symbol.to_uppercase();
+        let cluster = self.cluster_of(&symbol);
+        let cluster_positions: Vec<&PositionRisk> = self.positions.iter()
+            .filter(|p| self.cluster_of(&p.symbol) == cluster)
+            .collect();
+
+        let equity = equity.max(f64::EPSILON);
+        let total_heat = self.calculate_total_heat(equity);
+        let cluster_heat = self.heat_of(cluster_positions.iter().copied(), equity);
+        let cluster_exposure = cluster_positions.iter()
+            .map(|p| p.position_size.abs().to_f64().unwrap_or(0.0))
+            .sum::<f64>() / equity * 100.0;
+        let new_heat = risk_amount.max(0.0) / equity * 100.0;
+        let new_exposure = notional.max(0.0) / equity * 100.0;
+
+        let mut scale: f64 = 1.0;
+        let mut reasons = Vec::new();
+        let mut limit = |current: f64, added: f64, max: f64, label: String| {
+            if added > 0.0 && current + added > max {
+                scale = scale.min(((max - current) / added).max(0.0));
+                reasons.push(format!("{} at {:.2}% + {:.2}% exceeds {:.2}%", label, current, added, max));
+            }
+        };
+        limit(total_heat, new_heat, self.limits.max_portfolio_heat, "portfolio heat".to_string());
+        limit(cluster_heat, new_heat, self.limits.max_cluster_heat, format!("{} heat", cluster));
+        // A lone position is not correlated exposure; the limit applies once the cluster is held
+        if !cluster_positions.is_empty() {
+            limit(cluster_exposure, new_exposure, self.limits.max_correlation_exposure, format!("{} correlated exposure", cluster));
+        }
+
+        let blocked = scale < self.limits.min_entry_fraction;
+        HeatCheck {
+            cluster_members: self.clusters.iter()
+                .find(|members| members.contains(&symbol))
+                .cloned()
+                .unwrap_or_else(|| vec![symbol.clone()]),
+            symbol,
+            cluster,
+            requested_notional: notional,
+            allowed_notional: if blocked { 0.0 } else { notional * scale },
+            scale,
+            blocked,
+            total_heat,
+            cluster_heat,
+            cluster_exposure,
+            reasons,
+        }
+    }
+
     /// Get correlated positions for risk adjustment
     pub fn get_correlated_positions(&self, symbol: &str) -> Vec<&PositionRisk> {
-        let group = self.determine_correlation_group(symbol);
-        
+        let group = self.cluster_of(symbol);
+
         self.positions.iter()
-            .filter(|p| p.correlation_group == group)
+            .filter(|p| self.cluster_of(&p.symbol) == group)
             .collect()
     }
-    
-    /// Simple correlation grouping (can be enhanced with actual correlation calculation)
-    fn determine_correlation_group(&self, symbol: &str) -> Option<String> {
-        // Group by asset class or sector
-        if symbol.contains("BTC") || symbol.contains("ETH") {
-            Some("CRYPTO_MAJOR".to_string())
-        } else if symbol.contains("USD") {
-            Some("FOREX_USD".to_string())
-        } else {
-            Some("OTHER".to_string())
-        }
-    }
-    
-    /// Get risk summary for monitoring
-    pub fn get_risk_summary(&self) -> RiskSummary {
-        let total_heat = self.calculate_total_heat();
+
+    /// Get risk summary for monitoring, in percent of `equity`
+    pub fn get_risk_summary(&self, equity: f64) -> RiskSummary {
+        let equity = equity.max(f64::EPSILON);
+        let total_heat = self.calculate_total_heat(equity);
         let position_count = self.positions.len();
         let largest_risk = self.positions.iter()
-            .map(|p| p.risk_percent)
+            .map(|p| self.heat_of(std::iter::once(p), equity))
             .fold(0.0, f64::max);
-        
-        let grouped_risks = self.calculate_grouped_risks();
-        
+
+        let clusters = self.calculate_cluster_exposures(equity);
+        let grouped_risks = clusters.iter().map(|c| (c.cluster.clone(), c.heat)).collect();
+
         RiskSummary {
             total_heat,
             position_count,
             largest_risk,
             grouped_risks,
-            heat_available: self.max_portfolio_heat - total_heat,
+            heat_available: self.limits.max_portfolio_heat - total_heat,
+            clusters,
+            limits: self.limits.clone(),
         }
     }
-    
-    /// Calculate risk by correlation groups
-    fn calculate_grouped_risks(&self) -> Vec<(String, f64)> {
-        let mut groups: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
-        
+
+    /// Heat and exposure per held correlation cluster
+    fn calculate_cluster_exposures(&self, equity: f64) -> Vec<ClusterExposure> {
+        let mut groups: HashMap<String, Vec<&PositionRisk>> = HashMap::new();
         for position in &self.positions {
-            if let Some(group) = &position.correlation_group {
-                *groups.entry(group.clone()).or_insert(0.0) += position.risk_percent;
+            groups.entry(self.cluster_of(&position.symbol)).or_default().push(position);
+        }
+
+        let mut exposures: Vec<ClusterExposure> = groups.into_iter()
+            .map(|(cluster, positions)| {
+                let mut members: Vec<String> = positions.iter().map(|p| p.symbol.clone()).collect();
+                members.sort();
+                members.dedup();
+                ClusterExposure {
+                    heat: self.heat_of(positions.iter().copied(), equity),
+                    exposure_percent: positions.iter()
+                        .map(|p| p.position_size.abs().to_f64().unwrap_or(0.0))
+                        .sum::<f64>() / equity * 100.0,
+                    cluster,
+                    members,
+                }
+            })
+            .collect();
+        exposures.sort_by(|a, b| b.exposure_percent.partial_cmp(&a.exposure_percent).unwrap_or(std::cmp::Ordering::Equal));
+        exposures
+    }
+
+    fn heat_of<'a>(&self, positions: impl Iterator<Item = &'a PositionRisk>, equity: f64) -> f64 {
+        positions.map(|p| p.risk_amount.to_f64().unwrap_or(0.0)).sum::<f64>() / equity * 100.0
+    }
+
+    /// Recompute correlations and clusters over every symbol with history
+    fn recluster(&mut self) {
+        let window = self.limits.correlation_window;
+        self.correlations = pairwise_correlations(&self.price_history, window, (window / 3).max(20));
+
+        let mut symbols: Vec<String> = self.price_history.keys().cloned().collect();
+        symbols.sort();
+        self.clusters = cluster_by_correlation(&symbols, &self.correlations, self.limits.correlation_threshold);
+
+        let groups: Vec<String> = self.positions.iter().map(|p| self.cluster_of(&p.symbol)).collect();
+        for (position, group) in self.positions.iter_mut().zip(groups) {
+            position.correlation_group = Some(group);
+        }
+    }
+}
+
+impl Default for PortfolioHeatTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Average-linkage agglomerative clustering: the two clusters with the highest average
+/// pairwise correlation are merged until no pair averages `threshold` or more.
+/// Missing pairs count as uncorrelated. Clusters are returned largest first, members sorted.
+pub fn cluster_by_correlation(
+    symbols: &[String],
+    correlations: &HashMap<(String, String), f64>,
+    threshold: f64,
+) -> Vec<Vec<String>> {
+    let correlation = |a: &String, b: &String| {
+        let key = if a < b { (a.clone(), b.clone()) } else { (b.clone(), a.clone()) };
+        correlations.get(&key).copied().unwrap_or(0.0)
+    };
+    let linkage = |a: &[String], b: &[String]| {
+        let total: f64 = a.iter().flat_map(|x| b.iter().map(move |y| (x, y))).map(|(x, y)| correlation(x, y)).sum();
+        total / (a.len() * b.len()) as f64
+    };
+
+    let mut clusters: Vec<Vec<String>> = symbols.iter().map(|s| vec![s.clone()]).collect();
+    loop {
+        let mut best: Option<(usize, usize, f64)> = None;
+        for i in 0..clusters.len() {
+            for j in i + 1..clusters.len() {
+                let average = linkage(&clusters[i], &clusters[j]);
+                if average >= threshold && best.is_none_or(|(_, _, b)| average > b) {
+                    best = Some((i, j, average));
+                }
             }
         }
-        
-        groups.into_iter().collect()
+        match best {
+            Some((i, j, _)) => {
+                let merged = clusters.remove(j);
+                clusters[i].extend(merged);
+            }
+            None => break,
+        }
     }
+
+    for members in clusters.iter_mut() {
+        members.sort();
+    }
+    clusters.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+    clusters
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClusterExposure {
+    pub cluster: String,
+    pub members: Vec<String>,
+    /// Open risk in percent of equity
+    pub heat: f64,
+    /// Gross notional in percent of equity
+    pub exposure_percent: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -393,6 +785,8 @@ pub struct RiskSummary {
     pub largest_risk: f64,
     pub grouped_risks: Vec<(String, f64)>,
     pub heat_available: f64,
+    pub clusters: Vec<ClusterExposure>,
+    pub limits: HeatLimits,
 }
 
 /// Maximum Adverse Excursion (MAE) tracker
@@ -419,6 +813,21 @@ impl MAETracker {
         }
     }
     
+    /// Record a completed trade's excursions (percent of entry price)
+    pub fn record_trade(&mut self, trade: TradeMAE) {
+        self.trades.push_back(trade);
+        while self.trades.len() > self.max_history {
+            self.trades.pop_front();
+        }
+    }
+
+    /// Number of completed trades that were losers
+    pub fn losing_trade_count(&self) -> usize {
+        self.trades.iter()
+            .filter(|t| t.final_result.map(|r| r < 0.0).unwrap_or(false))
+            .count()
+    }
+
     /// Get average MAE for losing trades
     pub fn get_average_mae(&self) -> f64 {
         let losing_trades: Vec<f64> = self.trades.iter()
@@ -440,4 +849,69 @@ impl MAETracker {
         // Add 20% buffer to average MAE
         avg_mae * 1.2
     }
-}
\ No newline at end of file
+}
+
+impl Default for MAETracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn correlations(pairs: &[(&str, &str, f64)]) -> HashMap<(String, String), f64> {
+        pairs.iter().map(|(a, b, c)| ((a.to_string(), b.to_string()), *c)).collect()
+    }
+
+    #[test]
+    fn test_cluster_by_correlation() {
+        let symbols: Vec<String> = ["ADAUSDT", "BTCUSDT", "ETHUSDT", "XRPUSDT"].iter().map(|s| s.to_string()).collect();
+        let pairs = correlations(&[
+            ("BTCUSDT", "ETHUSDT", 0.9),
+            ("ADAUSDT", "ETHUSDT", 0.8),
+            ("ADAUSDT", "BTCUSDT", 0.7),
+            ("BTCUSDT", "XRPUSDT", 0.75),
+        ]);
+
+        // XRP only correlates with BTC, so its average linkage to the majors cluster is too low
+        let clusters = cluster_by_correlation(&symbols, &pairs, 0.7);
+        assert_eq!(clusters, vec![
+            vec!["ADAUSDT".to_string(), "BTCUSDT".to_string(), "ETHUSDT".to_string()],
+            vec!["XRPUSDT".to_string()],
+        ]);
+        assert_eq!(cluster_by_correlation(&symbols, &pairs, 0.95).len(), 4);
+    }
+
+    #[test]
+    fn test_check_entry_limits_correlated_exposure() {
+        let mut tracker = PortfolioHeatTracker::new();
+        tracker.clusters = vec![vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]];
+        let equity = Decimal::from(10_000);
+        // 5,000 notional with a 2% stop risks 100 (1% heat)
+        tracker.add_position("swing_bot", "BTCUSDT", Decimal::from(100), Decimal::from(98), Decimal::from(5_000), equity);
+        assert_eq!(tracker.positions()[0].correlation_group.as_deref(), Some("BTCUSDT+ETHUSDT"));
+
+        // Cluster exposure 50% + 40% exceeds 60%, so a quarter of the size fits
+        let check = tracker.check_entry("ETHUSDT", 4_000.0, 80.0, 10_000.0);
+        assert!(!check.blocked);
+        assert!((check.scale - 0.25).abs() < 1e-9);
+        assert!((check.allowed_notional - 1_000.0).abs() < 1e-6);
+
+        // Uncorrelated symbols only face the heat limits
+        let check = tracker.check_entry("XRPUSDT", 4_000.0, 80.0, 10_000.0);
+        assert!(check.reasons.is_empty());
+        assert_eq!(check.cluster, "XRPUSDT");
+
+        // 1.25% held leaves 1.75% of cluster heat, under a quarter of the 8% requested
+        tracker.add_position("advanced_trading", "ETHUSDT", Decimal::from(100), Decimal::from(95), Decimal::from(500), equity);
+        assert!((tracker.check_entry("BTCUSDT", 500.0, 250.0, 10_000.0).scale - 0.7).abs() < 1e-9);
+        let check = tracker.check_entry("BTCUSDT", 500.0, 800.0, 10_000.0);
+        assert!(check.blocked);
+        assert_eq!(check.allowed_notional, 0.0);
+
+        tracker.remove_position("swing_bot", "BTCUSDT");
+        assert_eq!(tracker.positions().len(), 1);
+    }
+}
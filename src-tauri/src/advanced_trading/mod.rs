@@ -4,13 +4,23 @@
 pub mod order_management;
 pub mod portfolio_analytics;
 pub mod risk_engine;
+pub mod stress_replay;
 pub mod technical_analysis;
+pub mod value_at_risk;
 
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use crate::errors::TradingResult;
+use crate::advanced_risk_manager::{PortfolioHeatTracker, PositionRisk, SharedHeatTracker};
+use crate::drawdown_governor::{DrawdownGovernor, DrawdownGovernorConfig, SharedDrawdownGovernor};
+use crate::kill_switch::{CancelledOrder, ClosedPosition, KillSwitch, PostMortemReport, SharedKillSwitch, UnflattenedPosition};
+use crate::log_warning;
+use crate::logging::LogCategory;
+use crate::risk_limits::{RiskLimitStore, SharedRiskLimits};
 
 /// Advanced Trading Engine - Central coordinator for professional trading features
 pub struct AdvancedTradingEngine {
@@ -18,8 +28,19 @@ pub struct AdvancedTradingEngine {
     pub portfolio_analytics: Arc<RwLock<portfolio_analytics::RealTimePortfolioAnalyzer>>,
     pub risk_engine: Arc<RwLock<risk_engine::AdvancedRiskEngine>>,
     pub technical_analyzer: Arc<RwLock<technical_analysis::TechnicalAnalysisEngine>>,
+    /// Heat and correlated exposure limits, shared with the other bots via `with_portfolio_heat`
+    pub portfolio_heat: SharedHeatTracker,
+    /// Account drawdown governor, shared with the other bots via `with_drawdown_governor`
+    pub drawdown_governor: SharedDrawdownGovernor,
+    /// Global kill switch blocking new entries, shared via `with_kill_switch`
+    pub kill_switch: SharedKillSwitch,
+    /// Configured risk limit profiles, shared via `with_risk_limits`
+    pub risk_limits: SharedRiskLimits,
 }
 
+/// Owner name of the engine in the shared portfolio heat tracker, drawdown governor and risk limits
+const ENGINE_OWNER: &str = "advanced_trading";
+
 /// Advanced order types for professional trading
 #[derive(Debug, Clone, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub enum AdvancedOrderType {
@@ -156,11 +177,49 @@ impl AdvancedTradingEngine {
             portfolio_analytics,
             risk_engine,
             technical_analyzer,
+            portfolio_heat: Arc::new(std::sync::Mutex::new(PortfolioHeatTracker::new())),
+            drawdown_governor: Arc::new(std::sync::Mutex::new(DrawdownGovernor::new(DrawdownGovernorConfig::default()))),
+            kill_switch: Arc::new(std::sync::Mutex::new(KillSwitch::new())),
+            risk_limits: Arc::new(std::sync::Mutex::new(RiskLimitStore::new())),
         })
     }
 
+    /// Use a heat tracker shared with the other bots
+    pub fn with_portfolio_heat(mut self, tracker: SharedHeatTracker) -> Self {
+        self.portfolio_heat = tracker;
+        self
+    }
+
+    /// Use a drawdown governor shared with the other bots
+    pub fn with_drawdown_governor(mut self, governor: SharedDrawdownGovernor) -> Self {
+        self.drawdown_governor = governor;
+        self
+    }
+
+    /// Use the global kill switch
+    pub fn with_kill_switch(mut self, kill_switch: SharedKillSwitch) -> Self {
+        self.kill_switch = kill_switch;
+        self
+    }
+
+    /// Use the risk limit profiles shared with the other bots
+    pub fn with_risk_limits(mut self, risk_limits: SharedRiskLimits) -> Self {
+        self.risk_limits = risk_limits;
+        self
+    }
+
     /// Place an advanced order with professional features
-    pub async fn place_advanced_order(&self, order: AdvancedOrderRequest) -> TradingResult<String> {
+    pub async fn place_advanced_order(&self, mut order: AdvancedOrderRequest) -> TradingResult<String> {
+        if !order.reduce_only {
+            self.kill_switch.lock().unwrap_or_else(|e| e.into_inner()).ensure_disengaged()?;
+        }
+        self.refresh_risk_inputs(std::slice::from_ref(&order.symbol)).await?;
+        self.apply_risk_limits(Some(&order.symbol)).await;
+        if !order.reduce_only {
+            self.apply_drawdown_governor(&mut order).await?;
+            self.apply_heat_limits(&mut order).await?;
+        }
+
         // Pre-trade risk assessment
         let risk_assessment = self.risk_engine.read().await
             .assess_order_risk(&order).await?;
@@ -184,6 +243,17 @@ impl AdvancedTradingEngine {
         Ok(order_id)
     }
 
+    /// Pre-trade assessment of an order against the engine's book without placing it,
+    /// with the one-day VaR before the order and the configured VaR limit
+    pub async fn preview_order_risk(&self, order: &AdvancedOrderRequest) -> TradingResult<(Decimal, Decimal, RiskAssessment)> {
+        self.refresh_risk_inputs(std::slice::from_ref(&order.symbol)).await?;
+        self.apply_risk_limits(Some(&order.symbol)).await;
+        let risk_engine = self.risk_engine.read().await;
+        let var_before = risk_engine.current_var(1)?;
+        let assessment = risk_engine.assess_order_risk(order).await?;
+        Ok((var_before, risk_engine.risk_limits().max_portfolio_var, assessment))
+    }
+
     /// Get real-time portfolio metrics
     pub async fn get_portfolio_metrics(&self) -> TradingResult<PortfolioMetrics> {
         self.portfolio_analytics.read().await
@@ -192,10 +262,184 @@ impl AdvancedTradingEngine {
 
     /// Perform comprehensive risk assessment
     pub async fn assess_portfolio_risk(&self) -> TradingResult<RiskAssessment> {
+        self.refresh_risk_inputs(&[]).await?;
         self.risk_engine.read().await
             .assess_portfolio_risk().await
     }
 
+    /// VaR/ES report across all methods for the current positions
+    pub async fn get_var_report(&self, horizon_days: u32) -> TradingResult<value_at_risk::VarReport> {
+        self.refresh_risk_inputs(&[]).await?;
+        self.risk_engine.read().await
+            .var_report(horizon_days)
+    }
+
+    /// Kupiec exception backtest of the VaR model for the current positions
+    pub async fn backtest_var(&self, method: value_at_risk::VarMethod, confidence: f64, window: usize) -> TradingResult<value_at_risk::VarBacktest> {
+        self.refresh_risk_inputs(&[]).await?;
+        self.risk_engine.read().await
+            .backtest_var(method, confidence, window)
+    }
+
+    /// Stress events available for replay
+    pub async fn get_stress_events(&self) -> Vec<risk_engine::HistoricalStressEvent> {
+        self.risk_engine.read().await
+            .historical_events()
+            .to_vec()
+    }
+
+    /// Replay a historical window from stored klines against the current positions and stops
+    pub async fn replay_stress_window(&self, window: stress_replay::ReplayWindow, interval: &str) -> TradingResult<stress_replay::StressReplayResult> {
+        window.validate()?;
+        let positions = self.stress_positions().await?;
+        let store = crate::kline_store::KlineStore::open_default()?;
+
+        let mut paths = std::collections::HashMap::new();
+        for position in &positions {
+            let symbol = position.symbol.to_uppercase();
+            let klines = store.load_range(&symbol, interval, window.start, window.end)?;
+            paths.insert(symbol, crate::kline_store::klines_to_price_data(&klines));
+        }
+
+        stress_replay::StressReplayer::default().replay(&positions, &paths, &window, interval)
+    }
+
+    /// Apply a custom per-asset shock vector to the current positions
+    pub async fn run_shock_scenario(&self, scenario: stress_replay::ShockScenario) -> TradingResult<stress_replay::ShockResult> {
+        let positions = self.stress_positions().await?;
+        let store = crate::kline_store::KlineStore::open_default()?;
+
+        // Average daily quote volume over the last 30 stored days drives liquidation costs
+        let mut volumes = std::collections::HashMap::new();
+        for position in &positions {
+            let symbol = position.symbol.to_uppercase();
+            let klines = store.load(&symbol, "1d")?;
+            let recent = &klines[klines.len().saturating_sub(30)..];
+            if !recent.is_empty() {
+                let total: Decimal = recent.iter().map(|k| k.volume * k.close).sum();
+                volumes.insert(symbol, total.to_f64().unwrap_or(0.0) / recent.len() as f64);
+            }
+        }
+
+        stress_replay::StressReplayer::default().shock(&positions, &scenario, &volumes)
+    }
+
+    /// Current positions with the stops from open protective orders attached
+    async fn stress_positions(&self) -> TradingResult<Vec<stress_replay::StressPosition>> {
+        let orders = self.order_manager.read().await.get_active_orders().await?;
+        let stops = stress_replay::protective_stops(&orders);
+        let mut positions = self.portfolio_analytics.read().await.stress_positions();
+        for position in positions.iter_mut() {
+            position.stop = stops.get(&position.symbol.to_uppercase()).cloned();
+        }
+        Ok(positions)
+    }
+
+    /// Report portfolio value to the shared drawdown governor, then down-size or reject an
+    /// entry according to the current drawdown step
+    async fn apply_drawdown_governor(&self, order: &mut AdvancedOrderRequest) -> TradingResult<()> {
+        let equity = self.portfolio_analytics.read().await.get_current_metrics().await?.total_value;
+
+        let mut governor = self.drawdown_governor.lock().unwrap_or_else(|e| e.into_inner());
+        governor.update_equity(ENGINE_OWNER, equity.to_f64().unwrap_or(0.0), chrono::Utc::now());
+        let multiplier = governor.size_multiplier();
+        if multiplier <= 0.0 {
+            return Err(crate::errors::TradingError::trading_logic_error(
+                crate::errors::TradingLogicErrorType::RiskLimitExceeded,
+                format!("Drawdown governor: {}", governor.status().reason),
+                Some(order.symbol.clone()),
+            ));
+        }
+        if multiplier < 1.0 {
+            let scale = Decimal::from_f64_retain(multiplier).unwrap_or(Decimal::ZERO);
+            order.quantity = (order.quantity * scale).round_dp(8);
+        }
+        Ok(())
+    }
+
+    /// Sync this engine's positions into the shared heat tracker, then down-size or reject an
+    /// entry that would breach the portfolio heat or correlated exposure limits
+    async fn apply_heat_limits(&self, order: &mut AdvancedOrderRequest) -> TradingResult<()> {
+        let equity = self.portfolio_analytics.read().await.get_current_metrics().await?.total_value;
+        let positions = self.stress_positions().await?;
+
+        let mut tracker = self.portfolio_heat.lock().unwrap_or_else(|e| e.into_inner());
+        let default_stop = tracker.limits().default_stop_percent / 100.0;
+        let held = positions.iter().map(|position| {
+            let price = position.price;
+            let stop = match position.stop {
+                Some(stress_replay::StopRule::Fixed { price: stop }) => stop,
+                Some(stress_replay::StopRule::Trailing { distance }) => price - distance * position.quantity.signum(),
+                None => price * (1.0 - default_stop * position.quantity.signum()),
+            };
+            PositionRisk::new(
+                ENGINE_OWNER,
+                &position.symbol,
+                Decimal::from_f64_retain(price).unwrap_or(Decimal::ZERO),
+                Decimal::from_f64_retain(stop).unwrap_or(Decimal::ZERO),
+                Decimal::from_f64_retain((price * position.quantity).abs()).unwrap_or(Decimal::ZERO),
+                equity,
+            )
+        }).collect();
+        tracker.sync_positions(ENGINE_OWNER, held);
+        tracker.refresh_from_store(std::slice::from_ref(&order.symbol))?;
+
+        let price = match order.price.or_else(|| tracker.last_close(&order.symbol)) {
+            Some(price) if price > Decimal::ZERO => price,
+            _ => return Ok(()),
+        };
+        let stop_distance = match &order.order_type {
+            AdvancedOrderType::StopLoss { stop_price, .. } => (price - stop_price).abs() / price,
+            AdvancedOrderType::Bracket { stop_loss, .. } => (price - stop_loss).abs() / price,
+            _ => Decimal::from_f64_retain(default_stop).unwrap_or(Decimal::ZERO),
+        };
+        let notional = (order.quantity * price).to_f64().unwrap_or(0.0);
+        let check = tracker.check_entry(
+            &order.symbol,
+            notional,
+            notional * stop_distance.to_f64().unwrap_or(default_stop),
+            equity.to_f64().unwrap_or(0.0),
+        );
+
+        if check.blocked {
+            return Err(crate::errors::TradingError::trading_logic_error(
+                crate::errors::TradingLogicErrorType::RiskLimitExceeded,
+                check.explanation(),
+                Some(order.symbol.clone()),
+            ));
+        }
+        if check.scale < 1.0 {
+            let scale = Decimal::from_f64_retain(check.scale).unwrap_or(Decimal::ZERO);
+            order.quantity = (order.quantity * scale).round_dp(8);
+        }
+        Ok(())
+    }
+
+    /// Sync live positions into the risk engine and load their stored daily klines
+    async fn refresh_risk_inputs(&self, extra_symbols: &[String]) -> TradingResult<()> {
+        let positions = self.portfolio_analytics.read().await.var_positions();
+        let store = crate::kline_store::KlineStore::open_default()?;
+
+        let mut risk_engine = self.risk_engine.write().await;
+        for symbol in positions.iter().map(|p| &p.symbol).chain(extra_symbols) {
+            let klines = store.load(&symbol.to_uppercase(), "1d")?;
+            risk_engine.update_price_history(symbol, crate::kline_store::klines_to_price_data(&klines));
+        }
+        risk_engine.refresh_correlations();
+        risk_engine.set_positions(positions);
+        drop(risk_engine);
+        self.apply_risk_limits(None).await;
+        Ok(())
+    }
+
+    /// Load the engine's configured limits (for `symbol` if given) into the risk engine,
+    /// picking up any edit to the risk limit file
+    async fn apply_risk_limits(&self, symbol: Option<&str>) {
+        let limits = self.risk_limits.lock().unwrap_or_else(|e| e.into_inner())
+            .current(Some(ENGINE_OWNER), symbol);
+        self.risk_engine.write().await.set_risk_limits(limits.global, limits.concentration);
+    }
+
     /// Get technical analysis for a symbol
     pub async fn get_technical_analysis(&self, symbol: &str, timeframe: &str) -> TradingResult<technical_analysis::TechnicalAnalysisResult> {
         self.technical_analyzer.read().await
@@ -211,10 +455,74 @@ impl AdvancedTradingEngine {
         // Close all positions if risk is critical
         let risk_assessment = self.assess_portfolio_risk().await?;
         if risk_assessment.risk_warnings.iter().any(|w| matches!(w.severity, RiskSeverity::Critical)) {
-            self.order_manager.write().await
-                .close_all_positions().await?;
+            // No market prices here, so every position is reported as left open
+            let mut report = PostMortemReport::new(ENGINE_OWNER, "Emergency stop on critical risk", chrono::Utc::now());
+            self.flatten_all(&HashMap::new(), &mut report).await?;
+            for warning in &report.warnings {
+                log_warning!(LogCategory::RiskManagement, "Emergency stop: {}", warning);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cancel every resting order and flatten every position with a price in `prices`, and
+    /// record what was cancelled, closed and left open in `report`
+    pub async fn flatten_all(&self, prices: &HashMap<String, Decimal>, report: &mut PostMortemReport) -> TradingResult<()> {
+        let positions = self.portfolio_analytics.read().await.open_positions();
+        for position in positions.iter().filter(|p| !p.size.is_zero() && !prices.contains_key(&p.symbol)) {
+            report.add_unflattened(UnflattenedPosition {
+                source: ENGINE_OWNER.to_string(),
+                symbol: position.symbol.clone(),
+                side: position_side(position),
+                quantity: position.size.abs(),
+                entry_price: position.entry_price,
+                reason: format!("no market price, last known {}", position.current_price),
+            });
+        }
+
+        let fills = {
+            let mut order_manager = self.order_manager.write().await;
+            let cancelled = order_manager.cancel_all_orders().await?;
+            report.cancelled_orders.extend(cancelled.into_iter().map(|order| CancelledOrder {
+                source: ENGINE_OWNER.to_string(),
+                order_id: order.id,
+                symbol: order.symbol,
+            }));
+            order_manager.close_all_positions(&positions, prices, &report.reason).await?
+        };
+
+        let mut analytics = self.portfolio_analytics.write().await;
+        let mut closed_symbols = Vec::new();
+        for fill in fills {
+            let position = match positions.iter().find(|p| p.symbol == fill.order.symbol) {
+                Some(position) => position,
+                None => continue,
+            };
+            let price = match fill.order.average_fill_price {
+                Some(price) => price,
+                None => continue,
+            };
+            let closed = ClosedPosition::new(ENGINE_OWNER, &position.symbol, position_side(position), fill.order.filled_quantity, position.entry_price, price);
+            analytics.add_trade(portfolio_analytics::Trade {
+                id: fill.order.id.clone(),
+                symbol: position.symbol.clone(),
+                side: fill.order.side.clone(),
+                quantity: closed.quantity,
+                price,
+                fee: fill.total_fee,
+                timestamp: fill.completed_at,
+                pnl: Some(closed.realized_pnl),
+                trade_type: portfolio_analytics::TradeType::Exit,
+            }).await?;
+            closed_symbols.push(position.symbol.clone());
+            report.add_closed(closed);
         }
 
+        let mut heat = self.portfolio_heat.lock().unwrap_or_else(|e| e.into_inner());
+        for symbol in closed_symbols {
+            heat.remove_position(ENGINE_OWNER, &symbol);
+        }
         Ok(())
     }
 
@@ -225,6 +533,13 @@ impl AdvancedTradingEngine {
     }
 }
 
+fn position_side(position: &portfolio_analytics::Position) -> crate::models::TradeSide {
+    match position.side {
+        portfolio_analytics::PositionSide::Short => crate::models::TradeSide::Short,
+        _ => crate::models::TradeSide::Long,
+    }
+}
+
 /// Advanced trading configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AdvancedTradingConfig {
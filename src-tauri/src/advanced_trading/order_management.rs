@@ -11,6 +11,7 @@ use chrono::{DateTime, Utc};
 
 use crate::errors::{TradingResult, TradingError, TradingLogicErrorType};
 use super::{AdvancedOrderRequest, AdvancedOrderType, OrderSide, TimeInForce};
+use super::portfolio_analytics::{Position, PositionSide};
 
 /// Professional order manager with advanced features
 pub struct ProfessionalOrderManager {
@@ -247,27 +248,94 @@ impl ProfessionalOrderManager {
         }
     }
 
-    /// Cancel all active orders
-    pub async fn cancel_all_orders(&mut self) -> TradingResult<()> {
-        let order_ids: Vec<String> = {
+    /// Cancel all active orders, returning the orders that were cancelled
+    pub async fn cancel_all_orders(&mut self) -> TradingResult<Vec<ActiveOrder>> {
+        let orders: Vec<ActiveOrder> = {
             let active_orders = self.active_orders.read().await;
-            active_orders.keys().cloned().collect()
+            active_orders.values().cloned().collect()
         };
 
-        for order_id in order_ids {
-            if let Err(e) = self.cancel_order(&order_id).await {
-                eprintln!("Failed to cancel order {}: {}", order_id, e);
+        let mut cancelled = Vec::new();
+        for order in orders {
+            // Children of a cancelled parent are already gone
+            if !self.active_orders.read().await.contains_key(&order.id) {
+                cancelled.push(order);
+                continue;
+            }
+            match self.cancel_order(&order.id).await {
+                Ok(()) => cancelled.push(order),
+                Err(e) => eprintln!("Failed to cancel order {}: {}", order.id, e),
             }
         }
 
-        Ok(())
+        Ok(cancelled)
     }
 
-    /// Close all positions (emergency function)
-    pub async fn close_all_positions(&mut self) -> TradingResult<()> {
-        // This would implement position closure logic
-        // For now, we'll cancel all orders as a safety measure
-        self.cancel_all_orders().await
+    /// Close all positions (emergency function): cancels resting orders, then flattens each
+    /// position with a reduce-only market order filled at `prices`. Positions without a price
+    /// are left open. Returns the filled closing orders
+    pub async fn close_all_positions(
+        &mut self,
+        positions: &[Position],
+        prices: &HashMap<String, Decimal>,
+        reason: &str,
+    ) -> TradingResult<Vec<CompletedOrder>> {
+        self.cancel_all_orders().await?;
+
+        let mut closed = Vec::new();
+        for position in positions.iter().filter(|p| !p.size.is_zero()) {
+            let price = match prices.get(&position.symbol) {
+                Some(price) => *price,
+                None => continue,
+            };
+            let (side, direction) = match position.side {
+                PositionSide::Short => (OrderSide::Buy, -Decimal::ONE),
+                _ => (OrderSide::Sell, Decimal::ONE),
+            };
+            let quantity = position.size.abs();
+            let request = AdvancedOrderRequest {
+                symbol: position.symbol.clone(),
+                side,
+                order_type: AdvancedOrderType::Market,
+                quantity,
+                price: Some(price),
+                time_in_force: TimeInForce::IOC,
+                reduce_only: true,
+                post_only: false,
+                client_order_id: Some(format!("flatten-{}", position.symbol)),
+                risk_limits: None,
+            };
+
+            let now = Utc::now();
+            let mut order = self.create_standard_order(Uuid::new_v4().to_string(), request).await?;
+            order.filled_quantity = quantity;
+            order.remaining_quantity = Decimal::ZERO;
+            order.average_fill_price = Some(price);
+            order.status = OrderStatus::Filled;
+            order.fills.push(OrderFill {
+                fill_id: Uuid::new_v4().to_string(),
+                price,
+                quantity,
+                fee: Decimal::ZERO,
+                fee_asset: "USDT".to_string(),
+                timestamp: now,
+                trade_id: Uuid::new_v4().to_string(),
+                commission_rate: Decimal::ZERO,
+            });
+
+            let completed = CompletedOrder {
+                order,
+                completion_reason: reason.to_string(),
+                final_status: OrderStatus::Filled,
+                completed_at: now,
+                total_fee: Decimal::ZERO,
+                net_pnl: Some((price - position.entry_price) * quantity * direction),
+            };
+            self.order_history.write().await.push(completed.clone());
+            closed.push(completed);
+        }
+
+        Ok(closed)
     }
 
     /// Get active orders
@@ -331,6 +331,50 @@ impl RealTimePortfolioAnalyzer {
         Ok(())
     }
 
+    /// Positions with a non-zero size
+    pub fn open_positions(&self) -> Vec<Position> {
+        self.positions.values()
+            .filter(|pos| !pos.size.is_zero())
+            .cloned()
+            .collect()
+    }
+
+    /// Signed market exposure of each open position for VaR calculations
+    pub fn var_positions(&self) -> Vec<super::value_at_risk::VarPosition> {
+        self.positions.values()
+            .filter(|pos| !pos.size.is_zero())
+            .map(|pos| {
+                let value = (pos.current_price * pos.size.abs()).to_f64().unwrap_or(0.0);
+                super::value_at_risk::VarPosition {
+                    symbol: pos.symbol.clone(),
+                    exposure: match pos.side {
+                        PositionSide::Short => -value,
+                        _ => value,
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Open positions at their current price for stress testing (stops are attached by the caller)
+    pub fn stress_positions(&self) -> Vec<super::stress_replay::StressPosition> {
+        self.positions.values()
+            .filter(|pos| !pos.size.is_zero())
+            .map(|pos| {
+                let quantity = pos.size.abs().to_f64().unwrap_or(0.0);
+                super::stress_replay::StressPosition {
+                    symbol: pos.symbol.clone(),
+                    quantity: match pos.side {
+                        PositionSide::Short => -quantity,
+                        _ => quantity,
+                    },
+                    price: pos.current_price.to_f64().unwrap_or(0.0),
+                    stop: None,
+                }
+            })
+            .collect()
+    }
+
     // Private helper methods
 
     async fn update_position_values(&mut self) -> TradingResult<()> {
@@ -427,6 +471,9 @@ impl RealTimePortfolioAnalyzer {
                     position.side = PositionSide::Long;
                 } else {
                     // Reducing short position
+                    let closed = trade.quantity.min(position.size.abs());
+                    position.realized_pnl += (position.entry_price - trade.price) * closed - trade.fee;
+                    position.cost_basis -= position.entry_price * closed;
                     position.size += trade.quantity;
                     if position.size >= Decimal::ZERO {
                         position.side = if position.size > Decimal::ZERO { PositionSide::Long } else { PositionSide::Neutral };
@@ -448,6 +495,9 @@ impl RealTimePortfolioAnalyzer {
                     position.side = PositionSide::Short;
                 } else {
                     // Reducing long position
+                    let closed = trade.quantity.min(position.size);
+                    position.realized_pnl += (trade.price - position.entry_price) * closed - trade.fee;
+                    position.cost_basis -= position.entry_price * closed;
                     position.size -= trade.quantity;
                     if position.size <= Decimal::ZERO {
                         position.side = if position.size < Decimal::ZERO { PositionSide::Short } else { PositionSide::Neutral };
@@ -456,6 +506,9 @@ impl RealTimePortfolioAnalyzer {
             },
         }
 
+        if position.size.is_zero() {
+            position.cost_basis = Decimal::ZERO;
+        }
         position.fees_paid += trade.fee;
         position.last_updated = trade.timestamp;
         position.current_price = trade.price;
@@ -482,7 +535,7 @@ impl RealTimePortfolioAnalyzer {
         let used_capital: Decimal = self.positions.values()
             .map(|pos| pos.cost_basis)
             .sum();
-        self.portfolio_config.initial_capital - used_capital
+        self.portfolio_config.initial_capital - used_capital + self.calculate_total_realized_pnl()
     }
 
     fn calculate_total_unrealized_pnl(&self) -> Decimal {
@@ -8,7 +8,16 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc, Duration};
 
 use crate::errors::{TradingResult, TradingError, TradingLogicErrorType};
-use super::{AdvancedOrderRequest, RiskAssessment, StressTestResult, RiskWarning, RiskSeverity};
+use crate::models::PriceData;
+use super::{AdvancedOrderRequest, OrderSide, RiskAssessment, StressTestResult, RiskWarning, RiskSeverity};
+use super::value_at_risk::{pairwise_correlations, ReturnMatrix, MIN_VAR_OBSERVATIONS, VarBacktest, VarCalculator, VarMethod, VarPosition, VarReport};
+use super::stress_replay;
+use crate::advanced_risk_manager::cluster_by_correlation;
+
+/// Daily returns in the rolling correlation window
+const CORRELATION_WINDOW: usize = 90;
+/// Correlation at which positions count as correlated exposure
+const CORRELATION_THRESHOLD: f64 = 0.7;
 
 /// Advanced risk management engine
 pub struct AdvancedRiskEngine {
@@ -18,6 +27,8 @@ pub struct AdvancedRiskEngine {
     stress_tester: StressTester,
     risk_limits: GlobalRiskLimits,
     position_tracker: PositionRiskTracker,
+    price_history: HashMap<String, Vec<PriceData>>,
+    var_positions: Vec<VarPosition>,
 }
 
 /// Risk model for individual assets
@@ -39,13 +50,6 @@ pub struct CorrelationMatrix {
     last_updated: DateTime<Utc>,
 }
 
-/// Value at Risk calculator
-pub struct VarCalculator {
-    confidence_levels: Vec<f64>,
-    historical_window_days: u32,
-    monte_carlo_simulations: u32,
-}
-
 /// Stress testing engine
 pub struct StressTester {
     scenarios: Vec<StressTestScenario>,
@@ -156,22 +160,10 @@ pub enum RiskAlertType {
 
 impl AdvancedRiskEngine {
     pub async fn new() -> TradingResult<Self> {
-        let risk_limits = GlobalRiskLimits {
-            max_portfolio_var: Decimal::from(5000), // $5,000 max 1-day VaR
-            max_single_position_percent: 25.0, // 25% max single position
-            max_sector_concentration: 40.0, // 40% max sector exposure
-            max_correlation_exposure: 60.0, // 60% max correlated positions
-            max_daily_loss_percent: 5.0, // 5% max daily loss
-            max_drawdown_percent: 15.0, // 15% max drawdown
-            leverage_limit: 3.0, // 3x max leverage
-            liquidity_requirement: 0.1, // 10% liquidity requirement
-        };
+        // Overridden per account, bot and symbol by the risk limit profiles
+        let risk_limits = GlobalRiskLimits::default();
 
-        let var_calculator = VarCalculator {
-            confidence_levels: vec![0.95, 0.99, 0.999],
-            historical_window_days: 252, // 1 year of trading days
-            monte_carlo_simulations: 10000,
-        };
+        let var_calculator = VarCalculator::default();
 
         let stress_scenarios = vec![
             StressTestScenario {
@@ -202,20 +194,13 @@ impl AdvancedRiskEngine {
 
         let stress_tester = StressTester {
             scenarios: stress_scenarios,
-            historical_events: Vec::new(),
-        };
-
-        let concentration_limits = ConcentrationLimits {
-            max_single_position: 25.0,
-            max_sector_exposure: 40.0,
-            max_correlated_positions: 60.0,
-            min_diversification_ratio: 0.3,
+            historical_events: stress_replay::historical_events(),
         };
 
         let position_tracker = PositionRiskTracker {
             position_risks: HashMap::new(),
             sector_exposures: HashMap::new(),
-            concentration_limits,
+            concentration_limits: ConcentrationLimits::default(),
         };
 
         Ok(Self {
@@ -228,9 +213,76 @@ impl AdvancedRiskEngine {
             stress_tester,
             risk_limits,
             position_tracker,
+            price_history: HashMap::new(),
+            var_positions: Vec::new(),
         })
     }
 
+    pub fn risk_limits(&self) -> &GlobalRiskLimits {
+        &self.risk_limits
+    }
+
+    /// Replace the global and concentration limits, e.g. after a risk limit reload
+    pub fn set_risk_limits(&mut self, risk_limits: GlobalRiskLimits, concentration_limits: ConcentrationLimits) {
+        self.risk_limits = risk_limits;
+        self.position_tracker.concentration_limits = concentration_limits;
+    }
+
+    /// Replace the stored daily price history used for return-based risk estimates
+    pub fn update_price_history(&mut self, symbol: &str, prices: Vec<PriceData>) {
+        self.price_history.insert(symbol.to_uppercase(), prices);
+    }
+
+    /// Replace the live position set the portfolio VaR is computed over
+    pub fn set_positions(&mut self, positions: Vec<VarPosition>) {
+        self.var_positions = positions;
+    }
+
+    /// Recompute pairwise return correlations from the stored price history
+    pub fn refresh_correlations(&mut self) {
+        self.correlation_matrix.correlations = pairwise_correlations(&self.price_history, CORRELATION_WINDOW, CORRELATION_WINDOW / 3);
+        self.correlation_matrix.last_updated = Utc::now();
+    }
+
+    /// Rolling correlation of two symbols' daily returns, if both have enough history
+    pub fn correlation(&self, first: &str, second: &str) -> Option<f64> {
+        let (first, second) = (first.to_uppercase(), second.to_uppercase());
+        if first == second {
+            return Some(1.0);
+        }
+        let key = if first < second { (first, second) } else { (second, first) };
+        self.correlation_matrix.correlations.get(&key).copied()
+    }
+
+    /// Historical market events available for stress replay
+    pub fn historical_events(&self) -> &[HistoricalStressEvent] {
+        &self.stress_tester.historical_events
+    }
+
+    /// Full VaR/ES report for the live positions over `horizon_days`
+    pub fn var_report(&self, horizon_days: u32) -> TradingResult<VarReport> {
+        let returns = self.return_matrix(self.var_positions.iter().map(|p| p.symbol.as_str()));
+        self.var_calculator.calculate(&self.var_positions, &returns, horizon_days)
+    }
+
+    /// Headline VaR of the live positions, as compared against `max_portfolio_var`
+    pub fn current_var(&self, horizon_days: u32) -> TradingResult<Decimal> {
+        Ok(self.headline_var(&self.var_report(horizon_days)?))
+    }
+
+    /// Kupiec backtest of rolling one-day VaR for the live positions
+    pub fn backtest_var(&self, method: VarMethod, confidence: f64, window: usize) -> TradingResult<VarBacktest> {
+        if self.var_positions.is_empty() {
+            return Err(TradingError::validation_error(
+                "positions".to_string(),
+                "No open positions to backtest".to_string(),
+                None,
+            ));
+        }
+        let returns = self.return_matrix(self.var_positions.iter().map(|p| p.symbol.as_str()));
+        self.var_calculator.backtest(&self.var_positions, &returns, method, confidence, window)
+    }
+
     /// Assess risk for a specific order before execution
     pub async fn assess_order_risk(&self, order: &AdvancedOrderRequest) -> TradingResult<RiskAssessment> {
         let mut risk_warnings = Vec::new();
@@ -255,8 +307,9 @@ impl AdvancedRiskEngine {
         let market_risk = self.calculate_market_risk(order).await?;
 
         // VaR calculations
-        let var_1_day = self.calculate_var_impact(order, 1).await?;
-        let var_1_week = self.calculate_var_impact(order, 7).await?;
+        let var_report = self.calculate_var_impact(order, 1).await?;
+        let var_1_day = self.headline_var(&var_report);
+        let var_1_week = self.headline_var(&self.calculate_var_impact(order, 7).await?);
 
         // Check risk limits and generate warnings
         if position_risk > Decimal::from_f64_retain(self.risk_limits.max_single_position_percent).unwrap_or(Decimal::ZERO) {
@@ -268,6 +321,20 @@ impl AdvancedRiskEngine {
             });
         }
 
+        if correlation_risk.to_f64().unwrap_or(0.0) > self.risk_limits.max_correlation_exposure {
+            risk_warnings.push(RiskWarning {
+                warning_type: "Correlated Exposure".to_string(),
+                severity: RiskSeverity::High,
+                message: format!("{:.1}% of exposure would be correlated with {}, above the {}% limit",
+                    correlation_risk, order.symbol, self.risk_limits.max_correlation_exposure),
+                recommended_action: "Reduce size or choose a less correlated asset".to_string(),
+            });
+        }
+
+        if let Some(warning) = Self::missing_var_warning(&var_report) {
+            risk_warnings.push(warning);
+        }
+
         if var_1_day > self.risk_limits.max_portfolio_var {
             risk_warnings.push(RiskWarning {
                 warning_type: "VaR Limit Breach".to_string(),
@@ -313,6 +380,10 @@ impl AdvancedRiskEngine {
         let var_1_day = self.calculate_portfolio_var(1).await?;
         let var_1_week = self.calculate_portfolio_var(7).await?;
 
+        if let Some(warning) = Self::missing_var_warning(&self.var_report(1)?) {
+            risk_warnings.push(warning);
+        }
+
         // Check global risk limits
         if var_1_day > self.risk_limits.max_portfolio_var {
             risk_warnings.push(RiskWarning {
@@ -352,15 +423,18 @@ impl AdvancedRiskEngine {
         })
     }
 
-    /// Update risk models with new market data
+    /// Update risk models with new market data; volatility and per-unit VaR are
+    /// estimated from stored history when available, otherwise taken from `market_data`
     pub async fn update_risk_models(&mut self, market_data: &HashMap<String, MarketData>) -> TradingResult<()> {
         for (symbol, data) in market_data {
+            let (volatility, var_1_day, var_1_week) = self.estimate_unit_risk(symbol, data.price)
+                .unwrap_or((data.volatility, data.var_1_day, data.var_1_week));
             let risk_model = RiskModel {
                 symbol: symbol.clone(),
-                volatility: data.volatility,
+                volatility,
                 beta: data.beta,
-                var_1_day: data.var_1_day,
-                var_1_week: data.var_1_week,
+                var_1_day,
+                var_1_week,
                 max_correlation: data.max_correlation,
                 liquidity_score: data.liquidity_score,
                 last_updated: Utc::now(),
@@ -381,6 +455,18 @@ impl AdvancedRiskEngine {
         
         // Check VaR limits
         let current_var = self.calculate_portfolio_var(1).await?;
+        if let Some(warning) = Self::missing_var_warning(&self.var_report(1)?) {
+            alerts.push(RiskAlert {
+                alert_id: uuid::Uuid::new_v4().to_string(),
+                alert_type: RiskAlertType::VarBreach,
+                severity: warning.severity,
+                message: warning.message,
+                triggered_at: Utc::now(),
+                threshold_value: self.risk_limits.max_portfolio_var,
+                current_value: current_var,
+                recommended_actions: vec![warning.recommended_action],
+            });
+        }
         if current_var > self.risk_limits.max_portfolio_var {
             alerts.push(RiskAlert {
                 alert_id: uuid::Uuid::new_v4().to_string(),
@@ -430,8 +516,19 @@ impl AdvancedRiskEngine {
         Ok(Decimal::from_f64_retain(1.8).unwrap_or(Decimal::ZERO))
     }
 
-    async fn calculate_correlation_risk(&self, _order: &AdvancedOrderRequest) -> TradingResult<Decimal> {
-        Ok(Decimal::from_f64_retain(3.2).unwrap_or(Decimal::ZERO))
+    /// Share of gross exposure after the order held in symbols correlated with the order's;
+    /// zero when nothing correlated is already held
+    async fn calculate_correlation_risk(&self, order: &AdvancedOrderRequest) -> TradingResult<Decimal> {
+        let held: f64 = self.var_positions.iter()
+            .filter(|p| self.correlation(&p.symbol, &order.symbol).is_some_and(|c| c >= CORRELATION_THRESHOLD))
+            .map(|p| p.exposure.abs())
+            .sum();
+        if held <= 0.0 {
+            return Ok(Decimal::ZERO);
+        }
+        let added = self.order_position(order).exposure.abs();
+        let gross = self.var_positions.iter().map(|p| p.exposure.abs()).sum::<f64>() + added;
+        Ok(Self::percent(held + added, gross))
     }
 
     async fn calculate_liquidity_risk(&self, _order: &AdvancedOrderRequest) -> TradingResult<Decimal> {
@@ -446,14 +543,34 @@ impl AdvancedRiskEngine {
         Ok(Decimal::from_f64_retain(2.8).unwrap_or(Decimal::ZERO))
     }
 
-    async fn calculate_var_impact(&self, _order: &AdvancedOrderRequest, _days: u32) -> TradingResult<Decimal> {
-        Ok(Decimal::from(500))
+    /// Portfolio VaR report after the order is filled
+    async fn calculate_var_impact(&self, order: &AdvancedOrderRequest, days: u32) -> TradingResult<VarReport> {
+        let mut positions = self.var_positions.clone();
+        positions.push(self.order_position(order));
+        let returns = self.return_matrix(positions.iter().map(|p| p.symbol.as_str()));
+        self.var_calculator.calculate(&positions, &returns, days)
+    }
+
+    /// Critical warning when positions are left out of the VaR estimate; without
+    /// their history the VaR limit cannot be checked, so the book is not treated as riskless
+    fn missing_var_warning(report: &VarReport) -> Option<RiskWarning> {
+        if report.missing_history.is_empty() {
+            return None;
+        }
+        Some(RiskWarning {
+            warning_type: "VaR Unavailable".to_string(),
+            severity: RiskSeverity::Critical,
+            message: format!("No VaR estimate for {}: fewer than {} aligned daily returns stored",
+                report.missing_history.join(", "), MIN_VAR_OBSERVATIONS),
+            recommended_action: "Download daily klines for these symbols before trading".to_string(),
+        })
     }
 
-    async fn perform_stress_test_for_order(&self, _order: &AdvancedOrderRequest, scenario: &StressTestScenario) -> TradingResult<StressTestResult> {
+    async fn perform_stress_test_for_order(&self, order: &AdvancedOrderRequest, scenario: &StressTestScenario) -> TradingResult<StressTestResult> {
+        let position = self.order_position(order);
         Ok(StressTestResult {
             scenario_name: scenario.name.clone(),
-            potential_loss: Decimal::from(1000),
+            potential_loss: Self::shock_loss(std::slice::from_ref(&position), scenario),
             probability: scenario.probability,
             impact_severity: RiskSeverity::Medium,
         })
@@ -463,8 +580,27 @@ impl AdvancedRiskEngine {
         Ok(Decimal::from_f64_retain(15.5).unwrap_or(Decimal::ZERO))
     }
 
+    /// Share of gross exposure held in the largest correlation cluster
     async fn calculate_portfolio_correlation_risk(&self) -> TradingResult<Decimal> {
-        Ok(Decimal::from_f64_retain(8.2).unwrap_or(Decimal::ZERO))
+        let mut exposures: HashMap<String, f64> = HashMap::new();
+        for position in &self.var_positions {
+            *exposures.entry(position.symbol.to_uppercase()).or_insert(0.0) += position.exposure.abs();
+        }
+        let mut symbols: Vec<String> = exposures.keys().cloned().collect();
+        symbols.sort();
+
+        let largest = cluster_by_correlation(&symbols, &self.correlation_matrix.correlations, CORRELATION_THRESHOLD)
+            .iter()
+            .map(|members| members.iter().map(|s| exposures[s]).sum::<f64>())
+            .fold(0.0, f64::max);
+        Ok(Self::percent(largest, exposures.values().sum()))
+    }
+
+    fn percent(part: f64, total: f64) -> Decimal {
+        if total <= 0.0 {
+            return Decimal::ZERO;
+        }
+        Decimal::from_f64_retain(part / total * 100.0).map(|p| p.round_dp(2)).unwrap_or(Decimal::ZERO)
     }
 
     async fn calculate_portfolio_concentration_risk(&self) -> TradingResult<Decimal> {
@@ -479,14 +615,73 @@ impl AdvancedRiskEngine {
         Ok(Decimal::from_f64_retain(18.7).unwrap_or(Decimal::ZERO))
     }
 
-    async fn calculate_portfolio_var(&self, _days: u32) -> TradingResult<Decimal> {
-        Ok(Decimal::from(2500))
+    async fn calculate_portfolio_var(&self, days: u32) -> TradingResult<Decimal> {
+        let report = self.var_report(days)?;
+        Ok(self.headline_var(&report))
+    }
+
+    /// Signed exposure the order adds, priced at its limit or the last stored close
+    fn order_position(&self, order: &AdvancedOrderRequest) -> VarPosition {
+        let symbol = order.symbol.to_uppercase();
+        let price = order.price
+            .or_else(|| self.price_history.get(&symbol).and_then(|h| h.last()).map(|p| p.close))
+            .and_then(|p| p.to_f64())
+            .unwrap_or(0.0);
+        let notional = order.quantity.to_f64().unwrap_or(0.0) * price;
+        let exposure = match order.side {
+            OrderSide::Buy | OrderSide::Long => notional,
+            OrderSide::Sell | OrderSide::Short => -notional,
+        };
+        VarPosition { symbol, exposure }
+    }
+
+    /// Loss from applying the scenario's uniform market shock to the exposures
+    fn shock_loss(positions: &[VarPosition], scenario: &StressTestScenario) -> Decimal {
+        let pnl: f64 = positions.iter().map(|p| p.exposure * scenario.market_shock_percent / 100.0).sum();
+        Decimal::from_f64_retain((-pnl).max(0.0)).map(|loss| loss.round_dp(2)).unwrap_or(Decimal::ZERO)
+    }
+
+    /// Historical VaR at the primary confidence level, used against risk limits
+    fn headline_var(&self, report: &VarReport) -> Decimal {
+        report.estimate(VarMethod::Historical, self.var_calculator.primary_confidence())
+            .and_then(|e| Decimal::from_f64_retain(e.var))
+            .map(|var| var.round_dp(2))
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    /// Aligned returns for the given symbols from the stored price history
+    fn return_matrix<'a>(&self, symbols: impl Iterator<Item = &'a str>) -> ReturnMatrix {
+        let mut prices = HashMap::new();
+        for symbol in symbols {
+            let symbol = symbol.to_uppercase();
+            if let Some(history) = self.price_history.get(&symbol) {
+                prices.insert(symbol, history.clone());
+            }
+        }
+        ReturnMatrix::from_prices(&prices, MIN_VAR_OBSERVATIONS, usize::MAX)
+    }
+
+    /// Daily volatility and one- and seven-day VaR of a single unit held at `price`
+    fn estimate_unit_risk(&self, symbol: &str, price: Decimal) -> Option<(f64, Decimal, Decimal)> {
+        let returns = self.return_matrix(std::iter::once(symbol));
+        let position = [VarPosition { symbol: symbol.to_uppercase(), exposure: price.to_f64()? }];
+        let var_1_day = self.var_calculator.calculate(&position, &returns, 1).ok()?;
+        let var_1_week = self.var_calculator.calculate(&position, &returns, 7).ok()?;
+        if !var_1_day.missing_history.is_empty() {
+            return None;
+        }
+
+        let series: Vec<f64> = returns.returns.iter().map(|row| row[0]).collect();
+        let mean = series.iter().sum::<f64>() / series.len() as f64;
+        let volatility = (series.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (series.len() - 1) as f64).sqrt();
+
+        Some((volatility, self.headline_var(&var_1_day), self.headline_var(&var_1_week)))
     }
 
     async fn perform_portfolio_stress_test(&self, scenario: &StressTestScenario) -> TradingResult<StressTestResult> {
         Ok(StressTestResult {
             scenario_name: scenario.name.clone(),
-            potential_loss: Decimal::from(5000),
+            potential_loss: Self::shock_loss(&self.var_positions, scenario),
             probability: scenario.probability,
             impact_severity: match scenario.market_shock_percent {
                 x if x < -40.0 => RiskSeverity::Critical,
@@ -498,8 +693,7 @@ impl AdvancedRiskEngine {
     }
 
     async fn update_correlation_matrix(&mut self, _market_data: &HashMap<String, MarketData>) -> TradingResult<()> {
-        // Update correlation calculations
-        self.correlation_matrix.last_updated = Utc::now();
+        self.refresh_correlations();
         Ok(())
     }
 }
@@ -522,14 +716,25 @@ pub struct MarketData {
 impl Default for GlobalRiskLimits {
     fn default() -> Self {
         Self {
-            max_portfolio_var: Decimal::from(5000),
-            max_single_position_percent: 25.0,
-            max_sector_concentration: 40.0,
-            max_correlation_exposure: 60.0,
-            max_daily_loss_percent: 5.0,
-            max_drawdown_percent: 15.0,
-            leverage_limit: 3.0,
-            liquidity_requirement: 0.1,
+            max_portfolio_var: Decimal::from(5000), // $5,000 max 1-day VaR
+            max_single_position_percent: 25.0, // 25% max single position
+            max_sector_concentration: 40.0, // 40% max sector exposure
+            max_correlation_exposure: 60.0, // 60% max correlated positions
+            max_daily_loss_percent: 5.0, // 5% max daily loss
+            max_drawdown_percent: 15.0, // 15% max drawdown
+            leverage_limit: 3.0, // 3x max leverage
+            liquidity_requirement: 0.1, // 10% liquidity requirement
+        }
+    }
+}
+
+impl Default for ConcentrationLimits {
+    fn default() -> Self {
+        Self {
+            max_single_position: 25.0,
+            max_sector_exposure: 40.0,
+            max_correlated_positions: 60.0,
+            min_diversification_ratio: 0.3,
         }
     }
 }
\ No newline at end of file
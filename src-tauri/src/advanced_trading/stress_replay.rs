@@ -0,0 +1,587 @@
+// Historical Stress Replay
+// Replays real market windows from stored klines and custom per-asset shock
+// vectors against the current positions and their protective stops
+
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{TradingError, TradingResult};
+use crate::models::PriceData;
+use super::AdvancedOrderType;
+use super::order_management::ActiveOrder;
+use super::risk_engine::HistoricalStressEvent;
+
+/// Square-root market impact coefficient used for liquidation costs
+pub const DEFAULT_IMPACT_COEFFICIENT: f64 = 0.1;
+
+/// Liquidation cost never exceeds this fraction of the notional
+const MAX_IMPACT_FRACTION: f64 = 0.5;
+
+/// Longest replay window accepted
+const MAX_REPLAY_DAYS: i64 = 366;
+
+/// Per-bar (mark-to-market P&L, adverse intrabar P&L) of one position
+type BarMarks = Vec<(DateTime<Utc>, (f64, f64))>;
+
+/// Protective stop attached to a position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StopRule {
+    Fixed { price: f64 },
+    /// Trails the best price seen by `distance` (quote currency)
+    Trailing { distance: f64 },
+}
+
+/// Position as seen by the stress tester
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressPosition {
+    pub symbol: String,
+    /// Signed base quantity (negative for shorts)
+    pub quantity: f64,
+    pub price: f64,
+    pub stop: Option<StopRule>,
+}
+
+/// Date range to replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayWindow {
+    pub name: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl ReplayWindow {
+    pub fn from_event(event: &HistoricalStressEvent) -> Self {
+        Self {
+            name: event.name.clone(),
+            start: event.date,
+            end: event.date + Duration::days(event.duration_days as i64),
+        }
+    }
+
+    pub fn validate(&self) -> TradingResult<()> {
+        if self.end <= self.start {
+            return Err(TradingError::validation_error(
+                "end".to_string(),
+                "Replay window must end after it starts".to_string(),
+                Some(format!("{} - {}", self.start, self.end)),
+            ));
+        }
+        if self.end - self.start > Duration::days(MAX_REPLAY_DAYS) {
+            return Err(TradingError::validation_error(
+                "end".to_string(),
+                format!("Replay window cannot exceed {} days", MAX_REPLAY_DAYS),
+                Some(format!("{} - {}", self.start, self.end)),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Well-known crypto drawdowns; `market_impact` is the approximate BTC peak-to-trough move
+pub fn historical_events() -> Vec<HistoricalStressEvent> {
+    let event = |name: &str, (y, m, d): (i32, u32, u32), market_impact: f64, duration_days: u32, recovery_days: u32| {
+        HistoricalStressEvent {
+            name: name.to_string(),
+            date: Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap(),
+            market_impact,
+            duration_days,
+            recovery_days,
+        }
+    };
+
+    vec![
+        event("March 2020 Crash", (2020, 3, 8), -0.50, 9, 55),
+        event("May 2021 Sell-off", (2021, 5, 10), -0.53, 14, 150),
+        event("LUNA Collapse", (2022, 5, 5), -0.33, 14, 580),
+        event("FTX Collapse", (2022, 11, 6), -0.26, 10, 70),
+    ]
+}
+
+/// Stop per symbol from open protective orders (stop-loss, bracket, OCO and trailing stops)
+pub fn protective_stops(orders: &[ActiveOrder]) -> HashMap<String, StopRule> {
+    let mut stops = HashMap::new();
+    for order in orders {
+        let stop = match &order.order_type {
+            AdvancedOrderType::StopLoss { stop_price, .. } => stop_price.to_f64().map(|price| StopRule::Fixed { price }),
+            AdvancedOrderType::Bracket { stop_loss, .. } => stop_loss.to_f64().map(|price| StopRule::Fixed { price }),
+            AdvancedOrderType::OCO { stop_price, .. } => stop_price.to_f64().map(|price| StopRule::Fixed { price }),
+            AdvancedOrderType::TrailingStop { trail_amount, .. } => trail_amount.to_f64().map(|distance| StopRule::Trailing { distance }),
+            _ => None,
+        };
+        if let Some(stop) = stop {
+            stops.insert(order.symbol.to_uppercase(), stop);
+        }
+    }
+    stops
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionReplay {
+    pub symbol: String,
+    pub quantity: f64,
+    pub start_price: f64,
+    /// Price the position was closed at (stop fill) or marked at the end of the window
+    pub exit_price: f64,
+    pub pnl: f64,
+    /// Most adverse intrabar P&L while the position was open
+    pub worst_pnl: f64,
+    pub stopped_out: bool,
+    pub time_to_stop_out_hours: Option<f64>,
+    pub liquidity_cost: f64,
+    pub bars: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressReplayResult {
+    pub scenario: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub interval: String,
+    pub positions: Vec<PositionReplay>,
+    pub final_pnl: f64,
+    /// Worst simultaneous portfolio P&L over the window
+    pub worst_pnl: f64,
+    pub worst_pnl_at: Option<DateTime<Utc>>,
+    pub worst_pnl_percent: f64,
+    pub time_to_first_stop_hours: Option<f64>,
+    pub liquidity_cost: f64,
+    pub liquidity_adjusted_pnl: f64,
+    pub liquidity_adjusted_worst_pnl: f64,
+    /// Position symbols with no stored klines in the window; excluded from the replay
+    pub missing_data: Vec<String>,
+}
+
+/// Instantaneous per-asset price shocks in percent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShockScenario {
+    pub name: String,
+    #[serde(default)]
+    pub shocks: HashMap<String, f64>,
+    /// Applied to positions without an explicit shock
+    #[serde(default)]
+    pub default_shock_percent: f64,
+}
+
+impl ShockScenario {
+    pub fn validate(&self) -> TradingResult<()> {
+        for (symbol, shock) in self.shocks.iter().map(|(s, v)| (s.as_str(), *v)).chain(std::iter::once(("default", self.default_shock_percent))) {
+            if !shock.is_finite() || shock <= -100.0 {
+                return Err(TradingError::validation_error(
+                    "shocks".to_string(),
+                    format!("Shock for {} must be greater than -100%", symbol),
+                    Some(shock.to_string()),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn shock_for(&self, symbol: &str) -> f64 {
+        self.shocks.iter()
+            .find(|(s, _)| s.eq_ignore_ascii_case(symbol))
+            .map(|(_, shock)| *shock)
+            .unwrap_or(self.default_shock_percent)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionShock {
+    pub symbol: String,
+    pub shock_percent: f64,
+    pub shocked_price: f64,
+    pub pnl: f64,
+    /// The shock gapped through the stop, so the stop gives no protection
+    pub stop_gapped: bool,
+    pub liquidity_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShockResult {
+    pub scenario: String,
+    pub positions: Vec<PositionShock>,
+    pub total_pnl: f64,
+    pub liquidity_cost: f64,
+    pub liquidity_adjusted_pnl: f64,
+}
+
+/// Stress replay engine
+#[derive(Debug, Clone)]
+pub struct StressReplayer {
+    pub impact_coefficient: f64,
+}
+
+impl Default for StressReplayer {
+    fn default() -> Self {
+        Self { impact_coefficient: DEFAULT_IMPACT_COEFFICIENT }
+    }
+}
+
+impl StressReplayer {
+    /// Apply each asset's actual path over `window` (rebased to today's price) to the positions.
+    /// `paths` holds the stored bars for each symbol within the window, oldest first.
+    pub fn replay(&self, positions: &[StressPosition], paths: &HashMap<String, Vec<PriceData>>, window: &ReplayWindow, interval: &str) -> TradingResult<StressReplayResult> {
+        window.validate()?;
+
+        let mut missing_data = Vec::new();
+        let mut replays = Vec::new();
+        // timestamp -> per-position (mark-to-market P&L, adverse intrabar P&L)
+        let mut timeline: BTreeMap<DateTime<Utc>, Vec<Option<(f64, f64)>>> = BTreeMap::new();
+
+        for position in positions.iter().filter(|p| p.quantity != 0.0 && p.price > 0.0) {
+            let symbol = position.symbol.to_uppercase();
+            let bars = match paths.get(&symbol) {
+                Some(bars) if !bars.is_empty() => bars,
+                _ => {
+                    missing_data.push(symbol);
+                    continue;
+                }
+            };
+
+            let index = replays.len();
+            let (replay, marks) = self.replay_position(position, bars);
+            for (timestamp, mark) in marks {
+                let row = timeline.entry(timestamp).or_default();
+                row.resize(index + 1, None);
+                row[index] = Some(mark);
+            }
+            replays.push(replay);
+        }
+
+        // Forward-fill each position's P&L so assets with different bar sets line up
+        let mut last = vec![(0.0, 0.0); replays.len()];
+        let mut worst_pnl = 0.0;
+        let mut worst_pnl_at = None;
+        for (timestamp, row) in &timeline {
+            for (i, mark) in row.iter().enumerate() {
+                if let Some((pnl, adverse)) = mark {
+                    last[i] = (*pnl, *adverse);
+                }
+            }
+            let adverse: f64 = last.iter().map(|(_, adverse)| adverse).sum();
+            if adverse < worst_pnl {
+                worst_pnl = adverse;
+                worst_pnl_at = Some(*timestamp);
+            }
+            for entry in last.iter_mut() {
+                entry.1 = entry.0;
+            }
+        }
+
+        let final_pnl: f64 = replays.iter().map(|r| r.pnl).sum();
+        let liquidity_cost: f64 = replays.iter().map(|r| r.liquidity_cost).sum();
+        let gross: f64 = positions.iter().map(|p| p.quantity.abs() * p.price).sum();
+
+        Ok(StressReplayResult {
+            scenario: window.name.clone(),
+            start: window.start,
+            end: window.end,
+            interval: interval.to_string(),
+            time_to_first_stop_hours: replays.iter()
+                .filter_map(|r| r.time_to_stop_out_hours)
+                .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal)),
+            positions: replays,
+            final_pnl,
+            worst_pnl,
+            worst_pnl_at,
+            worst_pnl_percent: if gross > 0.0 { worst_pnl / gross * 100.0 } else { 0.0 },
+            liquidity_cost,
+            liquidity_adjusted_pnl: final_pnl - liquidity_cost,
+            liquidity_adjusted_worst_pnl: worst_pnl - liquidity_cost,
+            missing_data,
+        })
+    }
+
+    /// Apply an instantaneous shock vector. `bar_quote_volume` is the average traded quote
+    /// volume per day for each symbol and drives the liquidation cost.
+    pub fn shock(&self, positions: &[StressPosition], scenario: &ShockScenario, bar_quote_volume: &HashMap<String, f64>) -> TradingResult<ShockResult> {
+        scenario.validate()?;
+
+        let shocked: Vec<PositionShock> = positions.iter()
+            .filter(|p| p.quantity != 0.0 && p.price > 0.0)
+            .map(|position| {
+                let symbol = position.symbol.to_uppercase();
+                let shock_percent = scenario.shock_for(&symbol);
+                let shocked_price = position.price * (1.0 + shock_percent / 100.0);
+                let stop_gapped = match position.stop {
+                    Some(StopRule::Fixed { price }) => {
+                        if position.quantity > 0.0 { shocked_price < price } else { shocked_price > price }
+                    }
+                    Some(StopRule::Trailing { distance }) => {
+                        if position.quantity > 0.0 { shocked_price < position.price - distance } else { shocked_price > position.price + distance }
+                    }
+                    None => false,
+                };
+                PositionShock {
+                    liquidity_cost: self.liquidity_cost(position.quantity.abs() * shocked_price, bar_quote_volume.get(&symbol).copied()),
+                    symbol,
+                    shock_percent,
+                    shocked_price,
+                    pnl: position.quantity * (shocked_price - position.price),
+                    stop_gapped,
+                }
+            })
+            .collect();
+
+        let total_pnl: f64 = shocked.iter().map(|p| p.pnl).sum();
+        let liquidity_cost: f64 = shocked.iter().map(|p| p.liquidity_cost).sum();
+        Ok(ShockResult {
+            scenario: scenario.name.clone(),
+            positions: shocked,
+            total_pnl,
+            liquidity_cost,
+            liquidity_adjusted_pnl: total_pnl - liquidity_cost,
+        })
+    }
+
+    /// Walk one position through its rebased path, returning the summary and per-bar
+    /// (mark-to-market P&L, adverse intrabar P&L) marks
+    fn replay_position(&self, position: &StressPosition, bars: &[PriceData]) -> (PositionReplay, BarMarks) {
+        let to_f64 = |d: rust_decimal::Decimal| d.to_f64().unwrap_or(0.0);
+        let base = to_f64(bars[0].open).max(f64::EPSILON);
+        let scale = position.price / base;
+        let long = position.quantity > 0.0;
+        let started = bars[0].timestamp;
+
+        let mut best = position.price;
+        let mut exit = None;
+        let mut worst_pnl: f64 = 0.0;
+        let mut marks = Vec::with_capacity(bars.len());
+
+        for bar in bars {
+            let (open, high, low, close) = (to_f64(bar.open) * scale, to_f64(bar.high) * scale, to_f64(bar.low) * scale, to_f64(bar.close) * scale);
+            let stop_level = position.stop.as_ref().map(|stop| match stop {
+                StopRule::Fixed { price } => *price,
+                StopRule::Trailing { distance } => if long { best - distance } else { best + distance },
+            });
+
+            // Gaps through the stop fill at the open, otherwise at the stop
+            let fill = stop_level.and_then(|stop| {
+                if long && low <= stop {
+                    Some(open.min(stop))
+                } else if !long && high >= stop {
+                    Some(open.max(stop))
+                } else {
+                    None
+                }
+            });
+
+            if let Some(price) = fill {
+                let pnl = position.quantity * (price - position.price);
+                let adverse = position.quantity * ((if long { low.max(price) } else { high.min(price) }) - position.price);
+                worst_pnl = worst_pnl.min(pnl).min(adverse);
+                marks.push((bar.timestamp, (pnl, adverse.min(pnl))));
+                exit = Some((price, bar.timestamp, to_f64(bar.volume) * close));
+                break;
+            }
+
+            let adverse = position.quantity * ((if long { low } else { high }) - position.price);
+            worst_pnl = worst_pnl.min(adverse);
+            marks.push((bar.timestamp, (position.quantity * (close - position.price), adverse)));
+            best = if long { best.max(high) } else { best.min(low) };
+        }
+
+        // Base volume is kept while prices are rebased, so quote volume is in the position's price terms
+        let average_quote_volume = bars.iter()
+            .map(|bar| to_f64(bar.volume) * to_f64(bar.close) * scale)
+            .sum::<f64>() / bars.len() as f64;
+        let (exit_price, stopped_at) = match exit {
+            Some((price, timestamp, _)) => (price, Some(timestamp)),
+            None => (bars.last().map(|bar| to_f64(bar.close) * scale).unwrap_or(position.price), None),
+        };
+        let liquidity_volume = exit.map(|(_, _, volume)| volume.max(average_quote_volume)).unwrap_or(average_quote_volume);
+
+        let replay = PositionReplay {
+            symbol: position.symbol.to_uppercase(),
+            quantity: position.quantity,
+            start_price: position.price,
+            exit_price,
+            pnl: position.quantity * (exit_price - position.price),
+            worst_pnl,
+            stopped_out: stopped_at.is_some(),
+            time_to_stop_out_hours: stopped_at.map(|t| (t - started).num_minutes() as f64 / 60.0),
+            liquidity_cost: self.liquidity_cost(position.quantity.abs() * exit_price, Some(liquidity_volume)),
+            bars: marks.len(),
+        };
+        (replay, marks)
+    }
+
+    /// Square-root impact cost of liquidating `notional` against `quote_volume` traded in the period
+    fn liquidity_cost(&self, notional: f64, quote_volume: Option<f64>) -> f64 {
+        match quote_volume {
+            Some(volume) if volume > 0.0 => {
+                let fraction = (self.impact_coefficient * (notional / volume).sqrt()).min(MAX_IMPACT_FRACTION);
+                notional * fraction
+            }
+            // No volume information: assume the worst
+            _ => notional * MAX_IMPACT_FRACTION.min(self.impact_coefficient),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn bars(start: DateTime<Utc>, prices: &[(f64, f64, f64, f64)]) -> Vec<PriceData> {
+        prices.iter().enumerate().map(|(i, (open, high, low, close))| PriceData {
+            timestamp: start + Duration::hours(i as i64),
+            open: Decimal::from_f64(*open).unwrap(),
+            high: Decimal::from_f64(*high).unwrap(),
+            low: Decimal::from_f64(*low).unwrap(),
+            close: Decimal::from_f64(*close).unwrap(),
+            volume: Decimal::from(1000),
+        }).collect()
+    }
+
+    fn window() -> ReplayWindow {
+        let start = Utc.with_ymd_and_hms(2020, 3, 12, 0, 0, 0).unwrap();
+        ReplayWindow { name: "test".to_string(), start, end: start + Duration::days(1) }
+    }
+
+    #[test]
+    fn test_replay_rebases_path_and_hits_stop() {
+        let start = window().start;
+        // Historical path halves from 8000 to 4000
+        let mut paths = HashMap::new();
+        paths.insert("BTCUSDT".to_string(), bars(start, &[
+            (8000.0, 8000.0, 7200.0, 7600.0),
+            (7600.0, 7700.0, 6000.0, 6400.0),
+            (6400.0, 6500.0, 3800.0, 4000.0),
+        ]));
+
+        let unprotected = StressPosition { symbol: "btcusdt".to_string(), quantity: 1.0, price: 60000.0, stop: None };
+        let result = StressReplayer::default().replay(std::slice::from_ref(&unprotected), &paths, &window(), "1h").unwrap();
+        let position = &result.positions[0];
+        assert!((position.exit_price - 30000.0).abs() < 1e-6);
+        assert!((result.final_pnl + 30000.0).abs() < 1e-6);
+        assert!((result.worst_pnl + 31500.0).abs() < 1e-6); // low of 3800 rebased
+        assert!(!position.stopped_out);
+        assert!(result.liquidity_adjusted_pnl < result.final_pnl);
+
+        // Stop at 50000 is hit intrabar on the second bar
+        let protected = StressPosition { stop: Some(StopRule::Fixed { price: 50000.0 }), ..unprotected };
+        let result = StressReplayer::default().replay(&[protected], &paths, &window(), "1h").unwrap();
+        let position = &result.positions[0];
+        assert!(position.stopped_out);
+        assert_eq!(position.time_to_stop_out_hours, Some(1.0));
+        assert!((position.pnl + 10000.0).abs() < 1e-6);
+        assert!((result.worst_pnl + 10000.0).abs() < 1e-6);
+        assert_eq!(result.time_to_first_stop_hours, Some(1.0));
+    }
+
+    #[test]
+    fn test_gap_through_stop_fills_at_open() {
+        let start = window().start;
+        let mut paths = HashMap::new();
+        paths.insert("ETHUSDT".to_string(), bars(start, &[
+            (100.0, 101.0, 99.0, 100.0),
+            (80.0, 82.0, 78.0, 79.0),
+        ]));
+
+        let position = StressPosition { symbol: "ETHUSDT".to_string(), quantity: 10.0, price: 100.0, stop: Some(StopRule::Fixed { price: 95.0 }) };
+        let result = StressReplayer::default().replay(&[position], &paths, &window(), "1h").unwrap();
+        assert!((result.positions[0].exit_price - 80.0).abs() < 1e-6);
+        assert!((result.final_pnl + 200.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_trailing_stop_and_short_positions() {
+        let start = window().start;
+        let mut paths = HashMap::new();
+        paths.insert("SOLUSDT".to_string(), bars(start, &[
+            (100.0, 120.0, 100.0, 118.0),
+            (118.0, 119.0, 105.0, 106.0),
+        ]));
+
+        // Long with a 10 trailing stop: peak 120 after the first bar, stopped at 110
+        let long = StressPosition { symbol: "SOLUSDT".to_string(), quantity: 1.0, price: 100.0, stop: Some(StopRule::Trailing { distance: 10.0 }) };
+        // Short without a stop loses as price rises
+        let short = StressPosition { symbol: "SOLUSDT".to_string(), quantity: -2.0, price: 100.0, stop: None };
+        let result = StressReplayer::default().replay(&[long, short], &paths, &window(), "1h").unwrap();
+
+        assert!((result.positions[0].pnl - 10.0).abs() < 1e-6);
+        assert!((result.positions[1].pnl + 12.0).abs() < 1e-6);
+        assert!((result.positions[1].worst_pnl + 40.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_missing_data_and_window_validation() {
+        let position = StressPosition { symbol: "DOGEUSDT".to_string(), quantity: 100.0, price: 0.1, stop: None };
+        let result = StressReplayer::default().replay(&[position], &HashMap::new(), &window(), "1h").unwrap();
+        assert_eq!(result.missing_data, vec!["DOGEUSDT".to_string()]);
+        assert_eq!(result.worst_pnl, 0.0);
+
+        let mut inverted = window();
+        inverted.end = inverted.start - Duration::days(1);
+        assert!(inverted.validate().is_err());
+
+        for event in historical_events() {
+            assert!(ReplayWindow::from_event(&event).validate().is_ok());
+        }
+    }
+
+    #[test]
+    fn test_custom_shock_vector() {
+        let positions = vec![
+            StressPosition { symbol: "BTCUSDT".to_string(), quantity: 1.0, price: 50000.0, stop: Some(StopRule::Fixed { price: 45000.0 }) },
+            StressPosition { symbol: "ETHUSDT".to_string(), quantity: -10.0, price: 3000.0, stop: None },
+        ];
+        let scenario = ShockScenario {
+            name: "custom".to_string(),
+            shocks: HashMap::from([("btcusdt".to_string(), -20.0)]),
+            default_shock_percent: -30.0,
+        };
+        let volumes = HashMap::from([("BTCUSDT".to_string(), 1e9), ("ETHUSDT".to_string(), 1e8)]);
+
+        let result = StressReplayer::default().shock(&positions, &scenario, &volumes).unwrap();
+        assert!((result.positions[0].pnl + 10000.0).abs() < 1e-6);
+        assert!(result.positions[0].stop_gapped);
+        assert!((result.positions[1].pnl - 9000.0).abs() < 1e-6);
+        assert!((result.total_pnl + 1000.0).abs() < 1e-6);
+        assert!(result.liquidity_cost > 0.0);
+
+        let invalid = ShockScenario { default_shock_percent: -100.0, ..scenario };
+        assert!(StressReplayer::default().shock(&positions, &invalid, &volumes).is_err());
+    }
+
+    #[test]
+    fn test_trailing_stop_gaps_only_against_the_position() {
+        let trailing = Some(StopRule::Trailing { distance: 10.0 });
+        let positions = vec![
+            StressPosition { symbol: "SOLUSDT".to_string(), quantity: 1.0, price: 100.0, stop: trailing.clone() },
+            StressPosition { symbol: "SOLUSDT".to_string(), quantity: -1.0, price: 100.0, stop: trailing },
+        ];
+        let volumes = HashMap::from([("SOLUSDT".to_string(), 1e8)]);
+        let gapped = |shock: f64| {
+            let scenario = ShockScenario { name: "move".to_string(), shocks: HashMap::new(), default_shock_percent: shock };
+            let result = StressReplayer::default().shock(&positions, &scenario, &volumes).unwrap();
+            (result.positions[0].stop_gapped, result.positions[1].stop_gapped)
+        };
+
+        assert_eq!(gapped(-20.0), (true, false));
+        assert_eq!(gapped(20.0), (false, true));
+        assert_eq!(gapped(-5.0), (false, false));
+    }
+
+    #[test]
+    fn test_liquidity_uses_rebased_quote_volume() {
+        let start = window().start;
+        let mut paths = HashMap::new();
+        paths.insert("BTCUSDT".to_string(), bars(start, &[
+            (8000.0, 8100.0, 7900.0, 8000.0),
+            (8000.0, 8100.0, 7900.0, 8000.0),
+        ]));
+
+        // The same position in units rebased tenfold faces the same relative impact
+        let replayer = StressReplayer::default();
+        let historical = StressPosition { symbol: "BTCUSDT".to_string(), quantity: 1.0, price: 8000.0, stop: None };
+        let rebased = StressPosition { price: 80000.0, ..historical.clone() };
+        let historical = replayer.replay(&[historical], &paths, &window(), "1h").unwrap();
+        let rebased = replayer.replay(&[rebased], &paths, &window(), "1h").unwrap();
+        assert!((rebased.liquidity_cost - 10.0 * historical.liquidity_cost).abs() < 1e-6);
+    }
+}
@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 
 use crate::errors::{TradingResult, TradingError};
+use crate::divergence::DivergenceKind;
 
 /// Professional technical analysis engine
 pub struct TechnicalAnalysisEngine {
@@ -151,16 +152,7 @@ pub struct MacdIndicator {
     pub macd_line: f64,
     pub signal_line: f64,
     pub histogram: f64,
-    pub divergence: DivergenceType,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum DivergenceType {
-    None,
-    BullishDivergence,
-    BearishDivergence,
-    HiddenBullish,
-    HiddenBearish,
+    pub divergence: Option<DivergenceKind>,
 }
 
 /// Moving averages
@@ -217,7 +209,7 @@ pub struct ChartPattern {
     pub status: PatternStatus,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum PatternType {
     // Continuation Patterns
     Triangle,
@@ -354,42 +346,8 @@ pub struct MultiTimeframeAnalyzer {
     analysis_cache: HashMap<String, TechnicalAnalysisResult>,
 }
 
-/// Technical alert system
-pub struct TechnicalAlertSystem {
-    alert_rules: Vec<AlertRule>,
-    active_alerts: Vec<TechnicalAlert>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AlertRule {
-    pub rule_id: String,
-    pub indicator: String,
-    pub condition: AlertCondition,
-    pub threshold: f64,
-    pub enabled: bool,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum AlertCondition {
-    Above,
-    Below,
-    CrossesAbove,
-    CrossesBelow,
-    Equals,
-    DivergenceDetected,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TechnicalAlert {
-    pub alert_id: String,
-    pub symbol: String,
-    pub timeframe: String,
-    pub rule_id: String,
-    pub message: String,
-    pub triggered_at: DateTime<Utc>,
-    pub current_value: f64,
-    pub threshold_value: f64,
-}
+/// Technical alert system (rule evaluation lives in `technical_alerts`)
+pub use crate::technical_alerts::{AlertCondition, AlertRule, TechnicalAlert, TechnicalAlertSystem};
 
 impl TechnicalAnalysisEngine {
     pub async fn new() -> TradingResult<Self> {
@@ -415,10 +373,7 @@ impl TechnicalAnalysisEngine {
             analysis_cache: HashMap::new(),
         };
 
-        let alert_system = TechnicalAlertSystem {
-            alert_rules: Vec::new(),
-            active_alerts: Vec::new(),
-        };
+        let alert_system = TechnicalAlertSystem::new();
 
         Ok(Self {
             indicators,
@@ -586,7 +541,7 @@ impl TechnicalAnalysisEngine {
                 macd_line: 125.5,
                 signal_line: 118.2,
                 histogram: 7.3,
-                divergence: DivergenceType::None,
+                divergence: None,
             },
             ppo: 2.5,
             cci: 85.0,
@@ -0,0 +1,721 @@
+// Value at Risk Engine
+// Historical, variance-covariance and Monte Carlo VaR / Expected Shortfall over
+// stored daily returns, Euler (component) VaR allocation and Kupiec backtesting
+
+use std::collections::{BTreeMap, HashMap};
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{TradingError, TradingResult};
+use crate::models::PriceData;
+
+/// Fewest aligned return observations accepted for an estimate
+pub const MIN_VAR_OBSERVATIONS: usize = 30;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum VarMethod {
+    Historical,
+    Parametric,
+    MonteCarlo,
+}
+
+impl VarMethod {
+    pub const ALL: [VarMethod; 3] = [VarMethod::Historical, VarMethod::Parametric, VarMethod::MonteCarlo];
+}
+
+/// Signed exposure in quote currency (negative for shorts)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarPosition {
+    pub symbol: String,
+    pub exposure: f64,
+}
+
+/// Simple returns aligned on common timestamps across symbols
+#[derive(Debug, Clone, Default)]
+pub struct ReturnMatrix {
+    pub symbols: Vec<String>,
+    pub timestamps: Vec<DateTime<Utc>>,
+    /// `returns[t][i]` is the return of `symbols[i]` over the bar ending at `timestamps[t]`
+    pub returns: Vec<Vec<f64>>,
+}
+
+impl ReturnMatrix {
+    /// Build close-to-close returns, keeping only timestamps present for every symbol
+    /// and at most the most recent `max_observations` rows. Symbols with fewer than
+    /// `min_returns` returns are left out, so a short history cannot empty the
+    /// intersection for the others
+    pub fn from_prices(prices: &HashMap<String, Vec<PriceData>>, min_returns: usize, max_observations: usize) -> Self {
+        let mut series: BTreeMap<&String, Vec<(DateTime<Utc>, f64)>> = BTreeMap::new();
+        for (symbol, history) in prices {
+            let returns: Vec<(DateTime<Utc>, f64)> = history.windows(2)
+                .filter_map(|pair| {
+                    let previous = pair[0].close.to_f64().unwrap_or(0.0);
+                    let current = pair[1].close.to_f64().unwrap_or(0.0);
+                    (previous > 0.0).then(|| (pair[1].timestamp, current / previous - 1.0))
+                })
+                .collect();
+            if returns.len() >= min_returns {
+                series.insert(symbol, returns);
+            }
+        }
+        let symbols: Vec<String> = series.keys().map(|s| (*s).clone()).collect();
+
+        let mut rows: BTreeMap<DateTime<Utc>, Vec<Option<f64>>> = BTreeMap::new();
+        for (i, returns) in series.values().enumerate() {
+            for &(timestamp, value) in returns {
+                rows.entry(timestamp).or_insert_with(|| vec![None; symbols.len()])[i] = Some(value);
+            }
+        }
+
+        let aligned: Vec<(DateTime<Utc>, Vec<f64>)> = rows
+            .into_iter()
+            .filter_map(|(timestamp, row)| row.into_iter().collect::<Option<Vec<f64>>>().map(|r| (timestamp, r)))
+            .collect();
+        let skip = aligned.len().saturating_sub(max_observations);
+        let (timestamps, returns) = aligned.into_iter().skip(skip).unzip();
+
+        Self { symbols, timestamps, returns }
+    }
+
+    pub fn observations(&self) -> usize {
+        self.returns.len()
+    }
+
+    fn column(&self, symbol: &str) -> Option<usize> {
+        self.symbols.iter().position(|s| s == symbol)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarEstimate {
+    pub method: VarMethod,
+    pub confidence: f64,
+    /// Loss not exceeded with probability `confidence` over the horizon (positive = loss)
+    pub var: f64,
+    /// Average loss beyond the VaR (CVaR)
+    pub expected_shortfall: f64,
+}
+
+/// Per-position VaR decomposition at the primary confidence level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionVar {
+    pub symbol: String,
+    pub exposure: f64,
+    pub standalone_var: f64,
+    /// Change in portfolio VaR per unit of additional exposure
+    pub marginal_var: f64,
+    /// Euler allocation; components sum to the parametric portfolio VaR
+    pub component_var: f64,
+    pub contribution_percent: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarReport {
+    pub horizon_days: u32,
+    pub gross_exposure: f64,
+    pub net_exposure: f64,
+    pub observations: usize,
+    pub estimates: Vec<VarEstimate>,
+    pub positions: Vec<PositionVar>,
+    /// Position symbols without enough stored returns; excluded from the estimates
+    pub missing_history: Vec<String>,
+    pub calculated_at: DateTime<Utc>,
+}
+
+impl VarReport {
+    pub fn estimate(&self, method: VarMethod, confidence: f64) -> Option<&VarEstimate> {
+        self.estimates.iter()
+            .find(|e| e.method == method && (e.confidence - confidence).abs() < 1e-9)
+    }
+}
+
+/// Kupiec proportion-of-failures test of VaR exceptions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarBacktest {
+    pub method: VarMethod,
+    pub confidence: f64,
+    pub window: usize,
+    pub observations: usize,
+    pub exceptions: usize,
+    pub expected_exceptions: f64,
+    pub exception_rate: f64,
+    pub lr_statistic: f64,
+    pub p_value: f64,
+    /// Exception rate is consistent with the confidence level at the 5% significance level
+    pub calibrated: bool,
+}
+
+/// Value at Risk calculator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VarCalculator {
+    pub confidence_levels: Vec<f64>,
+    pub historical_window_days: u32,
+    pub monte_carlo_simulations: u32,
+    pub seed: u64,
+}
+
+impl Default for VarCalculator {
+    fn default() -> Self {
+        Self {
+            confidence_levels: vec![0.95, 0.99, 0.999],
+            historical_window_days: 252, // 1 year of trading days
+            monte_carlo_simulations: 10000,
+            seed: 0x5EED_5EED,
+        }
+    }
+}
+
+impl VarCalculator {
+    /// Confidence level used for limits and the per-position decomposition
+    pub fn primary_confidence(&self) -> f64 {
+        self.confidence_levels.first().copied().unwrap_or(0.95)
+    }
+
+    /// VaR and ES for every method and confidence level, plus per-position allocation
+    pub fn calculate(&self, positions: &[VarPosition], returns: &ReturnMatrix, horizon_days: u32) -> TradingResult<VarReport> {
+        validate_horizon(horizon_days)?;
+
+        // Aggregate exposures per symbol and split off symbols without history
+        let mut exposures: BTreeMap<String, f64> = BTreeMap::new();
+        for position in positions {
+            *exposures.entry(position.symbol.to_uppercase()).or_insert(0.0) += position.exposure;
+        }
+        let observations = returns.observations().min(self.historical_window_days as usize);
+        let (covered, missing): (Vec<_>, Vec<_>) = exposures.into_iter()
+            .partition(|(symbol, _)| returns.column(symbol).is_some() && observations >= MIN_VAR_OBSERVATIONS);
+
+        let symbols: Vec<String> = covered.iter().map(|(s, _)| s.clone()).collect();
+        let weights: Vec<f64> = covered.iter().map(|(_, e)| *e).collect();
+        let rows = self.select_rows(returns, &symbols);
+
+        let mut estimates = Vec::new();
+        let mut position_vars = Vec::new();
+        if !symbols.is_empty() {
+            for method in VarMethod::ALL {
+                for &confidence in &self.confidence_levels {
+                    if let Some(estimate) = self.estimate(method, confidence, &weights, &rows, horizon_days) {
+                        estimates.push(estimate);
+                    }
+                }
+            }
+            position_vars = self.decompose(&symbols, &weights, &rows, horizon_days);
+        }
+
+        Ok(VarReport {
+            horizon_days,
+            gross_exposure: positions.iter().map(|p| p.exposure.abs()).sum(),
+            net_exposure: positions.iter().map(|p| p.exposure).sum(),
+            observations: if symbols.is_empty() { 0 } else { rows.len() },
+            estimates,
+            positions: position_vars,
+            missing_history: missing.into_iter().map(|(s, _)| s).collect(),
+            calculated_at: Utc::now(),
+        })
+    }
+
+    /// Single VaR/ES estimate for exposures `weights` against return rows aligned with them
+    pub fn estimate(&self, method: VarMethod, confidence: f64, weights: &[f64], rows: &[Vec<f64>], horizon_days: u32) -> Option<VarEstimate> {
+        if rows.len() < MIN_VAR_OBSERVATIONS || !(0.5..1.0).contains(&confidence) {
+            return None;
+        }
+        let scale = (horizon_days as f64).sqrt();
+
+        let (var, expected_shortfall) = match method {
+            VarMethod::Historical => {
+                // Square-root-of-time scaling of the one-period P&L distribution
+                let losses: Vec<f64> = rows.iter().map(|row| -dot(weights, row) * scale).collect();
+                empirical_var_es(losses, confidence)
+            }
+            VarMethod::Parametric => {
+                let (means, covariance) = moments(rows);
+                let mean = dot(weights, &means) * horizon_days as f64;
+                let sigma = quadratic_form(weights, &covariance).sqrt() * scale;
+                let z = inverse_normal_cdf(confidence);
+                let var = z * sigma - mean;
+                let es = sigma * normal_pdf(z) / (1.0 - confidence) - mean;
+                (var, es)
+            }
+            VarMethod::MonteCarlo => {
+                let (means, covariance) = moments(rows);
+                let cholesky = cholesky(&covariance)?;
+                let mut rng = SplitMix64::new(self.seed);
+                let mut shocks = vec![0.0; weights.len()];
+                let losses: Vec<f64> = (0..self.monte_carlo_simulations.max(1))
+                    .map(|_| {
+                        for shock in shocks.iter_mut() {
+                            *shock = rng.next_normal();
+                        }
+                        let pnl: f64 = (0..weights.len())
+                            .map(|i| {
+                                let correlated: f64 = (0..=i).map(|j| cholesky[i][j] * shocks[j]).sum();
+                                weights[i] * (means[i] * horizon_days as f64 + correlated * scale)
+                            })
+                            .sum();
+                        -pnl
+                    })
+                    .collect();
+                empirical_var_es(losses, confidence)
+            }
+        };
+
+        Some(VarEstimate {
+            method,
+            confidence,
+            var: var.max(0.0),
+            expected_shortfall: expected_shortfall.max(var).max(0.0),
+        })
+    }
+
+    /// Rolling one-day VaR backtest: each day's VaR is estimated from the preceding
+    /// `window` days only and compared with the realised loss
+    pub fn backtest(&self, positions: &[VarPosition], returns: &ReturnMatrix, method: VarMethod, confidence: f64, window: usize) -> TradingResult<VarBacktest> {
+        if window < MIN_VAR_OBSERVATIONS {
+            return Err(TradingError::validation_error(
+                "window".to_string(),
+                format!("Backtest window must be at least {} observations", MIN_VAR_OBSERVATIONS),
+                Some(window.to_string()),
+            ));
+        }
+        if !(0.5..1.0).contains(&confidence) {
+            return Err(TradingError::validation_error(
+                "confidence".to_string(),
+                "Confidence must be between 0.5 and 1".to_string(),
+                Some(confidence.to_string()),
+            ));
+        }
+
+        let mut weights = vec![0.0; returns.symbols.len()];
+        for position in positions {
+            let column = returns.column(&position.symbol.to_uppercase()).ok_or_else(|| TradingError::validation_error(
+                "symbol".to_string(),
+                format!("No stored returns for {}", position.symbol),
+                Some(position.symbol.clone()),
+            ))?;
+            weights[column] += position.exposure;
+        }
+        if returns.observations() <= window {
+            return Err(TradingError::validation_error(
+                "observations".to_string(),
+                format!("Need more than {} aligned observations, have {}", window, returns.observations()),
+                None,
+            ));
+        }
+
+        let mut exceptions = 0;
+        let mut tested = 0;
+        for t in window..returns.observations() {
+            if let Some(estimate) = self.estimate(method, confidence, &weights, &returns.returns[t - window..t], 1) {
+                tested += 1;
+                if -dot(&weights, &returns.returns[t]) > estimate.var {
+                    exceptions += 1;
+                }
+            }
+        }
+
+        let (lr_statistic, p_value) = kupiec_pof(exceptions, tested, confidence);
+        Ok(VarBacktest {
+            method,
+            confidence,
+            window,
+            observations: tested,
+            exceptions,
+            expected_exceptions: tested as f64 * (1.0 - confidence),
+            exception_rate: if tested > 0 { exceptions as f64 / tested as f64 } else { 0.0 },
+            lr_statistic,
+            p_value,
+            calibrated: p_value >= 0.05,
+        })
+    }
+
+    /// Return rows restricted to `symbols` (in that order) and the historical window
+    fn select_rows(&self, returns: &ReturnMatrix, symbols: &[String]) -> Vec<Vec<f64>> {
+        let columns: Vec<usize> = symbols.iter().filter_map(|s| returns.column(s)).collect();
+        let skip = returns.observations().saturating_sub(self.historical_window_days as usize);
+        returns.returns.iter()
+            .skip(skip)
+            .map(|row| columns.iter().map(|&c| row[c]).collect())
+            .collect()
+    }
+
+    /// Parametric Euler decomposition at the primary confidence level
+    fn decompose(&self, symbols: &[String], weights: &[f64], rows: &[Vec<f64>], horizon_days: u32) -> Vec<PositionVar> {
+        let (_, covariance) = moments(rows);
+        let scale = (horizon_days as f64).sqrt();
+        let z = inverse_normal_cdf(self.primary_confidence());
+        let sigma = quadratic_form(weights, &covariance).sqrt();
+        let portfolio_var = z * sigma * scale;
+
+        symbols.iter().enumerate().map(|(i, symbol)| {
+            let covariance_with_portfolio: f64 = (0..weights.len()).map(|j| covariance[i][j] * weights[j]).sum();
+            let marginal_var = if sigma > 0.0 { z * scale * covariance_with_portfolio / sigma } else { 0.0 };
+            let component_var = marginal_var * weights[i];
+            PositionVar {
+                symbol: symbol.clone(),
+                exposure: weights[i],
+                standalone_var: z * covariance[i][i].sqrt() * weights[i].abs() * scale,
+                marginal_var,
+                component_var,
+                contribution_percent: if portfolio_var > 0.0 { component_var / portfolio_var * 100.0 } else { 0.0 },
+            }
+        }).collect()
+    }
+}
+
+/// Rolling return correlation of every symbol pair, each pair aligned on its own common
+/// timestamps over the last `window` returns; pairs with fewer than `min_observations` are omitted.
+/// Keys are ordered so that the first symbol sorts before the second.
+pub fn pairwise_correlations(
+    prices: &HashMap<String, Vec<PriceData>>,
+    window: usize,
+    min_observations: usize,
+) -> HashMap<(String, String), f64> {
+    let mut symbols: Vec<&String> = prices.keys().collect();
+    symbols.sort();
+
+    let mut correlations = HashMap::new();
+    for (i, first) in symbols.iter().enumerate() {
+        for second in &symbols[i + 1..] {
+            let pair: HashMap<String, Vec<PriceData>> = [(*first, &prices[*first]), (*second, &prices[*second])]
+                .into_iter()
+                .map(|(symbol, history)| (symbol.clone(), history.clone()))
+                .collect();
+            let returns = ReturnMatrix::from_prices(&pair, 0, window);
+            if returns.observations() < min_observations.max(3) {
+                continue;
+            }
+            let (_, covariance) = moments(&returns.returns);
+            let scale = (covariance[0][0] * covariance[1][1]).sqrt();
+            if scale > 0.0 {
+                correlations.insert(((*first).clone(), (*second).clone()), (covariance[0][1] / scale).clamp(-1.0, 1.0));
+            }
+        }
+    }
+    correlations
+}
+
+fn validate_horizon(horizon_days: u32) -> TradingResult<()> {
+    if horizon_days == 0 || horizon_days > 365 {
+        return Err(TradingError::validation_error(
+            "horizon_days".to_string(),
+            "VaR horizon must be between 1 and 365 days".to_string(),
+            Some(horizon_days.to_string()),
+        ));
+    }
+    Ok(())
+}
+
+/// Kupiec POF likelihood ratio and its chi-squared(1) p-value
+pub fn kupiec_pof(exceptions: usize, observations: usize, confidence: f64) -> (f64, f64) {
+    if observations == 0 {
+        return (0.0, 1.0);
+    }
+    let n = observations as f64;
+    let x = exceptions as f64;
+    let p = 1.0 - confidence;
+    let observed = x / n;
+
+    // x ln(x) -> 0 as x -> 0
+    let xlogy = |x: f64, y: f64| if x == 0.0 { 0.0 } else { x * y.ln() };
+    let null = xlogy(n - x, 1.0 - p) + xlogy(x, p);
+    let alternative = xlogy(n - x, 1.0 - observed) + xlogy(x, observed);
+    let lr = (-2.0 * (null - alternative)).max(0.0);
+
+    (lr, erfc((lr / 2.0).sqrt()))
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn quadratic_form(weights: &[f64], matrix: &[Vec<f64>]) -> f64 {
+    weights.iter().enumerate()
+        .map(|(i, wi)| weights.iter().enumerate().map(|(j, wj)| wi * matrix[i][j] * wj).sum::<f64>())
+        .sum::<f64>()
+        .max(0.0)
+}
+
+/// Column means and sample covariance matrix
+fn moments(rows: &[Vec<f64>]) -> (Vec<f64>, Vec<Vec<f64>>) {
+    let k = rows.first().map(|r| r.len()).unwrap_or(0);
+    let n = rows.len() as f64;
+    let mut means = vec![0.0; k];
+    for row in rows {
+        for (mean, value) in means.iter_mut().zip(row) {
+            *mean += value / n;
+        }
+    }
+
+    let mut covariance = vec![vec![0.0; k]; k];
+    for row in rows {
+        for (i, covariance_row) in covariance.iter_mut().enumerate() {
+            for (j, cell) in covariance_row.iter_mut().enumerate() {
+                *cell += (row[i] - means[i]) * (row[j] - means[j]) / (n - 1.0).max(1.0);
+            }
+        }
+    }
+    (means, covariance)
+}
+
+/// Lower-triangular Cholesky factor; a small ridge is added for positive semi-definite input
+fn cholesky(matrix: &[Vec<f64>]) -> Option<Vec<Vec<f64>>> {
+    let k = matrix.len();
+    let ridge = (0..k).map(|i| matrix[i][i]).fold(0.0, f64::max) * 1e-10;
+    let mut lower = vec![vec![0.0; k]; k];
+    for i in 0..k {
+        for j in 0..=i {
+            let sum: f64 = (0..j).map(|m| lower[i][m] * lower[j][m]).sum();
+            if i == j {
+                let diagonal = matrix[i][i] + ridge - sum;
+                if diagonal < 0.0 {
+                    return None;
+                }
+                lower[i][j] = diagonal.sqrt();
+            } else if lower[j][j] > 0.0 {
+                lower[i][j] = (matrix[i][j] - sum) / lower[j][j];
+            }
+        }
+    }
+    Some(lower)
+}
+
+/// VaR as the `confidence` quantile of losses and ES as the mean of losses at or beyond it
+fn empirical_var_es(mut losses: Vec<f64>, confidence: f64) -> (f64, f64) {
+    losses.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let index = ((confidence * losses.len() as f64).ceil() as usize).clamp(1, losses.len()) - 1;
+    let tail = &losses[index..];
+    (losses[index], tail.iter().sum::<f64>() / tail.len() as f64)
+}
+
+fn normal_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Acklam's rational approximation of the standard normal quantile
+fn inverse_normal_cdf(p: f64) -> f64 {
+    const A: [f64; 6] = [-3.969683028665376e+01, 2.209460984245205e+02, -2.759285104469687e+02, 1.38357751867269e+02, -3.066479806614716e+01, 2.506628277459239e+00];
+    const B: [f64; 5] = [-5.447609879822406e+01, 1.615858368580409e+02, -1.556989798598866e+02, 6.680131188771972e+01, -1.328068155288572e+01];
+    const C: [f64; 6] = [-7.784894002430293e-03, -3.223964580411365e-01, -2.400758277161838e+00, -2.549732539343734e+00, 4.374664141464968e+00, 2.938163982698783e+00];
+    const D: [f64; 4] = [7.784695709041462e-03, 3.224671290700398e-01, 2.445134137142996e+00, 3.754408661907416e+00];
+    const P_LOW: f64 = 0.02425;
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+
+    if p < P_LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p <= 1.0 - P_LOW {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    } else {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    }
+}
+
+/// Complementary error function (Numerical Recipes `erfcc`, relative error < 1.2e-7)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.26551223 + t * (1.00002368 + t * (0.37409196 + t * (0.09678418
+        + t * (-0.18628806 + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587
+        + t * (-0.82215223 + t * 0.17087277))))))))).exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Deterministic generator so Monte Carlo VaR is reproducible between calls
+struct SplitMix64 {
+    state: u64,
+    spare: Option<f64>,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        Self { state: seed, spare: None }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in (0, 1)
+    fn next_uniform(&mut self) -> f64 {
+        ((self.next_u64() >> 11) as f64 + 0.5) / (1u64 << 53) as f64
+    }
+
+    /// Standard normal via Box-Muller
+    fn next_normal(&mut self) -> f64 {
+        if let Some(spare) = self.spare.take() {
+            return spare;
+        }
+        let radius = (-2.0 * self.next_uniform().ln()).sqrt();
+        let angle = 2.0 * std::f64::consts::PI * self.next_uniform();
+        self.spare = Some(radius * angle.sin());
+        radius * angle.cos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    /// Two correlated daily return streams with ~2% and ~3% volatility
+    fn sample_matrix(days: usize) -> ReturnMatrix {
+        let mut rng = SplitMix64::new(42);
+        let start = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let mut prices: HashMap<String, Vec<PriceData>> = HashMap::new();
+        let (mut btc, mut eth) = (30000.0, 2000.0);
+        for day in 0..=days {
+            if day > 0 {
+                let common = rng.next_normal();
+                btc *= 1.0 + 0.02 * common;
+                eth *= 1.0 + 0.03 * (0.8 * common + 0.6 * rng.next_normal());
+            }
+            for (symbol, close) in [("BTCUSDT", btc), ("ETHUSDT", eth)] {
+                let close = Decimal::from_f64(close).unwrap();
+                prices.entry(symbol.to_string()).or_default().push(PriceData {
+                    timestamp: start + Duration::days(day as i64),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: Decimal::ONE,
+                });
+            }
+        }
+        ReturnMatrix::from_prices(&prices, 0, 10_000)
+    }
+
+    fn positions() -> Vec<VarPosition> {
+        vec![
+            VarPosition { symbol: "BTCUSDT".to_string(), exposure: 10_000.0 },
+            VarPosition { symbol: "ETHUSDT".to_string(), exposure: 5_000.0 },
+        ]
+    }
+
+    #[test]
+    fn test_methods_agree_on_normal_returns() {
+        let returns = sample_matrix(500);
+        assert_eq!(returns.observations(), 500);
+
+        let calculator = VarCalculator { historical_window_days: 500, ..Default::default() };
+        let report = calculator.calculate(&positions(), &returns, 1).unwrap();
+        assert!(report.missing_history.is_empty());
+
+        let parametric = report.estimate(VarMethod::Parametric, 0.99).unwrap().var;
+        for method in [VarMethod::Historical, VarMethod::MonteCarlo] {
+            let var = report.estimate(method, 0.99).unwrap().var;
+            assert!((var - parametric).abs() / parametric < 0.2, "{:?} VaR {} vs parametric {}", method, var, parametric);
+        }
+        for estimate in &report.estimates {
+            assert!(estimate.expected_shortfall >= estimate.var);
+        }
+
+        // Ten-day VaR scales with the square root of time
+        let ten_day = calculator.calculate(&positions(), &returns, 10).unwrap();
+        let ratio = ten_day.estimate(VarMethod::Historical, 0.95).unwrap().var
+            / report.estimate(VarMethod::Historical, 0.95).unwrap().var;
+        assert!((ratio - 10f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_component_var_sums_to_portfolio_var() {
+        let returns = sample_matrix(300);
+        let calculator = VarCalculator::default();
+        let report = calculator.calculate(&positions(), &returns, 1).unwrap();
+
+        let total: f64 = report.positions.iter().map(|p| p.component_var).sum();
+        let (means, _) = moments(&calculator.select_rows(&returns, &returns.symbols));
+        let mean = dot(&[10_000.0, 5_000.0], &means);
+        let parametric = report.estimate(VarMethod::Parametric, 0.95).unwrap().var;
+        assert!((total - (parametric + mean)).abs() < 1e-6);
+
+        let percent: f64 = report.positions.iter().map(|p| p.contribution_percent).sum();
+        assert!((percent - 100.0).abs() < 1e-6);
+        // Diversification: sum of standalone VaR exceeds the portfolio VaR
+        assert!(report.positions.iter().map(|p| p.standalone_var).sum::<f64>() > total);
+    }
+
+    #[test]
+    fn test_missing_history_is_reported() {
+        let returns = sample_matrix(100);
+        let mut book = positions();
+        book.push(VarPosition { symbol: "solusdt".to_string(), exposure: 1_000.0 });
+
+        let report = VarCalculator::default().calculate(&book, &returns, 1).unwrap();
+        assert_eq!(report.missing_history, vec!["SOLUSDT".to_string()]);
+        assert_eq!(report.positions.len(), 2);
+        assert!(VarCalculator::default().calculate(&book, &returns, 0).is_err());
+    }
+
+    #[test]
+    fn test_short_history_does_not_empty_the_matrix() {
+        let returns = sample_matrix(100);
+        let mut prices: HashMap<String, Vec<PriceData>> = HashMap::new();
+        for (i, symbol) in returns.symbols.iter().enumerate() {
+            let mut close = 100.0;
+            let history = prices.entry(symbol.clone()).or_default();
+            for (t, row) in returns.returns.iter().enumerate() {
+                close *= 1.0 + row[i];
+                let close = Decimal::from_f64(close).unwrap();
+                history.push(PriceData { timestamp: returns.timestamps[t], open: close, high: close, low: close, close, volume: Decimal::ONE });
+            }
+        }
+        let last = returns.timestamps[returns.observations() - 1];
+        prices.insert("SOLUSDT".to_string(), (0..5).map(|day| PriceData {
+            timestamp: last - Duration::days(day),
+            open: Decimal::ONE_HUNDRED,
+            high: Decimal::ONE_HUNDRED,
+            low: Decimal::ONE_HUNDRED,
+            close: Decimal::ONE_HUNDRED,
+            volume: Decimal::ONE,
+        }).rev().collect());
+
+        let matrix = ReturnMatrix::from_prices(&prices, MIN_VAR_OBSERVATIONS, usize::MAX);
+        assert_eq!(matrix.symbols, vec!["BTCUSDT".to_string(), "ETHUSDT".to_string()]);
+        assert_eq!(matrix.observations(), 99);
+
+        let mut book = positions();
+        book.push(VarPosition { symbol: "SOLUSDT".to_string(), exposure: 1_000.0 });
+        let report = VarCalculator::default().calculate(&book, &matrix, 1).unwrap();
+        assert_eq!(report.missing_history, vec!["SOLUSDT".to_string()]);
+        assert!(report.estimate(VarMethod::Historical, 0.95).is_some());
+        // Without the length filter the short series leaves too few common rows for any estimate
+        assert!(ReturnMatrix::from_prices(&prices, 0, usize::MAX).observations() < MIN_VAR_OBSERVATIONS);
+    }
+
+    #[test]
+    fn test_kupiec_backtest() {
+        // Exactly the expected exception count gives LR = 0
+        let (lr, p_value) = kupiec_pof(5, 500, 0.99);
+        assert!(lr.abs() < 1e-9 && (p_value - 1.0).abs() < 1e-6);
+        // Far too many exceptions is rejected
+        let (_, p_value) = kupiec_pof(25, 500, 0.99);
+        assert!(p_value < 0.001);
+
+        let returns = sample_matrix(600);
+        let backtest = VarCalculator::default()
+            .backtest(&positions(), &returns, VarMethod::Parametric, 0.95, 250)
+            .unwrap();
+        assert_eq!(backtest.observations, 350);
+        assert!(backtest.calibrated, "{:?}", backtest);
+    }
+
+    #[test]
+    fn test_inverse_normal_cdf() {
+        assert!((inverse_normal_cdf(0.95) - 1.644853627).abs() < 1e-6);
+        assert!((inverse_normal_cdf(0.99) - 2.326347874).abs() < 1e-6);
+        assert!((inverse_normal_cdf(0.001) + 3.090232306).abs() < 1e-6);
+        assert!((erfc(1.0) - 0.157299207).abs() < 1e-6);
+    }
+}
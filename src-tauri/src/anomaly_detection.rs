@@ -0,0 +1,522 @@
+// Market Data Anomaly Detection
+// Robust statistics over the live feed: rolling MAD z-scores of bar returns, volume surges,
+// spread blow-outs, stale feeds and stream/REST price divergence, each classified into the
+// bot's pause reasons with a confidence and kept for review
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::TradingResult;
+use crate::models::{OrderBookDepth, PriceData};
+use crate::persistence;
+use crate::trading_strategy::PauseReason;
+
+/// Turns a median absolute deviation into the standard deviation of a normal distribution
+const MAD_SCALE: f64 = 1.4826;
+/// Scale floors so a perfectly flat window does not make every change infinitely unusual
+const RETURN_SCALE_FLOOR: f64 = 1e-4;
+const LOG_VOLUME_SCALE_FLOOR: f64 = 0.05;
+const SPREAD_SCALE_FLOOR_BPS: f64 = 0.5;
+/// A volume surge alone is weak evidence, so its confidence is scaled down
+const VOLUME_SURGE_WEIGHT: f64 = 0.8;
+/// Anomaly records kept on disk
+const MAX_RECORDS: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AnomalyConfig {
+    pub enabled: bool,
+    /// Observations in each rolling window
+    pub window: usize,
+    /// Observations required before z-scores are trusted
+    pub min_samples: usize,
+    /// Robust z-score of a bar's log return that counts as an outlier
+    pub return_z_threshold: f64,
+    /// Outlier moves at least this large (percent) are flash crashes rather than volatility
+    pub flash_crash_percent: f64,
+    /// Robust z-score of log volume that counts as a surge
+    pub volume_z_threshold: f64,
+    /// Robust z-score of the bid-ask spread that counts as a blow-out
+    pub spread_z_threshold: f64,
+    /// Spreads narrower than this (basis points) are never a blow-out
+    pub min_blowout_spread_bps: f64,
+    /// Seconds without a bar before the feed counts as stale
+    pub stale_after_seconds: i64,
+    /// Divergence between the stream and a REST price (basis points) that flags the feed
+    pub max_divergence_bps: f64,
+    /// REST prices further than this from the last bar are not compared
+    pub reference_max_age_seconds: i64,
+    /// Seconds between background REST checks of the bot's price feed
+    pub feed_check_interval_seconds: u64,
+    /// Minimum confidence before an anomaly pauses the bot
+    pub pause_confidence: f64,
+    /// Minutes without a related anomaly before an anomaly pause may auto-resume
+    pub quiet_minutes: i64,
+}
+
+impl Default for AnomalyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            window: 100,
+            min_samples: 20,
+            return_z_threshold: 6.0,
+            flash_crash_percent: 5.0,
+            volume_z_threshold: 6.0,
+            spread_z_threshold: 8.0,
+            min_blowout_spread_bps: 10.0,
+            stale_after_seconds: 300, // Same 5 minutes the bot uses for stale data
+            max_divergence_bps: 50.0,
+            reference_max_age_seconds: 60,
+            feed_check_interval_seconds: 60,
+            pause_confidence: 0.7,
+            quiet_minutes: 10,
+        }
+    }
+}
+
+impl AnomalyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_samples < 5 || self.window < self.min_samples {
+            return Err("Anomaly detection needs at least 5 samples and a window no smaller than min_samples".to_string());
+        }
+        if self.return_z_threshold <= 0.0 || self.volume_z_threshold <= 0.0 || self.spread_z_threshold <= 0.0 {
+            return Err("Anomaly z-score thresholds must be positive".to_string());
+        }
+        if self.flash_crash_percent <= 0.0 || self.min_blowout_spread_bps < 0.0 || self.max_divergence_bps <= 0.0 {
+            return Err("Flash crash, spread and divergence limits must be positive".to_string());
+        }
+        if self.stale_after_seconds <= 0 || self.reference_max_age_seconds <= 0 || self.feed_check_interval_seconds == 0 || self.quiet_minutes < 0 {
+            return Err("Anomaly time limits must be positive".to_string());
+        }
+        if !(0.5..=1.0).contains(&self.pause_confidence) {
+            return Err("Anomaly pause confidence must be between 0.5 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AnomalyKind {
+    ReturnOutlier,
+    FlashCrash,
+    VolumeSurge,
+    SpreadBlowout,
+    StaleFeed,
+    PriceDivergence,
+}
+
+impl AnomalyKind {
+    /// Kinds whose recurrence keeps a pause caused by this kind from resuming
+    fn related(&self) -> &'static [AnomalyKind] {
+        match self {
+            AnomalyKind::ReturnOutlier | AnomalyKind::FlashCrash | AnomalyKind::VolumeSurge => {
+                &[AnomalyKind::ReturnOutlier, AnomalyKind::FlashCrash, AnomalyKind::VolumeSurge]
+            }
+            AnomalyKind::SpreadBlowout => &[AnomalyKind::SpreadBlowout],
+            AnomalyKind::StaleFeed => &[AnomalyKind::StaleFeed],
+            AnomalyKind::PriceDivergence => &[AnomalyKind::PriceDivergence],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Anomaly {
+    pub id: String,
+    pub kind: AnomalyKind,
+    pub detected_at: DateTime<Utc>,
+    /// Timestamp of the bar, book or price that triggered it
+    pub observed_at: DateTime<Utc>,
+    pub value: f64,
+    /// Rolling median (or fixed limit) the value was compared against
+    pub baseline: f64,
+    /// Robust z-score, or the multiple of the limit for stale feeds and divergence
+    pub score: f64,
+    /// 0.5 at the threshold, approaching 1 as the score grows
+    pub confidence: f64,
+    pub pause_reason: PauseReason,
+    pub description: String,
+    /// Whether the anomaly paused the bot
+    pub paused: bool,
+}
+
+/// Rolling feed statistics and the record of every anomaly found
+#[derive(Debug, Clone, Default)]
+pub struct AnomalyDetector {
+    returns: VecDeque<f64>,
+    log_volumes: VecDeque<f64>,
+    spreads_bps: VecDeque<f64>,
+    /// Timestamp and close of the last bar
+    last_bar: Option<(DateTime<Utc>, f64)>,
+    stale_reported: bool,
+    records: VecDeque<Anomaly>,
+    path: Option<PathBuf>,
+}
+
+impl AnomalyDetector {
+    /// Detector whose records are never written to disk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("anomalies.json")
+    }
+
+    /// Load past anomaly records from `path`; new ones are saved back
+    pub fn open(path: PathBuf) -> TradingResult<Self> {
+        let records: Vec<Anomaly> = persistence::read_json(&path)?.unwrap_or_default();
+        Ok(Self {
+            records: records.into_iter().collect(),
+            path: Some(path),
+            ..Self::default()
+        })
+    }
+
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        match self.path.as_ref() {
+            Some(path) => persistence::write_json(path, &self.records),
+            None => Ok(()),
+        }
+    }
+
+    /// Anomaly records, newest first
+    pub fn records(&self, limit: usize) -> Vec<Anomaly> {
+        self.records.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Score a new bar's return, volume and the gap since the previous bar
+    pub fn observe_bar(&mut self, config: &AnomalyConfig, bar: &PriceData) -> Vec<Anomaly> {
+        let close = bar.close.to_f64().unwrap_or(0.0);
+        let volume = bar.volume.to_f64().unwrap_or(0.0);
+        let mut found = Vec::new();
+
+        let mut bar_return = None;
+        if let Some((last_at, last_close)) = self.last_bar {
+            let gap = (bar.timestamp - last_at).num_seconds();
+            if config.enabled && gap > config.stale_after_seconds && !self.stale_reported {
+                found.push(self.stale_anomaly(config, gap, bar.timestamp));
+            }
+            if last_close > 0.0 && close > 0.0 {
+                bar_return = Some((close / last_close).ln());
+            }
+        }
+
+        let return_stats = robust_stats(&self.returns, config.min_samples, RETURN_SCALE_FLOOR);
+        let return_threshold = return_stats.map(|(median, scale)| median.abs() + config.return_z_threshold * scale);
+        if let (Some(ret), Some((median, scale)), true) = (bar_return, return_stats, config.enabled) {
+            let z = (ret - median) / scale;
+            if z.abs() >= config.return_z_threshold {
+                let move_percent = ret.exp_m1() * 100.0;
+                let (kind, pause_reason) = if move_percent.abs() >= config.flash_crash_percent {
+                    (AnomalyKind::FlashCrash, PauseReason::FlashCrash { movement_percent: move_percent.abs() })
+                } else {
+                    (AnomalyKind::ReturnOutlier, PauseReason::HighVolatility {
+                        volatility: ret.abs(),
+                        threshold: return_threshold.unwrap_or(ret.abs()),
+                    })
+                };
+                found.push(anomaly(kind, bar.timestamp, ret, median, z.abs(), confidence(z.abs(), config.return_z_threshold), pause_reason,
+                    format!("{:+.2}% bar move is {:.1} robust deviations from the median return", move_percent, z.abs())));
+            }
+        }
+
+        if volume > 0.0 {
+            let log_volume = volume.ln();
+            if let (Some((median, scale)), true) = (robust_stats(&self.log_volumes, config.min_samples, LOG_VOLUME_SCALE_FLOOR), config.enabled) {
+                let z = (log_volume - median) / scale;
+                if z >= config.volume_z_threshold {
+                    let volatility = bar_return.map(f64::abs).unwrap_or(0.0);
+                    let pause_reason = PauseReason::HighVolatility {
+                        volatility,
+                        threshold: return_threshold.unwrap_or(volatility),
+                    };
+                    found.push(anomaly(AnomalyKind::VolumeSurge, bar.timestamp, volume, median.exp(), z,
+                        confidence(z, config.volume_z_threshold) * VOLUME_SURGE_WEIGHT, pause_reason,
+                        format!("Volume {:.0} is {:.1}x the median ({:.1} robust deviations)", volume, (log_volume - median).exp(), z)));
+                }
+            }
+            push_capped(&mut self.log_volumes, log_volume, config.window);
+        }
+
+        if let Some(ret) = bar_return {
+            push_capped(&mut self.returns, ret, config.window);
+        }
+        self.last_bar = Some((bar.timestamp, close));
+        self.stale_reported = false;
+        self.record(&found);
+        found
+    }
+
+    /// Score the bid-ask spread of an order book snapshot
+    pub fn observe_order_book(&mut self, config: &AnomalyConfig, book: &OrderBookDepth) -> Vec<Anomaly> {
+        let (bid, ask) = match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) => (bid.price.to_f64().unwrap_or(0.0), ask.price.to_f64().unwrap_or(0.0)),
+            _ => return Vec::new(),
+        };
+        let mid = (bid + ask) / 2.0;
+        if mid <= 0.0 {
+            return Vec::new();
+        }
+        let spread_bps = (ask - bid) / mid * 10_000.0;
+
+        let mut found = Vec::new();
+        if let (Some((median, scale)), true) = (robust_stats(&self.spreads_bps, config.min_samples, SPREAD_SCALE_FLOOR_BPS), config.enabled) {
+            let z = (spread_bps - median) / scale;
+            if z >= config.spread_z_threshold && spread_bps >= config.min_blowout_spread_bps {
+                let description = format!("Spread {:.1} bps against a median of {:.1} bps ({:.1} robust deviations)", spread_bps, median, z);
+                found.push(anomaly(AnomalyKind::SpreadBlowout, book.timestamp, spread_bps, median, z, confidence(z, config.spread_z_threshold),
+                    PauseReason::DataQuality { issue: format!("Spread blow-out: {}", description) }, description));
+            }
+        }
+        push_capped(&mut self.spreads_bps, spread_bps, config.window);
+        self.record(&found);
+        found
+    }
+
+    /// Cross-check the last streamed close against a price fetched over REST
+    pub fn observe_reference_price(&mut self, config: &AnomalyConfig, price: f64, at: DateTime<Utc>) -> Vec<Anomaly> {
+        let (bar_at, close) = match self.last_bar {
+            Some(last_bar) => last_bar,
+            None => return Vec::new(),
+        };
+        if !config.enabled || price <= 0.0 || (at - bar_at).num_seconds().abs() > config.reference_max_age_seconds {
+            return Vec::new();
+        }
+
+        let divergence_bps = (close - price).abs() / price * 10_000.0;
+        let mut found = Vec::new();
+        if divergence_bps > config.max_divergence_bps {
+            let score = divergence_bps / config.max_divergence_bps;
+            let description = format!("Stream close {:.4} differs from REST price {:.4} by {:.1} bps", close, price, divergence_bps);
+            found.push(anomaly(AnomalyKind::PriceDivergence, at, divergence_bps, config.max_divergence_bps, score, confidence(score, 1.0),
+                PauseReason::DataQuality { issue: description.clone() }, description));
+        }
+        self.record(&found);
+        found
+    }
+
+    /// Flag the feed once if no bar has arrived within the stale limit
+    pub fn check_stale(&mut self, config: &AnomalyConfig, now: DateTime<Utc>) -> Vec<Anomaly> {
+        let gap = match self.last_bar {
+            Some((last_at, _)) => (now - last_at).num_seconds(),
+            None => return Vec::new(),
+        };
+        if !config.enabled || self.stale_reported || gap <= config.stale_after_seconds {
+            return Vec::new();
+        }
+        let found = vec![self.stale_anomaly(config, gap, now)];
+        self.record(&found);
+        found
+    }
+
+    /// Whether the last `bars` returns are all within the outlier threshold; `None` until the
+    /// window holds enough returns to judge
+    pub fn recent_returns_consistent(&self, config: &AnomalyConfig, bars: usize) -> Option<bool> {
+        if self.returns.len() < config.min_samples + bars {
+            return None;
+        }
+        let history = self.returns.iter().take(self.returns.len() - bars).copied().collect::<VecDeque<_>>();
+        let (median, scale) = robust_stats(&history, config.min_samples, RETURN_SCALE_FLOOR)?;
+        Some(self.returns.iter().rev().take(bars).all(|ret| ((ret - median) / scale).abs() < config.return_z_threshold))
+    }
+
+    /// Whether a pause caused by `kind` may resume: no related anomaly within the quiet period
+    pub fn is_clear(&self, config: &AnomalyConfig, kind: AnomalyKind, now: DateTime<Utc>) -> bool {
+        let since = now - Duration::minutes(config.quiet_minutes);
+        let related = kind.related();
+        !self.records.iter().rev()
+            .take_while(|record| record.detected_at > since)
+            .any(|record| related.contains(&record.kind))
+    }
+
+    /// Mark an anomaly as the one that paused the bot
+    pub fn mark_paused(&mut self, id: &str) {
+        if let Some(record) = self.records.iter_mut().rev().find(|record| record.id == id) {
+            record.paused = true;
+        }
+    }
+
+    fn stale_anomaly(&mut self, config: &AnomalyConfig, gap_seconds: i64, at: DateTime<Utc>) -> Anomaly {
+        self.stale_reported = true;
+        let limit = config.stale_after_seconds as f64;
+        let score = gap_seconds as f64 / limit;
+        let description = format!("No market data for {}s (limit {}s)", gap_seconds, config.stale_after_seconds);
+        anomaly(AnomalyKind::StaleFeed, at, gap_seconds as f64, limit, score, confidence(score, 1.0),
+            PauseReason::ConnectionIssue { reason: description.clone() }, description)
+    }
+
+    fn record(&mut self, found: &[Anomaly]) {
+        self.records.extend(found.iter().cloned());
+        while self.records.len() > MAX_RECORDS {
+            self.records.pop_front();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn anomaly(kind: AnomalyKind, observed_at: DateTime<Utc>, value: f64, baseline: f64, score: f64, confidence: f64, pause_reason: PauseReason, description: String) -> Anomaly {
+    Anomaly {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind,
+        detected_at: Utc::now(),
+        observed_at,
+        value,
+        baseline,
+        score,
+        confidence,
+        pause_reason,
+        description,
+        paused: false,
+    }
+}
+
+/// 0.5 at the threshold, 0.75 at twice it, approaching 1
+fn confidence(score: f64, threshold: f64) -> f64 {
+    if score < threshold || score <= 0.0 {
+        return 0.0;
+    }
+    (1.0 - 0.5 * threshold / score).clamp(0.0, 1.0)
+}
+
+/// Median and MAD-based scale of the window, or `None` with too few samples
+fn robust_stats(values: &VecDeque<f64>, min_samples: usize, scale_floor: f64) -> Option<(f64, f64)> {
+    if values.len() < min_samples.max(1) {
+        return None;
+    }
+    let median = median(values.iter().copied().collect());
+    let mad = median_abs_deviation(values, median);
+    Some((median, (MAD_SCALE * mad).max(scale_floor)))
+}
+
+fn median_abs_deviation(values: &VecDeque<f64>, center: f64) -> f64 {
+    median(values.iter().map(|v| (v - center).abs()).collect())
+}
+
+fn median(mut values: Vec<f64>) -> f64 {
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+fn push_capped(window: &mut VecDeque<f64>, value: f64, capacity: usize) {
+    window.push_back(value);
+    while window.len() > capacity.max(1) {
+        window.pop_front();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn bar(minute: i64, close: f64, volume: f64) -> PriceData {
+        let close = Decimal::from_f64(close).unwrap();
+        PriceData {
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minute),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: Decimal::from_f64(volume).unwrap(),
+        }
+    }
+
+    /// Prices moving up to 0.1% a bar and slightly varying volumes
+    fn warmed_up(detector: &mut AnomalyDetector, config: &AnomalyConfig, bars: i64) -> f64 {
+        let mut close = 100.0;
+        for minute in 0..bars {
+            close *= 1.0 + 0.001 * (minute as f64 * 1.7).sin();
+            let volume = 1000.0 + (minute % 5) as f64 * 50.0;
+            assert!(detector.observe_bar(config, &bar(minute, close, volume)).is_empty());
+        }
+        close
+    }
+
+    #[test]
+    fn test_return_outliers_are_classified() {
+        let config = AnomalyConfig::default();
+        let mut detector = AnomalyDetector::new();
+        let close = warmed_up(&mut detector, &config, 40);
+        assert_eq!(detector.recent_returns_consistent(&config, 5), Some(true));
+
+        // 1.5% is far outside the noise but below the flash crash size
+        let found = detector.observe_bar(&config, &bar(40, close * 1.015, 1100.0));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, AnomalyKind::ReturnOutlier);
+        assert!(matches!(found[0].pause_reason, PauseReason::HighVolatility { .. }));
+        assert!(found[0].confidence > config.pause_confidence);
+        assert_eq!(detector.recent_returns_consistent(&config, 5), Some(false));
+
+        let found = detector.observe_bar(&config, &bar(41, close * 0.9, 1100.0));
+        assert_eq!(found[0].kind, AnomalyKind::FlashCrash);
+        assert!(matches!(found[0].pause_reason, PauseReason::FlashCrash { movement_percent } if movement_percent > 9.0));
+        assert!(!detector.is_clear(&config, AnomalyKind::ReturnOutlier, Utc::now()));
+        assert!(detector.is_clear(&config, AnomalyKind::StaleFeed, Utc::now()));
+        assert!(detector.is_clear(&config, AnomalyKind::ReturnOutlier, Utc::now() + Duration::minutes(11)));
+    }
+
+    #[test]
+    fn test_volume_surge_and_stale_feed() {
+        let config = AnomalyConfig::default();
+        let mut detector = AnomalyDetector::new();
+        let close = warmed_up(&mut detector, &config, 40);
+
+        let found = detector.observe_bar(&config, &bar(40, close, 50_000.0));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, AnomalyKind::VolumeSurge);
+        assert!(found[0].confidence <= VOLUME_SURGE_WEIGHT);
+
+        // A 10 minute gap is reported once, when checked and not again on the next bar
+        let now = bar(51, close, 1000.0).timestamp;
+        let stale = detector.check_stale(&config, now);
+        assert_eq!(stale.len(), 1);
+        assert!(matches!(stale[0].pause_reason, PauseReason::ConnectionIssue { .. }));
+        assert!(detector.check_stale(&config, now).is_empty());
+        assert!(detector.observe_bar(&config, &bar(51, close, 1000.0)).is_empty());
+        assert_eq!(detector.records(10).len(), 2);
+    }
+
+    #[test]
+    fn test_spread_blowout_and_rest_divergence() {
+        use crate::models::OrderBookLevel;
+        let config = AnomalyConfig::default();
+        let mut detector = AnomalyDetector::new();
+        let book = |bid: f64, ask: f64| OrderBookDepth {
+            symbol: "BTCUSDT".to_string(),
+            last_update_id: 1,
+            timestamp: Utc::now(),
+            bids: vec![OrderBookLevel { price: Decimal::from_f64(bid).unwrap(), quantity: Decimal::ONE }],
+            asks: vec![OrderBookLevel { price: Decimal::from_f64(ask).unwrap(), quantity: Decimal::ONE }],
+        };
+        for i in 0..30 {
+            let half_spread = 0.01 + (i % 3) as f64 * 0.002;
+            assert!(detector.observe_order_book(&config, &book(100.0 - half_spread, 100.0 + half_spread)).is_empty());
+        }
+        let found = detector.observe_order_book(&config, &book(99.0, 101.0));
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].kind, AnomalyKind::SpreadBlowout);
+
+        let close = warmed_up(&mut detector, &config, 3);
+        let at = bar(2, close, 1000.0).timestamp;
+        assert!(detector.observe_reference_price(&config, close * 1.001, at).is_empty());
+        let found = detector.observe_reference_price(&config, close * 1.02, at);
+        assert_eq!(found[0].kind, AnomalyKind::PriceDivergence);
+        assert!(matches!(found[0].pause_reason, PauseReason::DataQuality { .. }));
+        // Too far from the last bar to compare
+        assert!(detector.observe_reference_price(&config, close * 1.02, at + Duration::minutes(5)).is_empty());
+    }
+}
@@ -183,7 +183,7 @@ impl BotAuthMiddleware {
             "start_bot" | "stop_bot" => "bot:control",
             "configure_bot" => "bot:configure", 
             "view_bot_status" => "bot:view",
-            "emergency_stop" => "bot:emergency",
+            "emergency_stop" | "kill_switch" => "bot:emergency",
             _ => return Err(TradingError::auth_error(
                 AuthErrorType::PermissionDenied,
                 format!("Unknown bot operation: {}", operation)
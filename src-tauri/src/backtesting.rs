@@ -2,22 +2,42 @@
 // AGENT-TRADER-PRO Phase 2 Advanced Analytics
 
 use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
 use chrono::{DateTime, Utc, Duration};
 use rust_decimal::Decimal;
 use rust_decimal::prelude::*;
 use serde::{Serialize, Deserialize};
 use crate::models::{PriceData, Trade, TradeSide};
-use crate::enhanced_lro::{EnhancedLRO, LROConfig, LROSignal};
+use crate::enhanced_lro::{LROConfig, LROSignal};
+use crate::lro_engine::{LroEngine, LroEngineKind, RangeNormalizedLRO};
 use crate::errors::{TradingError, TradingResult};
 use crate::logging::LogCategory;
+use crate::multi_timeframe::{HigherTimeframeConfirmation, MultiTimeframeSeries};
+use crate::regime_detection::{Regime, RegimeClassifier, RegimeSwitching};
+use crate::divergence::{DivergenceDetector, DivergenceFilter};
+use crate::advanced_risk_manager::{KellyPositionSizer, MAETracker};
+use crate::position_sizing::{PositionSizing, SizingDecision, SizingInputs};
+use crate::stop_manager::{FixedStop, StopManager, StopManagerConfig, StopState};
+use crate::divergence::Divergence;
+use crate::signal_fusion::{self, fuse, ComponentReading, FusionInputs, FusionModel, FusionSample, SignalFusionConfig};
+use crate::model_inference::{klines_from_bars, ModelSignalFilter, OnnxModel};
+use crate::market_intelligence::fear_greed::{FearGreedFilter, FearGreedHistory};
+use crate::log_warning;
 
 /// Comprehensive backtesting configuration
+///
+/// Positions are sized and capped as in the live bot. The GPU risk assessment, the
+/// account drawdown governor, portfolio heat limits and risk limit profiles depend on
+/// live account state and are applied by the bot only.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestConfig {
     pub initial_balance: Decimal,
     pub commission_rate: f64,          // 0.001 = 0.1%
     pub slippage_rate: f64,           // 0.0005 = 0.05%
-    pub max_position_size: f64,       // As percentage of balance
+    #[serde(default = "default_max_position_notional")]
+    pub max_position_notional: f64,   // Largest position notional in quote currency, as in the bot config
+    #[serde(default, skip_serializing)]
+    pub max_position_size: Option<f64>, // Legacy cap as a fraction of balance, converted by `migrate_legacy_fields`
     pub risk_free_rate: f64,          // For Sharpe ratio calculation
     pub benchmark_symbol: Option<String>, // For comparison
     pub start_date: DateTime<Utc>,
@@ -25,6 +45,42 @@ pub struct BacktestConfig {
     pub rebalance_frequency: Duration, // How often to rebalance
     pub walk_forward_enabled: bool,
     pub out_of_sample_period: Duration, // For walk-forward analysis
+    #[serde(default = "default_base_timeframe")]
+    pub base_timeframe: String,        // Timeframe of the historical data fed to the engine
+    #[serde(default)]
+    pub higher_timeframe_confirmation: HigherTimeframeConfirmation,
+    #[serde(default)]
+    pub regime_detection_enabled: bool, // Refit the regime classifier on each training window
+    #[serde(default = "default_regime_window")]
+    pub regime_window: usize,
+    #[serde(default)]
+    pub regime_switching: RegimeSwitching,
+    #[serde(default)]
+    pub lro_engine: LroEngineKind,
+    #[serde(default)]
+    pub divergence_filter: DivergenceFilter,
+    #[serde(default)]
+    pub position_sizing: PositionSizing,
+    #[serde(default)]
+    pub stop_management: Option<StopManagerConfig>, // None exits on signals only
+    #[serde(default)]
+    pub signal_fusion: SignalFusionConfig, // Gates entries on the fused score when enabled
+    #[serde(default)]
+    pub model_signal: ModelSignalFilter, // Requires a user-supplied model to agree with entries when enabled
+    #[serde(default)]
+    pub fear_greed_filter: FearGreedFilter, // Blocks entries at index extremes using the stored daily history
+}
+
+fn default_base_timeframe() -> String {
+    "1m".to_string()
+}
+
+fn default_regime_window() -> usize {
+    50
+}
+
+fn default_max_position_notional() -> f64 {
+    1000.0 // Same as the bot default; the equity fraction cap is in position_sizing
 }
 
 impl Default for BacktestConfig {
@@ -33,7 +89,8 @@ impl Default for BacktestConfig {
             initial_balance: Decimal::from(10000),
             commission_rate: 0.001,
             slippage_rate: 0.0005,
-            max_position_size: 0.95, // 95% max position
+            max_position_notional: default_max_position_notional(),
+            max_position_size: None,
             risk_free_rate: 0.02,    // 2% annual
             benchmark_symbol: Some("BTCUSDT".to_string()),
             start_date: Utc::now() - Duration::days(365),
@@ -41,6 +98,18 @@ impl Default for BacktestConfig {
             rebalance_frequency: Duration::hours(1),
             walk_forward_enabled: true,
             out_of_sample_period: Duration::days(30),
+            base_timeframe: default_base_timeframe(),
+            higher_timeframe_confirmation: HigherTimeframeConfirmation::default(),
+            regime_detection_enabled: false,
+            regime_window: default_regime_window(),
+            regime_switching: RegimeSwitching::default(),
+            divergence_filter: DivergenceFilter::default(),
+            lro_engine: LroEngineKind::default(),
+            position_sizing: PositionSizing::default(),
+            stop_management: None,
+            signal_fusion: SignalFusionConfig::default(),
+            model_signal: ModelSignalFilter::default(),
+            fear_greed_filter: FearGreedFilter::default(),
         }
     }
 }
@@ -63,6 +132,26 @@ pub struct BacktestTrade {
     pub max_favorable_excursion: Option<Decimal>, // MFE
     pub signal_strength: f64,
     pub market_conditions: String,
+    /// How the position size was chosen
+    #[serde(default)]
+    pub sizing: Option<SizingDecision>,
+    /// Signal fusion readings at entry, the training features for the fusion model
+    #[serde(default)]
+    pub signal_components: Vec<ComponentReading>,
+}
+
+impl BacktestTrade {
+    /// Fusion training sample from a closed trade with recorded readings
+    pub fn fusion_sample(&self) -> Option<FusionSample> {
+        if self.signal_components.is_empty() {
+            return None;
+        }
+        Some(FusionSample {
+            timestamp: self.entry_time,
+            components: self.signal_components.clone(),
+            won: self.pnl? > Decimal::ZERO,
+        })
+    }
 }
 
 /// Portfolio performance metrics
@@ -115,7 +204,26 @@ pub struct BacktestEngine {
     drawdown_periods: Vec<(DateTime<Utc>, DateTime<Utc>, f64)>,
     
     // Strategy components
-    enhanced_lro: Option<EnhancedLRO>,
+    lro_engine: Option<LroEngine>,
+    timeframe_series: MultiTimeframeSeries,
+    regime_classifier: Option<RegimeClassifier>,
+    regime_prices: VecDeque<PriceData>,
+    current_regime: Option<(Regime, f64)>,
+    applied_regime: Option<Regime>,
+    // Bot strategy config with the active regime's parameter set applied, None outside switching
+    regime_config: Option<crate::trading_strategy::LROConfig>,
+    divergence_prices: VecDeque<PriceData>,
+    sizing_prices: VecDeque<PriceData>,
+    fusion_prices: VecDeque<PriceData>,
+    fusion_model: Option<FusionModel>,
+    model_prices: VecDeque<PriceData>,
+    signal_model: Option<Arc<OnnxModel>>,
+    fear_greed_history: FearGreedHistory,
+    kelly_sizer: KellyPositionSizer,
+    stop_manager: Option<StopManager>,
+    position_stop: Option<StopState>,
+    position_take_profit: Option<Decimal>,
+    mae_tracker: MAETracker,
     benchmark_data: HashMap<DateTime<Utc>, Decimal>,
     
     // Performance tracking
@@ -129,13 +237,46 @@ pub struct BacktestEngine {
     out_of_sample_results: Vec<BacktestMetrics>,
 }
 
+impl BacktestConfig {
+    /// Convert a legacy `max_position_size` fraction of the initial balance into
+    /// `max_position_notional`. Values outside (0, 1] are not a fraction and are rejected
+    pub fn migrate_legacy_fields(&mut self) -> TradingResult<()> {
+        let fraction = match self.max_position_size {
+            Some(fraction) => fraction,
+            None => return Ok(()),
+        };
+        if !(fraction > 0.0 && fraction <= 1.0) {
+            return Err(TradingError::validation_error(
+                "max_position_size".to_string(),
+                "Legacy max position size is a fraction of balance between 0 and 1; set max_position_notional for a quote-currency cap".to_string(),
+                Some(fraction.to_string()),
+            ));
+        }
+        self.max_position_notional = self.initial_balance.to_f64().unwrap_or(0.0) * fraction;
+        self.max_position_size = None;
+        Ok(())
+    }
+}
+
 impl BacktestEngine {
-    pub fn new(config: BacktestConfig) -> Self {
+    pub fn new(mut config: BacktestConfig) -> Self {
+        if let Err(e) = config.migrate_legacy_fields() {
+            log_warning!(LogCategory::Configuration, "Ignoring legacy backtest position cap: {}", e);
+            config.max_position_size = None;
+        }
         let training_periods = if config.walk_forward_enabled {
             Self::generate_walk_forward_periods(&config)
         } else {
             vec![(config.start_date, config.end_date)]
         };
+        let fear_greed_history = if config.fear_greed_filter.enabled {
+            FearGreedHistory::open_default().unwrap_or_else(|e| {
+                log_warning!(LogCategory::DataProcessing, "Failed to load Fear & Greed history, filter allows every entry: {}", e);
+                FearGreedHistory::new()
+            })
+        } else {
+            FearGreedHistory::new()
+        };
 
         Self {
             current_balance: config.initial_balance,
@@ -143,7 +284,25 @@ impl BacktestEngine {
             completed_trades: Vec::new(),
             equity_curve: VecDeque::new(),
             drawdown_periods: Vec::new(),
-            enhanced_lro: None,
+            lro_engine: None,
+            timeframe_series: MultiTimeframeSeries::new(&config.base_timeframe, &config.higher_timeframe_confirmation.timeframes),
+            regime_classifier: None,
+            regime_prices: VecDeque::new(),
+            current_regime: None,
+            applied_regime: None,
+            regime_config: None,
+            divergence_prices: VecDeque::new(),
+            sizing_prices: VecDeque::new(),
+            fusion_prices: VecDeque::new(),
+            fusion_model: None,
+            model_prices: VecDeque::new(),
+            signal_model: None,
+            fear_greed_history,
+            kelly_sizer: KellyPositionSizer::new(),
+            stop_manager: config.stop_management.clone().map(StopManager::new),
+            position_stop: None,
+            position_take_profit: None,
+            mae_tracker: MAETracker::new(),
             benchmark_data: HashMap::new(),
             high_water_mark: config.initial_balance,
             current_drawdown_start: None,
@@ -157,11 +316,40 @@ impl BacktestEngine {
 
     /// Initialize strategy components
     pub fn initialize_strategy(&mut self, lro_config: LROConfig) -> TradingResult<()> {
-        self.enhanced_lro = Some(EnhancedLRO::new(lro_config.into()));
-        // Backtesting strategy initialized with Enhanced LRO
+        if self.config.lro_engine == LroEngineKind::LroCache {
+            RangeNormalizedLRO::validate_config(&lro_config)
+                .map_err(|e| TradingError::validation_error("lro_config".to_string(), e, None))?;
+        }
+        self.lro_engine = Some(LroEngine::new(self.config.lro_engine, &lro_config));
+        // Backtesting strategy initialized with the configured LRO engine
         Ok(())
     }
 
+    /// Use a pre-trained regime classifier (replaced per window when regime detection is enabled)
+    pub fn set_regime_classifier(&mut self, classifier: Option<RegimeClassifier>) {
+        self.regime_classifier = classifier;
+    }
+
+    /// Use a trained signal fusion model for logistic fusion
+    pub fn set_fusion_model(&mut self, model: Option<FusionModel>) {
+        self.fusion_model = model;
+    }
+
+    /// ONNX model consulted by the model signal filter
+    pub fn set_signal_model(&mut self, model: Option<Arc<OnnxModel>>) {
+        self.signal_model = model;
+    }
+
+    /// Daily Fear & Greed readings consulted by the Fear & Greed filter
+    pub fn set_fear_greed_history(&mut self, history: FearGreedHistory) {
+        self.fear_greed_history = history;
+    }
+
+    /// Trades closed by the last run
+    pub fn completed_trades(&self) -> &[BacktestTrade] {
+        &self.completed_trades
+    }
+
     /// Run complete backtesting process
     pub async fn run_backtest(&mut self, historical_data: Vec<PriceData>) -> TradingResult<BacktestMetrics> {
         // Starting comprehensive backtesting analysis
@@ -237,13 +425,52 @@ impl BacktestEngine {
 
     /// Process individual price data point
     async fn process_price_data(&mut self, price_data: &PriceData) -> TradingResult<()> {
-        // Update strategy indicators
-        if let Some(ref mut lro) = self.enhanced_lro {
-            if let Some(signal) = lro.update(price_data) {
-                self.process_trading_signal(signal, price_data).await?;
+        // Resample before signal evaluation so only already-closed higher-timeframe bars are visible
+        self.timeframe_series.update(price_data);
+        self.update_regime(price_data);
+        if self.config.divergence_filter.enabled {
+            self.divergence_prices.push_back(price_data.clone());
+            if self.divergence_prices.len() > 200 {
+                self.divergence_prices.pop_front();
+            }
+        }
+        self.sizing_prices.push_back(price_data.clone());
+        let required_bars = self.config.position_sizing.required_bars()
+            .max(self.stop_manager.as_ref().map_or(0, |manager| manager.config().required_bars()));
+        if self.sizing_prices.len() > required_bars {
+            self.sizing_prices.pop_front();
+        }
+        self.fusion_prices.push_back(price_data.clone());
+        if self.fusion_prices.len() > signal_fusion::REQUIRED_BARS {
+            self.fusion_prices.pop_front();
+        }
+        if self.config.model_signal.enabled {
+            self.model_prices.push_back(price_data.clone());
+            let required_bars = self.signal_model.as_ref().map_or(0, |model| model.required_bars());
+            if self.model_prices.len() > required_bars {
+                self.model_prices.pop_front();
             }
         }
 
+        // Stops and targets placed on earlier bars are checked against this bar's range before new signals
+        if let Some(fill) = self.position_stop.as_ref().and_then(|stop| stop.intrabar_fill(price_data)) {
+            let fill_price = Decimal::from_f64(fill).unwrap_or(price_data.close);
+            self.close_position(&fill_price, &price_data.timestamp, "Stop Loss".to_string()).await?;
+        } else if let Some(fill_price) = self.take_profit_fill(price_data) {
+            self.close_position(&fill_price, &price_data.timestamp, "Take Profit".to_string()).await?;
+        } else if let Some(stop) = self.position_stop.as_mut() {
+            // Regime stops without a configured stop manager trail as fixed-percent stops
+            let manager = self.stop_manager.clone().unwrap_or_default();
+            let recent_bars: Vec<PriceData> = self.sizing_prices.iter().cloned().collect();
+            manager.update(stop, price_data, &recent_bars);
+        }
+
+        // Update strategy indicators
+        let signal = self.lro_engine.as_mut().and_then(|lro| lro.update(price_data));
+        if let Some(signal) = signal {
+            self.process_trading_signal(signal, price_data).await?;
+        }
+
         // Update position P&L if we have an open position
         if let Some(ref mut position) = self.current_position {
             self.update_position_pnl(position, &price_data.close);
@@ -257,8 +484,22 @@ impl BacktestEngine {
         match signal {
             crate::enhanced_lro::LROSignal::StrongBuy { confidence, .. } | 
             crate::enhanced_lro::LROSignal::Buy { confidence, .. } => {
-                if self.current_position.is_none() && confidence > 0.6 {
-                    self.open_position(TradeSide::Long, &price_data.close, &price_data.timestamp, confidence).await?;
+                if self.current_position.is_none() {
+                    // Readings are recorded on every entry so fusion models can be trained on the results
+                    let components = self.signal_components(TradeSide::Long, confidence);
+                    let strong_enough = if self.config.signal_fusion.enabled {
+                        fuse(&self.config.signal_fusion, self.fusion_model.as_ref(), TradeSide::Long, &components, price_data.timestamp).allowed
+                    } else {
+                        confidence > self.entry_confidence_threshold()
+                    };
+                    if strong_enough
+                        && self.timeframe_series.confirm_entry(&TradeSide::Long, &self.config.higher_timeframe_confirmation).allowed
+                        && self.divergences_allow(&TradeSide::Long)
+                        && self.model_signal_allows(&TradeSide::Long)
+                        && self.config.fear_greed_filter.allows_at(&TradeSide::Long, &self.fear_greed_history, price_data.timestamp)
+                    {
+                        self.open_position(TradeSide::Long, &price_data.close, &price_data.timestamp, confidence, components).await?;
+                    }
                 }
             },
             crate::enhanced_lro::LROSignal::StrongSell { confidence, .. } | 
@@ -277,11 +518,50 @@ impl BacktestEngine {
     }
 
     /// Open a new trading position
-    async fn open_position(&mut self, side: TradeSide, price: &Decimal, timestamp: &DateTime<Utc>, confidence: f64) -> TradingResult<()> {
-        let position_value = self.current_balance * Decimal::from_f64(self.config.max_position_size)
-            .unwrap_or(Decimal::new(95, 2));
+    async fn open_position(
+        &mut self,
+        side: TradeSide,
+        price: &Decimal,
+        timestamp: &DateTime<Utc>,
+        confidence: f64,
+        signal_components: Vec<ComponentReading>
+    ) -> TradingResult<()> {
+        // Same sizing, stop models and notional cap as the live bot; without stops the sizing
+        // default distance applies. An active regime parameter set places its own stop and
+        // take profit, as the bot does
+        let recent_bars: Vec<PriceData> = self.sizing_prices.iter().cloned().collect();
+        let fixed_stop = match self.regime_config.as_ref() {
+            Some(config) => FixedStop {
+                stop_percent: config.get_scaled_stop_loss(),
+                trailing_percent: config.trailing_stop_enabled.then(|| config.get_scaled_trailing_stop()),
+            },
+            None => FixedStop { stop_percent: self.config.position_sizing.default_stop_percent, trailing_percent: None },
+        };
+        let manager = self.stop_manager.clone()
+            .or_else(|| self.regime_config.as_ref().map(|_| StopManager::default()));
+        let stop = manager.map(|manager| manager.open(
+            price.to_f64().unwrap_or(0.0),
+            side == TradeSide::Long,
+            *timestamp,
+            fixed_stop,
+            &recent_bars,
+            &self.mae_tracker,
+        ));
+        let inputs = SizingInputs {
+            equity: self.current_balance,
+            price: *price,
+            stop_distance_percent: stop.as_ref().map(|stop| stop.initial_distance_percent()),
+            signal_strength: confidence,
+            risk_multiplier: 1.0,
+            risk_multiplier_reason: String::new(),
+            max_notional: Decimal::from_f64(self.config.max_position_notional),
+        };
+        let sizing = self.config.position_sizing.size(&inputs, &recent_bars, &self.config.base_timeframe, &self.kelly_sizer);
+        if sizing.quantity <= Decimal::ZERO {
+            return Ok(());
+        }
         
-        let quantity = position_value / price;
+        let quantity = sizing.quantity;
         let commission = quantity * price * Decimal::from_f64(self.config.commission_rate).unwrap_or_default();
         let slippage = quantity * price * Decimal::from_f64(self.config.slippage_rate).unwrap_or_default();
         
@@ -300,11 +580,23 @@ impl BacktestEngine {
             max_adverse_excursion: Some(Decimal::ZERO),
             max_favorable_excursion: Some(Decimal::ZERO),
             signal_strength: confidence,
-            market_conditions: "Normal".to_string(),
+            market_conditions: self.current_regime
+                .map(|(regime, _)| regime.as_str().to_string())
+                .unwrap_or_else(|| "Normal".to_string()),
+            sizing: Some(sizing),
+            signal_components,
         };
 
         self.current_balance -= commission + slippage;
+        self.position_take_profit = self.regime_config.as_ref().and_then(|config| {
+            let target = Decimal::from_f64(config.get_scaled_take_profit() / 100.0)?;
+            Some(match trade.side {
+                TradeSide::Long | TradeSide::Buy => *price * (Decimal::ONE + target),
+                TradeSide::Short | TradeSide::Sell => *price * (Decimal::ONE - target),
+            })
+        });
         self.current_position = Some(trade);
+        self.position_stop = stop;
         
         // Position opened successfully
         
@@ -313,6 +605,7 @@ impl BacktestEngine {
 
     /// Close current trading position
     async fn close_position(&mut self, exit_price: &Decimal, timestamp: &DateTime<Utc>, reason: String) -> TradingResult<()> {
+        self.position_take_profit = None;
         if let Some(mut position) = self.current_position.take() {
             let commission = position.quantity * exit_price * Decimal::from_f64(self.config.commission_rate).unwrap_or_default();
             let slippage = position.quantity * exit_price * Decimal::from_f64(self.config.slippage_rate).unwrap_or_default();
@@ -332,6 +625,10 @@ impl BacktestEngine {
             let net_pnl = gross_pnl - position.commission - position.slippage;
             position.pnl = Some(net_pnl);
             position.pnl_percentage = Some((net_pnl / (position.entry_price * position.quantity)).to_f64().unwrap_or(0.0) * 100.0);
+            self.kelly_sizer.update_trade_result(net_pnl > Decimal::ZERO, position.pnl_percentage.unwrap_or(0.0));
+            if let Some(stop) = self.position_stop.take() {
+                self.mae_tracker.record_trade(stop.to_trade_mae(*timestamp, position.pnl_percentage.unwrap_or(0.0)));
+            }
 
             // Update balance
             self.current_balance += (position.quantity * exit_price) + net_pnl - commission - slippage;
@@ -431,7 +728,7 @@ impl BacktestEngine {
             alpha: None, // Would need benchmark data
             crypto_correlation: HashMap::new(), // Placeholder
             overnight_returns: 0.0, // Placeholder
-            volatility_regime_performance: HashMap::new(), // Placeholder
+            volatility_regime_performance: self.calculate_regime_performance(),
         })
     }
 
@@ -486,11 +783,136 @@ impl BacktestEngine {
     fn calculate_var_95(&self, _returns: &[f64]) -> f64 { 0.0 } // Placeholder
     fn calculate_cvar_95(&self, _returns: &[f64]) -> f64 { 0.0 } // Placeholder
 
-    async fn optimize_strategy_parameters(&mut self, _training_data: &[PriceData]) -> TradingResult<()> {
+    async fn optimize_strategy_parameters(&mut self, training_data: &[PriceData]) -> TradingResult<()> {
+        // Refit the regime model on the training window only, so test periods stay out-of-sample
+        if self.config.regime_detection_enabled {
+            match RegimeClassifier::fit(training_data, self.config.regime_window) {
+                Ok(classifier) => self.regime_classifier = Some(classifier),
+                Err(e) => {
+                    log_warning!(LogCategory::DataProcessing, "Regime classifier not refit for training window: {}", e);
+                }
+            }
+        }
+
         // Strategy parameter optimization would be implemented here
         Ok(())
     }
 
+    /// Classify the regime using bars up to and including the current one
+    fn update_regime(&mut self, price_data: &PriceData) {
+        let classifier = match self.regime_classifier.as_ref() {
+            Some(classifier) => classifier,
+            None => return,
+        };
+
+        self.regime_prices.push_back(price_data.clone());
+        while self.regime_prices.len() > classifier.window + 1 {
+            self.regime_prices.pop_front();
+        }
+
+        let prices: Vec<PriceData> = self.regime_prices.iter().cloned().collect();
+        self.current_regime = classifier.classify(&prices)
+            .map(|probabilities| (probabilities.regime, probabilities.confidence()));
+        self.apply_regime_parameters();
+    }
+
+    /// Rebuild the working strategy config when the active regime changes. Below the switching
+    /// probability, or with switching disabled, no parameter set is active
+    fn apply_regime_parameters(&mut self) {
+        let switching = &self.config.regime_switching;
+        let regime = self.current_regime
+            .filter(|(_, probability)| switching.enabled && *probability >= switching.min_probability)
+            .map(|(regime, _)| regime);
+        if regime == self.applied_regime {
+            return;
+        }
+        self.applied_regime = regime;
+        self.regime_config = regime.map(|regime| {
+            let mut config = crate::trading_strategy::LROConfig {
+                timeframe: self.config.base_timeframe.clone(),
+                ..Default::default()
+            };
+            switching.parameters_for(regime).apply(&mut config);
+            config
+        });
+    }
+
+    /// Take-profit fill for this bar: the target, or the open when the bar gapped through it
+    fn take_profit_fill(&self, bar: &PriceData) -> Option<Decimal> {
+        let target = self.position_take_profit?;
+        match self.current_position.as_ref()?.side {
+            TradeSide::Long | TradeSide::Buy if bar.high >= target => Some(bar.open.max(target)),
+            TradeSide::Short | TradeSide::Sell if bar.low <= target => Some(bar.open.min(target)),
+            _ => None,
+        }
+    }
+
+    /// Divergences within the filter lookback, None while the divergence filter is disabled
+    fn recent_divergences(&self) -> Option<Vec<Divergence>> {
+        let filter = &self.config.divergence_filter;
+        if !filter.enabled || self.divergence_prices.is_empty() {
+            return None;
+        }
+
+        let prices: Vec<PriceData> = self.divergence_prices.iter().cloned().collect();
+        let last_index = prices.len() - 1;
+        Some(DivergenceDetector::new(filter.detection.clone())
+            .scan(&prices, &filter.sources)
+            .into_iter()
+            .filter(|d| last_index - d.confirmed_index <= filter.lookback_bars)
+            .collect())
+    }
+
+    /// Check the divergence filter using bars up to and including the current one
+    fn divergences_allow(&self, side: &TradeSide) -> bool {
+        match self.recent_divergences() {
+            Some(recent) => self.config.divergence_filter.allows_entry(side, &recent),
+            None => true,
+        }
+    }
+
+    /// Check the user-supplied model agrees with an entry, using bars up to and including the current one
+    fn model_signal_allows(&self, side: &TradeSide) -> bool {
+        let bars: Vec<PriceData> = self.model_prices.iter().cloned().collect();
+        self.config.model_signal.evaluate(self.signal_model.as_deref(), &klines_from_bars(&bars), side).0
+    }
+
+    /// Signal fusion readings from the price-derived components; the backtest has no order
+    /// book, intelligence or GPU risk history
+    fn signal_components(&self, side: TradeSide, confidence: f64) -> Vec<ComponentReading> {
+        let bars: Vec<PriceData> = self.fusion_prices.iter().cloned().collect();
+        let divergences = self.recent_divergences();
+        FusionInputs {
+            side,
+            lro_strength: confidence,
+            bars: &bars,
+            divergences: divergences.as_deref(),
+            depth: None,
+            intelligence: None,
+            risk: None,
+        }.readings()
+    }
+
+    /// Entry confidence threshold, taken from the active regime parameter set when switching is enabled
+    fn entry_confidence_threshold(&self) -> f64 {
+        self.regime_config.as_ref().map_or(0.6, |config| config.signal_strength_threshold)
+    }
+
+    /// Net P&L per regime as a percentage of initial balance
+    fn calculate_regime_performance(&self) -> HashMap<String, f64> {
+        let initial_balance = self.config.initial_balance.to_f64().unwrap_or(0.0);
+        let mut performance = HashMap::new();
+        if initial_balance <= 0.0 {
+            return performance;
+        }
+
+        for trade in &self.completed_trades {
+            let pnl = trade.pnl.and_then(|p| p.to_f64()).unwrap_or(0.0);
+            *performance.entry(trade.market_conditions.clone()).or_insert(0.0) += pnl / initial_balance * 100.0;
+        }
+        performance
+    }
+
     fn aggregate_walk_forward_results(&self, _metrics: Vec<BacktestMetrics>) -> TradingResult<BacktestMetrics> {
         // Aggregate multiple period results
         Ok(BacktestMetrics::empty()) // Placeholder
@@ -505,6 +927,19 @@ impl BacktestEngine {
         self.high_water_mark = self.config.initial_balance;
         self.current_drawdown_start = None;
         self.daily_returns.clear();
+        self.timeframe_series.reset();
+        self.regime_prices.clear();
+        self.current_regime = None;
+        self.applied_regime = None;
+        self.regime_config = None;
+        self.divergence_prices.clear();
+        self.sizing_prices.clear();
+        self.fusion_prices.clear();
+        self.model_prices.clear();
+        self.kelly_sizer = KellyPositionSizer::new();
+        self.position_stop = None;
+        self.position_take_profit = None;
+        self.mae_tracker = MAETracker::new();
     }
 
     fn update_equity_curve(&mut self, timestamp: &DateTime<Utc>) {
@@ -569,6 +1004,114 @@ impl From<crate::trading_strategy::LROConfig> for crate::enhanced_lro::LROConfig
             volatility_adjustment: config.adaptive_enabled,
             multi_timeframe: true,
             divergence_detection: true,
+            signal_period: config.signal_period,
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_json() -> serde_json::Value {
+        serde_json::to_value(BacktestConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn test_legacy_position_fraction_converts_to_notional() {
+        let mut json = config_json();
+        let fields = json.as_object_mut().unwrap();
+        fields.remove("max_position_notional");
+        fields.insert("max_position_size".to_string(), serde_json::json!(0.1));
+
+        let config: BacktestConfig = serde_json::from_value(json).unwrap();
+        assert_eq!(config.max_position_size, Some(0.1));
+        let engine = BacktestEngine::new(config);
+        assert!((engine.config.max_position_notional - 1000.0).abs() < 1e-9);
+        assert_eq!(engine.config.max_position_size, None);
+
+        // Saved configs only carry the notional cap
+        let saved = serde_json::to_value(&engine.config).unwrap();
+        assert!(saved.get("max_position_size").is_none());
+        assert_eq!(saved["max_position_notional"], serde_json::json!(1000.0));
+    }
+
+    #[test]
+    fn test_legacy_position_size_outside_fraction_range_is_rejected() {
+        let mut config = BacktestConfig { max_position_size: Some(1000.0), ..Default::default() };
+        assert!(config.migrate_legacy_fields().is_err());
+
+        let mut config = BacktestConfig { max_position_size: Some(0.95), ..Default::default() };
+        config.migrate_legacy_fields().unwrap();
+        assert!((config.max_position_notional - 9500.0).abs() < 1e-9);
+    }
+
+    /// Open and close a long at 100 under `regime`, returning the entry threshold, the stop
+    /// and its trailing distance, the take profit and the quantity
+    async fn regime_entry(
+        engine: &mut BacktestEngine,
+        regime: Option<(Regime, f64)>,
+    ) -> (f64, Option<(f64, Option<f64>)>, Option<Decimal>, Decimal) {
+        let (price, time) = (Decimal::from(100), Utc::now());
+        engine.current_regime = regime;
+        engine.apply_regime_parameters();
+        let threshold = engine.entry_confidence_threshold();
+        engine.open_position(TradeSide::Long, &price, &time, 0.9, Vec::new()).await.unwrap();
+        let stop = engine.position_stop.as_ref().map(|stop| (stop.initial_stop, stop.trailing_percent));
+        let opened = (threshold, stop, engine.position_take_profit, engine.current_position.as_ref().unwrap().quantity);
+        engine.close_position(&price, &time, "test".to_string()).await.unwrap();
+        opened
+    }
+
+    #[tokio::test]
+    async fn test_regime_parameters_set_stops_targets_and_size() {
+        let config = BacktestConfig {
+            max_position_notional: 1_000_000.0,
+            regime_switching: RegimeSwitching { enabled: true, ..Default::default() },
+            ..Default::default()
+        };
+        let mut engine = BacktestEngine::new(config);
+
+        // 1m bars scale the regime percentages by 0.2, as in the bot
+        let (threshold, stop, target, trending_quantity) = regime_entry(&mut engine, Some((Regime::Trending, 0.9))).await;
+        assert_eq!(threshold, 0.6);
+        let (initial_stop, trailing) = stop.unwrap();
+        assert!((initial_stop - 99.5).abs() < 1e-9);
+        assert!((trailing.unwrap() - 0.3).abs() < 1e-9);
+        assert_eq!(target, Some(Decimal::from_str("101.2").unwrap()));
+
+        let (threshold, stop, target, ranging_quantity) = regime_entry(&mut engine, Some((Regime::Ranging, 0.9))).await;
+        assert_eq!(threshold, 0.55);
+        let (initial_stop, trailing) = stop.unwrap();
+        assert!((initial_stop - 99.7).abs() < 1e-9);
+        assert_eq!(trailing, None);
+        assert_eq!(target, Some(Decimal::from_str("100.6").unwrap()));
+        // Risk-based sizing takes a larger position behind the tighter ranging stop
+        assert!(ranging_quantity > trending_quantity);
+
+        // Below the switching probability the backtest falls back to signal exits
+        let (threshold, stop, target, _) = regime_entry(&mut engine, Some((Regime::Volatile, 0.4))).await;
+        assert_eq!((threshold, stop, target), (0.6, None, None));
+    }
+
+    #[tokio::test]
+    async fn test_take_profit_fills_at_target_or_gap_open() {
+        let mut engine = BacktestEngine::new(BacktestConfig::default());
+        let bar = |open: i64, high: i64| PriceData {
+            timestamp: Utc::now(),
+            open: Decimal::from(open),
+            high: Decimal::from(high),
+            low: Decimal::from(open - 1),
+            close: Decimal::from(open),
+            volume: Decimal::ONE,
+        };
+        engine.position_take_profit = Some(Decimal::from(105));
+        assert_eq!(engine.take_profit_fill(&bar(103, 110)), None);
+
+        engine.open_position(TradeSide::Long, &Decimal::from(100), &Utc::now(), 0.9, Vec::new()).await.unwrap();
+        engine.position_take_profit = Some(Decimal::from(105));
+        assert_eq!(engine.take_profit_fill(&bar(101, 104)), None);
+        assert_eq!(engine.take_profit_fill(&bar(103, 106)), Some(Decimal::from(105)));
+        assert_eq!(engine.take_profit_fill(&bar(107, 108)), Some(Decimal::from(107)));
+    }
+}
@@ -558,6 +558,31 @@ impl ImprovedBinanceClient {
         Ok(result)
     }
 
+    /// Exchange info for one symbol, including its price, lot size and notional filters
+    pub async fn get_symbol_info(&self, symbol: &str) -> Result<SymbolInfo, Box<dyn std::error::Error + Send + Sync>> {
+        let url = format!("{}/api/v3/exchangeInfo", self.base_url);
+        let endpoint = BinanceEndpoints::get_endpoint_from_url(&url);
+        let weight = BinanceEndpoints::get_weight(endpoint);
+        
+        self.rate_limiter.wait_for_rate_limit(weight).await?;
+        
+        let response = self.client
+            .get(&url)
+            .query(&[("symbol", symbol.to_uppercase())])
+            .send()
+            .await?;
+            
+        if !response.status().is_success() {
+            return self.handle_api_error_response(response).await.map(|_| unreachable!());
+        }
+        
+        let data: Value = response.json().await?;
+        data["symbols"].as_array()
+            .and_then(|symbols| symbols.first())
+            .and_then(|symbol| self.parse_symbol_info(symbol))
+            .ok_or_else(|| format!("No exchange info for symbol: {}", symbol).into())
+    }
+
     pub async fn search_symbols(&self, query: &str, limit: u32) -> Result<Vec<SymbolInfo>, Box<dyn std::error::Error + Send + Sync>> {
         let all_symbols = self.get_all_symbols().await?;
         let query = query.to_uppercase();
@@ -628,7 +653,23 @@ impl ImprovedBinanceClient {
             low: None,
             is_spot_trading_allowed: data["isSpotTradingAllowed"].as_bool().unwrap_or(false),
             is_margin_trading_allowed: data["isMarginTradingAllowed"].as_bool().unwrap_or(false),
-            filters: Vec::new(), // Simplified for now
+            filters: data["filters"].as_array()
+                .map(|filters| filters.iter().filter_map(|f| self.parse_symbol_filter(f)).collect())
+                .unwrap_or_default(),
+        })
+    }
+
+    fn parse_symbol_filter(&self, data: &Value) -> Option<SymbolFilter> {
+        let decimal = |key: &str| data[key].as_str().and_then(|v| v.parse::<Decimal>().ok());
+        Some(SymbolFilter {
+            filter_type: data["filterType"].as_str()?.to_string(),
+            min_price: decimal("minPrice"),
+            max_price: decimal("maxPrice"),
+            tick_size: decimal("tickSize"),
+            min_qty: decimal("minQty"),
+            max_qty: decimal("maxQty"),
+            step_size: decimal("stepSize"),
+            min_notional: decimal("minNotional"),
         })
     }
 
@@ -12,6 +12,9 @@ use crate::advanced_trading::{
 use crate::advanced_trading::technical_analysis::TechnicalAnalysisResult;
 use crate::advanced_trading::portfolio_analytics::PerformanceReport;
 use crate::advanced_trading::order_management::{ActiveOrder, CompletedOrder};
+use crate::advanced_trading::value_at_risk::{VarBacktest, VarMethod, VarReport};
+use crate::advanced_trading::risk_engine::HistoricalStressEvent;
+use crate::advanced_trading::stress_replay::{ReplayWindow, ShockResult, ShockScenario, StressReplayResult};
 use rust_decimal::Decimal;
 
 /// Initialize the advanced trading engine
@@ -21,7 +24,11 @@ pub async fn initialize_advanced_trading(
 ) -> Result<(), String> {
     // Initialize the advanced trading engine
     let engine = AdvancedTradingEngine::new().await
-        .map_err(|e| format!("Failed to initialize advanced trading engine: {}", e))?;
+        .map_err(|e| format!("Failed to initialize advanced trading engine: {}", e))?
+        .with_portfolio_heat(trading_state.portfolio_heat.clone())
+        .with_drawdown_governor(trading_state.drawdown_governor.clone())
+        .with_kill_switch(trading_state.kill_switch.clone())
+        .with_risk_limits(trading_state.risk_limits.clone());
     
     // Store in state
     let mut advanced_engine = trading_state.advanced_trading_engine.write().await;
@@ -183,6 +190,89 @@ pub async fn get_performance_report(
     Ok(PerformanceReportDto::from(report))
 }
 
+/// Historical, parametric and Monte Carlo VaR/ES for the open positions
+#[tauri::command]
+pub async fn get_var_report(
+    horizon_days: Option<u32>,
+    trading_state: State<'_, TradingState>
+) -> Result<VarReport, String> {
+    let engine_guard = trading_state.advanced_trading_engine.read().await;
+    let engine = engine_guard.as_ref()
+        .ok_or("Advanced trading engine not initialized")?;
+
+    engine.get_var_report(horizon_days.unwrap_or(1)).await
+        .map_err(|e| format!("Failed to calculate VaR: {}", e))
+}
+
+/// Backtest VaR exceptions against realised daily losses (Kupiec test)
+#[tauri::command]
+pub async fn backtest_var_model(
+    method: VarMethod,
+    confidence: Option<f64>,
+    window: Option<usize>,
+    trading_state: State<'_, TradingState>
+) -> Result<VarBacktest, String> {
+    let engine_guard = trading_state.advanced_trading_engine.read().await;
+    let engine = engine_guard.as_ref()
+        .ok_or("Advanced trading engine not initialized")?;
+
+    engine.backtest_var(method, confidence.unwrap_or(0.99), window.unwrap_or(250)).await
+        .map_err(|e| format!("Failed to backtest VaR model: {}", e))
+}
+
+/// Historical stress events available for replay
+#[tauri::command]
+pub async fn list_stress_events(
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<HistoricalStressEvent>, String> {
+    let engine_guard = trading_state.advanced_trading_engine.read().await;
+    let engine = engine_guard.as_ref()
+        .ok_or("Advanced trading engine not initialized")?;
+
+    Ok(engine.get_stress_events().await)
+}
+
+/// Replay a named historical event, or a custom date range, against the current positions
+#[tauri::command]
+pub async fn run_stress_replay(
+    event: Option<String>,
+    start: Option<chrono::DateTime<chrono::Utc>>,
+    end: Option<chrono::DateTime<chrono::Utc>>,
+    interval: Option<String>,
+    trading_state: State<'_, TradingState>
+) -> Result<StressReplayResult, String> {
+    let engine_guard = trading_state.advanced_trading_engine.read().await;
+    let engine = engine_guard.as_ref()
+        .ok_or("Advanced trading engine not initialized")?;
+
+    let window = match (event, start, end) {
+        (Some(name), _, _) => engine.get_stress_events().await
+            .iter()
+            .find(|e| e.name.eq_ignore_ascii_case(&name))
+            .map(ReplayWindow::from_event)
+            .ok_or_else(|| format!("Unknown stress event: {}", name))?,
+        (None, Some(start), Some(end)) => ReplayWindow { name: "Custom Range".to_string(), start, end },
+        _ => return Err("Provide either an event name or a start and end date".to_string()),
+    };
+
+    engine.replay_stress_window(window, interval.as_deref().unwrap_or("1h")).await
+        .map_err(|e| format!("Failed to replay stress window: {}", e))
+}
+
+/// Apply a custom per-asset shock vector to the current positions
+#[tauri::command]
+pub async fn run_shock_scenario(
+    scenario: ShockScenario,
+    trading_state: State<'_, TradingState>
+) -> Result<ShockResult, String> {
+    let engine_guard = trading_state.advanced_trading_engine.read().await;
+    let engine = engine_guard.as_ref()
+        .ok_or("Advanced trading engine not initialized")?;
+
+    engine.run_shock_scenario(scenario).await
+        .map_err(|e| format!("Failed to run shock scenario: {}", e))
+}
+
 /// Emergency stop all trading activities
 #[tauri::command]
 pub async fn emergency_stop_advanced_trading(
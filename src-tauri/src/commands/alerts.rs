@@ -0,0 +1,59 @@
+use crate::TradingState;
+use crate::technical_alerts::{timeframe_supported, AlertRule, NewAlertRule, TechnicalAlert};
+use tauri::State;
+
+/// Create a technical alert rule evaluated against closed bars of the bot's feed, resampled to
+/// the rule's timeframe. The timeframe must equal the feed's or be a whole multiple of it.
+#[tauri::command]
+pub async fn create_alert_rule(
+    auth_token: String,
+    rule: NewAlertRule,
+    trading_state: State<'_, TradingState>
+) -> Result<AlertRule, String> {
+    trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let feed_timeframe = trading_state.swing_bot.read().await.config.timeframe.clone();
+    if !timeframe_supported(&rule.timeframe, &feed_timeframe) {
+        return Err(format!(
+            "Alert timeframe '{}' cannot be built from the {} price feed; use {} or a whole multiple of it",
+            rule.timeframe, feed_timeframe, feed_timeframe
+        ));
+    }
+
+    let mut alert_system = trading_state.alert_system.write().await;
+    Ok(alert_system.add_rule(rule)?)
+}
+
+#[tauri::command]
+pub async fn list_alert_rules(
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<AlertRule>, String> {
+    let alert_system = trading_state.alert_system.read().await;
+    Ok(alert_system.rules().to_vec())
+}
+
+#[tauri::command]
+pub async fn delete_alert_rule(
+    auth_token: String,
+    rule_id: String,
+    trading_state: State<'_, TradingState>
+) -> Result<bool, String> {
+    trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let mut alert_system = trading_state.alert_system.write().await;
+    Ok(alert_system.remove_rule(&rule_id)?)
+}
+
+/// Triggered alerts, newest first
+#[tauri::command]
+pub async fn get_alert_history(
+    limit: Option<usize>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<TechnicalAlert>, String> {
+    let alert_system = trading_state.alert_system.read().await;
+    Ok(alert_system.history(limit.unwrap_or(100)))
+}
@@ -0,0 +1,106 @@
+use crate::TradingState;
+use crate::anomaly_detection::Anomaly;
+use crate::binance_client::ImprovedBinanceClient;
+use crate::logging::LogCategory;
+use crate::models::AppSettings;
+use crate::trading_strategy::SwingTradingBot;
+use crate::websocket::ImprovedBinanceWebSocket;
+use crate::{log_info, log_warning};
+use chrono::Utc;
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::RwLock;
+
+type SharedFeedClient = Arc<RwLock<Option<Arc<ImprovedBinanceClient>>>>;
+
+/// Recorded market data anomalies, newest first
+#[tauri::command]
+pub async fn get_market_anomalies(
+    limit: Option<usize>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<Anomaly>, String> {
+    let bot = trading_state.swing_bot.read().await;
+    Ok(bot.anomalies(limit.unwrap_or(100)))
+}
+
+/// Fetch the REST ticker for the bot's feed symbol and run the bot's feed checks against it
+async fn check_feed(
+    client: &ImprovedBinanceClient,
+    websocket: &ImprovedBinanceWebSocket,
+    swing_bot: &RwLock<SwingTradingBot>,
+) -> Result<Vec<Anomaly>, String> {
+    let symbol = websocket.current_symbol.read().await.clone();
+    let ticker = client.get_24hr_ticker(&symbol).await.map_err(|e| e.to_string())?;
+    Ok(swing_bot.write().await.check_price_feed(ticker.price, Utc::now()))
+}
+
+/// Compare the streamed price with the REST ticker and check the feed is still updating.
+/// Returns any anomalies found; the bot pauses on the confident ones
+#[tauri::command]
+pub async fn verify_price_feed(
+    settings: AppSettings,
+    symbol: String,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<Anomaly>, String> {
+    let feed_symbol = trading_state.websocket.current_symbol.read().await.clone();
+    if !symbol.eq_ignore_ascii_case(&feed_symbol) {
+        return Err(format!("{} is not the bot's price feed ({})", symbol, feed_symbol));
+    }
+    let client = ImprovedBinanceClient::new(&settings).map_err(|e| e.to_string())?;
+    check_feed(&client, &trading_state.websocket, &trading_state.swing_bot).await
+}
+
+/// Check the bot's price feed on the configured interval while the bot is not stopped.
+/// Stops once the client is replaced or removed from state
+fn spawn_feed_check_task(
+    client: Arc<ImprovedBinanceClient>,
+    slot: SharedFeedClient,
+    websocket: Arc<ImprovedBinanceWebSocket>,
+    swing_bot: Arc<RwLock<SwingTradingBot>>,
+) {
+    tokio::spawn(async move {
+        loop {
+            let interval_seconds = swing_bot.read().await.config.anomaly_detection.feed_check_interval_seconds;
+            tokio::time::sleep(std::time::Duration::from_secs(interval_seconds.max(1))).await;
+            let current = slot.read().await.as_ref().is_some_and(|active| Arc::ptr_eq(active, &client));
+            if !current {
+                break;
+            }
+            if swing_bot.read().await.is_stopped() {
+                continue;
+            }
+
+            if let Err(e) = check_feed(&client, &websocket, &swing_bot).await {
+                log_warning!(LogCategory::DataProcessing, "Price feed check failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Start checking the bot's price feed against the REST ticker in the background
+#[tauri::command]
+pub async fn start_price_feed_monitor(
+    settings: AppSettings,
+    trading_state: State<'_, TradingState>
+) -> Result<(), String> {
+    let client = Arc::new(ImprovedBinanceClient::new(&settings).map_err(|e| e.to_string())?);
+    *trading_state.price_feed_monitor.write().await = Some(client.clone());
+
+    log_info!(LogCategory::Configuration, "Price feed monitor started");
+    spawn_feed_check_task(
+        client,
+        trading_state.price_feed_monitor.clone(),
+        trading_state.websocket.clone(),
+        trading_state.swing_bot.clone(),
+    );
+    Ok(())
+}
+
+/// Stop the background price feed checks
+#[tauri::command]
+pub async fn stop_price_feed_monitor(
+    trading_state: State<'_, TradingState>
+) -> Result<(), String> {
+    *trading_state.price_feed_monitor.write().await = None;
+    Ok(())
+}
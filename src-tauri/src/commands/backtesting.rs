@@ -2,6 +2,9 @@ use crate::TradingState;
 use crate::backtesting::{BacktestEngine, BacktestConfig, BacktestMetrics, BacktestTrade};
 use crate::models::PriceData;
 use crate::enhanced_lro::LROConfig;
+use crate::regime_detection::RegimeClassifier;
+use crate::signal_fusion::FusionModel;
+use crate::lro_engine::{compare_engines, EngineComparisonReport};
 use tauri::State;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
@@ -9,7 +12,7 @@ use std::sync::Arc;
 use tokio::sync::RwLock;
 
 // Global backtesting engine storage
-type BacktestEngineState = Arc<RwLock<Option<BacktestEngine>>>;
+pub type BacktestEngineState = Arc<RwLock<Option<BacktestEngine>>>;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestRequest {
@@ -42,11 +45,26 @@ pub struct WalkForwardResult {
 pub async fn initialize_backtest_engine(
     config: BacktestConfig,
     lro_config: LROConfig,
-    backtest_state: State<'_, BacktestEngineState>
+    backtest_state: State<'_, BacktestEngineState>,
+    trading_state: State<'_, TradingState>
 ) -> Result<(), String> {
+    config.higher_timeframe_confirmation.validate(&config.base_timeframe)?;
+    config.divergence_filter.validate()?;
+    config.position_sizing.validate()?;
+    if let Some(ref stop_management) = config.stop_management {
+        stop_management.validate()?;
+    }
+    config.signal_fusion.validate()?;
+    config.model_signal.validate()?;
+    let signal_model = trading_state.model_registry.read().await.resolve(&config.model_signal)?;
+    
     let mut engine = BacktestEngine::new(config);
     engine.initialize_strategy(lro_config)
         .map_err(|e| format!("Failed to initialize strategy: {}", e))?;
+    // Tag trades by regime using the persisted model, if one has been trained
+    engine.set_regime_classifier(RegimeClassifier::load().ok().flatten());
+    engine.set_fusion_model(FusionModel::load().ok().flatten());
+    engine.set_signal_model(signal_model);
     
     let mut state = backtest_state.write().await;
     *state = Some(engine);
@@ -120,9 +138,8 @@ pub async fn get_backtest_trades(
 ) -> Result<Vec<BacktestTrade>, String> {
     let state = backtest_state.read().await;
     
-    if let Some(_engine) = state.as_ref() {
-        // This would return actual trade history from the engine
-        Ok(Vec::new())
+    if let Some(engine) = state.as_ref() {
+        Ok(engine.completed_trades().to_vec())
     } else {
         Err("Backtesting engine not initialized".to_string())
     }
@@ -148,6 +165,19 @@ pub async fn generate_performance_report(
     }
 }
 
+/// Run the LroCache, Enhanced and Optimized LRO engines side by side on the same data
+#[tauri::command]
+pub async fn compare_lro_engines(
+    lro_config: LROConfig,
+    historical_data: Vec<PriceData>
+) -> Result<EngineComparisonReport, String> {
+    if historical_data.len() < lro_config.base_period + 1 {
+        return Err(format!("At least {} price bars are required to compare engines", lro_config.base_period + 1));
+    }
+    
+    Ok(compare_engines(&historical_data, &lro_config))
+}
+
 /// Compare multiple strategies
 #[tauri::command]
 pub async fn compare_strategies(
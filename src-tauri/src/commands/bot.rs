@@ -5,7 +5,9 @@ use crate::models::{MarketDepthAnalysis, LiquidityLevel};
 use crate::enhanced_lro::LROStatistics;
 use crate::auth::Claims;
 use crate::atomic_operations::BotStateSnapshot;
-use tauri::State;
+use crate::logging::LogCategory;
+use crate::{log_info, log_warning};
+use tauri::{Emitter, State};
 use serde::{Serialize, Deserialize};
 use rust_decimal::prelude::ToPrimitive;
 
@@ -181,6 +183,15 @@ pub async fn update_bot_config(
         return Err("Invalid virtual balance: must be positive".to_string());
     }
     
+    config.higher_timeframe_confirmation.validate(&config.timeframe)?;
+    config.regime_switching.validate()?;
+    config.divergence_filter.validate()?;
+    config.position_sizing.validate()?;
+    config.stop_management.validate()?;
+    config.anomaly_detection.validate()?;
+    config.model_signal.validate()?;
+    let signal_model = trading_state.model_registry.read().await.resolve(&config.model_signal)?;
+    
     // Check if bot is in a safe state for configuration updates
     let state_snapshot = trading_state.atomic_state.get_state();
     if state_snapshot.is_processing_signal {
@@ -193,7 +204,8 @@ pub async fn update_bot_config(
     
     // Update bot configuration with proper locking
     let mut bot = trading_state.swing_bot.write().await;
-    bot.config = config;
+    bot.set_config(config);
+    bot.set_signal_model(signal_model);
     
     // Update atomic state timestamp
     trading_state.atomic_state.update_heartbeat();
@@ -261,10 +273,35 @@ pub async fn get_lro_signals(
 #[tauri::command]
 pub async fn feed_price_data(
     price_data: PriceData,
+    symbol: Option<String>,
+    app_handle: tauri::AppHandle,
     trading_state: State<'_, TradingState>
 ) -> Result<(), String> {
-    let mut bot = trading_state.swing_bot.write().await;
-    bot.add_price_data(price_data);
+    let symbol = match symbol {
+        Some(symbol) => symbol,
+        None => trading_state.websocket.current_symbol.read().await.clone(),
+    };
+    let timeframe = {
+        let mut bot = trading_state.swing_bot.write().await;
+        bot.set_symbol(&symbol);
+        bot.add_price_data(price_data.clone());
+        bot.config.timeframe.clone()
+    };
+    
+    // Evaluate technical alert rules once the feed's bars close
+    let mut alert_system = trading_state.alert_system.write().await;
+    let alerts = alert_system.on_feed_update(&symbol, &timeframe, &price_data);
+    if !alerts.is_empty() {
+        if let Err(e) = alert_system.save() {
+            log_warning!(LogCategory::Configuration, "Failed to persist alert state: {}", e);
+        }
+        for alert in &alerts {
+            log_info!(LogCategory::Trading, "Technical alert: {}", alert.message);
+            if let Err(e) = app_handle.emit("technical-alert", alert) {
+                log_warning!(LogCategory::DataProcessing, "Failed to emit technical alert event: {}", e);
+            }
+        }
+    }
     Ok(())
 }
 
@@ -311,6 +348,11 @@ pub async fn reset_emergency_stop(
         .validate_bot_operation(&auth_token, "emergency_stop")
         .map_err(|e| format!("Authentication failed: {}", e))?;
     
+    // The global kill switch has its own re-authenticated reset
+    if trading_state.kill_switch.lock().map_err(|e| e.to_string())?.is_engaged() {
+        return Err("Kill switch is engaged - use reset_kill_switch".to_string());
+    }
+    
     // Reset atomic emergency stop
     trading_state.atomic_state
         .reset_emergency_stop()
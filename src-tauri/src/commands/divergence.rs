@@ -0,0 +1,35 @@
+use crate::TradingState;
+use crate::divergence::{Divergence, DivergenceConfig, DivergenceDetector, OscillatorSource};
+use crate::kline_store::{KlineStore, klines_to_price_data};
+use tauri::State;
+
+/// Scan stored klines for price/oscillator divergences
+#[tauri::command]
+pub async fn scan_divergences(
+    symbol: String,
+    interval: String,
+    sources: Option<Vec<OscillatorSource>>,
+    config: Option<DivergenceConfig>
+) -> Result<Vec<Divergence>, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    let sources = sources.unwrap_or_else(|| OscillatorSource::ALL.to_vec());
+
+    let store = KlineStore::open_default().map_err(|e| e.to_string())?;
+    let klines = store.load(&symbol, &interval).map_err(|e| e.to_string())?;
+    if klines.is_empty() {
+        return Err(format!("No stored klines for {} {}. Fetch klines first.", symbol, interval));
+    }
+
+    let prices = klines_to_price_data(&klines);
+    Ok(DivergenceDetector::new(config).scan(&prices, &sources))
+}
+
+/// Divergences currently affecting the swing bot's entry filter
+#[tauri::command]
+pub async fn get_recent_divergences(
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<Divergence>, String> {
+    let bot = trading_state.swing_bot.read().await;
+    Ok(bot.recent_divergences.clone())
+}
@@ -0,0 +1,51 @@
+use crate::TradingState;
+use crate::drawdown_governor::{DrawdownGovernorConfig, GovernorEvent, GovernorStatus};
+use tauri::State;
+
+/// Combined equity, drawdown from the high-water mark and the size multiplier for new entries
+#[tauri::command]
+pub async fn get_drawdown_status(
+    trading_state: State<'_, TradingState>
+) -> Result<GovernorStatus, String> {
+    let governor = trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?;
+    Ok(governor.status().clone())
+}
+
+#[tauri::command]
+pub async fn get_drawdown_governor_config(
+    trading_state: State<'_, TradingState>
+) -> Result<DrawdownGovernorConfig, String> {
+    let governor = trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?;
+    Ok(governor.config().clone())
+}
+
+/// Replace the drawdown steps, halt limit and equity-curve filter settings
+#[tauri::command]
+pub async fn set_drawdown_governor_config(
+    config: DrawdownGovernorConfig,
+    trading_state: State<'_, TradingState>
+) -> Result<GovernorStatus, String> {
+    let mut governor = trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?;
+    governor.set_config(config)?;
+    Ok(governor.status().clone())
+}
+
+/// Measure drawdown from the current equity from now on
+#[tauri::command]
+pub async fn reset_drawdown_high_water_mark(
+    trading_state: State<'_, TradingState>
+) -> Result<GovernorStatus, String> {
+    let mut governor = trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?;
+    governor.reset_high_water_mark()?;
+    Ok(governor.status().clone())
+}
+
+/// Size multiplier step changes, newest first
+#[tauri::command]
+pub async fn get_drawdown_events(
+    limit: Option<usize>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<GovernorEvent>, String> {
+    let governor = trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?;
+    Ok(governor.events().take(limit.unwrap_or(100)).cloned().collect())
+}
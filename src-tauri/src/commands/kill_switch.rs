@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use crate::TradingState;
+use crate::binance_client::ImprovedBinanceClient;
+use crate::kill_switch::{ClosedPosition, KillSwitchStatus, PostMortemReport};
+use crate::models::{AppSettings, OrderRequest, OrderType, TradeSide, TradeStatus};
+use crate::pairs_trading::DESK_OWNER;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use tauri::State;
+
+/// Stop every bot, cancel all resting orders and flatten every position at current market
+/// prices. The halted state is persisted before anything is closed and survives restarts
+#[tauri::command]
+pub async fn engage_kill_switch(
+    auth_token: String,
+    reason: String,
+    settings: AppSettings,
+    trading_state: State<'_, TradingState>
+) -> Result<PostMortemReport, String> {
+    let claims = trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "kill_switch")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    // Halt first: entry paths check the switch and the atomic emergency stop
+    let mut report = trading_state.kill_switch.lock().map_err(|e| e.to_string())?
+        .engage(&claims.sub, &reason)?;
+    trading_state.atomic_state.trigger_emergency_stop()?;
+
+    {
+        let mut bot = trading_state.swing_bot.write().await;
+        if let Some(closed) = bot.stop_bot(&format!("Kill switch: {}", reason)) {
+            report.add_closed(closed);
+        }
+        report.stopped_bots.push("swing_bot".to_string());
+    }
+
+    let engine = trading_state.advanced_trading_engine.read().await;
+    let mut symbols: Vec<String> = trading_state.paper_trades.read().await.iter()
+        .filter(|trade| matches!(trade.status, TradeStatus::Open))
+        .map(|trade| trade.symbol.clone())
+        .collect();
+    if let Some(engine) = engine.as_ref() {
+        symbols.extend(engine.portfolio_analytics.read().await.open_positions().into_iter().map(|p| p.symbol));
+    }
+    symbols.sort();
+    symbols.dedup();
+
+    let client = match ImprovedBinanceClient::new(&settings) {
+        Ok(client) => Some(client),
+        Err(e) => {
+            report.warnings.push(format!("No market data client, closing at last known prices: {}", e));
+            None
+        }
+    };
+    let mut prices = HashMap::new();
+    if let Some(client) = client.as_ref() {
+        for symbol in symbols {
+            match client.get_klines(&symbol, "1m", 1).await {
+                Ok(klines) if !klines.is_empty() => { prices.insert(symbol, klines[0].close); }
+                Ok(_) => report.warnings.push(format!("No market price for {}", symbol)),
+                Err(e) => report.warnings.push(format!("Failed to get market price for {}: {}", symbol, e)),
+            }
+        }
+    }
+
+    if let Some(engine) = engine.as_ref() {
+        if let Err(e) = engine.flatten_all(&prices, &mut report).await {
+            report.warnings.push(format!("Advanced trading flatten incomplete: {}", e));
+        }
+        report.stopped_bots.push("advanced_trading".to_string());
+    }
+    drop(engine);
+
+    // Pair positions are dropped from the desk; their leg trades close with the paper trades below
+    let pair_trade_ids: Vec<_> = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?
+        .flatten_all()
+        .into_iter()
+        .flat_map(|(_, position)| position.trade_ids)
+        .collect();
+    report.stopped_bots.push(DESK_OWNER.to_string());
+    let mut pairs_realized = 0.0;
+
+    // Manual paper trades close through the paper execution simulator
+    let mut paper_trades = trading_state.paper_trades.write().await;
+    for trade in paper_trades.iter_mut().filter(|trade| matches!(trade.status, TradeStatus::Open)) {
+        let price = match prices.get(&trade.symbol) {
+            Some(price) => *price,
+            None => {
+                report.warnings.push(format!("Paper trade {} on {} closed at its entry price", trade.id, trade.symbol));
+                trade.entry_price
+            }
+        };
+        let closing_order = OrderRequest {
+            symbol: trade.symbol.clone(),
+            side: match trade.side {
+                TradeSide::Short | TradeSide::Sell => TradeSide::Buy,
+                TradeSide::Long | TradeSide::Buy => TradeSide::Sell,
+            },
+            order_type: OrderType::Market,
+            quantity: trade.quantity,
+            price: None,
+            take_profit_percent: None,
+            stop_loss_percent: None,
+        };
+        let exit_price = match client.as_ref() {
+            Some(client) => client.simulate_order(&closing_order, Some(price)).await
+                .map(|fill| fill.entry_price)
+                .unwrap_or(price),
+            None => price,
+        };
+
+        let owner = if pair_trade_ids.contains(&trade.id) { DESK_OWNER } else { "paper_trading" };
+        let closed = ClosedPosition::new(owner, &trade.symbol, trade.side.clone(), trade.quantity, trade.entry_price, exit_price);
+        if pair_trade_ids.contains(&trade.id) {
+            pairs_realized += closed.realized_pnl.to_f64().unwrap_or(0.0);
+        }
+        trade.status = TradeStatus::Closed;
+        trade.exit_price = Some(exit_price);
+        trade.closed_at = Some(closed.closed_at);
+        trade.pnl = Some(closed.realized_pnl);
+        report.add_closed(closed);
+    }
+    drop(paper_trades);
+
+    {
+        let mut desk = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?;
+        desk.record_realized(pairs_realized);
+        if !desk.is_empty() {
+            let equity = desk.equity();
+            trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?
+                .update_equity(DESK_OWNER, equity, chrono::Utc::now());
+        }
+    }
+
+    trading_state.atomic_state.update_heartbeat();
+    Ok(trading_state.kill_switch.lock().map_err(|e| e.to_string())?.record_report(report)?)
+}
+
+/// Lift the kill switch. Requires a token issued after the switch was engaged, i.e. a fresh
+/// login; bots stay stopped until started again
+#[tauri::command]
+pub async fn reset_kill_switch(
+    auth_token: String,
+    trading_state: State<'_, TradingState>
+) -> Result<KillSwitchStatus, String> {
+    let claims = trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "kill_switch")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+    let issued_at = chrono::DateTime::from_timestamp(claims.iat, 0)
+        .ok_or_else(|| "Invalid token issue time".to_string())?;
+
+    let status = {
+        let mut kill_switch = trading_state.kill_switch.lock().map_err(|e| e.to_string())?;
+        kill_switch.reset(&claims.sub, issued_at)?;
+        kill_switch.status().clone()
+    };
+
+    // The atomic emergency stop may already have been cleared
+    let _ = trading_state.atomic_state.reset_emergency_stop();
+    trading_state.swing_bot.write().await.reset_emergency_stop()
+        .map_err(|e| format!("Kill switch reset, but the bot emergency stop is still active: {}", e))?;
+    trading_state.atomic_state.update_heartbeat();
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn get_kill_switch_status(
+    trading_state: State<'_, TradingState>
+) -> Result<KillSwitchStatus, String> {
+    Ok(trading_state.kill_switch.lock().map_err(|e| e.to_string())?.status().clone())
+}
+
+/// Post-mortem reports of past engagements, newest first
+#[tauri::command]
+pub async fn get_kill_switch_reports(
+    limit: Option<usize>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<PostMortemReport>, String> {
+    Ok(trading_state.kill_switch.lock().map_err(|e| e.to_string())?.reports(limit.unwrap_or(20)))
+}
@@ -0,0 +1,329 @@
+use crate::TradingState;
+use crate::binance_client::ImprovedBinanceClient;
+use crate::logging::LogCategory;
+use crate::market_intelligence::{
+    ComprehensiveMarketIntelligenceReport, CorrelationAnalysis, IntelligenceReading, MarketIntelligenceConfig,
+    MarketIntelligenceEngine, MarketIntelligenceResult, NewsIntelligence, PredictiveInsights, SentimentAnalysis,
+    SentimentAnalysisReport, TimeRange,
+};
+use crate::market_intelligence::correlation_engine::{
+    CorrelationAnalysisReport, CorrelationBreakpoint, CorrelationMatrix, CorrelationMethod, LeadLagAnalysis,
+    RollingCorrelationPoint, MAX_LEAD_LAG,
+};
+use crate::market_intelligence::fear_greed::FearGreedReading;
+use crate::market_intelligence::news_intelligence::NewsAlert;
+use crate::models::AppSettings;
+use crate::trading_strategy::SwingTradingBot;
+use crate::{log_info, log_warning};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use tauri::State;
+use tokio::sync::RwLock;
+
+type SharedIntelligenceEngine = Arc<RwLock<Option<Arc<MarketIntelligenceEngine>>>>;
+
+async fn intelligence_engine(trading_state: &TradingState) -> Result<Arc<MarketIntelligenceEngine>, String> {
+    trading_state.market_intelligence.read().await
+        .clone()
+        .ok_or_else(|| "Market intelligence engine not initialized".to_string())
+}
+
+/// Refresh every engine on the configured interval and push fresh intelligence scores and the
+/// latest Fear & Greed reading to the swing bot. Stops once the engine is replaced or removed from state
+fn spawn_refresh_task(
+    engine: Arc<MarketIntelligenceEngine>,
+    slot: SharedIntelligenceEngine,
+    swing_bot: Arc<RwLock<SwingTradingBot>>,
+    symbols: Vec<String>,
+    interval_seconds: u64,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_seconds.max(1)));
+
+        loop {
+            interval.tick().await;
+            let current = slot.read().await.as_ref().is_some_and(|active| Arc::ptr_eq(active, &engine));
+            if !current {
+                break;
+            }
+
+            if let Err(e) = engine.update_intelligence_data().await {
+                log_warning!(LogCategory::DataProcessing, "Market intelligence refresh failed: {}", e);
+            }
+            if let Some(reading) = engine.sentiment_analyzer.read().await.latest_fear_greed() {
+                swing_bot.write().await.record_fear_greed(reading);
+            }
+            for symbol in &symbols {
+                match engine.intelligence_reading(symbol).await {
+                    Ok(reading) => swing_bot.write().await.set_intelligence_reading(reading),
+                    Err(e) => {
+                        log_warning!(LogCategory::DataProcessing, "Failed to score market intelligence for {}: {}", symbol, e);
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Start the market intelligence engine and its background refresh. With exchange settings the
+/// tradable symbols are loaded so news entities link to them
+#[tauri::command]
+pub async fn initialize_market_intelligence(
+    settings: Option<AppSettings>,
+    symbols: Option<Vec<String>>,
+    config: Option<MarketIntelligenceConfig>,
+    trading_state: State<'_, TradingState>
+) -> Result<(), String> {
+    let config = config.unwrap_or_default();
+    let engine = MarketIntelligenceEngine::new().await
+        .map_err(|e| format!("Failed to initialize market intelligence engine: {}", e))?;
+
+    if let Some(settings) = settings {
+        let client = ImprovedBinanceClient::new(&settings).map_err(|e| e.to_string())?;
+        match client.get_all_symbols().await {
+            Ok(universe) => engine.set_symbol_universe(&universe).await,
+            Err(e) => {
+                log_warning!(LogCategory::DataProcessing, "Failed to load exchange symbols for news linking: {}", e);
+            }
+        }
+    }
+
+    let engine = Arc::new(engine);
+    *trading_state.market_intelligence.write().await = Some(engine.clone());
+
+    let symbols = symbols.unwrap_or_else(|| vec!["BTCUSDT".to_string()]);
+    log_info!(LogCategory::Configuration, "Market intelligence started, refreshing every {}s for {:?}",
+        config.update_interval_seconds, symbols);
+    spawn_refresh_task(
+        engine,
+        trading_state.market_intelligence.clone(),
+        trading_state.swing_bot.clone(),
+        symbols,
+        config.update_interval_seconds,
+    );
+
+    Ok(())
+}
+
+/// Poll news feeds and refresh models now instead of waiting for the next interval
+#[tauri::command]
+pub async fn refresh_market_intelligence(
+    trading_state: State<'_, TradingState>
+) -> Result<(), String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    engine.update_intelligence_data().await.map_err(|e| e.to_string())
+}
+
+/// Combined sentiment, predictions, news and correlations for a symbol
+#[tauri::command]
+pub async fn get_market_intelligence(
+    symbol: String,
+    trading_state: State<'_, TradingState>
+) -> Result<MarketIntelligenceResult, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    engine.get_market_intelligence(&symbol.to_uppercase()).await.map_err(|e| e.to_string())
+}
+
+/// Latest intelligence score the bot's entry filter sees for a symbol
+#[tauri::command]
+pub async fn get_intelligence_score(
+    symbol: String,
+    trading_state: State<'_, TradingState>
+) -> Result<Option<IntelligenceReading>, String> {
+    let bot = trading_state.swing_bot.read().await;
+    Ok(bot.intelligence_reading(&symbol.to_uppercase()).cloned())
+}
+
+/// Social, news and institutional sentiment for a symbol
+#[tauri::command]
+pub async fn get_market_sentiment(
+    symbol: String,
+    trading_state: State<'_, TradingState>
+) -> Result<SentimentAnalysis, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    engine.get_market_sentiment(&symbol.to_uppercase()).await.map_err(|e| e.to_string())
+}
+
+/// Market-wide news sentiment over the last `hours` (default 24)
+#[tauri::command]
+pub async fn get_sentiment_report(
+    hours: Option<i64>,
+    trading_state: State<'_, TradingState>
+) -> Result<SentimentAnalysisReport, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    engine.analyze_sentiment(TimeRange::last_hours(hours.unwrap_or(24))).await.map_err(|e| e.to_string())
+}
+
+/// Recorded Fear & Greed readings, oldest first
+#[tauri::command]
+pub async fn get_fear_greed_history(
+    since: Option<DateTime<Utc>>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<FearGreedReading>, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    let sentiment = engine.sentiment_analyzer.read().await;
+    Ok(sentiment.fear_greed_history(since))
+}
+
+/// Rebuild the Fear & Greed history from stored daily klines. Returns the days recorded
+#[tauri::command]
+pub async fn backfill_fear_greed(
+    trading_state: State<'_, TradingState>
+) -> Result<usize, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    let sentiment = engine.sentiment_analyzer.read().await;
+    sentiment.backfill_fear_greed().map_err(|e| e.to_string())
+}
+
+/// Price, trend and volatility forecasts for a symbol
+#[tauri::command]
+pub async fn get_market_predictions(
+    symbol: String,
+    timeframes: Option<Vec<String>>,
+    trading_state: State<'_, TradingState>
+) -> Result<PredictiveInsights, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    let timeframes = timeframes.unwrap_or_else(|| MarketIntelligenceConfig::default().prediction_timeframes);
+    engine.get_predictions(&symbol.to_uppercase(), timeframes).await.map_err(|e| e.to_string())
+}
+
+/// Correlation of a symbol with BTC and ETH
+#[tauri::command]
+pub async fn get_correlation_analysis(
+    symbol: String,
+    trading_state: State<'_, TradingState>
+) -> Result<CorrelationAnalysis, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    engine.get_correlation_analysis(&symbol.to_uppercase()).await.map_err(|e| e.to_string())
+}
+
+/// Full correlation report across a set of assets over the last `hours` (default 30 days)
+#[tauri::command]
+pub async fn analyze_asset_correlations(
+    assets: Vec<String>,
+    hours: Option<i64>,
+    trading_state: State<'_, TradingState>
+) -> Result<CorrelationAnalysisReport, String> {
+    if assets.len() < 2 {
+        return Err("At least two assets are required".to_string());
+    }
+    let engine = intelligence_engine(&trading_state).await?;
+    let assets: Vec<String> = assets.iter().map(|a| a.to_uppercase()).collect();
+    engine.analyze_correlations(&assets, TimeRange::last_hours(hours.unwrap_or(30 * 24))).await
+        .map_err(|e| e.to_string())
+}
+
+/// Correlation matrix of stored log returns, Pearson unless another method is given
+#[tauri::command]
+pub async fn get_correlation_matrix(
+    assets: Vec<String>,
+    method: Option<CorrelationMethod>,
+    hours: Option<i64>,
+    trading_state: State<'_, TradingState>
+) -> Result<CorrelationMatrix, String> {
+    if assets.len() < 2 {
+        return Err("At least two assets are required".to_string());
+    }
+    let engine = intelligence_engine(&trading_state).await?;
+    let assets: Vec<String> = assets.iter().map(|a| a.to_uppercase()).collect();
+    let correlations = engine.correlation_engine.read().await;
+    correlations.calculate_correlation_matrix_with(
+        &assets,
+        TimeRange::last_hours(hours.unwrap_or(30 * 24)),
+        method.unwrap_or(CorrelationMethod::Pearson)
+    ).await.map_err(|e| e.to_string())
+}
+
+/// Rolling correlation of two assets over `window` bars, or EWMA correlation when
+/// `ewma_lambda` is given
+#[tauri::command]
+pub async fn get_rolling_correlation(
+    asset_a: String,
+    asset_b: String,
+    window: Option<usize>,
+    ewma_lambda: Option<f64>,
+    hours: Option<i64>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<RollingCorrelationPoint>, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    let (asset_a, asset_b) = (asset_a.to_uppercase(), asset_b.to_uppercase());
+    let time_range = TimeRange::last_hours(hours.unwrap_or(30 * 24));
+    let correlations = engine.correlation_engine.read().await;
+    let points = match ewma_lambda {
+        Some(lambda) => correlations.calculate_ewma_correlation(&asset_a, &asset_b, lambda, time_range).await,
+        None => correlations.calculate_rolling_correlation(&asset_a, &asset_b, window.unwrap_or(48), time_range).await,
+    };
+    points.map_err(|e| e.to_string())
+}
+
+/// Points where the correlation of two assets changed significantly
+#[tauri::command]
+pub async fn get_correlation_breakpoints(
+    asset_a: String,
+    asset_b: String,
+    hours: Option<i64>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<CorrelationBreakpoint>, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    let correlations = engine.correlation_engine.read().await;
+    correlations.detect_correlation_breakpoints(
+        &asset_a.to_uppercase(),
+        &asset_b.to_uppercase(),
+        TimeRange::last_hours(hours.unwrap_or(90 * 24))
+    ).await.map_err(|e| e.to_string())
+}
+
+/// Which of two assets moves first, from cross-correlations up to `max_lag` bars
+#[tauri::command]
+pub async fn get_lead_lag(
+    asset_a: String,
+    asset_b: String,
+    max_lag: Option<usize>,
+    hours: Option<i64>,
+    trading_state: State<'_, TradingState>
+) -> Result<LeadLagAnalysis, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    let correlations = engine.correlation_engine.read().await;
+    correlations.analyze_lead_lag(
+        &asset_a.to_uppercase(),
+        &asset_b.to_uppercase(),
+        max_lag.unwrap_or(MAX_LEAD_LAG),
+        TimeRange::last_hours(hours.unwrap_or(30 * 24))
+    ).await.map_err(|e| e.to_string())
+}
+
+/// Breaking news, sentiment trend and market-moving events for a symbol
+#[tauri::command]
+pub async fn get_news_intelligence(
+    symbol: String,
+    trading_state: State<'_, TradingState>
+) -> Result<NewsIntelligence, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    engine.get_news_intelligence(&symbol.to_uppercase()).await.map_err(|e| e.to_string())
+}
+
+/// Alerts for high-impact news from the last few hours
+#[tauri::command]
+pub async fn get_news_alerts(
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<NewsAlert>, String> {
+    let engine = intelligence_engine(&trading_state).await?;
+    let news = engine.news_intelligence.read().await;
+    news.get_breaking_news_alerts().await.map_err(|e| e.to_string())
+}
+
+/// Sentiment, news, correlation and predictions for a set of assets over the last `hours`
+#[tauri::command]
+pub async fn get_market_intelligence_report(
+    assets: Vec<String>,
+    hours: Option<i64>,
+    trading_state: State<'_, TradingState>
+) -> Result<ComprehensiveMarketIntelligenceReport, String> {
+    if assets.is_empty() {
+        return Err("At least one asset is required".to_string());
+    }
+    let engine = intelligence_engine(&trading_state).await?;
+    let assets: Vec<String> = assets.iter().map(|a| a.to_uppercase()).collect();
+    engine.generate_comprehensive_report(&assets, TimeRange::last_hours(hours.unwrap_or(24))).await
+        .map_err(|e| e.to_string())
+}
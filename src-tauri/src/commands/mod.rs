@@ -6,6 +6,19 @@ pub mod advanced_trading;
 pub mod backtesting;
 pub mod validation;
 pub mod gpu_native;
+pub mod regime;
+pub mod divergence;
+pub mod alerts;
+pub mod portfolio_heat;
+pub mod drawdown;
+pub mod kill_switch;
+pub mod risk_limits;
+pub mod anomalies;
+pub mod market_intelligence;
+pub mod pairs;
+pub mod signal_fusion;
+pub mod neural;
+pub mod signal_models;
 
 // Re-export all commands for easy access
 pub use system::*;
@@ -15,4 +28,17 @@ pub use bot::*;
 pub use advanced_trading::*;
 pub use backtesting::*;
 pub use gpu_native::*;
-pub use validation::*;
\ No newline at end of file
+pub use validation::*;
+pub use regime::*;
+pub use divergence::*;
+pub use alerts::*;
+pub use portfolio_heat::*;
+pub use drawdown::*;
+pub use kill_switch::*;
+pub use risk_limits::*;
+pub use anomalies::*;
+pub use market_intelligence::*;
+pub use pairs::*;
+pub use signal_fusion::*;
+pub use neural::*;
+pub use signal_models::*;
\ No newline at end of file
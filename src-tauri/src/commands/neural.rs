@@ -0,0 +1,99 @@
+use crate::TradingState;
+use crate::binance_client::ImprovedBinanceClient;
+use crate::gpu_neural_predictor::{train_model, GpuNeuralPredictor, NeuralTrainingConfig, PricePrediction, TrainingReport};
+use crate::kline_store::KlineStore;
+use crate::logging::LogCategory;
+use crate::models::AppSettings;
+use crate::{log_info, log_warning};
+use tauri::State;
+
+/// Klines fetched for a prediction; covers the longest indicator warmup with room to spare
+const PREDICTION_KLINES: u32 = 120;
+
+fn predictor_key(symbol: &str, interval: &str) -> String {
+    format!("{}_{}", symbol, interval)
+}
+
+/// Move the predictor's forward pass onto the shared GPU device when the accelerator is running
+async fn attach_gpu(predictor: &mut GpuNeuralPredictor, trading_state: &TradingState) {
+    let device = trading_state.gpu_accelerator.read().await
+        .as_ref()
+        .map(|accelerator| (accelerator.get_device(), accelerator.get_queue()));
+    if let Some((device, queue)) = device {
+        if let Err(e) = predictor.enable_gpu(device, queue).await {
+            log_warning!(LogCategory::GPU, "Neural predictor stays on the CPU: {}", e);
+        }
+    }
+}
+
+/// Train a neural price predictor on the symbol's stored klines and save it to its model file
+#[tauri::command]
+pub async fn train_neural_predictor(
+    auth_token: String,
+    symbol: String,
+    interval: String,
+    config: Option<NeuralTrainingConfig>,
+    trading_state: State<'_, TradingState>
+) -> Result<TrainingReport, String> {
+    trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    let symbol = symbol.to_uppercase();
+    let klines = KlineStore::open_default().map_err(|e| e.to_string())?
+        .load(&symbol, &interval)
+        .map_err(|e| e.to_string())?;
+    if klines.is_empty() {
+        return Err(format!("No stored klines for {} {}. Fetch klines first.", symbol, interval));
+    }
+
+    let model = {
+        let (symbol, interval) = (symbol.clone(), interval.clone());
+        tokio::task::spawn_blocking(move || train_model(&symbol, &interval, &klines, &config))
+            .await
+            .map_err(|e| format!("Training task failed: {}", e))?
+            .map_err(|e| format!("Failed to train neural predictor: {}", e))?
+    };
+    model.save().map_err(|e| format!("Failed to save neural model: {}", e))?;
+
+    let report = model.report.clone();
+    log_info!(LogCategory::Configuration, "Neural predictor for {} {} trained in {} epochs: validation RMSE {:.5} against {:.5} baseline, {:.1}% directional accuracy",
+        symbol, interval, report.epochs_run, report.validation_rmse, report.baseline_rmse, report.directional_accuracy * 100.0);
+
+    let mut predictor = GpuNeuralPredictor::new(model);
+    attach_gpu(&mut predictor, &trading_state).await;
+    trading_state.neural_predictors.write().await.insert(predictor_key(&symbol, &interval), predictor);
+
+    Ok(report)
+}
+
+/// Predict the next close for a symbol from its latest closed klines, loading the saved model on first use
+#[tauri::command]
+pub async fn predict_neural_price(
+    symbol: String,
+    interval: String,
+    settings: AppSettings,
+    trading_state: State<'_, TradingState>
+) -> Result<PricePrediction, String> {
+    let symbol = symbol.to_uppercase();
+    let key = predictor_key(&symbol, &interval);
+    if !trading_state.neural_predictors.read().await.contains_key(&key) {
+        let mut predictor = GpuNeuralPredictor::load(&symbol, &interval)
+            .map_err(|e| format!("Failed to load neural model: {}", e))?
+            .ok_or_else(|| format!("No neural model trained for {} {}", symbol, interval))?;
+        attach_gpu(&mut predictor, &trading_state).await;
+        trading_state.neural_predictors.write().await.insert(key.clone(), predictor);
+    }
+
+    let client = ImprovedBinanceClient::new(&settings).map_err(|e| e.to_string())?;
+    let mut klines = client.get_klines(&symbol, &interval, PREDICTION_KLINES).await
+        .map_err(|e| format!("Failed to fetch klines: {}", e))?;
+    // The newest kline is still forming
+    klines.pop();
+
+    let predictors = trading_state.neural_predictors.read().await;
+    let predictor = predictors.get(&key).ok_or_else(|| format!("No neural model trained for {} {}", symbol, interval))?;
+    predictor.predict(&klines).await.map_err(|e| e.to_string())
+}
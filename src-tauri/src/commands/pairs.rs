@@ -0,0 +1,300 @@
+use crate::TradingState;
+use crate::binance_client::ImprovedBinanceClient;
+use crate::kline_store::KlineStore;
+use crate::logging::LogCategory;
+use crate::models::{AppSettings, Trade, TradeSide, TradeStatus};
+use crate::pairs_trading::{
+    backtest_pair, scan_pairs, PairPosition, PairPrices, PairSignal, PairStatistics, PairTrader, PairsBacktestResult,
+    PairsConfig, DESK_OWNER,
+};
+use crate::{log_info, log_warning};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairStatus {
+    pub pair_id: String,
+    pub statistics: PairStatistics,
+    pub hedge_ratio: f64,
+    pub zscore: Option<f64>,
+    pub position: Option<PairPosition>,
+    pub last_bar: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairExecution {
+    pub pair_id: String,
+    pub bar_time: DateTime<Utc>,
+    pub signal: PairSignal,
+    /// Paper trades opened on entry, or the leg trades closed on exit
+    pub trades: Vec<Trade>,
+}
+
+fn pairs_config(config: Option<PairsConfig>) -> Result<PairsConfig, String> {
+    let config = config.unwrap_or_default();
+    config.validate()?;
+    Ok(config)
+}
+
+fn stored_pair(store: &KlineStore, symbol_y: &str, symbol_x: &str, interval: &str) -> Result<PairPrices, String> {
+    let klines_y = store.load(symbol_y, interval).map_err(|e| e.to_string())?;
+    let klines_x = store.load(symbol_x, interval).map_err(|e| e.to_string())?;
+    for (symbol, klines) in [(symbol_y, &klines_y), (symbol_x, &klines_x)] {
+        if klines.is_empty() {
+            return Err(format!("No stored klines for {} {}. Fetch klines first.", symbol, interval));
+        }
+    }
+    Ok(PairPrices::align(&klines_y, &klines_x))
+}
+
+/// Mark a leg's paper trade closed at `exit_price`, returning its realized P/L
+fn close_leg_trade(trade: &mut Trade, exit_price: Decimal) -> f64 {
+    let pnl = match trade.side {
+        TradeSide::Long | TradeSide::Buy => (exit_price - trade.entry_price) * trade.quantity,
+        TradeSide::Short | TradeSide::Sell => (trade.entry_price - exit_price) * trade.quantity,
+    };
+    trade.status = TradeStatus::Closed;
+    trade.exit_price = Some(exit_price);
+    trade.closed_at = Some(Utc::now());
+    trade.pnl = Some(pnl);
+    pnl.to_f64().unwrap_or(0.0)
+}
+
+/// Test every pair of `symbols` for cointegration on stored klines, strongest first. Without
+/// symbols every symbol stored at the configured interval is scanned
+#[tauri::command]
+pub async fn scan_cointegrated_pairs(
+    symbols: Option<Vec<String>>,
+    config: Option<PairsConfig>
+) -> Result<Vec<PairStatistics>, String> {
+    let config = pairs_config(config)?;
+    let store = KlineStore::open_default().map_err(|e| e.to_string())?;
+    let symbols = match symbols {
+        Some(symbols) => symbols.iter().map(|s| s.to_uppercase()).collect(),
+        None => store.list().map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|(_, interval)| *interval == config.interval)
+            .map(|(symbol, _)| symbol)
+            .collect::<Vec<String>>(),
+    };
+    if symbols.len() < 2 {
+        return Err(format!("At least two symbols with stored {} klines are required", config.interval));
+    }
+
+    scan_pairs(&store, &symbols, &config).map_err(|e| e.to_string())
+}
+
+/// Backtest the z-score spread strategy on a pair's stored klines
+#[tauri::command]
+pub async fn backtest_pairs_strategy(
+    symbol_y: String,
+    symbol_x: String,
+    config: Option<PairsConfig>,
+    initial_capital: Option<f64>
+) -> Result<PairsBacktestResult, String> {
+    let config = pairs_config(config)?;
+    let (symbol_y, symbol_x) = (symbol_y.to_uppercase(), symbol_x.to_uppercase());
+    let store = KlineStore::open_default().map_err(|e| e.to_string())?;
+    let prices = stored_pair(&store, &symbol_y, &symbol_x, &config.interval)?;
+
+    backtest_pair(&symbol_y, &symbol_x, &prices, &config, initial_capital.unwrap_or(10_000.0))
+        .map_err(|e| e.to_string())
+}
+
+/// Fit a pair on its latest stored klines and start paper trading it. Pairs that are not
+/// cointegrated or revert too slowly are rejected
+#[tauri::command]
+pub async fn add_trading_pair(
+    symbol_y: String,
+    symbol_x: String,
+    config: Option<PairsConfig>,
+    trading_state: State<'_, TradingState>
+) -> Result<PairStatistics, String> {
+    let config = pairs_config(config)?;
+    let (symbol_y, symbol_x) = (symbol_y.to_uppercase(), symbol_x.to_uppercase());
+    let store = KlineStore::open_default().map_err(|e| e.to_string())?;
+    let prices = stored_pair(&store, &symbol_y, &symbol_x, &config.interval)?;
+    let formation = prices.slice(prices.len().saturating_sub(config.formation_bars)..prices.len());
+
+    let trader = PairTrader::fit(&symbol_y, &symbol_x, &formation, config).map_err(|e| e.to_string())?;
+    if !trader.statistics.is_tradable(&trader.config) {
+        return Err(format!(
+            "{}/{} is not tradable: ADF {:.2} against {:.2} critical, half-life {}",
+            symbol_y,
+            symbol_x,
+            trader.statistics.adf_statistic,
+            trader.statistics.critical_values.five_percent,
+            trader.statistics.half_life_bars.map(|h| format!("{:.1} bars", h)).unwrap_or_else(|| "none".to_string())
+        ));
+    }
+
+    let statistics = trader.statistics.clone();
+    let id = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?
+        .add(trader)
+        .map_err(|e| e.to_string())?;
+    log_info!(LogCategory::Configuration, "Paper trading pair {} with hedge ratio {:.4}", id, statistics.hedge_ratio);
+    Ok(statistics)
+}
+
+/// Stop trading a pair. Pairs with an open position must be closed first
+#[tauri::command]
+pub async fn remove_trading_pair(
+    pair_id: String,
+    trading_state: State<'_, TradingState>
+) -> Result<(), String> {
+    let mut desk = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?;
+    let trader = desk.get(&pair_id).ok_or_else(|| format!("Pair {} is not being traded", pair_id))?;
+    if trader.position().is_some() {
+        return Err(format!("{} has an open position, wait for it to exit", pair_id));
+    }
+    desk.remove(&pair_id);
+    Ok(())
+}
+
+/// Traded pairs with their latest z-score and open position
+#[tauri::command]
+pub async fn get_trading_pairs(
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<PairStatus>, String> {
+    let desk = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?;
+    let mut pairs: Vec<PairStatus> = desk.traders()
+        .map(|(id, trader)| PairStatus {
+            pair_id: id.clone(),
+            statistics: trader.statistics.clone(),
+            hedge_ratio: trader.hedge_ratio(),
+            zscore: trader.zscore(),
+            position: trader.position().cloned(),
+            last_bar: trader.last_bar(),
+        })
+        .collect();
+    pairs.sort_by(|a, b| a.pair_id.cmp(&b.pair_id));
+    Ok(pairs)
+}
+
+/// Feed each traded pair its latest closed bar and execute any entry or exit as two paper
+/// orders. Entries are skipped while the kill switch is engaged or the drawdown governor
+/// halts them; exits always go through. An entry with a leg that fails to fill is unwound
+/// so the pair stays flat. The desk's equity is then reported to the drawdown governor
+#[tauri::command]
+pub async fn evaluate_trading_pairs(
+    settings: AppSettings,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<PairExecution>, String> {
+    let client = ImprovedBinanceClient::new(&settings).map_err(|e| e.to_string())?;
+    let allow_entries = !trading_state.kill_switch.lock().map_err(|e| e.to_string())?.is_engaged()
+        && trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?.status().allows_entries();
+    let pairs: Vec<(String, String, String, String)> = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?
+        .traders()
+        .map(|(id, trader)| (id.clone(), trader.symbol_y().to_string(), trader.symbol_x().to_string(), trader.config.interval.clone()))
+        .collect();
+
+    let mut executions = Vec::new();
+    for (pair_id, symbol_y, symbol_x, interval) in pairs {
+        // The newest kline is still forming, so trade on the one before it
+        let (kline_y, kline_x) = match (client.get_klines(&symbol_y, &interval, 2).await, client.get_klines(&symbol_x, &interval, 2).await) {
+            (Ok(y), Ok(x)) if y.len() == 2 && x.len() == 2 && y[0].open_time == x[0].open_time => (y[0].clone(), x[0].clone()),
+            _ => {
+                log_warning!(LogCategory::DataProcessing, "No matching closed {} bars for pair {}", interval, pair_id);
+                continue;
+            }
+        };
+
+        let signal = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?
+            .get_mut(&pair_id)
+            .and_then(|trader| trader.on_bar(kline_y.open_time, kline_y.close, kline_x.close, allow_entries));
+        let Some(signal) = signal else {
+            continue;
+        };
+
+        let mut abandoned = false;
+        let trades = match &signal {
+            PairSignal::Enter { legs, .. } => {
+                let mut fills = Vec::new();
+                for leg in legs {
+                    match client.simulate_order(&leg.order(), Some(leg.price)).await {
+                        Ok(trade) => fills.push(Some(trade)),
+                        Err(e) => {
+                            log_warning!(LogCategory::Trading, "Pair {} leg {} failed to fill: {}", pair_id, leg.symbol, e);
+                            fills.push(None);
+                        }
+                    }
+                }
+                let fill_ids: Vec<_> = fills.iter().map(|fill| fill.as_ref().map(|trade| trade.id)).collect();
+                let unwind = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?
+                    .get_mut(&pair_id)
+                    .map(|trader| trader.settle_entry(&fill_ids))
+                    .unwrap_or_default();
+                let mut trades: Vec<Trade> = fills.into_iter().flatten().collect();
+
+                // A one-legged spread is a naked position, so close whatever did fill
+                abandoned = fill_ids.iter().any(Option::is_none);
+                let mut realized = 0.0;
+                for (trade_id, leg) in unwind {
+                    let exit_price = client.simulate_order(&leg.order(), Some(leg.price)).await
+                        .map(|fill| fill.entry_price)
+                        .unwrap_or(leg.price);
+                    if let Some(trade) = trades.iter_mut().find(|trade| trade.id == trade_id) {
+                        realized += close_leg_trade(trade, exit_price);
+                    }
+                }
+                if abandoned {
+                    log_warning!(LogCategory::Trading, "Pair {} entry abandoned, unwound {} filled leg(s)", pair_id, trades.len());
+                    trading_state.pairs_desk.lock().map_err(|e| e.to_string())?.record_realized(realized);
+                }
+                trading_state.paper_trades.write().await.extend(trades.iter().cloned());
+                trades
+            }
+            PairSignal::Exit { legs, position, .. } => {
+                let mut fills = Vec::new();
+                for leg in legs {
+                    let price = client.simulate_order(&leg.order(), Some(leg.price)).await
+                        .map(|fill| fill.entry_price)
+                        .unwrap_or(leg.price);
+                    fills.push((leg.symbol.clone(), price));
+                }
+
+                let mut paper_trades = trading_state.paper_trades.write().await;
+                let mut closed = Vec::new();
+                let mut realized = 0.0;
+                for trade in paper_trades.iter_mut()
+                    .filter(|trade| position.trade_ids.contains(&trade.id) && matches!(trade.status, TradeStatus::Open))
+                {
+                    let Some((_, exit_price)) = fills.iter().find(|(symbol, _)| *symbol == trade.symbol) else {
+                        continue;
+                    };
+                    realized += close_leg_trade(trade, *exit_price);
+                    closed.push(trade.clone());
+                }
+                trading_state.pairs_desk.lock().map_err(|e| e.to_string())?.record_realized(realized);
+                closed
+            }
+        };
+
+        match &signal {
+            PairSignal::Enter { .. } if abandoned => {}
+            PairSignal::Enter { direction, zscore, .. } => {
+                log_info!(LogCategory::Trading, "Pair {} opened {:?} spread at z-score {:.2}", pair_id, direction, zscore);
+            }
+            PairSignal::Exit { reason, zscore, .. } => {
+                log_info!(LogCategory::Trading, "Pair {} closed at z-score {:.2}: {:?}", pair_id, zscore, reason);
+            }
+        }
+        executions.push(PairExecution {
+            pair_id,
+            bar_time: kline_y.open_time,
+            signal,
+            trades,
+        });
+    }
+
+    let desk = trading_state.pairs_desk.lock().map_err(|e| e.to_string())?;
+    if !desk.is_empty() {
+        let equity = desk.equity();
+        trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?
+            .update_equity(DESK_OWNER, equity, Utc::now());
+    }
+    Ok(executions)
+}
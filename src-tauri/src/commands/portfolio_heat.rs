@@ -0,0 +1,33 @@
+use crate::TradingState;
+use crate::advanced_risk_manager::{HeatLimits, RiskSummary};
+use rust_decimal::prelude::ToPrimitive;
+use tauri::State;
+
+/// Portfolio heat and correlated exposure per cluster across every bot, in percent of
+/// `equity` (defaults to the swing bot's account balance)
+#[tauri::command]
+pub async fn get_portfolio_heat(
+    equity: Option<f64>,
+    trading_state: State<'_, TradingState>
+) -> Result<RiskSummary, String> {
+    let equity = match equity {
+        Some(equity) if equity > 0.0 => equity,
+        Some(_) => return Err("Equity must be positive".to_string()),
+        None => trading_state.swing_bot.read().await.account_balance.to_f64().unwrap_or(0.0),
+    };
+
+    let mut tracker = trading_state.portfolio_heat.lock().map_err(|e| e.to_string())?;
+    tracker.refresh_from_store(&[]).map_err(|e| e.to_string())?;
+    Ok(tracker.get_risk_summary(equity))
+}
+
+/// Replace the heat and correlated exposure limits applied to new entries
+#[tauri::command]
+pub async fn set_heat_limits(
+    limits: HeatLimits,
+    trading_state: State<'_, TradingState>
+) -> Result<HeatLimits, String> {
+    let mut tracker = trading_state.portfolio_heat.lock().map_err(|e| e.to_string())?;
+    tracker.set_limits(limits)?;
+    Ok(tracker.limits().clone())
+}
@@ -0,0 +1,75 @@
+use crate::TradingState;
+use crate::kline_store::{KlineStore, klines_to_price_data};
+use crate::regime_detection::{RegimeClassifier, RegimeProbabilities};
+use tauri::State;
+use serde::{Serialize, Deserialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeModelInfo {
+    pub symbol: Option<String>,
+    pub interval: Option<String>,
+    pub window: usize,
+    pub training_samples: usize,
+    pub trained_at: DateTime<Utc>,
+    pub current_regime: Option<RegimeProbabilities>,
+}
+
+/// Train the regime classifier on stored klines and install it in the swing bot
+#[tauri::command]
+pub async fn train_regime_classifier(
+    auth_token: String,
+    symbol: String,
+    interval: String,
+    window: usize,
+    trading_state: State<'_, TradingState>
+) -> Result<RegimeModelInfo, String> {
+    trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let store = KlineStore::open_default().map_err(|e| e.to_string())?;
+    let klines = store.load(&symbol, &interval).map_err(|e| e.to_string())?;
+    if klines.is_empty() {
+        return Err(format!("No stored klines for {} {}. Fetch klines first.", symbol, interval));
+    }
+
+    let prices = klines_to_price_data(&klines);
+    let mut classifier = RegimeClassifier::fit(&prices, window)
+        .map_err(|e| format!("Failed to train regime classifier: {}", e))?;
+    classifier.symbol = Some(symbol.to_uppercase());
+    classifier.interval = Some(interval);
+    classifier.save().map_err(|e| format!("Failed to save regime model: {}", e))?;
+
+    let current_regime = classifier.classify(&prices);
+    let info = RegimeModelInfo {
+        symbol: classifier.symbol.clone(),
+        interval: classifier.interval.clone(),
+        window: classifier.window,
+        training_samples: classifier.training_samples,
+        trained_at: classifier.trained_at,
+        current_regime,
+    };
+
+    let mut bot = trading_state.swing_bot.write().await;
+    bot.set_regime_classifier(Some(classifier));
+
+    Ok(info)
+}
+
+/// Latest regime classification from the swing bot's price feed
+#[tauri::command]
+pub async fn get_market_regime(
+    trading_state: State<'_, TradingState>
+) -> Result<Option<RegimeModelInfo>, String> {
+    let bot = trading_state.swing_bot.read().await;
+
+    Ok(bot.regime_classifier().map(|classifier| RegimeModelInfo {
+        symbol: classifier.symbol.clone(),
+        interval: classifier.interval.clone(),
+        window: classifier.window,
+        training_samples: classifier.training_samples,
+        trained_at: classifier.trained_at,
+        current_regime: bot.current_regime.clone(),
+    }))
+}
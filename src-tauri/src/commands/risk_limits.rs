@@ -0,0 +1,65 @@
+use crate::TradingState;
+use crate::risk_limits::{EffectiveRiskLimits, RiskLimitAuditEntry, RiskLimitsConfig};
+use tauri::State;
+
+/// The risk limit file as currently loaded, including its revision
+#[tauri::command]
+pub async fn get_risk_limits_config(
+    trading_state: State<'_, TradingState>
+) -> Result<RiskLimitsConfig, String> {
+    let mut store = trading_state.risk_limits.lock().map_err(|e| e.to_string())?;
+    store.reload_if_changed()?;
+    Ok(store.config().clone())
+}
+
+/// Save new risk limit profiles; `config.revision` must be the revision that was loaded.
+/// Returns the new revision
+#[tauri::command]
+pub async fn update_risk_limits(
+    auth_token: String,
+    config: RiskLimitsConfig,
+    trading_state: State<'_, TradingState>
+) -> Result<u64, String> {
+    let claims = trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let mut store = trading_state.risk_limits.lock().map_err(|e| e.to_string())?;
+    store.reload_if_changed()?;
+    Ok(store.update(config, &claims.sub)?)
+}
+
+/// Limits in force for a bot and/or symbol. With neither given, returns the account limits
+/// followed by every configured bot and symbol
+#[tauri::command]
+pub async fn get_effective_risk_limits(
+    bot: Option<String>,
+    symbol: Option<String>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<EffectiveRiskLimits>, String> {
+    let mut store = trading_state.risk_limits.lock().map_err(|e| e.to_string())?;
+    store.reload_if_changed()?;
+    if bot.is_some() || symbol.is_some() {
+        return Ok(vec![store.effective(bot.as_deref(), symbol.as_deref())?]);
+    }
+
+    let config = store.config();
+    let mut effective = vec![store.effective(None, None)?];
+    for bot in config.bots.keys() {
+        effective.push(store.effective(Some(bot), None)?);
+    }
+    for symbol in config.symbols.keys() {
+        effective.push(store.effective(None, Some(symbol))?);
+    }
+    Ok(effective)
+}
+
+/// Risk limit changes, newest first
+#[tauri::command]
+pub async fn get_risk_limit_audit(
+    limit: Option<usize>,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<RiskLimitAuditEntry>, String> {
+    let store = trading_state.risk_limits.lock().map_err(|e| e.to_string())?;
+    Ok(store.audit(limit.unwrap_or(100)))
+}
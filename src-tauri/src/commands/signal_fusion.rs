@@ -0,0 +1,88 @@
+use crate::TradingState;
+use crate::commands::backtesting::BacktestEngineState;
+use crate::models::TradeSide;
+use crate::signal_fusion::{FusedSignal, FusionModel, FusionSample, SignalFusionConfig};
+use crate::logging::LogCategory;
+use crate::log_info;
+use tauri::State;
+use serde::{Serialize, Deserialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalFusionStatus {
+    pub config: SignalFusionConfig,
+    pub model: Option<FusionModel>,
+    /// Fused decision behind the bot's last entry check
+    pub last_decision: Option<FusedSignal>,
+    /// The latest LRO signal fused for a long and a short entry
+    pub current: Vec<FusedSignal>,
+}
+
+/// Signal fusion settings, model and per-component breakdown of the swing bot's decisions
+#[tauri::command]
+pub async fn get_signal_fusion_status(
+    trading_state: State<'_, TradingState>
+) -> Result<SignalFusionStatus, String> {
+    let bot = trading_state.swing_bot.read().await;
+    let current = bot.get_latest_signal()
+        .map(|signal| [TradeSide::Long, TradeSide::Short].into_iter()
+            .map(|side| bot.fuse_signal(signal, side))
+            .collect())
+        .unwrap_or_default();
+
+    Ok(SignalFusionStatus {
+        config: bot.config.signal_fusion.clone(),
+        model: bot.fusion_model().cloned(),
+        last_decision: bot.last_fused_signal.clone(),
+        current,
+    })
+}
+
+/// Train the logistic fusion model on the last backtest's trades and install it in the swing bot
+#[tauri::command]
+pub async fn train_signal_fusion_model(
+    auth_token: String,
+    l2: Option<f64>,
+    trading_state: State<'_, TradingState>,
+    backtest_state: State<'_, BacktestEngineState>
+) -> Result<FusionModel, String> {
+    trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let samples: Vec<FusionSample> = {
+        let state = backtest_state.read().await;
+        let engine = state.as_ref().ok_or("Backtesting engine not initialized")?;
+        engine.completed_trades().iter().filter_map(|trade| trade.fusion_sample()).collect()
+    };
+
+    let model = FusionModel::fit(&samples, l2.unwrap_or(0.01))
+        .map_err(|e| format!("Failed to train signal fusion model: {}", e))?;
+    model.save().map_err(|e| format!("Failed to save signal fusion model: {}", e))?;
+    log_info!(LogCategory::Configuration, "Signal fusion model trained on {} backtest trades (win rate {:.1}%)",
+        model.training_samples, model.win_rate * 100.0);
+
+    if let Some(engine) = backtest_state.write().await.as_mut() {
+        engine.set_fusion_model(Some(model.clone()));
+    }
+    trading_state.swing_bot.write().await.set_fusion_model(Some(model.clone()));
+
+    Ok(model)
+}
+
+/// Remove the trained fusion model; logistic fusion falls back to the configured weights
+#[tauri::command]
+pub async fn clear_signal_fusion_model(
+    auth_token: String,
+    trading_state: State<'_, TradingState>
+) -> Result<(), String> {
+    trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let path = FusionModel::default_path().map_err(|e| e.to_string())?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to remove signal fusion model: {}", e))?;
+    }
+    trading_state.swing_bot.write().await.set_fusion_model(None);
+    Ok(())
+}
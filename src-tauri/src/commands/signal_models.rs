@@ -0,0 +1,107 @@
+use crate::TradingState;
+use crate::binance_client::ImprovedBinanceClient;
+use crate::logging::LogCategory;
+use crate::model_inference::{feature_catalog, ModelLoadError, ModelManifest, ModelPrediction, ModelRegistry};
+use crate::models::AppSettings;
+use crate::{log_info, log_warning};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalModelInfo {
+    pub manifest: ModelManifest,
+    pub loaded_at: DateTime<Utc>,
+    pub required_bars: usize,
+    /// Selected by the swing bot's model signal filter
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalModelCatalog {
+    pub models_dir: String,
+    pub models: Vec<SignalModelInfo>,
+    pub load_errors: Vec<ModelLoadError>,
+    /// Feature names a manifest can request
+    pub features: Vec<String>,
+    /// The bot's latest model prediction
+    pub last_prediction: Option<ModelPrediction>,
+}
+
+async fn catalog(trading_state: &TradingState) -> Result<SignalModelCatalog, String> {
+    let registry = trading_state.model_registry.read().await;
+    let bot = trading_state.swing_bot.read().await;
+    let active_hash = bot.signal_model().map(|model| model.sha256().to_string());
+
+    Ok(SignalModelCatalog {
+        models_dir: ModelRegistry::default_dir().map_err(|e| e.to_string())?.display().to_string(),
+        models: registry.list().iter()
+            .map(|model| SignalModelInfo {
+                manifest: model.manifest.clone(),
+                loaded_at: model.loaded_at,
+                required_bars: model.required_bars(),
+                active: active_hash.as_deref() == Some(model.sha256()),
+            })
+            .collect(),
+        load_errors: registry.load_errors.clone(),
+        features: feature_catalog(),
+        last_prediction: bot.last_model_prediction.clone(),
+    })
+}
+
+/// Loaded ONNX models, manifests that failed to load and the features manifests can request
+#[tauri::command]
+pub async fn get_signal_models(
+    trading_state: State<'_, TradingState>
+) -> Result<SignalModelCatalog, String> {
+    catalog(&trading_state).await
+}
+
+/// Reload every manifest from the models directory and re-select the bot's model. If the
+/// selected model is gone or its hash no longer matches the pin, the bot's filter blocks entries
+#[tauri::command]
+pub async fn reload_signal_models(
+    auth_token: String,
+    trading_state: State<'_, TradingState>
+) -> Result<SignalModelCatalog, String> {
+    trading_state.auth_middleware
+        .validate_bot_operation(&auth_token, "configure_bot")
+        .map_err(|e| format!("Authentication failed: {}", e))?;
+
+    let registry = ModelRegistry::open_default().map_err(|e| format!("Failed to load models: {}", e))?;
+    log_info!(LogCategory::Configuration, "Loaded {} signal models ({} failed)", registry.list().len(), registry.load_errors.len());
+    {
+        let mut bot = trading_state.swing_bot.write().await;
+        let model = registry.resolve(&bot.config.model_signal).unwrap_or_else(|e| {
+            log_warning!(LogCategory::Configuration, "Model signal filter has no model, entries are blocked: {}", e);
+            None
+        });
+        bot.set_signal_model(model);
+    }
+    *trading_state.model_registry.write().await = registry;
+
+    catalog(&trading_state).await
+}
+
+/// Run a loaded model on the symbol's latest closed klines at the model's interval
+#[tauri::command]
+pub async fn predict_with_signal_model(
+    model: String,
+    version: Option<String>,
+    symbol: String,
+    settings: AppSettings,
+    trading_state: State<'_, TradingState>
+) -> Result<ModelPrediction, String> {
+    let model = trading_state.model_registry.read().await
+        .get(&model, version.as_deref())
+        .ok_or_else(|| format!("Model {} {} is not loaded", model, version.as_deref().unwrap_or("(latest)")))?;
+
+    let client = ImprovedBinanceClient::new(&settings).map_err(|e| e.to_string())?;
+    let limit = model.required_bars() as u32 + 1;
+    let mut klines = client.get_klines(&symbol.to_uppercase(), &model.manifest.interval, limit).await
+        .map_err(|e| format!("Failed to fetch klines: {}", e))?;
+    // The newest kline is still forming
+    klines.pop();
+
+    model.predict(&klines).map_err(|e| e.to_string())
+}
@@ -1,7 +1,13 @@
 use crate::{TradingState};
-use crate::models::{AppSettings, OrderRequest, Trade, AccountInfo, KlineData, SymbolInfo, MarketStats, OrderBookDepth, MarketDepthAnalysis, LiquidityLevel};
+use crate::models::{AppSettings, OrderRequest, OrderType, Trade, TradeSide, AccountInfo, KlineData, SymbolInfo, MarketStats, OrderBookDepth, MarketDepthAnalysis, LiquidityLevel};
 use crate::binance_client::ImprovedBinanceClient;
+use crate::logging::LogCategory;
+use crate::log_warning;
 use crate::errors::{TradingError, TradingResult, TradingLogicErrorType, AuthErrorType};
+use crate::advanced_trading::{AdvancedOrderRequest, AdvancedOrderType, OrderSide, RiskSeverity, TimeInForce};
+use crate::order_preview::{ExchangeFilters, FeeSchedule, OrderPreview, PreviewInputs};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
 use tauri::State;
 
 #[tauri::command]
@@ -39,7 +45,13 @@ pub async fn get_account_info(settings: AppSettings) -> Result<AccountInfo, Stri
 }
 
 #[tauri::command]
-pub async fn get_klines(settings: AppSettings, symbol: String, interval: String, limit: u32) -> Result<Vec<KlineData>, String> {
+pub async fn get_klines(
+    settings: AppSettings,
+    symbol: String,
+    interval: String,
+    limit: u32,
+    trading_state: State<'_, TradingState>
+) -> Result<Vec<KlineData>, String> {
     // Validate inputs
     if symbol.is_empty() {
         return Err(TradingError::validation_error(
@@ -60,8 +72,18 @@ pub async fn get_klines(settings: AppSettings, symbol: String, interval: String,
     let client = ImprovedBinanceClient::new(&settings)
         .map_err(|e| TradingError::config_error("api_settings".to_string(), e.to_string()))?;
 
-    client.get_klines(&symbol, &interval, limit).await
-        .map_err(|e| TradingError::internal_error(e.to_string()).into())
+    let klines = client.get_klines(&symbol, &interval, limit).await
+        .map_err(|e| TradingError::internal_error(e.to_string()))?;
+
+    // Keep a local copy of newly closed klines for model training and historical replays
+    let recorded = trading_state.kline_recorder.lock()
+        .map_err(|e| e.to_string())?
+        .record(&symbol, &interval, &klines, chrono::Utc::now());
+    if let Err(e) = recorded {
+        log_warning!(LogCategory::DataProcessing, "Failed to store klines for {} {}: {}", symbol, interval, e);
+    }
+
+    Ok(klines)
 }
 
 #[tauri::command]
@@ -88,6 +110,8 @@ pub async fn place_order(
         ).into());
     }
 
+    trading_state.kill_switch.lock().map_err(|e| e.to_string())?.ensure_disengaged()?;
+
     // Safety check - live trading is permanently disabled
     if !paper_trading {
         eprintln!("CRITICAL SAFETY: Live trading attempted but is PERMANENTLY DISABLED");
@@ -129,6 +153,146 @@ pub async fn place_order(
     Ok(trade)
 }
 
+/// What-if preview of an order without placing it: exchange filter rounding, the estimated
+/// fill, slippage and fees, and the resulting position, portfolio heat, VaR, buying power and
+/// any risk limit breaches
+#[tauri::command]
+pub async fn preview_order(
+    settings: AppSettings,
+    order: OrderRequest,
+    fees: Option<FeeSchedule>,
+    trading_state: State<'_, TradingState>
+) -> Result<OrderPreview, String> {
+    if order.symbol.is_empty() {
+        return Err(TradingError::validation_error(
+            "symbol".to_string(),
+            "Order symbol cannot be empty".to_string(),
+            None
+        ).into());
+    }
+
+    if order.quantity <= Decimal::ZERO {
+        return Err(TradingError::validation_error(
+            "quantity".to_string(),
+            "Order quantity must be greater than zero".to_string(),
+            Some(order.quantity.to_string())
+        ).into());
+    }
+
+    let client = ImprovedBinanceClient::new(&settings)
+        .map_err(|e| TradingError::config_error("api_settings".to_string(), e.to_string()))?;
+
+    let mut warnings = Vec::new();
+    let filters = match client.get_symbol_info(&order.symbol).await {
+        Ok(info) => ExchangeFilters::from_symbol_filters(&info.filters),
+        Err(e) => {
+            warnings.push(format!("Exchange filters unavailable, price and quantity not rounded: {}", e));
+            ExchangeFilters::default()
+        }
+    };
+    let book = match client.get_order_book(&order.symbol, 100).await {
+        Ok(book) => Some(book),
+        Err(e) => {
+            warnings.push(format!("Order book unavailable, fill estimated at the last price: {}", e));
+            None
+        }
+    };
+    let last_price = match client.get_klines(&order.symbol, "1m", 1).await {
+        Ok(klines) if !klines.is_empty() => klines[0].close,
+        _ => book.as_ref()
+            .and_then(|book| Some((book.bids.first()?.price + book.asks.first()?.price) / Decimal::from(2)))
+            .ok_or_else(|| TradingError::trading_error(
+                TradingLogicErrorType::SymbolNotFound,
+                format!("No market data available for symbol: {}", order.symbol),
+                Some(order.symbol.clone())
+            ))?,
+    };
+
+    // Combined equity across bots when the governor has seen any, else the bot's balance
+    let governor_status = trading_state.drawdown_governor.lock().map_err(|e| e.to_string())?.status().clone();
+    let equity = if governor_status.equity > 0.0 {
+        governor_status.equity
+    } else {
+        trading_state.swing_bot.read().await.account_balance.to_f64().unwrap_or(0.0)
+    };
+    let limits = trading_state.risk_limits.lock().map_err(|e| e.to_string())?
+        .current(None, Some(&order.symbol));
+    let open_trades = trading_state.paper_trades.read().await.clone();
+
+    let mut preview = OrderPreview::build(&order, PreviewInputs {
+        filters,
+        book: book.as_ref(),
+        last_price,
+        fees: fees.unwrap_or_default(),
+        open_trades: &open_trades,
+        equity,
+        limits: &limits,
+    });
+    preview.warnings.extend(warnings);
+
+    if let Err(e) = trading_state.kill_switch.lock().map_err(|e| e.to_string())?.ensure_disengaged() {
+        preview.add_breach("kill_switch", e.to_string(), None, None);
+    }
+    if preview.is_increasing() {
+        if !governor_status.allows_entries() {
+            preview.add_breach("drawdown_governor", governor_status.reason.clone(), Some(governor_status.drawdown_percent), None);
+        }
+        if order.stop_loss_percent.is_none() {
+            preview.warnings.push("No stop loss, the whole notional counts as portfolio heat".to_string());
+        }
+        let check = {
+            let mut tracker = trading_state.portfolio_heat.lock().map_err(|e| e.to_string())?;
+            if let Err(e) = tracker.refresh_from_store(std::slice::from_ref(&preview.symbol)) {
+                preview.warnings.push(format!("Could not load correlation history for {}: {}", preview.symbol, e));
+            }
+            tracker.check_entry(&preview.symbol, preview.notional.to_f64().unwrap_or(0.0), preview.risk_amount(order.stop_loss_percent), equity)
+        };
+        preview.add_heat(check);
+    }
+
+    let engine = trading_state.advanced_trading_engine.read().await;
+    match engine.as_ref() {
+        Some(engine) => {
+            let advanced_order = AdvancedOrderRequest {
+                symbol: preview.symbol.clone(),
+                side: match order.side {
+                    TradeSide::Long => OrderSide::Long,
+                    TradeSide::Short => OrderSide::Short,
+                    TradeSide::Buy => OrderSide::Buy,
+                    TradeSide::Sell => OrderSide::Sell,
+                },
+                order_type: match order.order_type {
+                    OrderType::Market => AdvancedOrderType::Market,
+                    OrderType::Limit => AdvancedOrderType::Limit,
+                },
+                quantity: preview.quantity,
+                price: Some(preview.price.unwrap_or(preview.fill.average_price)),
+                time_in_force: TimeInForce::GTC,
+                reduce_only: !preview.is_increasing(),
+                post_only: false,
+                client_order_id: None,
+                risk_limits: None,
+            };
+            match engine.preview_order_risk(&advanced_order).await {
+                Ok((var_before, max_portfolio_var, assessment)) => {
+                    preview.add_var(var_before, assessment.var_1_day, max_portfolio_var);
+                    // VaR breaches are already recorded by add_var
+                    for warning in assessment.risk_warnings.iter()
+                        .filter(|w| matches!(w.severity, RiskSeverity::High | RiskSeverity::Critical))
+                        .filter(|w| w.warning_type != "VaR Limit Breach")
+                    {
+                        preview.add_breach(&warning.warning_type, warning.message.clone(), None, None);
+                    }
+                }
+                Err(e) => preview.warnings.push(format!("Risk engine assessment failed: {}", e)),
+            }
+        }
+        None => preview.warnings.push("Advanced trading not initialized, VaR impact not estimated".to_string()),
+    }
+
+    Ok(preview)
+}
+
 #[tauri::command]
 pub async fn get_paper_trades(trading_state: State<'_, TradingState>) -> Result<Vec<Trade>, String> {
     let trades = trading_state.paper_trades.read().await;
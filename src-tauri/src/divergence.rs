@@ -0,0 +1,636 @@
+// Divergence Detection
+// Finds regular and hidden divergences between price pivots and oscillator series
+// (LRO, RSI, MACD histogram, OBV) for use as entry filters and alert triggers
+
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::models::{PriceData, TradeSide};
+use crate::multi_timeframe::normalized_lro;
+
+/// Divergence patterns between price and an oscillator
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum DivergenceKind {
+    /// Price makes a lower low while the oscillator makes a higher low
+    RegularBullish,
+    /// Price makes a higher high while the oscillator makes a lower high
+    RegularBearish,
+    /// Price makes a higher low while the oscillator makes a lower low
+    HiddenBullish,
+    /// Price makes a lower high while the oscillator makes a higher high
+    HiddenBearish,
+}
+
+impl DivergenceKind {
+    pub const ALL: [DivergenceKind; 4] = [
+        DivergenceKind::RegularBullish,
+        DivergenceKind::RegularBearish,
+        DivergenceKind::HiddenBullish,
+        DivergenceKind::HiddenBearish,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DivergenceKind::RegularBullish => "Regular Bullish",
+            DivergenceKind::RegularBearish => "Regular Bearish",
+            DivergenceKind::HiddenBullish => "Hidden Bullish",
+            DivergenceKind::HiddenBearish => "Hidden Bearish",
+        }
+    }
+
+    pub fn is_bullish(&self) -> bool {
+        matches!(self, DivergenceKind::RegularBullish | DivergenceKind::HiddenBullish)
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        matches!(self, DivergenceKind::HiddenBullish | DivergenceKind::HiddenBearish)
+    }
+
+    /// Trade direction the divergence favours
+    pub fn favoured_side(&self) -> TradeSide {
+        if self.is_bullish() {
+            TradeSide::Long
+        } else {
+            TradeSide::Short
+        }
+    }
+}
+
+/// Oscillators the detector can compare against price
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum OscillatorSource {
+    Lro,
+    Rsi,
+    MacdHistogram,
+    Obv,
+}
+
+impl OscillatorSource {
+    pub const ALL: [OscillatorSource; 4] = [
+        OscillatorSource::Lro,
+        OscillatorSource::Rsi,
+        OscillatorSource::MacdHistogram,
+        OscillatorSource::Obv,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OscillatorSource::Lro => "LRO",
+            OscillatorSource::Rsi => "RSI",
+            OscillatorSource::MacdHistogram => "MACD Histogram",
+            OscillatorSource::Obv => "OBV",
+        }
+    }
+}
+
+/// Pivot and oscillator settings for divergence detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DivergenceConfig {
+    /// Bars on the left that a pivot must exceed
+    pub pivot_left: usize,
+    /// Bars on the right that a pivot must exceed; a pivot is only known this many bars later
+    pub pivot_right: usize,
+    /// Minimum bars between the two compared pivots
+    pub min_pivot_distance: usize,
+    /// Maximum bars between the two compared pivots
+    pub max_pivot_distance: usize,
+    /// Divergences scoring below this strength (0-1) are discarded
+    pub min_strength: f64,
+    pub detect_hidden: bool,
+    pub lro_period: usize,
+    pub rsi_period: usize,
+    pub macd_fast: usize,
+    pub macd_slow: usize,
+    pub macd_signal: usize,
+}
+
+impl Default for DivergenceConfig {
+    fn default() -> Self {
+        Self {
+            pivot_left: 3,
+            pivot_right: 3,
+            min_pivot_distance: 5,
+            max_pivot_distance: 60,
+            min_strength: 0.1,
+            detect_hidden: true,
+            lro_period: 20,
+            rsi_period: 14,
+            macd_fast: 12,
+            macd_slow: 26,
+            macd_signal: 9,
+        }
+    }
+}
+
+impl DivergenceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pivot_left == 0 || self.pivot_right == 0 {
+            return Err("Divergence pivot_left and pivot_right must be at least 1".to_string());
+        }
+        if self.min_pivot_distance == 0 || self.min_pivot_distance > self.max_pivot_distance {
+            return Err("Divergence min_pivot_distance must be between 1 and max_pivot_distance".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.min_strength) {
+            return Err("Divergence min_strength must be between 0 and 1".to_string());
+        }
+        if self.lro_period < 2 || self.rsi_period < 2 {
+            return Err("Divergence LRO and RSI periods must be at least 2".to_string());
+        }
+        if self.macd_fast == 0 || self.macd_signal == 0 || self.macd_fast >= self.macd_slow {
+            return Err("Divergence MACD periods must satisfy 0 < fast < slow and signal > 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A confirmed swing high or low with the oscillator reading at the same bar
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Pivot {
+    pub index: usize,
+    pub price: f64,
+    pub oscillator: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Divergence {
+    pub kind: DivergenceKind,
+    pub source: OscillatorSource,
+    pub start: Pivot,
+    pub end: Pivot,
+    /// Bar at which the end pivot was confirmed and the divergence became observable
+    pub confirmed_index: usize,
+    /// 0-1 score from the size of the price and oscillator moves
+    pub strength: f64,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Entry filter that blocks trades against recent divergences
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DivergenceFilter {
+    pub enabled: bool,
+    pub sources: Vec<OscillatorSource>,
+    /// Only divergences confirmed within this many bars are considered
+    pub lookback_bars: usize,
+    /// Minimum strength for a divergence to affect entries
+    pub min_strength: f64,
+    /// Require a divergence favouring the entry instead of only vetoing opposing ones
+    pub require_confirming: bool,
+    pub detection: DivergenceConfig,
+}
+
+impl Default for DivergenceFilter {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sources: vec![OscillatorSource::Lro, OscillatorSource::Rsi],
+            lookback_bars: 10,
+            min_strength: 0.3,
+            require_confirming: false,
+            detection: DivergenceConfig::default(),
+        }
+    }
+}
+
+impl DivergenceFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.sources.is_empty() {
+            return Err("Divergence filter requires at least one oscillator source".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.min_strength) {
+            return Err("Divergence filter min_strength must be between 0 and 1".to_string());
+        }
+        self.detection.validate()
+    }
+
+    /// Whether an entry on `side` is allowed given recently confirmed divergences
+    pub fn allows_entry(&self, side: &TradeSide, recent: &[Divergence]) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let relevant = recent.iter().filter(|d| d.strength >= self.min_strength);
+        let mut confirming = false;
+        for divergence in relevant {
+            if divergence.kind.favoured_side() == *side {
+                confirming = true;
+            } else {
+                return false;
+            }
+        }
+        confirming || !self.require_confirming
+    }
+}
+
+pub struct DivergenceDetector {
+    config: DivergenceConfig,
+}
+
+impl Default for DivergenceDetector {
+    fn default() -> Self {
+        Self::new(DivergenceConfig::default())
+    }
+}
+
+impl DivergenceDetector {
+    pub fn new(config: DivergenceConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &DivergenceConfig {
+        &self.config
+    }
+
+    /// Find all divergences between price pivots and an oscillator series.
+    ///
+    /// `highs`, `lows` and `oscillator` must be aligned bar-for-bar; non-finite
+    /// oscillator values (indicator warm-up) are treated as missing.
+    pub fn detect(&self, highs: &[f64], lows: &[f64], oscillator: &[f64], source: OscillatorSource) -> Vec<Divergence> {
+        let len = highs.len().min(lows.len()).min(oscillator.len());
+        let (highs, lows, oscillator) = (&highs[..len], &lows[..len], &oscillator[..len]);
+        let scale = oscillator_scale(oscillator);
+
+        let mut divergences = Vec::new();
+        let pivot_highs = self.find_pivots(highs, oscillator, true);
+        let pivot_lows = self.find_pivots(lows, oscillator, false);
+
+        for pair in pivot_lows.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            let kind = if end.price < start.price && end.oscillator > start.oscillator {
+                Some(DivergenceKind::RegularBullish)
+            } else if end.price > start.price && end.oscillator < start.oscillator {
+                Some(DivergenceKind::HiddenBullish)
+            } else {
+                None
+            };
+            self.push_divergence(&mut divergences, kind, start, end, scale, source);
+        }
+
+        for pair in pivot_highs.windows(2) {
+            let (start, end) = (&pair[0], &pair[1]);
+            let kind = if end.price > start.price && end.oscillator < start.oscillator {
+                Some(DivergenceKind::RegularBearish)
+            } else if end.price < start.price && end.oscillator > start.oscillator {
+                Some(DivergenceKind::HiddenBearish)
+            } else {
+                None
+            };
+            self.push_divergence(&mut divergences, kind, start, end, scale, source);
+        }
+
+        divergences.sort_by_key(|d| d.confirmed_index);
+        divergences
+    }
+
+    /// Divergences confirmed within the last `within_bars` bars of the series
+    pub fn recent(&self, highs: &[f64], lows: &[f64], oscillator: &[f64], source: OscillatorSource, within_bars: usize) -> Vec<Divergence> {
+        let len = highs.len().min(lows.len()).min(oscillator.len());
+        if len == 0 {
+            return Vec::new();
+        }
+        self.detect(highs, lows, oscillator, source)
+            .into_iter()
+            .filter(|d| len - 1 - d.confirmed_index <= within_bars)
+            .collect()
+    }
+
+    /// Most recently confirmed divergence within `within_bars` bars, if any
+    pub fn latest(&self, highs: &[f64], lows: &[f64], oscillator: &[f64], source: OscillatorSource, within_bars: usize) -> Option<Divergence> {
+        self.recent(highs, lows, oscillator, source, within_bars).pop()
+    }
+
+    /// Compute an oscillator from OHLCV data, aligned with `data` (NaN during warm-up)
+    pub fn oscillator_series(&self, data: &[PriceData], source: OscillatorSource) -> Vec<f64> {
+        let closes: Vec<f64> = data.iter().map(|p| p.close.to_f64().unwrap_or(0.0)).collect();
+        match source {
+            OscillatorSource::Lro => lro_series(&closes, self.config.lro_period),
+            OscillatorSource::Rsi => rsi_series(&closes, self.config.rsi_period),
+            OscillatorSource::MacdHistogram => macd_histogram_series(
+                &closes,
+                self.config.macd_fast,
+                self.config.macd_slow,
+                self.config.macd_signal,
+            ),
+            OscillatorSource::Obv => obv_series(data),
+        }
+    }
+
+    /// Detect divergences against each requested oscillator computed from `data`
+    pub fn scan(&self, data: &[PriceData], sources: &[OscillatorSource]) -> Vec<Divergence> {
+        let mut divergences = Vec::new();
+        for &source in sources {
+            let oscillator = self.oscillator_series(data, source);
+            divergences.extend(self.scan_with(data, &oscillator, source));
+        }
+        divergences.sort_by_key(|d| d.confirmed_index);
+        divergences
+    }
+
+    /// Detect divergences against a precomputed oscillator aligned with `data`
+    pub fn scan_with(&self, data: &[PriceData], oscillator: &[f64], source: OscillatorSource) -> Vec<Divergence> {
+        let highs: Vec<f64> = data.iter().map(|p| p.high.to_f64().unwrap_or(0.0)).collect();
+        let lows: Vec<f64> = data.iter().map(|p| p.low.to_f64().unwrap_or(0.0)).collect();
+
+        let mut divergences = self.detect(&highs, &lows, oscillator, source);
+        for divergence in &mut divergences {
+            divergence.timestamp = data.get(divergence.confirmed_index).map(|p| p.timestamp);
+        }
+        divergences
+    }
+
+    fn find_pivots(&self, prices: &[f64], oscillator: &[f64], highs: bool) -> Vec<Pivot> {
+        let (left, right) = (self.config.pivot_left, self.config.pivot_right);
+        if prices.len() < left + right + 1 {
+            return Vec::new();
+        }
+
+        let mut pivots = Vec::new();
+        for i in left..prices.len() - right {
+            if !oscillator[i].is_finite() {
+                continue;
+            }
+            let value = prices[i];
+            let is_pivot = if highs {
+                prices[i - left..i].iter().all(|&p| value > p)
+                    && prices[i + 1..=i + right].iter().all(|&p| value >= p)
+            } else {
+                prices[i - left..i].iter().all(|&p| value < p)
+                    && prices[i + 1..=i + right].iter().all(|&p| value <= p)
+            };
+            if is_pivot {
+                pivots.push(Pivot { index: i, price: value, oscillator: oscillator[i] });
+            }
+        }
+        pivots
+    }
+
+    fn push_divergence(
+        &self,
+        divergences: &mut Vec<Divergence>,
+        kind: Option<DivergenceKind>,
+        start: &Pivot,
+        end: &Pivot,
+        oscillator_scale: f64,
+        source: OscillatorSource,
+    ) {
+        let kind = match kind {
+            Some(kind) if self.config.detect_hidden || !kind.is_hidden() => kind,
+            _ => return,
+        };
+
+        let distance = end.index - start.index;
+        if distance < self.config.min_pivot_distance || distance > self.config.max_pivot_distance {
+            return;
+        }
+
+        let strength = divergence_strength(start, end, oscillator_scale);
+        if strength < self.config.min_strength {
+            return;
+        }
+
+        divergences.push(Divergence {
+            kind,
+            source,
+            start: start.clone(),
+            end: end.clone(),
+            confirmed_index: end.index + self.config.pivot_right,
+            strength,
+            timestamp: None,
+        });
+    }
+}
+
+/// Standard deviation of the finite oscillator values, used to compare moves across oscillators
+fn oscillator_scale(oscillator: &[f64]) -> f64 {
+    let values: Vec<f64> = oscillator.iter().copied().filter(|v| v.is_finite()).collect();
+    if values.len() < 2 {
+        return 1.0;
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    if std_dev > f64::EPSILON { std_dev } else { 1.0 }
+}
+
+/// Blend of the relative price move (saturating at 2%) and the oscillator move
+/// in standard deviations (saturating at 2σ)
+fn divergence_strength(start: &Pivot, end: &Pivot, oscillator_scale: f64) -> f64 {
+    let price_move = if start.price.abs() > f64::EPSILON {
+        ((end.price - start.price) / start.price).abs()
+    } else {
+        0.0
+    };
+    let oscillator_move = (end.oscillator - start.oscillator).abs() / oscillator_scale;
+
+    let price_score = (price_move / 0.02).min(1.0);
+    let oscillator_score = (oscillator_move / 2.0).min(1.0);
+    (0.4 * price_score + 0.6 * oscillator_score).clamp(0.0, 1.0)
+}
+
+/// Rolling range-normalised LRO, matching the multi-timeframe confirmation oscillator
+pub fn lro_series(closes: &[f64], period: usize) -> Vec<f64> {
+    (0..closes.len())
+        .map(|i| normalized_lro(&closes[..=i], period).unwrap_or(f64::NAN))
+        .collect()
+}
+
+/// Wilder-smoothed RSI
+pub fn rsi_series(closes: &[f64], period: usize) -> Vec<f64> {
+    let mut series = vec![f64::NAN; closes.len()];
+    if period == 0 || closes.len() <= period {
+        return series;
+    }
+
+    let mut avg_gain = 0.0;
+    let mut avg_loss = 0.0;
+    for i in 1..=period {
+        let change = closes[i] - closes[i - 1];
+        avg_gain += change.max(0.0);
+        avg_loss += (-change).max(0.0);
+    }
+    avg_gain /= period as f64;
+    avg_loss /= period as f64;
+
+    let rsi = |gain: f64, loss: f64| {
+        if loss <= f64::EPSILON {
+            if gain <= f64::EPSILON { 50.0 } else { 100.0 }
+        } else {
+            100.0 - 100.0 / (1.0 + gain / loss)
+        }
+    };
+
+    series[period] = rsi(avg_gain, avg_loss);
+    for i in period + 1..closes.len() {
+        let change = closes[i] - closes[i - 1];
+        avg_gain = (avg_gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        avg_loss = (avg_loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+        series[i] = rsi(avg_gain, avg_loss);
+    }
+    series
+}
+
+/// MACD histogram (MACD line minus its signal line)
+pub fn macd_histogram_series(closes: &[f64], fast: usize, slow: usize, signal: usize) -> Vec<f64> {
+    let mut series = vec![f64::NAN; closes.len()];
+    if fast == 0 || slow == 0 || signal == 0 || closes.is_empty() {
+        return series;
+    }
+
+    let fast_ema = ema_series(closes, fast);
+    let slow_ema = ema_series(closes, slow);
+    let macd: Vec<f64> = fast_ema.iter().zip(&slow_ema).map(|(f, s)| f - s).collect();
+    let signal_line = ema_series(&macd, signal);
+
+    // Both EMAs need to be seeded before the histogram is meaningful
+    let warm_up = slow.max(fast) + signal - 2;
+    for i in warm_up..closes.len() {
+        series[i] = macd[i] - signal_line[i];
+    }
+    series
+}
+
+/// On-balance volume
+pub fn obv_series(data: &[PriceData]) -> Vec<f64> {
+    let mut series = Vec::with_capacity(data.len());
+    let mut obv = 0.0;
+    for (i, price) in data.iter().enumerate() {
+        if i > 0 {
+            let volume = price.volume.to_f64().unwrap_or(0.0);
+            if price.close > data[i - 1].close {
+                obv += volume;
+            } else if price.close < data[i - 1].close {
+                obv -= volume;
+            }
+        }
+        series.push(obv);
+    }
+    series
+}
+
+fn ema_series(values: &[f64], period: usize) -> Vec<f64> {
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut series = Vec::with_capacity(values.len());
+    let mut ema = match values.first() {
+        Some(&first) => first,
+        None => return series,
+    };
+    for &value in values {
+        ema = alpha * value + (1.0 - alpha) * ema;
+        series.push(ema);
+    }
+    series
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    /// Two V-shaped dips centred on bars 10 and 30 with the given lows, flat at 100 elsewhere
+    fn double_dip(first_low: f64, second_low: f64) -> Vec<f64> {
+        (0..45).map(|i: i32| {
+            let (centre, low) = if i < 20 { (10, first_low) } else { (30, second_low) };
+            let distance = (i - centre).abs() as f64;
+            if distance < 5.0 {
+                low + (100.0 - low) * distance / 5.0
+            } else {
+                100.0
+            }
+        }).collect()
+    }
+
+    fn with_oscillator_at(len: usize, values: &[(usize, f64)]) -> Vec<f64> {
+        let mut oscillator = vec![0.0; len];
+        for &(i, v) in values {
+            oscillator[i] = v;
+        }
+        oscillator
+    }
+
+    #[test]
+    fn test_regular_bullish_divergence() {
+        let lows = double_dip(90.0, 88.0);
+        let oscillator = with_oscillator_at(lows.len(), &[(10, -1.0), (30, -0.4)]);
+
+        let divergences = DivergenceDetector::default().detect(&lows, &lows, &oscillator, OscillatorSource::Lro);
+        let bullish: Vec<_> = divergences.iter().filter(|d| d.kind.is_bullish()).collect();
+
+        assert_eq!(bullish.len(), 1);
+        assert_eq!(bullish[0].kind, DivergenceKind::RegularBullish);
+        assert_eq!((bullish[0].start.index, bullish[0].end.index), (10, 30));
+        // The second low is only known once the right-hand pivot bars have printed
+        assert_eq!(bullish[0].confirmed_index, 33);
+        assert!(bullish[0].strength > 0.0 && bullish[0].strength <= 1.0);
+    }
+
+    #[test]
+    fn test_hidden_divergence_can_be_disabled() {
+        let lows = double_dip(88.0, 92.0);
+        let oscillator = with_oscillator_at(lows.len(), &[(10, -0.4), (30, -1.0)]);
+
+        let detector = DivergenceDetector::default();
+        let found = detector.detect(&lows, &lows, &oscillator, OscillatorSource::Rsi);
+        assert!(found.iter().any(|d| d.kind == DivergenceKind::HiddenBullish));
+
+        let regular_only = DivergenceDetector::new(DivergenceConfig { detect_hidden: false, ..Default::default() });
+        assert!(regular_only.detect(&lows, &lows, &oscillator, OscillatorSource::Rsi).is_empty());
+    }
+
+    #[test]
+    fn test_no_divergence_before_pivot_confirmed() {
+        let lows = double_dip(90.0, 88.0);
+        let oscillator = with_oscillator_at(lows.len(), &[(10, -1.0), (30, -0.4)]);
+        let detector = DivergenceDetector::default();
+
+        // Bar 32 is one bar short of confirming the second pivot
+        assert!(detector.detect(&lows[..33], &lows[..33], &oscillator[..33], OscillatorSource::Lro).is_empty());
+        assert!(detector.latest(&lows[..34], &lows[..34], &oscillator[..34], OscillatorSource::Lro, 0).is_some());
+    }
+
+    #[test]
+    fn test_filter_blocks_opposing_entries() {
+        let start = Utc::now();
+        let prices: Vec<PriceData> = double_dip(90.0, 88.0).iter().enumerate().map(|(i, &c)| {
+            let price = Decimal::from_f64(c).unwrap_or_default();
+            PriceData {
+                timestamp: start + Duration::minutes(i as i64),
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: Decimal::from(1),
+            }
+        }).collect();
+        let oscillator = with_oscillator_at(prices.len(), &[(10, -1.0), (30, -0.4)]);
+
+        let detector = DivergenceDetector::default();
+        let divergences = detector.scan_with(&prices, &oscillator, OscillatorSource::Lro);
+        assert_eq!(divergences[0].timestamp, Some(prices[33].timestamp));
+
+        let filter = DivergenceFilter { enabled: true, min_strength: 0.0, ..Default::default() };
+        assert!(filter.allows_entry(&TradeSide::Long, &divergences));
+        assert!(!filter.allows_entry(&TradeSide::Short, &divergences));
+        assert!(!DivergenceFilter { require_confirming: true, ..filter }.allows_entry(&TradeSide::Long, &[]));
+    }
+
+    #[test]
+    fn test_oscillator_series_alignment() {
+        let closes: Vec<f64> = (0..60).map(|i| 100.0 + (i as f64 * 0.3).sin() * 5.0).collect();
+
+        let rsi = rsi_series(&closes, 14);
+        assert_eq!(rsi.len(), closes.len());
+        assert!(rsi[13].is_nan());
+        assert!(rsi[14..].iter().all(|v| (0.0..=100.0).contains(v)));
+
+        let histogram = macd_histogram_series(&closes, 12, 26, 9);
+        assert_eq!(histogram.len(), closes.len());
+        assert!(histogram[32].is_nan() && histogram[33].is_finite());
+
+        let lro = lro_series(&closes, 20);
+        assert!(lro[18].is_nan() && lro[19].is_finite());
+    }
+}
@@ -0,0 +1,412 @@
+// Drawdown Governor
+// Scales new positions down in steps as account drawdown from the high-water mark deepens,
+// halts entries at a hard limit and optionally trades the equity curve; shared by every bot
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{TradingError, TradingResult};
+use crate::logging::LogCategory;
+use crate::persistence;
+use crate::{log_info, log_warning};
+
+/// Equity samples kept for the equity-curve filter
+const MAX_EQUITY_POINTS: usize = 1000;
+/// Step changes kept in the event log
+const MAX_EVENTS: usize = 200;
+
+/// Governor shared by every bot placing entries
+pub type SharedDrawdownGovernor = Arc<Mutex<DrawdownGovernor>>;
+
+/// Size multiplier applied once drawdown reaches `drawdown_percent`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DrawdownStep {
+    pub drawdown_percent: f64,
+    pub size_multiplier: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawdownGovernorConfig {
+    pub enabled: bool,
+    /// Steps in increasing drawdown order with non-increasing multipliers
+    pub steps: Vec<DrawdownStep>,
+    /// Drawdown at which new entries stop entirely, in percent
+    pub halt_drawdown_percent: f64,
+    /// Drawdown must recover this far past a step before the step is lifted, in percent
+    pub recovery_buffer_percent: f64,
+    /// Pause new entries while equity is below its own moving average
+    pub equity_curve_filter: bool,
+    /// Samples in the equity moving average
+    pub equity_ma_period: usize,
+    /// Minutes between equity curve samples
+    pub sample_minutes: i64,
+}
+
+impl Default for DrawdownGovernorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            steps: vec![
+                DrawdownStep { drawdown_percent: 5.0, size_multiplier: 0.75 },
+                DrawdownStep { drawdown_percent: 8.0, size_multiplier: 0.5 },
+                DrawdownStep { drawdown_percent: 12.0, size_multiplier: 0.25 },
+            ],
+            // Matches GlobalRiskLimits::max_drawdown_percent
+            halt_drawdown_percent: 15.0,
+            recovery_buffer_percent: 1.0,
+            equity_curve_filter: false,
+            equity_ma_period: 20,
+            sample_minutes: 60,
+        }
+    }
+}
+
+impl DrawdownGovernorConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.halt_drawdown_percent <= 0.0 || self.halt_drawdown_percent > 100.0 {
+            return Err("Halt drawdown must be between 0 and 100 percent".to_string());
+        }
+        let mut previous = DrawdownStep { drawdown_percent: 0.0, size_multiplier: 1.0 };
+        for step in &self.steps {
+            if step.drawdown_percent <= previous.drawdown_percent || step.drawdown_percent >= self.halt_drawdown_percent {
+                return Err("Drawdown steps must increase and stay below the halt drawdown".to_string());
+            }
+            if step.size_multiplier <= 0.0 || step.size_multiplier > previous.size_multiplier {
+                return Err("Drawdown step multipliers must be positive and non-increasing".to_string());
+            }
+            previous = step.clone();
+        }
+        if self.recovery_buffer_percent < 0.0 || self.recovery_buffer_percent > 10.0 {
+            return Err("Recovery buffer must be between 0 and 10 percent".to_string());
+        }
+        if self.equity_ma_period < 2 {
+            return Err("Equity moving average needs at least 2 samples".to_string());
+        }
+        if self.sample_minutes < 1 {
+            return Err("Equity sample interval must be at least one minute".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EquityPoint {
+    pub timestamp: DateTime<Utc>,
+    pub equity: f64,
+}
+
+/// Current governor state as applied to new entries
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct GovernorStatus {
+    pub equity: f64,
+    pub high_water_mark: f64,
+    pub drawdown_percent: f64,
+    /// 0 at full size, otherwise the 1-based index of the active step
+    pub step: usize,
+    /// Multiplier for new position sizes; 0 while entries are halted or paused
+    pub size_multiplier: f64,
+    pub halted: bool,
+    pub equity_ma: Option<f64>,
+    pub below_equity_ma: bool,
+    pub reason: String,
+}
+
+impl GovernorStatus {
+    pub fn allows_entries(&self) -> bool {
+        self.size_multiplier > 0.0
+    }
+}
+
+/// A change in the multiplier applied to new entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GovernorEvent {
+    pub timestamp: DateTime<Utc>,
+    pub from_multiplier: f64,
+    pub to_multiplier: f64,
+    pub drawdown_percent: f64,
+    pub equity: f64,
+    pub high_water_mark: f64,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct GovernorStore {
+    #[serde(default)]
+    config: Option<DrawdownGovernorConfig>,
+    high_water_mark: f64,
+    #[serde(default)]
+    equity_curve: Vec<EquityPoint>,
+    #[serde(default)]
+    events: Vec<GovernorEvent>,
+}
+
+/// Tracks combined equity across bots against its high-water mark
+#[derive(Debug)]
+pub struct DrawdownGovernor {
+    config: DrawdownGovernorConfig,
+    equity_by_owner: HashMap<String, f64>,
+    high_water_mark: f64,
+    equity_curve: VecDeque<EquityPoint>,
+    status: GovernorStatus,
+    events: VecDeque<GovernorEvent>,
+    path: Option<PathBuf>,
+}
+
+impl DrawdownGovernor {
+    /// In-memory governor that is never written to disk
+    pub fn new(config: DrawdownGovernorConfig) -> Self {
+        Self {
+            config,
+            equity_by_owner: HashMap::new(),
+            high_water_mark: 0.0,
+            equity_curve: VecDeque::new(),
+            status: GovernorStatus { size_multiplier: 1.0, ..Default::default() },
+            events: VecDeque::new(),
+            path: None,
+        }
+    }
+
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("drawdown_governor.json")
+    }
+
+    /// Load the configuration, high-water mark and equity curve from `path`; changes are saved back
+    pub fn open(path: PathBuf) -> TradingResult<Self> {
+        let store: GovernorStore = persistence::read_json(&path)?.unwrap_or_default();
+        let mut governor = Self::new(store.config.unwrap_or_default());
+        governor.high_water_mark = store.high_water_mark;
+        governor.equity_curve = store.equity_curve.into_iter().collect();
+        governor.events = store.events.into_iter().collect();
+        governor.path = Some(path);
+        Ok(governor)
+    }
+
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let store = GovernorStore {
+            config: Some(self.config.clone()),
+            high_water_mark: self.high_water_mark,
+            equity_curve: self.equity_curve.iter().cloned().collect(),
+            events: self.events.iter().cloned().collect(),
+        };
+        persistence::write_json(path, &store)
+    }
+
+    pub fn config(&self) -> &DrawdownGovernorConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: DrawdownGovernorConfig) -> TradingResult<()> {
+        config.validate()
+            .map_err(|e| TradingError::validation_error("drawdown_governor".to_string(), e, None))?;
+        self.config = config;
+        self.evaluate(Utc::now(), "configuration changed");
+        self.save()
+    }
+
+    pub fn status(&self) -> &GovernorStatus {
+        &self.status
+    }
+
+    pub fn events(&self) -> impl Iterator<Item = &GovernorEvent> {
+        self.events.iter().rev()
+    }
+
+    pub fn equity_curve(&self) -> impl Iterator<Item = &EquityPoint> {
+        self.equity_curve.iter()
+    }
+
+    /// Multiplier for new position sizes (1 when the governor is disabled)
+    pub fn size_multiplier(&self) -> f64 {
+        if self.config.enabled { self.status.size_multiplier } else { 1.0 }
+    }
+
+    /// Report one bot's current equity; combined equity is the sum over every owner
+    pub fn update_equity(&mut self, owner: &str, equity: f64, timestamp: DateTime<Utc>) -> &GovernorStatus {
+        if !equity.is_finite() || equity < 0.0 {
+            return &self.status;
+        }
+        self.equity_by_owner.insert(owner.to_string(), equity);
+        let total: f64 = self.equity_by_owner.values().sum();
+        let mut dirty = false;
+        if total > self.high_water_mark {
+            self.high_water_mark = total;
+        }
+
+        let due = self.equity_curve.back()
+            .is_none_or(|last| timestamp - last.timestamp >= Duration::minutes(self.config.sample_minutes));
+        if due {
+            self.equity_curve.push_back(EquityPoint { timestamp, equity: total });
+            while self.equity_curve.len() > MAX_EQUITY_POINTS {
+                self.equity_curve.pop_front();
+            }
+            dirty = true;
+        }
+
+        if self.evaluate(timestamp, "equity update") || dirty {
+            if let Err(e) = self.save() {
+                log_warning!(LogCategory::RiskManagement, "Failed to save drawdown governor state: {}", e);
+            }
+        }
+        &self.status
+    }
+
+    /// Restart drawdown measurement from the current combined equity
+    pub fn reset_high_water_mark(&mut self) -> TradingResult<()> {
+        self.high_water_mark = self.equity_by_owner.values().sum();
+        self.evaluate(Utc::now(), "high-water mark reset");
+        self.save()
+    }
+
+    /// Recompute the status; returns whether the entry multiplier changed
+    fn evaluate(&mut self, timestamp: DateTime<Utc>, trigger: &str) -> bool {
+        let equity: f64 = self.equity_by_owner.values().sum();
+        let drawdown = if self.high_water_mark > 0.0 {
+            ((self.high_water_mark - equity) / self.high_water_mark * 100.0).max(0.0)
+        } else {
+            0.0
+        };
+        let buffer = self.config.recovery_buffer_percent;
+
+        // Tighten as soon as a threshold is reached; loosen only once drawdown recovers past the buffer
+        let reached = self.config.steps.iter().take_while(|s| drawdown >= s.drawdown_percent).count();
+        let step = if reached >= self.status.step {
+            reached
+        } else {
+            self.config.steps.iter().take_while(|s| drawdown > s.drawdown_percent - buffer).count()
+                .clamp(reached, self.status.step.min(self.config.steps.len()))
+        };
+        let halted = drawdown >= self.config.halt_drawdown_percent
+            || (self.status.halted && drawdown > self.config.halt_drawdown_percent - buffer);
+
+        let period = self.config.equity_ma_period;
+        let equity_ma = (self.equity_curve.len() >= period).then(|| {
+            self.equity_curve.iter().rev().take(period).map(|p| p.equity).sum::<f64>() / period as f64
+        });
+        let below_equity_ma = equity_ma.is_some_and(|ma| equity < ma);
+
+        let step_multiplier = step.checked_sub(1).map_or(1.0, |i| self.config.steps[i].size_multiplier);
+        let (size_multiplier, reason) = if !self.config.enabled {
+            (1.0, "governor disabled".to_string())
+        } else if halted {
+            (0.0, format!("entries halted at {:.2}% drawdown (limit {:.2}%)", drawdown, self.config.halt_drawdown_percent))
+        } else if self.config.equity_curve_filter && below_equity_ma {
+            (0.0, format!("equity {:.2} below its {}-sample average {:.2}", equity, period, equity_ma.unwrap_or(0.0)))
+        } else if step > 0 {
+            (step_multiplier, format!("{:.2}% drawdown, sizing x{:.2} from the {:.1}% step",
+                drawdown, step_multiplier, self.config.steps[step - 1].drawdown_percent))
+        } else {
+            (1.0, format!("{:.2}% drawdown, full size", drawdown))
+        };
+
+        let previous = self.status.size_multiplier;
+        self.status = GovernorStatus {
+            equity,
+            high_water_mark: self.high_water_mark,
+            drawdown_percent: drawdown,
+            step,
+            size_multiplier,
+            halted,
+            equity_ma,
+            below_equity_ma,
+            reason,
+        };
+
+        let changed = (previous - size_multiplier).abs() > f64::EPSILON;
+        if changed {
+            if size_multiplier < previous {
+                log_warning!(LogCategory::RiskManagement, "Drawdown governor x{:.2} -> x{:.2} ({}): {}", previous, size_multiplier, trigger, self.status.reason);
+            } else {
+                log_info!(LogCategory::RiskManagement, "Drawdown governor x{:.2} -> x{:.2} ({}): {}", previous, size_multiplier, trigger, self.status.reason);
+            }
+            self.events.push_back(GovernorEvent {
+                timestamp,
+                from_multiplier: previous,
+                to_multiplier: size_multiplier,
+                drawdown_percent: drawdown,
+                equity,
+                high_water_mark: self.high_water_mark,
+                reason: self.status.reason.clone(),
+            });
+            while self.events.len() > MAX_EVENTS {
+                self.events.pop_front();
+            }
+        }
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(hour: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(hour)
+    }
+
+    #[test]
+    fn test_steps_tighten_and_recover_with_buffer() {
+        let mut governor = DrawdownGovernor::new(DrawdownGovernorConfig::default());
+        governor.update_equity("swing_bot", 6_000.0, at(0));
+        governor.update_equity("advanced_trading", 4_000.0, at(0));
+        assert_eq!(governor.status().high_water_mark, 10_000.0);
+        assert_eq!(governor.size_multiplier(), 1.0);
+
+        // 9% combined drawdown passes the 5% and 8% steps
+        assert_eq!(governor.update_equity("swing_bot", 5_100.0, at(1)).step, 2);
+        assert_eq!(governor.size_multiplier(), 0.5);
+
+        // 7.5% is back under the 8% step but inside the 1% recovery buffer
+        assert_eq!(governor.update_equity("swing_bot", 5_250.0, at(2)).step, 2);
+        // 6.5% clears the buffer and lifts to the 5% step only
+        assert_eq!(governor.update_equity("swing_bot", 5_350.0, at(3)).step, 1);
+        assert_eq!(governor.size_multiplier(), 0.75);
+
+        let events: Vec<&GovernorEvent> = governor.events().collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].to_multiplier, 0.75);
+    }
+
+    #[test]
+    fn test_halt_and_reset() {
+        let mut governor = DrawdownGovernor::new(DrawdownGovernorConfig::default());
+        governor.update_equity("swing_bot", 10_000.0, at(0));
+        let status = governor.update_equity("swing_bot", 8_400.0, at(1));
+        assert!(status.halted);
+        assert!(!status.allows_entries());
+
+        governor.reset_high_water_mark().unwrap();
+        assert_eq!(governor.status().drawdown_percent, 0.0);
+        assert_eq!(governor.size_multiplier(), 1.0);
+    }
+
+    #[test]
+    fn test_equity_curve_filter() {
+        let config = DrawdownGovernorConfig { equity_curve_filter: true, equity_ma_period: 3, ..Default::default() };
+        let mut governor = DrawdownGovernor::new(config);
+        for (hour, equity) in [10_000.0, 10_200.0, 10_400.0].iter().enumerate() {
+            governor.update_equity("swing_bot", *equity, at(hour as i64));
+        }
+        assert!(governor.status().allows_entries());
+
+        // 2% off the high is no step, but equity falls below the 3-sample average
+        let status = governor.update_equity("swing_bot", 10_192.0, at(3));
+        assert!(status.below_equity_ma);
+        assert_eq!(status.step, 0);
+        assert_eq!(governor.size_multiplier(), 0.0);
+
+        assert!(DrawdownGovernorConfig { steps: vec![DrawdownStep { drawdown_percent: 20.0, size_multiplier: 0.5 }], ..Default::default() }
+            .validate().is_err());
+    }
+}
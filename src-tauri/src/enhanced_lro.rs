@@ -7,6 +7,7 @@ use rust_decimal::Decimal;
 use rust_decimal::prelude::*;
 use serde::{Serialize, Deserialize};
 use crate::models::PriceData;
+use crate::divergence::{Divergence, DivergenceDetector, OscillatorSource};
 
 /// Enhanced LRO configuration with dynamic parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +20,13 @@ pub struct LROConfig {
     pub volatility_adjustment: bool,
     pub multi_timeframe: bool,
     pub divergence_detection: bool,
+    /// Signal line smoothing used by the range-normalized `LroCache` engine
+    #[serde(default = "default_signal_period")]
+    pub signal_period: usize,
+}
+
+fn default_signal_period() -> usize {
+    9
 }
 
 impl Default for LROConfig {
@@ -32,6 +40,7 @@ impl Default for LROConfig {
             volatility_adjustment: true,
             multi_timeframe: true,
             divergence_detection: true,
+            signal_period: default_signal_period(),
         }
     }
 }
@@ -46,6 +55,19 @@ pub enum LROSignal {
     StrongSell { confidence: f64, deviation: f64 },
 }
 
+impl LROSignal {
+    /// Deviation from the regression line carried by every signal
+    pub fn deviation(&self) -> f64 {
+        match self {
+            LROSignal::StrongBuy { deviation, .. } |
+            LROSignal::Buy { deviation, .. } |
+            LROSignal::Neutral { deviation } |
+            LROSignal::Sell { deviation, .. } |
+            LROSignal::StrongSell { deviation, .. } => *deviation,
+        }
+    }
+}
+
 /// Linear regression results with statistical measures
 #[derive(Debug, Clone)]
 struct RegressionResult {
@@ -394,37 +416,15 @@ impl EnhancedLRO {
         (base_overbought * multiplier, base_oversold * multiplier)
     }
 
-    /// Check for divergence patterns
-    pub fn detect_divergence(&self, price_highs: &[f64], price_lows: &[f64], lro_values: &[f64]) -> Option<String> {
-        if !self.config.divergence_detection || price_highs.len() < 2 || lro_values.len() < 2 {
+    /// Most recent price/LRO divergence over aligned high, low and LRO series
+    pub fn detect_divergence(&self, price_highs: &[f64], price_lows: &[f64], lro_values: &[f64]) -> Option<Divergence> {
+        if !self.config.divergence_detection {
             return None;
         }
 
-        // Bullish divergence: price makes lower low, LRO makes higher low
-        if price_lows.len() >= 2 && lro_values.len() >= 2 {
-            let recent_price_low = price_lows[price_lows.len() - 1];
-            let prev_price_low = price_lows[price_lows.len() - 2];
-            let recent_lro = lro_values[lro_values.len() - 1];
-            let prev_lro = lro_values[lro_values.len() - 2];
-
-            if recent_price_low < prev_price_low && recent_lro > prev_lro {
-                return Some("Bullish Divergence".to_string());
-            }
-        }
-
-        // Bearish divergence: price makes higher high, LRO makes lower high
-        if price_highs.len() >= 2 && lro_values.len() >= 2 {
-            let recent_price_high = price_highs[price_highs.len() - 1];
-            let prev_price_high = price_highs[price_highs.len() - 2];
-            let recent_lro = lro_values[lro_values.len() - 1];
-            let prev_lro = lro_values[lro_values.len() - 2];
-
-            if recent_price_high > prev_price_high && recent_lro < prev_lro {
-                return Some("Bearish Divergence".to_string());
-            }
-        }
-
-        None
+        DivergenceDetector::default()
+            .detect(price_highs, price_lows, lro_values, OscillatorSource::Lro)
+            .pop()
     }
 
     /// Get current LRO statistics for analysis
@@ -4,9 +4,11 @@ use tokio::sync::RwLock;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::*;
 use chrono::{DateTime, Utc, Duration};
+use serde::{Deserialize, Serialize};
 use crate::models::{Trade, AccountInfo, PriceData, OrderRequest, TradeSide};
 use crate::config::TradingConfig;
 use crate::errors::{TradingError, TradingResult, TradingLogicErrorType};
+use crate::risk_limits::SharedRiskLimits;
 
 /// Risk assessment levels
 #[derive(Debug, Clone, PartialEq)]
@@ -18,7 +20,7 @@ pub enum RiskLevel {
 }
 
 /// Risk management rules and limits
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RiskLimits {
     pub max_position_size_percent: f64,
     pub max_daily_loss_percent: f64,
@@ -134,6 +136,22 @@ impl EnhancedRiskManager {
         }
     }
 
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Replace the limits without resetting session state
+    pub fn set_limits(&mut self, limits: RiskLimits) {
+        self.limits = limits;
+    }
+
+    /// Load the trade limits configured for `bot` and `symbol`, picking up any edit to the
+    /// risk limit file
+    pub fn refresh_limits(&mut self, risk_limits: &SharedRiskLimits, bot: Option<&str>, symbol: Option<&str>) {
+        let profile = risk_limits.lock().unwrap_or_else(|e| e.into_inner()).current(bot, symbol);
+        self.set_limits(profile.trade);
+    }
+
     /// Assess risk for a potential trade
     pub async fn assess_trade_risk(
         &self,
@@ -1,423 +1,1093 @@
 // GPU-Accelerated Neural Network for Price Prediction
-// Uses WGPU for massive parallel matrix operations
+// Multilayer perceptron trained on the CPU from kline features, with an optional WGPU forward pass
 
-use wgpu::util::DeviceExt;
+use crate::errors::{TradingError, TradingResult};
+use crate::logging::LogCategory;
+use crate::market_intelligence::predictive_analytics::normal_cdf;
+use crate::models::KlineData;
+use crate::persistence;
+use crate::{log_info, log_warning};
 use bytemuck::{Pod, Zeroable};
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use std::sync::Arc;
-use serde::{Serialize, Deserialize};
-use rand::Rng;
+use wgpu::util::DeviceExt;
 
-/// Neural network layer data for GPU
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct GpuNeuron {
-    weights: [f32; 32],  // Max 32 inputs per neuron
-    bias: f32,
-    activation: f32,
-    gradient: f32,
-    _padding: f32,  // Ensure 16-byte alignment
+/// Fewest labelled feature rows a model is trained on
+pub const MIN_TRAINING_SAMPLES: usize = 100;
+
+/// Directional confidence needed before a prediction suggests a trade
+const ACTION_CONFIDENCE: f64 = 0.6;
+
+/// Workgroup size of the `forward_pass` shader entry point
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Hidden layer activation. The output layer is always linear
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Activation {
+    #[default]
+    Tanh,
+    Relu,
+    Linear,
 }
 
-/// Matrix for GPU operations
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct GpuMatrix {
-    data: [f32; 1024],  // Flattened matrix data
-    rows: u32,
-    cols: u32,
-    _padding: [u32; 2],
+impl Activation {
+    fn apply(self, x: f32) -> f32 {
+        match self {
+            Activation::Tanh => x.tanh(),
+            Activation::Relu => x.max(0.0),
+            Activation::Linear => x,
+        }
+    }
+
+    /// Derivative expressed in terms of the activated output
+    fn derivative(self, output: f32) -> f32 {
+        match self {
+            Activation::Tanh => 1.0 - output * output,
+            Activation::Relu => if output > 0.0 { 1.0 } else { 0.0 },
+            Activation::Linear => 1.0,
+        }
+    }
+
+    /// Activation selector understood by `shaders/neural_network.wgsl`
+    fn shader_code(self) -> u32 {
+        match self {
+            Activation::Linear => 0,
+            Activation::Tanh => 1,
+            Activation::Relu => 2,
+        }
+    }
+}
+
+/// Fully connected layer with row-major `outputs x inputs` weights
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DenseLayer {
+    pub inputs: usize,
+    pub outputs: usize,
+    pub weights: Vec<f32>,
+    pub biases: Vec<f32>,
+}
+
+impl DenseLayer {
+    fn zeros(inputs: usize, outputs: usize) -> Self {
+        Self { inputs, outputs, weights: vec![0.0; inputs * outputs], biases: vec![0.0; outputs] }
+    }
+
+    /// Xavier weight initialization
+    fn xavier(inputs: usize, outputs: usize, rng: &mut StdRng) -> Self {
+        let scale = (6.0 / (inputs + outputs) as f32).sqrt();
+        Self {
+            inputs,
+            outputs,
+            weights: (0..inputs * outputs).map(|_| rng.gen_range(-scale..scale)).collect(),
+            biases: vec![0.0; outputs],
+        }
+    }
+
+    fn forward(&self, input: &[f32], activation: Activation) -> Vec<f32> {
+        (0..self.outputs)
+            .map(|o| {
+                let row = &self.weights[o * self.inputs..(o + 1) * self.inputs];
+                let sum = self.biases[o] + row.iter().zip(input).map(|(w, x)| w * x).sum::<f32>();
+                activation.apply(sum)
+            })
+            .collect()
+    }
+}
+
+/// Multilayer perceptron evaluated on the CPU, or on the GPU through `GpuForwardPass`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NeuralNetwork {
+    pub input_size: usize,
+    pub hidden_sizes: Vec<usize>,
+    pub output_size: usize,
+    pub activation: Activation,
+    pub layers: Vec<DenseLayer>,
+}
+
+impl NeuralNetwork {
+    pub fn new(input_size: usize, hidden_sizes: Vec<usize>, output_size: usize, activation: Activation, seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut sizes = vec![input_size];
+        sizes.extend(hidden_sizes.iter().copied());
+        sizes.push(output_size);
+        let layers = sizes.windows(2).map(|pair| DenseLayer::xavier(pair[0], pair[1], &mut rng)).collect();
+
+        Self { input_size, hidden_sizes, output_size, activation, layers }
+    }
+
+    /// Check that the layers match the declared architecture, e.g. after loading a model file
+    pub fn validate(&self) -> TradingResult<()> {
+        let mut sizes = vec![self.input_size];
+        sizes.extend(self.hidden_sizes.iter().copied());
+        sizes.push(self.output_size);
+
+        let consistent = sizes.iter().all(|&size| size > 0)
+            && self.layers.len() == sizes.len() - 1
+            && self.layers.iter().zip(sizes.windows(2)).all(|(layer, pair)| {
+                layer.inputs == pair[0]
+                    && layer.outputs == pair[1]
+                    && layer.weights.len() == pair[0] * pair[1]
+                    && layer.biases.len() == pair[1]
+                    && layer.weights.iter().chain(&layer.biases).all(|v| v.is_finite())
+            });
+        if !consistent {
+            return Err(TradingError::validation_error(
+                "network".to_string(),
+                "Network layers do not match its architecture".to_string(),
+                Some(format!("{:?}", sizes))
+            ));
+        }
+        Ok(())
+    }
+
+    fn layer_activation(&self, index: usize) -> Activation {
+        if index + 1 == self.layers.len() { Activation::Linear } else { self.activation }
+    }
+
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        self.layers.iter().enumerate().fold(input.to_vec(), |x, (i, layer)| layer.forward(&x, self.layer_activation(i)))
+    }
+
+    /// Accumulate the squared-error gradient of one sample into `grads`, returning its loss
+    fn backpropagate(&self, input: &[f32], target: &[f32], grads: &mut [DenseLayer]) -> f32 {
+        let mut activations = vec![input.to_vec()];
+        for (i, layer) in self.layers.iter().enumerate() {
+            let next = layer.forward(&activations[i], self.layer_activation(i));
+            activations.push(next);
+        }
+
+        let output = &activations[self.layers.len()];
+        let mut delta: Vec<f32> = output.iter().zip(target).map(|(y, t)| y - t).collect();
+        let loss = delta.iter().map(|d| d * d).sum::<f32>() / delta.len() as f32;
+        // d(mean squared error)/dy = 2(y - t)/n
+        let scale = 2.0 / delta.len() as f32;
+        delta.iter_mut().for_each(|d| *d *= scale);
+
+        for i in (0..self.layers.len()).rev() {
+            let layer = &self.layers[i];
+            let input = &activations[i];
+            let grad = &mut grads[i];
+            for ((bias, row), d) in grad.biases.iter_mut().zip(grad.weights.chunks_mut(layer.inputs)).zip(&delta) {
+                *bias += d;
+                row.iter_mut().zip(input).for_each(|(w, x)| *w += d * x);
+            }
+
+            if i > 0 {
+                let below = self.layer_activation(i - 1);
+                delta = (0..layer.inputs)
+                    .map(|j| {
+                        let back: f32 = (0..layer.outputs).map(|o| layer.weights[o * layer.inputs + j] * delta[o]).sum();
+                        back * below.derivative(input[j])
+                    })
+                    .collect();
+            }
+        }
+        loss
+    }
+
+    fn zero_gradients(&self) -> Vec<DenseLayer> {
+        self.layers.iter().map(|layer| DenseLayer::zeros(layer.inputs, layer.outputs)).collect()
+    }
+}
+
+/// Which features are built from the klines
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeatureConfig {
+    /// Lagged one-bar log returns, newest first
+    pub return_lags: usize,
+    /// Bars ahead of the forward log return being predicted
+    pub horizon: usize,
+    /// RSI, MACD histogram, Bollinger %B, ATR, realized volatility and momentum
+    pub indicators: bool,
+    /// Spread and liquidity estimated from OHLCV, since order book history is not stored
+    pub depth: bool,
+}
+
+impl Default for FeatureConfig {
+    fn default() -> Self {
+        Self {
+            return_lags: 5,
+            horizon: 1,
+            indicators: true,
+            depth: true,
+        }
+    }
+}
+
+impl FeatureConfig {
+    pub fn feature_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = (1..=self.return_lags).map(|lag| format!("return_lag_{}", lag)).collect();
+        if self.indicators {
+            names.extend(["rsi_14", "macd_histogram", "bollinger_percent_b", "atr_14", "realized_volatility_20", "momentum_10"]
+                .iter().map(|s| s.to_string()));
+        }
+        if self.depth {
+            names.extend(["corwin_schultz_spread", "amihud_illiquidity", "volume_zscore_20", "range", "close_location"]
+                .iter().map(|s| s.to_string()));
+        }
+        names
+    }
+
+    /// Klines consumed before the first complete feature row
+    pub fn warmup_bars(&self) -> usize {
+        // MACD's signal line settles last, then the 20-bar volume and volatility windows
+        let indicators = if self.indicators { 33 } else { 0 };
+        let depth = if self.depth { 19 } else { 0 };
+        indicators.max(depth).max(self.return_lags)
+    }
+}
+
+/// Feature rows built from klines, one per bar once every feature has warmed up
+#[derive(Debug, Clone)]
+pub struct FeatureSet {
+    pub names: Vec<String>,
+    pub rows: Vec<Vec<f64>>,
+    /// Index of the kline each row describes
+    pub bars: Vec<usize>,
+    /// Forward log return over the horizon, `None` for the newest bars
+    pub targets: Vec<Option<f64>>,
+}
+
+/// Build the feature matrix for `klines` (oldest first)
+pub fn build_features(klines: &[KlineData], config: &FeatureConfig) -> FeatureSet {
+    let series = |f: fn(&KlineData) -> rust_decimal::Decimal| -> Vec<f64> {
+        klines.iter().map(|k| f(k).to_f64().unwrap_or(f64::NAN)).collect()
+    };
+    let high = series(|k| k.high);
+    let low = series(|k| k.low);
+    let close = series(|k| k.close);
+    let volume = series(|k| k.volume);
+    let n = close.len();
+
+    let returns: Vec<f64> = (0..n)
+        .map(|i| if i == 0 { f64::NAN } else { (close[i] / close[i - 1]).ln() })
+        .collect();
+
+    let mut columns: Vec<Vec<f64>> = (0..config.return_lags)
+        .map(|lag| (0..n).map(|i| if i >= lag { returns[i - lag] } else { f64::NAN }).collect())
+        .collect();
+
+    if config.indicators {
+        let rsi = wilder_rsi(&close, 14);
+        columns.push(rsi.iter().map(|r| (r - 50.0) / 50.0).collect());
+        columns.push(macd_histogram(&close).iter().zip(&close).map(|(h, c)| h / c).collect());
+        columns.push(percent_b(&close, 20));
+        columns.push(wilder_atr(&high, &low, &close, 14).iter().zip(&close).map(|(a, c)| a / c).collect());
+        columns.push(rolling(&returns, 20, |window| mean_std(window).1));
+        columns.push((0..n).map(|i| if i >= 10 { (close[i] / close[i - 10]).ln() } else { f64::NAN }).collect());
+    }
+
+    if config.depth {
+        columns.push(corwin_schultz_spread(&high, &low));
+        columns.push((0..n)
+            .map(|i| {
+                let quote_volume = (close[i] * volume[i]).max(1.0);
+                (1.0 + returns[i].abs() * 1e6 / quote_volume).ln()
+            })
+            .collect());
+        let log_volume: Vec<f64> = volume.iter().map(|v| v.max(0.0).ln_1p()).collect();
+        columns.push(rolling(&log_volume, 20, |window| {
+            let (mean, std) = mean_std(window);
+            if std > 0.0 { (window[window.len() - 1] - mean) / std } else { 0.0 }
+        }));
+        columns.push((0..n).map(|i| (high[i] / low[i]).ln()).collect());
+        columns.push((0..n)
+            .map(|i| {
+                let range = high[i] - low[i];
+                if range > 0.0 { ((close[i] - low[i]) - (high[i] - close[i])) / range } else { 0.0 }
+            })
+            .collect());
+    }
+
+    let mut features = FeatureSet { names: config.feature_names(), rows: Vec::new(), bars: Vec::new(), targets: Vec::new() };
+    for i in 0..n {
+        let row: Vec<f64> = columns.iter().map(|column| column[i]).collect();
+        if !row.iter().all(|v| v.is_finite()) {
+            continue;
+        }
+        let target = (i + config.horizon < n)
+            .then(|| (close[i + config.horizon] / close[i]).ln())
+            .filter(|t| t.is_finite());
+        features.rows.push(row);
+        features.bars.push(i);
+        features.targets.push(target);
+    }
+    features
+}
+
+/// Per-feature standardization fitted on the training split
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeatureScaler {
+    pub means: Vec<f64>,
+    pub stds: Vec<f64>,
+}
+
+impl FeatureScaler {
+    pub fn fit(rows: &[Vec<f64>]) -> Self {
+        let width = rows.first().map_or(0, |row| row.len());
+        let (means, stds) = (0..width)
+            .map(|j| {
+                let column: Vec<f64> = rows.iter().map(|row| row[j]).collect();
+                let (mean, std) = mean_std(&column);
+                (mean, if std > 1e-12 { std } else { 1.0 })
+            })
+            .unzip();
+        Self { means, stds }
+    }
+
+    pub fn transform(&self, row: &[f64]) -> Vec<f32> {
+        row.iter().zip(self.means.iter().zip(&self.stds)).map(|(x, (m, s))| ((x - m) / s) as f32).collect()
+    }
+}
+
+/// Network shape, optimizer and early stopping settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NeuralTrainingConfig {
+    pub hidden_sizes: Vec<usize>,
+    pub activation: Activation,
+    pub learning_rate: f32,
+    pub momentum: f32,
+    pub batch_size: usize,
+    pub max_epochs: usize,
+    /// Epochs without a validation improvement before training stops
+    pub patience: usize,
+    /// Newest fraction of the samples held out for validation
+    pub validation_fraction: f64,
+    /// L2 penalty on the weights
+    pub weight_decay: f32,
+    /// Seed for weight initialization and shuffling
+    pub seed: u64,
+    pub features: FeatureConfig,
+}
+
+impl Default for NeuralTrainingConfig {
+    fn default() -> Self {
+        Self {
+            hidden_sizes: vec![32, 16],
+            activation: Activation::Tanh,
+            learning_rate: 0.01,
+            momentum: 0.9,
+            batch_size: 32,
+            max_epochs: 200,
+            patience: 15,
+            validation_fraction: 0.2,
+            weight_decay: 1e-4,
+            seed: 42,
+            features: FeatureConfig::default(),
+        }
+    }
+}
+
+impl NeuralTrainingConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hidden_sizes.iter().any(|&size| size == 0 || size > 512) {
+            return Err("Hidden layer sizes must be between 1 and 512".to_string());
+        }
+        if !(self.learning_rate > 0.0 && self.learning_rate <= 1.0) {
+            return Err("Learning rate must be in (0, 1]".to_string());
+        }
+        if !(0.0..1.0).contains(&self.momentum) {
+            return Err("Momentum must be in [0, 1)".to_string());
+        }
+        if self.batch_size == 0 || self.max_epochs == 0 || self.patience == 0 {
+            return Err("Batch size, max epochs and patience must be positive".to_string());
+        }
+        if !(0.05..=0.5).contains(&self.validation_fraction) {
+            return Err("Validation fraction must be between 0.05 and 0.5".to_string());
+        }
+        if !(self.weight_decay >= 0.0 && self.weight_decay.is_finite()) {
+            return Err("Weight decay must be non-negative".to_string());
+        }
+        if self.features.horizon == 0 || self.features.return_lags > 50 {
+            return Err("Horizon must be positive and at most 50 return lags are supported".to_string());
+        }
+        if self.features.feature_names().is_empty() {
+            return Err("At least one feature group must be enabled".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Mean squared error after one epoch, on standardized targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpochLoss {
+    pub epoch: usize,
+    pub train_loss: f64,
+    pub validation_loss: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrainingReport {
+    pub training_samples: usize,
+    pub validation_samples: usize,
+    pub epochs_run: usize,
+    /// Epoch whose weights were kept
+    pub best_epoch: usize,
+    pub stopped_early: bool,
+    pub train_loss: f64,
+    pub validation_loss: f64,
+    /// Validation error of the predicted log return
+    pub validation_rmse: f64,
+    /// Validation error of always predicting the training mean return
+    pub baseline_rmse: f64,
+    /// Share of validation bars whose direction was predicted correctly
+    pub directional_accuracy: f64,
+    pub history: Vec<EpochLoss>,
+}
+
+/// Trained network with everything needed to rebuild its inputs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NeuralModelFile {
+    pub symbol: String,
+    pub interval: String,
+    pub trained_at: DateTime<Utc>,
+    pub config: NeuralTrainingConfig,
+    pub feature_names: Vec<String>,
+    pub scaler: FeatureScaler,
+    pub target_mean: f64,
+    pub target_std: f64,
+    pub network: NeuralNetwork,
+    pub report: TrainingReport,
+}
+
+impl NeuralModelFile {
+    pub fn path(symbol: &str, interval: &str) -> TradingResult<PathBuf> {
+        let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric());
+        if !valid(symbol) || !valid(interval) {
+            return Err(TradingError::validation_error(
+                "symbol".to_string(),
+                "Symbol and interval must be alphanumeric".to_string(),
+                Some(format!("{}_{}", symbol, interval))
+            ));
+        }
+        persistence::data_file(&format!("neural_model_{}_{}.json", symbol.to_uppercase(), interval))
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        persistence::write_json(&Self::path(&self.symbol, &self.interval)?, self)
+    }
+
+    /// Load a persisted model, if one has been trained for the symbol and interval
+    pub fn load(symbol: &str, interval: &str) -> TradingResult<Option<Self>> {
+        let model: Option<Self> = persistence::read_json(&Self::path(symbol, interval)?)?;
+        if let Some(model) = &model {
+            model.validate()?;
+        }
+        Ok(model)
+    }
+
+    /// Check the network and scaler against the feature pipeline that will feed them
+    pub fn validate(&self) -> TradingResult<()> {
+        self.network.validate()?;
+        let names = self.config.features.feature_names();
+        if names != self.feature_names
+            || self.network.input_size != names.len()
+            || self.network.output_size != 1
+            || self.scaler.means.len() != names.len()
+            || self.scaler.stds.len() != names.len()
+        {
+            return Err(TradingError::validation_error(
+                "model".to_string(),
+                "Model file does not match the feature pipeline".to_string(),
+                Some(format!("{}_{}", self.symbol, self.interval))
+            ));
+        }
+        Ok(())
+    }
+
+    /// Scaled features of the newest kline
+    fn latest_input(&self, klines: &[KlineData]) -> TradingResult<Vec<f32>> {
+        let features = build_features(klines, &self.config.features);
+        match (features.bars.last(), features.rows.last()) {
+            (Some(&bar), Some(row)) if bar + 1 == klines.len() => Ok(self.scaler.transform(row)),
+            _ => Err(TradingError::validation_error(
+                "klines".to_string(),
+                "Not enough klines to build features for the latest bar".to_string(),
+                Some(klines.len().to_string())
+            )),
+        }
+    }
+
+    fn to_return(&self, output: f32) -> f64 {
+        self.target_mean + output as f64 * self.target_std
+    }
+}
+
+/// Train a network on the symbol's klines, holding out the newest bars for early stopping
+pub fn train_model(symbol: &str, interval: &str, klines: &[KlineData], config: &NeuralTrainingConfig) -> TradingResult<NeuralModelFile> {
+    config.validate().map_err(|e| TradingError::validation_error("config".to_string(), e, None))?;
+
+    let features = build_features(klines, &config.features);
+    let samples: Vec<(&Vec<f64>, f64)> = features.rows.iter()
+        .zip(&features.targets)
+        .filter_map(|(row, target)| target.map(|t| (row, t)))
+        .collect();
+    if samples.len() < MIN_TRAINING_SAMPLES {
+        return Err(TradingError::validation_error(
+            "klines".to_string(),
+            format!("Need at least {} labelled feature rows", MIN_TRAINING_SAMPLES),
+            Some(samples.len().to_string())
+        ));
+    }
+
+    // Chronological split; training targets that overlap the validation period are purged
+    let split = ((samples.len() as f64) * (1.0 - config.validation_fraction)).round() as usize;
+    let train = &samples[..split.saturating_sub(config.features.horizon - 1)];
+    let validation = &samples[split..];
+
+    let scaler = FeatureScaler::fit(&train.iter().map(|(row, _)| (*row).clone()).collect::<Vec<_>>());
+    let train_targets: Vec<f64> = train.iter().map(|(_, t)| *t).collect();
+    let (target_mean, target_std) = mean_std(&train_targets);
+    let target_std = if target_std > 1e-12 { target_std } else { 1.0 };
+    let scale = |set: &[(&Vec<f64>, f64)]| -> Vec<(Vec<f32>, f32)> {
+        set.iter().map(|(row, t)| (scaler.transform(row), ((t - target_mean) / target_std) as f32)).collect()
+    };
+    let train_set = scale(train);
+    let validation_set = scale(validation);
+
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut network = NeuralNetwork::new(scaler.means.len(), config.hidden_sizes.clone(), 1, config.activation, config.seed);
+    let mut velocity = network.zero_gradients();
+    let mut best = (network.clone(), 0, f64::INFINITY, f64::INFINITY);
+    let mut history = Vec::new();
+    let mut order: Vec<usize> = (0..train_set.len()).collect();
+
+    for epoch in 1..=config.max_epochs {
+        order.shuffle(&mut rng);
+        let mut train_loss = 0.0;
+        for batch in order.chunks(config.batch_size) {
+            let mut grads = network.zero_gradients();
+            for &i in batch {
+                let (input, target) = &train_set[i];
+                train_loss += network.backpropagate(input, &[*target], &mut grads) as f64;
+            }
+
+            let n = batch.len() as f32;
+            for ((layer, grad), vel) in network.layers.iter_mut().zip(&grads).zip(velocity.iter_mut()) {
+                for ((w, g), v) in layer.weights.iter_mut().zip(&grad.weights).zip(vel.weights.iter_mut()) {
+                    *v = config.momentum * *v - config.learning_rate * (g / n + config.weight_decay * *w);
+                    *w += *v;
+                }
+                for ((b, g), v) in layer.biases.iter_mut().zip(&grad.biases).zip(vel.biases.iter_mut()) {
+                    *v = config.momentum * *v - config.learning_rate * g / n;
+                    *b += *v;
+                }
+            }
+        }
+        let train_loss = train_loss / train_set.len() as f64;
+        let validation_loss = mean_squared_error(&network, &validation_set);
+        if !validation_loss.is_finite() {
+            return Err(TradingError::validation_error(
+                "learning_rate".to_string(),
+                "Training diverged, lower the learning rate".to_string(),
+                Some(config.learning_rate.to_string())
+            ));
+        }
+        history.push(EpochLoss { epoch, train_loss, validation_loss });
+
+        if validation_loss < best.3 - 1e-9 {
+            best = (network.clone(), epoch, train_loss, validation_loss);
+        } else if epoch - best.1 >= config.patience {
+            break;
+        }
+    }
+
+    let (network, best_epoch, train_loss, validation_loss) = best;
+    let epochs_run = history.len();
+    let predicted: Vec<f64> = validation_set.iter()
+        .map(|(input, _)| target_mean + network.forward(input)[0] as f64 * target_std)
+        .collect();
+    let actual: Vec<f64> = validation.iter().map(|(_, t)| *t).collect();
+    let rmse = |errors: &mut dyn Iterator<Item = f64>| (errors.map(|e| e * e).sum::<f64>() / actual.len() as f64).sqrt();
+    let directional: Vec<bool> = predicted.iter().zip(&actual)
+        .filter(|(_, a)| **a != 0.0)
+        .map(|(p, a)| p.signum() == a.signum())
+        .collect();
+
+    let report = TrainingReport {
+        training_samples: train_set.len(),
+        validation_samples: validation_set.len(),
+        epochs_run,
+        best_epoch,
+        stopped_early: epochs_run < config.max_epochs,
+        train_loss,
+        validation_loss,
+        validation_rmse: rmse(&mut predicted.iter().zip(&actual).map(|(p, a)| p - a)),
+        baseline_rmse: rmse(&mut actual.iter().map(|a| a - target_mean)),
+        directional_accuracy: if directional.is_empty() {
+            0.0
+        } else {
+            directional.iter().filter(|&&hit| hit).count() as f64 / directional.len() as f64
+        },
+        history,
+    };
+
+    Ok(NeuralModelFile {
+        symbol: symbol.to_uppercase(),
+        interval: interval.to_string(),
+        trained_at: Utc::now(),
+        config: config.clone(),
+        feature_names: features.names,
+        scaler,
+        target_mean,
+        target_std,
+        network,
+        report,
+    })
+}
+
+fn mean_squared_error(network: &NeuralNetwork, set: &[(Vec<f32>, f32)]) -> f64 {
+    if set.is_empty() {
+        return 0.0;
+    }
+    set.iter().map(|(input, target)| ((network.forward(input)[0] - target) as f64).powi(2)).sum::<f64>() / set.len() as f64
 }
 
-/// Training batch for GPU
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
-struct TrainingBatch {
-    inputs: [f32; 64],   // Feature vector
-    targets: [f32; 8],   // Target outputs
-    loss: f32,
-    accuracy: f32,
-    _padding: [f32; 2],
+struct LayerDims {
+    inputs: u32,
+    outputs: u32,
+    batch: u32,
+    activation: u32,
 }
 
-/// GPU-accelerated neural network for trading predictions
-pub struct GpuNeuralPredictor {
+struct GpuLayer {
+    dims: LayerDims,
+    weights: wgpu::Buffer,
+    biases: wgpu::Buffer,
+}
+
+/// Network weights uploaded to the GPU for the `forward_pass` shader
+struct GpuForwardPass {
     device: Arc<wgpu::Device>,
     queue: Arc<wgpu::Queue>,
-    
-    // Network architecture
-    input_size: usize,
-    hidden_sizes: Vec<usize>,
-    output_size: usize,
-    
-    // GPU resources
-    forward_pipeline: wgpu::ComputePipeline,
-    backward_pipeline: wgpu::ComputePipeline,
-    correlation_pipeline: wgpu::ComputePipeline,
-    
-    // Buffers
-    weight_buffers: Vec<wgpu::Buffer>,
-    activation_buffers: Vec<wgpu::Buffer>,
-    gradient_buffers: Vec<wgpu::Buffer>,
-    
-    // Training state
-    learning_rate: f32,
-    momentum: f32,
-    batch_size: usize,
-    epoch: usize,
+    pipeline: wgpu::ComputePipeline,
+    layers: Vec<GpuLayer>,
 }
 
-impl GpuNeuralPredictor {
-    pub async fn new(
-        input_size: usize,
-        hidden_sizes: Vec<usize>,
-        output_size: usize,
-    ) -> Result<Self, String> {
-        // Initialize WGPU
-        let instance = wgpu::Instance::default();
-        
-        let adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                force_fallback_adapter: false,
-                compatible_surface: None,
-            })
-            .await
-            .ok_or("Failed to find GPU adapter")?;
-        
-        // Check GPU capabilities
-        let info = adapter.get_info();
-        println!("Using GPU: {} ({:?})", info.name, info.backend);
-        println!("GPU Memory: Available for neural network operations");
-        
-        let (device, queue) = adapter
-            .request_device(
-                &wgpu::DeviceDescriptor {
-                    label: Some("Neural Network GPU"),
-                    required_features: wgpu::Features::empty(),
-                    required_limits: wgpu::Limits {
-                        max_compute_workgroup_size_x: 256,
-                        max_compute_workgroup_size_y: 256,
-                        max_compute_workgroup_size_z: 64,
-                        max_compute_invocations_per_workgroup: 256,
-                        ..Default::default()
-                    },
-                    memory_hints: Default::default(),
-                },
-                None,
-            )
-            .await
-            .map_err(|e| format!("Failed to create GPU device: {}", e))?;
-        
-        let device = Arc::new(device);
-        let queue = Arc::new(queue);
-        
-        // Create compute shaders
-        let nn_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+impl GpuForwardPass {
+    fn new(device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>, network: &NeuralNetwork) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Neural Network Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/neural_network.wgsl")),
+            source: wgpu::ShaderSource::Wgsl(include_str!("shaders/neural_network.wgsl").into()),
         });
-        
-        // Initialize weight buffers with Xavier initialization
-        let mut weight_buffers = Vec::new();
-        let mut layer_sizes = vec![input_size];
-        layer_sizes.extend(hidden_sizes.clone());
-        layer_sizes.push(output_size);
-        
-        for i in 0..layer_sizes.len() - 1 {
-            let weights = Self::xavier_init(layer_sizes[i], layer_sizes[i + 1]);
-            let buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some(&format!("Weight Buffer Layer {}", i)),
-                contents: bytemuck::cast_slice(&weights),
-                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
-            });
-            weight_buffers.push(buffer);
-        }
-        
-        // Create activation buffers
-        let mut activation_buffers = Vec::new();
-        for (i, &size) in layer_sizes.iter().enumerate() {
-            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some(&format!("Activation Buffer Layer {}", i)),
-                size: (size * std::mem::size_of::<f32>()) as u64,
-                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
-                mapped_at_creation: false,
-            });
-            activation_buffers.push(buffer);
-        }
-        
-        // Create gradient buffers for backpropagation
-        let gradient_buffers = weight_buffers.iter().map(|_| {
-            device.create_buffer(&wgpu::BufferDescriptor {
-                label: Some("Gradient Buffer"),
-                size: 1024 * 1024 * 4, // 4MB per gradient buffer
-                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
-                mapped_at_creation: false,
-            })
-        }).collect();
-        
-        // Create compute pipelines
-        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-            label: Some("Neural Network Pipeline Layout"),
-            bind_group_layouts: &[],
-            push_constant_ranges: &[],
-        });
-        
-        let forward_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some("Forward Pass Pipeline"),
-            layout: Some(&pipeline_layout),
-            module: &nn_shader,
-            entry_point: Some("forward_pass"),
-            compilation_options: Default::default(),
-            cache: None,
-        });
-        
-        let backward_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Backward Pass Pipeline"),
-            layout: Some(&pipeline_layout),
-            module: &nn_shader,
-            entry_point: Some("backward_pass"),
-            compilation_options: Default::default(),
-            cache: None,
-        });
-        
-        let correlation_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Correlation Pipeline"),
-            layout: Some(&pipeline_layout),
-            module: &nn_shader,
-            entry_point: Some("calculate_correlation"),
+            layout: None,
+            module: &shader,
+            entry_point: "forward_pass",
             compilation_options: Default::default(),
-            cache: None,
         });
-        
-        Ok(Self {
-            device,
-            queue,
-            input_size,
-            hidden_sizes,
-            output_size,
-            forward_pipeline,
-            backward_pipeline,
-            correlation_pipeline,
-            weight_buffers,
-            activation_buffers,
-            gradient_buffers,
-            learning_rate: 0.001,
-            momentum: 0.9,
-            batch_size: 32,
-            epoch: 0,
-        })
-    }
-    
-    /// Xavier weight initialization
-    fn xavier_init(input_size: usize, output_size: usize) -> Vec<f32> {
-        let mut rng = rand::thread_rng();
-        let scale = (2.0 / (input_size + output_size) as f32).sqrt();
-        
-        (0..input_size * output_size)
-            .map(|_| rng.gen_range(-scale..scale))
-            .collect()
+
+        let layers = network.layers.iter().enumerate()
+            .map(|(i, layer)| GpuLayer {
+                dims: LayerDims {
+                    inputs: layer.inputs as u32,
+                    outputs: layer.outputs as u32,
+                    batch: 1,
+                    activation: network.layer_activation(i).shader_code(),
+                },
+                weights: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Weight Buffer Layer {}", i)),
+                    contents: bytemuck::cast_slice(&layer.weights),
+                    usage: wgpu::BufferUsages::STORAGE,
+                }),
+                biases: device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Bias Buffer Layer {}", i)),
+                    contents: bytemuck::cast_slice(&layer.biases),
+                    usage: wgpu::BufferUsages::STORAGE,
+                }),
+            })
+            .collect();
+
+        Self { device, queue, pipeline, layers }
     }
-    
-    /// Forward pass through the network
-    pub async fn predict(&self, inputs: &[f32]) -> Vec<f32> {
-        // Upload input data to GPU
-        self.queue.write_buffer(&self.activation_buffers[0], 0, bytemuck::cast_slice(inputs));
-        
+
+    /// Run every layer for a single input vector and read the output back
+    async fn forward(&self, input: &[f32]) -> Result<Vec<f32>, String> {
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Prediction Encoder"),
         });
-        
-        // Execute forward pass through each layer
-        for layer in 0..self.weight_buffers.len() {
-            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                label: Some(&format!("Forward Pass Layer {}", layer)),
-                timestamp_writes: None,
-            });
-            
-            compute_pass.set_pipeline(&self.forward_pipeline);
-            // Set bind groups for weights and activations
-            // compute_pass.set_bind_group(0, &bind_group, &[]);
-            
-            let workgroup_size = 64;
-            let num_neurons = if layer < self.hidden_sizes.len() {
-                self.hidden_sizes[layer]
-            } else {
-                self.output_size
-            };
-            let workgroups = (num_neurons as u32 + workgroup_size - 1) / workgroup_size;
-            
-            compute_pass.dispatch_workgroups(workgroups, 1, 1);
-        }
-        
-        self.queue.submit(Some(encoder.finish()));
-        
-        // Read predictions from GPU
-        self.read_predictions().await
-    }
-    
-    /// Train the network on a batch of data
-    pub async fn train_batch(&mut self, batch: &[(Vec<f32>, Vec<f32>)]) -> f32 {
-        let mut total_loss = 0.0;
-        
-        for (inputs, targets) in batch {
-            // Forward pass
-            let predictions = self.predict(inputs).await;
-            
-            // Calculate loss
-            let loss = self.calculate_loss(&predictions, targets);
-            total_loss += loss;
-            
-            // Backward pass
-            self.backward_pass(targets).await;
-            
-            // Update weights
-            self.update_weights().await;
-        }
-        
-        self.epoch += 1;
-        total_loss / batch.len() as f32
-    }
-    
-    /// Backward pass for training
-    async fn backward_pass(&self, targets: &[f32]) {
-        // Upload target data
-        let target_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Target Buffer"),
-            contents: bytemuck::cast_slice(targets),
+        let mut current = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Input Buffer"),
+            contents: bytemuck::cast_slice(input),
             usage: wgpu::BufferUsages::STORAGE,
         });
-        
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Backward Pass Encoder"),
-        });
-        
-        // Execute backward pass through each layer (in reverse)
-        for layer in (0..self.weight_buffers.len()).rev() {
-            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                label: Some(&format!("Backward Pass Layer {}", layer)),
-                timestamp_writes: None,
+
+        for (i, layer) in self.layers.iter().enumerate() {
+            let dims = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Layer Dims Buffer"),
+                contents: bytemuck::bytes_of(&layer.dims),
+                usage: wgpu::BufferUsages::UNIFORM,
             });
-            
-            compute_pass.set_pipeline(&self.backward_pipeline);
-            // Set bind groups
-            
-            let workgroups = 64;
-            compute_pass.dispatch_workgroups(workgroups, 1, 1);
-        }
-        
-        self.queue.submit(Some(encoder.finish()));
-    }
-    
-    /// Update weights using gradients
-    async fn update_weights(&self) {
-        // Apply gradient descent with momentum
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Weight Update Encoder"),
+            let output = self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("Activation Buffer Layer {}", i)),
+                size: (layer.dims.outputs as usize * std::mem::size_of::<f32>()) as u64,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Forward Pass Bind Group"),
+                layout: &self.pipeline.get_bind_group_layout(0),
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: dims.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 1, resource: layer.weights.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 2, resource: layer.biases.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 3, resource: current.as_entire_binding() },
+                    wgpu::BindGroupEntry { binding: 4, resource: output.as_entire_binding() },
+                ],
+            });
+
+            {
+                let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                    label: Some(&format!("Forward Pass Layer {}", i)),
+                    timestamp_writes: None,
+                });
+                compute_pass.set_pipeline(&self.pipeline);
+                compute_pass.set_bind_group(0, &bind_group, &[]);
+                compute_pass.dispatch_workgroups((layer.dims.outputs * layer.dims.batch).div_ceil(WORKGROUP_SIZE), 1, 1);
+            }
+            current = output;
+        }
+
+        let size = current.size();
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Prediction Staging Buffer"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
         });
-        
-        for (weight_buffer, gradient_buffer) in self.weight_buffers.iter().zip(&self.gradient_buffers) {
-            // GPU kernel for weight update: w = w - learning_rate * gradient + momentum * prev_gradient
-            encoder.copy_buffer_to_buffer(
-                gradient_buffer,
-                0,
-                weight_buffer,
-                0,
-                1024, // Size to copy
-            );
-        }
-        
+        encoder.copy_buffer_to_buffer(&current, 0, &staging, 0, size);
         self.queue.submit(Some(encoder.finish()));
-    }
-    
-    /// Calculate loss (MSE)
-    fn calculate_loss(&self, predictions: &[f32], targets: &[f32]) -> f32 {
-        predictions.iter()
-            .zip(targets.iter())
-            .map(|(p, t)| (p - t).powi(2))
-            .sum::<f32>() / predictions.len() as f32
-    }
-    
-    /// Read predictions from GPU
-    async fn read_predictions(&self) -> Vec<f32> {
-        let output_buffer = &self.activation_buffers.last().unwrap();
-        let buffer_slice = output_buffer.slice(..);
-        
+
+        if let Some(error) = self.device.pop_error_scope().await {
+            return Err(format!("GPU forward pass failed: {}", error));
+        }
+
+        let buffer_slice = staging.slice(..);
         let (sender, receiver) = futures_intrusive::channel::shared::oneshot_channel();
-        
         buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
             let _ = sender.send(result);
         });
-        
         self.device.poll(wgpu::Maintain::Wait);
-        
-        if let Ok(Ok(())) = receiver.receive().await {
-            let data = buffer_slice.get_mapped_range();
-            let predictions: Vec<f32> = bytemuck::cast_slice(&data).to_vec();
-            drop(data);
-            output_buffer.unmap();
-            predictions
-        } else {
-            vec![0.0; self.output_size]
-        }
-    }
-    
-    /// Calculate correlation matrix on GPU (for portfolio optimization)
-    pub async fn calculate_correlation_matrix(&self, returns: &[Vec<f32>]) -> Vec<Vec<f32>> {
-        let n = returns.len();
-        let m = returns[0].len();
-        
-        // Flatten returns matrix for GPU
-        let flattened: Vec<f32> = returns.iter().flatten().copied().collect();
-        
-        let input_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Returns Buffer"),
-            contents: bytemuck::cast_slice(&flattened),
-            usage: wgpu::BufferUsages::STORAGE,
-        });
-        
-        let output_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Correlation Matrix Buffer"),
-            size: (n * n * std::mem::size_of::<f32>()) as u64,
-            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
-            mapped_at_creation: false,
-        });
-        
-        let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
-            label: Some("Correlation Encoder"),
-        });
-        
-        {
-            let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
-                label: Some("Correlation Compute Pass"),
-                timestamp_writes: None,
-            });
-            
-            compute_pass.set_pipeline(&self.correlation_pipeline);
-            // Set bind groups
-            
-            let workgroups = ((n * n) as u32 + 63) / 64;
-            compute_pass.dispatch_workgroups(workgroups, 1, 1);
-        }
-        
-        self.queue.submit(Some(encoder.finish()));
-        
-        // Read correlation matrix from GPU
-        self.read_correlation_matrix(n).await
+
+        match receiver.receive().await {
+            Some(Ok(())) => {
+                let data = buffer_slice.get_mapped_range();
+                let output: Vec<f32> = bytemuck::cast_slice(&data).to_vec();
+                drop(data);
+                staging.unmap();
+                Ok(output)
+            }
+            _ => Err("Failed to read predictions from GPU".to_string()),
+        }
+    }
+}
+
+/// Trained price predictor that runs on the GPU when one is available and on the CPU otherwise
+pub struct GpuNeuralPredictor {
+    model: NeuralModelFile,
+    gpu: Option<GpuForwardPass>,
+}
+
+impl GpuNeuralPredictor {
+    pub fn new(model: NeuralModelFile) -> Self {
+        Self { model, gpu: None }
+    }
+
+    pub fn train(symbol: &str, interval: &str, klines: &[KlineData], config: &NeuralTrainingConfig) -> TradingResult<Self> {
+        train_model(symbol, interval, klines, config).map(Self::new)
+    }
+
+    pub fn load(symbol: &str, interval: &str) -> TradingResult<Option<Self>> {
+        Ok(NeuralModelFile::load(symbol, interval)?.map(Self::new))
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        self.model.save()
+    }
+
+    pub fn model(&self) -> &NeuralModelFile {
+        &self.model
+    }
+
+    pub fn uses_gpu(&self) -> bool {
+        self.gpu.is_some()
     }
-    
-    /// Read correlation matrix from GPU
-    async fn read_correlation_matrix(&self, size: usize) -> Vec<Vec<f32>> {
-        // Implementation similar to read_predictions
-        vec![vec![0.0; size]; size]
+
+    /// Upload the network to the GPU, keeping it only if it reproduces the CPU output
+    pub async fn enable_gpu(&mut self, device: Arc<wgpu::Device>, queue: Arc<wgpu::Queue>) -> Result<(), String> {
+        let gpu = GpuForwardPass::new(device, queue, &self.model.network);
+        let probe = vec![0.5; self.model.network.input_size];
+        let expected = self.model.network.forward(&probe);
+        let actual = gpu.forward(&probe).await?;
+        if actual.len() != expected.len() || actual.iter().zip(&expected).any(|(a, e)| (a - e).abs() > 1e-3) {
+            return Err("GPU forward pass does not match the CPU network".to_string());
+        }
+        self.gpu = Some(gpu);
+        Ok(())
+    }
+
+    /// Predicted log return over the model's horizon for the newest kline
+    pub async fn predict_return(&self, klines: &[KlineData]) -> TradingResult<(f64, bool)> {
+        let input = self.model.latest_input(klines)?;
+        if let Some(gpu) = &self.gpu {
+            match gpu.forward(&input).await {
+                Ok(output) if output.first().is_some_and(|v| v.is_finite()) => {
+                    return Ok((self.model.to_return(output[0]), true));
+                }
+                Ok(_) => {
+                    log_warning!(LogCategory::GPU, "GPU forward pass returned no output, using the CPU network");
+                }
+                Err(e) => {
+                    log_warning!(LogCategory::GPU, "{}, using the CPU network", e);
+                }
+            }
+        }
+        Ok((self.model.to_return(self.model.network.forward(&input)[0]), false))
+    }
+
+    /// Predict the close `horizon` bars after the newest kline in `klines` (oldest first, closed bars only)
+    pub async fn predict(&self, klines: &[KlineData]) -> TradingResult<PricePrediction> {
+        let (predicted_return, used_gpu) = self.predict_return(klines).await?;
+        let last = &klines[klines.len() - 1];
+        let current_price = last.close.to_f64().unwrap_or(0.0);
+        let rmse = self.model.report.validation_rmse;
+        let confidence = if rmse > 0.0 { normal_cdf(predicted_return.abs() / rmse) } else { 0.5 };
+
+        let direction = if predicted_return >= 0.0 { "up" } else { "down" };
+        let suggested_action = match direction {
+            _ if confidence < ACTION_CONFIDENCE => "hold",
+            "up" => "buy",
+            _ => "sell",
+        };
+        log_info!(LogCategory::DataProcessing, "Neural prediction for {} {}: {:+.4}% ({:.0}% confidence, {})",
+            self.model.symbol, self.model.interval, predicted_return * 100.0, confidence * 100.0, if used_gpu { "GPU" } else { "CPU" });
+
+        Ok(PricePrediction {
+            symbol: self.model.symbol.clone(),
+            interval: self.model.interval.clone(),
+            as_of: last.close_time,
+            current_price,
+            predicted_return,
+            next_price: current_price * predicted_return.exp(),
+            confidence,
+            direction: direction.to_string(),
+            suggested_action: suggested_action.to_string(),
+            risk_level: rmse * 100.0,
+            used_gpu,
+        })
     }
 }
 
 /// Prediction results with confidence
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PricePrediction {
+    pub symbol: String,
+    pub interval: String,
+    /// Close time of the newest kline the prediction is based on
+    pub as_of: DateTime<Utc>,
+    pub current_price: f64,
+    /// Predicted log return over the model's horizon
+    pub predicted_return: f64,
     pub next_price: f64,
+    /// Probability the direction is right given the validation error
     pub confidence: f64,
     pub direction: String,
     pub suggested_action: String,
+    /// Validation RMSE of the predicted return, in percent
     pub risk_level: f64,
-}
\ No newline at end of file
+    pub used_gpu: bool,
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    (mean, variance.sqrt())
+}
+
+/// Apply `f` to each trailing window of `period` values, NaN until the window is full
+fn rolling(values: &[f64], period: usize, f: impl Fn(&[f64]) -> f64) -> Vec<f64> {
+    (0..values.len())
+        .map(|i| if i + 1 >= period { f(&values[i + 1 - period..=i]) } else { f64::NAN })
+        .collect()
+}
+
+fn ema(values: &[f64], period: usize) -> Vec<f64> {
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut out = Vec::with_capacity(values.len());
+    for (i, &v) in values.iter().enumerate() {
+        out.push(if i == 0 { v } else { alpha * v + (1.0 - alpha) * out[i - 1] });
+    }
+    out
+}
+
+fn wilder_rsi(close: &[f64], period: usize) -> Vec<f64> {
+    let mut out = vec![f64::NAN; close.len()];
+    let (mut gain, mut loss) = (0.0, 0.0);
+    for i in 1..close.len() {
+        let change = close[i] - close[i - 1];
+        let (up, down) = (change.max(0.0), (-change).max(0.0));
+        if i <= period {
+            gain += up / period as f64;
+            loss += down / period as f64;
+        } else {
+            gain = (gain * (period - 1) as f64 + up) / period as f64;
+            loss = (loss * (period - 1) as f64 + down) / period as f64;
+        }
+        if i >= period {
+            out[i] = if loss == 0.0 { 100.0 } else { 100.0 - 100.0 / (1.0 + gain / loss) };
+        }
+    }
+    out
+}
+
+fn macd_histogram(close: &[f64]) -> Vec<f64> {
+    let macd: Vec<f64> = ema(close, 12).iter().zip(ema(close, 26)).map(|(fast, slow)| fast - slow).collect();
+    let signal = ema(&macd, 9);
+    // Leave the slow EMA and signal line time to settle
+    (0..close.len()).map(|i| if i >= 33 { macd[i] - signal[i] } else { f64::NAN }).collect()
+}
+
+/// Bollinger %B centred on zero: -0.5 at the lower band, 0.5 at the upper band
+fn percent_b(close: &[f64], period: usize) -> Vec<f64> {
+    rolling(close, period, |window| {
+        let (mean, std) = mean_std(window);
+        if std > 0.0 { (window[window.len() - 1] - mean) / (4.0 * std) } else { 0.0 }
+    })
+}
+
+fn wilder_atr(high: &[f64], low: &[f64], close: &[f64], period: usize) -> Vec<f64> {
+    let mut out = vec![f64::NAN; close.len()];
+    let mut atr = 0.0;
+    for i in 1..close.len() {
+        let true_range = (high[i] - low[i]).max((high[i] - close[i - 1]).abs()).max((low[i] - close[i - 1]).abs());
+        atr = if i <= period { atr + true_range / period as f64 } else { (atr * (period - 1) as f64 + true_range) / period as f64 };
+        if i >= period {
+            out[i] = atr;
+        }
+    }
+    out
+}
+
+/// Corwin-Schultz bid-ask spread estimate from two consecutive high/low ranges
+fn corwin_schultz_spread(high: &[f64], low: &[f64]) -> Vec<f64> {
+    let k = 3.0 - 2.0 * std::f64::consts::SQRT_2;
+    (0..high.len())
+        .map(|i| {
+            if i == 0 {
+                return f64::NAN;
+            }
+            let beta = (high[i] / low[i]).ln().powi(2) + (high[i - 1] / low[i - 1]).ln().powi(2);
+            let gamma = (high[i].max(high[i - 1]) / low[i].min(low[i - 1])).ln().powi(2);
+            let alpha = ((2.0 * beta).sqrt() - beta.sqrt()) / k - (gamma / k).sqrt();
+            (2.0 * (alpha.exp() - 1.0) / (1.0 + alpha.exp())).max(0.0)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::Decimal;
+
+    fn synthetic_klines(n: usize, seed: u64, persistence: f64) -> Vec<KlineData> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut price = 100.0;
+        let mut last_return = 0.0;
+        (0..n)
+            .map(|i| {
+                last_return = persistence * last_return + rng.gen_range(-0.01..0.01);
+                let open = price;
+                price *= f64::exp(last_return);
+                let high = open.max(price) * (1.0 + rng.gen_range(0.0..0.004));
+                let low = open.min(price) * (1.0 - rng.gen_range(0.0..0.004));
+                let decimal = |v: f64| Decimal::from_f64_retain(v).unwrap();
+                KlineData {
+                    open_time: start + Duration::hours(i as i64),
+                    close_time: start + Duration::hours(i as i64 + 1) - Duration::milliseconds(1),
+                    open: decimal(open),
+                    high: decimal(high),
+                    low: decimal(low),
+                    close: decimal(price),
+                    volume: decimal(rng.gen_range(50.0..150.0)),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn backpropagation_matches_numerical_gradient() {
+        let network = NeuralNetwork::new(4, vec![5, 3], 1, Activation::Tanh, 7);
+        let input = [0.3, -0.8, 0.5, 0.1];
+        let target = [0.4];
+        let mut grads = network.zero_gradients();
+        network.backpropagate(&input, &target, &mut grads);
+
+        let loss = |net: &NeuralNetwork| (net.forward(&input)[0] - target[0]).powi(2);
+        let epsilon = 1e-3;
+        for (l, layer) in network.layers.iter().enumerate() {
+            for w in [0, layer.weights.len() - 1] {
+                let mut plus = network.clone();
+                plus.layers[l].weights[w] += epsilon;
+                let mut minus = network.clone();
+                minus.layers[l].weights[w] -= epsilon;
+                let numerical = (loss(&plus) - loss(&minus)) / (2.0 * epsilon);
+                assert!((numerical - grads[l].weights[w]).abs() < 1e-3, "layer {} weight {}", l, w);
+            }
+        }
+    }
+
+    #[test]
+    fn features_are_finite_and_labelled_except_the_newest_bars() {
+        let klines = synthetic_klines(120, 1, 0.0);
+        let config = FeatureConfig { horizon: 3, ..FeatureConfig::default() };
+        let features = build_features(&klines, &config);
+
+        assert_eq!(features.names.len(), 16);
+        assert!(features.rows.iter().all(|row| row.len() == 16 && row.iter().all(|v| v.is_finite())));
+        // MACD needs the longest warmup
+        assert_eq!(features.bars[0], 33);
+        assert_eq!(config.warmup_bars(), 33);
+        assert_eq!(FeatureConfig { indicators: false, ..config }.warmup_bars(), 19);
+        assert_eq!(*features.bars.last().unwrap(), 119);
+        assert_eq!(features.targets.iter().filter(|t| t.is_none()).count(), 3);
+    }
+
+    #[tokio::test]
+    async fn trained_model_learns_persistent_returns_and_survives_a_round_trip() {
+        let klines = synthetic_klines(600, 3, 0.7);
+        let config = NeuralTrainingConfig { max_epochs: 150, patience: 10, ..NeuralTrainingConfig::default() };
+        let model = train_model("btcusdt", "1h", &klines, &config).unwrap();
+
+        assert_eq!(model.symbol, "BTCUSDT");
+        assert!(model.report.best_epoch <= model.report.epochs_run);
+        assert!(model.report.validation_rmse < model.report.baseline_rmse);
+        assert!(model.report.directional_accuracy > 0.6);
+
+        let restored: NeuralModelFile = serde_json::from_str(&serde_json::to_string(&model).unwrap()).unwrap();
+        restored.validate().unwrap();
+        let before = GpuNeuralPredictor::new(model).predict(&klines).await.unwrap();
+        let after = GpuNeuralPredictor::new(restored).predict(&klines).await.unwrap();
+        assert_eq!(before.predicted_return, after.predicted_return);
+        assert!(!after.used_gpu);
+    }
+}
@@ -5,6 +5,28 @@ use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use crate::models::{OrderBookDepth, PriceData};
 
+/// Thresholds applied when turning GPU analysis into a trade recommendation
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct GpuRiskThresholds {
+    /// Maximum account risk per trade passed to the analysis, as a fraction
+    pub max_risk_percent: f32,
+    /// Overall risk score (0-1) above which trades are skipped
+    pub skip_overall_risk: f32,
+    /// Bars in the volatility window
+    pub volatility_window: u32,
+}
+
+impl Default for GpuRiskThresholds {
+    fn default() -> Self {
+        Self {
+            max_risk_percent: 0.02, // 2% max risk per trade
+            skip_overall_risk: 0.8,
+            volatility_window: 20,
+        }
+    }
+}
+
 /// GPU-accelerated risk management system
 pub struct GpuRiskManager {
     device: Arc<wgpu::Device>,
@@ -17,6 +39,7 @@ pub struct GpuRiskManager {
     orderbook_buffer: wgpu::Buffer,
     result_buffer: wgpu::Buffer,
     readback_buffer: wgpu::Buffer,
+    thresholds: std::sync::RwLock<GpuRiskThresholds>,
 }
 
 #[repr(C)]
@@ -136,9 +159,15 @@ impl GpuRiskManager {
             orderbook_buffer,
             result_buffer,
             readback_buffer,
+            thresholds: std::sync::RwLock::new(GpuRiskThresholds::default()),
         })
     }
 
+    /// Replace the recommendation thresholds, e.g. after a risk limit reload
+    pub fn set_thresholds(&self, thresholds: GpuRiskThresholds) {
+        *self.thresholds.write().unwrap_or_else(|e| e.into_inner()) = thresholds;
+    }
+
     /// Comprehensive GPU-accelerated risk analysis
     pub async fn analyze_trading_risk(
         &self,
@@ -189,12 +218,13 @@ impl GpuRiskManager {
         }
 
         // Set analysis parameters
+        let thresholds = self.thresholds.read().unwrap_or_else(|e| e.into_inner()).clone();
         let params = RiskAnalysisParams {
             data_length: price_data.len() as u32,
-            volatility_window: 20.min(price_data.len()) as u32,
+            volatility_window: thresholds.volatility_window.min(price_data.len() as u32),
             position_size: position_size.to_f64().unwrap_or(0.0) as f32,
             account_balance: account_balance.to_f64().unwrap_or(10000.0) as f32,
-            max_risk_percent: 0.02, // 2% max risk per trade
+            max_risk_percent: thresholds.max_risk_percent,
             _padding: [0.0; 3],
         };
 
@@ -216,7 +246,7 @@ impl GpuRiskManager {
             None
         };
 
-        let should_skip_trade = overall_risk > 0.8 || matches!(market_regime, MarketRegime::Crisis);
+        let should_skip_trade = overall_risk > thresholds.skip_overall_risk || matches!(market_regime, MarketRegime::Crisis);
 
         Ok(TradingRiskAssessment {
             volatility_risk: result.volatility_risk,
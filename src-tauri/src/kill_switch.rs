@@ -0,0 +1,318 @@
+// Global Kill Switch
+// Flatten-and-halt across every bot; the halted state is persisted so it survives restarts
+// and every engagement leaves a post-mortem report of what was closed at what price
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{AuthErrorType, TradingError, TradingLogicErrorType, TradingResult};
+use crate::logging::LogCategory;
+use crate::models::TradeSide;
+use crate::persistence;
+use crate::{log_error, log_info};
+
+/// Post-mortem reports kept on disk
+const MAX_REPORTS: usize = 50;
+/// A reset needs a token issued this recently, and after the switch was engaged
+const REAUTH_WINDOW_MINUTES: i64 = 5;
+
+/// Kill switch shared by every bot and command
+pub type SharedKillSwitch = Arc<Mutex<KillSwitch>>;
+
+/// A position flattened by the kill switch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedPosition {
+    /// Bot or book that held the position
+    pub source: String,
+    pub symbol: String,
+    pub side: TradeSide,
+    pub quantity: Decimal,
+    pub entry_price: Decimal,
+    pub exit_price: Decimal,
+    pub realized_pnl: Decimal,
+    pub closed_at: DateTime<Utc>,
+}
+
+impl ClosedPosition {
+    pub fn new(source: &str, symbol: &str, side: TradeSide, quantity: Decimal, entry_price: Decimal, exit_price: Decimal) -> Self {
+        let realized_pnl = match side {
+            TradeSide::Short | TradeSide::Sell => (entry_price - exit_price) * quantity,
+            TradeSide::Long | TradeSide::Buy => (exit_price - entry_price) * quantity,
+        };
+        Self {
+            source: source.to_string(),
+            symbol: symbol.to_string(),
+            side,
+            quantity,
+            entry_price,
+            exit_price,
+            realized_pnl,
+            closed_at: Utc::now(),
+        }
+    }
+}
+
+/// A position the kill switch could not flatten, still open after the engagement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnflattenedPosition {
+    pub source: String,
+    pub symbol: String,
+    pub side: TradeSide,
+    pub quantity: Decimal,
+    pub entry_price: Decimal,
+    pub reason: String,
+}
+
+/// A resting order cancelled by the kill switch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CancelledOrder {
+    pub source: String,
+    pub order_id: String,
+    pub symbol: String,
+}
+
+/// What the kill switch cancelled and closed during one engagement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostMortemReport {
+    pub id: String,
+    pub engaged_at: DateTime<Utc>,
+    pub engaged_by: String,
+    pub reason: String,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub stopped_bots: Vec<String>,
+    pub cancelled_orders: Vec<CancelledOrder>,
+    pub closed_positions: Vec<ClosedPosition>,
+    /// Positions left open, e.g. for lack of a market price to close at
+    #[serde(default)]
+    pub unflattened_positions: Vec<UnflattenedPosition>,
+    /// Steps that failed or fell back, e.g. positions closed at a stale price
+    pub warnings: Vec<String>,
+    pub total_realized_pnl: Decimal,
+}
+
+impl PostMortemReport {
+    pub fn new(engaged_by: &str, reason: &str, engaged_at: DateTime<Utc>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            engaged_at,
+            engaged_by: engaged_by.to_string(),
+            reason: reason.to_string(),
+            completed_at: None,
+            stopped_bots: Vec::new(),
+            cancelled_orders: Vec::new(),
+            closed_positions: Vec::new(),
+            unflattened_positions: Vec::new(),
+            warnings: Vec::new(),
+            total_realized_pnl: Decimal::ZERO,
+        }
+    }
+
+    pub fn add_closed(&mut self, position: ClosedPosition) {
+        self.total_realized_pnl += position.realized_pnl;
+        self.closed_positions.push(position);
+    }
+
+    pub fn add_unflattened(&mut self, position: UnflattenedPosition) {
+        self.warnings.push(format!("{} {} not flattened: {}", position.source, position.symbol, position.reason));
+        self.unflattened_positions.push(position);
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "Kill switch by {} ({}): {} bots stopped, {} orders cancelled, {} positions closed, {} left open, realized P/L {}, {} warnings",
+            self.engaged_by,
+            self.reason,
+            self.stopped_bots.len(),
+            self.cancelled_orders.len(),
+            self.closed_positions.len(),
+            self.unflattened_positions.len(),
+            self.total_realized_pnl.round_dp(2),
+            self.warnings.len(),
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KillSwitchStatus {
+    pub engaged: bool,
+    pub engaged_at: Option<DateTime<Utc>>,
+    pub engaged_by: Option<String>,
+    pub reason: Option<String>,
+    pub last_reset_at: Option<DateTime<Utc>>,
+    pub last_reset_by: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct KillSwitchStore {
+    status: KillSwitchStatus,
+    #[serde(default)]
+    reports: Vec<PostMortemReport>,
+}
+
+#[derive(Debug, Default)]
+pub struct KillSwitch {
+    store: KillSwitchStore,
+    path: Option<PathBuf>,
+}
+
+impl KillSwitch {
+    /// In-memory kill switch that is never written to disk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("kill_switch.json")
+    }
+
+    /// Load the halted state and past reports from `path`; changes are saved back
+    pub fn open(path: PathBuf) -> TradingResult<Self> {
+        let store = persistence::read_json(&path)?.unwrap_or_default();
+        Ok(Self { store, path: Some(path) })
+    }
+
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        match self.path.as_ref() {
+            Some(path) => persistence::write_json(path, &self.store),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.store.status.engaged
+    }
+
+    pub fn status(&self) -> &KillSwitchStatus {
+        &self.store.status
+    }
+
+    /// Error for entry paths to return while the switch is engaged
+    pub fn ensure_disengaged(&self) -> TradingResult<()> {
+        if !self.is_engaged() {
+            return Ok(());
+        }
+        Err(TradingError::trading_logic_error(
+            TradingLogicErrorType::EmergencyStopActive,
+            format!("Kill switch engaged: {}", self.store.status.reason.as_deref().unwrap_or("no reason given")),
+            None,
+        ))
+    }
+
+    /// Persist the halted state before anything is flattened; returns the report to fill in.
+    /// Engaging again while halted starts a new report so a failed flatten can be retried
+    pub fn engage(&mut self, user: &str, reason: &str) -> TradingResult<PostMortemReport> {
+        let now = Utc::now();
+        let status = &mut self.store.status;
+        if !status.engaged {
+            status.engaged = true;
+            status.engaged_at = Some(now);
+            status.engaged_by = Some(user.to_string());
+            status.reason = Some(reason.to_string());
+        }
+        self.save()?;
+        log_error!(LogCategory::RiskManagement, "KILL SWITCH ENGAGED by {}: {}", user, reason);
+        Ok(PostMortemReport::new(user, reason, now))
+    }
+
+    /// Store a completed post-mortem report
+    pub fn record_report(&mut self, mut report: PostMortemReport) -> TradingResult<PostMortemReport> {
+        report.completed_at = Some(Utc::now());
+        log_info!(LogCategory::RiskManagement, "{}", report.summary());
+        self.store.reports.push(report.clone());
+        if self.store.reports.len() > MAX_REPORTS {
+            let excess = self.store.reports.len() - MAX_REPORTS;
+            self.store.reports.drain(..excess);
+        }
+        self.save()?;
+        Ok(report)
+    }
+
+    /// Post-mortem reports, newest first
+    pub fn reports(&self, limit: usize) -> Vec<PostMortemReport> {
+        self.store.reports.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Lift the halt; `token_issued_at` must show the user logged in again after engagement
+    pub fn reset(&mut self, user: &str, token_issued_at: DateTime<Utc>) -> TradingResult<()> {
+        let engaged_at = match (self.store.status.engaged, self.store.status.engaged_at) {
+            (true, Some(engaged_at)) => engaged_at,
+            _ => return Err(TradingError::validation_error(
+                "kill_switch".to_string(),
+                "Kill switch is not engaged".to_string(),
+                None,
+            )),
+        };
+        let now = Utc::now();
+        if token_issued_at < engaged_at || now - token_issued_at > Duration::minutes(REAUTH_WINDOW_MINUTES) {
+            return Err(TradingError::auth_error(
+                AuthErrorType::SessionInvalid,
+                format!("Re-authenticate to reset the kill switch (token must be issued after engagement and within {} minutes)", REAUTH_WINDOW_MINUTES),
+            ));
+        }
+
+        let status = &mut self.store.status;
+        status.engaged = false;
+        status.last_reset_at = Some(now);
+        status.last_reset_by = Some(user.to_string());
+        self.save()?;
+        log_info!(LogCategory::RiskManagement, "Kill switch reset by {}", user);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reset_requires_fresh_authentication() {
+        let mut kill_switch = KillSwitch::new();
+        let stale_token = Utc::now() - Duration::seconds(1);
+        kill_switch.engage("alice", "runaway losses").unwrap();
+        assert!(kill_switch.ensure_disengaged().is_err());
+
+        assert!(kill_switch.reset("alice", stale_token).is_err());
+        assert!(kill_switch.is_engaged());
+
+        kill_switch.reset("alice", Utc::now()).unwrap();
+        assert!(!kill_switch.is_engaged());
+        assert_eq!(kill_switch.status().last_reset_by.as_deref(), Some("alice"));
+        assert!(kill_switch.reset("alice", Utc::now()).is_err());
+    }
+
+    #[test]
+    fn test_report_totals() {
+        let mut kill_switch = KillSwitch::new();
+        let mut report = kill_switch.engage("alice", "test").unwrap();
+        report.add_closed(ClosedPosition::new("swing_bot", "BTCUSDT", TradeSide::Long, Decimal::from(2), Decimal::from(100), Decimal::from(90)));
+        report.add_closed(ClosedPosition::new("advanced_trading", "ETHUSDT", TradeSide::Short, Decimal::from(1), Decimal::from(50), Decimal::from(40)));
+        assert_eq!(report.total_realized_pnl, Decimal::from(-10));
+
+        // A position without a price is reported as left open, not closed
+        report.add_unflattened(UnflattenedPosition {
+            source: "advanced_trading".to_string(),
+            symbol: "SOLUSDT".to_string(),
+            side: TradeSide::Long,
+            quantity: Decimal::from(3),
+            entry_price: Decimal::from(20),
+            reason: "no market price".to_string(),
+        });
+        assert_eq!(report.closed_positions.len(), 2);
+        assert_eq!(report.total_realized_pnl, Decimal::from(-10));
+        assert!(report.summary().contains("2 positions closed, 1 left open"));
+        assert_eq!(report.warnings, vec!["advanced_trading SOLUSDT not flattened: no market price".to_string()]);
+
+        kill_switch.record_report(report).unwrap();
+        let reports = kill_switch.reports(10);
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].completed_at.is_some());
+        assert_eq!(reports[0].unflattened_positions.len(), 1);
+    }
+}
@@ -0,0 +1,223 @@
+// Local Kline Store
+// Persists fetched klines per symbol/interval so models and replays can run offline
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use crate::errors::{TradingError, TradingResult};
+use crate::models::{KlineData, PriceData};
+use crate::persistence;
+
+/// Maximum number of klines retained per symbol/interval file
+const MAX_STORED_KLINES: usize = 100_000;
+
+/// File-backed kline storage under `data/klines/{SYMBOL}_{interval}.json`
+#[derive(Debug, Clone)]
+pub struct KlineStore {
+    root: PathBuf,
+}
+
+impl KlineStore {
+    /// Store rooted at the application data directory
+    pub fn open_default() -> TradingResult<Self> {
+        Ok(Self::new(persistence::data_dir()?.join("klines")))
+    }
+
+    pub fn new(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn path_for(&self, symbol: &str, interval: &str) -> TradingResult<PathBuf> {
+        let valid = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric());
+        if !valid(symbol) || !valid(interval) {
+            return Err(TradingError::validation_error(
+                "symbol".to_string(),
+                "Symbol and interval must be alphanumeric".to_string(),
+                Some(format!("{}_{}", symbol, interval))
+            ));
+        }
+
+        Ok(self.root.join(format!("{}_{}.json", symbol.to_uppercase(), interval)))
+    }
+
+    /// Load all stored klines for a symbol/interval, oldest first
+    pub fn load(&self, symbol: &str, interval: &str) -> TradingResult<Vec<KlineData>> {
+        let path = self.path_for(symbol, interval)?;
+        Ok(persistence::read_json(&path)?.unwrap_or_default())
+    }
+
+    /// Load stored klines whose open time falls within `[start, end]`
+    pub fn load_range(
+        &self,
+        symbol: &str,
+        interval: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> TradingResult<Vec<KlineData>> {
+        Ok(self.load(symbol, interval)?
+            .into_iter()
+            .filter(|k| k.open_time >= start && k.open_time <= end)
+            .collect())
+    }
+
+    /// Merge klines into the store, de-duplicating by open time (newer data wins).
+    /// Returns the number of klines stored after the merge.
+    pub fn merge(&self, symbol: &str, interval: &str, klines: &[KlineData]) -> TradingResult<usize> {
+        if klines.is_empty() {
+            return Ok(self.load(symbol, interval)?.len());
+        }
+
+        let path = self.path_for(symbol, interval)?;
+        let mut merged: BTreeMap<DateTime<Utc>, KlineData> = self.load(symbol, interval)?
+            .into_iter()
+            .map(|k| (k.open_time, k))
+            .collect();
+
+        for kline in klines {
+            merged.insert(kline.open_time, kline.clone());
+        }
+
+        let mut stored: Vec<KlineData> = merged.into_values().collect();
+        if stored.len() > MAX_STORED_KLINES {
+            stored.drain(..stored.len() - MAX_STORED_KLINES);
+        }
+
+        persistence::write_json(&path, &stored)?;
+        Ok(stored.len())
+    }
+
+    /// List stored (symbol, interval) pairs
+    pub fn list(&self) -> TradingResult<Vec<(String, String)>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = std::fs::read_dir(&self.root)
+            .map_err(|e| TradingError::config_error(
+                "kline_store".to_string(),
+                format!("Failed to read kline store: {}", e)
+            ))?;
+
+        let mut pairs: Vec<(String, String)> = entries
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                let stem = name.strip_suffix(".json")?;
+                let (symbol, interval) = stem.split_once('_')?;
+                Some((symbol.to_string(), interval.to_string()))
+            })
+            .collect();
+        pairs.sort();
+        Ok(pairs)
+    }
+}
+
+/// Records fetched klines into the store, appending only klines that closed after the last
+/// recorded one so repeated fetches of the same window do not rewrite the file
+#[derive(Debug, Clone, Default)]
+pub struct KlineRecorder {
+    store: Option<KlineStore>,
+    // Open time of the newest stored kline per (SYMBOL, interval)
+    stored_through: HashMap<(String, String), DateTime<Utc>>,
+}
+
+impl KlineRecorder {
+    /// Recorder without a store; klines are not persisted
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_store(store: KlineStore) -> Self {
+        Self { store: Some(store), stored_through: HashMap::new() }
+    }
+
+    /// Recorder writing to the application kline store
+    pub fn open_default() -> TradingResult<Self> {
+        Ok(Self::with_store(KlineStore::open_default()?))
+    }
+
+    /// Store the klines closed by `now` that are newer than the last recorded kline.
+    /// The stored series is read only the first time a symbol/interval is recorded.
+    /// Returns the number of klines appended.
+    pub fn record(&mut self, symbol: &str, interval: &str, klines: &[KlineData], now: DateTime<Utc>) -> TradingResult<usize> {
+        let store = match self.store.as_ref() {
+            Some(store) => store,
+            None => return Ok(0),
+        };
+
+        let key = (symbol.to_uppercase(), interval.to_string());
+        let stored_through = match self.stored_through.get(&key) {
+            Some(open_time) => Some(*open_time),
+            None => store.load(symbol, interval)?.last().map(|k| k.open_time),
+        };
+
+        let closed: Vec<KlineData> = klines.iter()
+            .filter(|k| k.close_time <= now && stored_through.is_none_or(|through| k.open_time > through))
+            .cloned()
+            .collect();
+
+        if !closed.is_empty() {
+            store.merge(symbol, interval, &closed)?;
+        }
+        if let Some(newest) = closed.iter().map(|k| k.open_time).max().or(stored_through) {
+            self.stored_through.insert(key, newest);
+        }
+        Ok(closed.len())
+    }
+}
+
+/// Convert klines into the price bars consumed by strategies (timestamped at open time)
+pub fn klines_to_price_data(klines: &[KlineData]) -> Vec<PriceData> {
+    klines.iter()
+        .map(|k| PriceData {
+            timestamp: k.open_time,
+            open: k.open,
+            high: k.high,
+            low: k.low,
+            close: k.close,
+            volume: k.volume,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::Decimal;
+
+    fn kline(open_time: DateTime<Utc>, close: i64) -> KlineData {
+        KlineData {
+            open_time,
+            close_time: open_time + Duration::minutes(1) - Duration::milliseconds(1),
+            open: Decimal::from(close),
+            high: Decimal::from(close),
+            low: Decimal::from(close),
+            close: Decimal::from(close),
+            volume: Decimal::ONE,
+        }
+    }
+
+    #[test]
+    fn test_recorder_appends_only_newly_closed_klines() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = KlineStore::new(dir.path().to_path_buf());
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut recorder = KlineRecorder::with_store(store.clone());
+
+        // The third kline is still forming
+        let klines: Vec<KlineData> = (0..3).map(|i| kline(start + Duration::minutes(i), 100 + i)).collect();
+        let now = start + Duration::minutes(2) + Duration::seconds(30);
+        assert_eq!(recorder.record("BTCUSDT", "1m", &klines, now).unwrap(), 2);
+        assert_eq!(recorder.record("BTCUSDT", "1m", &klines, now).unwrap(), 0);
+
+        let now = start + Duration::minutes(3);
+        assert_eq!(recorder.record("BTCUSDT", "1m", &klines, now).unwrap(), 1);
+        assert_eq!(store.load("BTCUSDT", "1m").unwrap().len(), 3);
+
+        // A fresh recorder resumes from the stored series
+        let mut resumed = KlineRecorder::with_store(store.clone());
+        assert_eq!(resumed.record("BTCUSDT", "1m", &klines, now).unwrap(), 0);
+        assert_eq!(KlineRecorder::new().record("BTCUSDT", "1m", &klines, now).unwrap(), 0);
+    }
+}
@@ -0,0 +1,426 @@
+// Selectable LRO Oscillator Engines
+// Unifies the legacy range-normalized LRO, EnhancedLRO and the Kalman-smoothed OptimizedLRO
+
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::enhanced_lro::{EnhancedLRO, LROConfig as EnhancedLROConfig, LROSignal};
+use crate::models::PriceData;
+use crate::multi_timeframe::regression;
+use crate::optimized_lro::{OptimizedLRO, SignalType as OptimizedSignalType};
+
+/// Maximum number of per-bar differences kept in a comparison report
+const MAX_REPORTED_DIFFERENCES: usize = 200;
+
+/// Oscillator engine used to generate LRO signals
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
+pub enum LroEngineKind {
+    /// Range-normalized regression deviation (the bot's incremental `LroCache` path)
+    LroCache,
+    /// Adaptive multi-timeframe EnhancedLRO
+    #[default]
+    Enhanced,
+    /// Kalman-smoothed incremental OptimizedLRO
+    Optimized,
+}
+
+impl LroEngineKind {
+    pub const ALL: [LroEngineKind; 3] = [LroEngineKind::LroCache, LroEngineKind::Enhanced, LroEngineKind::Optimized];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LroEngineKind::LroCache => "LroCache",
+            LroEngineKind::Enhanced => "Enhanced",
+            LroEngineKind::Optimized => "Optimized",
+        }
+    }
+}
+
+/// Direction of the LRO crossing its signal line inside the overbought/oversold zones, the bot's
+/// `LroCache` entry rule. `previous` and `current` are (LRO, signal line) pairs on consecutive bars.
+pub fn signal_line_cross(previous: (f64, f64), current: (f64, f64), overbought: f64, oversold: f64) -> SignalDirection {
+    let (prev_lro, prev_line) = previous;
+    let (lro, line) = current;
+
+    if lro < oversold && lro > line && prev_lro <= prev_line {
+        SignalDirection::Bullish
+    } else if lro > overbought && lro < line && prev_lro >= prev_line {
+        SignalDirection::Bearish
+    } else {
+        SignalDirection::Neutral
+    }
+}
+
+/// Signal strength from the distance to the signal line and to zero, before the bot's volume adjustment
+pub fn signal_line_strength(lro: f64, signal_line: f64) -> f64 {
+    ((lro - signal_line).abs() + lro.abs()) / 2.0
+}
+
+/// The bot's `LroCache` oscillator: deviation of the close from the regression line normalized by
+/// the window's high-low range (-1..1), signalling on signal-line crossovers.
+/// Adaptive thresholds and the trending-market strong signals depend on the bot's market analysis and
+/// are not reproduced; crossovers produce `Buy`/`Sell`.
+#[derive(Debug, Clone)]
+pub struct RangeNormalizedLRO {
+    period: usize,
+    signal_period: usize,
+    overbought: f64,
+    oversold: f64,
+    // (high, low, close) of the regression window
+    bars: VecDeque<(f64, f64, f64)>,
+    lro_history: VecDeque<f64>,
+    // LRO and signal line of the previous bar
+    previous: Option<(f64, f64)>,
+}
+
+impl RangeNormalizedLRO {
+    pub fn new(period: usize, signal_period: usize, overbought: f64, oversold: f64) -> Self {
+        Self {
+            period: period.max(2),
+            signal_period: signal_period.max(1),
+            overbought,
+            oversold,
+            bars: VecDeque::with_capacity(period + 1),
+            lro_history: VecDeque::with_capacity(signal_period + 1),
+            previous: None,
+        }
+    }
+
+    /// Thresholds must sit on the oscillator's -1..1 scale
+    pub fn validate_config(config: &EnhancedLROConfig) -> Result<(), String> {
+        if config.overbought_threshold <= 0.0 || config.overbought_threshold >= 1.0
+            || config.oversold_threshold >= 0.0 || config.oversold_threshold <= -1.0
+        {
+            return Err("LroCache thresholds must be within (0, 1) for overbought and (-1, 0) for oversold".to_string());
+        }
+        if config.base_period < 5 || config.signal_period < 3 {
+            return Err("LroCache needs a period of at least 5 and a signal period of at least 3".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn update(&mut self, price_data: &PriceData) -> Option<LROSignal> {
+        let close = price_data.close.to_f64()?;
+        if !close.is_finite() || close <= 0.0 {
+            return None;
+        }
+
+        let high = price_data.high.to_f64().unwrap_or(close);
+        let low = price_data.low.to_f64().unwrap_or(close);
+        self.bars.push_back((high, low, close));
+        if self.bars.len() > self.period {
+            self.bars.pop_front();
+        }
+        if self.bars.len() < self.period {
+            return None;
+        }
+
+        let closes: Vec<f64> = self.bars.iter().map(|(_, _, close)| *close).collect();
+        let (slope, intercept) = regression(&closes)?;
+        let predicted = slope * (self.period - 1) as f64 + intercept;
+        let range_high = self.bars.iter().map(|(high, _, _)| *high).fold(f64::NEG_INFINITY, f64::max);
+        let range_low = self.bars.iter().map(|(_, low, _)| *low).fold(f64::INFINITY, f64::min);
+        let range = range_high - range_low;
+        let lro = if range > 0.0 { ((close - predicted) / range).clamp(-1.0, 1.0) } else { 0.0 };
+
+        self.lro_history.push_back(lro);
+        if self.lro_history.len() > self.signal_period {
+            self.lro_history.pop_front();
+        }
+        if self.lro_history.len() < self.signal_period {
+            return None;
+        }
+
+        let signal_line = self.lro_history.iter().sum::<f64>() / self.signal_period as f64;
+        let direction = self.previous
+            .map_or(SignalDirection::Neutral, |previous| signal_line_cross(previous, (lro, signal_line), self.overbought, self.oversold));
+        self.previous = Some((lro, signal_line));
+
+        let confidence = signal_line_strength(lro, signal_line).min(1.0);
+        Some(match direction {
+            SignalDirection::Bullish => LROSignal::Buy { confidence, deviation: lro },
+            SignalDirection::Bearish => LROSignal::Sell { confidence, deviation: lro },
+            _ => LROSignal::Neutral { deviation: lro },
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.bars.clear();
+        self.lro_history.clear();
+        self.previous = None;
+    }
+}
+
+/// Convert an OptimizedLRO signal into the shared signal type (Kalman-filtered deviation)
+pub fn from_optimized_signal(signal: &crate::optimized_lro::LROSignal) -> LROSignal {
+    let confidence = signal.confidence;
+    let deviation = signal.filtered_value;
+    match signal.signal_type {
+        OptimizedSignalType::StrongBuy => LROSignal::StrongBuy { confidence, deviation },
+        OptimizedSignalType::Buy => LROSignal::Buy { confidence, deviation },
+        OptimizedSignalType::Neutral => LROSignal::Neutral { deviation },
+        OptimizedSignalType::Sell => LROSignal::Sell { confidence, deviation },
+        OptimizedSignalType::StrongSell => LROSignal::StrongSell { confidence, deviation },
+    }
+}
+
+/// A selected oscillator engine producing signals in the EnhancedLRO format
+#[derive(Debug, Clone)]
+pub enum LroEngine {
+    LroCache(RangeNormalizedLRO),
+    Enhanced(Box<EnhancedLRO>),
+    Optimized(Box<OptimizedLRO>),
+}
+
+impl LroEngine {
+    pub fn new(kind: LroEngineKind, config: &EnhancedLROConfig) -> Self {
+        match kind {
+            LroEngineKind::LroCache => LroEngine::LroCache(RangeNormalizedLRO::new(
+                config.base_period,
+                config.signal_period,
+                config.overbought_threshold,
+                config.oversold_threshold,
+            )),
+            LroEngineKind::Enhanced => LroEngine::Enhanced(Box::new(EnhancedLRO::new(config.clone()))),
+            LroEngineKind::Optimized => LroEngine::Optimized(Box::new(OptimizedLRO::new(config.base_period))),
+        }
+    }
+
+    pub fn kind(&self) -> LroEngineKind {
+        match self {
+            LroEngine::LroCache(_) => LroEngineKind::LroCache,
+            LroEngine::Enhanced(_) => LroEngineKind::Enhanced,
+            LroEngine::Optimized(_) => LroEngineKind::Optimized,
+        }
+    }
+
+    pub fn update(&mut self, price_data: &PriceData) -> Option<LROSignal> {
+        match self {
+            LroEngine::LroCache(engine) => engine.update(price_data),
+            LroEngine::Enhanced(engine) => engine.update(price_data),
+            LroEngine::Optimized(engine) => engine
+                .update(price_data.close.to_f64()?)
+                .map(|signal| from_optimized_signal(&signal)),
+        }
+    }
+}
+
+/// Signal direction used to compare engines
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SignalDirection {
+    Bullish,
+    Bearish,
+    Neutral,
+    NoSignal,
+}
+
+impl SignalDirection {
+    fn of(signal: Option<&LROSignal>) -> Self {
+        match signal {
+            Some(LROSignal::StrongBuy { .. }) | Some(LROSignal::Buy { .. }) => SignalDirection::Bullish,
+            Some(LROSignal::StrongSell { .. }) | Some(LROSignal::Sell { .. }) => SignalDirection::Bearish,
+            Some(LROSignal::Neutral { .. }) => SignalDirection::Neutral,
+            None => SignalDirection::NoSignal,
+        }
+    }
+}
+
+/// Per-engine results of a comparison run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineRunSummary {
+    pub engine: LroEngineKind,
+    pub updates: usize,
+    pub signals_produced: usize,
+    pub bullish_signals: usize,
+    pub bearish_signals: usize,
+    pub neutral_signals: usize,
+    pub mean_update_micros: f64,
+    pub p95_update_micros: f64,
+    pub max_update_micros: f64,
+}
+
+/// Directional agreement between two engines on bars where both produced a signal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineAgreement {
+    pub first: LroEngineKind,
+    pub second: LroEngineKind,
+    pub compared_bars: usize,
+    pub agreeing_bars: usize,
+    pub agreement_rate: f64,
+}
+
+/// A bar where at least one engine gave an actionable signal and engines disagreed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalDifference {
+    pub timestamp: DateTime<Utc>,
+    pub directions: HashMap<String, SignalDirection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EngineComparisonReport {
+    pub bars: usize,
+    pub engines: Vec<EngineRunSummary>,
+    pub agreement: Vec<EngineAgreement>,
+    pub total_differences: usize,
+    pub differences: Vec<SignalDifference>,
+}
+
+/// Run every engine over the same data and report signal differences and update latency
+pub fn compare_engines(data: &[PriceData], config: &EnhancedLROConfig) -> EngineComparisonReport {
+    let mut engines: Vec<LroEngine> = LroEngineKind::ALL.iter().map(|kind| LroEngine::new(*kind, config)).collect();
+    let mut latencies: Vec<Vec<f64>> = vec![Vec::with_capacity(data.len()); engines.len()];
+    let mut directions: Vec<Vec<SignalDirection>> = vec![Vec::with_capacity(data.len()); engines.len()];
+
+    for price_data in data {
+        for (i, engine) in engines.iter_mut().enumerate() {
+            let start = Instant::now();
+            let signal = engine.update(price_data);
+            latencies[i].push(start.elapsed().as_secs_f64() * 1_000_000.0);
+            directions[i].push(SignalDirection::of(signal.as_ref()));
+        }
+    }
+
+    let summaries = engines.iter().enumerate().map(|(i, engine)| {
+        let count = |d: SignalDirection| directions[i].iter().filter(|x| **x == d).count();
+        let mut sorted = latencies[i].clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let mean = if sorted.is_empty() { 0.0 } else { sorted.iter().sum::<f64>() / sorted.len() as f64 };
+        let p95 = sorted.get(((sorted.len() as f64 * 0.95) as usize).min(sorted.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or(0.0);
+
+        EngineRunSummary {
+            engine: engine.kind(),
+            updates: data.len(),
+            signals_produced: data.len() - count(SignalDirection::NoSignal),
+            bullish_signals: count(SignalDirection::Bullish),
+            bearish_signals: count(SignalDirection::Bearish),
+            neutral_signals: count(SignalDirection::Neutral),
+            mean_update_micros: mean,
+            p95_update_micros: p95,
+            max_update_micros: sorted.last().copied().unwrap_or(0.0),
+        }
+    }).collect();
+
+    let mut agreement = Vec::new();
+    for a in 0..engines.len() {
+        for b in (a + 1)..engines.len() {
+            let compared: Vec<(SignalDirection, SignalDirection)> = directions[a].iter()
+                .zip(&directions[b])
+                .filter(|(x, y)| **x != SignalDirection::NoSignal && **y != SignalDirection::NoSignal)
+                .map(|(x, y)| (*x, *y))
+                .collect();
+            let agreeing = compared.iter().filter(|(x, y)| x == y).count();
+            agreement.push(EngineAgreement {
+                first: engines[a].kind(),
+                second: engines[b].kind(),
+                compared_bars: compared.len(),
+                agreeing_bars: agreeing,
+                agreement_rate: if compared.is_empty() { 0.0 } else { agreeing as f64 / compared.len() as f64 },
+            });
+        }
+    }
+
+    let mut differences = Vec::new();
+    let mut total_differences = 0;
+    for (bar, price_data) in data.iter().enumerate() {
+        let bar_directions: Vec<SignalDirection> = directions.iter().map(|d| d[bar]).collect();
+        let actionable = bar_directions.iter().any(|d| matches!(d, SignalDirection::Bullish | SignalDirection::Bearish));
+        let all_same = bar_directions.windows(2).all(|w| w[0] == w[1]);
+        if actionable && !all_same {
+            total_differences += 1;
+            if differences.len() < MAX_REPORTED_DIFFERENCES {
+                differences.push(SignalDifference {
+                    timestamp: price_data.timestamp,
+                    directions: engines.iter()
+                        .zip(&bar_directions)
+                        .map(|(engine, direction)| (engine.kind().as_str().to_string(), *direction))
+                        .collect(),
+                });
+            }
+        }
+    }
+
+    EngineComparisonReport {
+        bars: data.len(),
+        engines: summaries,
+        agreement,
+        total_differences,
+        differences,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn sample_data(count: usize) -> Vec<PriceData> {
+        let start = Utc::now() - Duration::minutes(count as i64);
+        (0..count).map(|i| {
+            let close = 100.0 + (i as f64 * 0.3).sin() * 0.5 + i as f64 * 0.01;
+            let price = Decimal::from_f64(close).unwrap_or_default();
+            PriceData {
+                timestamp: start + Duration::minutes(i as i64),
+                open: price,
+                high: price + Decimal::new(1, 1),
+                low: price - Decimal::new(1, 1),
+                close: price,
+                volume: Decimal::from(100),
+            }
+        }).collect()
+    }
+
+    #[test]
+    fn test_range_normalized_lro_signals_on_signal_line_crossovers() {
+        let closes: Vec<f64> = (0..22).map(|i| 100.0 + (i % 2) as f64 * 0.2)
+            .chain([90.0, 89.0, 89.5])
+            .collect();
+        let data: Vec<PriceData> = sample_data(closes.len()).into_iter().zip(&closes).map(|(bar, &close)| {
+            let price = Decimal::from_f64(close).unwrap_or_default();
+            PriceData { open: price, high: price + Decimal::new(1, 1), low: price - Decimal::new(1, 1), close: price, ..bar }
+        }).collect();
+
+        let config = EnhancedLROConfig { base_period: 20, signal_period: 3, overbought_threshold: 0.4, oversold_threshold: -0.4, ..Default::default() };
+        assert!(RangeNormalizedLRO::validate_config(&config).is_ok());
+        assert!(RangeNormalizedLRO::validate_config(&EnhancedLROConfig::default()).is_err());
+
+        let mut lro = LroEngine::new(LroEngineKind::LroCache, &config);
+        let signals: Vec<Option<LROSignal>> = data.iter().map(|bar| lro.update(bar)).collect();
+        // Warming up until the regression window and the signal line are full
+        assert!(signals[..21].iter().all(Option::is_none));
+        // The drop takes the LRO deep into oversold, below its signal line: no entry yet
+        assert!(matches!(signals[22], Some(LROSignal::Neutral { deviation }) if deviation < -0.4));
+        // Turning back up through the signal line while still oversold is the bot's buy crossover
+        assert!(matches!(signals[24], Some(LROSignal::Buy { deviation, .. }) if deviation < -0.4));
+    }
+
+    #[test]
+    fn test_every_engine_produces_signals() {
+        let data = sample_data(150);
+        for kind in LroEngineKind::ALL {
+            let mut engine = LroEngine::new(kind, &EnhancedLROConfig::default());
+            let produced = data.iter().filter_map(|p| engine.update(p)).count();
+            assert!(produced > 0, "{} produced no signals", kind.as_str());
+        }
+    }
+
+    #[test]
+    fn test_comparison_report_covers_all_engines() {
+        let data = sample_data(120);
+        let report = compare_engines(&data, &EnhancedLROConfig::default());
+
+        assert_eq!(report.bars, 120);
+        assert_eq!(report.engines.len(), 3);
+        assert_eq!(report.agreement.len(), 3);
+        assert!(report.differences.len() <= report.total_differences);
+        for summary in &report.engines {
+            assert_eq!(summary.updates, 120);
+            assert!(summary.max_update_micros >= summary.mean_update_micros);
+        }
+    }
+}
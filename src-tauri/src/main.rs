@@ -28,10 +28,31 @@ mod cache;
 mod connection_pool;
 mod gpu_memory_manager;
 mod enhanced_risk_manager;
+mod advanced_risk_manager;
 mod advanced_trading;
 mod performance_cache;
 mod enhanced_lro;
 mod backtesting;
+mod multi_timeframe;
+mod persistence;
+mod kline_store;
+mod regime_detection;
+mod optimized_lro;
+mod lro_engine;
+mod divergence;
+mod technical_alerts;
+mod position_sizing;
+mod stop_manager;
+mod drawdown_governor;
+mod kill_switch;
+mod risk_limits;
+mod order_preview;
+mod anomaly_detection;
+mod market_intelligence;
+mod pairs_trading;
+mod signal_fusion;
+mod gpu_neural_predictor;
+mod model_inference;
 
 use gpu_renderer::GpuRenderer;
 use gpu_trading::GpuTradingAccelerator;
@@ -44,6 +65,22 @@ use backtesting::BacktestEngine;
 use validation::InputValidator;
 use atomic_operations::AtomicBotState;
 use auth::BotAuthMiddleware;
+use regime_detection::RegimeClassifier;
+use signal_fusion::FusionModel;
+use technical_alerts::TechnicalAlertSystem;
+use advanced_risk_manager::{PortfolioHeatTracker, SharedHeatTracker};
+use drawdown_governor::{DrawdownGovernor, DrawdownGovernorConfig, SharedDrawdownGovernor};
+use kill_switch::{KillSwitch, SharedKillSwitch};
+use risk_limits::{RiskLimitStore, SharedRiskLimits};
+use anomaly_detection::AnomalyDetector;
+use market_intelligence::MarketIntelligenceEngine;
+use market_intelligence::fear_greed::FearGreedHistory;
+use pairs_trading::{PairsDesk, SharedPairsDesk};
+use gpu_neural_predictor::GpuNeuralPredictor;
+use model_inference::ModelRegistry;
+use kline_store::KlineRecorder;
+use binance_client::ImprovedBinanceClient;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, serde::Serialize)]
 struct SystemStats {
@@ -60,6 +97,27 @@ pub struct TradingState {
     pub swing_bot: Arc<RwLock<SwingTradingBot>>,
     pub gpu_accelerator: Arc<RwLock<Option<GpuTradingAccelerator>>>,
     pub advanced_trading_engine: Arc<RwLock<Option<AdvancedTradingEngine>>>,
+    pub alert_system: Arc<RwLock<TechnicalAlertSystem>>,
+    // Portfolio heat and correlated exposure shared by every bot
+    pub portfolio_heat: SharedHeatTracker,
+    // Account drawdown governor shared by every bot
+    pub drawdown_governor: SharedDrawdownGovernor,
+    // Global flatten-and-halt switch, persisted across restarts
+    pub kill_switch: SharedKillSwitch,
+    // Per-account, per-bot and per-symbol risk limits, hot-reloaded from disk
+    pub risk_limits: SharedRiskLimits,
+    // Sentiment, news, correlation and predictive analytics, started on demand
+    pub market_intelligence: Arc<RwLock<Option<Arc<MarketIntelligenceEngine>>>>,
+    // Cointegrated pairs traded through paper execution
+    pub pairs_desk: SharedPairsDesk,
+    // Trained neural price predictors keyed by symbol and interval
+    pub neural_predictors: Arc<RwLock<HashMap<String, GpuNeuralPredictor>>>,
+    // Hash-verified ONNX models loaded from the models directory
+    pub model_registry: Arc<RwLock<ModelRegistry>>,
+    // Appends newly closed klines from fetches to the local kline store
+    pub kline_recorder: Arc<std::sync::Mutex<KlineRecorder>>,
+    // REST client of the background price feed check, started on demand
+    pub price_feed_monitor: Arc<RwLock<Option<Arc<ImprovedBinanceClient>>>>,
     // Modern atomic state management
     pub atomic_state: Arc<AtomicBotState>,
     pub auth_middleware: Arc<BotAuthMiddleware>,
@@ -71,6 +129,56 @@ pub struct TradingState {
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let mut swing_bot = SwingTradingBot::new(LROConfig::default());
+    swing_bot.set_regime_classifier(RegimeClassifier::load().ok().flatten());
+    swing_bot.set_fusion_model(FusionModel::load().ok().flatten());
+    swing_bot.set_fear_greed_history(FearGreedHistory::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to load Fear & Greed history, starting empty: {}", e);
+        FearGreedHistory::new()
+    }));
+    let portfolio_heat: SharedHeatTracker = Arc::new(std::sync::Mutex::new(PortfolioHeatTracker::new()));
+    swing_bot.set_portfolio_heat(portfolio_heat.clone());
+    let drawdown_governor = DrawdownGovernor::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to load drawdown governor state, starting fresh: {}", e);
+        DrawdownGovernor::new(DrawdownGovernorConfig::default())
+    });
+    let drawdown_governor: SharedDrawdownGovernor = Arc::new(std::sync::Mutex::new(drawdown_governor));
+    swing_bot.set_drawdown_governor(drawdown_governor.clone());
+    let risk_limits = RiskLimitStore::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to load risk limits, using defaults: {}", e);
+        RiskLimitStore::new()
+    });
+    let risk_limits: SharedRiskLimits = Arc::new(std::sync::Mutex::new(risk_limits));
+    swing_bot.set_risk_limits(risk_limits.clone());
+    swing_bot.set_anomaly_detector(AnomalyDetector::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to load market data anomalies, starting fresh: {}", e);
+        AnomalyDetector::new()
+    }));
+    let kill_switch = KillSwitch::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to load kill switch state, starting disengaged: {}", e);
+        KillSwitch::new()
+    });
+    let atomic_state = AtomicBotState::new();
+    if kill_switch.is_engaged() {
+        let reason = kill_switch.status().reason.clone().unwrap_or_default();
+        eprintln!("Kill switch engaged ({}), bots stay halted until it is reset", reason);
+        swing_bot.stop_bot(&format!("Kill switch: {}", reason));
+        let _ = atomic_state.trigger_emergency_stop();
+    }
+    let kill_switch: SharedKillSwitch = Arc::new(std::sync::Mutex::new(kill_switch));
+    let alert_system = TechnicalAlertSystem::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to load alert rules, starting with none: {}", e);
+        TechnicalAlertSystem::new()
+    });
+    let model_registry = ModelRegistry::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to load signal models, starting with none: {}", e);
+        ModelRegistry::new()
+    });
+    let kline_recorder = KlineRecorder::open_default().unwrap_or_else(|e| {
+        eprintln!("Failed to open kline store, fetched klines will not be stored: {}", e);
+        KlineRecorder::new()
+    });
+    
     tauri::Builder::default()
         .manage(AppState::new(RwLock::new(SystemStats {
             fps: 0.0,
@@ -80,11 +188,22 @@ pub fn run() {
         .manage(TradingState {
             paper_trades: Arc::new(RwLock::new(Vec::new())),
             websocket: Arc::new(ImprovedBinanceWebSocket::new()),
-            swing_bot: Arc::new(RwLock::new(SwingTradingBot::new(LROConfig::default()))),
+            swing_bot: Arc::new(RwLock::new(swing_bot)),
             gpu_accelerator: Arc::new(RwLock::new(None)),
             advanced_trading_engine: Arc::new(RwLock::new(None)),
+            alert_system: Arc::new(RwLock::new(alert_system)),
+            portfolio_heat,
+            drawdown_governor,
+            kill_switch,
+            risk_limits,
+            market_intelligence: Arc::new(RwLock::new(None)),
+            pairs_desk: Arc::new(std::sync::Mutex::new(PairsDesk::new())),
+            neural_predictors: Arc::new(RwLock::new(HashMap::new())),
+            model_registry: Arc::new(RwLock::new(model_registry)),
+            kline_recorder: Arc::new(std::sync::Mutex::new(kline_recorder)),
+            price_feed_monitor: Arc::new(RwLock::new(None)),
             // Modern atomic state management
-            atomic_state: Arc::new(AtomicBotState::new()),
+            atomic_state: Arc::new(atomic_state),
             auth_middleware: Arc::new(BotAuthMiddleware::new()),
             // Legacy fields for compatibility (deprecated)
             bot_operation_lock: Arc::new(Mutex::new(())),
@@ -164,7 +283,73 @@ pub fn run() {
             commands::validate_price_data_batch,
             commands::validate_user_input,
             commands::validate_file_path,
-            commands::validate_comprehensive_config
+            commands::validate_comprehensive_config,
+            commands::train_regime_classifier,
+            commands::get_market_regime,
+            commands::compare_lro_engines,
+            commands::scan_divergences,
+            commands::get_recent_divergences,
+            commands::create_alert_rule,
+            commands::list_alert_rules,
+            commands::delete_alert_rule,
+            commands::get_alert_history,
+            commands::get_var_report,
+            commands::backtest_var_model,
+            commands::list_stress_events,
+            commands::run_stress_replay,
+            commands::run_shock_scenario,
+            commands::get_portfolio_heat,
+            commands::set_heat_limits,
+            commands::get_drawdown_status,
+            commands::get_drawdown_governor_config,
+            commands::set_drawdown_governor_config,
+            commands::reset_drawdown_high_water_mark,
+            commands::get_drawdown_events,
+            commands::engage_kill_switch,
+            commands::reset_kill_switch,
+            commands::get_kill_switch_status,
+            commands::get_kill_switch_reports,
+            commands::get_risk_limits_config,
+            commands::update_risk_limits,
+            commands::get_effective_risk_limits,
+            commands::get_risk_limit_audit,
+            commands::preview_order,
+            commands::get_market_anomalies,
+            commands::verify_price_feed,
+            commands::start_price_feed_monitor,
+            commands::stop_price_feed_monitor,
+            commands::initialize_market_intelligence,
+            commands::refresh_market_intelligence,
+            commands::get_market_intelligence,
+            commands::get_intelligence_score,
+            commands::get_market_sentiment,
+            commands::get_sentiment_report,
+            commands::get_fear_greed_history,
+            commands::backfill_fear_greed,
+            commands::get_market_predictions,
+            commands::get_correlation_analysis,
+            commands::analyze_asset_correlations,
+            commands::get_correlation_matrix,
+            commands::get_rolling_correlation,
+            commands::get_correlation_breakpoints,
+            commands::get_lead_lag,
+            commands::get_news_intelligence,
+            commands::get_news_alerts,
+            commands::get_market_intelligence_report,
+            commands::scan_cointegrated_pairs,
+            commands::backtest_pairs_strategy,
+            commands::add_trading_pair,
+            commands::remove_trading_pair,
+            commands::get_trading_pairs,
+            commands::evaluate_trading_pairs,
+            commands::get_signal_fusion_status,
+            commands::train_signal_fusion_model,
+            commands::clear_signal_fusion_model,
+            commands::train_neural_predictor,
+            commands::predict_neural_price,
+            commands::get_signal_models,
+            commands::reload_signal_models,
+            commands::predict_with_signal_model
         ])
         .setup(|app| {
             // Initialize logging system
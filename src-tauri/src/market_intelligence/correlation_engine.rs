@@ -1,14 +1,40 @@
 // Correlation Analysis Engine for Market Intelligence Agent
-use crate::types::*;
+use async_trait::async_trait;
+use crate::errors::{TradingError, TradingResult};
+use crate::kline_store::KlineStore;
+use crate::logging::LogCategory;
+use crate::log_warning;
+use crate::market_intelligence::TimeRange;
+use super::predictive_analytics::{interval_minutes, normal_cdf};
+use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tokio::sync::RwLock;
-use std::sync::Arc;
-use chrono::{DateTime, Utc};
+use std::collections::{BTreeSet, HashMap};
+use chrono::{DateTime, Duration, Timelike, Utc};
+
+/// Kline interval correlations are computed on
+const CORRELATION_INTERVAL: &str = "1h";
+/// Fewest overlapping returns a correlation is computed from
+const MIN_OBSERVATIONS: usize = 10;
+/// Longest rolling window used in reports
+const ROLLING_WINDOW: usize = 48;
+/// RiskMetrics decay for EWMA correlation
+pub const EWMA_LAMBDA: f64 = 0.94;
+/// Shortest segment either side of a correlation break
+const MIN_BREAK_SEGMENT: usize = 30;
+/// Family-wise significance a break must clear
+const BREAK_SIGNIFICANCE: f64 = 0.01;
+/// Largest lead or lag, in bars, searched between two assets
+pub const MAX_LEAD_LAG: usize = 12;
+/// Bars ahead correlation forecasts cover
+const FORECAST_BARS: usize = 24;
+const SIGNIFICANCE: f64 = 0.05;
+const Z_95: f64 = 1.959964;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorrelationAnalysisEngine {
     correlation_calculators: HashMap<String, Box<dyn CorrelationCalculator + Send + Sync>>,
+    /// Source of the klines returns are computed from
+    kline_store: Option<KlineStore>,
+    interval: String,
     pattern_detector: CorrelationPatternDetector,
     relationship_analyzer: MarketRelationshipAnalyzer,
     cross_asset_analyzer: CrossAssetCorrelationAnalyzer,
@@ -16,6 +42,108 @@ pub struct CorrelationAnalysisEngine {
     predictive_correlator: PredictiveCorrelationEngine,
 }
 
+/// An asset and its (open time, close) pairs
+pub type CloseSeries = (String, Vec<(DateTime<Utc>, f64)>);
+
+/// Log returns of several assets over the bars they all have, oldest first
+#[derive(Debug, Clone)]
+pub struct AlignedReturns {
+    pub assets: Vec<String>,
+    /// Open time of the bar each return ends on
+    pub timestamps: Vec<DateTime<Utc>>,
+    /// One series per asset, in `assets` order
+    pub returns: Vec<Vec<f64>>,
+    pub bar: Duration,
+}
+
+impl AlignedReturns {
+    /// Keep the timestamps every asset has a positive close for and take log returns between them
+    pub fn from_closes(series: &[CloseSeries], bar: Duration) -> Self {
+        let mut common: Option<BTreeSet<DateTime<Utc>>> = None;
+        for (_, closes) in series {
+            let times: BTreeSet<DateTime<Utc>> = closes.iter()
+                .filter(|(_, close)| *close > 0.0)
+                .map(|(time, _)| *time)
+                .collect();
+            common = Some(match common {
+                Some(common) => common.intersection(&times).copied().collect(),
+                None => times,
+            });
+        }
+        let common: Vec<DateTime<Utc>> = common.unwrap_or_default().into_iter().collect();
+
+        let returns = series.iter()
+            .map(|(_, closes)| {
+                let by_time: HashMap<DateTime<Utc>, f64> = closes.iter().copied().collect();
+                common.windows(2).map(|pair| (by_time[&pair[1]] / by_time[&pair[0]]).ln()).collect()
+            })
+            .collect();
+
+        Self {
+            assets: series.iter().map(|(asset, _)| asset.clone()).collect(),
+            timestamps: common.iter().skip(1).copied().collect(),
+            returns,
+            bar,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timestamps.is_empty()
+    }
+
+    fn span(&self) -> std::time::Duration {
+        (self.bar * self.len() as i32).to_std().unwrap_or_default()
+    }
+}
+
+/// Cross-correlation of two assets' returns over a range of lags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeadLagAnalysis {
+    pub asset_a: String,
+    pub asset_b: String,
+    /// Correlation of `asset_a` at t with `asset_b` at t + lag bars
+    pub cross_correlations: Vec<(i64, f64)>,
+    /// Lag with the strongest correlation; positive when `asset_a` moves first
+    pub best_lag: i64,
+    pub best_correlation: f64,
+    /// Two-sided p-value of the best correlation, Bonferroni-adjusted for the lags searched
+    pub p_value: f64,
+    /// The asset that moves first, when the best lag is not zero and significant
+    pub leader: Option<String>,
+    pub bar_minutes: i64,
+}
+
+/// Significant change in the correlation of two return series
+#[derive(Debug, Clone)]
+struct CorrelationBreak {
+    /// First return of the new regime
+    index: usize,
+    pre: f64,
+    post: f64,
+    p_value: f64,
+}
+
+/// Everything the analyzers need about one pair of assets
+struct PairAnalysis {
+    a: usize,
+    b: usize,
+    correlation: f64,
+    p_value: f64,
+    window: usize,
+    /// Pearson correlation of each window, the first ending at `window - 1`
+    rolling: Vec<f64>,
+    /// EWMA correlation, the first value after `MIN_OBSERVATIONS` returns
+    ewma: Vec<f64>,
+    breaks: Vec<CorrelationBreak>,
+    /// (start, end, correlation) of the stretches between breaks
+    segments: Vec<(usize, usize, f64)>,
+    lead_lag: LeadLagAnalysis,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CorrelationData {
     pub asset_a: String,
@@ -29,7 +157,7 @@ pub struct CorrelationData {
     pub last_updated: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum CorrelationMethod {
     Pearson,
     Spearman,
@@ -284,54 +412,64 @@ pub enum ExtremeType {
     RegimeShift,
 }
 
+
+#[async_trait]
 pub trait CorrelationCalculator {
     async fn calculate_correlation(&self, data_a: &[f64], data_b: &[f64]) -> TradingResult<CorrelationData>;
     async fn calculate_rolling_correlation(&self, data_a: &[f64], data_b: &[f64], window: usize) -> TradingResult<Vec<f64>>;
+    /// Two-sided p-value of a correlation over `sample_size` observations
     async fn test_significance(&self, correlation: f64, sample_size: usize) -> TradingResult<f64>;
 }
 
 impl CorrelationAnalysisEngine {
     /// Create new correlation analysis engine
     pub async fn new() -> TradingResult<Self> {
-        let correlation_calculators = Self::initialize_correlation_calculators().await?;
-        let pattern_detector = CorrelationPatternDetector::new().await?;
-        let relationship_analyzer = MarketRelationshipAnalyzer::new().await?;
-        let cross_asset_analyzer = CrossAssetCorrelationAnalyzer::new().await?;
-        let temporal_analyzer = TemporalCorrelationAnalyzer::new().await?;
-        let predictive_correlator = PredictiveCorrelationEngine::new().await?;
-
-        Ok(Self {
+        let kline_store = match KlineStore::open_default() {
+            Ok(store) => Some(store),
+            Err(e) => {
+                log_warning!(LogCategory::DataProcessing, "Kline store unavailable, correlations disabled: {}", e);
+                None
+            }
+        };
+        Ok(Self::with_store(kline_store, CORRELATION_INTERVAL))
+    }
+
+    pub fn with_store(kline_store: Option<KlineStore>, interval: &str) -> Self {
+        let mut correlation_calculators: HashMap<String, Box<dyn CorrelationCalculator + Send + Sync>> = HashMap::new();
+        correlation_calculators.insert("pearson".to_string(), Box::new(PearsonCorrelationCalculator));
+        correlation_calculators.insert("spearman".to_string(), Box::new(SpearmanCorrelationCalculator));
+        correlation_calculators.insert("kendall".to_string(), Box::new(KendallCorrelationCalculator));
+
+        Self {
             correlation_calculators,
-            pattern_detector,
-            relationship_analyzer,
-            cross_asset_analyzer,
-            temporal_analyzer,
-            predictive_correlator,
-        })
+            kline_store,
+            interval: interval.to_string(),
+            pattern_detector: CorrelationPatternDetector,
+            relationship_analyzer: MarketRelationshipAnalyzer,
+            cross_asset_analyzer: CrossAssetCorrelationAnalyzer,
+            temporal_analyzer: TemporalCorrelationAnalyzer,
+            predictive_correlator: PredictiveCorrelationEngine,
+        }
     }
 
     /// Perform comprehensive correlation analysis
     pub async fn analyze_correlations(&self, assets: &[String], time_range: TimeRange) -> TradingResult<CorrelationAnalysisReport> {
+        let data = self.get_price_data(assets, time_range).await?;
+
         // Calculate correlation matrix
-        let correlation_matrix = self.calculate_correlation_matrix(assets, time_range).await?;
-        
+        let correlation_matrix = self.matrix_from(&data, CorrelationMethod::Pearson, time_range).await?;
+
         // Identify significant correlations
-        let significant_correlations = self.identify_significant_correlations(&correlation_matrix).await?;
-        
-        // Detect correlation patterns
-        let correlation_patterns = self.pattern_detector.detect_patterns(assets, time_range).await?;
-        
-        // Analyze market relationships
-        let market_relationships = self.relationship_analyzer.analyze_relationships(assets, time_range).await?;
-        
-        // Perform cross-asset analysis
-        let cross_asset_analysis = self.cross_asset_analyzer.analyze_cross_assets(assets, time_range).await?;
-        
-        // Analyze temporal correlations
-        let temporal_analysis = self.temporal_analyzer.analyze_temporal_correlations(assets, time_range).await?;
-        
-        // Generate predictive insights
-        let predictive_insights = self.predictive_correlator.generate_insights(assets, time_range).await?;
+        let significant_correlations = self.identify_significant_correlations(&data, &correlation_matrix);
+
+        // Rolling, EWMA, breaks and lead-lag for every pair feed the remaining analyses
+        let pairs = analyze_pairs(&data);
+
+        let correlation_patterns = self.pattern_detector.detect_patterns(&data, &pairs);
+        let market_relationships = self.relationship_analyzer.analyze_relationships(&data, &pairs);
+        let cross_asset_analysis = self.cross_asset_analyzer.analyze_cross_assets(&data);
+        let temporal_analysis = self.temporal_analyzer.analyze_temporal_correlations(&data, &pairs);
+        let predictive_insights = self.predictive_correlator.generate_insights(&data, &pairs);
 
         Ok(CorrelationAnalysisReport {
             analysis_timestamp: Utc::now(),
@@ -345,46 +483,115 @@ impl CorrelationAnalysisEngine {
         })
     }
 
-    /// Calculate correlation matrix for multiple assets
-    pub async fn calculate_correlation_matrix(&self, assets: &[String], time_range: TimeRange) -> TradingResult<CorrelationMatrix> {
-        let n = assets.len();
-        let mut correlation_values = vec![vec![0.0; n]; n];
-        let mut p_values = vec![vec![1.0; n]; n];
-        
-        // Get price data for all assets
-        let price_data = self.get_price_data(assets, time_range).await?;
-        
-        // Calculate pairwise correlations
-        for i in 0..n {
-            for j in 0..n {
-                if i == j {
-                    correlation_values[i][j] = 1.0;
-                    p_values[i][j] = 0.0;
-                } else if i < j {
-                    let data_a = &price_data[&assets[i]];
-                    let data_b = &price_data[&assets[j]];
-                    
-                    if let Some(calculator) = self.correlation_calculators.get("pearson") {
-                        let correlation_data = calculator.calculate_correlation(data_a, data_b).await?;
-                        correlation_values[i][j] = correlation_data.correlation_coefficient;
-                        correlation_values[j][i] = correlation_data.correlation_coefficient;
-                        p_values[i][j] = correlation_data.p_value;
-                        p_values[j][i] = correlation_data.p_value;
-                    }
-                }
+    /// Correlation summary for one symbol against BTC and ETH over the last 30 days
+    pub async fn analyze_symbol_correlations(&self, symbol: &str) -> TradingResult<super::CorrelationAnalysis> {
+        let time_range = TimeRange::last_hours(30 * 24);
+        let mut assets = vec![symbol.to_string()];
+        for benchmark in ["BTCUSDT", "ETHUSDT"] {
+            if !assets.iter().any(|a| a == benchmark) && self.has_klines(benchmark) {
+                assets.push(benchmark.to_string());
             }
         }
+        let data = self.get_price_data(&assets, time_range).await?;
+        let matrix = self.matrix_from(&data, CorrelationMethod::Pearson, time_range).await?;
+        let correlation_with = |benchmark: &str| assets.iter()
+            .position(|a| a == benchmark)
+            .map(|j| matrix.correlation_values[0][j])
+            .unwrap_or(0.0);
+        let others: Vec<f64> = matrix.correlation_values[0].iter().skip(1).copied().collect();
+        let top_correlation = if others.is_empty() { 1.0 } else { mean(&others) };
+
+        // Equal-weight portfolio of the assets: n / sqrt(sum of all pairwise correlations)
+        let n = assets.len() as f64;
+        let correlation_sum: f64 = matrix.correlation_values.iter().flatten().sum();
+        let diversification_ratio = n / correlation_sum.max(n).sqrt() / n.sqrt();
+        let effective_assets = n * n / correlation_sum.max(n);
+
+        // Stability of the correlation with the first benchmark
+        let (direction, stability, breakdown_risk) = match data.returns.get(1) {
+            Some(benchmark) => {
+                let window = report_window(data.len());
+                let rolling = rolling_correlation(&data.returns[0], benchmark, window, pearson);
+                let stability = (1.0 - std_dev(&rolling)).clamp(0.0, 1.0);
+                let change = match (rolling.first(), rolling.last()) {
+                    (Some(first), Some(last)) => last - first,
+                    _ => 0.0,
+                };
+                let direction = match change {
+                    c if c > 0.4 => super::TrendDirection::StronglyIncreasing,
+                    c if c > 0.1 => super::TrendDirection::Increasing,
+                    c if c < -0.4 => super::TrendDirection::StronglyDecreasing,
+                    c if c < -0.1 => super::TrendDirection::Decreasing,
+                    _ => super::TrendDirection::Stable,
+                };
+                let breaks = correlation_breaks(&data.returns[0], benchmark, MIN_BREAK_SEGMENT, BREAK_SIGNIFICANCE);
+                // A recent break means the relationship is already shifting
+                let recent_break = breaks.last().is_some_and(|b| data.len() - b.index <= window);
+                (direction, stability, if recent_break { 1.0_f64.min(2.0 * (1.0 - stability)) } else { 1.0 - stability })
+            }
+            None => (super::TrendDirection::Stable, 1.0, 0.0),
+        };
+        let btc_correlation = correlation_with("BTCUSDT");
+        let current_regime = match top_correlation {
+            c if c > 0.85 => super::RiskRegime::MarketStress,
+            c if c > 0.6 => super::RiskRegime::RiskOn,
+            _ => super::RiskRegime::Normal,
+        };
 
-        Ok(CorrelationMatrix {
-            assets: assets.to_vec(),
-            correlation_values,
-            p_values,
-            calculation_timestamp: Utc::now(),
-            method_used: CorrelationMethod::Pearson,
-            time_period: time_range,
+        Ok(super::CorrelationAnalysis {
+            crypto_correlations: super::CryptoCorrelations {
+                btc_correlation,
+                eth_correlation: correlation_with("ETHUSDT"),
+                top_10_correlation: top_correlation,
+                sector_correlations: HashMap::new(),
+            },
+            // Only exchange klines are available, so there is nothing to correlate other asset classes with
+            cross_asset_correlations: super::CrossAssetCorrelations {
+                stock_correlation: 0.0,
+                bond_correlation: 0.0,
+                commodity_correlation: 0.0,
+                currency_correlation: 0.0,
+                risk_on_correlation: btc_correlation,
+            },
+            correlation_trends: super::CorrelationTrends {
+                correlation_direction: direction,
+                correlation_stability: stability,
+                correlation_breakdown_risk: breakdown_risk,
+            },
+            regime_detection: super::RegimeDetection {
+                current_regime,
+                regime_confidence: stability,
+                regime_transition_signals: Vec::new(),
+            },
+            diversification_metrics: super::DiversificationMetrics {
+                diversification_ratio,
+                effective_assets,
+                concentration_risk: (1.0 - effective_assets / n) * 100.0,
+            },
         })
     }
 
+    /// Nothing is cached between analyses, so there is nothing to refresh
+    pub async fn update_correlations(&mut self) -> TradingResult<()> {
+        Ok(())
+    }
+
+    /// Calculate the Pearson correlation matrix for multiple assets
+    pub async fn calculate_correlation_matrix(&self, assets: &[String], time_range: TimeRange) -> TradingResult<CorrelationMatrix> {
+        self.calculate_correlation_matrix_with(assets, time_range, CorrelationMethod::Pearson).await
+    }
+
+    /// Correlation matrix of log returns using Pearson, Spearman or Kendall
+    pub async fn calculate_correlation_matrix_with(
+        &self,
+        assets: &[String],
+        time_range: TimeRange,
+        method: CorrelationMethod
+    ) -> TradingResult<CorrelationMatrix> {
+        let data = self.get_price_data(assets, time_range).await?;
+        self.matrix_from(&data, method, time_range).await
+    }
+
     /// Calculate rolling correlation between two assets
     pub async fn calculate_rolling_correlation(
         &self,
@@ -393,202 +600,317 @@ impl CorrelationAnalysisEngine {
         window_size: usize,
         time_range: TimeRange
     ) -> TradingResult<Vec<RollingCorrelationPoint>> {
-        let price_data = self.get_price_data(&[asset_a.to_string(), asset_b.to_string()], time_range).await?;
-        
-        let data_a = &price_data[asset_a];
-        let data_b = &price_data[asset_b];
-        
-        if let Some(calculator) = self.correlation_calculators.get("pearson") {
-            let rolling_correlations = calculator.calculate_rolling_correlation(data_a, data_b, window_size).await?;
-            
-            let mut result = Vec::new();
-            for (i, correlation) in rolling_correlations.iter().enumerate() {
-                if i >= window_size {
-                    result.push(RollingCorrelationPoint {
-                        timestamp: Utc::now() - chrono::Duration::days((rolling_correlations.len() - i) as i64),
-                        correlation: *correlation,
-                        window_size,
-                        volatility: self.calculate_correlation_volatility(&rolling_correlations, i, window_size).await?,
-                        significance: calculator.test_significance(*correlation, window_size).await?,
-                    });
-                }
-            }
-            
-            Ok(result)
-        } else {
-            Err(TradingError::CalculationError("Pearson calculator not found".to_string()))
+        let data = self.get_price_data(&[asset_a.to_string(), asset_b.to_string()], time_range).await?;
+        let calculator = self.calculator(CorrelationMethod::Pearson)?;
+        let rolling = calculator.calculate_rolling_correlation(&data.returns[0], &data.returns[1], window_size).await?;
+
+        Ok(rolling_points(&data, &rolling, window_size - 1, window_size, window_size))
+    }
+
+    /// Exponentially weighted correlation between two assets; `lambda` is the decay per bar
+    pub async fn calculate_ewma_correlation(
+        &self,
+        asset_a: &str,
+        asset_b: &str,
+        lambda: f64,
+        time_range: TimeRange
+    ) -> TradingResult<Vec<RollingCorrelationPoint>> {
+        if !(0.0..1.0).contains(&lambda) || lambda == 0.0 {
+            return Err(TradingError::validation_error(
+                "lambda".to_string(),
+                "EWMA decay must be between 0 and 1".to_string(),
+                Some(lambda.to_string())
+            ));
         }
+        let data = self.get_price_data(&[asset_a.to_string(), asset_b.to_string()], time_range).await?;
+        let ewma = ewma_correlation(&data.returns[0], &data.returns[1], lambda);
+
+        // Observations carrying the same weight as the EWMA
+        let effective_window = ((1.0 + lambda) / (1.0 - lambda)).round() as usize;
+        Ok(rolling_points(&data, &ewma, MIN_OBSERVATIONS, effective_window, effective_window))
     }
 
-    /// Detect correlation breakpoints
+    /// Detect structural breaks in the correlation of two assets
     pub async fn detect_correlation_breakpoints(
         &self,
         asset_a: &str,
         asset_b: &str,
         time_range: TimeRange
     ) -> TradingResult<Vec<CorrelationBreakpoint>> {
-        let rolling_correlations = self.calculate_rolling_correlation(asset_a, asset_b, 30, time_range).await?;
-        
-        let mut breakpoints = Vec::new();
-        let threshold = 0.3; // Minimum change in correlation to be considered a breakpoint
-        
-        for i in 1..rolling_correlations.len() {
-            let change = (rolling_correlations[i].correlation - rolling_correlations[i-1].correlation).abs();
-            
-            if change > threshold {
-                breakpoints.push(CorrelationBreakpoint {
-                    timestamp: rolling_correlations[i].timestamp,
-                    pre_correlation: rolling_correlations[i-1].correlation,
-                    post_correlation: rolling_correlations[i].correlation,
-                    change_magnitude: change,
-                    statistical_significance: self.test_breakpoint_significance(
-                        rolling_correlations[i-1].correlation,
-                        rolling_correlations[i].correlation,
-                        30
-                    ).await?,
-                    potential_causes: self.identify_potential_causes(rolling_correlations[i].timestamp).await?,
-                });
-            }
+        let data = self.get_price_data(&[asset_a.to_string(), asset_b.to_string()], time_range).await?;
+        let breaks = correlation_breaks(&data.returns[0], &data.returns[1], MIN_BREAK_SEGMENT, BREAK_SIGNIFICANCE);
+
+        Ok(breaks.iter().map(|brk| breakpoint(&data, 0, 1, brk)).collect())
+    }
+
+    /// Cross-correlation of two assets for lags up to `max_lag` bars either way
+    pub async fn analyze_lead_lag(
+        &self,
+        asset_a: &str,
+        asset_b: &str,
+        max_lag: usize,
+        time_range: TimeRange
+    ) -> TradingResult<LeadLagAnalysis> {
+        let data = self.get_price_data(&[asset_a.to_string(), asset_b.to_string()], time_range).await?;
+        if max_lag + MIN_OBSERVATIONS > data.len() {
+            return Err(TradingError::validation_error(
+                "max_lag".to_string(),
+                format!("Lags up to {} bars need more than {} overlapping returns", max_lag, data.len()),
+                Some(max_lag.to_string())
+            ));
         }
-        
-        Ok(breakpoints)
-    }
-
-    /// Initialize correlation calculators
-    async fn initialize_correlation_calculators() -> TradingResult<HashMap<String, Box<dyn CorrelationCalculator + Send + Sync>>> {
-        let mut calculators = HashMap::new();
-        
-        calculators.insert(
-            "pearson".to_string(),
-            Box::new(PearsonCorrelationCalculator::new().await?) as Box<dyn CorrelationCalculator + Send + Sync>
-        );
-        
-        calculators.insert(
-            "spearman".to_string(),
-            Box::new(SpearmanCorrelationCalculator::new().await?) as Box<dyn CorrelationCalculator + Send + Sync>
-        );
-        
-        calculators.insert(
-            "kendall".to_string(),
-            Box::new(KendallCorrelationCalculator::new().await?) as Box<dyn CorrelationCalculator + Send + Sync>
-        );
-
-        Ok(calculators)
-    }
-
-    /// Get price data for assets
-    async fn get_price_data(&self, assets: &[String], _time_range: TimeRange) -> TradingResult<HashMap<String, Vec<f64>>> {
-        let mut price_data = HashMap::new();
-        
-        // Mock price data generation for demonstration
+        Ok(lead_lag(&data, 0, 1, max_lag))
+    }
+
+    fn calculator(&self, method: CorrelationMethod) -> TradingResult<&(dyn CorrelationCalculator + Send + Sync)> {
+        let key = match method {
+            CorrelationMethod::Pearson => "pearson",
+            CorrelationMethod::Spearman => "spearman",
+            CorrelationMethod::Kendall => "kendall",
+            other => return Err(TradingError::validation_error(
+                "method".to_string(),
+                format!("{:?} correlation is not supported", other),
+                None
+            )),
+        };
+        self.correlation_calculators.get(key)
+            .map(|calculator| calculator.as_ref())
+            .ok_or_else(|| TradingError::internal_error(format!("{} calculator not found", key)))
+    }
+
+    fn has_klines(&self, asset: &str) -> bool {
+        self.kline_store.as_ref()
+            .and_then(|store| store.load(asset, &self.interval).ok())
+            .is_some_and(|klines| !klines.is_empty())
+    }
+
+    /// Log returns of the assets from stored klines, aligned on common bars
+    async fn get_price_data(&self, assets: &[String], time_range: TimeRange) -> TradingResult<AlignedReturns> {
+        let store = self.kline_store.as_ref().ok_or_else(|| TradingError::config_error(
+            "kline_store".to_string(),
+            "Kline store is unavailable".to_string()
+        ))?;
+        let bar = interval_minutes(&self.interval)
+            .map(|minutes| Duration::minutes(minutes as i64))
+            .ok_or_else(|| TradingError::config_error("interval".to_string(), format!("Invalid kline interval {}", self.interval)))?;
+
+        let mut series = Vec::with_capacity(assets.len());
         for asset in assets {
-            let mut prices = Vec::new();
-            let mut price = 100.0;
-            
-            for i in 0..100 {
-                price += (rand::random::<f64>() - 0.5) * 2.0; // Random walk
-                if asset.contains("BTC") && i > 50 {
-                    price += 0.5; // Add some correlation for BTC-related assets
-                }
-                prices.push(price);
+            let klines = store.load_range(asset, &self.interval, time_range.start, time_range.end)?;
+            if klines.is_empty() {
+                return Err(TradingError::validation_error(
+                    "assets".to_string(),
+                    format!("No stored {} klines for {} in the requested range. Fetch klines first.", self.interval, asset),
+                    Some(asset.clone())
+                ));
             }
-            
-            price_data.insert(asset.clone(), prices);
+            series.push((asset.clone(), klines.iter().filter_map(|k| k.close.to_f64().map(|close| (k.open_time, close))).collect()));
         }
-        
-        Ok(price_data)
+
+        let data = AlignedReturns::from_closes(&series, bar);
+        if data.len() < MIN_OBSERVATIONS {
+            return Err(TradingError::validation_error(
+                "assets".to_string(),
+                format!("Only {} overlapping returns for {}, at least {} are needed", data.len(), assets.join(", "), MIN_OBSERVATIONS),
+                None
+            ));
+        }
+        Ok(data)
+    }
+
+    async fn matrix_from(&self, data: &AlignedReturns, method: CorrelationMethod, time_range: TimeRange) -> TradingResult<CorrelationMatrix> {
+        let calculator = self.calculator(method)?;
+        let n = data.assets.len();
+        let mut correlation_values = vec![vec![0.0; n]; n];
+        let mut p_values = vec![vec![1.0; n]; n];
+
+        for i in 0..n {
+            correlation_values[i][i] = 1.0;
+            p_values[i][i] = 0.0;
+            for j in (i + 1)..n {
+                let correlation_data = calculator.calculate_correlation(&data.returns[i], &data.returns[j]).await?;
+                correlation_values[i][j] = correlation_data.correlation_coefficient;
+                correlation_values[j][i] = correlation_data.correlation_coefficient;
+                p_values[i][j] = correlation_data.p_value;
+                p_values[j][i] = correlation_data.p_value;
+            }
+        }
+
+        Ok(CorrelationMatrix {
+            assets: data.assets.clone(),
+            correlation_values,
+            p_values,
+            calculation_timestamp: Utc::now(),
+            method_used: method,
+            time_period: time_range,
+        })
     }
 
     /// Identify significant correlations from matrix
-    async fn identify_significant_correlations(&self, matrix: &CorrelationMatrix) -> TradingResult<Vec<CorrelationData>> {
-        let mut significant_correlations = Vec::new();
-        let significance_threshold = 0.05;
+    fn identify_significant_correlations(&self, data: &AlignedReturns, matrix: &CorrelationMatrix) -> Vec<CorrelationData> {
         let correlation_threshold = 0.3;
-        
+        let mut significant_correlations = Vec::new();
+
         for i in 0..matrix.assets.len() {
             for j in (i+1)..matrix.assets.len() {
                 let correlation = matrix.correlation_values[i][j];
                 let p_value = matrix.p_values[i][j];
-                
-                if p_value < significance_threshold && correlation.abs() > correlation_threshold {
+
+                if p_value < SIGNIFICANCE && correlation.abs() > correlation_threshold {
                     significant_correlations.push(CorrelationData {
                         asset_a: matrix.assets[i].clone(),
                         asset_b: matrix.assets[j].clone(),
                         correlation_coefficient: correlation,
                         p_value,
-                        confidence_interval: self.calculate_confidence_interval(correlation, 100).await?,
-                        sample_size: 100, // Mock sample size
-                        calculation_method: CorrelationMethod::Pearson,
+                        confidence_interval: fisher_interval(correlation, data.len()),
+                        sample_size: data.len(),
+                        calculation_method: matrix.method_used,
                         time_period: matrix.time_period,
                         last_updated: Utc::now(),
                     });
                 }
             }
         }
-        
+
         // Sort by absolute correlation strength
         significant_correlations.sort_by(|a, b| {
-            b.correlation_coefficient.abs().partial_cmp(&a.correlation_coefficient.abs())
-                .unwrap_or(std::cmp::Ordering::Equal)
+            b.correlation_coefficient.abs().total_cmp(&a.correlation_coefficient.abs())
         });
-        
-        Ok(significant_correlations)
-    }
-
-    /// Calculate confidence interval for correlation
-    async fn calculate_confidence_interval(&self, correlation: f64, sample_size: usize) -> TradingResult<(f64, f64)> {
-        // Fisher z-transformation for confidence interval calculation
-        let z = 0.5 * ((1.0 + correlation) / (1.0 - correlation)).ln();
-        let se = 1.0 / (sample_size as f64 - 3.0).sqrt();
-        let z_critical = 1.96; // 95% confidence interval
-        
-        let z_lower = z - z_critical * se;
-        let z_upper = z + z_critical * se;
-        
-        // Transform back to correlation scale
-        let r_lower = (z_lower.exp() - 1.0) / (z_lower.exp() + 1.0);
-        let r_upper = (z_upper.exp() - 1.0) / (z_upper.exp() + 1.0);
-        
-        Ok((r_lower, r_upper))
-    }
-
-    /// Calculate correlation volatility
-    async fn calculate_correlation_volatility(&self, correlations: &[f64], index: usize, window: usize) -> TradingResult<f64> {
-        if index < window {
-            return Ok(0.0);
+
+        significant_correlations
+    }
+}
+
+/// Rolling window for reports: up to `ROLLING_WINDOW`, leaving at least two windows of data
+fn report_window(observations: usize) -> usize {
+    (observations / 2).clamp(MIN_OBSERVATIONS, ROLLING_WINDOW)
+}
+
+fn analyze_pairs(data: &AlignedReturns) -> Vec<PairAnalysis> {
+    let n = data.len();
+    let window = report_window(n);
+    let max_lag = MAX_LEAD_LAG.min(n.saturating_sub(MIN_OBSERVATIONS));
+    let mut pairs = Vec::new();
+
+    for i in 0..data.assets.len() {
+        for j in (i + 1)..data.assets.len() {
+            let (a, b) = (&data.returns[i], &data.returns[j]);
+            let correlation = pearson(a, b);
+            let breaks = correlation_breaks(a, b, MIN_BREAK_SEGMENT, BREAK_SIGNIFICANCE);
+            let mut bounds: Vec<usize> = vec![0];
+            bounds.extend(breaks.iter().map(|brk| brk.index));
+            bounds.push(n);
+            let segments = bounds.windows(2)
+                .map(|segment| (segment[0], segment[1], pearson(&a[segment[0]..segment[1]], &b[segment[0]..segment[1]])))
+                .collect();
+
+            pairs.push(PairAnalysis {
+                a: i,
+                b: j,
+                correlation,
+                p_value: correlation_p_value(correlation, n),
+                window,
+                rolling: rolling_correlation(a, b, window, pearson),
+                ewma: ewma_correlation(a, b, EWMA_LAMBDA),
+                breaks,
+                segments,
+                lead_lag: lead_lag(data, i, j, max_lag),
+            });
         }
-        
-        let start = index.saturating_sub(window);
-        let end = index + 1;
-        let slice = &correlations[start..end];
-        
-        let mean = slice.iter().sum::<f64>() / slice.len() as f64;
-        let variance = slice.iter()
-            .map(|x| (x - mean).powi(2))
-            .sum::<f64>() / slice.len() as f64;
-        
-        Ok(variance.sqrt())
     }
 
-    /// Test breakpoint significance
-    async fn test_breakpoint_significance(&self, pre_corr: f64, post_corr: f64, _sample_size: usize) -> TradingResult<f64> {
-        // Simplified significance test - in practice would use Chow test or similar
-        let change_magnitude = (post_corr - pre_corr).abs();
-        Ok(1.0 - change_magnitude) // Mock p-value
+    pairs
+}
+
+/// Rolling points for `values`, the first of which ends on return `first_index`
+fn rolling_points(data: &AlignedReturns, values: &[f64], first_index: usize, window: usize, sample_size: usize) -> Vec<RollingCorrelationPoint> {
+    values.iter()
+        .enumerate()
+        .map(|(k, &correlation)| RollingCorrelationPoint {
+            timestamp: data.timestamps[first_index + k],
+            correlation,
+            window_size: window,
+            // Spread of the preceding window of correlation values
+            volatility: std_dev(&values[k.saturating_sub(window)..=k]),
+            significance: correlation_p_value(correlation, sample_size),
+        })
+        .collect()
+}
+
+fn breakpoint(data: &AlignedReturns, a: usize, b: usize, brk: &CorrelationBreak) -> CorrelationBreakpoint {
+    let before = brk.index.saturating_sub(MIN_BREAK_SEGMENT)..brk.index;
+    let after = brk.index..(brk.index + MIN_BREAK_SEGMENT).min(data.len());
+    let mut potential_causes = Vec::new();
+
+    for asset in [a, b] {
+        let returns = &data.returns[asset];
+        let ratio = std_dev(&returns[after.clone()]) / std_dev(&returns[before.clone()]).max(f64::EPSILON);
+        if ratio > 1.5 {
+            potential_causes.push(format!("{} volatility rose {:.1}x", data.assets[asset], ratio));
+        } else if ratio < 1.0 / 1.5 {
+            potential_causes.push(format!("{} volatility fell {:.1}x", data.assets[asset], 1.0 / ratio));
+        }
+    }
+    if brk.pre.signum() != brk.post.signum() {
+        potential_causes.push("Correlation changed sign".to_string());
+    }
+    if potential_causes.is_empty() {
+        potential_causes.push("Co-movement changed without a volatility shift".to_string());
+    }
+
+    CorrelationBreakpoint {
+        timestamp: data.timestamps[brk.index],
+        pre_correlation: brk.pre,
+        post_correlation: brk.post,
+        change_magnitude: (brk.post - brk.pre).abs(),
+        statistical_significance: brk.p_value,
+        potential_causes,
     }
+}
 
-    /// Identify potential causes of correlation changes
-    async fn identify_potential_causes(&self, _timestamp: DateTime<Utc>) -> TradingResult<Vec<String>> {
-        // In practice, this would analyze news events, economic data releases, etc.
-        Ok(vec![
-            "Market volatility spike".to_string(),
-            "Regulatory announcement".to_string(),
-            "Economic data release".to_string(),
-        ])
+fn lead_lag(data: &AlignedReturns, a: usize, b: usize, max_lag: usize) -> LeadLagAnalysis {
+    let correlations = cross_correlations(&data.returns[a], &data.returns[b], max_lag);
+    let (best_lag, best_correlation, observations) = correlations.iter()
+        .copied()
+        .max_by(|x, y| x.1.abs().total_cmp(&y.1.abs()))
+        .unwrap_or((0, 0.0, 0));
+    let p_value = (correlation_p_value(best_correlation, observations) * correlations.len() as f64).min(1.0);
+    let leader = match best_lag {
+        lag if lag > 0 && p_value < SIGNIFICANCE => Some(data.assets[a].clone()),
+        lag if lag < 0 && p_value < SIGNIFICANCE => Some(data.assets[b].clone()),
+        _ => None,
+    };
+
+    LeadLagAnalysis {
+        asset_a: data.assets[a].clone(),
+        asset_b: data.assets[b].clone(),
+        cross_correlations: correlations.iter().map(|(lag, correlation, _)| (*lag, *correlation)).collect(),
+        best_lag,
+        best_correlation,
+        p_value,
+        leader,
+        bar_minutes: data.bar.num_minutes(),
     }
 }
 
-// Mock implementations for various analyzers and calculators
+/// Trend and volatility conditions over the last `window` returns compared with the whole sample
+fn market_conditions(returns: &[f64], window: usize) -> Vec<MarketCondition> {
+    let recent = &returns[returns.len().saturating_sub(window)..];
+    let volatility_ratio = std_dev(recent) / std_dev(returns).max(f64::EPSILON);
+    let mut conditions = vec![if recent.iter().sum::<f64>() >= 0.0 {
+        MarketCondition::BullMarket
+    } else {
+        MarketCondition::BearMarket
+    }];
+    if volatility_ratio > 1.5 {
+        conditions.push(MarketCondition::HighVolatility);
+    } else if volatility_ratio < 1.0 / 1.5 {
+        conditions.push(MarketCondition::LowVolatility);
+    } else {
+        conditions.push(MarketCondition::NormalConditions);
+    }
+    conditions
+}
 
 pub struct CorrelationPatternDetector;
 pub struct MarketRelationshipAnalyzer;
@@ -597,96 +919,306 @@ pub struct TemporalCorrelationAnalyzer;
 pub struct PredictiveCorrelationEngine;
 
 impl CorrelationPatternDetector {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
-
-    pub async fn detect_patterns(&self, _assets: &[String], _time_range: TimeRange) -> TradingResult<Vec<CorrelationPattern>> {
-        Ok(vec![
-            CorrelationPattern {
-                pattern_type: PatternType::PositiveCorrelation,
-                assets_involved: vec!["BTC".to_string(), "ETH".to_string()],
-                pattern_strength: 0.75,
-                duration: std::time::Duration::from_days(30),
-                frequency: 0.8,
-                confidence_score: 0.85,
-                historical_occurrences: 15,
+    /// Persistent co-movement, lead-lag and correlation regime shifts between pairs
+    fn detect_patterns(&self, data: &AlignedReturns, pairs: &[PairAnalysis]) -> Vec<CorrelationPattern> {
+        let mut patterns = Vec::new();
+
+        for pair in pairs {
+            let assets_involved = vec![data.assets[pair.a].clone(), data.assets[pair.b].clone()];
+
+            if pair.p_value < SIGNIFICANCE && pair.correlation.abs() >= 0.3 {
+                let persistent = pair.rolling.iter()
+                    .filter(|r| r.signum() == pair.correlation.signum() && r.abs() >= 0.3)
+                    .count();
+                patterns.push(CorrelationPattern {
+                    pattern_type: if pair.correlation > 0.0 { PatternType::PositiveCorrelation } else { PatternType::NegativeCorrelation },
+                    assets_involved: assets_involved.clone(),
+                    pattern_strength: pair.correlation.abs(),
+                    duration: data.span(),
+                    frequency: if pair.rolling.is_empty() { 1.0 } else { persistent as f64 / pair.rolling.len() as f64 },
+                    confidence_score: 1.0 - pair.p_value,
+                    historical_occurrences: pair.segments.iter()
+                        .filter(|(_, _, r)| r.signum() == pair.correlation.signum())
+                        .count() as i32,
+                });
+            }
+
+            if pair.lead_lag.leader.is_some() {
+                // How often the same asset leads in each half of the sample
+                let half = data.len() / 2;
+                let max_lag = pair.lead_lag.best_lag.unsigned_abs() as usize;
+                let agreeing = [0..half, half..data.len()].into_iter()
+                    .filter(|range| range.len() >= max_lag + MIN_OBSERVATIONS)
+                    .filter(|range| {
+                        cross_correlations(&data.returns[pair.a][range.clone()], &data.returns[pair.b][range.clone()], max_lag)
+                            .into_iter()
+                            .max_by(|x, y| x.1.abs().total_cmp(&y.1.abs()))
+                            .is_some_and(|(lag, _, _)| lag.signum() == pair.lead_lag.best_lag.signum())
+                    })
+                    .count();
+                patterns.push(CorrelationPattern {
+                    pattern_type: PatternType::LeadLagRelationship,
+                    assets_involved: assets_involved.clone(),
+                    pattern_strength: pair.lead_lag.best_correlation.abs(),
+                    duration: (data.bar * pair.lead_lag.best_lag.abs() as i32).to_std().unwrap_or_default(),
+                    frequency: agreeing as f64 / 2.0,
+                    confidence_score: 1.0 - pair.lead_lag.p_value,
+                    historical_occurrences: agreeing as i32,
+                });
             }
-        ])
+
+            if let Some(last_break) = pair.breaks.last() {
+                patterns.push(CorrelationPattern {
+                    pattern_type: PatternType::RegimeShift,
+                    assets_involved,
+                    pattern_strength: (last_break.post - last_break.pre).abs() / 2.0,
+                    duration: (data.bar * (data.len() - last_break.index) as i32).to_std().unwrap_or_default(),
+                    // Breaks per 100 bars
+                    frequency: pair.breaks.len() as f64 * 100.0 / data.len() as f64,
+                    confidence_score: 1.0 - last_break.p_value,
+                    historical_occurrences: pair.breaks.len() as i32,
+                });
+            }
+        }
+
+        patterns
     }
 }
 
 impl MarketRelationshipAnalyzer {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
-
-    pub async fn analyze_relationships(&self, _assets: &[String], _time_range: TimeRange) -> TradingResult<Vec<MarketRelationship>> {
-        Ok(vec![
-            MarketRelationship {
-                relationship_type: RelationshipType::Causal,
-                primary_asset: "BTC".to_string(),
-                secondary_asset: "ETH".to_string(),
-                relationship_strength: 0.7,
-                causal_direction: CausalDirection::AToB,
-                time_lag: Some(std::time::Duration::from_hours(2)),
-                relationship_stability: 0.8,
-                market_conditions: vec![MarketCondition::BullMarket, MarketCondition::HighVolatility],
-            }
-        ])
+    /// Relationships between significantly related pairs. `Causal` means one asset's returns
+    /// lead the other's (Granger-style), not proven causation
+    fn analyze_relationships(&self, data: &AlignedReturns, pairs: &[PairAnalysis]) -> Vec<MarketRelationship> {
+        pairs.iter()
+            .filter(|pair| pair.p_value < SIGNIFICANCE || pair.lead_lag.leader.is_some())
+            .map(|pair| {
+                let (asset_a, asset_b) = (&data.assets[pair.a], &data.assets[pair.b]);
+                let (relationship_type, causal_direction, strength, time_lag) = match pair.lead_lag.leader.as_deref() {
+                    Some(leader) => (
+                        RelationshipType::Causal,
+                        if leader == asset_a { CausalDirection::AToB } else { CausalDirection::BToA },
+                        pair.lead_lag.best_correlation.abs(),
+                        (data.bar * pair.lead_lag.best_lag.abs() as i32).to_std().ok(),
+                    ),
+                    None => (
+                        if pair.correlation >= 0.0 { RelationshipType::RiskOnRiskOff } else { RelationshipType::Contrarian },
+                        CausalDirection::NoDirectionality,
+                        pair.correlation.abs(),
+                        None,
+                    ),
+                };
+
+                MarketRelationship {
+                    relationship_type,
+                    primary_asset: asset_a.clone(),
+                    secondary_asset: asset_b.clone(),
+                    relationship_strength: strength,
+                    causal_direction,
+                    time_lag,
+                    relationship_stability: (1.0 - std_dev(&pair.rolling)).clamp(0.0, 1.0),
+                    market_conditions: market_conditions(&data.returns[pair.a], pair.window),
+                }
+            })
+            .collect()
     }
 }
 
 impl CrossAssetCorrelationAnalyzer {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
+    /// Correlation and beta of each asset against the equal-weighted basket of all of them
+    fn analyze_cross_assets(&self, data: &AlignedReturns) -> CrossAssetAnalysis {
+        let market: Vec<f64> = (0..data.len())
+            .map(|t| data.returns.iter().map(|r| r[t]).sum::<f64>() / data.assets.len() as f64)
+            .collect();
+        let market_std = std_dev(&market).max(f64::EPSILON);
+        let mut crypto_correlations = HashMap::new();
+        let mut factor_exposures = HashMap::new();
+
+        for (asset, returns) in data.assets.iter().zip(&data.returns) {
+            let correlation = pearson(returns, &market);
+            crypto_correlations.insert(asset.clone(), correlation);
+            // Only exchange klines are stored, so factors needing equity, rate or inflation data stay at zero
+            factor_exposures.insert(asset.clone(), FactorExposure {
+                market_factor: correlation * std_dev(returns) / market_std,
+                size_factor: 0.0,
+                value_factor: 0.0,
+                momentum_factor: 0.0,
+                quality_factor: 0.0,
+                volatility_factor: std_dev(returns) / market_std,
+                interest_rate_sensitivity: 0.0,
+                inflation_sensitivity: 0.0,
+            });
+        }
 
-    pub async fn analyze_cross_assets(&self, _assets: &[String], _time_range: TimeRange) -> TradingResult<CrossAssetAnalysis> {
         let mut asset_class_correlations = HashMap::new();
-        let mut crypto_correlations = HashMap::new();
-        crypto_correlations.insert("BTC".to_string(), 0.75);
-        crypto_correlations.insert("ETH".to_string(), 0.82);
         asset_class_correlations.insert("Cryptocurrency".to_string(), crypto_correlations);
 
-        Ok(CrossAssetAnalysis {
+        CrossAssetAnalysis {
             asset_class_correlations,
             sector_correlations: HashMap::new(),
             geographic_correlations: HashMap::new(),
             currency_correlations: HashMap::new(),
-            factor_exposures: HashMap::new(),
-        })
+            factor_exposures,
+        }
     }
 }
 
 impl TemporalCorrelationAnalyzer {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
+    /// Rolling correlation, breaks, regimes and hour-of-day correlation of the first pair
+    fn analyze_temporal_correlations(&self, data: &AlignedReturns, pairs: &[PairAnalysis]) -> TemporalCorrelationAnalysis {
+        let Some(pair) = pairs.first() else {
+            return TemporalCorrelationAnalysis {
+                rolling_correlations: vec![],
+                correlation_breakpoints: vec![],
+                regime_changes: vec![],
+                seasonal_patterns: vec![],
+                intraday_patterns: vec![],
+            };
+        };
+        let (a, b) = (&data.returns[pair.a], &data.returns[pair.b]);
+
+        let regime_changes = pair.segments.iter()
+            .enumerate()
+            .map(|(k, &(start, end, correlation))| {
+                let rolling: Vec<f64> = pair.rolling.iter()
+                    .enumerate()
+                    .filter(|(w, _)| (start..end).contains(&(w + pair.window - 1)))
+                    .map(|(_, r)| *r)
+                    .collect();
+                let correlation_volatility = std_dev(&rolling);
+                CorrelationRegime {
+                    regime_id: format!("{}-{}-{}", data.assets[pair.a], data.assets[pair.b], k + 1),
+                    start_date: data.timestamps[start],
+                    end_date: (end < data.len()).then(|| data.timestamps[end - 1]),
+                    average_correlation: correlation,
+                    correlation_volatility,
+                    market_characteristics: market_conditions(&a[start..end], end - start),
+                    regime_stability: (1.0 - correlation_volatility).clamp(0.0, 1.0),
+                }
+            })
+            .collect();
+
+        // Hour-of-day correlation only means something for intraday bars
+        let mut intraday_patterns = Vec::new();
+        if data.bar < Duration::days(1) {
+            for hour in 0..24 {
+                let (x, y): (Vec<f64>, Vec<f64>) = data.timestamps.iter()
+                    .enumerate()
+                    .filter(|(_, time)| time.hour() == hour)
+                    .map(|(t, _)| (a[t], b[t]))
+                    .unzip();
+                if x.len() < MIN_OBSERVATIONS {
+                    continue;
+                }
+                intraday_patterns.push(IntradayCorrelationPattern {
+                    hour,
+                    average_correlation: pearson(&x, &y),
+                    // Standard error of the Fisher-transformed correlation
+                    correlation_volatility: 1.0 / (x.len() as f64 - 3.0).sqrt(),
+                    // Volume is not part of the return series
+                    trading_volume_impact: 0.0,
+                    // US equity session opens 13:30 and closes 20:00 UTC
+                    market_open_effect: hour == 13 || hour == 14,
+                    market_close_effect: hour == 20,
+                });
+            }
+        }
 
-    pub async fn analyze_temporal_correlations(&self, _assets: &[String], _time_range: TimeRange) -> TradingResult<TemporalCorrelationAnalysis> {
-        Ok(TemporalCorrelationAnalysis {
-            rolling_correlations: vec![],
-            correlation_breakpoints: vec![],
-            regime_changes: vec![],
+        TemporalCorrelationAnalysis {
+            rolling_correlations: rolling_points(data, &pair.rolling, pair.window - 1, pair.window, pair.window),
+            correlation_breakpoints: pair.breaks.iter().map(|brk| breakpoint(data, pair.a, pair.b, brk)).collect(),
+            regime_changes,
+            // Month-of-year effects need several years of klines
             seasonal_patterns: vec![],
-            intraday_patterns: vec![],
-        })
+            intraday_patterns,
+        }
     }
 }
 
 impl PredictiveCorrelationEngine {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
-
-    pub async fn generate_insights(&self, _assets: &[String], _time_range: TimeRange) -> TradingResult<PredictiveCorrelationInsights> {
-        Ok(PredictiveCorrelationInsights {
+    /// Correlation forecasts that blend the EWMA correlation with the sample correlation it
+    /// reverts to, scored by replaying the same rule over the history
+    fn generate_insights(&self, data: &AlignedReturns, pairs: &[PairAnalysis]) -> PredictiveCorrelationInsights {
+        let horizon = (data.bar * FORECAST_BARS as i32).to_std().unwrap_or_default();
+        let effective_window = ((1.0 + EWMA_LAMBDA) / (1.0 - EWMA_LAMBDA)).round() as usize;
+        let mut insights = PredictiveCorrelationInsights {
             correlation_forecasts: vec![],
             relationship_stability_forecast: vec![],
             regime_change_probability: vec![],
             correlation_extremes_forecast: vec![],
-        })
+        };
+
+        for pair in pairs {
+            let asset_pair = (data.assets[pair.a].clone(), data.assets[pair.b].clone());
+            let (a, b) = (&data.returns[pair.a], &data.returns[pair.b]);
+            let current = pair.ewma.last().copied().unwrap_or(pair.correlation);
+            let predicted = 0.5 * current + 0.5 * pair.correlation;
+
+            // Replay: forecast from each origin, compare with the next FORECAST_BARS
+            let errors: Vec<f64> = (MIN_OBSERVATIONS..a.len().saturating_sub(FORECAST_BARS))
+                .step_by(FORECAST_BARS)
+                .map(|origin| {
+                    let forecast = 0.5 * pair.ewma[origin - MIN_OBSERVATIONS] + 0.5 * pearson(&a[..=origin], &b[..=origin]);
+                    let end = origin + 1 + FORECAST_BARS;
+                    (forecast - pearson(&a[origin + 1..end], &b[origin + 1..end])).abs()
+                })
+                .collect();
+
+            insights.correlation_forecasts.push(CorrelationForecast {
+                asset_pair: asset_pair.clone(),
+                forecast_horizon: horizon,
+                predicted_correlation: predicted,
+                confidence_interval: fisher_interval(predicted, effective_window.max(FORECAST_BARS)),
+                forecast_accuracy: if errors.is_empty() { 0.0 } else { 1.0 - mean(&errors) / 2.0 },
+                key_factors: vec![
+                    format!("EWMA correlation {:.2}", current),
+                    format!("Sample correlation {:.2} over {} bars", pair.correlation, data.len()),
+                    format!("{} structural breaks", pair.breaks.len()),
+                ],
+            });
+
+            let weakened = pair.rolling.iter()
+                .filter(|r| r.signum() != pair.correlation.signum() || r.abs() < pair.correlation.abs() / 2.0)
+                .count();
+            insights.relationship_stability_forecast.push(StabilityForecast {
+                asset_pair: asset_pair.clone(),
+                stability_score: (1.0 - std_dev(&pair.rolling)).clamp(0.0, 1.0),
+                volatility_forecast: std_dev(&pair.rolling),
+                breakdown_probability: if pair.rolling.is_empty() { 0.0 } else { weakened as f64 / pair.rolling.len() as f64 },
+                time_horizon: horizon,
+            });
+
+            if !pair.breaks.is_empty() {
+                // Breaks as a Poisson process over the sample
+                let rate = pair.breaks.len() as f64 / data.len() as f64;
+                let current_regime = pair.segments.last().map(|s| s.2).unwrap_or(pair.correlation);
+                insights.regime_change_probability.push(RegimeChangeProbability {
+                    current_regime: format!("Correlation {:.2}", current_regime),
+                    potential_regime: if current_regime > pair.correlation { "Weaker correlation" } else { "Stronger correlation" }.to_string(),
+                    transition_probability: 1.0 - (-rate * FORECAST_BARS as f64).exp(),
+                    expected_timeframe: (data.bar * (1.0 / rate).round() as i32).to_std().unwrap_or_default(),
+                    trigger_events: vec![],
+                });
+            }
+
+            let extreme = if predicted.abs() > 0.8 {
+                Some((ExtremeType::VeryHighCorrelation, pair.rolling.iter().filter(|r| r.abs() > 0.8).count()))
+            } else if predicted.abs() < 0.1 {
+                Some((ExtremeType::VeryLowCorrelation, pair.rolling.iter().filter(|r| r.abs() < 0.1).count()))
+            } else {
+                None
+            };
+            if let Some((extreme_type, count)) = extreme.filter(|(_, count)| *count > 0) {
+                insights.correlation_extremes_forecast.push(ExtremeForecast {
+                    asset_pair,
+                    extreme_type,
+                    probability: count as f64 / pair.rolling.len() as f64,
+                    magnitude: predicted.abs(),
+                    time_horizon: horizon,
+                });
+            }
+        }
+
+        insights
     }
 }
 
@@ -696,216 +1228,507 @@ pub struct PearsonCorrelationCalculator;
 pub struct SpearmanCorrelationCalculator;
 pub struct KendallCorrelationCalculator;
 
-impl PearsonCorrelationCalculator {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
+fn check_series(data_a: &[f64], data_b: &[f64]) -> TradingResult<()> {
+    if data_a.len() != data_b.len() || data_a.len() < 3 {
+        return Err(TradingError::validation_error(
+            "data".to_string(),
+            "Correlation needs two series of the same length with at least 3 values".to_string(),
+            None
+        ));
     }
+    Ok(())
 }
 
-impl CorrelationCalculator for PearsonCorrelationCalculator {
-    async fn calculate_correlation(&self, data_a: &[f64], data_b: &[f64]) -> TradingResult<CorrelationData> {
-        if data_a.len() != data_b.len() || data_a.is_empty() {
-            return Err(TradingError::CalculationError("Invalid data for correlation calculation".to_string()));
-        }
+fn check_window(data_a: &[f64], data_b: &[f64], window: usize) -> TradingResult<()> {
+    if data_a.len() != data_b.len() || window < 3 || window > data_a.len() {
+        return Err(TradingError::validation_error(
+            "window".to_string(),
+            format!("Rolling window must be between 3 and {} observations", data_a.len()),
+            Some(window.to_string())
+        ));
+    }
+    Ok(())
+}
 
-        let n = data_a.len() as f64;
-        let mean_a = data_a.iter().sum::<f64>() / n;
-        let mean_b = data_b.iter().sum::<f64>() / n;
+/// Correlation result before the engine labels it with assets and a period
+fn correlation_data(correlation: f64, p_value: f64, sample_size: usize, method: CorrelationMethod) -> CorrelationData {
+    let now = Utc::now();
+    CorrelationData {
+        asset_a: String::new(),
+        asset_b: String::new(),
+        correlation_coefficient: correlation,
+        p_value,
+        confidence_interval: fisher_interval(correlation, sample_size),
+        sample_size,
+        calculation_method: method,
+        time_period: TimeRange { start: now, end: now },
+        last_updated: now,
+    }
+}
 
-        let numerator: f64 = data_a.iter().zip(data_b.iter())
-            .map(|(a, b)| (a - mean_a) * (b - mean_b))
-            .sum();
+fn spearman(data_a: &[f64], data_b: &[f64]) -> f64 {
+    pearson(&average_ranks(data_a), &average_ranks(data_b))
+}
 
-        let sum_sq_a: f64 = data_a.iter().map(|a| (a - mean_a).powi(2)).sum();
-        let sum_sq_b: f64 = data_b.iter().map(|b| (b - mean_b).powi(2)).sum();
+#[async_trait]
+impl CorrelationCalculator for PearsonCorrelationCalculator {
+    async fn calculate_correlation(&self, data_a: &[f64], data_b: &[f64]) -> TradingResult<CorrelationData> {
+        check_series(data_a, data_b)?;
+        let correlation = pearson(data_a, data_b);
+        Ok(correlation_data(correlation, correlation_p_value(correlation, data_a.len()), data_a.len(), CorrelationMethod::Pearson))
+    }
 
-        let denominator = (sum_sq_a * sum_sq_b).sqrt();
-        
-        let correlation = if denominator != 0.0 {
-            numerator / denominator
-        } else {
-            0.0
-        };
+    async fn calculate_rolling_correlation(&self, data_a: &[f64], data_b: &[f64], window: usize) -> TradingResult<Vec<f64>> {
+        check_window(data_a, data_b, window)?;
+        Ok(rolling_correlation(data_a, data_b, window, pearson))
+    }
 
-        // Calculate t-statistic for significance testing
-        let t_stat = correlation * ((n - 2.0) / (1.0 - correlation.powi(2))).sqrt();
-        
-        // Simplified p-value calculation (would use proper t-distribution in practice)
-        let p_value = if t_stat.abs() > 2.0 { 0.01 } else { 0.1 };
-
-        Ok(CorrelationData {
-            asset_a: "Asset A".to_string(),
-            asset_b: "Asset B".to_string(),
-            correlation_coefficient: correlation,
-            p_value,
-            confidence_interval: (correlation - 0.1, correlation + 0.1),
-            sample_size: data_a.len(),
-            calculation_method: CorrelationMethod::Pearson,
-            time_period: TimeRange::Days(30),
-            last_updated: Utc::now(),
-        })
+    async fn test_significance(&self, correlation: f64, sample_size: usize) -> TradingResult<f64> {
+        Ok(correlation_p_value(correlation, sample_size))
+    }
+}
+
+#[async_trait]
+impl CorrelationCalculator for SpearmanCorrelationCalculator {
+    async fn calculate_correlation(&self, data_a: &[f64], data_b: &[f64]) -> TradingResult<CorrelationData> {
+        check_series(data_a, data_b)?;
+        let correlation = spearman(data_a, data_b);
+        Ok(correlation_data(correlation, correlation_p_value(correlation, data_a.len()), data_a.len(), CorrelationMethod::Spearman))
     }
 
     async fn calculate_rolling_correlation(&self, data_a: &[f64], data_b: &[f64], window: usize) -> TradingResult<Vec<f64>> {
-        if data_a.len() != data_b.len() || window > data_a.len() {
-            return Err(TradingError::CalculationError("Invalid parameters for rolling correlation".to_string()));
-        }
+        check_window(data_a, data_b, window)?;
+        Ok(rolling_correlation(data_a, data_b, window, spearman))
+    }
 
-        let mut rolling_correlations = Vec::new();
+    /// t approximation, good from about 10 observations
+    async fn test_significance(&self, correlation: f64, sample_size: usize) -> TradingResult<f64> {
+        Ok(correlation_p_value(correlation, sample_size))
+    }
+}
 
-        for i in window..=data_a.len() {
-            let window_a = &data_a[i-window..i];
-            let window_b = &data_b[i-window..i];
-            
-            let correlation_data = self.calculate_correlation(window_a, window_b).await?;
-            rolling_correlations.push(correlation_data.correlation_coefficient);
-        }
+#[async_trait]
+impl CorrelationCalculator for KendallCorrelationCalculator {
+    async fn calculate_correlation(&self, data_a: &[f64], data_b: &[f64]) -> TradingResult<CorrelationData> {
+        check_series(data_a, data_b)?;
+        let tau = kendall_tau_b(data_a, data_b);
+        Ok(correlation_data(tau, kendall_p_value(tau, data_a.len()), data_a.len(), CorrelationMethod::Kendall))
+    }
 
-        Ok(rolling_correlations)
+    async fn calculate_rolling_correlation(&self, data_a: &[f64], data_b: &[f64], window: usize) -> TradingResult<Vec<f64>> {
+        check_window(data_a, data_b, window)?;
+        Ok(rolling_correlation(data_a, data_b, window, kendall_tau_b))
     }
 
+    /// Normal approximation to the null distribution of tau
     async fn test_significance(&self, correlation: f64, sample_size: usize) -> TradingResult<f64> {
-        if sample_size < 3 {
-            return Ok(1.0);
-        }
+        Ok(kendall_p_value(correlation, sample_size))
+    }
+}
 
-        let t_stat = correlation * ((sample_size as f64 - 2.0) / (1.0 - correlation.powi(2))).sqrt();
-        
-        // Simplified p-value calculation
-        let p_value = if t_stat.abs() > 2.576 { 0.01 }  // 99% confidence
-                     else if t_stat.abs() > 1.96 { 0.05 }   // 95% confidence
-                     else { 0.1 };
+// Statistics
 
-        Ok(p_value)
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
     }
 }
 
-impl SpearmanCorrelationCalculator {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
     }
+    let m = mean(values);
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / values.len() as f64).sqrt()
 }
 
-impl CorrelationCalculator for SpearmanCorrelationCalculator {
-    async fn calculate_correlation(&self, data_a: &[f64], data_b: &[f64]) -> TradingResult<CorrelationData> {
-        // Convert to ranks and calculate Pearson correlation on ranks
-        let ranks_a = self.calculate_ranks(data_a).await?;
-        let ranks_b = self.calculate_ranks(data_b).await?;
-        
-        // Use Pearson calculator on ranks
-        let pearson_calc = PearsonCorrelationCalculator::new().await?;
-        let mut result = pearson_calc.calculate_correlation(&ranks_a, &ranks_b).await?;
-        result.calculation_method = CorrelationMethod::Spearman;
-        
-        Ok(result)
+/// Pearson correlation, zero when either series is constant
+fn pearson(data_a: &[f64], data_b: &[f64]) -> f64 {
+    let (mean_a, mean_b) = (mean(data_a), mean(data_b));
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (a, b) in data_a.iter().zip(data_b) {
+        let (da, db) = (a - mean_a, b - mean_b);
+        covariance += da * db;
+        variance_a += da * da;
+        variance_b += db * db;
     }
+    if variance_a <= 0.0 || variance_b <= 0.0 {
+        0.0
+    } else {
+        (covariance / (variance_a * variance_b).sqrt()).clamp(-1.0, 1.0)
+    }
+}
 
-    async fn calculate_rolling_correlation(&self, data_a: &[f64], data_b: &[f64], window: usize) -> TradingResult<Vec<f64>> {
-        let mut rolling_correlations = Vec::new();
-
-        for i in window..=data_a.len() {
-            let window_a = &data_a[i-window..i];
-            let window_b = &data_b[i-window..i];
-            
-            let correlation_data = self.calculate_correlation(window_a, window_b).await?;
-            rolling_correlations.push(correlation_data.correlation_coefficient);
+/// Ranks from 1, with tied values sharing their average rank
+fn average_ranks(data: &[f64]) -> Vec<f64> {
+    let mut order: Vec<usize> = (0..data.len()).collect();
+    order.sort_by(|&i, &j| data[i].total_cmp(&data[j]));
+    let mut ranks = vec![0.0; data.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && data[order[end]] == data[order[start]] {
+            end += 1;
         }
-
-        Ok(rolling_correlations)
+        for &index in &order[start..end] {
+            ranks[index] = (start + end + 1) as f64 / 2.0;
+        }
+        start = end;
     }
+    ranks
+}
 
-    async fn test_significance(&self, correlation: f64, sample_size: usize) -> TradingResult<f64> {
-        // Similar to Pearson but adjusted for rank correlation
-        if sample_size < 3 {
-            return Ok(1.0);
+/// Kendall's tau-b, which corrects for ties
+fn kendall_tau_b(data_a: &[f64], data_b: &[f64]) -> f64 {
+    let (mut concordant, mut discordant, mut tied_a, mut tied_b) = (0i64, 0i64, 0i64, 0i64);
+    for i in 0..data_a.len() {
+        for j in (i + 1)..data_a.len() {
+            let (da, db) = (data_a[j] - data_a[i], data_b[j] - data_b[i]);
+            if da == 0.0 && db == 0.0 {
+                continue;
+            } else if da == 0.0 {
+                tied_a += 1;
+            } else if db == 0.0 {
+                tied_b += 1;
+            } else if (da > 0.0) == (db > 0.0) {
+                concordant += 1;
+            } else {
+                discordant += 1;
+            }
         }
+    }
+    let untied = concordant + discordant;
+    let denominator = (((untied + tied_a) * (untied + tied_b)) as f64).sqrt();
+    if denominator == 0.0 { 0.0 } else { (concordant - discordant) as f64 / denominator }
+}
 
-        let t_stat = correlation * ((sample_size as f64 - 2.0) / (1.0 - correlation.powi(2))).sqrt();
-        let p_value = if t_stat.abs() > 2.0 { 0.01 } else { 0.1 };
+fn rolling_correlation(data_a: &[f64], data_b: &[f64], window: usize, correlation: fn(&[f64], &[f64]) -> f64) -> Vec<f64> {
+    if window == 0 || window > data_a.len() {
+        return Vec::new();
+    }
+    (window..=data_a.len())
+        .map(|end| correlation(&data_a[end - window..end], &data_b[end - window..end]))
+        .collect()
+}
 
-        Ok(p_value)
+/// Zero-mean EWMA correlation after each return, seeded from the first `MIN_OBSERVATIONS`
+fn ewma_correlation(data_a: &[f64], data_b: &[f64], lambda: f64) -> Vec<f64> {
+    if data_a.len() <= MIN_OBSERVATIONS {
+        return Vec::new();
     }
+    let seed = MIN_OBSERVATIONS as f64;
+    let mut variance_a = data_a[..MIN_OBSERVATIONS].iter().map(|a| a * a).sum::<f64>() / seed;
+    let mut variance_b = data_b[..MIN_OBSERVATIONS].iter().map(|b| b * b).sum::<f64>() / seed;
+    let mut covariance = data_a[..MIN_OBSERVATIONS].iter().zip(data_b).map(|(a, b)| a * b).sum::<f64>() / seed;
+
+    data_a.iter().zip(data_b).skip(MIN_OBSERVATIONS)
+        .map(|(a, b)| {
+            variance_a = lambda * variance_a + (1.0 - lambda) * a * a;
+            variance_b = lambda * variance_b + (1.0 - lambda) * b * b;
+            covariance = lambda * covariance + (1.0 - lambda) * a * b;
+            if variance_a > 0.0 && variance_b > 0.0 {
+                (covariance / (variance_a * variance_b).sqrt()).clamp(-1.0, 1.0)
+            } else {
+                0.0
+            }
+        })
+        .collect()
 }
 
-impl SpearmanCorrelationCalculator {
-    async fn calculate_ranks(&self, data: &[f64]) -> TradingResult<Vec<f64>> {
-        let mut indexed_data: Vec<(usize, f64)> = data.iter().enumerate().map(|(i, &x)| (i, x)).collect();
-        indexed_data.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
-        
-        let mut ranks = vec![0.0; data.len()];
-        for (rank, &(original_index, _)) in indexed_data.iter().enumerate() {
-            ranks[original_index] = (rank + 1) as f64;
+/// (lag, correlation, observations) of `data_a` at t against `data_b` at t + lag
+fn cross_correlations(data_a: &[f64], data_b: &[f64], max_lag: usize) -> Vec<(i64, f64, usize)> {
+    let n = data_a.len();
+    (-(max_lag as i64)..=max_lag as i64)
+        .filter_map(|lag| {
+            let shift = lag.unsigned_abs() as usize;
+            if shift + MIN_OBSERVATIONS > n {
+                return None;
+            }
+            let (a, b) = if lag >= 0 {
+                (&data_a[..n - shift], &data_b[shift..])
+            } else {
+                (&data_a[shift..], &data_b[..n - shift])
+            };
+            Some((lag, pearson(a, b), n - shift))
+        })
+        .collect()
+}
+
+/// Binary segmentation on the correlation: keep the split with the largest Fisher-z difference
+/// when it clears `alpha` after a Bonferroni adjustment for the splits tried, then search each side
+fn correlation_breaks(data_a: &[f64], data_b: &[f64], min_segment: usize, alpha: f64) -> Vec<CorrelationBreak> {
+    let fisher_z = |r: f64| r.clamp(-0.999_999, 0.999_999).atanh();
+    let mut breaks = Vec::new();
+    let mut segments = vec![(0, data_a.len())];
+
+    while let Some((start, end)) = segments.pop() {
+        if end - start < 2 * min_segment {
+            continue;
+        }
+        let candidates = (end - start - 2 * min_segment + 1) as f64;
+        let best = (start + min_segment..=end - min_segment)
+            .map(|split| {
+                let pre = pearson(&data_a[start..split], &data_b[start..split]);
+                let post = pearson(&data_a[split..end], &data_b[split..end]);
+                let standard_error = (1.0 / (split - start - 3) as f64 + 1.0 / (end - split - 3) as f64).sqrt();
+                (split, pre, post, (fisher_z(post) - fisher_z(pre)) / standard_error)
+            })
+            .max_by(|x, y| x.3.abs().total_cmp(&y.3.abs()));
+
+        if let Some((split, pre, post, z)) = best {
+            let p_value = (normal_p_value(z) * candidates).min(1.0);
+            if p_value < alpha {
+                breaks.push(CorrelationBreak { index: split, pre, post, p_value });
+                segments.push((start, split));
+                segments.push((split, end));
+            }
         }
-        
-        Ok(ranks)
     }
+
+    breaks.sort_by_key(|brk| brk.index);
+    breaks
 }
 
-impl KendallCorrelationCalculator {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
+/// 95% interval from the Fisher z-transformation
+fn fisher_interval(correlation: f64, sample_size: usize) -> (f64, f64) {
+    if sample_size <= 3 {
+        return (-1.0, 1.0);
     }
+    let z = correlation.clamp(-0.999_999, 0.999_999).atanh();
+    let standard_error = 1.0 / (sample_size as f64 - 3.0).sqrt();
+    ((z - Z_95 * standard_error).tanh(), (z + Z_95 * standard_error).tanh())
 }
 
-impl CorrelationCalculator for KendallCorrelationCalculator {
-    async fn calculate_correlation(&self, data_a: &[f64], data_b: &[f64]) -> TradingResult<CorrelationData> {
-        if data_a.len() != data_b.len() || data_a.is_empty() {
-            return Err(TradingError::CalculationError("Invalid data for Kendall correlation".to_string()));
-        }
+/// Two-sided p-value of a Pearson or Spearman correlation from its t statistic
+fn correlation_p_value(correlation: f64, sample_size: usize) -> f64 {
+    if sample_size < 3 {
+        return 1.0;
+    }
+    if correlation.abs() >= 1.0 {
+        return 0.0;
+    }
+    let df = sample_size as f64 - 2.0;
+    student_t_p_value(correlation * (df / (1.0 - correlation * correlation)).sqrt(), df)
+}
 
-        let n = data_a.len();
-        let mut concordant_pairs = 0;
-        let mut discordant_pairs = 0;
+fn kendall_p_value(tau: f64, sample_size: usize) -> f64 {
+    if sample_size < 3 {
+        return 1.0;
+    }
+    let n = sample_size as f64;
+    normal_p_value(3.0 * tau * (n * (n - 1.0)).sqrt() / (2.0 * (2.0 * n + 5.0)).sqrt())
+}
 
-        for i in 0..n {
-            for j in (i+1)..n {
-                let sign_a = (data_a[j] - data_a[i]).signum();
-                let sign_b = (data_b[j] - data_b[i]).signum();
-                
-                if sign_a * sign_b > 0.0 {
-                    concordant_pairs += 1;
-                } else if sign_a * sign_b < 0.0 {
-                    discordant_pairs += 1;
-                }
-            }
-        }
+fn normal_p_value(z: f64) -> f64 {
+    (2.0 * (1.0 - normal_cdf(z.abs()))).clamp(0.0, 1.0)
+}
 
-        let total_pairs = n * (n - 1) / 2;
-        let tau = (concordant_pairs as f64 - discordant_pairs as f64) / total_pairs as f64;
-
-        Ok(CorrelationData {
-            asset_a: "Asset A".to_string(),
-            asset_b: "Asset B".to_string(),
-            correlation_coefficient: tau,
-            p_value: 0.05, // Simplified
-            confidence_interval: (tau - 0.1, tau + 0.1),
-            sample_size: n,
-            calculation_method: CorrelationMethod::Kendall,
-            time_period: TimeRange::Days(30),
-            last_updated: Utc::now(),
-        })
+/// Two-sided p-value of a Student-t statistic with `df` degrees of freedom
+fn student_t_p_value(t: f64, df: f64) -> f64 {
+    if !t.is_finite() {
+        return 0.0;
+    }
+    regularized_beta(df / 2.0, 0.5, df / (df + t * t))
+}
+
+/// Lanczos approximation of ln Γ(x) for x > 0
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146, -86.50532032941677, 24.01409824083091,
+        -1.231739572450155, 0.1208650973866179e-2, -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut y = x;
+    let mut series = 1.000000000190015;
+    for coefficient in COEFFICIENTS {
+        y += 1.0;
+        series += coefficient / y;
     }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
 
-    async fn calculate_rolling_correlation(&self, data_a: &[f64], data_b: &[f64], window: usize) -> TradingResult<Vec<f64>> {
-        let mut rolling_correlations = Vec::new();
-
-        for i in window..=data_a.len() {
-            let window_a = &data_a[i-window..i];
-            let window_b = &data_b[i-window..i];
-            
-            let correlation_data = self.calculate_correlation(window_a, window_b).await?;
-            rolling_correlations.push(correlation_data.correlation_coefficient);
+/// Regularized incomplete beta function I_x(a, b)
+fn regularized_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front = (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Lentz's method for the incomplete beta continued fraction
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 300;
+    const EPSILON: f64 = 3e-14;
+    const TINY: f64 = 1e-300;
+    let guard = |v: f64| if v.abs() < TINY { TINY } else { v };
+
+    let mut c = 1.0;
+    let mut d = 1.0 / guard(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..=MAX_ITERATIONS {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / guard(1.0 + even * d);
+        c = guard(1.0 + even / c);
+        h *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / guard(1.0 + odd * d);
+        c = guard(1.0 + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
         }
+    }
+    h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+    use crate::models::KlineData;
+
+    fn gaussian(rng: &mut StdRng) -> f64 {
+        let u1: f64 = rng.gen_range(1e-12..1.0);
+        let u2: f64 = rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// Return pairs with correlation `rho`
+    fn correlated(n: usize, rho: f64, rng: &mut StdRng) -> (Vec<f64>, Vec<f64>) {
+        (0..n).map(|_| {
+            let (x, e) = (gaussian(rng), gaussian(rng));
+            (0.01 * x, 0.01 * (rho * x + (1.0 - rho * rho).sqrt() * e))
+        }).unzip()
+    }
+
+    #[test]
+    fn test_correlation_statistics_and_p_values() {
+        // t = 2 with 10 degrees of freedom
+        assert!((student_t_p_value(2.0, 10.0) - 0.0734).abs() < 1e-3);
+        assert!((student_t_p_value(0.0, 10.0) - 1.0).abs() < 1e-9);
+        assert!((normal_p_value(1.959964) - 0.05).abs() < 1e-3);
+
+        assert_eq!(average_ranks(&[3.0, 1.0, 3.0, 2.0]), vec![3.5, 1.0, 3.5, 2.0]);
+        let x: Vec<f64> = (1..=30).map(|i| i as f64).collect();
+        let cubed: Vec<f64> = x.iter().map(|v| v.powi(3)).collect();
+        let reversed: Vec<f64> = x.iter().rev().copied().collect();
+        assert!(pearson(&x, &cubed) < 0.95);
+        assert!((spearman(&x, &cubed) - 1.0).abs() < 1e-12);
+        assert!((kendall_tau_b(&x, &cubed) - 1.0).abs() < 1e-12);
+        assert!((kendall_tau_b(&x, &reversed) + 1.0).abs() < 1e-12);
+        assert!(kendall_p_value(1.0, 30) < 1e-6);
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let (a, b) = correlated(500, 0.6, &mut rng);
+        let r = pearson(&a, &b);
+        let (lower, upper) = fisher_interval(r, a.len());
+        assert!(lower < 0.6 && 0.6 < upper, "{} ({}, {})", r, lower, upper);
+        assert!(correlation_p_value(r, a.len()) < 1e-10);
+        let (c, _) = correlated(500, 0.0, &mut rng);
+        assert!(correlation_p_value(pearson(&a, &c), a.len()) > 0.01);
+    }
 
-        Ok(rolling_correlations)
+    #[test]
+    fn test_ewma_and_break_detection_follow_a_correlation_flip() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let (mut a, mut b) = correlated(300, 0.8, &mut rng);
+        let (a2, b2) = correlated(300, -0.5, &mut rng);
+        a.extend(a2);
+        b.extend(b2);
+
+        let breaks = correlation_breaks(&a, &b, MIN_BREAK_SEGMENT, BREAK_SIGNIFICANCE);
+        assert!(!breaks.is_empty());
+        let main = breaks.iter().max_by(|x, y| (x.post - x.pre).abs().total_cmp(&(y.post - y.pre).abs())).unwrap();
+        assert!((main.index as i64 - 300).abs() <= 20, "break at {}", main.index);
+        assert!(main.pre > 0.6 && main.post < -0.3);
+
+        let ewma = ewma_correlation(&a, &b, EWMA_LAMBDA);
+        assert_eq!(ewma.len(), a.len() - MIN_OBSERVATIONS);
+        assert!(ewma[250] > 0.5 && ewma[ewma.len() - 1] < 0.0);
+        let rolling = rolling_correlation(&a, &b, 48, pearson);
+        assert_eq!(rolling.len(), a.len() - 47);
+
+        // A stable relationship has no breaks
+        let (c, d) = correlated(600, 0.5, &mut rng);
+        assert!(correlation_breaks(&c, &d, MIN_BREAK_SEGMENT, BREAK_SIGNIFICANCE).is_empty());
     }
 
-    async fn test_significance(&self, _correlation: f64, sample_size: usize) -> TradingResult<f64> {
-        // Simplified significance test for Kendall's tau
-        if sample_size < 10 {
-            return Ok(0.1);
+    #[tokio::test]
+    async fn test_engine_reads_returns_from_kline_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = KlineStore::new(dir.path().to_path_buf());
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut rng = StdRng::seed_from_u64(21);
+        let leader: Vec<f64> = (0..400).map(|_| 0.01 * gaussian(&mut rng)).collect();
+        // FOLLOW repeats LEAD's move two bars later
+        let follower: Vec<f64> = (0..400).map(|t| if t >= 2 { 0.8 * leader[t - 2] } else { 0.0 } + 0.004 * gaussian(&mut rng)).collect();
+        let noise: Vec<f64> = (0..400).map(|_| 0.01 * gaussian(&mut rng)).collect();
+        for (symbol, returns) in [("LEADUSDT", &leader), ("FOLLOWUSDT", &follower), ("NOISEUSDT", &noise)] {
+            let mut close = 100.0;
+            let klines: Vec<KlineData> = returns.iter().enumerate().map(|(i, r)| {
+                close *= r.exp();
+                let price = Decimal::from_f64(close).unwrap().round_dp(8);
+                let open_time = start + Duration::hours(i as i64);
+                KlineData {
+                    open_time,
+                    close_time: open_time + Duration::hours(1) - Duration::milliseconds(1),
+                    open: price,
+                    high: price,
+                    low: price,
+                    close: price,
+                    volume: Decimal::ONE,
+                }
+            }).collect();
+            store.merge(symbol, "1h", &klines).unwrap();
         }
-        
-        Ok(0.05)
+        let engine = CorrelationAnalysisEngine::with_store(Some(store), "1h");
+        let range = TimeRange { start, end: start + Duration::hours(400) };
+
+        let lead_lag = engine.analyze_lead_lag("LEADUSDT", "FOLLOWUSDT", 6, range).await.unwrap();
+        assert_eq!(lead_lag.best_lag, 2);
+        assert_eq!(lead_lag.leader.as_deref(), Some("LEADUSDT"));
+        assert_eq!(lead_lag.cross_correlations.len(), 13);
+
+        let assets: Vec<String> = ["LEADUSDT", "FOLLOWUSDT", "NOISEUSDT"].iter().map(|s| s.to_string()).collect();
+        for method in [CorrelationMethod::Pearson, CorrelationMethod::Spearman, CorrelationMethod::Kendall] {
+            let matrix = engine.calculate_correlation_matrix_with(&assets, range, method).await.unwrap();
+            assert_eq!(matrix.method_used, method);
+            // Same-bar returns are unrelated, so nothing is significant at lag zero
+            assert!(matrix.correlation_values[0][1].abs() < 0.2, "{:?}", matrix.correlation_values);
+            assert!(matrix.p_values[0][0] == 0.0 && matrix.p_values[0][2] > 0.001);
+        }
+        assert!(engine.calculate_correlation_matrix_with(&assets, range, CorrelationMethod::MutualInformation).await.is_err());
+
+        let report = engine.analyze_correlations(&assets, range).await.unwrap();
+        let relationship = report.market_relationships.iter()
+            .find(|r| matches!(r.relationship_type, RelationshipType::Causal))
+            .expect("lead-lag relationship");
+        assert!(matches!(relationship.causal_direction, CausalDirection::AToB));
+        assert_eq!(relationship.time_lag, Some(std::time::Duration::from_secs(2 * 3600)));
+        assert!(report.correlation_patterns.iter().any(|p| matches!(p.pattern_type, PatternType::LeadLagRelationship)));
+        assert_eq!(report.temporal_analysis.rolling_correlations.len(), 399 - 48 + 1);
+        assert_eq!(report.predictive_insights.correlation_forecasts.len(), 3);
+
+        let ewma = engine.calculate_ewma_correlation("LEADUSDT", "NOISEUSDT", 0.94, range).await.unwrap();
+        assert_eq!(ewma.len(), 399 - MIN_OBSERVATIONS);
+        assert!(engine.calculate_ewma_correlation("LEADUSDT", "NOISEUSDT", 1.5, range).await.is_err());
+        assert!(engine.detect_correlation_breakpoints("LEADUSDT", "MISSINGUSDT", range).await.is_err());
     }
-}
\ No newline at end of file
+}
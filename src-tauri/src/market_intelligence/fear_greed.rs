@@ -0,0 +1,525 @@
+// Fear & Greed Index
+// Market-wide sentiment on a 0-100 scale built from locally stored daily klines:
+// volatility and momentum of the benchmark, volume pressure, benchmark dominance,
+// plus social and news sentiment when those sources are available
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{TradingError, TradingResult};
+use crate::kline_store::KlineStore;
+use crate::models::{KlineData, TradeSide};
+use crate::persistence;
+
+/// Symbol whose behaviour stands in for the whole market
+pub const BENCHMARK_SYMBOL: &str = "BTCUSDT";
+/// Daily klines drive the index
+pub const INDEX_INTERVAL: &str = "1d";
+
+/// Daily returns in each realized volatility sample
+const VOLATILITY_WINDOW: usize = 14;
+/// Days of recent activity compared against the longer averages
+const RECENT_DAYS: usize = 7;
+const SHORT_AVERAGE_DAYS: usize = 30;
+const LONG_AVERAGE_DAYS: usize = 90;
+/// Steepness of the logistic maps from ratios to scores
+const VOLATILITY_SENSITIVITY: f64 = 3.0;
+const MOMENTUM_SENSITIVITY: f64 = 15.0;
+const VOLUME_DIRECTION_SENSITIVITY: f64 = 20.0;
+const DOMINANCE_SENSITIVITY: f64 = 10.0;
+/// Daily readings kept on disk
+const MAX_HISTORY_DAYS: usize = 3650;
+
+/// Relative weight of each component; missing components are left out and the rest renormalized
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FearGreedWeights {
+    pub volatility: f64,
+    pub momentum: f64,
+    pub volume: f64,
+    pub social: f64,
+    pub dominance: f64,
+    pub news: f64,
+}
+
+impl Default for FearGreedWeights {
+    fn default() -> Self {
+        Self {
+            volatility: 0.25,
+            momentum: 0.25,
+            volume: 0.15,
+            social: 0.15,
+            dominance: 0.1,
+            news: 0.1,
+        }
+    }
+}
+
+/// Component scores on the index scale (0 = extreme fear, 100 = extreme greed)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FearGreedComponents {
+    /// Realized volatility against its 30- and 90-day averages; calm markets score high
+    pub volatility: Option<f64>,
+    /// Close against its 30- and 90-day moving averages
+    pub momentum: Option<f64>,
+    /// Recent volume against its averages, signed by the direction of the move
+    pub volume: Option<f64>,
+    /// Benchmark share of quote volume across the universe; flight to the benchmark scores low
+    pub dominance: Option<f64>,
+    pub social: Option<f64>,
+    pub news: Option<f64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FearGreedClass {
+    ExtremeFear,
+    Fear,
+    Neutral,
+    Greed,
+    ExtremeGreed,
+}
+
+impl FearGreedClass {
+    pub fn from_index(index: f64) -> Self {
+        match index {
+            i if i < 25.0 => FearGreedClass::ExtremeFear,
+            i if i < 45.0 => FearGreedClass::Fear,
+            i if i <= 55.0 => FearGreedClass::Neutral,
+            i if i < 75.0 => FearGreedClass::Greed,
+            _ => FearGreedClass::ExtremeGreed,
+        }
+    }
+}
+
+/// Index value for one day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FearGreedReading {
+    /// Close time of the last benchmark kline used
+    pub timestamp: DateTime<Utc>,
+    pub index: f64,
+    pub classification: FearGreedClass,
+    pub components: FearGreedComponents,
+}
+
+/// Data the index is computed from
+#[derive(Debug, Clone, Default)]
+pub struct FearGreedInputs {
+    /// Daily benchmark klines, oldest first
+    pub benchmark: Vec<KlineData>,
+    /// Daily klines of the other symbols in the universe, used for dominance
+    pub universe: Vec<Vec<KlineData>>,
+    /// Social sentiment in [-1, 1], when a social source is present
+    pub social: Option<f64>,
+    /// News sentiment in [-1, 1], when news has been ingested
+    pub news: Option<f64>,
+}
+
+impl FearGreedInputs {
+    /// Benchmark and every other stored USDT pair at the daily interval
+    pub fn from_store(store: &KlineStore) -> TradingResult<Self> {
+        let benchmark = store.load(BENCHMARK_SYMBOL, INDEX_INTERVAL)?;
+        let mut universe = Vec::new();
+        for (symbol, interval) in store.list()? {
+            if interval == INDEX_INTERVAL && symbol != BENCHMARK_SYMBOL && symbol.ends_with("USDT") {
+                universe.push(store.load(&symbol, &interval)?);
+            }
+        }
+        Ok(Self { benchmark, universe, social: None, news: None })
+    }
+
+    /// The market data as it stood at `at`; sentiment inputs are dropped since they are not historical
+    pub fn as_of(&self, at: DateTime<Utc>) -> Self {
+        let until = |klines: &[KlineData]| klines.iter().filter(|k| k.close_time <= at).cloned().collect::<Vec<_>>();
+        Self {
+            benchmark: until(&self.benchmark),
+            universe: self.universe.iter().map(|klines| until(klines)).collect(),
+            social: None,
+            news: None,
+        }
+    }
+}
+
+/// Combines the component scores into the index
+#[derive(Debug, Clone, Default)]
+pub struct FearGreedCalculator {
+    pub weights: FearGreedWeights,
+}
+
+impl FearGreedCalculator {
+    pub fn new(weights: FearGreedWeights) -> Self {
+        Self { weights }
+    }
+
+    pub fn components(&self, inputs: &FearGreedInputs) -> FearGreedComponents {
+        let closes: Vec<f64> = inputs.benchmark.iter().map(|k| k.close.to_f64().unwrap_or(0.0)).collect();
+        let volumes: Vec<f64> = inputs.benchmark.iter().map(|k| k.volume.to_f64().unwrap_or(0.0)).collect();
+        FearGreedComponents {
+            volatility: volatility_score(&closes),
+            momentum: momentum_score(&closes),
+            volume: volume_score(&closes, &volumes),
+            dominance: dominance_score(&inputs.benchmark, &inputs.universe),
+            social: inputs.social.map(sentiment_score),
+            news: inputs.news.map(sentiment_score),
+        }
+    }
+
+    pub fn calculate(&self, inputs: &FearGreedInputs) -> TradingResult<FearGreedReading> {
+        let components = self.components(inputs);
+        let weighted = [
+            (components.volatility, self.weights.volatility),
+            (components.momentum, self.weights.momentum),
+            (components.volume, self.weights.volume),
+            (components.dominance, self.weights.dominance),
+            (components.social, self.weights.social),
+            (components.news, self.weights.news),
+        ];
+
+        // Sentiment alone is not enough to call the market
+        if components.volatility.is_none() && components.momentum.is_none() {
+            return Err(TradingError::validation_error(
+                "benchmark".to_string(),
+                format!("Need at least {} daily {} klines to compute the index", SHORT_AVERAGE_DAYS + 1, BENCHMARK_SYMBOL),
+                Some(inputs.benchmark.len().to_string())
+            ));
+        }
+
+        let (sum, total_weight) = weighted.iter()
+            .filter_map(|(score, weight)| score.map(|s| (s * weight, *weight)))
+            .fold((0.0, 0.0), |(sum, total), (s, w)| (sum + s, total + w));
+        let index = if total_weight > 0.0 { (sum / total_weight).clamp(0.0, 100.0) } else { 50.0 };
+
+        Ok(FearGreedReading {
+            timestamp: inputs.benchmark.last().map(|k| k.close_time).unwrap_or_else(Utc::now),
+            index,
+            classification: FearGreedClass::from_index(index),
+            components,
+        })
+    }
+}
+
+/// Entry filter that avoids buying into euphoria and selling into panic
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FearGreedFilter {
+    pub enabled: bool,
+    /// Longs are blocked above this index
+    pub max_index_for_longs: f64,
+    /// Shorts are blocked below this index
+    pub min_index_for_shorts: f64,
+}
+
+impl Default for FearGreedFilter {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_index_for_longs: 80.0,
+            min_index_for_shorts: 20.0,
+        }
+    }
+}
+
+impl FearGreedFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        let in_range = |v: f64| (0.0..=100.0).contains(&v);
+        if !in_range(self.max_index_for_longs) || !in_range(self.min_index_for_shorts) {
+            return Err("Fear & Greed filter thresholds must be between 0 and 100".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether an entry on `side` is allowed at `index`; always true when disabled
+    pub fn allows(&self, side: &TradeSide, index: f64) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        match side {
+            TradeSide::Long | TradeSide::Buy => index <= self.max_index_for_longs,
+            TradeSide::Short | TradeSide::Sell => index >= self.min_index_for_shorts,
+        }
+    }
+
+    /// Whether an entry on `side` at `at` is allowed by the index then in force; entries
+    /// before the first recorded reading are allowed
+    pub fn allows_at(&self, side: &TradeSide, history: &FearGreedHistory, at: DateTime<Utc>) -> bool {
+        history.index_at(at).is_none_or(|index| self.allows(side, index))
+    }
+}
+
+/// Daily readings persisted for charting and backtests
+#[derive(Debug, Clone, Default)]
+pub struct FearGreedHistory {
+    readings: Vec<FearGreedReading>,
+    path: Option<PathBuf>,
+}
+
+impl FearGreedHistory {
+    /// In-memory history that is never written to disk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("fear_greed_history.json")
+    }
+
+    pub fn open(path: PathBuf) -> TradingResult<Self> {
+        let readings = persistence::read_json(&path)?.unwrap_or_default();
+        Ok(Self { readings, path: Some(path) })
+    }
+
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        match self.path.as_ref() {
+            Some(path) => persistence::write_json(path, &self.readings),
+            None => Ok(()),
+        }
+    }
+
+    /// Add a reading, replacing any earlier one for the same UTC day
+    pub fn record(&mut self, reading: FearGreedReading) {
+        let day = reading.timestamp.date_naive();
+        self.readings.retain(|r| r.timestamp.date_naive() != day);
+        let position = self.readings.partition_point(|r| r.timestamp < reading.timestamp);
+        self.readings.insert(position, reading);
+        if self.readings.len() > MAX_HISTORY_DAYS {
+            let excess = self.readings.len() - MAX_HISTORY_DAYS;
+            self.readings.drain(..excess);
+        }
+    }
+
+    /// Readings at or after `since`, oldest first
+    pub fn readings(&self, since: Option<DateTime<Utc>>) -> Vec<FearGreedReading> {
+        self.readings.iter()
+            .filter(|r| since.is_none_or(|since| r.timestamp >= since))
+            .cloned()
+            .collect()
+    }
+
+    pub fn latest(&self) -> Option<&FearGreedReading> {
+        self.readings.last()
+    }
+
+    /// Index in force at `at`: the latest reading not after it
+    pub fn index_at(&self, at: DateTime<Utc>) -> Option<f64> {
+        let position = self.readings.partition_point(|r| r.timestamp <= at);
+        position.checked_sub(1).map(|i| self.readings[i].index)
+    }
+
+    /// Recompute a reading for every benchmark day using only the data available on that day.
+    /// Returns the number of readings recorded
+    pub fn backfill(&mut self, calculator: &FearGreedCalculator, inputs: &FearGreedInputs) -> usize {
+        let mut recorded = 0;
+        for kline in &inputs.benchmark {
+            if let Ok(reading) = calculator.calculate(&inputs.as_of(kline.close_time)) {
+                self.record(reading);
+                recorded += 1;
+            }
+        }
+        recorded
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+fn tail(values: &[f64], n: usize) -> &[f64] {
+    &values[values.len().saturating_sub(n)..]
+}
+
+/// Logistic map onto 0-100, 50 at `x == 0`
+fn logistic_score(x: f64, sensitivity: f64) -> f64 {
+    100.0 / (1.0 + (-sensitivity * x).exp())
+}
+
+fn sentiment_score(sentiment: f64) -> f64 {
+    (sentiment.clamp(-1.0, 1.0) + 1.0) * 50.0
+}
+
+fn log_returns(closes: &[f64]) -> Vec<f64> {
+    closes.windows(2)
+        .filter(|w| w[0] > 0.0 && w[1] > 0.0)
+        .map(|w| (w[1] / w[0]).ln())
+        .collect()
+}
+
+fn volatility_score(closes: &[f64]) -> Option<f64> {
+    let returns = log_returns(closes);
+    let rolling: Vec<f64> = returns.windows(VOLATILITY_WINDOW).map(std_dev).collect();
+    if rolling.len() < SHORT_AVERAGE_DAYS {
+        return None;
+    }
+    let current = *rolling.last()?;
+    let scores: Vec<f64> = [SHORT_AVERAGE_DAYS, LONG_AVERAGE_DAYS].iter()
+        .map(|&days| mean(tail(&rolling, days)))
+        .filter(|&average| average > 0.0)
+        .map(|average| logistic_score(1.0 - current / average, VOLATILITY_SENSITIVITY))
+        .collect();
+    (!scores.is_empty()).then(|| mean(&scores))
+}
+
+fn momentum_score(closes: &[f64]) -> Option<f64> {
+    if closes.len() < SHORT_AVERAGE_DAYS + 1 {
+        return None;
+    }
+    let last = *closes.last()?;
+    let deviations: Vec<f64> = [SHORT_AVERAGE_DAYS, LONG_AVERAGE_DAYS].iter()
+        .map(|&days| mean(tail(closes, days)))
+        .filter(|&average| average > 0.0)
+        .map(|average| last / average - 1.0)
+        .collect();
+    (!deviations.is_empty()).then(|| logistic_score(mean(&deviations), MOMENTUM_SENSITIVITY))
+}
+
+fn volume_score(closes: &[f64], volumes: &[f64]) -> Option<f64> {
+    if volumes.len() < SHORT_AVERAGE_DAYS + RECENT_DAYS || closes.len() <= RECENT_DAYS {
+        return None;
+    }
+    let recent = mean(tail(volumes, RECENT_DAYS));
+    let baseline = (mean(tail(volumes, SHORT_AVERAGE_DAYS)) + mean(tail(volumes, LONG_AVERAGE_DAYS))) / 2.0;
+    let start = closes[closes.len() - 1 - RECENT_DAYS];
+    if recent <= 0.0 || baseline <= 0.0 || start <= 0.0 {
+        return None;
+    }
+    // Heavy volume into a rally is greed, heavy volume into a sell-off is fear
+    let direction = ((closes[closes.len() - 1] / start - 1.0) * VOLUME_DIRECTION_SENSITIVITY).tanh();
+    Some(50.0 + 50.0 * (recent / baseline).ln().tanh() * direction)
+}
+
+fn dominance_score(benchmark: &[KlineData], universe: &[Vec<KlineData>]) -> Option<f64> {
+    if universe.is_empty() {
+        return None;
+    }
+    let quote_volume = |k: &KlineData| (k.close * k.volume).to_f64().unwrap_or(0.0);
+    let mut others: HashMap<DateTime<Utc>, f64> = HashMap::new();
+    for kline in universe.iter().flatten() {
+        *others.entry(kline.open_time).or_default() += quote_volume(kline);
+    }
+
+    let shares: Vec<f64> = benchmark.iter()
+        .filter_map(|k| {
+            let own = quote_volume(k);
+            let total = own + others.get(&k.open_time).copied().unwrap_or(0.0);
+            (total > 0.0).then(|| own / total)
+        })
+        .collect();
+    if shares.len() < SHORT_AVERAGE_DAYS {
+        return None;
+    }
+    let change = mean(tail(&shares, RECENT_DAYS)) - mean(tail(&shares, LONG_AVERAGE_DAYS));
+    Some(logistic_score(-change, DOMINANCE_SENSITIVITY))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn daily(closes: &[f64], volumes: &[f64]) -> Vec<KlineData> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        closes.iter().zip(volumes).enumerate().map(|(i, (&close, &volume))| {
+            let open_time = start + Duration::days(i as i64);
+            let close = Decimal::from_f64(close).unwrap();
+            KlineData {
+                open_time,
+                close_time: open_time + Duration::days(1) - Duration::milliseconds(1),
+                open: close,
+                high: close,
+                low: close,
+                close,
+                volume: Decimal::from_f64(volume).unwrap(),
+            }
+        }).collect()
+    }
+
+    fn calm_market(days: usize, drift: f64) -> Vec<f64> {
+        (0..days).map(|d| 100.0 * (1.0 + drift).powi(d as i32) * (1.0 + 0.01 * (d as f64 * 1.3).sin())).collect()
+    }
+
+    #[test]
+    fn test_crash_reads_as_fear_and_rally_as_greed() {
+        let calculator = FearGreedCalculator::default();
+
+        let mut crash = calm_market(120, 0.0);
+        let last = *crash.last().unwrap();
+        crash.extend((1..=10).map(|d| last * (1.0 - 0.05 * d as f64) * if d % 2 == 0 { 1.04 } else { 0.97 }));
+        let mut volumes = vec![1000.0; 120];
+        volumes.extend(vec![4000.0; 10]);
+        let fear = calculator.calculate(&FearGreedInputs { benchmark: daily(&crash, &volumes), ..Default::default() }).unwrap();
+        assert!(fear.index < 25.0, "crash index {}", fear.index);
+        assert_eq!(fear.classification, FearGreedClass::ExtremeFear);
+
+        let rally = calm_market(130, 0.006);
+        let mut volumes = vec![1000.0; 123];
+        volumes.extend(vec![2500.0; 7]);
+        let greed = calculator.calculate(&FearGreedInputs { benchmark: daily(&rally, &volumes), ..Default::default() }).unwrap();
+        assert!(greed.index > 55.0, "rally index {}", greed.index);
+        assert!(greed.components.dominance.is_none() && greed.components.social.is_none());
+
+        let too_short = daily(&rally[..20], &volumes[..20]);
+        assert!(calculator.calculate(&FearGreedInputs { benchmark: too_short, ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_optional_components_and_dominance() {
+        let calculator = FearGreedCalculator::default();
+        let closes = calm_market(100, 0.0);
+        let benchmark = daily(&closes, &vec![1000.0; 100]);
+        let neutral = calculator.calculate(&FearGreedInputs { benchmark: benchmark.clone(), ..Default::default() }).unwrap();
+
+        // Money rotating out of alts into the benchmark is risk-off
+        let mut alt_volumes = vec![1000.0; 93];
+        alt_volumes.extend(vec![100.0; 7]);
+        let alt = daily(&closes, &alt_volumes);
+        let inputs = FearGreedInputs { benchmark, universe: vec![alt], social: Some(-0.8), news: Some(-0.6) };
+        let risk_off = calculator.calculate(&inputs).unwrap();
+        assert!(risk_off.components.dominance.unwrap() < 30.0);
+        assert!((risk_off.components.social.unwrap() - 10.0).abs() < 1e-9);
+        assert!(risk_off.index < neutral.index);
+    }
+
+    #[test]
+    fn test_history_backfill_and_filter() {
+        let closes = calm_market(60, 0.002);
+        let inputs = FearGreedInputs { benchmark: daily(&closes, &vec![1000.0; 60]), ..Default::default() };
+        let mut history = FearGreedHistory::new();
+        let recorded = history.backfill(&FearGreedCalculator::default(), &inputs);
+        assert_eq!(recorded, 60 - SHORT_AVERAGE_DAYS);
+        assert_eq!(history.readings(None).len(), recorded);
+
+        // Re-recording a day replaces the earlier reading
+        let mut latest = history.latest().unwrap().clone();
+        latest.index = 90.0;
+        history.record(latest.clone());
+        assert_eq!(history.readings(None).len(), recorded);
+        assert_eq!(history.index_at(latest.timestamp), Some(90.0));
+        assert_eq!(history.index_at(inputs.benchmark[0].close_time), None);
+
+        let filter = FearGreedFilter { enabled: true, ..Default::default() };
+        assert!(!filter.allows(&TradeSide::Long, 90.0));
+        assert!(filter.allows(&TradeSide::Short, 90.0));
+        assert!(!filter.allows(&TradeSide::Sell, 10.0));
+        assert!(FearGreedFilter::default().allows(&TradeSide::Long, 99.0));
+
+        assert!(!filter.allows_at(&TradeSide::Long, &history, latest.timestamp));
+        assert!(filter.allows_at(&TradeSide::Long, &history, inputs.benchmark[0].close_time));
+    }
+}
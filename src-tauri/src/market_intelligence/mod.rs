@@ -5,6 +5,9 @@ pub mod sentiment_analysis;
 pub mod predictive_analytics;
 pub mod news_intelligence;
 pub mod correlation_engine;
+pub mod fear_greed;
+pub mod news_ingestion;
+pub mod nlp;
 
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -12,7 +15,8 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
 use crate::errors::TradingResult;
-use crate::types::*;
+use correlation_engine::CorrelationAnalysisReport;
+use news_intelligence::NewsIntelligenceReport;
 
 /// Market Intelligence Engine - Central coordinator for intelligent market analysis
 pub struct MarketIntelligenceEngine {
@@ -22,6 +26,78 @@ pub struct MarketIntelligenceEngine {
     pub correlation_engine: Arc<RwLock<correlation_engine::CorrelationAnalysisEngine>>,
 }
 
+/// Window of time an analysis covers
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TimeRange {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl TimeRange {
+    /// The last `hours` up to now
+    pub fn last_hours(hours: i64) -> Self {
+        let end = Utc::now();
+        Self { start: end - chrono::Duration::hours(hours), end }
+    }
+}
+
+/// Market state handed to the predictive engine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketContext {
+    pub assets: Vec<String>,
+    pub time_range: TimeRange,
+    pub sentiment_score: f64,
+    pub market_trend: MarketTrend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum MarketTrend {
+    Bullish,
+    Neutral,
+    Bearish,
+}
+
+/// Market-wide sentiment over a time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SentimentAnalysisReport {
+    pub time_range: TimeRange,
+    pub overall_sentiment: OverallSentiment,
+    pub fear_greed_index: f64,
+    pub articles_analyzed: usize,
+    pub confidence_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverallSentiment {
+    /// -1 (bearish) to 1 (bullish)
+    pub score: f64,
+    pub sentiment_label: String,
+}
+
+/// Sentiment, news, correlation and predictions for a set of assets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComprehensiveMarketIntelligenceReport {
+    pub analysis_timestamp: DateTime<Utc>,
+    pub time_range: TimeRange,
+    pub assets: Vec<String>,
+    pub sentiment_analysis: SentimentAnalysisReport,
+    pub news_intelligence: NewsIntelligenceReport,
+    pub correlation_analysis: CorrelationAnalysisReport,
+    pub predictive_insights: PredictiveInsights,
+    pub market_summary: MarketSummary,
+    pub confidence_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketSummary {
+    pub overall_sentiment: String,
+    pub key_trends: Vec<String>,
+    pub dominant_correlations: Vec<String>,
+    pub market_outlook: String,
+    pub risk_factors: Vec<String>,
+    pub opportunities: Vec<String>,
+}
+
 /// Comprehensive market intelligence result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketIntelligenceResult {
@@ -170,6 +246,17 @@ pub struct VolatilityForecast {
     pub volatility_trend: TrendDirection,
     pub volatility_regime: VolatilityRegime,
     pub garch_forecast: GarchForecast,
+    /// Annualized volatility expected over each forecast horizon
+    pub horizon_forecasts: Vec<HorizonVolatility>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HorizonVolatility {
+    pub timeframe: String,
+    pub volatility: f64,
+    pub lower_bound: f64,
+    pub upper_bound: f64,
+    pub confidence_level: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -430,17 +517,16 @@ pub struct DiversificationMetrics {
 impl MarketIntelligenceEngine {
     /// Initialize the market intelligence engine
     pub async fn new() -> TradingResult<Self> {
-        let sentiment_analyzer = Arc::new(RwLock::new(
-            sentiment_analysis::SentimentAnalysisEngine::new().await?
-        ));
+        let news_engine = news_intelligence::NewsIntelligenceEngine::new().await?;
+        let mut sentiment_engine = sentiment_analysis::SentimentAnalysisEngine::new().await?;
+        sentiment_engine.set_news_store(news_engine.news_store());
+        let sentiment_analyzer = Arc::new(RwLock::new(sentiment_engine));
         
         let predictive_analytics = Arc::new(RwLock::new(
             predictive_analytics::PredictiveAnalyticsEngine::new().await?
         ));
         
-        let news_intelligence = Arc::new(RwLock::new(
-            news_intelligence::NewsIntelligenceEngine::new().await?
-        ));
+        let news_intelligence = Arc::new(RwLock::new(news_engine));
         
         let correlation_engine = Arc::new(RwLock::new(
             correlation_engine::CorrelationAnalysisEngine::new().await?
@@ -467,7 +553,7 @@ impl MarketIntelligenceEngine {
             .analyze_news_impact(symbol).await?;
         
         let correlation_analysis = self.correlation_engine.read().await
-            .analyze_correlations(symbol).await?;
+            .analyze_symbol_correlations(symbol).await?;
 
         // Generate market signals based on combined intelligence
         let market_signals = self.generate_market_signals(
@@ -526,7 +612,7 @@ impl MarketIntelligenceEngine {
     /// Get correlation analysis
     pub async fn get_correlation_analysis(&self, symbol: &str) -> TradingResult<CorrelationAnalysis> {
         self.correlation_engine.read().await
-            .analyze_correlations(symbol).await
+            .analyze_symbol_correlations(symbol).await
     }
 
     /// Generate actionable market signals
@@ -535,7 +621,7 @@ impl MarketIntelligenceEngine {
         sentiment: &SentimentAnalysis,
         predictions: &PredictiveInsights,
         news: &NewsIntelligence,
-        correlations: &CorrelationAnalysis,
+        _correlations: &CorrelationAnalysis,
     ) -> TradingResult<Vec<MarketSignal>> {
         let mut signals = Vec::new();
 
@@ -581,15 +667,19 @@ impl MarketIntelligenceEngine {
 
         // Prediction-based signals
         for prediction in &predictions.price_predictions {
+            let Some(trend) = predictions.trend_predictions.iter().find(|t| t.timeframe == prediction.timeframe) else {
+                continue;
+            };
+            let signal_type = match trend.trend_direction {
+                TrendDirection::StronglyIncreasing | TrendDirection::Increasing => MarketSignalType::Buy,
+                TrendDirection::StronglyDecreasing | TrendDirection::Decreasing => MarketSignalType::Sell,
+                TrendDirection::Stable => continue,
+            };
             if prediction.confidence_interval.confidence_level > 0.8 {
                 signals.push(MarketSignal {
-                    signal_type: if prediction.predicted_price > Decimal::from(50000) { // Example threshold
-                        MarketSignalType::Buy
-                    } else {
-                        MarketSignalType::Sell
-                    },
-                    signal_strength: SignalStrength::Moderate,
-                    confidence: prediction.confidence_interval.confidence_level,
+                    signal_type,
+                    signal_strength: if trend.trend_strength > 0.5 { SignalStrength::Strong } else { SignalStrength::Moderate },
+                    confidence: trend.trend_strength,
                     timeframe: prediction.timeframe.clone(),
                     reasoning: format!("Price prediction model: {}", prediction.model_used),
                     supporting_evidence: vec![
@@ -679,11 +769,16 @@ impl MarketIntelligenceEngine {
             assets: assets.to_vec(),
             time_range,
             sentiment_score: sentiment_analysis.overall_sentiment.score,
-            volatility_level: 0.3, // Mock value
-            market_trend: MarketTrend::Bullish,
+            market_trend: match sentiment_analysis.overall_sentiment.score {
+                s if s > 0.15 => MarketTrend::Bullish,
+                s if s < -0.15 => MarketTrend::Bearish,
+                _ => MarketTrend::Neutral,
+            },
         };
         
         let predictive_insights = self.generate_predictive_insights(&market_context).await?;
+        let market_summary = self.generate_market_summary(&sentiment_analysis, &news_intelligence, &correlation_analysis).await?;
+        let confidence_score = self.calculate_overall_confidence(&sentiment_analysis, &news_intelligence, &correlation_analysis).await?;
         
         Ok(ComprehensiveMarketIntelligenceReport {
             analysis_timestamp: chrono::Utc::now(),
@@ -693,8 +788,8 @@ impl MarketIntelligenceEngine {
             news_intelligence,
             correlation_analysis,
             predictive_insights,
-            market_summary: self.generate_market_summary(&sentiment_analysis, &news_intelligence, &correlation_analysis).await?,
-            confidence_score: self.calculate_overall_confidence(&sentiment_analysis, &news_intelligence, &correlation_analysis).await?,
+            market_summary,
+            confidence_score,
         })
     }
 
@@ -824,22 +919,30 @@ impl MarketIntelligenceEngine {
 
     /// Update all intelligence engines with new data
     pub async fn update_intelligence_data(&self) -> TradingResult<()> {
-        // Update all engines concurrently
-        let sentiment_update = self.sentiment_analyzer.write().await.update_data();
-        let prediction_update = self.predictive_analytics.write().await.update_models();
-        let news_update = self.news_intelligence.write().await.update_news_feeds();
-        let correlation_update = self.correlation_engine.write().await.update_correlations();
-
-        // Wait for all updates to complete
-        futures::try_join!(
-            sentiment_update,
-            prediction_update,
-            news_update,
-            correlation_update
-        )?;
+        // News first so sentiment sees the freshly stored articles
+        self.news_intelligence.write().await.update_news_feeds().await?;
+        self.sentiment_analyzer.write().await.update_data().await?;
+        self.predictive_analytics.write().await.update_models().await?;
+        self.correlation_engine.write().await.update_correlations().await?;
 
         Ok(())
     }
+
+    /// Link news entities to the exchange's tradable symbols
+    pub async fn set_symbol_universe(&self, universe: &[crate::models::SymbolInfo]) {
+        self.news_intelligence.write().await.set_symbol_universe(universe);
+    }
+
+    /// Current intelligence score for a symbol, as used by the bot's entry filter
+    pub async fn intelligence_reading(&self, symbol: &str) -> TradingResult<IntelligenceReading> {
+        let intelligence = self.get_market_intelligence(symbol).await?;
+        Ok(IntelligenceReading {
+            symbol: symbol.to_string(),
+            score: intelligence.intelligence_score,
+            confidence: intelligence.confidence_level,
+            timestamp: intelligence.timestamp,
+        })
+    }
 }
 
 /// Market intelligence configuration
@@ -882,4 +985,117 @@ impl Default for MarketIntelligenceConfig {
             ],
         }
     }
-}
\ No newline at end of file
+}
+
+/// Latest intelligence score computed for a symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntelligenceReading {
+    pub symbol: String,
+    /// 0-100, see `MarketIntelligenceResult::intelligence_score`
+    pub score: f64,
+    pub confidence: f64,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Bot entry filter on the market intelligence score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntelligenceFilter {
+    pub enabled: bool,
+    /// Entries need at least this score
+    pub min_score: f64,
+    /// Readings older than this are treated as missing
+    pub max_age_minutes: u32,
+    /// Block entries while no fresh reading is available instead of letting them through
+    pub block_without_reading: bool,
+}
+
+impl Default for IntelligenceFilter {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_score: 50.0,
+            max_age_minutes: 30,
+            block_without_reading: false,
+        }
+    }
+}
+
+impl IntelligenceFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !(0.0..=100.0).contains(&self.min_score) {
+            return Err("Intelligence filter min_score must be between 0 and 100".to_string());
+        }
+        if self.max_age_minutes == 0 {
+            return Err("Intelligence filter max_age_minutes must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether an entry is allowed given the latest reading for the symbol
+    pub fn allows_entry(&self, reading: Option<&IntelligenceReading>, now: DateTime<Utc>) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        let max_age = chrono::Duration::minutes(self.max_age_minutes as i64);
+        match reading.filter(|r| now - r.timestamp <= max_age) {
+            Some(reading) => reading.score >= self.min_score,
+            None => !self.block_without_reading,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(score: f64, minutes_ago: i64) -> IntelligenceReading {
+        IntelligenceReading {
+            symbol: "BTCUSDT".to_string(),
+            score,
+            confidence: 0.5,
+            timestamp: Utc::now() - chrono::Duration::minutes(minutes_ago),
+        }
+    }
+
+    #[test]
+    fn test_intelligence_filter_gates_on_fresh_score() {
+        let now = Utc::now();
+        let mut filter = IntelligenceFilter::default();
+        assert!(filter.allows_entry(Some(&reading(10.0, 1)), now), "disabled filter allows everything");
+
+        filter.enabled = true;
+        filter.min_score = 60.0;
+        assert!(filter.validate().is_ok());
+        assert!(filter.allows_entry(Some(&reading(65.0, 1)), now));
+        assert!(!filter.allows_entry(Some(&reading(40.0, 1)), now));
+
+        // A stale low score no longer blocks unless missing readings do
+        assert!(filter.allows_entry(Some(&reading(40.0, 120)), now));
+        filter.block_without_reading = true;
+        assert!(!filter.allows_entry(Some(&reading(40.0, 120)), now));
+        assert!(!filter.allows_entry(None, now));
+
+        filter.min_score = 150.0;
+        assert!(filter.validate().is_err());
+    }
+
+    #[test]
+    fn test_time_range_and_context_serialize() {
+        let range = TimeRange::last_hours(24);
+        assert_eq!((range.end - range.start).num_hours(), 24);
+
+        let context = MarketContext {
+            assets: vec!["BTCUSDT".to_string()],
+            time_range: range,
+            sentiment_score: 0.2,
+            market_trend: MarketTrend::Bullish,
+        };
+        let json = serde_json::to_string(&context).unwrap();
+        let parsed: MarketContext = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.assets, context.assets);
+        assert!(matches!(parsed.market_trend, MarketTrend::Bullish));
+    }
+}
@@ -0,0 +1,666 @@
+// News Ingestion Pipeline
+// Polls RSS/Atom feeds and local JSON drops on each source's schedule, deduplicates
+// articles by URL and content hash and keeps them on disk for the news engines
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::errors::{TradingError, TradingResult};
+use crate::logging::LogCategory;
+use crate::persistence;
+use crate::{log_info, log_warning};
+use super::news_intelligence::{NewsArticle, NewsSource};
+use super::nlp::NLPProcessor;
+
+/// Articles kept on disk; the oldest are dropped first
+const MAX_STORED_ARTICLES: usize = 5000;
+const FETCH_TIMEOUT_SECONDS: u64 = 20;
+
+/// News store shared by the news and sentiment engines
+pub type SharedNewsStore = Arc<Mutex<NewsStore>>;
+
+/// A source of articles; implementations only fetch and parse, the pipeline handles
+/// scheduling, deduplication and storage
+#[async_trait::async_trait]
+pub trait NewsFeed: Send + Sync {
+    /// Articles currently published by `source`
+    async fn fetch(&self, source: &NewsSource) -> TradingResult<Vec<NewsArticle>>;
+}
+
+/// RSS 2.0 or Atom feed fetched over HTTP
+pub struct RssFeed {
+    client: reqwest::Client,
+}
+
+impl RssFeed {
+    pub fn new() -> TradingResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(FETCH_TIMEOUT_SECONDS))
+            .build()
+            .map_err(|e| TradingError::internal_error(format!("Failed to build news client: {}", e)))?;
+        Ok(Self { client })
+    }
+}
+
+#[async_trait::async_trait]
+impl NewsFeed for RssFeed {
+    async fn fetch(&self, source: &NewsSource) -> TradingResult<Vec<NewsArticle>> {
+        let response = self.client.get(&source.url).send().await
+            .map_err(|_| TradingError::connection_failed(Some(source.url.clone())))?;
+        if !response.status().is_success() {
+            return Err(TradingError::api_error(
+                response.status().as_u16() as i64,
+                format!("News feed {} returned {}", source.name, response.status()),
+                source.url.clone(),
+            ));
+        }
+        let body = response.text().await
+            .map_err(|_| TradingError::connection_failed(Some(source.url.clone())))?;
+        parse_feed(&body, source)
+    }
+}
+
+/// Article as written to local JSON files
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalArticle {
+    pub title: String,
+    pub url: String,
+    #[serde(default)]
+    pub content: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub published_at: DateTime<Utc>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// A JSON file holding an array of `LocalArticle`, or a directory of such files.
+/// The source `url` is the path, optionally prefixed with `file://`
+pub struct JsonFileFeed;
+
+impl JsonFileFeed {
+    fn read_file(path: &Path, source: &NewsSource) -> TradingResult<Vec<NewsArticle>> {
+        let articles: Vec<LocalArticle> = persistence::read_json(path)?.unwrap_or_default();
+        Ok(articles.into_iter()
+            .map(|local| {
+                let mut article = new_article(source, local.title, local.url, local.content, local.summary, local.published_at);
+                article.author = local.author;
+                if let Some(category) = local.category {
+                    article.category = category;
+                }
+                article.tags = local.tags;
+                article
+            })
+            .collect())
+    }
+}
+
+#[async_trait::async_trait]
+impl NewsFeed for JsonFileFeed {
+    async fn fetch(&self, source: &NewsSource) -> TradingResult<Vec<NewsArticle>> {
+        let path = PathBuf::from(source.url.strip_prefix("file://").unwrap_or(&source.url));
+        if !path.is_dir() {
+            return Self::read_file(&path, source);
+        }
+
+        let entries = std::fs::read_dir(&path).map_err(|e| TradingError::config_error(
+            "news_source".to_string(),
+            format!("Failed to read news directory {}: {}", path.display(), e)
+        ))?;
+        let mut files: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|file| file.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        files.sort();
+
+        let mut articles = Vec::new();
+        for file in files {
+            match Self::read_file(&file, source) {
+                Ok(found) => articles.extend(found),
+                Err(e) => {
+                    log_warning!(LogCategory::DataProcessing, "Skipping news file {}: {}", file.display(), e);
+                }
+            }
+        }
+        Ok(articles)
+    }
+}
+
+/// Feed implementation for a source, chosen from its URL
+pub fn feed_for_source(source: &NewsSource) -> TradingResult<Box<dyn NewsFeed>> {
+    if source.url.starts_with("http://") || source.url.starts_with("https://") {
+        Ok(Box::new(RssFeed::new()?))
+    } else {
+        Ok(Box::new(JsonFileFeed))
+    }
+}
+
+fn new_article(
+    source: &NewsSource,
+    title: String,
+    url: String,
+    content: String,
+    summary: String,
+    published_at: DateTime<Utc>,
+) -> NewsArticle {
+    NewsArticle {
+        id: String::new(),
+        title: title.trim().to_string(),
+        content: content.trim().to_string(),
+        summary: summary.trim().to_string(),
+        source: source.id.clone(),
+        author: None,
+        published_at,
+        url: url.trim().to_string(),
+        category: source.categories.first().cloned().unwrap_or_else(|| "general".to_string()),
+        tags: Vec::new(),
+        sentiment_score: 0.0,
+        relevance_score: 0.0,
+        impact_score: 0.0,
+        market_entities: Vec::new(),
+        impact_categories: Vec::new(),
+    }
+}
+
+fn parse_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc2822(value)
+        .or_else(|_| DateTime::parse_from_rfc3339(value))
+        .ok()
+        .map(|date| date.with_timezone(&Utc))
+}
+
+/// Parse an RSS 2.0 or Atom document into articles
+pub fn parse_feed(xml: &str, source: &NewsSource) -> TradingResult<Vec<NewsArticle>> {
+    #[derive(Default)]
+    struct Entry {
+        title: String,
+        link: String,
+        guid: String,
+        summary: String,
+        content: String,
+        author: String,
+        published: String,
+        updated: String,
+        categories: Vec<String>,
+    }
+
+    let mut reader = Reader::from_str(xml);
+    let mut entries = Vec::new();
+    let mut current: Option<Entry> = None;
+    // Element names from the entry down to the current text node
+    let mut path: Vec<String> = Vec::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| TradingError::validation_error(
+            "news_feed".to_string(),
+            format!("Malformed feed from {}: {}", source.name, e),
+            Some(source.url.clone())
+        ))?;
+        match event {
+            Event::Start(ref element) | Event::Empty(ref element) => {
+                let name = element.local_name().as_ref().to_string();
+                let is_empty = matches!(event, Event::Empty(_));
+                let Some(entry) = current.as_mut() else {
+                    if !is_empty && matches!(name.as_str(), "item" | "entry") {
+                        current = Some(Entry::default());
+                    }
+                    continue;
+                };
+                let attribute = |key: &str| element.attributes()
+                    .filter_map(|a| a.ok())
+                    .find(|a| a.key.local_name().as_ref() == key)
+                    .map(|a| a.value.to_string());
+                match name.as_str() {
+                    // Atom links and categories carry their values in attributes
+                    "link" => if let Some(href) = attribute("href") {
+                        let rel = attribute("rel").unwrap_or_else(|| "alternate".to_string());
+                        if rel == "alternate" && entry.link.is_empty() {
+                            entry.link = href;
+                        }
+                    },
+                    "category" => if let Some(term) = attribute("term") {
+                        entry.categories.push(term);
+                    },
+                    _ => {}
+                }
+                if !is_empty {
+                    path.push(name);
+                }
+            }
+            Event::End(element) => {
+                let name = element.local_name().as_ref().to_string();
+                if matches!(name.as_str(), "item" | "entry") && path.is_empty() {
+                    if let Some(entry) = current.take() {
+                        entries.push(entry);
+                    }
+                } else if path.last() == Some(&name) {
+                    path.pop();
+                }
+            }
+            Event::Text(_) | Event::CData(_) | Event::GeneralRef(_) => {
+                let (Some(entry), Some(field)) = (current.as_mut(), path.first()) else { continue };
+                let text = match &event {
+                    Event::Text(text) => text.xml10_content().to_string(),
+                    Event::CData(data) => data.xml10_content().to_string(),
+                    Event::GeneralRef(reference) => match reference.resolve_char_ref() {
+                        Ok(Some(c)) => c.to_string(),
+                        _ => quick_xml::escape::resolve_predefined_entity(&reference.xml10_content())
+                            .unwrap_or_default()
+                            .to_string(),
+                    },
+                    _ => continue,
+                };
+                let target = match (field.as_str(), path.len()) {
+                    ("title", 1) => &mut entry.title,
+                    ("link", 1) => &mut entry.link,
+                    ("guid", 1) | ("id", 1) => &mut entry.guid,
+                    ("description", 1) | ("summary", 1) => &mut entry.summary,
+                    ("encoded", 1) | ("content", 1) => &mut entry.content,
+                    ("creator", 1) | ("author", _) => &mut entry.author,
+                    ("pubDate", 1) | ("published", 1) | ("date", 1) => &mut entry.published,
+                    ("updated", 1) => &mut entry.updated,
+                    ("category", 1) => {
+                        entry.categories.push(text);
+                        continue;
+                    }
+                    _ => continue,
+                };
+                target.push_str(&text);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(entries.into_iter()
+        .filter_map(|entry| {
+            // RSS guids are often permalinks when no link is given
+            let url = if entry.link.trim().is_empty() { entry.guid } else { entry.link };
+            if entry.title.trim().is_empty() || url.trim().is_empty() {
+                return None;
+            }
+            let published_at = parse_date(&entry.published)
+                .or_else(|| parse_date(&entry.updated))
+                .unwrap_or_else(Utc::now);
+            let mut article = new_article(source, entry.title, url, entry.content, entry.summary, published_at);
+            article.author = Some(entry.author.trim().to_string()).filter(|author| !author.is_empty());
+            article.tags = entry.categories.into_iter().map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect();
+            Some(article)
+        })
+        .collect())
+}
+
+/// URL used for duplicate detection: no fragment, tracking parameters or trailing slash
+fn normalize_url(raw: &str) -> String {
+    match url::Url::parse(raw.trim()) {
+        Ok(mut url) => {
+            url.set_fragment(None);
+            let query: Vec<(String, String)> = url.query_pairs()
+                .filter(|(key, _)| !key.starts_with("utm_"))
+                .map(|(key, value)| (key.into_owned(), value.into_owned()))
+                .collect();
+            if query.is_empty() {
+                url.set_query(None);
+            } else {
+                url.query_pairs_mut().clear().extend_pairs(query);
+            }
+            url.to_string().trim_end_matches('/').to_lowercase()
+        }
+        Err(_) => raw.trim().trim_end_matches('/').to_lowercase(),
+    }
+}
+
+/// Hash of the title and body with case and whitespace normalized, so syndicated copies match
+fn content_hash(article: &NewsArticle) -> String {
+    let body = if article.content.is_empty() { &article.summary } else { &article.content };
+    let normalized: String = format!("{} {}", article.title, body)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase();
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+/// Deduplicated articles, oldest first
+#[derive(Debug, Default)]
+pub struct NewsStore {
+    articles: VecDeque<NewsArticle>,
+    urls: HashSet<String>,
+    hashes: HashSet<String>,
+    path: Option<PathBuf>,
+}
+
+impl NewsStore {
+    /// In-memory store that is never written to disk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("news_articles.json")
+    }
+
+    pub fn open(path: PathBuf) -> TradingResult<Self> {
+        let articles: Vec<NewsArticle> = persistence::read_json(&path)?.unwrap_or_default();
+        let mut store = Self { path: Some(path), ..Self::default() };
+        for article in articles {
+            store.insert(article);
+        }
+        Ok(store)
+    }
+
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        match self.path.as_ref() {
+            Some(path) => persistence::write_json(path, &self.articles),
+            None => Ok(()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.articles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.articles.is_empty()
+    }
+
+    /// Add an article unless its URL or content was seen before. The article id is
+    /// derived from the content hash. Returns whether it was added
+    pub fn insert(&mut self, mut article: NewsArticle) -> bool {
+        let url = normalize_url(&article.url);
+        let hash = content_hash(&article);
+        if self.urls.contains(&url) || self.hashes.contains(&hash) {
+            return false;
+        }
+        article.id = hash[..16].to_string();
+        self.urls.insert(url);
+        self.hashes.insert(hash);
+
+        let position = self.articles.partition_point(|a| a.published_at <= article.published_at);
+        self.articles.insert(position, article);
+        if self.articles.len() > MAX_STORED_ARTICLES {
+            // Keep the dedup sets so dropped articles are not ingested again
+            self.articles.pop_front();
+        }
+        true
+    }
+
+    /// Articles published within `[start, end]`, oldest first
+    pub fn articles_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<NewsArticle> {
+        self.articles.iter()
+            .filter(|a| a.published_at >= start && a.published_at <= end)
+            .cloned()
+            .collect()
+    }
+
+    /// Articles published in the last `hours`, newest first
+    pub fn recent(&self, hours: i64) -> Vec<NewsArticle> {
+        let since = Utc::now() - Duration::hours(hours);
+        self.articles.iter().rev()
+            .take_while(|a| a.published_at >= since)
+            .cloned()
+            .collect()
+    }
+
+    /// Replace stored articles with updated copies, e.g. after scoring, matched by id
+    pub fn update(&mut self, updated: &[NewsArticle]) {
+        let by_id: HashMap<&str, &NewsArticle> = updated.iter().map(|a| (a.id.as_str(), a)).collect();
+        for article in self.articles.iter_mut() {
+            if let Some(new) = by_id.get(article.id.as_str()) {
+                *article = (*new).clone();
+            }
+        }
+    }
+}
+
+/// Outcome of polling one source
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourcePollResult {
+    pub source: String,
+    pub fetched: usize,
+    pub added: usize,
+    pub error: Option<String>,
+    pub next_poll: DateTime<Utc>,
+}
+
+struct ScheduledSource {
+    source: NewsSource,
+    feed: Box<dyn NewsFeed>,
+    next_poll: DateTime<Utc>,
+}
+
+/// Polls each source on its `update_frequency` and feeds new articles into the store
+pub struct NewsPipeline {
+    sources: Vec<ScheduledSource>,
+    store: SharedNewsStore,
+    /// Scores new articles before they are stored
+    processor: NLPProcessor,
+}
+
+impl NewsPipeline {
+    pub fn new(store: SharedNewsStore) -> Self {
+        Self { sources: Vec::new(), store, processor: NLPProcessor::default() }
+    }
+
+    /// Replace the NLP processor, e.g. once the exchange symbol universe is known
+    pub fn set_processor(&mut self, processor: NLPProcessor) {
+        self.processor = processor;
+    }
+
+    pub fn store(&self) -> SharedNewsStore {
+        self.store.clone()
+    }
+
+    /// Add a source with the feed implementation its URL calls for; it is due immediately
+    pub fn add_source(&mut self, source: NewsSource) -> TradingResult<()> {
+        let feed = feed_for_source(&source)?;
+        self.add_feed(source, feed);
+        Ok(())
+    }
+
+    /// Add a source backed by a custom feed implementation
+    pub fn add_feed(&mut self, source: NewsSource, feed: Box<dyn NewsFeed>) {
+        self.sources.retain(|s| s.source.id != source.id);
+        self.sources.push(ScheduledSource { source, feed, next_poll: DateTime::<Utc>::MIN_UTC });
+    }
+
+    pub fn sources(&self) -> Vec<&NewsSource> {
+        self.sources.iter().map(|s| &s.source).collect()
+    }
+
+    /// Fetch every source whose poll time has come, store new articles and schedule the
+    /// next poll. A failing source is retried at its normal interval
+    pub async fn poll_due(&mut self, now: DateTime<Utc>) -> TradingResult<Vec<SourcePollResult>> {
+        let mut results = Vec::new();
+        for scheduled in self.sources.iter_mut().filter(|s| s.next_poll <= now) {
+            scheduled.next_poll = now + Duration::minutes(scheduled.source.update_frequency.max(1));
+            let mut result = SourcePollResult {
+                source: scheduled.source.id.clone(),
+                fetched: 0,
+                added: 0,
+                error: None,
+                next_poll: scheduled.next_poll,
+            };
+            match scheduled.feed.fetch(&scheduled.source).await {
+                Ok(mut articles) => {
+                    result.fetched = articles.len();
+                    for article in articles.iter_mut() {
+                        self.processor.annotate(article, scheduled.source.credibility_score);
+                    }
+                    let mut store = self.store.lock().unwrap_or_else(|e| e.into_inner());
+                    result.added = articles.into_iter().filter(|article| store.insert(article.clone())).count();
+                }
+                Err(e) => {
+                    log_warning!(LogCategory::DataProcessing, "Failed to fetch news from {}: {}", scheduled.source.name, e);
+                    result.error = Some(e.to_string());
+                }
+            }
+            results.push(result);
+        }
+
+        let added: usize = results.iter().map(|r| r.added).sum();
+        if added > 0 {
+            self.store.lock().unwrap_or_else(|e| e.into_inner()).save()?;
+            log_info!(LogCategory::DataProcessing, "Ingested {} new articles from {} sources", added, results.len());
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::news_intelligence::NewsReliability;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn source(id: &str, url: &str, update_frequency: i64) -> NewsSource {
+        NewsSource {
+            id: id.to_string(),
+            name: id.to_string(),
+            url: url.to_string(),
+            credibility_score: 0.9,
+            update_frequency,
+            categories: vec!["crypto".to_string()],
+            language: "en".to_string(),
+            reliability_rating: NewsReliability::High,
+        }
+    }
+
+    const RSS: &str = r#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:dc="http://purl.org/dc/elements/1.1/"><channel>
+  <title>Fixture</title>
+  <link>https://news.example/</link>
+  <item>
+    <title>Bitcoin ETF inflows hit record &amp; climb</title>
+    <link>https://news.example/btc-etf?utm_source=rss</link>
+    <description><![CDATA[<p>Spot ETFs saw record inflows.</p>]]></description>
+    <dc:creator>Jane Doe</dc:creator>
+    <category>Bitcoin</category>
+    <pubDate>Tue, 01 Oct 2024 12:00:00 +0000</pubDate>
+  </item>
+  <item>
+    <title>Exchange hacked</title>
+    <guid>https://news.example/hack</guid>
+    <description>Hot wallet drained.</description>
+    <pubDate>Tue, 01 Oct 2024 13:00:00 GMT</pubDate>
+  </item>
+</channel></rss>"#;
+
+    const ATOM: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom fixture</title>
+  <entry>
+    <title type="html">Ethereum upgrade ships</title>
+    <link rel="alternate" href="https://atom.example/eth-upgrade"/>
+    <id>urn:uuid:1</id>
+    <updated>2024-10-02T08:00:00Z</updated>
+    <summary>The network upgraded.</summary>
+    <author><name>Core Devs</name></author>
+    <category term="Ethereum"/>
+  </entry>
+</feed>"#;
+
+    #[test]
+    fn test_parse_rss_and_atom() {
+        let rss = parse_feed(RSS, &source("fixture", "https://news.example/rss", 10)).unwrap();
+        assert_eq!(rss.len(), 2);
+        assert_eq!(rss[0].title, "Bitcoin ETF inflows hit record & climb");
+        assert_eq!(rss[0].summary, "<p>Spot ETFs saw record inflows.</p>");
+        assert_eq!(rss[0].author.as_deref(), Some("Jane Doe"));
+        assert_eq!(rss[0].tags, vec!["Bitcoin".to_string()]);
+        assert_eq!(rss[0].published_at.to_rfc3339(), "2024-10-01T12:00:00+00:00");
+        assert_eq!(rss[1].url, "https://news.example/hack");
+        assert_eq!(rss[1].source, "fixture");
+
+        let atom = parse_feed(ATOM, &source("atom", "https://atom.example/feed", 10)).unwrap();
+        assert_eq!(atom.len(), 1);
+        assert_eq!(atom[0].url, "https://atom.example/eth-upgrade");
+        assert_eq!(atom[0].author.as_deref(), Some("Core Devs"));
+        assert_eq!(atom[0].tags, vec!["Ethereum".to_string()]);
+        assert_eq!(atom[0].published_at.to_rfc3339(), "2024-10-02T08:00:00+00:00");
+    }
+
+    #[test]
+    fn test_store_deduplicates_by_url_and_content() {
+        let feed_source = source("fixture", "https://news.example/rss", 10);
+        let mut store = NewsStore::new();
+        let articles = parse_feed(RSS, &feed_source).unwrap();
+        assert!(store.insert(articles[0].clone()));
+        assert!(!store.insert(articles[0].clone()));
+
+        // Same story without tracking parameters
+        let mut same_url = articles[0].clone();
+        same_url.url = "https://news.example/btc-etf/".to_string();
+        same_url.title = "Different headline".to_string();
+        assert!(!store.insert(same_url));
+
+        // Syndicated copy under another URL
+        let mut syndicated = articles[1].clone();
+        assert!(store.insert(articles[1].clone()));
+        syndicated.url = "https://mirror.example/hack".to_string();
+        syndicated.title = "  exchange   HACKED ".to_string();
+        assert!(!store.insert(syndicated));
+
+        assert_eq!(store.len(), 2);
+        assert_eq!(store.articles_between(DateTime::<Utc>::MIN_UTC, Utc::now()).len(), 2);
+        assert_eq!(store.articles_between(DateTime::<Utc>::MIN_UTC, Utc::now())[0].id.len(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_polls_http_and_local_sources_on_schedule() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).and(path("/rss"))
+            .respond_with(ResponseTemplate::new(200).set_body_string(RSS))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let local = vec![LocalArticle {
+            title: "Desk note: SOL listing".to_string(),
+            url: "https://desk.local/sol".to_string(),
+            content: "Solana listed on a new venue.".to_string(),
+            summary: String::new(),
+            author: None,
+            published_at: Utc::now(),
+            category: Some("listings".to_string()),
+            tags: vec![],
+        }];
+        persistence::write_json(&dir.path().join("notes.json"), &local).unwrap();
+
+        let store: SharedNewsStore = Arc::new(Mutex::new(NewsStore::new()));
+        let mut pipeline = NewsPipeline::new(store.clone());
+        pipeline.add_source(source("fixture", &format!("{}/rss", server.uri()), 15)).unwrap();
+        pipeline.add_source(source("desk", &dir.path().to_string_lossy(), 5)).unwrap();
+        pipeline.add_source(source("broken", &format!("{}/missing", server.uri()), 5)).unwrap();
+
+        let now = Utc::now();
+        let results = pipeline.poll_due(now).await.unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results.iter().map(|r| r.added).sum::<usize>(), 3);
+        assert!(results.iter().find(|r| r.source == "broken").unwrap().error.is_some());
+        assert_eq!(store.lock().unwrap().recent(1).len(), 1);
+
+        // Only the five-minute sources are due again after ten minutes
+        let later = pipeline.poll_due(now + Duration::minutes(10)).await.unwrap();
+        let polled: Vec<&str> = later.iter().map(|r| r.source.as_str()).collect();
+        assert_eq!(polled, vec!["desk", "broken"]);
+        assert_eq!(later.iter().map(|r| r.added).sum::<usize>(), 0);
+    }
+}
@@ -1,16 +1,19 @@
 // News Intelligence Engine for Market Intelligence Agent
-use crate::types::*;
-use crate::market_intelligence::correlation_engine::CorrelationData;
+use crate::errors::TradingResult;
+use crate::market_intelligence::TimeRange;
+use crate::market_intelligence::news_ingestion::{NewsPipeline, NewsStore, SharedNewsStore, SourcePollResult};
+use crate::market_intelligence::nlp::{ImpactCategory, NLPProcessor};
+use crate::models::SymbolInfo;
+use crate::persistence;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use tokio::sync::RwLock;
 use std::sync::Arc;
 use chrono::{DateTime, Utc};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NewsIntelligenceEngine {
     news_sources: HashMap<String, NewsSource>,
-    content_analyzers: HashMap<String, Box<dyn NewsContentAnalyzer + Send + Sync>>,
+    pipeline: NewsPipeline,
+    nlp: NLPProcessor,
     impact_assessor: NewsImpactAssessor,
     trend_detector: NewsTrendDetector,
     relevance_scorer: NewsRelevanceScorer,
@@ -54,6 +57,21 @@ pub struct NewsArticle {
     pub relevance_score: f64,
     pub impact_score: f64,
     pub market_entities: Vec<MarketEntity>,
+    #[serde(default)]
+    pub impact_categories: Vec<ImpactCategory>,
+}
+
+impl NewsArticle {
+    /// Whether the article mentions the base asset of a trading pair such as `SOLUSDT`
+    pub fn mentions_symbol(&self, symbol: &str) -> bool {
+        let base = ["USDT", "BUSD", "USDC", "BTC", "ETH"].iter()
+            .find_map(|quote| symbol.strip_suffix(quote).filter(|base| !base.is_empty()))
+            .unwrap_or(symbol)
+            .to_lowercase();
+        format!("{} {} {}", self.title, self.summary, self.content).to_lowercase().contains(&base)
+            || self.tags.iter().any(|tag| tag.to_lowercase() == base)
+            || self.market_entities.iter().any(|e| e.ticker.as_deref().is_some_and(|t| t.eq_ignore_ascii_case(&base)))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -159,17 +177,19 @@ pub enum TimeSensitivity {
     LongTerm,
 }
 
-pub trait NewsContentAnalyzer {
-    async fn analyze_content(&self, article: &NewsArticle) -> TradingResult<ContentAnalysis>;
-    async fn extract_entities(&self, content: &str) -> TradingResult<Vec<MarketEntity>>;
-    async fn assess_impact(&self, article: &NewsArticle) -> TradingResult<f64>;
-}
-
 impl NewsIntelligenceEngine {
     /// Create new news intelligence engine
     pub async fn new() -> TradingResult<Self> {
         let news_sources = Self::initialize_news_sources().await?;
-        let content_analyzers = Self::initialize_content_analyzers().await?;
+        let store = NewsStore::open_default().unwrap_or_else(|e| {
+            eprintln!("Failed to load stored news, starting fresh: {}", e);
+            NewsStore::new()
+        });
+        let mut pipeline = NewsPipeline::new(Arc::new(std::sync::Mutex::new(store)));
+        for source in news_sources.values() {
+            pipeline.add_source(source.clone())?;
+        }
+        let nlp = NLPProcessor::default();
         let impact_assessor = NewsImpactAssessor::new().await?;
         let trend_detector = NewsTrendDetector::new().await?;
         let relevance_scorer = NewsRelevanceScorer::new().await?;
@@ -177,7 +197,8 @@ impl NewsIntelligenceEngine {
 
         Ok(Self {
             news_sources,
-            content_analyzers,
+            pipeline,
+            nlp,
             impact_assessor,
             trend_detector,
             relevance_scorer,
@@ -223,47 +244,189 @@ impl NewsIntelligenceEngine {
         })
     }
 
+    /// News picture for one symbol: breaking stories, sentiment trend and market-moving
+    /// events from stored articles that mention its base asset
+    pub async fn analyze_news_impact(&self, symbol: &str) -> TradingResult<super::NewsIntelligence> {
+        let now = Utc::now();
+        let articles = self.collect_news_articles(TimeRange::last_hours(7 * 24)).await?;
+        let mut relevant: Vec<NewsArticle> = self.analyze_article_content(&articles).await?
+            .into_iter()
+            .filter(|article| article.mentions_symbol(symbol))
+            .collect();
+        relevant.sort_by_key(|article| std::cmp::Reverse(article.published_at));
+        let hours_ago = |article: &NewsArticle| (now - article.published_at).num_hours();
+        let last_day: Vec<&NewsArticle> = relevant.iter().filter(|a| hours_ago(a) < 24).collect();
+
+        let mut breaking: Vec<&NewsArticle> = last_day.iter()
+            .copied()
+            .filter(|a| hours_ago(a) < 6 && a.impact_score >= 0.6)
+            .collect();
+        breaking.sort_by(|a, b| b.impact_score.partial_cmp(&a.impact_score).unwrap_or(std::cmp::Ordering::Equal));
+        let breaking_news = breaking.into_iter()
+            .take(10)
+            .map(|article| super::BreakingNews {
+                headline: article.title.clone(),
+                source: article.source.clone(),
+                published_at: article.published_at,
+                sentiment_score: article.sentiment_score,
+                impact_score: article.impact_score,
+                market_relevance: article.relevance_score,
+                credibility_score: self.source_credibility(&article.source),
+                entities: article.market_entities.iter().map(|e| e.name.clone()).collect(),
+                categories: article.impact_categories.iter().map(|c| format!("{:?}", c).to_lowercase()).collect(),
+            })
+            .collect();
+
+        let mean = |items: &[&NewsArticle]| if items.is_empty() {
+            0.0
+        } else {
+            items.iter().map(|a| a.sentiment_score).sum::<f64>() / items.len() as f64
+        };
+        let bucket_means = |bucket_hours: i64, buckets: i64| -> Vec<f64> {
+            (0..buckets).rev()
+                .filter_map(|bucket| {
+                    let items: Vec<&NewsArticle> = relevant.iter()
+                        .filter(|a| hours_ago(a) / bucket_hours == bucket)
+                        .collect();
+                    (!items.is_empty()).then(|| mean(&items))
+                })
+                .collect()
+        };
+        let recent: Vec<&NewsArticle> = last_day.iter().copied().filter(|a| hours_ago(a) < 6).collect();
+        let earlier: Vec<&NewsArticle> = last_day.iter().copied().filter(|a| hours_ago(a) >= 6).collect();
+        let previous_day = relevant.iter().filter(|a| (24..48).contains(&hours_ago(a))).count() as f64;
+        let volume_change = (last_day.len() as f64 - previous_day) / previous_day.max(1.0);
+        let news_volume_trend = match volume_change {
+            c if c > 1.0 => super::TrendDirection::StronglyIncreasing,
+            c if c > 0.25 => super::TrendDirection::Increasing,
+            c if c < -0.5 => super::TrendDirection::StronglyDecreasing,
+            c if c < -0.25 => super::TrendDirection::Decreasing,
+            _ => super::TrendDirection::Stable,
+        };
+
+        let market_moving_events = last_day.iter()
+            .filter(|a| !a.impact_categories.is_empty())
+            .map(|article| super::MarketEvent {
+                event_type: match article.impact_categories[0] {
+                    ImpactCategory::Regulation => super::EventType::Regulatory,
+                    ImpactCategory::Listing | ImpactCategory::Delisting => super::EventType::Exchange,
+                    ImpactCategory::Etf => super::EventType::Institutional,
+                    ImpactCategory::Hack => super::EventType::Other,
+                },
+                event_description: article.title.clone(),
+                impact_magnitude: article.impact_score,
+                affected_assets: article.market_entities.iter().filter_map(|e| e.ticker.clone()).collect(),
+                event_timing: article.published_at,
+                duration_estimate: None,
+            })
+            .collect();
+
+        let mut sources: Vec<&str> = last_day.iter().map(|a| a.source.as_str()).collect();
+        sources.sort_unstable();
+        sources.dedup();
+        let mut source_credibility = super::SourceCredibilityAnalysis {
+            high_credibility_sources: Vec::new(),
+            medium_credibility_sources: Vec::new(),
+            low_credibility_sources: Vec::new(),
+            overall_credibility_score: 0.0,
+        };
+        for source in &sources {
+            let credibility = self.source_credibility(source);
+            source_credibility.overall_credibility_score += credibility / sources.len() as f64;
+            match credibility {
+                c if c >= 0.8 => source_credibility.high_credibility_sources.push(source.to_string()),
+                c if c >= 0.6 => source_credibility.medium_credibility_sources.push(source.to_string()),
+                _ => source_credibility.low_credibility_sources.push(source.to_string()),
+            }
+        }
+
+        Ok(super::NewsIntelligence {
+            breaking_news,
+            news_sentiment_trend: super::NewsSentimentTrend {
+                hourly_sentiment: bucket_means(1, 24),
+                daily_sentiment: bucket_means(24, 7),
+                sentiment_momentum: if earlier.is_empty() { 0.0 } else { mean(&recent) - mean(&earlier) },
+                news_volume_trend,
+            },
+            market_moving_events,
+            // Scheduled events need a calendar feed, which no configured source provides
+            scheduled_events: Vec::new(),
+            news_impact_score: last_day.iter().map(|a| a.impact_score).fold(0.0, f64::max),
+            source_credibility,
+        })
+    }
+
+    /// Poll the sources that are due and store any new articles
+    pub async fn update_news_feeds(&mut self) -> TradingResult<Vec<SourcePollResult>> {
+        self.pipeline.poll_due(Utc::now()).await
+    }
+
+    /// Link entities in news to the exchange's tradable symbols
+    pub fn set_symbol_universe(&mut self, universe: &[SymbolInfo]) {
+        self.nlp = NLPProcessor::new(universe);
+        self.pipeline.set_processor(self.nlp.clone());
+    }
+
+    /// Store of ingested articles, shared with the sentiment engine
+    pub fn news_store(&self) -> SharedNewsStore {
+        self.pipeline.store()
+    }
+
     /// Real-time news monitoring
     pub async fn start_real_time_monitoring(&self) -> TradingResult<()> {
         self.real_time_monitor.start_monitoring().await
     }
 
-    /// Get breaking news alerts
+    /// Alerts for high-impact articles from the last six hours, newest first
     pub async fn get_breaking_news_alerts(&self) -> TradingResult<Vec<NewsAlert>> {
-        self.real_time_monitor.get_active_alerts().await
+        let articles = self.collect_news_articles(TimeRange::last_hours(6)).await?;
+        let mut alerts: Vec<NewsAlert> = self.analyze_article_content(&articles).await?
+            .into_iter()
+            .filter(|article| article.impact_score >= NewsImpactAssessor::HIGH_IMPACT)
+            .map(|article| NewsAlert {
+                id: article.id.clone(),
+                alert_type: if article.impact_categories.is_empty() {
+                    AlertType::HighImpact
+                } else {
+                    AlertType::MarketMoving
+                },
+                title: article.title.clone(),
+                description: article.summary.clone(),
+                urgency: match article.impact_score {
+                    s if s >= 0.9 => AlertUrgency::Critical,
+                    s if s >= 0.8 => AlertUrgency::High,
+                    _ => AlertUrgency::Medium,
+                },
+                affected_markets: article.market_entities.iter().filter_map(|e| e.ticker.clone()).collect(),
+                source_article: article.url.clone(),
+                created_at: article.published_at,
+            })
+            .collect();
+        alerts.extend(self.real_time_monitor.get_active_alerts().await?);
+        alerts.sort_by_key(|alert| std::cmp::Reverse(alert.created_at));
+        Ok(alerts)
     }
 
     /// Initialize news sources
     async fn initialize_news_sources() -> TradingResult<HashMap<String, NewsSource>> {
         let mut sources = HashMap::new();
         
-        // Financial news sources
-        sources.insert("reuters".to_string(), NewsSource {
-            id: "reuters".to_string(),
-            name: "Reuters".to_string(),
-            url: "https://www.reuters.com/".to_string(),
-            credibility_score: 0.95,
-            update_frequency: 15,
-            categories: vec!["finance".to_string(), "markets".to_string(), "crypto".to_string()],
-            language: "en".to_string(),
-            reliability_rating: NewsReliability::VeryHigh,
-        });
-        
-        sources.insert("bloomberg".to_string(), NewsSource {
-            id: "bloomberg".to_string(),
-            name: "Bloomberg".to_string(),
-            url: "https://www.bloomberg.com/".to_string(),
-            credibility_score: 0.93,
+        // Crypto news feeds (RSS)
+        sources.insert("cointelegraph".to_string(), NewsSource {
+            id: "cointelegraph".to_string(),
+            name: "CoinTelegraph".to_string(),
+            url: "https://cointelegraph.com/rss".to_string(),
+            credibility_score: 0.8,
             update_frequency: 10,
-            categories: vec!["finance".to_string(), "markets".to_string(), "technology".to_string()],
+            categories: vec!["crypto".to_string(), "markets".to_string()],
             language: "en".to_string(),
-            reliability_rating: NewsReliability::VeryHigh,
+            reliability_rating: NewsReliability::High,
         });
         
         sources.insert("coindesk".to_string(), NewsSource {
             id: "coindesk".to_string(),
             name: "CoinDesk".to_string(),
-            url: "https://www.coindesk.com/".to_string(),
+            url: "https://www.coindesk.com/arc/outboundfeeds/rss/".to_string(),
             credibility_score: 0.88,
             update_frequency: 20,
             categories: vec!["crypto".to_string(), "blockchain".to_string(), "defi".to_string()],
@@ -271,45 +434,30 @@ impl NewsIntelligenceEngine {
             reliability_rating: NewsReliability::High,
         });
 
-        Ok(sources)
-    }
-
-    /// Initialize content analyzers
-    async fn initialize_content_analyzers() -> TradingResult<HashMap<String, Box<dyn NewsContentAnalyzer + Send + Sync>>> {
-        let mut analyzers = HashMap::new();
-        
-        // Add different types of content analyzers
-        analyzers.insert(
-            "sentiment".to_string(),
-            Box::new(SentimentContentAnalyzer::new().await?) as Box<dyn NewsContentAnalyzer + Send + Sync>
-        );
-        
-        analyzers.insert(
-            "entity".to_string(),
-            Box::new(EntityContentAnalyzer::new().await?) as Box<dyn NewsContentAnalyzer + Send + Sync>
-        );
-        
-        analyzers.insert(
-            "impact".to_string(),
-            Box::new(ImpactContentAnalyzer::new().await?) as Box<dyn NewsContentAnalyzer + Send + Sync>
-        );
+        // JSON files dropped into the data directory, e.g. desk notes or exported feeds
+        let local_dir = persistence::data_dir()?.join("news");
+        if std::fs::create_dir_all(&local_dir).is_ok() {
+            sources.insert("local".to_string(), NewsSource {
+                id: "local".to_string(),
+                name: "Local files".to_string(),
+                url: local_dir.to_string_lossy().to_string(),
+                credibility_score: 0.7,
+                update_frequency: 5,
+                categories: vec!["notes".to_string()],
+                language: "en".to_string(),
+                reliability_rating: NewsReliability::Medium,
+            });
+        }
 
-        Ok(analyzers)
+        Ok(sources)
     }
 
-    /// Collect news articles from sources
+    /// Ingested articles published within the time range
     async fn collect_news_articles(&self, time_range: TimeRange) -> TradingResult<Vec<NewsArticle>> {
-        let mut all_articles = Vec::new();
-        
-        for source in self.news_sources.values() {
-            match self.fetch_articles_from_source(source, time_range).await {
-                Ok(articles) => all_articles.extend(articles),
-                Err(e) => {
-                    eprintln!("Failed to fetch articles from {}: {}", source.name, e);
-                    continue;
-                }
-            }
-        }
+        let mut all_articles = self.pipeline.store()
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .articles_between(time_range.start, time_range.end);
         
         // Sort by relevance and published time
         all_articles.sort_by(|a, b| {
@@ -321,59 +469,23 @@ impl NewsIntelligenceEngine {
         Ok(all_articles)
     }
 
-    /// Fetch articles from specific news source
-    async fn fetch_articles_from_source(&self, source: &NewsSource, time_range: TimeRange) -> TradingResult<Vec<NewsArticle>> {
-        // In a real implementation, this would make HTTP requests to news APIs
-        // For now, we'll return mock data
-        
-        let mut articles = Vec::new();
-        
-        // Mock article generation for demonstration
-        for i in 0..10 {
-            let article = NewsArticle {
-                id: format!("{}_article_{}", source.id, i),
-                title: format!("Market Analysis Article {} from {}", i, source.name),
-                content: "Detailed market analysis content...".to_string(),
-                summary: "Summary of market developments".to_string(),
-                source: source.id.clone(),
-                author: Some(format!("Author {}", i)),
-                published_at: Utc::now() - chrono::Duration::hours(i),
-                url: format!("{}/article/{}", source.url, i),
-                category: "markets".to_string(),
-                tags: vec!["trading".to_string(), "analysis".to_string()],
-                sentiment_score: 0.0, // Will be calculated later
-                relevance_score: 0.0, // Will be calculated later
-                impact_score: 0.0, // Will be calculated later
-                market_entities: Vec::new(), // Will be extracted later
-            };
-            articles.push(article);
-        }
-        
-        Ok(articles)
+    /// Configured credibility of a source, neutral for unknown ones
+    fn source_credibility(&self, source: &str) -> f64 {
+        self.news_sources.get(source)
+            .map(|source| source.credibility_score)
+            .unwrap_or(0.5)
     }
 
-    /// Analyze content of articles
+    /// Re-score articles with the current NLP processor, so stored articles pick up
+    /// universe changes
     async fn analyze_article_content(&self, articles: &[NewsArticle]) -> TradingResult<Vec<NewsArticle>> {
-        let mut analyzed_articles = Vec::new();
-        
-        for article in articles {
-            let mut analyzed_article = article.clone();
-            
-            // Run all content analyzers
-            for analyzer in self.content_analyzers.values() {
-                let analysis = analyzer.analyze_content(&analyzed_article).await?;
-                
-                // Update article with analysis results
-                analyzed_article.sentiment_score = analysis.sentiment_score;
-                analyzed_article.relevance_score = analysis.relevance_score;
-                analyzed_article.impact_score = analysis.impact_score;
-                analyzed_article.market_entities = analysis.entities;
-            }
-            
-            analyzed_articles.push(analyzed_article);
-        }
-        
-        Ok(analyzed_articles)
+        Ok(articles.iter()
+            .map(|article| {
+                let mut analyzed_article = article.clone();
+                self.nlp.annotate(&mut analyzed_article, self.source_credibility(&article.source));
+                analyzed_article
+            })
+            .collect())
     }
 
     /// Calculate overall market sentiment
@@ -488,7 +600,7 @@ impl NewsIntelligenceEngine {
         
         for article in articles {
             let hour_key = article.published_at.timestamp() / 3600;
-            hourly_groups.entry(hour_key).or_insert_with(Vec::new).push(article);
+            hourly_groups.entry(hour_key).or_default().push(article);
         }
         
         for (hour_key, hour_articles) in hourly_groups {
@@ -498,7 +610,7 @@ impl NewsIntelligenceEngine {
             
             trend_data.push(SentimentDataPoint {
                 timestamp: DateTime::from_timestamp(hour_key * 3600, 0)
-                    .unwrap_or_else(|| Utc::now()),
+                    .unwrap_or_else(Utc::now),
                 sentiment: avg_sentiment,
                 article_count: hour_articles.len() as i32,
                 confidence: self.calculate_hourly_confidence(&hour_articles).await.unwrap_or(0.5),
@@ -602,13 +714,26 @@ impl NewsIntelligenceEngine {
             0.0
         };
         
+        // Compare the last day's volume with the day before
+        let now = Utc::now();
+        let last_day = articles.iter().filter(|a| a.published_at > now - chrono::Duration::hours(24)).count() as f64;
+        let previous_day = articles.iter()
+            .filter(|a| a.published_at <= now - chrono::Duration::hours(24) && a.published_at > now - chrono::Duration::hours(48))
+            .count() as f64;
+        let volume_trend = match (last_day, previous_day) {
+            (_, 0.0) => VolumeDirection::Stable,
+            (l, p) if l > p * 1.2 => VolumeDirection::Increasing,
+            (l, p) if l < p * 0.8 => VolumeDirection::Decreasing,
+            _ => VolumeDirection::Stable,
+        };
+        
         Ok(NewsVolumeAnalysis {
             total_articles,
             avg_hourly_volume,
             peak_hour_volume: hourly_counts.values().max().copied().unwrap_or(0),
             source_distribution: source_counts,
             category_distribution: category_counts,
-            volume_trend: VolumeDirection::Stable, // Simplified
+            volume_trend,
         })
     }
 
@@ -666,16 +791,6 @@ impl NewsIntelligenceEngine {
 
 // Supporting structures for news intelligence
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ContentAnalysis {
-    pub sentiment_score: f64,
-    pub relevance_score: f64,
-    pub impact_score: f64,
-    pub entities: Vec<MarketEntity>,
-    pub topics: Vec<String>,
-    pub keywords: Vec<String>,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SentimentDataPoint {
     pub timestamp: DateTime<Utc>,
@@ -764,123 +879,82 @@ pub enum AlertUrgency {
     Low,
 }
 
-// Mock implementations for content analyzers
-pub struct SentimentContentAnalyzer;
-pub struct EntityContentAnalyzer;
-pub struct ImpactContentAnalyzer;
+// Supporting components
 pub struct NewsImpactAssessor;
 pub struct NewsTrendDetector;
 pub struct NewsRelevanceScorer;
 pub struct RealTimeNewsMonitor;
 
-impl SentimentContentAnalyzer {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
-}
-
-impl NewsContentAnalyzer for SentimentContentAnalyzer {
-    async fn analyze_content(&self, article: &NewsArticle) -> TradingResult<ContentAnalysis> {
-        // Mock sentiment analysis
-        Ok(ContentAnalysis {
-            sentiment_score: 0.3, // Positive sentiment
-            relevance_score: 0.7,
-            impact_score: 0.5,
-            entities: vec![],
-            topics: vec!["trading".to_string()],
-            keywords: vec!["market".to_string(), "analysis".to_string()],
-        })
-    }
-
-    async fn extract_entities(&self, _content: &str) -> TradingResult<Vec<MarketEntity>> {
-        Ok(vec![])
-    }
-
-    async fn assess_impact(&self, _article: &NewsArticle) -> TradingResult<f64> {
-        Ok(0.5)
-    }
-}
-
-impl EntityContentAnalyzer {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
-}
-
-impl NewsContentAnalyzer for EntityContentAnalyzer {
-    async fn analyze_content(&self, _article: &NewsArticle) -> TradingResult<ContentAnalysis> {
-        Ok(ContentAnalysis {
-            sentiment_score: 0.0,
-            relevance_score: 0.8,
-            impact_score: 0.6,
-            entities: vec![
-                MarketEntity {
-                    entity_type: EntityType::Cryptocurrency,
-                    name: "Bitcoin".to_string(),
-                    ticker: Some("BTC".to_string()),
-                    confidence: 0.9,
-                    sentiment: 0.4,
-                    mentions: 3,
-                }
-            ],
-            topics: vec![],
-            keywords: vec![],
-        })
-    }
-
-    async fn extract_entities(&self, _content: &str) -> TradingResult<Vec<MarketEntity>> {
-        Ok(vec![])
-    }
-
-    async fn assess_impact(&self, _article: &NewsArticle) -> TradingResult<f64> {
-        Ok(0.6)
-    }
-}
+// Implementations for other components
+impl NewsImpactAssessor {
+    /// Articles at or above this impact are reported as high impact
+    const HIGH_IMPACT: f64 = 0.7;
 
-impl ImpactContentAnalyzer {
     pub async fn new() -> TradingResult<Self> {
         Ok(Self)
     }
-}
-
-impl NewsContentAnalyzer for ImpactContentAnalyzer {
-    async fn analyze_content(&self, _article: &NewsArticle) -> TradingResult<ContentAnalysis> {
-        Ok(ContentAnalysis {
-            sentiment_score: 0.0,
-            relevance_score: 0.0,
-            impact_score: 0.7,
-            entities: vec![],
-            topics: vec![],
-            keywords: vec![],
-        })
-    }
-
-    async fn extract_entities(&self, _content: &str) -> TradingResult<Vec<MarketEntity>> {
-        Ok(vec![])
-    }
 
-    async fn assess_impact(&self, _article: &NewsArticle) -> TradingResult<f64> {
-        Ok(0.7)
-    }
-}
-
-// Mock implementations for other components
-impl NewsImpactAssessor {
-    pub async fn new() -> TradingResult<Self> {
-        Ok(Self)
-    }
+    /// Events come from the impact categories the NLP processor tagged; sector impact is
+    /// the mean impact per category and temporal impact the mean by article age
+    pub async fn analyze_news_impact(&self, articles: &[NewsArticle]) -> TradingResult<NewsImpactAnalysis> {
+        let mut high_impact_news: Vec<NewsArticle> = articles.iter()
+            .filter(|a| a.impact_score >= Self::HIGH_IMPACT)
+            .cloned()
+            .collect();
+        high_impact_news.sort_by(|a, b| b.impact_score.partial_cmp(&a.impact_score).unwrap_or(std::cmp::Ordering::Equal));
+        high_impact_news.truncate(20);
+
+        let mut market_moving_events = Vec::new();
+        let mut by_category: HashMap<String, Vec<f64>> = HashMap::new();
+        for article in articles {
+            for category in &article.impact_categories {
+                by_category.entry(format!("{:?}", category).to_lowercase()).or_default().push(article.impact_score);
+            }
+            let Some(category) = article.impact_categories.first() else {
+                continue;
+            };
+            let (event_type, time_sensitivity) = match category {
+                ImpactCategory::Hack => (EventType::SecurityBreach, TimeSensitivity::Immediate),
+                ImpactCategory::Listing => (EventType::Other("Listing".to_string()), TimeSensitivity::Immediate),
+                ImpactCategory::Delisting => (EventType::Other("Delisting".to_string()), TimeSensitivity::ShortTerm),
+                ImpactCategory::Regulation => (EventType::RegulatoryChange, TimeSensitivity::MediumTerm),
+                ImpactCategory::Etf => (EventType::Other("ETF".to_string()), TimeSensitivity::MediumTerm),
+            };
+            market_moving_events.push(MarketEvent {
+                event_type,
+                description: article.title.clone(),
+                impact_score: article.impact_score,
+                affected_markets: article.market_entities.iter().filter_map(|e| e.ticker.clone()).collect(),
+                time_sensitivity,
+                related_articles: vec![article.id.clone()],
+            });
+        }
+        let sector_impact = by_category.into_iter()
+            .map(|(category, scores)| (category, scores.iter().sum::<f64>() / scores.len() as f64))
+            .collect();
+
+        let now = Utc::now();
+        let mean_impact = |min_hours: i64, max_hours: i64| {
+            let scores: Vec<f64> = articles.iter()
+                .filter(|a| {
+                    let age = (now - a.published_at).num_hours();
+                    age >= min_hours && age < max_hours
+                })
+                .map(|a| a.impact_score)
+                .collect();
+            if scores.is_empty() { 0.0 } else { scores.iter().sum::<f64>() / scores.len() as f64 }
+        };
 
-    pub async fn analyze_news_impact(&self, _articles: &[NewsArticle]) -> TradingResult<NewsImpactAnalysis> {
         Ok(NewsImpactAnalysis {
-            high_impact_news: vec![],
-            market_moving_events: vec![],
-            sector_impact: HashMap::new(),
+            high_impact_news,
+            market_moving_events,
+            sector_impact,
             geographic_impact: HashMap::new(),
             temporal_impact: TemporalImpactAnalysis {
-                immediate_impact: 0.3,
-                short_term_impact: 0.5,
-                medium_term_impact: 0.4,
-                long_term_impact: 0.2,
+                immediate_impact: mean_impact(i64::MIN, 1),
+                short_term_impact: mean_impact(1, 24),
+                medium_term_impact: mean_impact(24, 24 * 7),
+                long_term_impact: mean_impact(24 * 7, i64::MAX),
             },
         })
     }
@@ -891,17 +965,40 @@ impl NewsTrendDetector {
         Ok(Self)
     }
 
-    pub async fn detect_trending_topics(&self, _articles: &[NewsArticle]) -> TradingResult<Vec<TrendingTopic>> {
-        Ok(vec![
-            TrendingTopic {
-                topic: "DeFi Innovation".to_string(),
-                mention_count: 15,
-                sentiment_score: 0.6,
-                growth_rate: 0.25,
-                related_entities: vec!["Ethereum".to_string(), "Uniswap".to_string()],
-                key_articles: vec!["article_1".to_string(), "article_5".to_string()],
+    /// Most-tagged topics, with growth over the last 12 hours against the 12 before
+    pub async fn detect_trending_topics(&self, articles: &[NewsArticle]) -> TradingResult<Vec<TrendingTopic>> {
+        let now = Utc::now();
+        let mut by_topic: HashMap<String, Vec<&NewsArticle>> = HashMap::new();
+        for article in articles {
+            for tag in &article.tags {
+                by_topic.entry(tag.to_lowercase()).or_default().push(article);
             }
-        ])
+        }
+
+        let mut topics: Vec<TrendingTopic> = by_topic.into_iter()
+            .map(|(topic, tagged)| {
+                let recent = tagged.iter().filter(|a| a.published_at > now - chrono::Duration::hours(12)).count() as f64;
+                let earlier = tagged.iter()
+                    .filter(|a| a.published_at <= now - chrono::Duration::hours(12) && a.published_at > now - chrono::Duration::hours(24))
+                    .count() as f64;
+                let mut related_entities: Vec<String> = tagged.iter()
+                    .flat_map(|a| a.market_entities.iter().map(|e| e.name.clone()))
+                    .collect();
+                related_entities.sort();
+                related_entities.dedup();
+                TrendingTopic {
+                    topic,
+                    mention_count: tagged.len() as i32,
+                    sentiment_score: tagged.iter().map(|a| a.sentiment_score).sum::<f64>() / tagged.len() as f64,
+                    growth_rate: (recent - earlier) / earlier.max(1.0),
+                    related_entities,
+                    key_articles: tagged.iter().take(3).map(|a| a.id.clone()).collect(),
+                }
+            })
+            .collect();
+        topics.sort_by(|a, b| b.mention_count.cmp(&a.mention_count).then(a.topic.cmp(&b.topic)));
+        topics.truncate(10);
+        Ok(topics)
     }
 }
 
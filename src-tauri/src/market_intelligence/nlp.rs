@@ -0,0 +1,576 @@
+// Offline News NLP
+// Crypto-specific lexicon sentiment with negation and intensifier handling, entity
+// linking against the exchange symbol universe, event impact classification and
+// source credibility scoring
+
+use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::models::SymbolInfo;
+use super::news_intelligence::{EntityType, MarketEntity, NewsArticle};
+
+/// Normalisation constant for the compound score, as in VADER
+const COMPOUND_ALPHA: f64 = 15.0;
+/// How far back a negator flips a sentiment word
+const NEGATION_WINDOW: usize = 3;
+const NEGATION_SCALAR: f64 = -0.74;
+const BOOSTER_INCREMENT: f64 = 0.293;
+const CAPS_INCREMENT: f64 = 0.733;
+/// Weight of sentiment before and after a "but"
+const BUT_BEFORE: f64 = 0.5;
+const BUT_AFTER: f64 = 1.5;
+
+/// Word valences on a -4..4 scale, tuned for crypto market news
+const LEXICON: &[(&str, f64)] = &[
+    ("accumulate", 1.2), ("accumulation", 1.2), ("adoption", 2.0), ("approval", 2.0), ("approve", 2.0),
+    ("approved", 2.2), ("approves", 2.0), ("ath", 2.5), ("bankrupt", -3.2), ("bankruptcy", -3.2),
+    ("bear", -1.5), ("bearish", -2.5), ("boost", 1.8), ("boosts", 1.8), ("breach", -2.8),
+    ("breakout", 1.8), ("bubble", -1.5), ("bull", 1.5), ("bullish", 2.5), ("capitulation", -2.5),
+    ("charged", -1.8), ("charges", -1.8), ("climb", 1.5), ("climbs", 1.5), ("collapse", -3.0),
+    ("collapses", -3.0), ("crash", -3.0), ("crashes", -3.0), ("decline", -1.8), ("declines", -1.8),
+    ("default", -2.5), ("delay", -1.2), ("delays", -1.2), ("delist", -2.5), ("delisted", -2.5),
+    ("delisting", -2.5), ("drained", -3.0), ("drop", -1.8), ("drops", -1.8), ("dump", -2.2),
+    ("exploit", -2.8), ("exploited", -2.8), ("fail", -2.0), ("failed", -2.0), ("failure", -2.2),
+    ("fall", -1.5), ("falls", -1.5), ("fear", -2.0), ("fomo", 1.0), ("fraud", -3.0),
+    ("fud", -2.0), ("gain", 1.5), ("gains", 1.5), ("growth", 1.6), ("hack", -3.0),
+    ("hacked", -3.0), ("hodl", 1.0), ("inflow", 1.2), ("inflows", 1.2), ("insolvent", -3.0),
+    ("investigation", -1.8), ("jump", 1.8), ("jumps", 1.8), ("lawsuit", -2.0), ("launch", 1.2),
+    ("launches", 1.2), ("liquidated", -2.2), ("liquidation", -2.0), ("liquidations", -2.0), ("listing", 1.5),
+    ("loss", -1.8), ("losses", -1.8), ("manipulation", -2.2), ("moon", 2.0), ("negative", -1.5),
+    ("optimism", 2.0), ("optimistic", 2.0), ("outflow", -1.2), ("outflows", -1.2), ("panic", -2.8),
+    ("partnership", 1.8), ("penalty", -1.8), ("plummet", -3.0), ("plummets", -3.0), ("plunge", -2.8),
+    ("plunges", -2.8), ("positive", 1.5), ("pump", 1.5), ("rally", 2.0), ("rallies", 2.0),
+    ("rebound", 1.8), ("rebounds", 1.8), ("record", 1.2), ("recovery", 1.5), ("reject", -2.0),
+    ("rejected", -2.0), ("rejects", -2.0), ("rise", 1.5), ("rises", 1.5), ("risk", -1.0),
+    ("scam", -3.0), ("selloff", -2.3), ("slump", -2.3), ("slumps", -2.3), ("soar", 2.5),
+    ("soars", 2.5), ("stolen", -3.0), ("strong", 1.2), ("success", 2.0), ("sued", -2.0),
+    ("sues", -2.0), ("surge", 2.2), ("surges", 2.2), ("theft", -3.0), ("tumble", -2.5),
+    ("tumbles", -2.5), ("uncertainty", -1.5), ("upgrade", 1.5), ("volatile", -0.8), ("warning", -1.5),
+    ("weak", -1.2), ("win", 1.8), ("wins", 1.8),
+];
+
+/// Multi-word expressions scored as a unit
+const PHRASES: &[(&[&str], f64)] = &[
+    (&["all", "time", "high"], 2.5),
+    (&["all", "time", "low"], -2.5),
+    (&["rug", "pull"], -3.2),
+    (&["sell", "off"], -2.3),
+    (&["short", "squeeze"], 1.5),
+    (&["to", "the", "moon"], 2.0),
+    (&["death", "cross"], -2.0),
+    (&["golden", "cross"], 2.0),
+];
+
+const NEGATORS: &[&str] = &[
+    "not", "no", "never", "without", "hardly", "neither", "nor", "cannot", "isn't", "aren't", "wasn't",
+    "weren't", "don't", "doesn't", "didn't", "won't", "wouldn't", "shouldn't", "can't", "couldn't", "fails", "failed",
+];
+const BOOSTERS: &[&str] = &[
+    "very", "extremely", "massive", "massively", "huge", "hugely", "highly", "incredibly", "significantly",
+    "sharply", "strongly", "heavily", "major", "biggest",
+];
+const DAMPENERS: &[&str] = &["slightly", "somewhat", "barely", "marginally", "modestly", "partly", "minor"];
+
+/// Well-known names for base assets; only those in the symbol universe are linked
+const ASSET_NAMES: &[(&str, &str)] = &[
+    ("bitcoin", "BTC"), ("ethereum", "ETH"), ("ether", "ETH"), ("solana", "SOL"), ("ripple", "XRP"),
+    ("cardano", "ADA"), ("dogecoin", "DOGE"), ("polkadot", "DOT"), ("chainlink", "LINK"), ("litecoin", "LTC"),
+    ("avalanche", "AVAX"), ("polygon", "POL"), ("tron", "TRX"), ("bnb", "BNB"), ("shiba inu", "SHIB"),
+    ("uniswap", "UNI"), ("tether", "USDT"), ("usd coin", "USDC"), ("toncoin", "TON"), ("near protocol", "NEAR"),
+    ("aptos", "APT"), ("arbitrum", "ARB"), ("optimism", "OP"), ("sui", "SUI"), ("pepe", "PEPE"),
+    ("stellar", "XLM"), ("cosmos", "ATOM"), ("filecoin", "FIL"), ("bitcoin cash", "BCH"), ("monero", "XMR"),
+];
+
+/// Exchanges and venues recognised as institutions
+const EXCHANGES: &[&str] = &[
+    "binance", "coinbase", "kraken", "okx", "bybit", "bitfinex", "kucoin", "gemini", "bitstamp", "huobi",
+    "htx", "ftx", "crypto.com", "gate.io", "mexc", "upbit", "bithumb",
+];
+
+/// Market-moving event types recognised in news
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ImpactCategory {
+    Hack,
+    Listing,
+    Delisting,
+    Regulation,
+    Etf,
+}
+
+impl ImpactCategory {
+    /// Typical market impact of the event type, 0..1
+    pub fn severity(&self) -> f64 {
+        match self {
+            ImpactCategory::Hack => 0.9,
+            ImpactCategory::Delisting => 0.8,
+            ImpactCategory::Etf => 0.8,
+            ImpactCategory::Regulation => 0.7,
+            ImpactCategory::Listing => 0.6,
+        }
+    }
+
+    fn keywords(&self) -> &'static [&'static str] {
+        match self {
+            ImpactCategory::Hack => &["hack", "hacked", "hacker", "exploit", "exploited", "breach", "stolen", "drained", "attacker", "security incident"],
+            ImpactCategory::Listing => &["will list", "to list", "lists", "listing", "listed on", "new trading pair", "launchpool"],
+            ImpactCategory::Delisting => &["delist", "delists", "delisting", "delisted", "cease trading", "remove trading pairs"],
+            ImpactCategory::Regulation => &["sec", "cftc", "regulator", "regulators", "regulation", "regulatory", "lawsuit", "sued", "ban", "banned", "enforcement", "court", "legislation", "license", "mica"],
+            ImpactCategory::Etf => &["etf", "etfs", "exchange-traded fund", "exchange traded fund"],
+        }
+    }
+}
+
+/// Lexicon-based sentiment model
+#[derive(Debug, Clone)]
+pub struct SentimentModel {
+    lexicon: HashMap<&'static str, f64>,
+}
+
+impl Default for SentimentModel {
+    fn default() -> Self {
+        Self { lexicon: LEXICON.iter().copied().collect() }
+    }
+}
+
+impl SentimentModel {
+    /// Compound sentiment of a text in [-1, 1]
+    pub fn score(&self, text: &str) -> f64 {
+        let words = tokenize(text);
+        let all_caps_text = text.chars().filter(|c| c.is_alphabetic()).all(|c| c.is_uppercase());
+        let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+        let mut valences: Vec<f64> = Vec::new();
+        let mut but_at: Option<usize> = None;
+
+        let mut i = 0;
+        while i < lower.len() {
+            if lower[i] == "but" {
+                but_at = Some(valences.len());
+            }
+
+            let phrase = PHRASES.iter()
+                .filter(|(phrase, _)| lower[i..].starts_with(&phrase.iter().map(|w| w.to_string()).collect::<Vec<_>>()))
+                .max_by_key(|(phrase, _)| phrase.len());
+            let (mut valence, length) = match phrase {
+                Some((phrase, valence)) => (*valence, phrase.len()),
+                None => match self.lexicon.get(lower[i].as_str()) {
+                    Some(valence) => (*valence, 1),
+                    None => {
+                        i += 1;
+                        continue;
+                    }
+                },
+            };
+
+            // Shouting a sentiment word strengthens it
+            if !all_caps_text && words[i].len() > 1 && words[i].chars().all(|c| c.is_uppercase()) {
+                valence += CAPS_INCREMENT * valence.signum();
+            }
+            let window = &lower[i.saturating_sub(NEGATION_WINDOW)..i];
+            // Modifiers further from the word count for less
+            for (distance, modifier) in window.iter().rev().enumerate() {
+                let increment = BOOSTER_INCREMENT * (1.0 - 0.05 * distance as f64) * valence.signum();
+                if BOOSTERS.contains(&modifier.as_str()) {
+                    valence += increment;
+                } else if DAMPENERS.contains(&modifier.as_str()) {
+                    valence -= increment;
+                }
+            }
+            if window.iter().any(|w| NEGATORS.contains(&w.as_str())) {
+                valence *= NEGATION_SCALAR;
+            }
+
+            valences.push(valence);
+            i += length;
+        }
+
+        if let Some(split) = but_at {
+            for (index, valence) in valences.iter_mut().enumerate() {
+                *valence *= if index < split { BUT_BEFORE } else { BUT_AFTER };
+            }
+        }
+
+        let sum: f64 = valences.iter().sum();
+        sum / (sum * sum + COMPOUND_ALPHA).sqrt()
+    }
+}
+
+/// Links coin names, tickers and exchanges in text to the symbol universe
+#[derive(Debug, Clone)]
+pub struct EntityExtractor {
+    /// Lowercase name -> base asset
+    names: Vec<(String, String)>,
+    /// Base asset -> tradable symbols
+    symbols: HashMap<String, Vec<String>>,
+}
+
+impl Default for EntityExtractor {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl EntityExtractor {
+    /// Extractor for the given universe; with no symbols, every built-in asset name is linked
+    pub fn new(universe: &[SymbolInfo]) -> Self {
+        let mut symbols: HashMap<String, Vec<String>> = HashMap::new();
+        for info in universe {
+            symbols.entry(info.base_asset.to_uppercase()).or_default().push(info.symbol.clone());
+        }
+        if symbols.is_empty() {
+            for (_, base) in ASSET_NAMES {
+                symbols.entry(base.to_string()).or_default();
+            }
+        }
+        let mut names: Vec<(String, String)> = ASSET_NAMES.iter()
+            .filter(|(_, base)| symbols.contains_key(*base))
+            .map(|(name, base)| (name.to_string(), base.to_string()))
+            .collect();
+        // Longest names first so "bitcoin cash" wins over "bitcoin"
+        names.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+        Self { names, symbols }
+    }
+
+    /// Tradable symbols for a base asset
+    pub fn symbols_for(&self, base_asset: &str) -> &[String] {
+        self.symbols.get(&base_asset.to_uppercase()).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Entities mentioned in `text`; sentiment is the mean over the sentences that mention them
+    pub fn extract(&self, text: &str, model: &SentimentModel) -> Vec<MarketEntity> {
+        struct Found {
+            entity_type: EntityType,
+            name: String,
+            ticker: Option<String>,
+            confidence: f64,
+            sentences: HashSet<usize>,
+            mentions: i32,
+        }
+        let mut found: Vec<Found> = Vec::new();
+        let mut add = |entity_type: EntityType, name: String, ticker: Option<String>, confidence: f64, sentence: usize| {
+            match found.iter_mut().find(|f| f.name == name) {
+                Some(existing) => {
+                    existing.mentions += 1;
+                    existing.confidence = existing.confidence.max(confidence);
+                    existing.sentences.insert(sentence);
+                }
+                None => found.push(Found { entity_type, name, ticker, confidence, sentences: HashSet::from([sentence]), mentions: 1 }),
+            }
+        };
+
+        let sentences = split_sentences(text);
+        for (index, sentence) in sentences.iter().enumerate() {
+            let words = tokenize(sentence);
+            let lower: Vec<String> = words.iter().map(|w| w.to_lowercase()).collect();
+            let mut claimed = vec![false; words.len()];
+
+            for (name, base) in &self.names {
+                let parts: Vec<String> = name.split(' ').map(str::to_string).collect();
+                for start in 0..lower.len() {
+                    if lower[start..].starts_with(&parts) && !claimed[start] {
+                        claimed[start..start + parts.len()].iter_mut().for_each(|c| *c = true);
+                        add(EntityType::Cryptocurrency, base.clone(), Some(base.clone()), 0.9, index);
+                    }
+                }
+            }
+
+            // Tickers must be written in capitals; short ones need a cashtag
+            let raw: Vec<&str> = sentence.split_whitespace().collect();
+            for token in raw {
+                let cashtag = token.starts_with('$');
+                let ticker: String = token.trim_start_matches('$').chars().take_while(|c| c.is_ascii_alphanumeric()).collect();
+                if ticker.is_empty() || ticker.chars().any(|c| c.is_ascii_lowercase()) || !self.symbols.contains_key(&ticker) {
+                    continue;
+                }
+                if ticker.len() < 3 && !cashtag {
+                    continue;
+                }
+                add(EntityType::Cryptocurrency, ticker.clone(), Some(ticker), if cashtag { 0.95 } else { 0.8 }, index);
+            }
+
+            let lowered = sentence.to_lowercase();
+            for exchange in EXCHANGES {
+                let hits = lower.iter().filter(|w| w.as_str() == *exchange).count()
+                    + usize::from(exchange.contains('.') && lowered.contains(exchange));
+                for _ in 0..hits {
+                    add(EntityType::Institution, exchange.to_string(), None, 0.85, index);
+                }
+            }
+        }
+
+        found.into_iter()
+            .map(|f| {
+                let sentiment = f.sentences.iter().map(|&i| model.score(&sentences[i])).sum::<f64>() / f.sentences.len() as f64;
+                MarketEntity {
+                    entity_type: f.entity_type,
+                    name: f.name,
+                    ticker: f.ticker,
+                    confidence: f.confidence,
+                    sentiment,
+                    mentions: f.mentions,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Detects hacks, listings, delistings, regulation and ETF news
+#[derive(Debug, Clone, Default)]
+pub struct ImpactClassifier;
+
+impl ImpactClassifier {
+    pub fn classify(&self, text: &str) -> Vec<ImpactCategory> {
+        let lower = format!(" {} ", tokenize(text).join(" ").to_lowercase());
+        let mentions = |category: ImpactCategory| category.keywords().iter()
+            .any(|keyword| lower.contains(&format!(" {} ", tokenize(keyword).join(" "))));
+
+        let mut categories: Vec<ImpactCategory> = [
+            ImpactCategory::Hack,
+            ImpactCategory::Delisting,
+            ImpactCategory::Etf,
+            ImpactCategory::Regulation,
+            ImpactCategory::Listing,
+        ].into_iter().filter(|c| mentions(*c)).collect();
+        // "Delisting" is not also a listing
+        if categories.contains(&ImpactCategory::Delisting) {
+            categories.retain(|c| *c != ImpactCategory::Listing);
+        }
+        categories
+    }
+
+    /// Impact on a 0..1 scale: the most severe event, or a small baseline for routine news
+    pub fn impact_score(&self, categories: &[ImpactCategory], sentiment: f64) -> f64 {
+        let severity = categories.iter().map(|c| c.severity()).fold(0.0, f64::max);
+        (severity.max(0.2) + 0.1 * sentiment.abs()).min(1.0)
+    }
+}
+
+/// Adjusts a source's credibility for the quality signals of one article
+#[derive(Debug, Clone, Default)]
+pub struct CredibilityScorer;
+
+impl CredibilityScorer {
+    pub fn score(&self, source_credibility: f64, article: &NewsArticle) -> f64 {
+        let mut score = source_credibility;
+        let title_letters: Vec<char> = article.title.chars().filter(|c| c.is_alphabetic()).collect();
+        let caps_ratio = title_letters.iter().filter(|c| c.is_uppercase()).count() as f64 / title_letters.len().max(1) as f64;
+        let lower_title = article.title.to_lowercase();
+        if caps_ratio > 0.6 || article.title.matches('!').count() > 1 {
+            score -= 0.2;
+        }
+        if ["you won't believe", "shocking", "guaranteed", "100x", "to the moon"].iter().any(|bait| lower_title.contains(bait)) {
+            score -= 0.2;
+        }
+        if article.author.is_some() {
+            score += 0.05;
+        }
+        if article.content.len() + article.summary.len() < 80 {
+            score -= 0.1;
+        }
+        score.clamp(0.0, 1.0)
+    }
+}
+
+/// Everything the NLP pipeline derives from one text
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextAnalysis {
+    pub sentiment: f64,
+    pub entities: Vec<MarketEntity>,
+    pub impact_categories: Vec<ImpactCategory>,
+    pub impact_score: f64,
+    pub relevance_score: f64,
+}
+
+/// Offline NLP pipeline for news
+#[derive(Debug, Clone, Default)]
+pub struct NLPProcessor {
+    sentiment_model: SentimentModel,
+    entity_extractor: EntityExtractor,
+    impact_classifier: ImpactClassifier,
+    credibility_scorer: CredibilityScorer,
+}
+
+impl NLPProcessor {
+    /// Processor linking entities to the given symbol universe
+    pub fn new(universe: &[SymbolInfo]) -> Self {
+        Self { entity_extractor: EntityExtractor::new(universe), ..Self::default() }
+    }
+
+    pub fn entity_extractor(&self) -> &EntityExtractor {
+        &self.entity_extractor
+    }
+
+    pub fn sentiment(&self, text: &str) -> f64 {
+        self.sentiment_model.score(text)
+    }
+
+    /// Headline sentiment counts double since it carries the story's framing
+    pub fn analyze(&self, title: &str, body: &str) -> TextAnalysis {
+        let title_sentiment = self.sentiment_model.score(title);
+        let sentiment = if body.trim().is_empty() {
+            title_sentiment
+        } else {
+            (2.0 * title_sentiment + self.sentiment_model.score(body)) / 3.0
+        };
+        let text = format!("{}. {}", title, body);
+        let entities = self.entity_extractor.extract(&text, &self.sentiment_model);
+        let impact_categories = self.impact_classifier.classify(&text);
+        let impact_score = self.impact_classifier.impact_score(&impact_categories, sentiment);
+
+        // Entities named in the headline make the story about them
+        let title_entities = self.entity_extractor.extract(title, &self.sentiment_model);
+        let relevance_score = match (title_entities.is_empty(), entities.is_empty()) {
+            (false, _) => 0.9,
+            (true, false) => (0.5 + 0.05 * entities.iter().map(|e| e.mentions).sum::<i32>() as f64).min(0.85),
+            (true, true) => 0.1,
+        };
+
+        TextAnalysis { sentiment, entities, impact_categories, impact_score, relevance_score }
+    }
+
+    /// Fill in an article's sentiment, entities, impact and relevance, weighting impact by
+    /// how credible the article looks
+    pub fn annotate(&self, article: &mut NewsArticle, source_credibility: f64) {
+        let body = if article.content.is_empty() { &article.summary } else { &article.content };
+        let analysis = self.analyze(&article.title, &strip_markup(body));
+        let credibility = self.credibility_scorer.score(source_credibility, article);
+        article.sentiment_score = analysis.sentiment;
+        article.relevance_score = analysis.relevance_score;
+        article.impact_score = analysis.impact_score * (0.5 + 0.5 * credibility);
+        article.market_entities = analysis.entities;
+        article.impact_categories = analysis.impact_categories;
+    }
+}
+
+/// Words, keeping in-word apostrophes so contractions stay whole
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '\u{2019}'))
+        .map(|w| w.trim_matches(|c| c == '\'' || c == '\u{2019}').replace('\u{2019}', "'"))
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn split_sentences(text: &str) -> Vec<String> {
+    text.split(['.', '!', '?', '\n'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Drop HTML tags that feeds embed in descriptions
+fn strip_markup(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                plain.push(' ');
+            }
+            _ if !in_tag => plain.push(c),
+            _ => {}
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbol(base: &str) -> SymbolInfo {
+        SymbolInfo {
+            symbol: format!("{}USDT", base),
+            base_asset: base.to_string(),
+            quote_asset: "USDT".to_string(),
+            status: "TRADING".to_string(),
+            price: None,
+            price_change_percent: None,
+            volume: None,
+            high: None,
+            low: None,
+            is_spot_trading_allowed: true,
+            is_margin_trading_allowed: false,
+            filters: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_lexicon_handles_negation_intensifiers_and_phrases() {
+        let model = SentimentModel::default();
+        let bullish = model.score("Bitcoin rallies to a new all-time high");
+        assert!(bullish > 0.5, "{}", bullish);
+        assert!(model.score("Bitcoin is not bullish") < 0.0);
+        assert!(model.score("Exchange hacked, funds stolen") < -0.6);
+        assert!(model.score("Prices rise") < model.score("Prices very sharply rise"));
+        assert!(model.score("Prices rise") > model.score("Prices modestly rise"));
+        assert!(model.score("Investors fear a rug pull") < -0.6);
+        // The clause after "but" dominates
+        assert!(model.score("Markets slump but recovery is strong") > 0.0);
+        assert_eq!(model.score("The committee met on Tuesday"), 0.0);
+    }
+
+    #[test]
+    fn test_entities_link_to_symbol_universe() {
+        let universe = vec![symbol("BTC"), symbol("ETH"), symbol("OP"), symbol("BCH")];
+        let extractor = EntityExtractor::new(&universe);
+        let model = SentimentModel::default();
+        let entities = extractor.extract(
+            "Bitcoin surges as BTC ETFs see inflows. Ether slumps on Binance. \
+             Bitcoin Cash flat. OP is up, $OP rallies. SOL not in universe.",
+            &model,
+        );
+        let find = |name: &str| entities.iter().find(|e| e.name == name);
+
+        let btc = find("BTC").unwrap();
+        assert_eq!(btc.mentions, 2);
+        assert!(btc.sentiment > 0.0);
+        assert_eq!(find("ETH").unwrap().ticker.as_deref(), Some("ETH"));
+        assert!(find("ETH").unwrap().sentiment < 0.0);
+        assert_eq!(find("BCH").unwrap().mentions, 1);
+        assert_eq!(find("OP").unwrap().mentions, 1);
+        assert!(matches!(find("binance").unwrap().entity_type, EntityType::Institution));
+        assert!(find("SOL").is_none());
+        assert_eq!(extractor.symbols_for("btc"), &["BTCUSDT".to_string()]);
+    }
+
+    #[test]
+    fn test_impact_classification_and_annotation() {
+        let classifier = ImpactClassifier;
+        assert_eq!(classifier.classify("Exchange hacked, $40M drained from hot wallet"), vec![ImpactCategory::Hack]);
+        assert_eq!(classifier.classify("Binance will delist three tokens"), vec![ImpactCategory::Delisting]);
+        assert_eq!(classifier.classify("Coinbase lists new token"), vec![ImpactCategory::Listing]);
+        assert_eq!(
+            classifier.classify("SEC approves spot Ether ETF"),
+            vec![ImpactCategory::Etf, ImpactCategory::Regulation]
+        );
+        assert!(classifier.classify("Analysts discuss market structure").is_empty());
+
+        let processor = NLPProcessor::new(&[symbol("ETH")]);
+        let mut article = NewsArticle {
+            id: "1".to_string(),
+            title: "SEC approves spot Ethereum ETF".to_string(),
+            content: "<p>The approval is a major win for ETH holders, and inflows are expected.</p>".to_string(),
+            summary: String::new(),
+            source: "fixture".to_string(),
+            author: Some("Reporter".to_string()),
+            published_at: chrono::Utc::now(),
+            url: "https://news.example/eth-etf".to_string(),
+            category: "crypto".to_string(),
+            tags: Vec::new(),
+            sentiment_score: 0.0,
+            relevance_score: 0.0,
+            impact_score: 0.0,
+            market_entities: Vec::new(),
+            impact_categories: Vec::new(),
+        };
+        processor.annotate(&mut article, 0.9);
+        assert!(article.sentiment_score > 0.3);
+        assert_eq!(article.relevance_score, 0.9);
+        assert!(article.impact_score > 0.7);
+        assert_eq!(article.market_entities[0].name, "ETH");
+        assert_eq!(article.market_entities[0].mentions, 2);
+        assert!(article.impact_categories.contains(&ImpactCategory::Etf));
+    }
+}
@@ -0,0 +1,1083 @@
+// Predictive Analytics Engine
+// Multi-horizon return and volatility forecasts from stored klines: damped-trend
+// exponential smoothing, autoregressive and ridge feature models for returns and
+// GARCH(1,1) for volatility, with each return model scored by rolling-origin
+// evaluation before its forecasts are trusted
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::{TradingError, TradingResult};
+use crate::kline_store::KlineStore;
+use crate::logging::LogCategory;
+use crate::{log_info, log_warning};
+use super::{
+    ConfidenceInterval, GarchForecast, HorizonVolatility, MarketContext, MarketRegime, MarketRegimeForecast,
+    PredictionConfidence, PredictiveInsights, PricePrediction, TrendDirection, TrendPrediction,
+    UncertaintyFactor, VolatilityForecast, VolatilityRegime,
+};
+use super::fear_greed::BENCHMARK_SYMBOL;
+
+/// Klines the forecasts are built from
+pub const FORECAST_INTERVAL: &str = "1h";
+/// Horizons forecast when none are requested
+pub const DEFAULT_TIMEFRAMES: [&str; 3] = ["1h", "4h", "1d"];
+/// Nominal coverage of the reported prediction intervals
+pub const INTERVAL_LEVEL: f64 = 0.9;
+
+const Z_90: f64 = 1.6449;
+const Z_50: f64 = 0.6745;
+/// Returns needed before any forecast is made
+const MIN_HISTORY: usize = 150;
+const MAX_HISTORY: usize = 2000;
+/// Each model is fit on at most this many of the latest returns
+const MAX_TRAINING: usize = 500;
+/// First evaluation origin; earlier fits are too noisy to judge
+const MIN_TRAINING: usize = 100;
+const MAX_EVALUATION_ORIGINS: usize = 60;
+const MIN_EVALUATION_SAMPLES: usize = 20;
+/// Allowed gap between nominal and observed interval coverage
+const MAX_CALIBRATION_ERROR: f64 = 0.15;
+/// Window for realized volatility and recent returns
+const VOLATILITY_WINDOW: usize = 20;
+
+/// Cumulative log return expected over a horizon, with its standard deviation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReturnForecast {
+    pub mean: f64,
+    pub std_dev: f64,
+}
+
+impl ReturnForecast {
+    /// Probability that the realized return is positive
+    pub fn probability_up(&self) -> f64 {
+        if self.std_dev <= 0.0 {
+            return if self.mean > 0.0 { 1.0 } else if self.mean < 0.0 { 0.0 } else { 0.5 };
+        }
+        normal_cdf(self.mean / self.std_dev)
+    }
+
+    pub fn interval(&self, z: f64) -> (f64, f64) {
+        (self.mean - z * self.std_dev, self.mean + z * self.std_dev)
+    }
+}
+
+/// A model of future log returns
+pub trait Forecaster: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Cumulative log return over the next `horizon` bars given past log returns, oldest first
+    fn forecast(&self, returns: &[f64], horizon: usize) -> Option<ReturnForecast>;
+}
+
+/// Holt's damped additive trend (ETS(A,Ad,N)) on log prices, parameters chosen by one-step SSE
+#[derive(Debug, Clone, Default)]
+pub struct DampedTrendEts;
+
+struct EtsFit {
+    alpha: f64,
+    beta: f64,
+    phi: f64,
+    level: f64,
+    trend: f64,
+    sigma2: f64,
+}
+
+impl DampedTrendEts {
+    const ALPHAS: [f64; 7] = [0.05, 0.1, 0.2, 0.3, 0.5, 0.7, 0.9];
+    const BETAS: [f64; 5] = [0.0, 0.01, 0.05, 0.1, 0.2];
+    const PHIS: [f64; 3] = [0.8, 0.9, 0.98];
+
+    fn fit(prices: &[f64]) -> Option<EtsFit> {
+        if prices.len() < 10 {
+            return None;
+        }
+        let mut best: Option<(f64, EtsFit)> = None;
+        for alpha in Self::ALPHAS {
+            for beta in Self::BETAS.into_iter().filter(|beta| *beta <= alpha) {
+                for phi in Self::PHIS {
+                    let mut level = prices[0];
+                    let mut trend = prices[1] - prices[0];
+                    let mut sse = 0.0;
+                    for &y in &prices[1..] {
+                        let forecast = level + phi * trend;
+                        let error = y - forecast;
+                        sse += error * error;
+                        level = forecast + alpha * error;
+                        trend = phi * trend + beta * error;
+                    }
+                    if best.as_ref().is_none_or(|(best_sse, _)| sse < *best_sse) {
+                        let sigma2 = sse / (prices.len() - 1) as f64;
+                        best = Some((sse, EtsFit { alpha, beta, phi, level, trend, sigma2 }));
+                    }
+                }
+            }
+        }
+        best.map(|(_, fit)| fit)
+    }
+}
+
+impl Forecaster for DampedTrendEts {
+    fn name(&self) -> &'static str {
+        "ets"
+    }
+
+    fn forecast(&self, returns: &[f64], horizon: usize) -> Option<ReturnForecast> {
+        let prices = log_prices(returns);
+        let fit = Self::fit(&prices)?;
+        let last = *prices.last()?;
+
+        let damped_steps: f64 = (1..=horizon).map(|i| fit.phi.powi(i as i32)).sum();
+        let mean = fit.level + damped_steps * fit.trend - last;
+        // Forecast variance of ETS(A,Ad,N), Hyndman et al. (2008) class 1
+        let spread: f64 = (1..horizon)
+            .map(|j| {
+                let c = fit.alpha + fit.beta * fit.phi * (1.0 - fit.phi.powi(j as i32)) / (1.0 - fit.phi);
+                c * c
+            })
+            .sum();
+        Some(ReturnForecast { mean, std_dev: (fit.sigma2 * (1.0 + spread)).sqrt() })
+    }
+}
+
+/// AR(p) on log returns, i.e. ARIMA(p,1,0) on log prices, with the order chosen by AIC
+#[derive(Debug, Clone)]
+pub struct AutoRegressive {
+    pub max_order: usize,
+}
+
+impl Default for AutoRegressive {
+    fn default() -> Self {
+        Self { max_order: 5 }
+    }
+}
+
+impl AutoRegressive {
+    /// Intercept followed by lag coefficients, and the residual variance
+    fn fit(&self, returns: &[f64]) -> Option<(Vec<f64>, f64)> {
+        let start = self.max_order;
+        if returns.len() < start + 30 {
+            return None;
+        }
+        let samples = (returns.len() - start) as f64;
+        let mut best: Option<(f64, Vec<f64>, f64)> = None;
+        for order in 1..=self.max_order {
+            let rows: Vec<Vec<f64>> = (start..returns.len())
+                .map(|t| std::iter::once(1.0).chain((1..=order).map(|lag| returns[t - lag])).collect())
+                .collect();
+            let targets = &returns[start..];
+            let Some(coefficients) = least_squares(&rows, targets, 0.0) else {
+                continue;
+            };
+            let sse: f64 = rows.iter().zip(targets).map(|(row, y)| (y - dot(row, &coefficients)).powi(2)).sum();
+            let sigma2 = sse / samples;
+            if sigma2 <= 0.0 {
+                continue;
+            }
+            let aic = samples * sigma2.ln() + 2.0 * (order + 1) as f64;
+            if best.as_ref().is_none_or(|(best_aic, _, _)| aic < *best_aic) {
+                best = Some((aic, coefficients, sigma2));
+            }
+        }
+        best.map(|(_, coefficients, sigma2)| (coefficients, sigma2))
+    }
+}
+
+impl Forecaster for AutoRegressive {
+    fn name(&self) -> &'static str {
+        "ar"
+    }
+
+    fn forecast(&self, returns: &[f64], horizon: usize) -> Option<ReturnForecast> {
+        let (coefficients, sigma2) = self.fit(returns)?;
+        let lags = &coefficients[1..];
+
+        let mut path: Vec<f64> = returns[returns.len() - lags.len()..].to_vec();
+        for _ in 0..horizon {
+            let next = coefficients[0] + lags.iter().enumerate().map(|(i, phi)| phi * path[path.len() - 1 - i]).sum::<f64>();
+            path.push(next);
+        }
+        let mean: f64 = path[lags.len()..].iter().sum();
+
+        // Psi weights of the MA(inf) form give the variance of the summed errors
+        let mut psi = vec![1.0];
+        for j in 1..horizon {
+            psi.push((1..=j.min(lags.len())).map(|i| lags[i - 1] * psi[j - i]).sum());
+        }
+        let variance: f64 = (1..=horizon)
+            .map(|k| psi[..=horizon - k].iter().sum::<f64>().powi(2))
+            .sum::<f64>() * sigma2;
+        Some(ReturnForecast { mean, std_dev: variance.sqrt() })
+    }
+}
+
+/// Ridge regression of the horizon return on engineered momentum, volatility and
+/// breadth features, fit directly for each horizon
+#[derive(Debug, Clone)]
+pub struct FeatureRegression {
+    pub lambda: f64,
+}
+
+impl Default for FeatureRegression {
+    fn default() -> Self {
+        Self { lambda: 1.0 }
+    }
+}
+
+impl FeatureRegression {
+    /// Returns needed to compute one feature row
+    const LOOKBACK: usize = 20;
+    const MIN_SAMPLES: usize = 50;
+
+    /// Features known at the close of bar `t`
+    fn features(returns: &[f64], t: usize) -> Vec<f64> {
+        let last_5 = &returns[t + 1 - 5..=t];
+        let last_20 = &returns[t + 1 - Self::LOOKBACK..=t];
+        let up_share = last_20.iter().filter(|r| **r > 0.0).count() as f64 / last_20.len() as f64;
+        vec![returns[t], mean(last_5), mean(last_20), std_dev(last_20), up_share - 0.5]
+    }
+}
+
+impl Forecaster for FeatureRegression {
+    fn name(&self) -> &'static str {
+        "ridge"
+    }
+
+    fn forecast(&self, returns: &[f64], horizon: usize) -> Option<ReturnForecast> {
+        if returns.len() < Self::LOOKBACK + horizon + Self::MIN_SAMPLES {
+            return None;
+        }
+        let origins = Self::LOOKBACK - 1..returns.len() - horizon;
+        let raw: Vec<Vec<f64>> = origins.clone().map(|t| Self::features(returns, t)).collect();
+        let targets: Vec<f64> = origins.map(|t| returns[t + 1..=t + horizon].iter().sum()).collect();
+
+        // Standardize so the penalty treats every feature alike
+        let columns = raw[0].len();
+        let scale: Vec<(f64, f64)> = (0..columns)
+            .map(|c| {
+                let column: Vec<f64> = raw.iter().map(|row| row[c]).collect();
+                (mean(&column), std_dev(&column).max(1e-12))
+            })
+            .collect();
+        let standardize = |row: &[f64]| -> Vec<f64> {
+            std::iter::once(1.0).chain(row.iter().zip(&scale).map(|(x, (m, s))| (x - m) / s)).collect()
+        };
+        let rows: Vec<Vec<f64>> = raw.iter().map(|row| standardize(row)).collect();
+        let coefficients = least_squares(&rows, &targets, self.lambda)?;
+
+        let sse: f64 = rows.iter().zip(&targets).map(|(row, y)| (y - dot(row, &coefficients)).powi(2)).sum();
+        let dof = (rows.len() - coefficients.len()).max(1) as f64;
+        let latest = standardize(&Self::features(returns, returns.len() - 1));
+        Some(ReturnForecast { mean: dot(&latest, &coefficients), std_dev: (sse / dof).sqrt() })
+    }
+}
+
+/// GARCH(1,1) with variance targeting, fit by grid-search maximum likelihood
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Garch11 {
+    pub omega: f64,
+    pub alpha: f64,
+    pub beta: f64,
+    pub long_run_variance: f64,
+    /// Conditional variance of the next bar
+    pub next_variance: f64,
+}
+
+impl Garch11 {
+    const ALPHAS: [f64; 8] = [0.02, 0.05, 0.08, 0.12, 0.16, 0.2, 0.25, 0.3];
+    const BETAS: [f64; 8] = [0.5, 0.6, 0.7, 0.8, 0.85, 0.9, 0.93, 0.96];
+
+    pub fn fit(returns: &[f64]) -> Option<Self> {
+        if returns.len() < 30 {
+            return None;
+        }
+        let center = mean(returns);
+        let residuals: Vec<f64> = returns.iter().map(|r| r - center).collect();
+        let variance = residuals.iter().map(|e| e * e).sum::<f64>() / residuals.len() as f64;
+        if variance <= 0.0 {
+            return None;
+        }
+
+        let mut best: Option<(f64, Garch11)> = None;
+        for alpha in Self::ALPHAS {
+            for beta in Self::BETAS.into_iter().filter(|beta| alpha + beta < 0.995) {
+                let omega = variance * (1.0 - alpha - beta);
+                let mut conditional = variance;
+                let mut log_likelihood = 0.0;
+                for e in &residuals {
+                    log_likelihood -= 0.5 * (conditional.ln() + e * e / conditional);
+                    conditional = omega + alpha * e * e + beta * conditional;
+                }
+                if best.as_ref().is_none_or(|(best_ll, _)| log_likelihood > *best_ll) {
+                    let model = Garch11 { omega, alpha, beta, long_run_variance: variance, next_variance: conditional };
+                    best = Some((log_likelihood, model));
+                }
+            }
+        }
+        best.map(|(_, model)| model)
+    }
+
+    pub fn persistence(&self) -> f64 {
+        self.alpha + self.beta
+    }
+
+    /// Expected variance of each of the next `horizon` bars
+    pub fn variance_path(&self, horizon: usize) -> Vec<f64> {
+        (0..horizon)
+            .map(|k| self.long_run_variance + self.persistence().powi(k as i32) * (self.next_variance - self.long_run_variance))
+            .collect()
+    }
+
+    /// Expected variance of the cumulative return over `horizon` bars
+    pub fn horizon_variance(&self, horizon: usize) -> f64 {
+        self.variance_path(horizon).iter().sum()
+    }
+}
+
+/// How one forecaster fared at one horizon in rolling-origin evaluation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastEvaluation {
+    pub model: String,
+    pub timeframe: String,
+    pub horizon_bars: usize,
+    pub samples: usize,
+    pub mae: f64,
+    pub rmse: f64,
+    /// MAE of the random walk, which always forecasts no change
+    pub naive_mae: f64,
+    /// 1 - mae / naive_mae; positive when the model beats the random walk
+    pub skill: f64,
+    pub directional_accuracy: f64,
+    /// Share of outcomes inside the 50% and 90% prediction intervals
+    pub coverage_50: f64,
+    pub coverage_90: f64,
+    /// Mean gap between nominal and observed coverage
+    pub calibration_error: f64,
+    pub trusted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvaluationReport {
+    pub symbol: String,
+    pub interval: String,
+    pub generated_at: DateTime<Utc>,
+    /// Returns available when the report was built
+    pub history_bars: usize,
+    pub evaluations: Vec<ForecastEvaluation>,
+}
+
+impl EvaluationReport {
+    pub fn get(&self, model: &str, horizon_bars: usize) -> Option<&ForecastEvaluation> {
+        self.evaluations.iter().find(|e| e.model == model && e.horizon_bars == horizon_bars)
+    }
+
+    pub fn trusted(&self, horizon_bars: usize) -> Vec<&ForecastEvaluation> {
+        self.evaluations.iter().filter(|e| e.horizon_bars == horizon_bars && e.trusted).collect()
+    }
+
+    pub fn timeframes(&self) -> Vec<String> {
+        let mut timeframes: Vec<String> = Vec::new();
+        for evaluation in &self.evaluations {
+            if !timeframes.contains(&evaluation.timeframe) {
+                timeframes.push(evaluation.timeframe.clone());
+            }
+        }
+        timeframes
+    }
+}
+
+/// Refit the forecaster at successive origins on the data before each, and score its
+/// forecasts of the following `horizon` bars against what happened
+pub fn evaluate_forecaster(forecaster: &dyn Forecaster, returns: &[f64], horizon: usize, timeframe: &str) -> ForecastEvaluation {
+    let last_origin = returns.len().saturating_sub(horizon);
+    let origins: Vec<usize> = if last_origin >= MIN_TRAINING {
+        let count = last_origin - MIN_TRAINING + 1;
+        let step = count.div_ceil(MAX_EVALUATION_ORIGINS);
+        (MIN_TRAINING..=last_origin).rev().step_by(step).collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut errors = Vec::new();
+    let mut naive_errors = Vec::new();
+    let mut hits = 0.0;
+    let mut inside_50 = 0;
+    let mut inside_90 = 0;
+    for origin in origins {
+        let training = &returns[origin.saturating_sub(MAX_TRAINING)..origin];
+        let Some(forecast) = forecaster.forecast(training, horizon) else {
+            continue;
+        };
+        let actual: f64 = returns[origin..origin + horizon].iter().sum();
+        errors.push(actual - forecast.mean);
+        naive_errors.push(actual);
+        hits += match (forecast.mean * actual).partial_cmp(&0.0) {
+            Some(std::cmp::Ordering::Greater) => 1.0,
+            Some(std::cmp::Ordering::Less) => 0.0,
+            _ => 0.5,
+        };
+        let within = |z: f64| {
+            let (lower, upper) = forecast.interval(z);
+            actual >= lower && actual <= upper
+        };
+        inside_50 += usize::from(within(Z_50));
+        inside_90 += usize::from(within(Z_90));
+    }
+
+    let samples = errors.len();
+    let n = samples.max(1) as f64;
+    let mae = errors.iter().map(|e| e.abs()).sum::<f64>() / n;
+    let naive_mae = naive_errors.iter().map(|e| e.abs()).sum::<f64>() / n;
+    let skill = if naive_mae > 0.0 { 1.0 - mae / naive_mae } else { 0.0 };
+    let directional_accuracy = if samples > 0 { hits / n } else { 0.5 };
+    let coverage_50 = inside_50 as f64 / n;
+    let coverage_90 = inside_90 as f64 / n;
+    let calibration_error = ((coverage_50 - 0.5).abs() + (coverage_90 - INTERVAL_LEVEL).abs()) / 2.0;
+    // Better than a coin flip at one-sided 95% significance, not just by luck
+    let significant_accuracy = 0.5 + Z_90 * 0.5 / n.sqrt();
+
+    ForecastEvaluation {
+        model: forecaster.name().to_string(),
+        timeframe: timeframe.to_string(),
+        horizon_bars: horizon,
+        samples,
+        mae,
+        rmse: (errors.iter().map(|e| e * e).sum::<f64>() / n).sqrt(),
+        naive_mae,
+        skill,
+        directional_accuracy,
+        coverage_50,
+        coverage_90,
+        calibration_error,
+        trusted: samples >= MIN_EVALUATION_SAMPLES
+            && skill > 0.0
+            && directional_accuracy > significant_accuracy
+            && calibration_error <= MAX_CALIBRATION_ERROR,
+    }
+}
+
+/// Bars of `interval` in `timeframe`, e.g. "4h" of "1h" klines is 4
+pub fn horizon_bars(timeframe: &str, interval: &str) -> TradingResult<usize> {
+    let invalid = || TradingError::validation_error(
+        "timeframe".to_string(),
+        format!("Timeframe must be a whole multiple of the {} forecast interval, e.g. 4h or 1d", interval),
+        Some(timeframe.to_string())
+    );
+    let timeframe_minutes = interval_minutes(timeframe).ok_or_else(invalid)?;
+    let interval_minutes = interval_minutes(interval).ok_or_else(invalid)?;
+    if timeframe_minutes < interval_minutes || timeframe_minutes % interval_minutes != 0 {
+        return Err(invalid());
+    }
+    Ok((timeframe_minutes / interval_minutes) as usize)
+}
+
+pub(super) fn interval_minutes(interval: &str) -> Option<u64> {
+    let unit = interval.chars().last()?;
+    let count: u64 = interval[..interval.len() - unit.len_utf8()].parse().ok().filter(|c| *c > 0)?;
+    let minutes = match unit {
+        'm' => 1,
+        'h' => 60,
+        'd' => 1440,
+        'w' => 10080,
+        _ => return None,
+    };
+    Some(count * minutes)
+}
+
+/// Forecasts built from stored klines, combining the models that earned trust
+pub struct PredictiveAnalyticsEngine {
+    kline_store: Option<KlineStore>,
+    interval: String,
+    forecasters: Vec<Box<dyn Forecaster>>,
+    /// Latest evaluation per symbol
+    evaluations: Mutex<HashMap<String, EvaluationReport>>,
+}
+
+/// The combined forecast for one horizon
+struct HorizonForecast {
+    timeframe: String,
+    horizon: usize,
+    forecast: ReturnForecast,
+    models: Vec<String>,
+    accuracy: f64,
+    /// Forecasts of every model, trusted or not
+    candidates: Vec<ReturnForecast>,
+}
+
+impl PredictiveAnalyticsEngine {
+    pub async fn new() -> TradingResult<Self> {
+        let kline_store = match KlineStore::open_default() {
+            Ok(store) => Some(store),
+            Err(e) => {
+                log_warning!(LogCategory::DataProcessing, "Kline store unavailable, predictions disabled: {}", e);
+                None
+            }
+        };
+        Ok(Self::with_store(kline_store, FORECAST_INTERVAL))
+    }
+
+    pub fn with_store(kline_store: Option<KlineStore>, interval: &str) -> Self {
+        Self {
+            kline_store,
+            interval: interval.to_string(),
+            forecasters: vec![
+                Box::new(DampedTrendEts),
+                Box::new(AutoRegressive::default()),
+                Box::new(FeatureRegression::default()),
+            ],
+            evaluations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forecasts over the default horizons
+    pub async fn generate_predictions(&self, symbol: &str) -> TradingResult<PredictiveInsights> {
+        let timeframes = DEFAULT_TIMEFRAMES.iter().map(|t| t.to_string()).collect();
+        self.generate_predictions_for_timeframes(symbol, timeframes).await
+    }
+
+    /// Forecasts for the first asset in the context, or the benchmark
+    pub async fn generate_insights(&self, market_context: &MarketContext) -> TradingResult<PredictiveInsights> {
+        let symbol = market_context.assets.first().map(String::as_str).unwrap_or(BENCHMARK_SYMBOL);
+        self.generate_predictions(symbol).await
+    }
+
+    pub async fn generate_predictions_for_timeframes(&self, symbol: &str, timeframes: Vec<String>) -> TradingResult<PredictiveInsights> {
+        let (last_close, returns) = self.load_returns(symbol)?;
+        let horizons = timeframes.iter()
+            .map(|timeframe| Ok((timeframe.clone(), horizon_bars(timeframe, &self.interval)?)))
+            .collect::<TradingResult<Vec<(String, usize)>>>()?;
+        let report = self.report_for(symbol, &returns, &timeframes)?;
+        let training = &returns[returns.len().saturating_sub(MAX_TRAINING)..];
+        let garch = Garch11::fit(training);
+
+        let forecasts: Vec<HorizonForecast> = horizons.into_iter()
+            .map(|(timeframe, horizon)| self.combine(&report, training, garch.as_ref(), timeframe, horizon))
+            .collect();
+        let now = Utc::now();
+
+        let price_predictions = forecasts.iter()
+            .map(|f| {
+                let (lower, upper) = f.forecast.interval(Z_90);
+                PricePrediction {
+                    timeframe: f.timeframe.clone(),
+                    predicted_price: to_price(last_close * f.forecast.mean.exp()),
+                    confidence_interval: ConfidenceInterval {
+                        lower_bound: to_price(last_close * lower.exp()),
+                        upper_bound: to_price(last_close * upper.exp()),
+                        confidence_level: INTERVAL_LEVEL,
+                    },
+                    model_used: f.models.join("+"),
+                    accuracy_score: f.accuracy,
+                    prediction_date: now,
+                }
+            })
+            .collect();
+
+        let trend_predictions = forecasts.iter()
+            .map(|f| {
+                let probability_up = f.forecast.probability_up();
+                let recent: f64 = returns[returns.len().saturating_sub(f.horizon)..].iter().sum();
+                TrendPrediction {
+                    timeframe: f.timeframe.clone(),
+                    trend_direction: match probability_up {
+                        p if p > 0.75 => TrendDirection::StronglyIncreasing,
+                        p if p > 0.55 => TrendDirection::Increasing,
+                        p if p < 0.25 => TrendDirection::StronglyDecreasing,
+                        p if p < 0.45 => TrendDirection::Decreasing,
+                        _ => TrendDirection::Stable,
+                    },
+                    trend_strength: (2.0 * probability_up - 1.0).abs(),
+                    trend_duration: None,
+                    reversal_probability: if recent >= 0.0 { 1.0 - probability_up } else { probability_up },
+                }
+            })
+            .collect();
+
+        let volatility_forecast = self.volatility_forecast(&returns, garch.as_ref(), &forecasts);
+        let market_regime_forecast = Self::regime_forecast(&returns, garch.as_ref(), &forecasts);
+        let prediction_confidence = Self::prediction_confidence(&returns, &forecasts, &volatility_forecast);
+
+        Ok(PredictiveInsights {
+            price_predictions,
+            trend_predictions,
+            volatility_forecast,
+            market_regime_forecast,
+            prediction_confidence,
+        })
+    }
+
+    /// Run rolling-origin evaluation of every forecaster and cache the report
+    pub fn evaluate(&self, symbol: &str, timeframes: &[String]) -> TradingResult<EvaluationReport> {
+        let (_, returns) = self.load_returns(symbol)?;
+        let report = self.build_report(symbol, &returns, timeframes)?;
+        self.evaluations.lock().unwrap_or_else(|e| e.into_inner()).insert(symbol.to_string(), report.clone());
+        Ok(report)
+    }
+
+    /// Latest cached evaluation for a symbol
+    pub fn evaluation_report(&self, symbol: &str) -> Option<EvaluationReport> {
+        self.evaluations.lock().unwrap_or_else(|e| e.into_inner()).get(symbol).cloned()
+    }
+
+    /// Re-evaluate every symbol forecast so far against the latest klines
+    pub async fn update_models(&mut self) -> TradingResult<()> {
+        let cached: Vec<(String, Vec<String>)> = self.evaluations.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .values()
+            .map(|report| (report.symbol.clone(), report.timeframes()))
+            .collect();
+        for (symbol, timeframes) in cached {
+            match self.evaluate(&symbol, &timeframes) {
+                Ok(report) => {
+                    let trusted = report.evaluations.iter().filter(|e| e.trusted).count();
+                    log_info!(LogCategory::DataProcessing, "Re-evaluated forecasts for {}: {}/{} trusted", symbol, trusted, report.evaluations.len());
+                }
+                Err(e) => {
+                    log_warning!(LogCategory::DataProcessing, "Failed to re-evaluate forecasts for {}: {}", symbol, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Latest close and log returns, oldest first
+    fn load_returns(&self, symbol: &str) -> TradingResult<(f64, Vec<f64>)> {
+        let store = self.kline_store.as_ref().ok_or_else(|| TradingError::config_error(
+            "kline_store".to_string(),
+            "Kline store is unavailable".to_string()
+        ))?;
+        let klines = store.load(symbol, &self.interval)?;
+        let closes: Vec<f64> = klines[klines.len().saturating_sub(MAX_HISTORY + 1)..].iter()
+            .filter_map(|k| k.close.to_f64())
+            .filter(|close| *close > 0.0)
+            .collect();
+        if closes.len() < MIN_HISTORY + 1 {
+            return Err(TradingError::validation_error(
+                "symbol".to_string(),
+                format!("Need at least {} {} klines for {} to forecast; download history first", MIN_HISTORY + 1, self.interval, symbol),
+                Some(closes.len().to_string())
+            ));
+        }
+        let returns = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+        Ok((closes[closes.len() - 1], returns))
+    }
+
+    /// Cached report if it covers these timeframes on the same data, otherwise a fresh one
+    fn report_for(&self, symbol: &str, returns: &[f64], timeframes: &[String]) -> TradingResult<EvaluationReport> {
+        let mut evaluations = self.evaluations.lock().unwrap_or_else(|e| e.into_inner());
+        let mut wanted: Vec<String> = timeframes.to_vec();
+        if let Some(report) = evaluations.get(symbol) {
+            let cached = report.timeframes();
+            if report.history_bars == returns.len() && timeframes.iter().all(|t| cached.contains(t)) {
+                return Ok(report.clone());
+            }
+            wanted.extend(cached.into_iter().filter(|t| !timeframes.contains(t)));
+        }
+        let report = self.build_report(symbol, returns, &wanted)?;
+        evaluations.insert(symbol.to_string(), report.clone());
+        Ok(report)
+    }
+
+    fn build_report(&self, symbol: &str, returns: &[f64], timeframes: &[String]) -> TradingResult<EvaluationReport> {
+        let mut evaluations = Vec::new();
+        for timeframe in timeframes {
+            let horizon = horizon_bars(timeframe, &self.interval)?;
+            for forecaster in &self.forecasters {
+                evaluations.push(evaluate_forecaster(forecaster.as_ref(), returns, horizon, timeframe));
+            }
+        }
+        Ok(EvaluationReport {
+            symbol: symbol.to_string(),
+            interval: self.interval.clone(),
+            generated_at: Utc::now(),
+            history_bars: returns.len(),
+            evaluations,
+        })
+    }
+
+    /// Inverse-MAE weighted mix of the trusted forecasters; a random walk with GARCH
+    /// volatility when none beat it
+    fn combine(&self, report: &EvaluationReport, training: &[f64], garch: Option<&Garch11>, timeframe: String, horizon: usize) -> HorizonForecast {
+        let mut weighted = Vec::new();
+        let mut candidates = Vec::new();
+        for forecaster in &self.forecasters {
+            let Some(forecast) = forecaster.forecast(training, horizon) else {
+                continue;
+            };
+            candidates.push(forecast);
+            if let Some(evaluation) = report.get(forecaster.name(), horizon).filter(|e| e.trusted) {
+                weighted.push((forecaster.name().to_string(), forecast, 1.0 / evaluation.mae.max(1e-12), evaluation.directional_accuracy));
+            }
+        }
+
+        if weighted.is_empty() {
+            let variance = garch.map(|g| g.horizon_variance(horizon))
+                .unwrap_or_else(|| horizon as f64 * std_dev(training).powi(2));
+            return HorizonForecast {
+                timeframe,
+                horizon,
+                forecast: ReturnForecast { mean: 0.0, std_dev: variance.sqrt() },
+                models: vec!["random_walk".to_string()],
+                accuracy: 0.5,
+                candidates,
+            };
+        }
+
+        let total: f64 = weighted.iter().map(|(_, _, w, _)| w).sum();
+        let blend = |value: &dyn Fn(&ReturnForecast, f64) -> f64| weighted.iter().map(|(_, f, w, a)| value(f, *a) * w).sum::<f64>() / total;
+        HorizonForecast {
+            timeframe,
+            horizon,
+            forecast: ReturnForecast { mean: blend(&|f, _| f.mean), std_dev: blend(&|f, _| f.std_dev) },
+            models: weighted.iter().map(|(name, _, _, _)| name.clone()).collect(),
+            accuracy: blend(&|_, accuracy| accuracy),
+            candidates,
+        }
+    }
+
+    fn volatility_forecast(&self, returns: &[f64], garch: Option<&Garch11>, forecasts: &[HorizonForecast]) -> VolatilityForecast {
+        let annualize = (interval_minutes(&self.interval).map_or(60.0, |m| 525_600.0 / m as f64)).sqrt();
+        let recent = &returns[returns.len().saturating_sub(VOLATILITY_WINDOW)..];
+        let current_volatility = std_dev(recent) * annualize;
+        let per_bar = |horizon: usize| match garch {
+            Some(g) => (g.horizon_variance(horizon) / horizon as f64).sqrt(),
+            None => std_dev(recent),
+        };
+        let longest = forecasts.iter().map(|f| f.horizon).max().unwrap_or(1);
+        let predicted_volatility = per_bar(longest) * annualize;
+
+        // Realized variance over h bars is roughly sigma^2 * chi2(h) / h; Wilson-Hilferty quantiles
+        let chi2_ratio = |h: f64, z: f64| (1.0 - 2.0 / (9.0 * h) + z * (2.0 / (9.0 * h)).sqrt()).max(0.0).powi(3);
+        let horizon_forecasts = forecasts.iter()
+            .map(|f| {
+                let volatility = per_bar(f.horizon) * annualize;
+                HorizonVolatility {
+                    timeframe: f.timeframe.clone(),
+                    volatility,
+                    lower_bound: volatility * chi2_ratio(f.horizon as f64, -Z_90).sqrt(),
+                    upper_bound: volatility * chi2_ratio(f.horizon as f64, Z_90).sqrt(),
+                    confidence_level: INTERVAL_LEVEL,
+                }
+            })
+            .collect();
+
+        VolatilityForecast {
+            current_volatility,
+            predicted_volatility,
+            volatility_trend: match predicted_volatility / current_volatility.max(1e-12) {
+                r if r > 1.3 => TrendDirection::StronglyIncreasing,
+                r if r > 1.1 => TrendDirection::Increasing,
+                r if r < 0.7 => TrendDirection::StronglyDecreasing,
+                r if r < 0.9 => TrendDirection::Decreasing,
+                _ => TrendDirection::Stable,
+            },
+            // Annualized bands typical of crypto majors
+            volatility_regime: match predicted_volatility {
+                v if v < 0.3 => VolatilityRegime::LowVolatility,
+                v if v < 0.7 => VolatilityRegime::NormalVolatility,
+                v if v < 1.2 => VolatilityRegime::HighVolatility,
+                _ => VolatilityRegime::ExtremeVolatility,
+            },
+            garch_forecast: GarchForecast {
+                conditional_volatility: garch.map_or(current_volatility, |g| g.next_variance.sqrt() * annualize),
+                volatility_persistence: garch.map_or(0.0, |g| g.persistence()),
+                volatility_clustering: garch.map_or(0.0, |g| g.alpha),
+            },
+            horizon_forecasts,
+        }
+    }
+
+    /// Regime from the recent move in volatility units; its probability is the chance the
+    /// longest-horizon forecast keeps the market there
+    fn regime_forecast(returns: &[f64], garch: Option<&Garch11>, forecasts: &[HorizonForecast]) -> MarketRegimeForecast {
+        let Some(longest) = forecasts.iter().max_by_key(|f| f.horizon) else {
+            return MarketRegimeForecast {
+                current_regime: MarketRegime::Sideways,
+                regime_probability: 0.5,
+                regime_transition_probability: 0.5,
+                expected_regime_duration: None,
+            };
+        };
+        let lookback = longest.horizon.max(VOLATILITY_WINDOW).min(returns.len());
+        let recent: f64 = returns[returns.len() - lookback..].iter().sum();
+        let bar_volatility = std_dev(&returns[returns.len().saturating_sub(MAX_TRAINING)..]).max(1e-12);
+        let z = recent / (bar_volatility * (lookback as f64).sqrt());
+        let current_regime = match z {
+            z if z < -3.0 => MarketRegime::Crash,
+            z if z < -1.0 => MarketRegime::StrongBear,
+            z if z < -0.3 => MarketRegime::Bear,
+            z if z < 0.3 => MarketRegime::Sideways,
+            z if z < 1.0 => MarketRegime::Bull,
+            _ => MarketRegime::StrongBull,
+        };
+
+        let forecast = longest.forecast;
+        let probability_up = forecast.probability_up();
+        let regime_probability = match current_regime {
+            MarketRegime::Bull | MarketRegime::StrongBull => probability_up,
+            MarketRegime::Sideways => {
+                let band = 0.3 * garch.map_or(bar_volatility * (longest.horizon as f64).sqrt(), |g| g.horizon_variance(longest.horizon).sqrt());
+                let s = forecast.std_dev.max(1e-12);
+                normal_cdf((band - forecast.mean) / s) - normal_cdf((-band - forecast.mean) / s)
+            }
+            _ => 1.0 - probability_up,
+        };
+        let transition = (1.0 - regime_probability).clamp(0.01, 1.0);
+
+        MarketRegimeForecast {
+            current_regime,
+            regime_probability,
+            regime_transition_probability: 1.0 - regime_probability,
+            // Geometric waiting time, counted in bars
+            expected_regime_duration: Some((longest.horizon as f64 / transition).round() as u32),
+        }
+    }
+
+    fn prediction_confidence(returns: &[f64], forecasts: &[HorizonForecast], volatility: &VolatilityForecast) -> PredictionConfidence {
+        let count = forecasts.len().max(1) as f64;
+        let historical_accuracy = forecasts.iter().map(|f| f.accuracy).sum::<f64>() / count;
+        // Share of models on the same side as the combined forecast, or near zero when it is flat
+        let model_agreement = forecasts.iter()
+            .map(|f| {
+                let agreeing = f.candidates.iter()
+                    .filter(|c| if f.forecast.mean == 0.0 { c.mean.abs() < 0.25 * c.std_dev } else { c.mean * f.forecast.mean > 0.0 })
+                    .count();
+                agreeing as f64 / f.candidates.len().max(1) as f64
+            })
+            .sum::<f64>() / count;
+        let data_quality_score = (returns.len() as f64 / (MAX_TRAINING + MIN_TRAINING) as f64).min(1.0);
+
+        let mut uncertainty_factors = Vec::new();
+        let untrusted: Vec<&str> = forecasts.iter().filter(|f| f.models == ["random_walk"]).map(|f| f.timeframe.as_str()).collect();
+        if !untrusted.is_empty() {
+            uncertainty_factors.push(UncertaintyFactor {
+                factor_type: "no_trusted_model".to_string(),
+                impact_level: untrusted.len() as f64 / count,
+                description: format!("No forecaster beat a random walk out of sample for {}", untrusted.join(", ")),
+            });
+        }
+        if matches!(volatility.volatility_regime, VolatilityRegime::HighVolatility | VolatilityRegime::ExtremeVolatility) {
+            uncertainty_factors.push(UncertaintyFactor {
+                factor_type: "high_volatility".to_string(),
+                impact_level: 0.7,
+                description: format!("Forecast annualized volatility of {:.0}%", volatility.predicted_volatility * 100.0),
+            });
+        }
+        if data_quality_score < 1.0 {
+            uncertainty_factors.push(UncertaintyFactor {
+                factor_type: "short_history".to_string(),
+                impact_level: 1.0 - data_quality_score,
+                description: format!("Only {} bars of history", returns.len()),
+            });
+        }
+
+        PredictionConfidence {
+            overall_confidence: 0.5 * historical_accuracy + 0.25 * model_agreement + 0.25 * data_quality_score,
+            model_agreement,
+            historical_accuracy,
+            data_quality_score,
+            uncertainty_factors,
+        }
+    }
+}
+
+fn to_price(value: f64) -> Decimal {
+    Decimal::from_f64(value).map(|d| d.round_dp(8)).unwrap_or_default()
+}
+
+/// Log price path starting at zero
+fn log_prices(returns: &[f64]) -> Vec<f64> {
+    std::iter::once(0.0)
+        .chain(returns.iter().scan(0.0, |price, r| {
+            *price += r;
+            Some(*price)
+        }))
+        .collect()
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let center = mean(values);
+    (values.iter().map(|v| (v - center).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+/// Solve the (ridge) normal equations; the first column is the unpenalized intercept
+fn least_squares(rows: &[Vec<f64>], targets: &[f64], ridge: f64) -> Option<Vec<f64>> {
+    let k = rows.first()?.len();
+    let mut a = vec![vec![0.0; k + 1]; k];
+    for (row, y) in rows.iter().zip(targets) {
+        for i in 0..k {
+            for j in 0..k {
+                a[i][j] += row[i] * row[j];
+            }
+            a[i][k] += row[i] * y;
+        }
+    }
+    for (i, equation) in a.iter_mut().enumerate().skip(1) {
+        equation[i] += ridge;
+    }
+
+    // Gaussian elimination with partial pivoting
+    for col in 0..k {
+        let pivot = (col..k).max_by(|x, y| a[*x][col].abs().total_cmp(&a[*y][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let pivot_row = a[col].clone();
+        for row in a.iter_mut().skip(col + 1) {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row.iter_mut().zip(&pivot_row).skip(col) {
+                *value -= factor * pivot_value;
+            }
+        }
+    }
+    let mut solution = vec![0.0; k];
+    for row in (0..k).rev() {
+        let known: f64 = (row + 1..k).map(|c| a[row][c] * solution[c]).sum();
+        solution[row] = (a[row][k] - known) / a[row][row];
+    }
+    Some(solution)
+}
+
+/// Standard normal CDF via the Abramowitz-Stegun erf approximation
+pub(crate) fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 { 0.5 * (1.0 + erf) } else { 0.5 * (1.0 - erf) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use crate::models::KlineData;
+
+    fn gaussian(rng: &mut StdRng) -> f64 {
+        let u1: f64 = rng.gen_range(1e-12..1.0);
+        let u2: f64 = rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    fn ar1(n: usize, phi: f64, sigma: f64, seed: u64) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut returns = vec![0.0];
+        for _ in 1..n {
+            let previous = returns[returns.len() - 1];
+            returns.push(phi * previous + sigma * gaussian(&mut rng));
+        }
+        returns
+    }
+
+    #[test]
+    fn test_rolling_evaluation_trusts_only_models_with_edge() {
+        let returns = ar1(700, 0.6, 0.01, 7);
+        let ar = AutoRegressive::default();
+
+        let one_step = evaluate_forecaster(&ar, &returns, 1, "1h");
+        assert_eq!(one_step.samples, MAX_EVALUATION_ORIGINS);
+        assert!(one_step.directional_accuracy > 0.6, "{:?}", one_step);
+        assert!(one_step.skill > 0.1);
+        assert!((0.75..=1.0).contains(&one_step.coverage_90));
+        assert!(one_step.trusted);
+
+        // Pure noise leaves nothing to exploit
+        let noise = ar1(700, 0.0, 0.01, 11);
+        let evaluation = evaluate_forecaster(&ar, &noise, 1, "1h");
+        assert!(!evaluation.trusted, "{:?}", evaluation);
+        assert!(evaluation.calibration_error < MAX_CALIBRATION_ERROR);
+
+        // Positive autocorrelation carries a large up move forward
+        let mut rising = returns.clone();
+        rising.push(0.05);
+        let forecast = ar.forecast(&rising, 4).unwrap();
+        assert!(forecast.mean > 0.02 && forecast.probability_up() > 0.9);
+        assert!(forecast.std_dev > ar.forecast(&rising, 1).unwrap().std_dev);
+    }
+
+    #[test]
+    fn test_ets_follows_trend_and_garch_captures_clustering() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let trending: Vec<f64> = (0..400).map(|_| 0.002 + 0.001 * gaussian(&mut rng)).collect();
+        let forecast = DampedTrendEts.forecast(&trending, 10).unwrap();
+        assert!((0.01..0.03).contains(&forecast.mean), "{:?}", forecast);
+        let ridge = FeatureRegression::default().forecast(&trending, 10).unwrap();
+        assert!((0.01..0.03).contains(&ridge.mean), "{:?}", ridge);
+
+        let (omega, alpha, beta): (f64, f64, f64) = (1e-6, 0.1, 0.85);
+        let mut variance = omega / (1.0 - alpha - beta);
+        let mut clustered = Vec::new();
+        for _ in 0..2000 {
+            let r = variance.sqrt() * gaussian(&mut rng);
+            clustered.push(r);
+            variance = omega + alpha * r * r + beta * variance;
+        }
+        let garch = Garch11::fit(&clustered).unwrap();
+        assert!(garch.persistence() > 0.8 && garch.persistence() < 1.0, "{:?}", garch);
+        let path = garch.variance_path(500);
+        assert!((path[499] - garch.long_run_variance).abs() < (path[0] - garch.long_run_variance).abs() + 1e-18);
+    }
+
+    #[tokio::test]
+    async fn test_engine_forecasts_from_kline_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = KlineStore::new(dir.path().to_path_buf());
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut close = 100.0;
+        let klines: Vec<KlineData> = ar1(400, 0.5, 0.01, 5).into_iter().enumerate().map(|(i, r)| {
+            close *= r.exp();
+            let price = Decimal::from_f64(close).unwrap().round_dp(6);
+            let open_time = start + Duration::hours(i as i64);
+            KlineData {
+                open_time,
+                close_time: open_time + Duration::hours(1) - Duration::milliseconds(1),
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: Decimal::ONE,
+            }
+        }).collect();
+        store.merge("TESTUSDT", "1h", &klines).unwrap();
+        let engine = PredictiveAnalyticsEngine::with_store(Some(store), "1h");
+
+        let insights = engine.generate_predictions_for_timeframes("TESTUSDT", vec!["1h".to_string(), "4h".to_string()]).await.unwrap();
+        assert_eq!(insights.price_predictions.len(), 2);
+        for prediction in &insights.price_predictions {
+            let interval = &prediction.confidence_interval;
+            assert!(interval.lower_bound < prediction.predicted_price && prediction.predicted_price < interval.upper_bound);
+        }
+        assert_eq!(insights.volatility_forecast.horizon_forecasts.len(), 2);
+        assert!(insights.volatility_forecast.garch_forecast.volatility_persistence < 1.0);
+        assert!((0.0..=1.0).contains(&insights.prediction_confidence.overall_confidence));
+
+        let report = engine.evaluation_report("TESTUSDT").unwrap();
+        assert_eq!(report.evaluations.len(), 6);
+        assert_eq!(report.timeframes(), vec!["1h".to_string(), "4h".to_string()]);
+
+        assert!(engine.generate_predictions_for_timeframes("TESTUSDT", vec!["30m".to_string()]).await.is_err());
+        assert!(engine.generate_predictions("MISSINGUSDT").await.is_err());
+        assert_eq!(horizon_bars("1d", "1h").unwrap(), 24);
+    }
+}
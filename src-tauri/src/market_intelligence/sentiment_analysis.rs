@@ -2,15 +2,24 @@
 // Market Intelligence Agent - Week 8 Implementation
 
 use std::collections::HashMap;
+use std::sync::Mutex;
 use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc, Duration};
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 
 use crate::errors::{TradingResult, TradingError};
+use crate::kline_store::KlineStore;
+use crate::logging::LogCategory;
+use crate::log_warning;
 use super::{
+    OverallSentiment, SentimentAnalysisReport, TimeRange,
     SentimentAnalysis, SentimentScore, SentimentTrend, TrendDirection,
     SocialSentiment, NewsSentiment, InstitutionalSentiment, SentimentDivergence, DivergenceSignificance
 };
+use super::fear_greed::{FearGreedCalculator, FearGreedHistory, FearGreedInputs, FearGreedReading};
+use super::news_ingestion::SharedNewsStore;
+use super::news_intelligence::NewsArticle;
+use super::nlp::NLPProcessor;
 
 /// Real-time sentiment analysis engine
 pub struct SentimentAnalysisEngine {
@@ -20,6 +29,9 @@ pub struct SentimentAnalysisEngine {
     sentiment_aggregator: SentimentAggregator,
     historical_sentiment: Vec<SentimentDataPoint>,
     fear_greed_calculator: FearGreedCalculator,
+    /// Source of the daily klines behind the Fear & Greed index
+    kline_store: Option<KlineStore>,
+    fear_greed_history: Mutex<FearGreedHistory>,
 }
 
 /// Social media sentiment analyzer trait
@@ -53,8 +65,10 @@ pub struct VolumeMetrics {
 /// News sentiment analyzer
 pub struct NewsAnalyzer {
     news_sources: Vec<NewsSource>,
+    /// Articles ingested by the news pipeline
+    store: Option<SharedNewsStore>,
+    /// Scores articles the pipeline stored before they were annotated
     nlp_processor: NLPProcessor,
-    credibility_scorer: CredibilityScorer,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,13 +89,6 @@ pub enum NewsCategory {
     Regulatory,
 }
 
-/// NLP processor for news content
-pub struct NLPProcessor {
-    sentiment_model: SentimentModel,
-    entity_extractor: EntityExtractor,
-    impact_classifier: ImpactClassifier,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessedNews {
     pub title: String,
@@ -135,17 +142,6 @@ pub struct SentimentDataPoint {
     pub sources: HashMap<String, f64>,
 }
 
-/// Fear and Greed index calculator
-pub struct FearGreedCalculator {
-    volatility_weight: f64,
-    momentum_weight: f64,
-    volume_weight: f64,
-    social_weight: f64,
-    dominance_weight: f64,
-    trends_weight: f64,
-    surveys_weight: f64,
-}
-
 impl SentimentAnalysisEngine {
     pub async fn new() -> TradingResult<Self> {
         // Initialize social media analyzers
@@ -193,15 +189,17 @@ impl SentimentAnalysisEngine {
             trend_calculator: TrendCalculator::new(),
         };
 
-        let fear_greed_calculator = FearGreedCalculator {
-            volatility_weight: 0.25,
-            momentum_weight: 0.25,
-            volume_weight: 0.15,
-            social_weight: 0.15,
-            dominance_weight: 0.1,
-            trends_weight: 0.05,
-            surveys_weight: 0.05,
+        let kline_store = match KlineStore::open_default() {
+            Ok(store) => Some(store),
+            Err(e) => {
+                log_warning!(LogCategory::DataProcessing, "Kline store unavailable, Fear & Greed index disabled: {}", e);
+                None
+            }
         };
+        let fear_greed_history = FearGreedHistory::open_default().unwrap_or_else(|e| {
+            log_warning!(LogCategory::DataProcessing, "Failed to load Fear & Greed history, starting fresh: {}", e);
+            FearGreedHistory::new()
+        });
 
         Ok(Self {
             social_analyzers,
@@ -209,7 +207,9 @@ impl SentimentAnalysisEngine {
             institutional_analyzer,
             sentiment_aggregator,
             historical_sentiment: Vec::new(),
-            fear_greed_calculator,
+            fear_greed_calculator: FearGreedCalculator::default(),
+            kline_store,
+            fear_greed_history: Mutex::new(fear_greed_history),
         })
     }
 
@@ -234,11 +234,7 @@ impl SentimentAnalysisEngine {
         let sentiment_strength = self.calculate_sentiment_strength(&overall_sentiment).await?;
 
         // Calculate Fear & Greed Index
-        let fear_greed_index = self.fear_greed_calculator.calculate_index(
-            symbol,
-            &social_sentiment,
-            &news_sentiment,
-        ).await?;
+        let fear_greed_index = self.update_fear_greed_index(&social_sentiment, &news_sentiment)?;
 
         // Check for sentiment divergence
         let sentiment_divergence = self.detect_sentiment_divergence(symbol, &overall_sentiment).await?;
@@ -255,6 +251,42 @@ impl SentimentAnalysisEngine {
         })
     }
 
+    /// Market-wide sentiment over a time range: relevance-weighted news sentiment alongside
+    /// the latest Fear & Greed reading
+    pub async fn analyze_market_sentiment(&self, time_range: TimeRange) -> TradingResult<SentimentAnalysisReport> {
+        let articles = self.news_analyzer.articles_between(time_range);
+        let total_weight: f64 = articles.iter().map(|a| a.relevance_score.max(0.1)).sum();
+        let score = if total_weight > 0.0 {
+            articles.iter().map(|a| a.sentiment_score * a.relevance_score.max(0.1)).sum::<f64>() / total_weight
+        } else {
+            0.0
+        };
+        let sentiment_label = match score {
+            s if s > 0.5 => "Very Bullish",
+            s if s > 0.15 => "Bullish",
+            s if s < -0.5 => "Very Bearish",
+            s if s < -0.15 => "Bearish",
+            _ => "Neutral",
+        };
+        let fear_greed_index = self.fear_greed_history.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .latest()
+            .map(|r| r.index)
+            .unwrap_or(50.0);
+
+        Ok(SentimentAnalysisReport {
+            time_range,
+            overall_sentiment: OverallSentiment {
+                score: score.clamp(-1.0, 1.0),
+                sentiment_label: sentiment_label.to_string(),
+            },
+            fear_greed_index,
+            articles_analyzed: articles.len(),
+            // Confidence saturates once a few dozen articles back the score
+            confidence_score: 1.0 - (-(articles.len() as f64) / 20.0).exp(),
+        })
+    }
+
     /// Analyze social media sentiment
     async fn analyze_social_sentiment(&self, symbol: &str) -> TradingResult<SocialSentiment> {
         let mut platform_sentiments = HashMap::new();
@@ -468,11 +500,11 @@ impl SentimentAnalysisEngine {
     }
 
     /// Detect sentiment divergence
-    async fn detect_sentiment_divergence(&self, symbol: &str, overall_sentiment: &SentimentScore) -> TradingResult<Option<SentimentDivergence>> {
+    async fn detect_sentiment_divergence(&self, _symbol: &str, _overall_sentiment: &SentimentScore) -> TradingResult<Option<SentimentDivergence>> {
         // This would require price data correlation analysis
         // Simplified implementation for now
-        let price_sentiment_divergence = 0.0; // Would calculate actual divergence
-        let volume_sentiment_divergence = 0.0; // Would calculate actual divergence
+        let price_sentiment_divergence: f64 = 0.0; // Would calculate actual divergence
+        let volume_sentiment_divergence: f64 = 0.0; // Would calculate actual divergence
         
         let divergence_significance = if price_sentiment_divergence.abs() > 0.5 {
             DivergenceSignificance::High
@@ -490,19 +522,72 @@ impl SentimentAnalysisEngine {
             Ok(Some(SentimentDivergence {
                 price_sentiment_divergence,
                 volume_sentiment_divergence,
-                divergence_significance,
                 potential_reversal_signal: matches!(divergence_significance, DivergenceSignificance::High),
+                divergence_significance,
             }))
         }
     }
 
+    /// Compute today's Fear & Greed reading and add it to the history. Falls back to the last
+    /// recorded index, or neutral, while there is too little stored data
+    fn update_fear_greed_index(&self, social: &SocialSentiment, news: &NewsSentiment) -> TradingResult<f64> {
+        let mut inputs = match self.kline_store.as_ref() {
+            Some(store) => FearGreedInputs::from_store(store)?,
+            None => FearGreedInputs::default(),
+        };
+        if social.social_volume > 0 {
+            inputs.social = Some((social.twitter_sentiment + social.reddit_sentiment + social.telegram_sentiment) / 3.0);
+        }
+        if news.news_volume > 0 {
+            inputs.news = Some(news.news_score);
+        }
+
+        let mut history = self.fear_greed_history.lock().unwrap_or_else(|e| e.into_inner());
+        match self.fear_greed_calculator.calculate(&inputs) {
+            Ok(reading) => {
+                let index = reading.index;
+                history.record(reading);
+                if let Err(e) = history.save() {
+                    log_warning!(LogCategory::DataProcessing, "Failed to save Fear & Greed history: {}", e);
+                }
+                Ok(index)
+            }
+            Err(_) => Ok(history.latest().map(|r| r.index).unwrap_or(50.0)),
+        }
+    }
+
+    /// Recorded Fear & Greed readings, oldest first
+    pub fn fear_greed_history(&self, since: Option<DateTime<Utc>>) -> Vec<FearGreedReading> {
+        self.fear_greed_history.lock().unwrap_or_else(|e| e.into_inner()).readings(since)
+    }
+
+    /// Most recent Fear & Greed reading
+    pub fn latest_fear_greed(&self) -> Option<FearGreedReading> {
+        self.fear_greed_history.lock().unwrap_or_else(|e| e.into_inner()).latest().cloned()
+    }
+
+    /// Rebuild the Fear & Greed history from every stored daily kline so it can be charted
+    /// and used by backtests. Returns the number of days recorded
+    pub fn backfill_fear_greed(&self) -> TradingResult<usize> {
+        let store = self.kline_store.as_ref().ok_or_else(|| TradingError::config_error(
+            "kline_store".to_string(),
+            "Kline store is unavailable".to_string()
+        ))?;
+        let inputs = FearGreedInputs::from_store(store)?;
+        let mut history = self.fear_greed_history.lock().unwrap_or_else(|e| e.into_inner());
+        let recorded = history.backfill(&self.fear_greed_calculator, &inputs);
+        history.save()?;
+        Ok(recorded)
+    }
+
+    /// Read news from the store the news intelligence pipeline fills
+    pub fn set_news_store(&mut self, store: SharedNewsStore) {
+        self.news_analyzer.store = Some(store);
+    }
+
     /// Update sentiment data
     pub async fn update_data(&mut self) -> TradingResult<()> {
-        // Update all analyzers
-        for (_, analyzer) in &mut self.social_analyzers {
-            // Update social media data
-        }
-        
+        // Social analyzers query their platforms on demand and keep no cached data
         self.news_analyzer.update_news_feeds().await?;
         self.institutional_analyzer.update_data().await?;
         
@@ -527,18 +612,50 @@ impl NewsAnalyzer {
     async fn new(sources: Vec<NewsSource>) -> TradingResult<Self> {
         Ok(Self {
             news_sources: sources,
-            nlp_processor: NLPProcessor::new(),
-            credibility_scorer: CredibilityScorer::new(),
+            store: None,
+            nlp_processor: NLPProcessor::default(),
         })
     }
 
-    async fn get_recent_news(&self, _symbol: &str) -> TradingResult<Vec<ProcessedNews>> {
-        // Would implement actual news retrieval and processing
-        Ok(vec![])
+    /// Stored articles published in the range, annotating any the pipeline stored unscored
+    fn articles_between(&self, time_range: TimeRange) -> Vec<NewsArticle> {
+        let Some(store) = self.store.as_ref() else {
+            return vec![];
+        };
+        let mut articles = store.lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .articles_between(time_range.start, time_range.end);
+        for article in articles.iter_mut().filter(|a| a.market_entities.is_empty() && a.sentiment_score == 0.0) {
+            self.nlp_processor.annotate(article, 0.5);
+        }
+        articles
+    }
+
+    /// Articles from the last day that mention the symbol's base asset
+    async fn get_recent_news(&self, symbol: &str) -> TradingResult<Vec<ProcessedNews>> {
+        Ok(self.articles_between(TimeRange::last_hours(24)).into_iter()
+            .filter(|article| article.mentions_symbol(symbol))
+            .map(|article| ProcessedNews {
+                title: article.title,
+                content: article.content,
+                sentiment_score: article.sentiment_score,
+                entities: article.market_entities.into_iter().map(|e| e.name).collect(),
+                impact_score: article.impact_score,
+                relevance_score: article.relevance_score,
+                emotion_scores: EmotionScores {
+                    fear: 0.0,
+                    greed: 0.0,
+                    anger: 0.0,
+                    joy: 0.0,
+                    trust: 0.0,
+                    anticipation: 0.0,
+                },
+            })
+            .collect())
     }
 
+    /// Feeds are polled by the news intelligence pipeline; nothing to fetch here
     async fn update_news_feeds(&mut self) -> TradingResult<()> {
-        // Update news feeds from all sources
         Ok(())
     }
 }
@@ -624,27 +741,6 @@ impl SentimentAggregator {
     }
 }
 
-impl FearGreedCalculator {
-    async fn calculate_index(
-        &self,
-        _symbol: &str,
-        _social: &SocialSentiment,
-        _news: &NewsSentiment,
-    ) -> TradingResult<f64> {
-        // Simplified Fear & Greed calculation
-        // In reality, this would incorporate:
-        // - Market volatility (25%)
-        // - Market momentum/volume (25%)
-        // - Social media sentiment (15%)
-        // - Surveys (15%)
-        // - Bitcoin dominance (10%)
-        // - Google trends (5%)
-        // - Safe haven demand (5%)
-        
-        Ok(55.0) // Placeholder value (0-100 scale)
-    }
-}
-
 // Placeholder implementations for specific analyzers
 
 struct TwitterAnalyzer;
@@ -747,19 +843,6 @@ impl SocialMediaAnalyzer for TelegramAnalyzer {
 }
 
 // Placeholder structures
-struct NLPProcessor;
-impl NLPProcessor {
-    fn new() -> Self { Self }
-}
-
-struct SentimentModel;
-struct EntityExtractor;
-struct ImpactClassifier;
-struct CredibilityScorer;
-impl CredibilityScorer {
-    fn new() -> Self { Self }
-}
-
 struct AnalystTracker;
 impl AnalystTracker {
     fn new() -> Self { Self }
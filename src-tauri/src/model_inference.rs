@@ -0,0 +1,707 @@
+use crate::errors::{TradingError, TradingResult};
+use crate::gpu_neural_predictor::{build_features, FeatureConfig};
+use crate::logging::LogCategory;
+use crate::models::{KlineData, PriceData, TradeSide};
+use crate::persistence;
+use crate::{log_info, log_warning};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tract_onnx::prelude::*;
+
+/// Most lagged one-bar returns a manifest may request
+pub const MAX_RETURN_LAG: usize = 20;
+
+/// Bars the swing bot keeps in its price history, which bounds a model's lookback
+pub const MAX_REQUIRED_BARS: usize = 200;
+
+/// How a model's output value is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModelOutputKind {
+    /// Predicted log return
+    Return,
+    /// Probability of an up move, 0-1
+    Probability,
+    /// Unbounded long/short score
+    Score,
+}
+
+/// Which output element is the signal and where its long/short thresholds sit
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelOutputSpec {
+    pub kind: ModelOutputKind,
+    /// Output tensor holding the signal
+    #[serde(default)]
+    pub tensor: usize,
+    /// Element of the flattened output tensor
+    #[serde(default)]
+    pub element: usize,
+    /// Values above this signal a long
+    pub long_threshold: f64,
+    /// Values below this signal a short
+    pub short_threshold: f64,
+}
+
+/// One model input, taken from the local feature pipeline and standardized with the
+/// statistics the model was trained with
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelFeature {
+    /// Name from `feature_catalog()`, e.g. `rsi_14` or `return_lag_3`
+    pub name: String,
+    /// Bars back from the newest bar
+    #[serde(default)]
+    pub lag: usize,
+    #[serde(default)]
+    pub mean: f64,
+    #[serde(default = "default_std")]
+    pub std: f64,
+}
+
+fn default_std() -> f64 {
+    1.0
+}
+
+/// Sidecar manifest (`<name>.json`) declaring an ONNX model's version, pinned hash and inputs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelManifest {
+    pub name: String,
+    pub version: String,
+    /// ONNX file name inside the models directory
+    pub file: String,
+    /// Hex SHA-256 of the ONNX file; models whose file does not match are rejected
+    pub sha256: String,
+    /// Kline interval the model was trained on
+    pub interval: String,
+    /// Inputs in the order the model expects them
+    pub features: Vec<ModelFeature>,
+    /// Input tensor shape, `[1, features]` when omitted
+    #[serde(default)]
+    pub input_shape: Option<Vec<usize>>,
+    pub output: ModelOutputSpec,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+impl ModelManifest {
+    pub fn validate(&self) -> TradingResult<()> {
+        let fail = |field: &str, message: &str, value: String| {
+            Err(TradingError::validation_error(field.to_string(), message.to_string(), Some(value)))
+        };
+        let identifier = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+
+        if !identifier(&self.name) || self.name.contains("..") {
+            return fail("name", "Model name must be alphanumeric, '-', '_' or '.'", self.name.clone());
+        }
+        if !identifier(&self.version) {
+            return fail("version", "Model version must be alphanumeric, '-', '_' or '.'", self.version.clone());
+        }
+        if !identifier(&self.file) || self.file.contains("..") || !self.file.ends_with(".onnx") {
+            return fail("file", "Model file must be a plain .onnx file name in the models directory", self.file.clone());
+        }
+        if self.sha256.len() != 64 || !self.sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return fail("sha256", "Model hash must be a hex SHA-256", self.sha256.clone());
+        }
+        if self.features.is_empty() {
+            return fail("features", "A model needs at least one input feature", String::new());
+        }
+
+        let catalog = feature_catalog();
+        for feature in &self.features {
+            if !catalog.contains(&feature.name) {
+                return fail("features", "Unknown feature, see the feature catalog", feature.name.clone());
+            }
+            if !(feature.mean.is_finite() && feature.std.is_finite() && feature.std > 0.0) {
+                return fail("features", "Feature mean must be finite and std positive", feature.name.clone());
+            }
+            if feature.lag >= MAX_REQUIRED_BARS {
+                return fail("features", "Feature lag exceeds the available price history", feature.lag.to_string());
+            }
+        }
+        if self.required_bars() > MAX_REQUIRED_BARS {
+            return fail("features", "Feature lags exceed the available price history", self.required_bars().to_string());
+        }
+
+        let shape = self.input_shape();
+        if shape.iter().product::<usize>() != self.features.len() {
+            return fail("input_shape", "Input shape must hold exactly one value per feature", format!("{:?}", shape));
+        }
+
+        let output = &self.output;
+        if !output.long_threshold.is_finite() || !output.short_threshold.is_finite() || output.long_threshold < output.short_threshold {
+            return fail("output", "Long threshold must be finite and at least the short threshold",
+                format!("{} / {}", output.long_threshold, output.short_threshold));
+        }
+        if output.kind == ModelOutputKind::Probability
+            && !((0.0..=1.0).contains(&output.long_threshold) && (0.0..=1.0).contains(&output.short_threshold))
+        {
+            return fail("output", "Probability thresholds must be between 0 and 1",
+                format!("{} / {}", output.long_threshold, output.short_threshold));
+        }
+        Ok(())
+    }
+
+    pub fn input_shape(&self) -> Vec<usize> {
+        self.input_shape.clone().unwrap_or_else(|| vec![1, self.features.len()])
+    }
+
+    /// Feature pipeline settings covering every requested feature
+    fn feature_config(&self) -> FeatureConfig {
+        let return_lags = self.features.iter()
+            .filter_map(|f| f.name.strip_prefix("return_lag_").and_then(|lag| lag.parse::<usize>().ok()))
+            .max()
+            .unwrap_or(0);
+        let indicators = FeatureConfig { return_lags: 0, depth: false, ..FeatureConfig::default() }.feature_names();
+        let depth = FeatureConfig { return_lags: 0, indicators: false, ..FeatureConfig::default() }.feature_names();
+        FeatureConfig {
+            return_lags,
+            horizon: 1,
+            indicators: self.features.iter().any(|f| indicators.contains(&f.name)),
+            depth: self.features.iter().any(|f| depth.contains(&f.name)),
+        }
+    }
+
+    /// Klines needed to compute every input for the newest bar
+    pub fn required_bars(&self) -> usize {
+        let max_lag = self.features.iter().map(|f| f.lag).max().unwrap_or(0);
+        self.feature_config().warmup_bars() + max_lag + 1
+    }
+
+    /// Standardized model inputs for the newest kline in `klines` (oldest first)
+    pub fn inputs(&self, klines: &[KlineData]) -> TradingResult<Vec<f32>> {
+        let features = build_features(klines, &self.feature_config());
+        let newest = klines.len().checked_sub(1);
+        self.features.iter()
+            .map(|feature| {
+                let row = newest
+                    .and_then(|newest| newest.checked_sub(feature.lag))
+                    .and_then(|bar| features.bars.binary_search(&bar).ok())
+                    .map(|position| &features.rows[position]);
+                let column = features.names.iter().position(|name| *name == feature.name);
+                match (row, column) {
+                    (Some(row), Some(column)) => Ok(((row[column] - feature.mean) / feature.std) as f32),
+                    _ => Err(TradingError::validation_error(
+                        "klines".to_string(),
+                        format!("Not enough klines for feature {} (need {})", feature.name, self.required_bars()),
+                        Some(klines.len().to_string())
+                    )),
+                }
+            })
+            .collect()
+    }
+}
+
+/// Every feature name a manifest can request
+pub fn feature_catalog() -> Vec<String> {
+    FeatureConfig { return_lags: MAX_RETURN_LAG, ..FeatureConfig::default() }.feature_names()
+}
+
+/// Convert the bot's price bars into klines for the feature pipeline
+pub fn klines_from_bars(bars: &[PriceData]) -> Vec<KlineData> {
+    bars.iter()
+        .map(|bar| KlineData {
+            open_time: bar.timestamp,
+            close_time: bar.timestamp,
+            open: bar.open,
+            high: bar.high,
+            low: bar.low,
+            close: bar.close,
+            volume: bar.volume,
+        })
+        .collect()
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// A model's prediction for the newest bar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrediction {
+    pub model: String,
+    pub version: String,
+    pub sha256: String,
+    pub kind: ModelOutputKind,
+    pub timestamp: DateTime<Utc>,
+    pub value: f64,
+    /// `None` when the value sits between the short and long thresholds
+    pub direction: Option<TradeSide>,
+}
+
+impl ModelPrediction {
+    pub fn agrees_with(&self, side: &TradeSide) -> bool {
+        let long = |s: &TradeSide| matches!(s, TradeSide::Long | TradeSide::Buy);
+        self.direction.as_ref().is_some_and(|direction| long(direction) == long(side))
+    }
+}
+
+/// Hash-verified ONNX model compiled for the tract runtime
+pub struct OnnxModel {
+    pub manifest: ModelManifest,
+    pub loaded_at: DateTime<Utc>,
+    plan: TypedRunnableModel<TypedModel>,
+}
+
+impl std::fmt::Debug for OnnxModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnnxModel")
+            .field("name", &self.manifest.name)
+            .field("version", &self.manifest.version)
+            .field("sha256", &self.manifest.sha256)
+            .finish()
+    }
+}
+
+impl OnnxModel {
+    /// Verify `bytes` against the manifest's pinned hash and compile the model
+    pub fn from_bytes(mut manifest: ModelManifest, bytes: &[u8]) -> TradingResult<Self> {
+        manifest.validate()?;
+        let sha256 = sha256_hex(bytes);
+        if !sha256.eq_ignore_ascii_case(&manifest.sha256) {
+            return Err(TradingError::validation_error(
+                "sha256".to_string(),
+                format!("{} does not match the hash pinned in the {} {} manifest", manifest.file, manifest.name, manifest.version),
+                Some(sha256)
+            ));
+        }
+        // Keep the verified hash in one case so pins compare consistently
+        manifest.sha256 = sha256;
+
+        let compile_error = |e: TractError| TradingError::config_error(
+            "model".to_string(),
+            format!("Failed to load ONNX model {} {}: {}", manifest.name, manifest.version, e)
+        );
+        let shape = manifest.input_shape();
+        let plan = tract_onnx::onnx()
+            .model_for_read(&mut &bytes[..])
+            .and_then(|model| model.with_input_fact(0, f32::fact(&shape).into()))
+            .and_then(|model| model.into_optimized())
+            .and_then(|model| model.into_runnable())
+            .map_err(compile_error)?;
+        if plan.model().inputs.len() != 1 || manifest.output.tensor >= plan.model().outputs.len() {
+            return Err(TradingError::validation_error(
+                "model".to_string(),
+                "Model must take a single input and produce the manifest's output tensor".to_string(),
+                Some(format!("{} inputs, {} outputs", plan.model().inputs.len(), plan.model().outputs.len()))
+            ));
+        }
+
+        Ok(Self { manifest, loaded_at: Utc::now(), plan })
+    }
+
+    /// Load a model from its manifest and the ONNX file next to it
+    pub fn load(manifest_path: &Path) -> TradingResult<Self> {
+        let manifest: ModelManifest = persistence::read_json(manifest_path)?.ok_or_else(|| TradingError::config_error(
+            "manifest".to_string(),
+            format!("{} does not exist", manifest_path.display())
+        ))?;
+        manifest.validate()?;
+        let model_path = manifest_path.with_file_name(&manifest.file);
+        let bytes = std::fs::read(&model_path).map_err(|e| TradingError::config_error(
+            "file_read".to_string(),
+            format!("Failed to read {}: {}", model_path.display(), e)
+        ))?;
+        Self::from_bytes(manifest, &bytes)
+    }
+
+    pub fn sha256(&self) -> &str {
+        &self.manifest.sha256
+    }
+
+    pub fn required_bars(&self) -> usize {
+        self.manifest.required_bars()
+    }
+
+    /// Run the model on already standardized inputs, returning the flattened output tensor
+    pub fn run(&self, inputs: &[f32]) -> TradingResult<Vec<f32>> {
+        let run_error = |e: TractError| TradingError::internal_error(
+            format!("Inference failed for {} {}: {}", self.manifest.name, self.manifest.version, e)
+        );
+        let input = Tensor::from_shape(&self.manifest.input_shape(), inputs).map_err(run_error)?;
+        let outputs = self.plan.run(tvec!(input.into())).map_err(run_error)?;
+        let output = outputs[self.manifest.output.tensor].cast_to::<f32>().map_err(run_error)?;
+        Ok(output.as_slice::<f32>().map_err(run_error)?.to_vec())
+    }
+
+    /// Predict from klines (oldest first); the newest kline must be closed
+    pub fn predict(&self, klines: &[KlineData]) -> TradingResult<ModelPrediction> {
+        let output = self.run(&self.manifest.inputs(klines)?)?;
+        let spec = &self.manifest.output;
+        let value = output.get(spec.element)
+            .map(|v| *v as f64)
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| TradingError::internal_error(format!(
+                "{} {} produced no finite value at output element {}", self.manifest.name, self.manifest.version, spec.element
+            )))?;
+
+        let direction = if value > spec.long_threshold {
+            Some(TradeSide::Long)
+        } else if value < spec.short_threshold {
+            Some(TradeSide::Short)
+        } else {
+            None
+        };
+        Ok(ModelPrediction {
+            model: self.manifest.name.clone(),
+            version: self.manifest.version.clone(),
+            sha256: self.manifest.sha256.clone(),
+            kind: spec.kind,
+            timestamp: klines.last().map_or_else(Utc::now, |k| k.close_time),
+            value,
+            direction,
+        })
+    }
+}
+
+/// Entry filter requiring a user-supplied model to agree with the trade direction
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ModelSignalFilter {
+    pub enabled: bool,
+    pub model: String,
+    /// Exact version to use, the newest loaded version when unset
+    pub version: Option<String>,
+    /// Refuse any model file whose hash differs from this pin
+    pub sha256: Option<String>,
+}
+
+impl ModelSignalFilter {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.model.is_empty() {
+            return Err("Model signal filter needs a model name".to_string());
+        }
+        if let Some(pin) = &self.sha256 {
+            if pin.len() != 64 || !pin.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err("Model signal sha256 pin must be a hex SHA-256".to_string());
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether a loaded model satisfies the configured name, version and hash pin
+    pub fn matches(&self, model: &OnnxModel) -> bool {
+        model.manifest.name == self.model
+            && self.version.as_ref().is_none_or(|version| *version == model.manifest.version)
+            && self.sha256.as_ref().is_none_or(|pin| pin.eq_ignore_ascii_case(model.sha256()))
+    }
+
+    /// Run the model on `klines` and check it agrees with `side`. Missing or failing models block
+    /// entries while the filter is enabled
+    pub fn evaluate(&self, model: Option<&OnnxModel>, klines: &[KlineData], side: &TradeSide) -> (bool, Option<ModelPrediction>) {
+        if !self.enabled {
+            return (true, None);
+        }
+        let Some(model) = model.filter(|model| self.matches(model)) else {
+            log_warning!(LogCategory::TradingLogic, "{:?} entry blocked: model {} is not loaded with the pinned version and hash", side, self.model);
+            return (false, None);
+        };
+
+        match model.predict(klines) {
+            Ok(prediction) => {
+                let allowed = prediction.agrees_with(side);
+                if !allowed {
+                    log_info!(LogCategory::TradingLogic, "{:?} entry blocked by model {} {} ({:.4}, direction {:?})",
+                        side, prediction.model, prediction.version, prediction.value, prediction.direction);
+                }
+                (allowed, Some(prediction))
+            }
+            Err(e) => {
+                log_warning!(LogCategory::TradingLogic, "{:?} entry blocked: {}", side, e);
+                (false, None)
+            }
+        }
+    }
+}
+
+/// A manifest that failed to load
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelLoadError {
+    pub manifest: String,
+    pub error: String,
+}
+
+/// Models loaded from the models directory, every version kept and sorted oldest first
+#[derive(Debug, Default)]
+pub struct ModelRegistry {
+    models: HashMap<String, Vec<Arc<OnnxModel>>>,
+    pub load_errors: Vec<ModelLoadError>,
+}
+
+impl ModelRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn default_dir() -> TradingResult<PathBuf> {
+        Ok(persistence::data_dir()?.join("models"))
+    }
+
+    /// Load every manifest in the application's models directory
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(&Self::default_dir()?)
+    }
+
+    /// Load every `*.json` manifest in `dir`. Models that fail to load are recorded in
+    /// `load_errors` instead of failing the whole registry
+    pub fn open(dir: &Path) -> TradingResult<Self> {
+        let mut registry = Self::new();
+        if !dir.exists() {
+            return Ok(registry);
+        }
+        let entries = std::fs::read_dir(dir).map_err(|e| TradingError::config_error(
+            "models_dir".to_string(),
+            format!("Failed to read {}: {}", dir.display(), e)
+        ))?;
+        let mut manifests: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        manifests.sort();
+
+        for path in manifests {
+            let result = OnnxModel::load(&path).and_then(|model| registry.insert(model));
+            if let Err(e) = result {
+                log_warning!(LogCategory::Configuration, "Skipping model manifest {}: {}", path.display(), e);
+                registry.load_errors.push(ModelLoadError { manifest: path.display().to_string(), error: e.to_string() });
+            }
+        }
+        Ok(registry)
+    }
+
+    pub fn insert(&mut self, model: OnnxModel) -> TradingResult<()> {
+        let versions = self.models.entry(model.manifest.name.clone()).or_default();
+        if versions.iter().any(|loaded| loaded.manifest.version == model.manifest.version) {
+            return Err(TradingError::validation_error(
+                "version".to_string(),
+                "Model version is already loaded".to_string(),
+                Some(format!("{} {}", model.manifest.name, model.manifest.version))
+            ));
+        }
+        log_info!(LogCategory::Configuration, "Loaded model {} {} ({})", model.manifest.name, model.manifest.version, model.sha256());
+        versions.push(Arc::new(model));
+        versions.sort_by(|a, b| compare_versions(&a.manifest.version, &b.manifest.version));
+        Ok(())
+    }
+
+    /// A specific version of a model, or its newest version
+    pub fn get(&self, name: &str, version: Option<&str>) -> Option<Arc<OnnxModel>> {
+        let versions = self.models.get(name)?;
+        match version {
+            Some(version) => versions.iter().find(|model| model.manifest.version == version).cloned(),
+            None => versions.last().cloned(),
+        }
+    }
+
+    /// Loaded models ordered by name, then version
+    pub fn list(&self) -> Vec<Arc<OnnxModel>> {
+        let mut names: Vec<&String> = self.models.keys().collect();
+        names.sort();
+        names.into_iter().flat_map(|name| self.models[name].iter().cloned()).collect()
+    }
+
+    /// The model a filter selects, `None` while it is disabled
+    pub fn resolve(&self, filter: &ModelSignalFilter) -> Result<Option<Arc<OnnxModel>>, String> {
+        if !filter.enabled {
+            return Ok(None);
+        }
+        let model = self.get(&filter.model, filter.version.as_deref()).ok_or_else(|| format!(
+            "Model {} {} is not loaded",
+            filter.model,
+            filter.version.as_deref().unwrap_or("(latest)")
+        ))?;
+        if !filter.matches(&model) {
+            return Err(format!("Model {} {} has hash {}, which does not match the pinned hash",
+                filter.model, model.manifest.version, model.sha256()));
+        }
+        Ok(Some(model))
+    }
+}
+
+/// Compare dotted versions numerically where both parts are numbers ("1.10" after "1.9")
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => {
+                let ordering = match (x.parse::<u64>(), y.parse::<u64>()) {
+                    (Ok(x), Ok(y)) => x.cmp(&y),
+                    _ => x.cmp(y),
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use prost::Message;
+    use rust_decimal::Decimal;
+    use tract_onnx::pb;
+
+    /// ONNX encoding of the linear model `y = x w + b`
+    fn linear_onnx(weights: &[f32], bias: f32) -> Vec<u8> {
+        let float = pb::tensor_proto::DataType::Float as i32;
+        let tensor_type = |dims: &[i64]| Some(pb::TypeProto {
+            value: Some(pb::type_proto::Value::TensorType(pb::type_proto::Tensor {
+                elem_type: float,
+                shape: Some(pb::TensorShapeProto {
+                    dim: dims.iter()
+                        .map(|&d| pb::tensor_shape_proto::Dimension {
+                            value: Some(pb::tensor_shape_proto::dimension::Value::DimValue(d)),
+                            ..Default::default()
+                        })
+                        .collect(),
+                }),
+            })),
+            ..Default::default()
+        });
+        let n = weights.len() as i64;
+        let node = |op: &str, inputs: [&str; 2], output: &str| pb::NodeProto {
+            op_type: op.to_string(),
+            input: inputs.iter().map(|s| s.to_string()).collect(),
+            output: vec![output.to_string()],
+            ..Default::default()
+        };
+        let graph = pb::GraphProto {
+            name: "linear".to_string(),
+            node: vec![node("MatMul", ["x", "w"], "h"), node("Add", ["h", "b"], "y")],
+            initializer: vec![
+                pb::TensorProto { name: "w".to_string(), dims: vec![n, 1], data_type: float, float_data: weights.to_vec(), ..Default::default() },
+                pb::TensorProto { name: "b".to_string(), dims: vec![1], data_type: float, float_data: vec![bias], ..Default::default() },
+            ],
+            input: vec![pb::ValueInfoProto { name: "x".to_string(), r#type: tensor_type(&[1, n]), ..Default::default() }],
+            output: vec![pb::ValueInfoProto { name: "y".to_string(), r#type: tensor_type(&[1, 1]), ..Default::default() }],
+            ..Default::default()
+        };
+        pb::ModelProto {
+            ir_version: 7,
+            opset_import: vec![pb::OperatorSetIdProto { domain: String::new(), version: 13 }],
+            graph: Some(graph),
+            ..Default::default()
+        }
+        .encode_to_vec()
+    }
+
+    fn feature(name: &str, lag: usize) -> ModelFeature {
+        ModelFeature { name: name.to_string(), lag, mean: 0.0, std: 1.0 }
+    }
+
+    fn manifest(version: &str, bytes: &[u8]) -> ModelManifest {
+        ModelManifest {
+            name: "momentum".to_string(),
+            version: version.to_string(),
+            file: format!("momentum-{}.onnx", version),
+            sha256: sha256_hex(bytes),
+            interval: "1h".to_string(),
+            // The linear model reads the last return; RSI only widens the warmup
+            features: vec![feature("return_lag_1", 0), feature("rsi_14", 1)],
+            input_shape: None,
+            output: ModelOutputSpec {
+                kind: ModelOutputKind::Return,
+                tensor: 0,
+                element: 0,
+                long_threshold: 0.001,
+                short_threshold: -0.001,
+            },
+            description: None,
+        }
+    }
+
+    fn trending_klines(n: usize, step: f64) -> Vec<KlineData> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let decimal = |v: f64| Decimal::from_f64_retain(v).unwrap();
+        (0..n)
+            .map(|i| {
+                let open = 100.0 * (1.0 + step).powi(i as i32);
+                let close = open * (1.0 + step);
+                KlineData {
+                    open_time: start + Duration::hours(i as i64),
+                    close_time: start + Duration::hours(i as i64 + 1),
+                    open: decimal(open),
+                    high: decimal(open.max(close) * 1.002),
+                    low: decimal(open.min(close) * 0.998),
+                    close: decimal(close),
+                    volume: decimal(100.0),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn predictions_use_manifest_features_and_thresholds() {
+        let bytes = linear_onnx(&[1.0, 0.0], 0.0);
+        let model = OnnxModel::from_bytes(manifest("1.0", &bytes), &bytes).unwrap();
+        assert_eq!(model.required_bars(), 35);
+
+        let rising = trending_klines(60, 0.01);
+        let prediction = model.predict(&rising).unwrap();
+        assert!((prediction.value - 1.01f64.ln()).abs() < 1e-5);
+        assert!(prediction.agrees_with(&TradeSide::Long) && !prediction.agrees_with(&TradeSide::Short));
+        assert_eq!(model.predict(&trending_klines(60, -0.01)).unwrap().direction, Some(TradeSide::Short));
+        assert_eq!(model.predict(&trending_klines(60, 0.0)).unwrap().direction, None);
+
+        assert!(model.predict(&rising[..34]).is_err());
+        assert!(model.predict(&rising[..35]).is_ok());
+    }
+
+    #[test]
+    fn tampered_files_and_hash_pins_block_models() {
+        let bytes = linear_onnx(&[1.0, 0.0], 0.0);
+        let tampered = linear_onnx(&[-1.0, 0.0], 0.0);
+        assert!(OnnxModel::from_bytes(manifest("1.0", &bytes), &tampered).is_err());
+
+        let model = OnnxModel::from_bytes(manifest("1.0", &bytes), &bytes).unwrap();
+        let klines = trending_klines(60, 0.01);
+        let mut filter = ModelSignalFilter { enabled: true, model: "momentum".to_string(), ..ModelSignalFilter::default() };
+        assert!(filter.evaluate(Some(&model), &klines, &TradeSide::Long).0);
+        assert!(!filter.evaluate(Some(&model), &klines, &TradeSide::Short).0);
+        assert!(!filter.evaluate(None, &klines, &TradeSide::Long).0);
+
+        filter.sha256 = Some(sha256_hex(&tampered));
+        assert!(!filter.matches(&model));
+        assert!(!filter.evaluate(Some(&model), &klines, &TradeSide::Long).0);
+        assert!(ModelSignalFilter::default().evaluate(None, &klines, &TradeSide::Long).0);
+    }
+
+    #[test]
+    fn registry_loads_every_version_and_skips_bad_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        for (version, weight) in [("1.9", 1.0), ("1.10", 2.0)] {
+            let bytes = linear_onnx(&[weight, 0.0], 0.0);
+            let manifest = manifest(version, &bytes);
+            std::fs::write(dir.path().join(&manifest.file), &bytes).unwrap();
+            persistence::write_json(&dir.path().join(format!("momentum-{}.json", version)), &manifest).unwrap();
+        }
+        let broken = ModelManifest { sha256: "0".repeat(64), ..manifest("2.0", &[]) };
+        std::fs::write(dir.path().join(&broken.file), linear_onnx(&[1.0, 0.0], 0.0)).unwrap();
+        persistence::write_json(&dir.path().join("momentum-2.0.json"), &broken).unwrap();
+
+        let registry = ModelRegistry::open(dir.path()).unwrap();
+        assert_eq!(registry.list().len(), 2);
+        assert_eq!(registry.load_errors.len(), 1);
+        assert_eq!(registry.get("momentum", None).unwrap().manifest.version, "1.10");
+        assert_eq!(registry.get("momentum", Some("1.9")).unwrap().manifest.version, "1.9");
+
+        let pinned = registry.get("momentum", Some("1.9")).unwrap().sha256().to_string();
+        let filter = ModelSignalFilter { enabled: true, model: "momentum".to_string(), version: None, sha256: Some(pinned) };
+        assert!(registry.resolve(&filter).is_err());
+        let filter = ModelSignalFilter { version: Some("1.9".to_string()), ..filter };
+        assert_eq!(registry.resolve(&filter).unwrap().unwrap().manifest.version, "1.9");
+    }
+}
@@ -0,0 +1,541 @@
+// Multi-Timeframe Resampling and Higher-Timeframe Confirmation
+// Builds synchronized 1m -> 5m -> 1h -> 4h -> 1d series from a single feed
+
+use std::collections::VecDeque;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::models::{PriceData, TradeSide};
+
+/// Maximum number of completed bars kept per timeframe
+const MAX_BARS_PER_TIMEFRAME: usize = 200;
+
+/// Timeframes maintained by default on top of the base feed
+pub const DEFAULT_HIGHER_TIMEFRAMES: [&str; 4] = ["5m", "1h", "4h", "1d"];
+
+/// Convert a Binance-style timeframe string into a duration
+pub fn timeframe_duration(timeframe: &str) -> Option<Duration> {
+    let (value, unit) = timeframe.split_at(timeframe.len().checked_sub(1)?);
+    let value: i64 = value.parse().ok()?;
+    if value <= 0 {
+        return None;
+    }
+
+    match unit {
+        "s" => Some(Duration::seconds(value)),
+        "m" => Some(Duration::minutes(value)),
+        "h" => Some(Duration::hours(value)),
+        "d" => Some(Duration::days(value)),
+        "w" => Some(Duration::weeks(value)),
+        _ => None, // Calendar months cannot be bucketed with a fixed duration
+    }
+}
+
+/// Rule used to decide whether a higher timeframe agrees with an entry
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum ConfirmationRule {
+    /// Higher-timeframe LRO must be moving in the trade direction
+    LroAgreement,
+    /// Higher-timeframe regression slope must point in the trade direction
+    TrendAgreement,
+    /// Either LRO or trend agreement is sufficient
+    Either,
+    /// Both LRO and trend must agree
+    Both,
+}
+
+/// Higher-timeframe confirmation settings shared by the bot and the backtester
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HigherTimeframeConfirmation {
+    pub enabled: bool,
+    pub timeframes: Vec<String>,
+    pub rule: ConfirmationRule,
+    pub lro_period: usize,
+    /// Number of timeframes that must agree (0 = all of them)
+    pub min_agreeing_timeframes: usize,
+}
+
+impl Default for HigherTimeframeConfirmation {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            timeframes: vec!["1h".to_string(), "4h".to_string()],
+            rule: ConfirmationRule::Either,
+            lro_period: 20,
+            min_agreeing_timeframes: 0,
+        }
+    }
+}
+
+impl HigherTimeframeConfirmation {
+    /// Validate the confirmation settings against the base timeframe
+    pub fn validate(&self, base_timeframe: &str) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let base = timeframe_duration(base_timeframe)
+            .ok_or_else(|| format!("Base timeframe '{}' cannot be resampled", base_timeframe))?;
+
+        if self.timeframes.is_empty() {
+            return Err("Higher-timeframe confirmation requires at least one timeframe".to_string());
+        }
+
+        for timeframe in &self.timeframes {
+            let duration = timeframe_duration(timeframe)
+                .ok_or_else(|| format!("Invalid confirmation timeframe '{}'", timeframe))?;
+            if duration <= base || duration.num_seconds() % base.num_seconds() != 0 {
+                return Err(format!(
+                    "Confirmation timeframe '{}' must be a whole multiple of base timeframe '{}'",
+                    timeframe, base_timeframe
+                ));
+            }
+        }
+
+        if self.lro_period < 5 || self.lro_period > 200 {
+            return Err("Higher-timeframe LRO period must be between 5 and 200".to_string());
+        }
+
+        if self.min_agreeing_timeframes > self.timeframes.len() {
+            return Err("Minimum agreeing timeframes cannot exceed the number of timeframes".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Resampled series for a single timeframe
+#[derive(Debug, Clone)]
+struct TimeframeSeries {
+    timeframe: String,
+    duration: Duration,
+    forming: Option<PriceData>,
+    completed: VecDeque<PriceData>,
+}
+
+impl TimeframeSeries {
+    fn new(timeframe: &str, duration: Duration) -> Self {
+        Self {
+            timeframe: timeframe.to_string(),
+            duration,
+            forming: None,
+            completed: VecDeque::with_capacity(MAX_BARS_PER_TIMEFRAME),
+        }
+    }
+
+    /// Start of the bucket containing `timestamp`, aligned to the Unix epoch (UTC)
+    fn bucket_start(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
+        let size = self.duration.num_seconds();
+        let seconds = timestamp.timestamp();
+        let aligned = seconds - seconds.rem_euclid(size);
+        Utc.timestamp_opt(aligned, 0).single().unwrap_or(timestamp)
+    }
+
+    /// Fold a base bar into this timeframe. Returns true when a bar was completed.
+    fn push(&mut self, bar: &PriceData, base_duration: Duration) -> bool {
+        let bucket = self.bucket_start(bar.timestamp);
+        let mut completed_any = false;
+
+        // A base bar from a later bucket closes the forming bar (handles feed gaps)
+        if let Some(forming) = self.forming.take() {
+            if forming.timestamp < bucket {
+                self.complete(forming);
+                completed_any = true;
+            } else {
+                self.forming = Some(forming);
+            }
+        }
+
+        match self.forming.as_mut() {
+            Some(forming) => {
+                forming.high = forming.high.max(bar.high);
+                forming.low = forming.low.min(bar.low);
+                forming.close = bar.close;
+                forming.volume += bar.volume;
+            }
+            None => {
+                self.forming = Some(PriceData {
+                    timestamp: bucket,
+                    open: bar.open,
+                    high: bar.high,
+                    low: bar.low,
+                    close: bar.close,
+                    volume: bar.volume,
+                });
+            }
+        }
+
+        // The bar is complete once the base bar covering the bucket's last interval has closed
+        if bar.timestamp + base_duration >= bucket + self.duration {
+            if let Some(forming) = self.forming.take() {
+                self.complete(forming);
+                completed_any = true;
+            }
+        }
+
+        completed_any
+    }
+
+    fn complete(&mut self, bar: PriceData) {
+        self.completed.push_back(bar);
+        if self.completed.len() > MAX_BARS_PER_TIMEFRAME {
+            self.completed.pop_front();
+        }
+    }
+
+    fn closes(&self) -> Vec<f64> {
+        self.completed.iter().map(|p| p.close.to_f64().unwrap_or(0.0)).collect()
+    }
+}
+
+/// Snapshot of a higher timeframe used for entry confirmation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeframeState {
+    pub timeframe: String,
+    pub completed_bars: usize,
+    pub last_close: Option<f64>,
+    pub lro_value: Option<f64>,
+    pub previous_lro_value: Option<f64>,
+    pub trend_slope: Option<f64>,
+}
+
+/// Result of evaluating higher-timeframe agreement for an entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfirmationResult {
+    pub allowed: bool,
+    pub agreeing_timeframes: Vec<String>,
+    pub disagreeing_timeframes: Vec<String>,
+    pub insufficient_data: Vec<String>,
+}
+
+/// Synchronized multi-timeframe series built from one base feed without lookahead.
+/// Only completed higher-timeframe bars are ever exposed to signal logic.
+#[derive(Debug, Clone)]
+pub struct MultiTimeframeSeries {
+    base_timeframe: String,
+    base_duration: Duration,
+    series: Vec<TimeframeSeries>,
+    last_timestamp: Option<DateTime<Utc>>,
+}
+
+impl MultiTimeframeSeries {
+    /// Create series for the given higher timeframes. Timeframes that are not
+    /// whole multiples of the base timeframe are ignored.
+    pub fn new(base_timeframe: &str, higher_timeframes: &[String]) -> Self {
+        let base_duration = timeframe_duration(base_timeframe).unwrap_or_else(|| Duration::minutes(1));
+        let mut series: Vec<TimeframeSeries> = higher_timeframes
+            .iter()
+            .filter_map(|tf| timeframe_duration(tf).map(|d| (tf, d)))
+            .filter(|(_, d)| *d > base_duration && d.num_seconds() % base_duration.num_seconds() == 0)
+            .map(|(tf, d)| TimeframeSeries::new(tf, d))
+            .collect();
+        series.sort_by_key(|s| s.duration);
+        series.dedup_by(|a, b| a.duration == b.duration);
+
+        Self {
+            base_timeframe: base_timeframe.to_string(),
+            base_duration,
+            series,
+            last_timestamp: None,
+        }
+    }
+
+    /// Standard 1m -> 5m -> 1h -> 4h -> 1d ladder (only levels above the base are kept)
+    pub fn with_default_ladder(base_timeframe: &str) -> Self {
+        let timeframes: Vec<String> = DEFAULT_HIGHER_TIMEFRAMES.iter().map(|s| s.to_string()).collect();
+        Self::new(base_timeframe, &timeframes)
+    }
+
+    pub fn base_timeframe(&self) -> &str {
+        &self.base_timeframe
+    }
+
+    pub fn timeframes(&self) -> Vec<String> {
+        self.series.iter().map(|s| s.timeframe.clone()).collect()
+    }
+
+    /// Feed one base bar. Out-of-order bars are ignored.
+    pub fn update(&mut self, bar: &PriceData) {
+        if let Some(last) = self.last_timestamp {
+            if bar.timestamp <= last {
+                return;
+            }
+        }
+        self.last_timestamp = Some(bar.timestamp);
+
+        let base_duration = self.base_duration;
+        for series in &mut self.series {
+            series.push(bar, base_duration);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        for series in &mut self.series {
+            series.forming = None;
+            series.completed.clear();
+        }
+        self.last_timestamp = None;
+    }
+
+    /// Completed bars for a timeframe (oldest first)
+    pub fn completed_bars(&self, timeframe: &str) -> Vec<PriceData> {
+        self.find(timeframe)
+            .map(|s| s.completed.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Most recently completed bar of a timeframe
+    pub fn last_completed(&self, timeframe: &str) -> Option<&PriceData> {
+        self.find(timeframe)?.completed.back()
+    }
+
+    /// Current state of a higher timeframe computed from completed bars only
+    pub fn timeframe_state(&self, timeframe: &str, lro_period: usize) -> Option<TimeframeState> {
+        let series = self.find(timeframe)?;
+        let closes = series.closes();
+
+        let lro_value = normalized_lro(&closes, lro_period);
+        let previous_lro_value = if closes.len() > lro_period {
+            normalized_lro(&closes[..closes.len() - 1], lro_period)
+        } else {
+            None
+        };
+        let trend_slope = if closes.len() >= lro_period {
+            regression(&closes[closes.len() - lro_period..]).map(|(slope, _)| slope)
+        } else {
+            None
+        };
+
+        Some(TimeframeState {
+            timeframe: series.timeframe.clone(),
+            completed_bars: closes.len(),
+            last_close: closes.last().copied(),
+            lro_value,
+            previous_lro_value,
+            trend_slope,
+        })
+    }
+
+    /// Evaluate whether the configured higher timeframes agree with an entry side
+    pub fn confirm_entry(&self, side: &TradeSide, confirmation: &HigherTimeframeConfirmation) -> ConfirmationResult {
+        let mut result = ConfirmationResult {
+            allowed: true,
+            agreeing_timeframes: Vec::new(),
+            disagreeing_timeframes: Vec::new(),
+            insufficient_data: Vec::new(),
+        };
+
+        if !confirmation.enabled || confirmation.timeframes.is_empty() {
+            return result;
+        }
+
+        let direction = match side {
+            TradeSide::Long | TradeSide::Buy => 1.0,
+            TradeSide::Short | TradeSide::Sell => -1.0,
+        };
+
+        for timeframe in &confirmation.timeframes {
+            let state = match self.timeframe_state(timeframe, confirmation.lro_period) {
+                Some(state) => state,
+                None => {
+                    result.insufficient_data.push(timeframe.clone());
+                    continue;
+                }
+            };
+
+            let lro_agrees = match (state.lro_value, state.previous_lro_value) {
+                (Some(current), Some(previous)) => Some((current - previous) * direction > 0.0),
+                _ => None,
+            };
+            let trend_agrees = state.trend_slope.map(|slope| slope * direction > 0.0);
+
+            let agrees = match confirmation.rule {
+                ConfirmationRule::LroAgreement => lro_agrees,
+                ConfirmationRule::TrendAgreement => trend_agrees,
+                ConfirmationRule::Either => match (lro_agrees, trend_agrees) {
+                    (Some(a), Some(b)) => Some(a || b),
+                    _ => None,
+                },
+                ConfirmationRule::Both => match (lro_agrees, trend_agrees) {
+                    (Some(a), Some(b)) => Some(a && b),
+                    _ => None,
+                },
+            };
+
+            match agrees {
+                Some(true) => result.agreeing_timeframes.push(timeframe.clone()),
+                Some(false) => result.disagreeing_timeframes.push(timeframe.clone()),
+                None => result.insufficient_data.push(timeframe.clone()),
+            }
+        }
+
+        let required = if confirmation.min_agreeing_timeframes == 0 {
+            confirmation.timeframes.len()
+        } else {
+            confirmation.min_agreeing_timeframes
+        };
+        result.allowed = result.agreeing_timeframes.len() >= required;
+        result
+    }
+
+    fn find(&self, timeframe: &str) -> Option<&TimeframeSeries> {
+        self.series.iter().find(|s| s.timeframe == timeframe)
+    }
+}
+
+impl Default for MultiTimeframeSeries {
+    fn default() -> Self {
+        Self::with_default_ladder("1m")
+    }
+}
+
+/// Least-squares slope and intercept over evenly spaced points
+pub(crate) fn regression(values: &[f64]) -> Option<(f64, f64)> {
+    let n = values.len() as f64;
+    if values.len() < 2 {
+        return None;
+    }
+
+    let sum_x: f64 = (0..values.len()).map(|i| i as f64).sum();
+    let sum_y: f64 = values.iter().sum();
+    let sum_xy: f64 = values.iter().enumerate().map(|(i, &v)| i as f64 * v).sum();
+    let sum_x2: f64 = (0..values.len()).map(|i| (i as f64).powi(2)).sum();
+
+    let denominator = n * sum_x2 - sum_x * sum_x;
+    if denominator.abs() < f64::EPSILON {
+        return None;
+    }
+
+    let slope = (n * sum_xy - sum_x * sum_y) / denominator;
+    let intercept = (sum_y - slope * sum_x) / n;
+    Some((slope, intercept))
+}
+
+/// LRO normalized by the close range, matching the bot's legacy oscillator scale (-1..1)
+pub(crate) fn normalized_lro(closes: &[f64], period: usize) -> Option<f64> {
+    if period < 2 || closes.len() < period {
+        return None;
+    }
+
+    let window = &closes[closes.len() - period..];
+    let (slope, intercept) = regression(window)?;
+    let predicted = slope * (period - 1) as f64 + intercept;
+    let current = window[period - 1];
+
+    let max = window.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let min = window.iter().copied().fold(f64::INFINITY, f64::min);
+    let range = max - min;
+
+    if range > 0.0 {
+        Some(((current - predicted) / range).clamp(-1.0, 1.0))
+    } else {
+        Some(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn bar(minute: i64, close: f64) -> PriceData {
+        let price = Decimal::from_f64(close).unwrap_or_default();
+        PriceData {
+            timestamp: Utc.timestamp_opt(1_700_000_000 - 1_700_000_000 % 86_400 + minute * 60, 0).unwrap(),
+            open: price,
+            high: price + Decimal::ONE,
+            low: price - Decimal::ONE,
+            close: price,
+            volume: Decimal::from(10),
+        }
+    }
+
+    #[test]
+    fn test_timeframe_duration_parsing() {
+        assert_eq!(timeframe_duration("5m"), Some(Duration::minutes(5)));
+        assert_eq!(timeframe_duration("4h"), Some(Duration::hours(4)));
+        assert_eq!(timeframe_duration("1d"), Some(Duration::days(1)));
+        assert_eq!(timeframe_duration("1M"), None);
+        assert_eq!(timeframe_duration(""), None);
+    }
+
+    #[test]
+    fn test_resampling_completes_bars_without_lookahead() {
+        let mut series = MultiTimeframeSeries::new("1m", &["5m".to_string()]);
+
+        for minute in 0..4 {
+            series.update(&bar(minute, 100.0 + minute as f64));
+        }
+        // Four of five minutes seen - the 5m bar must not be visible yet
+        assert!(series.completed_bars("5m").is_empty());
+
+        series.update(&bar(4, 110.0));
+        let bars = series.completed_bars("5m");
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].open, Decimal::from(100));
+        assert_eq!(bars[0].close, Decimal::from(110));
+        assert_eq!(bars[0].high, Decimal::from(111));
+        assert_eq!(bars[0].volume, Decimal::from(50));
+    }
+
+    #[test]
+    fn test_feed_gap_closes_forming_bar() {
+        let mut series = MultiTimeframeSeries::new("1m", &["5m".to_string()]);
+        series.update(&bar(0, 100.0));
+        series.update(&bar(1, 101.0));
+        // Jump straight into the next bucket
+        series.update(&bar(7, 105.0));
+
+        let bars = series.completed_bars("5m");
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].close, Decimal::from(101));
+    }
+
+    #[test]
+    fn test_confirmation_follows_higher_timeframe_trend() {
+        let mut series = MultiTimeframeSeries::new("1m", &["5m".to_string()]);
+        for minute in 0..200 {
+            series.update(&bar(minute, 100.0 + minute as f64 * 0.5));
+        }
+
+        let confirmation = HigherTimeframeConfirmation {
+            enabled: true,
+            timeframes: vec!["5m".to_string()],
+            rule: ConfirmationRule::TrendAgreement,
+            lro_period: 10,
+            min_agreeing_timeframes: 0,
+        };
+
+        assert!(series.confirm_entry(&TradeSide::Long, &confirmation).allowed);
+        assert!(!series.confirm_entry(&TradeSide::Short, &confirmation).allowed);
+    }
+
+    #[test]
+    fn test_insufficient_history_blocks_entry() {
+        let mut series = MultiTimeframeSeries::new("1m", &["1h".to_string()]);
+        for minute in 0..30 {
+            series.update(&bar(minute, 100.0));
+        }
+
+        let confirmation = HigherTimeframeConfirmation {
+            enabled: true,
+            timeframes: vec!["1h".to_string()],
+            ..Default::default()
+        };
+        let result = series.confirm_entry(&TradeSide::Long, &confirmation);
+        assert!(!result.allowed);
+        assert_eq!(result.insufficient_data, vec!["1h".to_string()]);
+    }
+
+    #[test]
+    fn test_validate_rejects_non_multiple_timeframes() {
+        let confirmation = HigherTimeframeConfirmation {
+            enabled: true,
+            timeframes: vec!["3m".to_string()],
+            ..Default::default()
+        };
+        assert!(confirmation.validate("5m").is_err());
+        assert!(HigherTimeframeConfirmation::default().validate("1m").is_ok());
+    }
+}
@@ -5,6 +5,7 @@ use std::collections::VecDeque;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
+use crate::divergence::{Divergence, DivergenceDetector, OscillatorSource};
 
 /// Incremental regression calculator for O(1) updates
 #[derive(Debug, Clone)]
@@ -129,16 +130,25 @@ impl IncrementalRegression {
         Some((slope, intercept, r_squared))
     }
     
-    /// Get current deviation from regression line
+    /// Get current deviation from regression line, in residual standard errors
     pub fn get_deviation(&mut self) -> Option<f64> {
-        if let Some((_, last_y)) = self.window.back() {
-            if let Some((slope, intercept, _)) = self.calculate() {
-                let last_x = (self.n - 1) as f64;
-                let predicted = slope * last_x + intercept;
-                return Some(last_y - predicted);
-            }
+        let (last_x, last_y) = *self.window.back()?;
+        let (slope, intercept, _) = self.calculate()?;
+        
+        if self.n < 3 {
+            return None;
         }
-        None
+        
+        let ss_res: f64 = self.window.iter()
+            .map(|(x, y)| (y - (slope * x + intercept)).powi(2))
+            .sum();
+        let standard_error = (ss_res / (self.n - 2) as f64).sqrt();
+        
+        let predicted = slope * last_x + intercept;
+        if standard_error < 1e-12 {
+            return Some(0.0);
+        }
+        Some((last_y - predicted) / standard_error)
     }
 }
 
@@ -170,10 +180,26 @@ impl KalmanFilter {
         // Measurement update
         self.k = self.p / (self.p + self.r);
         self.x += self.k * (measurement - self.x);
-        self.p = (1.0 - self.k) * self.p;
+        self.p *= 1.0 - self.k;
         
         self.x
     }
+
+    /// Update with a measurement of `regressor * x`, tracking `x` as a drifting regression
+    /// coefficient such as a hedge ratio
+    pub fn update_regression(&mut self, measurement: f64, regressor: f64) -> f64 {
+        self.p += self.q;
+
+        self.k = self.p * regressor / (regressor * regressor * self.p + self.r);
+        self.x += self.k * (measurement - regressor * self.x);
+        self.p *= 1.0 - self.k * regressor;
+
+        self.x
+    }
+
+    pub fn value(&self) -> f64 {
+        self.x
+    }
 }
 
 /// Adaptive threshold calculator based on market volatility
@@ -274,6 +300,11 @@ impl OptimizedLRO {
         }
     }
     
+    /// Regression window length
+    pub fn period(&self) -> usize {
+        self.regression.max_window_size
+    }
+    
     /// Initialize multi-timeframe analysis
     pub fn add_timeframe(&mut self, name: String, period: usize) {
         self.timeframes.insert(name, IncrementalRegression::new(period));
@@ -287,9 +318,13 @@ impl OptimizedLRO {
             return None;
         }
         
-        // Update regression with new price
+        // Capture the previous price before the window moves
+        let previous_price = self.get_last_price();
+        
+        // Update regression with new price (x advances even while warming up)
         let x = self.calculation_count as f64;
         self.regression.add_point(x, price);
+        self.calculation_count += 1;
         
         // Get deviation and regression stats
         let deviation = self.regression.get_deviation()?;
@@ -299,8 +334,8 @@ impl OptimizedLRO {
         let filtered_value = self.signal_filter.update(deviation);
         
         // Update adaptive thresholds
-        if self.calculation_count > 0 {
-            let price_change = (price - self.get_last_price()) / self.get_last_price();
+        if previous_price > 0.0 {
+            let price_change = (price - previous_price) / previous_price;
             self.thresholds.update(price_change);
         }
         
@@ -334,7 +369,6 @@ impl OptimizedLRO {
         }
         
         // Update tracking
-        self.calculation_count += 1;
         self.last_update = Utc::now();
         
         Some(signal)
@@ -384,48 +418,25 @@ impl OptimizedLRO {
             .unwrap_or(0.0)
     }
     
-    /// Check for divergences between price and oscillator
-    pub fn check_divergence(&self, recent_prices: &[f64]) -> Option<DivergenceType> {
-        if self.signal_history.len() < 10 || recent_prices.len() < 10 {
+    /// Most recent divergence between closes and the oscillator history.
+    ///
+    /// `recent_prices` are the closes fed to the latest updates, oldest first.
+    pub fn check_divergence(&self, recent_prices: &[f64]) -> Option<Divergence> {
+        let n = recent_prices.len().min(self.signal_history.len());
+        if n < 10 {
             return None;
         }
         
-        // Get recent oscillator values
-        let recent_lro: Vec<f64> = self.signal_history
+        let prices = &recent_prices[recent_prices.len() - n..];
+        let oscillator: Vec<f64> = self.signal_history
             .iter()
-            .rev()
-            .take(10)
+            .skip(self.signal_history.len() - n)
             .map(|s| s.value)
             .collect();
         
-        // Check for bullish divergence (price making lower lows, oscillator making higher lows)
-        let price_trend = Self::calculate_trend(recent_prices);
-        let lro_trend = Self::calculate_trend(&recent_lro);
-        
-        if price_trend < -0.1 && lro_trend > 0.1 {
-            return Some(DivergenceType::Bullish);
-        }
-        
-        // Check for bearish divergence (price making higher highs, oscillator making lower highs)
-        if price_trend > 0.1 && lro_trend < -0.1 {
-            return Some(DivergenceType::Bearish);
-        }
-        
-        None
-    }
-    
-    /// Simple trend calculation
-    fn calculate_trend(data: &[f64]) -> f64 {
-        if data.len() < 2 {
-            return 0.0;
-        }
-        
-        let first_half_avg: f64 = data[..data.len()/2].iter().sum::<f64>() 
-            / (data.len()/2) as f64;
-        let second_half_avg: f64 = data[data.len()/2..].iter().sum::<f64>() 
-            / (data.len() - data.len()/2) as f64;
-        
-        (second_half_avg - first_half_avg) / first_half_avg
+        DivergenceDetector::default()
+            .detect(prices, prices, &oscillator, OscillatorSource::Lro)
+            .pop()
     }
     
     /// Get statistics for monitoring
@@ -442,12 +453,6 @@ impl OptimizedLRO {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub enum DivergenceType {
-    Bullish,
-    Bearish,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LROStatistics {
     pub calculation_count: u64,
@@ -0,0 +1,578 @@
+// Order Preview
+// What-if analysis of an order before it is placed: exchange filter rounding, the fill
+// estimated from the order book, fees, and the resulting position, exposure and limit breaches
+
+use std::collections::BTreeMap;
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+
+use crate::advanced_risk_manager::HeatCheck;
+use crate::models::{OrderBookDepth, OrderRequest, OrderType, SymbolFilter, Trade, TradeSide, TradeStatus};
+use crate::risk_limits::RiskLimitProfile;
+
+/// Price, lot size and notional filters of one exchange symbol
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExchangeFilters {
+    pub tick_size: Option<Decimal>,
+    pub min_price: Option<Decimal>,
+    pub max_price: Option<Decimal>,
+    pub step_size: Option<Decimal>,
+    pub min_qty: Option<Decimal>,
+    pub max_qty: Option<Decimal>,
+    pub min_notional: Option<Decimal>,
+}
+
+impl ExchangeFilters {
+    /// Collect the PRICE_FILTER, LOT_SIZE and (MIN_)NOTIONAL filters; zero means "no limit"
+    pub fn from_symbol_filters(filters: &[SymbolFilter]) -> Self {
+        let positive = |value: Option<Decimal>| value.filter(|v| *v > Decimal::ZERO);
+        let mut result = Self::default();
+        for filter in filters {
+            match filter.filter_type.as_str() {
+                "PRICE_FILTER" => {
+                    result.tick_size = positive(filter.tick_size);
+                    result.min_price = positive(filter.min_price);
+                    result.max_price = positive(filter.max_price);
+                }
+                "LOT_SIZE" => {
+                    result.step_size = positive(filter.step_size);
+                    result.min_qty = positive(filter.min_qty);
+                    result.max_qty = positive(filter.max_qty);
+                }
+                "MIN_NOTIONAL" | "NOTIONAL" => result.min_notional = positive(filter.min_notional),
+                _ => {}
+            }
+        }
+        result
+    }
+
+    /// Round to the tick size, down for buys and up for sells so the limit never gets worse
+    pub fn round_price(&self, price: Decimal, side: &TradeSide) -> Decimal {
+        match self.tick_size {
+            Some(tick) => {
+                let ticks = price / tick;
+                let ticks = if is_buy(side) { ticks.floor() } else { ticks.ceil() };
+                (ticks * tick).normalize()
+            }
+            None => price,
+        }
+    }
+
+    /// Round down to the lot step size
+    pub fn round_quantity(&self, quantity: Decimal) -> Decimal {
+        match self.step_size {
+            Some(step) => ((quantity / step).floor() * step).normalize(),
+            None => quantity,
+        }
+    }
+
+    /// Filters a rounded price and quantity would still fail
+    pub fn violations(&self, price: Decimal, quantity: Decimal) -> Vec<String> {
+        let mut violations = Vec::new();
+        if quantity <= Decimal::ZERO {
+            violations.push("Quantity rounds to zero at the lot step size".to_string());
+        }
+        if let Some(min_qty) = self.min_qty.filter(|min| quantity < *min) {
+            violations.push(format!("Quantity {} below the minimum of {}", quantity, min_qty));
+        }
+        if let Some(max_qty) = self.max_qty.filter(|max| quantity > *max) {
+            violations.push(format!("Quantity {} above the maximum of {}", quantity, max_qty));
+        }
+        if let Some(min_price) = self.min_price.filter(|min| price < *min) {
+            violations.push(format!("Price {} below the minimum of {}", price, min_price));
+        }
+        if let Some(max_price) = self.max_price.filter(|max| price > *max) {
+            violations.push(format!("Price {} above the maximum of {}", price, max_price));
+        }
+        if let Some(min_notional) = self.min_notional.filter(|min| price * quantity < *min) {
+            violations.push(format!("Notional {} below the minimum of {}", (price * quantity).round_dp(2), min_notional));
+        }
+        violations
+    }
+}
+
+/// Maker and taker fee rates as fractions of notional
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FeeSchedule {
+    pub maker_rate: f64,
+    pub taker_rate: f64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        Self {
+            maker_rate: 0.001, // Binance spot 0.1%
+            taker_rate: 0.001,
+        }
+    }
+}
+
+/// Expected execution of an order against the visible book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillEstimate {
+    /// Best opposite-side price, or the last price without a book
+    pub reference_price: Decimal,
+    pub average_price: Decimal,
+    pub filled_quantity: Decimal,
+    /// Quantity the visible book could not fill within the limit price
+    pub unfilled_quantity: Decimal,
+    /// Adverse move of the average price from the reference, in basis points
+    pub slippage_bps: f64,
+    pub levels_consumed: usize,
+    /// Whether the order takes liquidity; limit orders that rest pay the maker fee
+    pub is_taker: bool,
+}
+
+/// Walk the opposite side of the book to fill `quantity`, stopping at the limit price if any.
+/// A limit order that does not cross the spread rests and is assumed to fill at its price
+pub fn estimate_fill(book: Option<&OrderBookDepth>, side: &TradeSide, quantity: Decimal, limit_price: Option<Decimal>, last_price: Decimal) -> FillEstimate {
+    let buy = is_buy(side);
+    let levels = book.map(|book| if buy { &book.asks } else { &book.bids })
+        .filter(|levels| !levels.is_empty());
+    let reference_price = levels.map(|levels| levels[0].price).unwrap_or(last_price);
+    let crosses = |price: Decimal| match limit_price {
+        Some(limit) => if buy { price <= limit } else { price >= limit },
+        None => true,
+    };
+
+    let levels = match levels {
+        Some(levels) if crosses(reference_price) => levels,
+        _ => {
+            // No book, or a limit order resting behind the spread
+            let average_price = limit_price.unwrap_or(last_price);
+            return FillEstimate {
+                reference_price,
+                average_price,
+                filled_quantity: quantity,
+                unfilled_quantity: Decimal::ZERO,
+                slippage_bps: slippage_bps(reference_price, average_price, buy),
+                levels_consumed: 0,
+                is_taker: limit_price.is_none() || crosses(reference_price),
+            };
+        }
+    };
+
+    let mut remaining = quantity;
+    let mut cost = Decimal::ZERO;
+    let mut levels_consumed = 0;
+    for level in levels.iter().take_while(|level| crosses(level.price)) {
+        if remaining <= Decimal::ZERO {
+            break;
+        }
+        let take = remaining.min(level.quantity);
+        cost += take * level.price;
+        remaining -= take;
+        levels_consumed += 1;
+    }
+    let filled_quantity = quantity - remaining;
+    let average_price = if filled_quantity > Decimal::ZERO { cost / filled_quantity } else { reference_price };
+    FillEstimate {
+        reference_price,
+        average_price: average_price.round_dp(8),
+        filled_quantity,
+        unfilled_quantity: remaining,
+        slippage_bps: slippage_bps(reference_price, average_price, buy),
+        levels_consumed,
+        is_taker: true,
+    }
+}
+
+/// Net position in one symbol
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PositionSnapshot {
+    /// Positive for long, negative for short
+    pub quantity: Decimal,
+    pub average_entry: Decimal,
+    /// Absolute quantity at the mark price
+    pub notional: Decimal,
+}
+
+impl PositionSnapshot {
+    /// Net the open trades in `symbol`
+    pub fn from_trades(trades: &[Trade], symbol: &str, mark_price: Decimal) -> Self {
+        trades.iter()
+            .filter(|trade| matches!(trade.status, TradeStatus::Open) && trade.symbol.eq_ignore_ascii_case(symbol))
+            .fold(Self::default(), |position, trade| position.apply(&trade.side, trade.quantity, trade.entry_price, mark_price))
+    }
+
+    /// Position after a fill of `quantity` at `price`
+    pub fn apply(&self, side: &TradeSide, quantity: Decimal, price: Decimal, mark_price: Decimal) -> Self {
+        let delta = if is_buy(side) { quantity } else { -quantity };
+        let new_quantity = self.quantity + delta;
+        let average_entry = if new_quantity.is_zero() {
+            Decimal::ZERO
+        } else if self.quantity.is_zero() || self.quantity.is_sign_negative() != new_quantity.is_sign_negative() {
+            // Opened or flipped: the remainder was all bought at the fill price
+            price
+        } else if self.quantity.is_sign_negative() == delta.is_sign_negative() {
+            (self.quantity.abs() * self.average_entry + quantity * price) / new_quantity.abs()
+        } else {
+            self.average_entry
+        };
+        Self {
+            quantity: new_quantity,
+            average_entry: average_entry.round_dp(8),
+            notional: new_quantity.abs() * mark_price,
+        }
+    }
+}
+
+/// A limit the order would break
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LimitBreach {
+    pub limit: String,
+    pub message: String,
+    pub value: Option<f64>,
+    pub limit_value: Option<f64>,
+}
+
+/// Market data, book state and limits an order is previewed against
+pub struct PreviewInputs<'a> {
+    pub filters: ExchangeFilters,
+    pub book: Option<&'a OrderBookDepth>,
+    pub last_price: Decimal,
+    pub fees: FeeSchedule,
+    /// Open paper trades across every symbol
+    pub open_trades: &'a [Trade],
+    pub equity: f64,
+    pub limits: &'a RiskLimitProfile,
+}
+
+/// What an order would do, without placing it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderPreview {
+    pub symbol: String,
+    pub side: TradeSide,
+    pub order_type: OrderType,
+    pub requested_quantity: Decimal,
+    /// Quantity rounded to the lot step size
+    pub quantity: Decimal,
+    pub requested_price: Option<Decimal>,
+    /// Limit price rounded to the tick size
+    pub price: Option<Decimal>,
+    pub filters: ExchangeFilters,
+    pub fill: FillEstimate,
+    /// Notional, fee and position are for the filled quantity only
+    pub notional: Decimal,
+    pub fee_rate: f64,
+    pub fee: Decimal,
+    pub position_before: PositionSnapshot,
+    pub position_after: PositionSnapshot,
+    pub equity: f64,
+    /// Gross open notional across symbols; other symbols are valued at their entry prices
+    pub gross_exposure_before: f64,
+    pub gross_exposure_after: f64,
+    pub leverage_after: f64,
+    /// Equity times the configured leverage limit, less gross exposure
+    pub buying_power_before: f64,
+    pub buying_power_after: f64,
+    pub heat: Option<HeatCheck>,
+    /// One-day portfolio VaR of the advanced trading book
+    pub var_before: Option<Decimal>,
+    pub var_after: Option<Decimal>,
+    pub var_delta: Option<Decimal>,
+    pub breaches: Vec<LimitBreach>,
+    /// Parts of the preview that fell back or could not be estimated
+    pub warnings: Vec<String>,
+}
+
+impl OrderPreview {
+    /// Round, fill and cost the order, work out the resulting position and exposure and check
+    /// the exchange filters, position, order size, leverage and buying power limits
+    pub fn build(order: &OrderRequest, inputs: PreviewInputs) -> Self {
+        let filters = inputs.filters;
+        let quantity = filters.round_quantity(order.quantity);
+        let price = match order.order_type {
+            OrderType::Limit => order.price.map(|price| filters.round_price(price, &order.side)),
+            OrderType::Market => None,
+        };
+        let fill = estimate_fill(inputs.book, &order.side, quantity, price, inputs.last_price);
+        let notional = fill.filled_quantity * fill.average_price;
+        let fee_rate = if fill.is_taker { inputs.fees.taker_rate } else { inputs.fees.maker_rate };
+        let fee = (notional * Decimal::from_f64(fee_rate).unwrap_or(Decimal::ZERO)).round_dp(8);
+
+        let mark_price = inputs.last_price;
+        let position_before = PositionSnapshot::from_trades(inputs.open_trades, &order.symbol, mark_price);
+        let position_after = position_before.apply(&order.side, fill.filled_quantity, fill.average_price, mark_price);
+
+        let mut other_symbols: BTreeMap<String, Decimal> = BTreeMap::new();
+        for trade in inputs.open_trades.iter().filter(|t| matches!(t.status, TradeStatus::Open) && !t.symbol.eq_ignore_ascii_case(&order.symbol)) {
+            let signed = if is_buy(&trade.side) { trade.quantity } else { -trade.quantity };
+            *other_symbols.entry(trade.symbol.to_uppercase()).or_insert(Decimal::ZERO) += signed * trade.entry_price;
+        }
+        let other_exposure = other_symbols.values().map(|v| v.abs().to_f64().unwrap_or(0.0)).sum::<f64>();
+        let gross_exposure_before = other_exposure + position_before.notional.to_f64().unwrap_or(0.0);
+        let gross_exposure_after = other_exposure + position_after.notional.to_f64().unwrap_or(0.0);
+
+        let equity = inputs.equity;
+        let fee_f64 = fee.to_f64().unwrap_or(0.0);
+        let leverage_limit = inputs.limits.global.leverage_limit;
+        let buying_power_before = equity * leverage_limit - gross_exposure_before;
+        let buying_power_after = (equity - fee_f64) * leverage_limit - gross_exposure_after;
+
+        let mut preview = Self {
+            symbol: order.symbol.to_uppercase(),
+            side: order.side.clone(),
+            order_type: order.order_type.clone(),
+            requested_quantity: order.quantity,
+            quantity,
+            requested_price: order.price,
+            price,
+            filters: filters.clone(),
+            fill,
+            notional: notional.round_dp(8),
+            fee_rate,
+            fee,
+            position_before,
+            position_after,
+            equity,
+            gross_exposure_before,
+            gross_exposure_after,
+            leverage_after: if equity > 0.0 { gross_exposure_after / equity } else { 0.0 },
+            buying_power_before,
+            buying_power_after,
+            heat: None,
+            var_before: None,
+            var_after: None,
+            var_delta: None,
+            breaches: Vec::new(),
+            warnings: Vec::new(),
+        };
+
+        for violation in filters.violations(price.unwrap_or(preview.fill.average_price), quantity) {
+            preview.add_breach("exchange_filters", violation, None, None);
+        }
+        if preview.fill.unfilled_quantity > Decimal::ZERO {
+            let message = format!(
+                "Visible book only fills {} of {}; the unfilled {} is left out of the notional, fee and position",
+                preview.fill.filled_quantity, quantity, preview.fill.unfilled_quantity
+            );
+            preview.warnings.push(message);
+        }
+        if equity <= 0.0 {
+            preview.warnings.push("No account equity, percentage limits not checked".to_string());
+            return preview;
+        }
+        if preview.is_increasing() {
+            let position_percent = gross_percent(preview.position_after.notional, equity);
+            let position_limit = inputs.limits.global.max_single_position_percent;
+            if position_percent > position_limit {
+                let message = format!("{} position would be {:.1}% of equity, above the {}% limit", preview.symbol, position_percent, position_limit);
+                preview.add_breach("global.max_single_position_percent", message, Some(position_percent), Some(position_limit));
+            }
+            let order_percent = gross_percent(preview.notional, equity);
+            let order_limit = inputs.limits.trade.max_position_size_percent;
+            if order_percent > order_limit {
+                let message = format!("Order is {:.1}% of equity, above the {}% per-trade limit", order_percent, order_limit);
+                preview.add_breach("trade.max_position_size_percent", message, Some(order_percent), Some(order_limit));
+            }
+            if preview.leverage_after > leverage_limit {
+                let message = format!("Leverage would be {:.2}x, above the {}x limit", preview.leverage_after, leverage_limit);
+                preview.add_breach("global.leverage_limit", message, Some(preview.leverage_after), Some(leverage_limit));
+            }
+            if preview.buying_power_after < 0.0 {
+                let message = format!("Order needs {:.2} more buying power than is available", -preview.buying_power_after);
+                preview.add_breach("buying_power", message, Some(preview.buying_power_after), Some(0.0));
+            }
+        }
+        preview
+    }
+
+    /// Whether the order adds to the absolute position rather than reducing it
+    pub fn is_increasing(&self) -> bool {
+        self.position_after.quantity.abs() > self.position_before.quantity.abs()
+    }
+
+    /// Loss at the stop for the added quantity; without a stop the whole notional is at risk
+    pub fn risk_amount(&self, stop_loss_percent: Option<Decimal>) -> f64 {
+        let notional = self.notional.to_f64().unwrap_or(0.0);
+        match stop_loss_percent.and_then(|percent| percent.to_f64()) {
+            Some(percent) if percent > 0.0 => notional * percent / 100.0,
+            _ => notional,
+        }
+    }
+
+    /// Record the portfolio heat check of the added exposure
+    pub fn add_heat(&mut self, check: HeatCheck) {
+        if check.blocked || check.scale < 1.0 {
+            self.add_breach("portfolio_heat", check.explanation(), Some(check.requested_notional), Some(check.allowed_notional));
+        }
+        self.heat = Some(check);
+    }
+
+    /// Record one-day VaR before and after the order against the portfolio VaR limit
+    pub fn add_var(&mut self, var_before: Decimal, var_after: Decimal, max_portfolio_var: Decimal) {
+        if var_after > max_portfolio_var && var_after > var_before {
+            let message = format!("One-day VaR would be {}, above the {} limit", var_after, max_portfolio_var);
+            self.add_breach("global.max_portfolio_var", message, var_after.to_f64(), max_portfolio_var.to_f64());
+        }
+        self.var_before = Some(var_before);
+        self.var_after = Some(var_after);
+        self.var_delta = Some(var_after - var_before);
+    }
+
+    pub fn add_breach(&mut self, limit: &str, message: String, value: Option<f64>, limit_value: Option<f64>) {
+        self.breaches.push(LimitBreach {
+            limit: limit.to_string(),
+            message,
+            value,
+            limit_value,
+        });
+    }
+}
+
+fn is_buy(side: &TradeSide) -> bool {
+    matches!(side, TradeSide::Long | TradeSide::Buy)
+}
+
+fn slippage_bps(reference: Decimal, average: Decimal, buy: bool) -> f64 {
+    if reference.is_zero() {
+        return 0.0;
+    }
+    let adverse = if buy { average - reference } else { reference - average };
+    (adverse / reference * Decimal::from(10_000)).to_f64().unwrap_or(0.0)
+}
+
+fn gross_percent(notional: Decimal, equity: f64) -> f64 {
+    notional.abs().to_f64().unwrap_or(0.0) / equity * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::OrderBookLevel;
+    use chrono::Utc;
+
+    fn dec(value: &str) -> Decimal {
+        value.parse().unwrap()
+    }
+
+    fn book() -> OrderBookDepth {
+        let level = |price: &str, quantity: &str| OrderBookLevel { price: dec(price), quantity: dec(quantity) };
+        OrderBookDepth {
+            symbol: "BTCUSDT".to_string(),
+            last_update_id: 1,
+            timestamp: Utc::now(),
+            bids: vec![level("99", "1"), level("98", "2")],
+            asks: vec![level("100", "1"), level("101", "1"), level("102", "5")],
+        }
+    }
+
+    fn order(side: TradeSide, order_type: OrderType, quantity: &str, price: Option<&str>) -> OrderRequest {
+        OrderRequest {
+            symbol: "BTCUSDT".to_string(),
+            side,
+            order_type,
+            quantity: dec(quantity),
+            price: price.map(dec),
+            take_profit_percent: None,
+            stop_loss_percent: None,
+        }
+    }
+
+    #[test]
+    fn test_filters_round_toward_a_better_price() {
+        let filters = ExchangeFilters {
+            tick_size: Some(dec("0.01")),
+            step_size: Some(dec("0.001")),
+            min_notional: Some(dec("10")),
+            ..Default::default()
+        };
+        assert_eq!(filters.round_price(dec("100.017"), &TradeSide::Buy), dec("100.01"));
+        assert_eq!(filters.round_price(dec("100.011"), &TradeSide::Sell), dec("100.02"));
+        assert_eq!(filters.round_quantity(dec("0.12345")), dec("0.123"));
+        assert!(filters.violations(dec("100"), dec("0.123")).is_empty());
+        assert_eq!(filters.violations(dec("100"), dec("0.05")).len(), 1);
+    }
+
+    #[test]
+    fn test_fill_walks_the_book() {
+        let book = book();
+        let fill = estimate_fill(Some(&book), &TradeSide::Buy, dec("2.5"), None, dec("99.5"));
+        // 1 @ 100 + 1 @ 101 + 0.5 @ 102
+        assert_eq!(fill.average_price, dec("100.8"));
+        assert_eq!(fill.levels_consumed, 3);
+        assert!((fill.slippage_bps - 80.0).abs() < 1e-9);
+        assert!(fill.is_taker);
+
+        let capped = estimate_fill(Some(&book), &TradeSide::Buy, dec("2.5"), Some(dec("101")), dec("99.5"));
+        assert_eq!(capped.unfilled_quantity, dec("0.5"));
+
+        let resting = estimate_fill(Some(&book), &TradeSide::Buy, dec("1"), Some(dec("95")), dec("99.5"));
+        assert!(!resting.is_taker);
+        assert_eq!(resting.average_price, dec("95"));
+    }
+
+    #[test]
+    fn test_preview_position_and_breaches() {
+        let book = book();
+        let limits = RiskLimitProfile::default();
+        let held = Trade {
+            id: uuid::Uuid::new_v4(),
+            symbol: "BTCUSDT".to_string(),
+            side: TradeSide::Long,
+            order_type: OrderType::Market,
+            quantity: dec("1"),
+            entry_price: dec("90"),
+            exit_price: None,
+            take_profit: None,
+            stop_loss: None,
+            status: TradeStatus::Open,
+            created_at: Utc::now(),
+            closed_at: None,
+            pnl: None,
+        };
+        let inputs = |trades| PreviewInputs {
+            filters: ExchangeFilters::default(),
+            book: Some(&book),
+            last_price: dec("99.5"),
+            fees: FeeSchedule::default(),
+            open_trades: trades,
+            equity: 1000.0,
+            limits: &limits,
+        };
+
+        let trades = [held];
+        let buy = OrderPreview::build(&order(TradeSide::Buy, OrderType::Market, "1", None), inputs(&trades));
+        assert_eq!(buy.position_after.quantity, dec("2"));
+        assert_eq!(buy.position_after.average_entry, dec("95"));
+        assert_eq!(buy.fee, dec("0.1"));
+        // 10% order against the 5% per-trade limit
+        assert!(buy.breaches.iter().any(|b| b.limit == "trade.max_position_size_percent"));
+
+        let sell = OrderPreview::build(&order(TradeSide::Sell, OrderType::Market, "1", None), inputs(&trades));
+        assert!(sell.position_after.quantity.is_zero());
+        assert!(!sell.is_increasing());
+        assert!(sell.breaches.is_empty());
+        assert!(sell.buying_power_after > sell.buying_power_before);
+    }
+
+    #[test]
+    fn test_preview_on_thin_book_costs_only_the_filled_quantity() {
+        let book = book();
+        let limits = RiskLimitProfile::default();
+        let inputs = PreviewInputs {
+            filters: ExchangeFilters::default(),
+            book: Some(&book),
+            last_price: dec("99.5"),
+            fees: FeeSchedule::default(),
+            open_trades: &[],
+            equity: 100_000.0,
+            limits: &limits,
+        };
+
+        // Bids hold 3, so only 1 @ 99 + 2 @ 98 of the 5 sold fill
+        let preview = OrderPreview::build(&order(TradeSide::Sell, OrderType::Market, "5", None), inputs);
+        assert_eq!(preview.quantity, dec("5"));
+        assert_eq!(preview.fill.filled_quantity, dec("3"));
+        assert_eq!(preview.fill.unfilled_quantity, dec("2"));
+        // Average price is rounded to 8 places
+        assert!((preview.notional - dec("295")).abs() < dec("0.000001"));
+        assert!((preview.fee - dec("0.295")).abs() < dec("0.000001"));
+        assert_eq!(preview.position_after.quantity, dec("-3"));
+        assert_eq!(preview.position_after.notional, dec("298.5"));
+        assert!(preview.warnings.iter().any(|w| w.contains("unfilled 2")));
+    }
+}
@@ -0,0 +1,1227 @@
+// Pairs Trading
+// Engle-Granger cointegration scan over stored klines, OLS or Kalman hedge ratios and a
+// z-score spread strategy shared by the pair backtester and paper execution
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::errors::{TradingError, TradingResult};
+use crate::kline_store::KlineStore;
+use crate::models::{KlineData, OrderRequest, OrderType, TradeSide};
+use crate::multi_timeframe::timeframe_duration;
+use crate::optimized_lro::KalmanFilter;
+
+/// Fewest aligned closes a pair is tested or traded on
+const MIN_OBSERVATIONS: usize = 60;
+/// Largest ADF lag order considered when choosing the lag by AIC
+const MAX_ADF_LAGS: usize = 12;
+
+/// Pairs traded through paper execution, shared by the pairs commands
+pub type SharedPairsDesk = Arc<Mutex<PairsDesk>>;
+
+/// Owner the pairs desk reports its equity under to the drawdown governor
+pub const DESK_OWNER: &str = "pairs_desk";
+/// Paper capital the desk's P/L is measured against, the pair backtest's default capital
+const DESK_CAPITAL: f64 = 10_000.0;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum HedgeRatioMethod {
+    /// Fixed ratio from a regression of log prices over the formation window
+    #[default]
+    Ols,
+    /// Ratio re-estimated every bar by a Kalman filter
+    Kalman,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairsConfig {
+    /// Kline interval pairs are tested and traded on
+    pub interval: String,
+    /// Bars the hedge ratio and cointegration test are estimated on
+    pub formation_bars: usize,
+    pub hedge_method: HedgeRatioMethod,
+    /// Drift allowed in the Kalman hedge ratio per bar
+    pub kalman_process_noise: f64,
+    pub kalman_measurement_noise: f64,
+    /// Spreads in the rolling mean and deviation of the z-score
+    pub zscore_window: usize,
+    pub entry_zscore: f64,
+    /// Close once the z-score reverts inside this band
+    pub exit_zscore: f64,
+    /// Close when the spread keeps diverging past this z-score; entries stop short of it
+    pub stop_zscore: f64,
+    /// Close after this many spread half-lives; 0 disables the time stop
+    pub max_holding_half_lives: f64,
+    /// Engle-Granger significance level: 0.01, 0.05 or 0.10
+    pub significance: f64,
+    /// Scans skip pairs whose log returns correlate less than this
+    pub min_return_correlation: f64,
+    /// Pairs reverting slower than this many bars are not worth trading
+    pub max_half_life_bars: f64,
+    /// Gross notional of both legs, in quote currency
+    pub pair_notional: f64,
+    pub commission_rate: f64,
+    pub slippage_rate: f64,
+}
+
+impl Default for PairsConfig {
+    fn default() -> Self {
+        Self {
+            interval: "1h".to_string(),
+            formation_bars: 500,
+            hedge_method: HedgeRatioMethod::Ols,
+            kalman_process_noise: 1e-5,
+            kalman_measurement_noise: 1e-3,
+            zscore_window: 50,
+            entry_zscore: 2.0,
+            exit_zscore: 0.5,
+            stop_zscore: 4.0,
+            max_holding_half_lives: 3.0,
+            significance: 0.05,
+            min_return_correlation: 0.5,
+            max_half_life_bars: 100.0,
+            pair_notional: 1000.0,
+            // Same costs as the single-asset backtester
+            commission_rate: 0.001,
+            slippage_rate: 0.0005,
+        }
+    }
+}
+
+impl PairsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if timeframe_duration(&self.interval).is_none() {
+            return Err(format!("Unsupported interval {}", self.interval));
+        }
+        if self.formation_bars < MIN_OBSERVATIONS {
+            return Err(format!("Formation window must be at least {} bars", MIN_OBSERVATIONS));
+        }
+        if self.zscore_window < 10 || self.zscore_window > self.formation_bars {
+            return Err("Z-score window must be between 10 bars and the formation window".to_string());
+        }
+        if !(0.0 <= self.exit_zscore && self.exit_zscore < self.entry_zscore && self.entry_zscore < self.stop_zscore) {
+            return Err("Z-scores must satisfy 0 <= exit < entry < stop".to_string());
+        }
+        if self.max_holding_half_lives < 0.0 {
+            return Err("Maximum holding time cannot be negative".to_string());
+        }
+        if ![0.01, 0.05, 0.10].contains(&self.significance) {
+            return Err("Significance must be 0.01, 0.05 or 0.10".to_string());
+        }
+        if !(-1.0..=1.0).contains(&self.min_return_correlation) {
+            return Err("Minimum return correlation must be between -1 and 1".to_string());
+        }
+        if self.max_half_life_bars <= 0.0 {
+            return Err("Maximum half-life must be positive".to_string());
+        }
+        if self.pair_notional <= 0.0 {
+            return Err("Pair notional must be positive".to_string());
+        }
+        if self.kalman_process_noise <= 0.0 || self.kalman_measurement_noise <= 0.0 {
+            return Err("Kalman noise variances must be positive".to_string());
+        }
+        if self.commission_rate < 0.0 || self.slippage_rate < 0.0 {
+            return Err("Costs cannot be negative".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Closes of two symbols on the bars both have, oldest first
+#[derive(Debug, Clone, Default)]
+pub struct PairPrices {
+    pub times: Vec<DateTime<Utc>>,
+    pub y: Vec<f64>,
+    pub x: Vec<f64>,
+}
+
+impl PairPrices {
+    pub fn align(klines_y: &[KlineData], klines_x: &[KlineData]) -> Self {
+        let closes_x: HashMap<DateTime<Utc>, f64> = klines_x.iter()
+            .filter_map(|k| Some((k.open_time, k.close.to_f64().filter(|c| *c > 0.0)?)))
+            .collect();
+        let mut prices = Self::default();
+        for kline in klines_y {
+            let (Some(y), Some(x)) = (kline.close.to_f64().filter(|c| *c > 0.0), closes_x.get(&kline.open_time)) else {
+                continue;
+            };
+            prices.times.push(kline.open_time);
+            prices.y.push(y);
+            prices.x.push(*x);
+        }
+        prices
+    }
+
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    pub fn slice(&self, range: std::ops::Range<usize>) -> Self {
+        Self {
+            times: self.times[range.clone()].to_vec(),
+            y: self.y[range.clone()].to_vec(),
+            x: self.x[range].to_vec(),
+        }
+    }
+}
+
+/// MacKinnon (2010) Engle-Granger critical values for two variables with a constant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalValues {
+    pub one_percent: f64,
+    pub five_percent: f64,
+    pub ten_percent: f64,
+}
+
+impl CriticalValues {
+    pub fn engle_granger(observations: usize) -> Self {
+        let t = observations as f64;
+        let value = |c: [f64; 3]| c[0] + c[1] / t + c[2] / (t * t);
+        Self {
+            one_percent: value([-3.89644, -10.9519, -22.527]),
+            five_percent: value([-3.33613, -6.1101, -6.823]),
+            ten_percent: value([-3.04445, -4.2412, -2.720]),
+        }
+    }
+
+    fn at(&self, significance: f64) -> f64 {
+        if significance <= 0.01 {
+            self.one_percent
+        } else if significance <= 0.05 {
+            self.five_percent
+        } else {
+            self.ten_percent
+        }
+    }
+}
+
+/// Cointegration test, hedge ratio and spread statistics of `symbol_y` against `symbol_x`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairStatistics {
+    pub symbol_y: String,
+    pub symbol_x: String,
+    pub hedge_method: HedgeRatioMethod,
+    /// Units of log `symbol_x` per unit of log `symbol_y`; the latest estimate for Kalman
+    pub hedge_ratio: f64,
+    /// Intercept of the log-price regression
+    pub intercept: f64,
+    /// ADF t-statistic of the regression residuals
+    pub adf_statistic: f64,
+    pub adf_lags: usize,
+    pub critical_values: CriticalValues,
+    pub cointegrated: bool,
+    /// Bars for the spread to close half its distance to the mean; None when it does not revert
+    pub half_life_bars: Option<f64>,
+    pub return_correlation: f64,
+    pub spread_mean: f64,
+    pub spread_std: f64,
+    pub current_zscore: Option<f64>,
+    pub observations: usize,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
+impl PairStatistics {
+    pub fn estimate(symbol_y: &str, symbol_x: &str, prices: &PairPrices, config: &PairsConfig) -> TradingResult<Self> {
+        Ok(fit_pair(symbol_y, symbol_x, prices, config)?.0)
+    }
+
+    /// Cointegrated and reverting fast enough to trade
+    pub fn is_tradable(&self, config: &PairsConfig) -> bool {
+        self.cointegrated && self.half_life_bars.is_some_and(|h| h <= config.max_half_life_bars)
+    }
+}
+
+/// Spread of log prices under a fixed or Kalman hedge ratio
+#[derive(Debug, Clone)]
+enum HedgeModel {
+    Ols { intercept: f64, ratio: f64 },
+    /// Tracks log(y / base_y) = ratio * log(x / base_x), so no intercept is needed
+    Kalman { filter: KalmanFilter, base_y: f64, base_x: f64 },
+}
+
+impl HedgeModel {
+    fn ratio(&self) -> f64 {
+        match self {
+            HedgeModel::Ols { ratio, .. } => *ratio,
+            HedgeModel::Kalman { filter, .. } => filter.value(),
+        }
+    }
+
+    /// Spread of the new closes under the current ratio, then update the ratio with them
+    fn step(&mut self, y: f64, x: f64) -> f64 {
+        match self {
+            HedgeModel::Ols { intercept, ratio } => y.ln() - *intercept - *ratio * x.ln(),
+            HedgeModel::Kalman { filter, base_y, base_x } => {
+                let (log_y, log_x) = ((y / *base_y).ln(), (x / *base_x).ln());
+                let spread = log_y - filter.value() * log_x;
+                filter.update_regression(log_y, log_x);
+                spread
+            }
+        }
+    }
+}
+
+fn fit_pair(
+    symbol_y: &str,
+    symbol_x: &str,
+    prices: &PairPrices,
+    config: &PairsConfig
+) -> TradingResult<(PairStatistics, HedgeModel, Vec<f64>)> {
+    let n = prices.len();
+    if n < MIN_OBSERVATIONS {
+        return Err(TradingError::validation_error(
+            "prices".to_string(),
+            format!("{}/{} has {} aligned bars, at least {} are needed", symbol_y, symbol_x, n, MIN_OBSERVATIONS),
+            None
+        ));
+    }
+
+    let log_y: Vec<f64> = prices.y.iter().map(|p| p.ln()).collect();
+    let log_x: Vec<f64> = prices.x.iter().map(|p| p.ln()).collect();
+    let regression = ols(&log_y, &[vec![1.0; n], log_x.clone()]).ok_or_else(|| TradingError::validation_error(
+        "prices".to_string(),
+        format!("{} prices are constant, no hedge ratio can be estimated", symbol_x),
+        None
+    ))?;
+    let (intercept, ratio) = (regression.coefficients[0], regression.coefficients[1]);
+    let adf = adf_test(&regression.residuals).ok_or_else(|| TradingError::validation_error(
+        "prices".to_string(),
+        format!("{}/{} residuals are degenerate, the ADF test cannot be run", symbol_y, symbol_x),
+        None
+    ))?;
+    let critical_values = CriticalValues::engle_granger(n);
+
+    let mut model = match config.hedge_method {
+        HedgeRatioMethod::Ols => HedgeModel::Ols { intercept, ratio },
+        HedgeRatioMethod::Kalman => {
+            let mut filter = KalmanFilter::new(config.kalman_process_noise, config.kalman_measurement_noise, ratio);
+            // Start from the OLS ratio with the filter settled rather than fully uncertain
+            filter.update_regression(ratio, 1.0);
+            HedgeModel::Kalman { filter, base_y: prices.y[0], base_x: prices.x[0] }
+        }
+    };
+    let spreads: Vec<f64> = prices.y.iter().zip(&prices.x).map(|(y, x)| model.step(*y, *x)).collect();
+    let returns_y: Vec<f64> = log_y.windows(2).map(|w| w[1] - w[0]).collect();
+    let returns_x: Vec<f64> = log_x.windows(2).map(|w| w[1] - w[0]).collect();
+
+    let statistics = PairStatistics {
+        symbol_y: symbol_y.to_string(),
+        symbol_x: symbol_x.to_string(),
+        hedge_method: config.hedge_method,
+        hedge_ratio: model.ratio(),
+        intercept,
+        adf_statistic: adf.statistic,
+        adf_lags: adf.lags,
+        cointegrated: adf.statistic < critical_values.at(config.significance),
+        critical_values,
+        half_life_bars: half_life(&spreads),
+        return_correlation: correlation(&returns_y, &returns_x),
+        spread_mean: mean(&spreads),
+        spread_std: std_dev(&spreads),
+        current_zscore: zscore(&spreads[n.saturating_sub(config.zscore_window)..]),
+        observations: n,
+        start: prices.times[0],
+        end: prices.times[n - 1],
+    };
+    Ok((statistics, model, spreads))
+}
+
+/// Test every pair of `symbols` on their latest `formation_bars` stored klines. Pairs whose
+/// returns correlate less than the configured minimum are skipped; each remaining pair is
+/// tested both ways round and the orientation with the stronger ADF statistic kept.
+/// Strongest first
+pub fn scan_pairs(store: &KlineStore, symbols: &[String], config: &PairsConfig) -> TradingResult<Vec<PairStatistics>> {
+    let mut history = Vec::new();
+    for symbol in symbols {
+        let klines = store.load(symbol, &config.interval)?;
+        if klines.len() >= MIN_OBSERVATIONS {
+            let start = klines.len().saturating_sub(config.formation_bars);
+            history.push((symbol.clone(), klines[start..].to_vec()));
+        }
+    }
+
+    let mut results = Vec::new();
+    for i in 0..history.len() {
+        for j in (i + 1)..history.len() {
+            let (symbol_a, klines_a) = &history[i];
+            let (symbol_b, klines_b) = &history[j];
+            let prices = PairPrices::align(klines_a, klines_b);
+            if prices.len() < MIN_OBSERVATIONS {
+                continue;
+            }
+            let returns_a: Vec<f64> = prices.y.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+            let returns_b: Vec<f64> = prices.x.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+            if correlation(&returns_a, &returns_b) < config.min_return_correlation {
+                continue;
+            }
+            let swapped = PairPrices { times: prices.times.clone(), y: prices.x.clone(), x: prices.y.clone() };
+            let best = [
+                PairStatistics::estimate(symbol_a, symbol_b, &prices, config),
+                PairStatistics::estimate(symbol_b, symbol_a, &swapped, config),
+            ]
+            .into_iter()
+            .filter_map(|statistics| statistics.ok())
+            .min_by(|a, b| a.adf_statistic.total_cmp(&b.adf_statistic));
+            results.extend(best);
+        }
+    }
+
+    results.sort_by(|a, b| a.adf_statistic.total_cmp(&b.adf_statistic));
+    Ok(results)
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SpreadDirection {
+    /// Long `symbol_y`, hedged with `symbol_x`; entered when the spread is cheap
+    Long,
+    /// Short `symbol_y`, hedged with `symbol_x`; entered when the spread is rich
+    Short,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PairExitReason {
+    MeanReversion,
+    StopLoss,
+    MaxHoldingTime,
+}
+
+/// One side of a two-legged pair order, priced at the bar close
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairLeg {
+    pub symbol: String,
+    pub side: TradeSide,
+    pub quantity: Decimal,
+    pub price: Decimal,
+}
+
+impl PairLeg {
+    pub fn order(&self) -> OrderRequest {
+        OrderRequest {
+            symbol: self.symbol.clone(),
+            side: self.side.clone(),
+            order_type: OrderType::Market,
+            quantity: self.quantity,
+            price: None,
+            take_profit_percent: None,
+            stop_loss_percent: None,
+        }
+    }
+
+    fn is_buy(&self) -> bool {
+        matches!(self.side, TradeSide::Buy | TradeSide::Long)
+    }
+
+    fn closing(&self, price: Decimal) -> Self {
+        Self {
+            symbol: self.symbol.clone(),
+            side: if self.is_buy() { TradeSide::Sell } else { TradeSide::Buy },
+            quantity: self.quantity,
+            price,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairPosition {
+    pub direction: SpreadDirection,
+    pub entry_time: DateTime<Utc>,
+    pub entry_zscore: f64,
+    pub hedge_ratio: f64,
+    pub legs: Vec<PairLeg>,
+    pub bars_held: usize,
+    /// Paper trades opened for the legs
+    #[serde(default)]
+    pub trade_ids: Vec<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum PairSignal {
+    Enter {
+        direction: SpreadDirection,
+        zscore: f64,
+        legs: Vec<PairLeg>,
+    },
+    Exit {
+        reason: PairExitReason,
+        zscore: f64,
+        legs: Vec<PairLeg>,
+        position: PairPosition,
+    },
+}
+
+/// Z-score state machine for one pair, fed one closed bar at a time
+#[derive(Debug, Clone)]
+pub struct PairTrader {
+    pub statistics: PairStatistics,
+    pub config: PairsConfig,
+    model: HedgeModel,
+    spreads: VecDeque<f64>,
+    position: Option<PairPosition>,
+    last_bar: Option<DateTime<Utc>>,
+    last_zscore: Option<f64>,
+    last_prices: Option<(Decimal, Decimal)>,
+}
+
+impl PairTrader {
+    /// Estimate the pair on `formation` and warm the z-score window with its spreads
+    pub fn fit(symbol_y: &str, symbol_x: &str, formation: &PairPrices, config: PairsConfig) -> TradingResult<Self> {
+        let (statistics, model, spreads) = fit_pair(symbol_y, symbol_x, formation, &config)?;
+        let window_start = spreads.len().saturating_sub(config.zscore_window);
+        Ok(Self {
+            last_bar: formation.times.last().copied(),
+            last_zscore: statistics.current_zscore,
+            spreads: spreads[window_start..].iter().copied().collect(),
+            statistics,
+            config,
+            model,
+            position: None,
+            last_prices: None,
+        })
+    }
+
+    pub fn symbol_y(&self) -> &str {
+        &self.statistics.symbol_y
+    }
+
+    pub fn symbol_x(&self) -> &str {
+        &self.statistics.symbol_x
+    }
+
+    pub fn hedge_ratio(&self) -> f64 {
+        self.model.ratio()
+    }
+
+    pub fn zscore(&self) -> Option<f64> {
+        self.last_zscore
+    }
+
+    pub fn position(&self) -> Option<&PairPosition> {
+        self.position.as_ref()
+    }
+
+    pub fn last_bar(&self) -> Option<DateTime<Utc>> {
+        self.last_bar
+    }
+
+    /// Attach the paper trades that filled the open position's legs
+    pub fn set_position_trades(&mut self, trade_ids: Vec<Uuid>) {
+        if let Some(position) = self.position.as_mut() {
+            position.trade_ids = trade_ids;
+        }
+    }
+
+    /// Record the paper trade that filled each entry leg, in leg order. If any leg failed to
+    /// fill the entry is abandoned and the trader left flat; the trades that did fill are
+    /// returned with the legs that close them at the entry price
+    pub fn settle_entry(&mut self, fills: &[Option<Uuid>]) -> Vec<(Uuid, PairLeg)> {
+        let Some(position) = self.position.as_ref() else {
+            return Vec::new();
+        };
+        if fills.len() == position.legs.len() && fills.iter().all(Option::is_some) {
+            self.set_position_trades(fills.iter().flatten().copied().collect());
+            return Vec::new();
+        }
+        self.position.take()
+            .map(|position| position.legs.iter()
+                .zip(fills)
+                .filter_map(|(leg, fill)| fill.map(|id| (id, leg.closing(leg.price))))
+                .collect())
+            .unwrap_or_default()
+    }
+
+    /// Drop the open position without trading, returning it so its paper trades can be closed
+    pub fn flatten(&mut self) -> Option<PairPosition> {
+        self.position.take()
+    }
+
+    /// Open P/L of the position marked at the last bar's closes
+    pub fn unrealized_pnl(&self) -> f64 {
+        let (Some(position), Some((price_y, price_x))) = (self.position.as_ref(), self.last_prices) else {
+            return 0.0;
+        };
+        position.legs.iter()
+            .map(|leg| {
+                let price = if leg.symbol == self.statistics.symbol_y { price_y } else { price_x };
+                let pnl = (price - leg.price) * leg.quantity;
+                if leg.is_buy() { pnl } else { -pnl }
+            })
+            .sum::<Decimal>()
+            .to_f64()
+            .unwrap_or(0.0)
+    }
+
+    /// Process a closed bar. Bars at or before the last one seen are ignored. With
+    /// `allow_entries` false open positions still exit but no new ones are opened
+    pub fn on_bar(&mut self, time: DateTime<Utc>, price_y: Decimal, price_x: Decimal, allow_entries: bool) -> Option<PairSignal> {
+        if self.last_bar.is_some_and(|last| time <= last) {
+            return None;
+        }
+        let (y, x) = (price_y.to_f64()?, price_x.to_f64()?);
+        if y <= 0.0 || x <= 0.0 {
+            return None;
+        }
+        self.last_bar = Some(time);
+        self.last_prices = Some((price_y, price_x));
+
+        let spread = self.model.step(y, x);
+        self.spreads.push_back(spread);
+        while self.spreads.len() > self.config.zscore_window {
+            self.spreads.pop_front();
+        }
+        let spreads: Vec<f64> = self.spreads.iter().copied().collect();
+        self.last_zscore = zscore(&spreads);
+        let z = self.last_zscore?;
+
+        if let Some(position) = self.position.as_mut() {
+            position.bars_held += 1;
+            let (reverted, stopped) = match position.direction {
+                SpreadDirection::Long => (z >= -self.config.exit_zscore, z <= -self.config.stop_zscore),
+                SpreadDirection::Short => (z <= self.config.exit_zscore, z >= self.config.stop_zscore),
+            };
+            let max_bars = self.statistics.half_life_bars
+                .filter(|_| self.config.max_holding_half_lives > 0.0)
+                .map(|half_life| (half_life * self.config.max_holding_half_lives).ceil() as usize);
+            let reason = if stopped {
+                Some(PairExitReason::StopLoss)
+            } else if reverted {
+                Some(PairExitReason::MeanReversion)
+            } else if max_bars.is_some_and(|max| position.bars_held >= max) {
+                Some(PairExitReason::MaxHoldingTime)
+            } else {
+                None
+            };
+
+            return reason.and_then(|reason| {
+                let position = self.position.take()?;
+                let legs = position.legs.iter()
+                    .map(|leg| leg.closing(if leg.symbol == self.statistics.symbol_y { price_y } else { price_x }))
+                    .collect();
+                Some(PairSignal::Exit { reason, zscore: z, legs, position })
+            });
+        }
+
+        if !allow_entries || z.abs() < self.config.entry_zscore || z.abs() >= self.config.stop_zscore {
+            return None;
+        }
+        let direction = if z > 0.0 { SpreadDirection::Short } else { SpreadDirection::Long };
+        let legs = self.entry_legs(direction, y, x, price_y, price_x)?;
+        self.position = Some(PairPosition {
+            direction,
+            entry_time: time,
+            entry_zscore: z,
+            hedge_ratio: self.model.ratio(),
+            legs: legs.clone(),
+            bars_held: 0,
+            trade_ids: Vec::new(),
+        });
+        Some(PairSignal::Enter { direction, zscore: z, legs })
+    }
+
+    /// Split the pair notional so the `symbol_x` leg is `hedge_ratio` times the `symbol_y` leg
+    fn entry_legs(&self, direction: SpreadDirection, y: f64, x: f64, price_y: Decimal, price_x: Decimal) -> Option<Vec<PairLeg>> {
+        let ratio = self.model.ratio();
+        let notional_y = self.config.pair_notional / (1.0 + ratio.abs());
+        let notional_x = notional_y * ratio.abs();
+        let buy_y = direction == SpreadDirection::Long;
+        // A positive ratio hedges with the opposite side, a negative one with the same side
+        let buy_x = if ratio >= 0.0 { !buy_y } else { buy_y };
+        let side = |buy: bool| if buy { TradeSide::Buy } else { TradeSide::Sell };
+
+        let quantity_y = Decimal::from_f64(notional_y / y)?.round_dp(8);
+        let quantity_x = Decimal::from_f64(notional_x / x)?.round_dp(8);
+        if quantity_y.is_zero() || quantity_x.is_zero() {
+            return None;
+        }
+        Some(vec![
+            PairLeg { symbol: self.statistics.symbol_y.clone(), side: side(buy_y), quantity: quantity_y, price: price_y },
+            PairLeg { symbol: self.statistics.symbol_x.clone(), side: side(buy_x), quantity: quantity_x, price: price_x },
+        ])
+    }
+}
+
+/// Pairs traded through paper execution, keyed by "Y/X"
+#[derive(Debug)]
+pub struct PairsDesk {
+    traders: HashMap<String, PairTrader>,
+    realized_pnl: f64,
+}
+
+impl Default for PairsDesk {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PairsDesk {
+    pub fn new() -> Self {
+        Self { traders: HashMap::new(), realized_pnl: 0.0 }
+    }
+
+    pub fn pair_id(symbol_y: &str, symbol_x: &str) -> String {
+        format!("{}/{}", symbol_y, symbol_x)
+    }
+
+    /// Register a pair, replacing any flat trader for it. Fails while the pair has an open position
+    pub fn add(&mut self, trader: PairTrader) -> TradingResult<String> {
+        let id = Self::pair_id(trader.symbol_y(), trader.symbol_x());
+        if self.traders.get(&id).is_some_and(|existing| existing.position().is_some()) {
+            return Err(TradingError::validation_error(
+                "pair".to_string(),
+                format!("{} has an open position, close it before refitting", id),
+                Some(id)
+            ));
+        }
+        self.traders.insert(id.clone(), trader);
+        Ok(id)
+    }
+
+    pub fn remove(&mut self, id: &str) -> Option<PairTrader> {
+        self.traders.remove(id)
+    }
+
+    pub fn get(&self, id: &str) -> Option<&PairTrader> {
+        self.traders.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut PairTrader> {
+        self.traders.get_mut(id)
+    }
+
+    pub fn traders(&self) -> impl Iterator<Item = (&String, &PairTrader)> {
+        self.traders.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.traders.is_empty()
+    }
+
+    /// Add the P/L of closed leg trades to the desk's realized P/L
+    pub fn record_realized(&mut self, pnl: f64) {
+        if pnl.is_finite() {
+            self.realized_pnl += pnl;
+        }
+    }
+
+    /// Paper capital plus realized and open P/L, as reported to the drawdown governor
+    pub fn equity(&self) -> f64 {
+        DESK_CAPITAL + self.realized_pnl + self.traders.values().map(PairTrader::unrealized_pnl).sum::<f64>()
+    }
+
+    /// Drop every open position without trading, returning them by pair id so their paper
+    /// trades can be closed
+    pub fn flatten_all(&mut self) -> Vec<(String, PairPosition)> {
+        self.traders.iter_mut()
+            .filter_map(|(id, trader)| trader.flatten().map(|position| (id.clone(), position)))
+            .collect()
+    }
+}
+
+/// Round trip of a pair in the backtester
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairTrade {
+    pub direction: SpreadDirection,
+    pub entry_time: DateTime<Utc>,
+    pub exit_time: DateTime<Utc>,
+    pub entry_zscore: f64,
+    pub exit_zscore: f64,
+    pub hedge_ratio: f64,
+    pub bars_held: usize,
+    /// Net of commission and slippage
+    pub pnl: f64,
+    /// Net profit over the gross notional of both legs, in percent
+    pub return_percent: f64,
+    pub costs: f64,
+    pub exit_reason: PairExitReason,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairsBacktestResult {
+    /// Estimated on the formation window before trading starts
+    pub statistics: PairStatistics,
+    pub trades: Vec<PairTrade>,
+    pub initial_capital: f64,
+    pub final_equity: f64,
+    pub total_return: f64,
+    pub annualized_return: f64,
+    pub sharpe_ratio: f64,
+    pub max_drawdown: f64,
+    pub win_rate: f64,
+    /// Share of trading bars with a position open
+    pub exposure: f64,
+    pub trading_bars: usize,
+    pub equity_curve: Vec<(DateTime<Utc>, f64)>,
+}
+
+/// Fit the pair on the first `formation_bars` of `prices` and trade the rest bar by bar with
+/// the same state machine as paper trading. Fills take the slippage rate against the close
+/// and pay commission on each leg; a position still open at the end is closed on the last bar
+pub fn backtest_pair(
+    symbol_y: &str,
+    symbol_x: &str,
+    prices: &PairPrices,
+    config: &PairsConfig,
+    initial_capital: f64
+) -> TradingResult<PairsBacktestResult> {
+    config.validate().map_err(|e| TradingError::config_error("pairs_config".to_string(), e))?;
+    if prices.len() <= config.formation_bars {
+        return Err(TradingError::validation_error(
+            "prices".to_string(),
+            format!("{} aligned bars leave nothing to trade after the {}-bar formation window", prices.len(), config.formation_bars),
+            None
+        ));
+    }
+    let bar = timeframe_duration(&config.interval).unwrap_or_else(|| Duration::hours(1));
+
+    let mut trader = PairTrader::fit(symbol_y, symbol_x, &prices.slice(0..config.formation_bars), config.clone())?;
+    let statistics = trader.statistics.clone();
+    let fill = |leg: &PairLeg| {
+        let price = leg.price.to_f64().unwrap_or(0.0);
+        if leg.is_buy() { price * (1.0 + config.slippage_rate) } else { price * (1.0 - config.slippage_rate) }
+    };
+    let signed_quantity = |leg: &PairLeg| {
+        let quantity = leg.quantity.to_f64().unwrap_or(0.0);
+        if leg.is_buy() { quantity } else { -quantity }
+    };
+
+    let mut cash = initial_capital;
+    // (signed quantity, symbol is y) of open legs, and the commission paid to open them
+    let mut holdings: Vec<(f64, bool)> = Vec::new();
+    let mut entry_cash_flow = 0.0;
+    let mut trades = Vec::new();
+    let mut equity_curve = Vec::with_capacity(prices.len() - config.formation_bars);
+    let mut bars_in_market = 0;
+
+    for t in config.formation_bars..prices.len() {
+        let last_bar = t == prices.len() - 1;
+        let (price_y, price_x) = match (Decimal::from_f64(prices.y[t]), Decimal::from_f64(prices.x[t])) {
+            (Some(y), Some(x)) => (y, x),
+            _ => continue,
+        };
+        let mut signal = trader.on_bar(prices.times[t], price_y, price_x, !last_bar);
+        if last_bar && signal.is_none() {
+            // Close out at the final close
+            if let Some(position) = trader.position.take() {
+                let legs = position.legs.iter()
+                    .map(|leg| leg.closing(if leg.symbol == symbol_y { price_y } else { price_x }))
+                    .collect();
+                signal = Some(PairSignal::Exit {
+                    reason: PairExitReason::MaxHoldingTime,
+                    zscore: trader.zscore().unwrap_or(0.0),
+                    legs,
+                    position,
+                });
+            }
+        }
+
+        match signal {
+            Some(PairSignal::Enter { legs, .. }) => {
+                entry_cash_flow = 0.0;
+                for leg in &legs {
+                    let notional = signed_quantity(leg) * fill(leg);
+                    entry_cash_flow -= notional + notional.abs() * config.commission_rate;
+                    holdings.push((signed_quantity(leg), leg.symbol == symbol_y));
+                }
+                cash += entry_cash_flow;
+            }
+            Some(PairSignal::Exit { reason, zscore, legs, position }) => {
+                let mut exit_cash_flow = 0.0;
+                for leg in &legs {
+                    let notional = signed_quantity(leg) * fill(leg);
+                    exit_cash_flow -= notional + notional.abs() * config.commission_rate;
+                }
+                cash += exit_cash_flow;
+                holdings.clear();
+
+                let gross: f64 = position.legs.iter()
+                    .zip(&legs)
+                    .map(|(entry, exit)| signed_quantity(entry) * (exit.price.to_f64().unwrap_or(0.0) - entry.price.to_f64().unwrap_or(0.0)))
+                    .sum();
+                let pnl = entry_cash_flow + exit_cash_flow;
+                let notional: f64 = position.legs.iter().map(|leg| leg.quantity.to_f64().unwrap_or(0.0) * leg.price.to_f64().unwrap_or(0.0)).sum();
+                trades.push(PairTrade {
+                    direction: position.direction,
+                    entry_time: position.entry_time,
+                    exit_time: prices.times[t],
+                    entry_zscore: position.entry_zscore,
+                    exit_zscore: zscore,
+                    hedge_ratio: position.hedge_ratio,
+                    bars_held: position.bars_held,
+                    pnl,
+                    return_percent: if notional > 0.0 { pnl / notional * 100.0 } else { 0.0 },
+                    costs: gross - pnl,
+                    exit_reason: reason,
+                });
+            }
+            None => {}
+        }
+
+        if !holdings.is_empty() {
+            bars_in_market += 1;
+        }
+        let marked: f64 = holdings.iter()
+            .map(|(quantity, is_y)| quantity * if *is_y { prices.y[t] } else { prices.x[t] })
+            .sum();
+        equity_curve.push((prices.times[t], cash + marked));
+    }
+
+    let equities: Vec<f64> = equity_curve.iter().map(|(_, equity)| *equity).collect();
+    let final_equity = equities.last().copied().unwrap_or(initial_capital);
+    let returns: Vec<f64> = std::iter::once(initial_capital)
+        .chain(equities.iter().copied())
+        .collect::<Vec<f64>>()
+        .windows(2)
+        .map(|w| if w[0] > 0.0 { w[1] / w[0] - 1.0 } else { 0.0 })
+        .collect();
+    let bars_per_year = Duration::days(365).num_minutes() as f64 / bar.num_minutes().max(1) as f64;
+    let total_return = final_equity / initial_capital - 1.0;
+    let years = equities.len() as f64 / bars_per_year;
+    let volatility = std_dev(&returns);
+    let mut peak = initial_capital;
+    let mut max_drawdown: f64 = 0.0;
+    for equity in &equities {
+        peak = peak.max(*equity);
+        max_drawdown = max_drawdown.max(1.0 - equity / peak);
+    }
+
+    Ok(PairsBacktestResult {
+        statistics,
+        win_rate: if trades.is_empty() { 0.0 } else { trades.iter().filter(|t| t.pnl > 0.0).count() as f64 / trades.len() as f64 },
+        trades,
+        initial_capital,
+        final_equity,
+        total_return,
+        annualized_return: if years > 0.0 && total_return > -1.0 { (1.0 + total_return).powf(1.0 / years) - 1.0 } else { 0.0 },
+        sharpe_ratio: if volatility > 0.0 { mean(&returns) / volatility * bars_per_year.sqrt() } else { 0.0 },
+        max_drawdown,
+        exposure: bars_in_market as f64 / equities.len().max(1) as f64,
+        trading_bars: equities.len(),
+        equity_curve,
+    })
+}
+
+// Statistics
+
+struct Regression {
+    coefficients: Vec<f64>,
+    standard_errors: Vec<f64>,
+    residuals: Vec<f64>,
+    sum_squared_residuals: f64,
+}
+
+/// Least squares of `y` on the given regressor columns. None when they are collinear
+fn ols(y: &[f64], columns: &[Vec<f64>]) -> Option<Regression> {
+    let (n, k) = (y.len(), columns.len());
+    if n <= k {
+        return None;
+    }
+
+    // Invert X'X by Gauss-Jordan elimination on [X'X | I]
+    let mut augmented = vec![vec![0.0; 2 * k]; k];
+    for i in 0..k {
+        for j in 0..k {
+            augmented[i][j] = columns[i].iter().zip(&columns[j]).map(|(a, b)| a * b).sum();
+        }
+        augmented[i][k + i] = 1.0;
+    }
+    let scale = (0..k).map(|i| augmented[i][i].abs()).fold(0.0, f64::max);
+    for column in 0..k {
+        let pivot = (column..k).max_by(|a, b| augmented[*a][column].abs().total_cmp(&augmented[*b][column].abs()))?;
+        if augmented[pivot][column].abs() <= scale * 1e-12 {
+            return None;
+        }
+        augmented.swap(column, pivot);
+        let divisor = augmented[column][column];
+        augmented[column].iter_mut().for_each(|v| *v /= divisor);
+        for row in 0..k {
+            if row != column {
+                let factor = augmented[row][column];
+                let pivot_row = augmented[column].clone();
+                augmented[row].iter_mut().zip(&pivot_row).for_each(|(v, p)| *v -= factor * p);
+            }
+        }
+    }
+
+    let xty: Vec<f64> = columns.iter().map(|column| column.iter().zip(y).map(|(a, b)| a * b).sum()).collect();
+    let coefficients: Vec<f64> = (0..k).map(|i| (0..k).map(|j| augmented[i][k + j] * xty[j]).sum()).collect();
+    let residuals: Vec<f64> = (0..n)
+        .map(|t| y[t] - (0..k).map(|i| coefficients[i] * columns[i][t]).sum::<f64>())
+        .collect();
+    let sum_squared_residuals: f64 = residuals.iter().map(|r| r * r).sum();
+    let variance = sum_squared_residuals / (n - k) as f64;
+
+    Some(Regression {
+        standard_errors: (0..k).map(|i| (variance * augmented[i][k + i]).max(0.0).sqrt()).collect(),
+        coefficients,
+        residuals,
+        sum_squared_residuals,
+    })
+}
+
+struct AdfResult {
+    statistic: f64,
+    lags: usize,
+}
+
+/// Augmented Dickey-Fuller t-statistic without a constant, as used on Engle-Granger residuals.
+/// The lag order is chosen by AIC up to the Schwert bound on a common sample, then refit
+fn adf_test(series: &[f64]) -> Option<AdfResult> {
+    let n = series.len();
+    let schwert = (12.0 * (n as f64 / 100.0).powf(0.25)).floor() as usize;
+    let max_lags = schwert.min(MAX_ADF_LAGS).min(n / 4);
+    let differences: Vec<f64> = series.windows(2).map(|w| w[1] - w[0]).collect();
+
+    // Regress Δe_t on e_{t-1} and Δe_{t-1..t-lags} for t from `start`
+    let regress = |lags: usize, start: usize| {
+        let dependent: Vec<f64> = (start..n).map(|t| differences[t - 1]).collect();
+        let mut columns = vec![(start..n).map(|t| series[t - 1]).collect::<Vec<f64>>()];
+        for lag in 1..=lags {
+            columns.push((start..n).map(|t| differences[t - 1 - lag]).collect());
+        }
+        ols(&dependent, &columns)
+    };
+
+    let sample = (n - max_lags - 1) as f64;
+    let lags = (0..=max_lags)
+        .filter_map(|lags| {
+            let regression = regress(lags, max_lags + 1)?;
+            let aic = sample * (regression.sum_squared_residuals / sample).ln() + 2.0 * (lags + 1) as f64;
+            Some((lags, aic))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))?
+        .0;
+
+    let regression = regress(lags, lags + 1)?;
+    Some(AdfResult {
+        statistic: regression.coefficients[0] / regression.standard_errors[0],
+        lags,
+    })
+}
+
+/// Half-life of mean reversion from an AR(1) fit of the spread
+fn half_life(spread: &[f64]) -> Option<f64> {
+    let changes: Vec<f64> = spread.windows(2).map(|w| w[1] - w[0]).collect();
+    let lagged = spread[..spread.len().saturating_sub(1)].to_vec();
+    let regression = ols(&changes, &[vec![1.0; lagged.len()], lagged])?;
+    let persistence = 1.0 + regression.coefficients[1];
+    if persistence > 0.0 && persistence < 1.0 {
+        Some(-std::f64::consts::LN_2 / persistence.ln())
+    } else {
+        None
+    }
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        values.iter().sum::<f64>() / values.len() as f64
+    }
+}
+
+fn std_dev(values: &[f64]) -> f64 {
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let m = mean(values);
+    (values.iter().map(|v| (v - m).powi(2)).sum::<f64>() / (values.len() - 1) as f64).sqrt()
+}
+
+fn correlation(a: &[f64], b: &[f64]) -> f64 {
+    let (std_a, std_b) = (std_dev(a), std_dev(b));
+    if std_a == 0.0 || std_b == 0.0 {
+        return 0.0;
+    }
+    let (mean_a, mean_b) = (mean(a), mean(b));
+    let covariance = a.iter().zip(b).map(|(x, y)| (x - mean_a) * (y - mean_b)).sum::<f64>() / (a.len() - 1) as f64;
+    covariance / (std_a * std_b)
+}
+
+/// Z-score of the last spread against the window it ends
+fn zscore(window: &[f64]) -> Option<f64> {
+    let deviation = std_dev(window);
+    if deviation > 0.0 {
+        Some((window.last()? - mean(window)) / deviation)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn gaussian(rng: &mut StdRng) -> f64 {
+        let u1: f64 = rng.gen_range(1e-12..1.0);
+        let u2: f64 = rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+
+    /// log y = 0.5 + ratio * log x + AR(1) noise with coefficient `phi`; `phi` of 1 breaks cointegration
+    fn synthetic_pair(n: usize, ratio: f64, phi: f64, seed: u64) -> PairPrices {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let (mut log_x, mut noise) = (100f64.ln(), 0.0);
+        let mut prices = PairPrices::default();
+        for t in 0..n {
+            log_x += 0.01 * gaussian(&mut rng);
+            noise = phi * noise + 0.005 * gaussian(&mut rng);
+            prices.times.push(start + Duration::hours(t as i64));
+            prices.x.push(log_x.exp());
+            prices.y.push((0.5 + ratio * log_x + noise).exp());
+        }
+        prices
+    }
+
+    #[test]
+    fn test_engle_granger_separates_cointegrated_pairs() {
+        let config = PairsConfig::default();
+        let prices = synthetic_pair(600, 1.5, 0.8, 1);
+        let statistics = PairStatistics::estimate("YUSDT", "XUSDT", &prices, &config).unwrap();
+        assert!(statistics.cointegrated, "ADF {}", statistics.adf_statistic);
+        assert!(statistics.adf_statistic < statistics.critical_values.one_percent);
+        assert!((statistics.hedge_ratio - 1.5).abs() < 0.05, "ratio {}", statistics.hedge_ratio);
+        // AR(1) with 0.8 halves in ln 2 / -ln 0.8 = 3.1 bars
+        let half_life = statistics.half_life_bars.unwrap();
+        assert!((2.0..5.0).contains(&half_life), "half-life {}", half_life);
+        assert!(statistics.is_tradable(&config));
+
+        let random_walks = synthetic_pair(600, 1.5, 1.0, 5);
+        let statistics = PairStatistics::estimate("YUSDT", "XUSDT", &random_walks, &config).unwrap();
+        assert!(!statistics.cointegrated, "ADF {}", statistics.adf_statistic);
+
+        // Critical values tighten towards the asymptotic ones as samples grow
+        assert!(CriticalValues::engle_granger(100).five_percent < CriticalValues::engle_granger(10_000).five_percent);
+        assert!(PairStatistics::estimate("YUSDT", "XUSDT", &prices.slice(0..20), &config).is_err());
+
+        // Kalman tracking of a regression coefficient
+        let mut filter = KalmanFilter::new(1e-6, 1e-4, 0.0);
+        for t in 1..500 {
+            let regressor = (t as f64 * 0.1).sin();
+            filter.update_regression(2.0 * regressor, regressor);
+        }
+        assert!((filter.value() - 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_trader_enters_two_legs_and_exits_on_reversion() {
+        let config = PairsConfig { zscore_window: 20, max_holding_half_lives: 0.0, ..PairsConfig::default() };
+        let formation = synthetic_pair(200, 1.0, 0.5, 3);
+        let mut trader = PairTrader::fit("YUSDT", "XUSDT", &formation, config).unwrap();
+        let ratio = trader.hedge_ratio();
+        let intercept = trader.statistics.intercept;
+        let mut time = *formation.times.last().unwrap();
+        let x = Decimal::from(100);
+        let mut bar = |trader: &mut PairTrader, offset: f64, allow: bool| {
+            time += Duration::hours(1);
+            let y = (intercept + ratio * 100f64.ln() + offset).exp();
+            trader.on_bar(time, Decimal::from_f64(y).unwrap(), x, allow)
+        };
+
+        // Rich spread with entries paused does nothing, then opens a short spread
+        assert!(bar(&mut trader, 0.03, false).is_none());
+        let signal = bar(&mut trader, 0.03, true);
+        let Some(PairSignal::Enter { direction, legs, zscore }) = signal else { panic!("expected entry, got {:?}", signal) };
+        assert_eq!(direction, SpreadDirection::Short);
+        assert!(zscore >= 2.0);
+        assert_eq!((legs[0].symbol.as_str(), &legs[0].side), ("YUSDT", &TradeSide::Sell));
+        assert_eq!((legs[1].symbol.as_str(), &legs[1].side), ("XUSDT", &TradeSide::Buy));
+        let notional: f64 = legs.iter().map(|l| (l.quantity * l.price).to_f64().unwrap()).sum();
+        assert!((notional - 1000.0).abs() < 0.01, "notional {}", notional);
+        trader.set_position_trades(vec![Uuid::new_v4(), Uuid::new_v4()]);
+
+        // A repeated bar is ignored and the spread reverting closes both legs
+        let entry_time = trader.position().unwrap().entry_time;
+        assert!(trader.on_bar(entry_time, Decimal::ONE, Decimal::ONE, true).is_none());
+        let signal = bar(&mut trader, 0.0, true);
+        let Some(PairSignal::Exit { reason, legs: exit_legs, position, .. }) = signal else { panic!("expected exit, got {:?}", signal) };
+        assert_eq!(reason, PairExitReason::MeanReversion);
+        assert_eq!(position.trade_ids.len(), 2);
+        assert_eq!(exit_legs[0].side, TradeSide::Buy);
+        assert_eq!(exit_legs[1].side, TradeSide::Sell);
+        assert_eq!(exit_legs[0].quantity, legs[0].quantity);
+        assert!(trader.position().is_none());
+
+        let mut desk = PairsDesk::new();
+        assert_eq!(desk.add(trader).unwrap(), "YUSDT/XUSDT");
+        assert!(desk.get("YUSDT/XUSDT").is_some());
+    }
+
+    #[test]
+    fn test_failed_leg_unwinds_entry_and_desk_reports_pnl() {
+        let config = PairsConfig { zscore_window: 20, max_holding_half_lives: 0.0, ..PairsConfig::default() };
+        let formation = synthetic_pair(200, 1.0, 0.5, 3);
+        let mut trader = PairTrader::fit("YUSDT", "XUSDT", &formation, config).unwrap();
+        let (ratio, intercept) = (trader.hedge_ratio(), trader.statistics.intercept);
+        let mut time = *formation.times.last().unwrap();
+        let x = Decimal::from(100);
+        let mut bar = |trader: &mut PairTrader, offset: f64| {
+            time += Duration::hours(1);
+            let y = (intercept + ratio * 100f64.ln() + offset).exp();
+            trader.on_bar(time, Decimal::from_f64(y).unwrap(), x, true)
+        };
+
+        // The second leg failing leaves the trader flat with the first leg to close
+        let Some(PairSignal::Enter { legs, .. }) = bar(&mut trader, 0.03) else { panic!("expected entry") };
+        let filled = Uuid::new_v4();
+        let unwind = trader.settle_entry(&[Some(filled), None]);
+        assert!(trader.position().is_none());
+        assert_eq!(unwind.len(), 1);
+        assert_eq!(unwind[0].0, filled);
+        assert_eq!(unwind[0].1.symbol, "YUSDT");
+        assert_eq!(unwind[0].1.side, TradeSide::Buy);
+        assert_eq!((unwind[0].1.quantity, unwind[0].1.price), (legs[0].quantity, legs[0].price));
+
+        // Both legs filling keeps the position and its trades, marked to the last bar
+        let mut signal = bar(&mut trader, 0.03);
+        while !matches!(signal, Some(PairSignal::Enter { .. })) {
+            signal = bar(&mut trader, 0.03);
+        }
+        let ids = [Some(Uuid::new_v4()), Some(Uuid::new_v4())];
+        assert!(trader.settle_entry(&ids).is_empty());
+        assert_eq!(trader.position().unwrap().trade_ids.len(), 2);
+        assert_eq!(trader.unrealized_pnl(), 0.0);
+
+        let mut desk = PairsDesk::new();
+        desk.add(trader).unwrap();
+        desk.record_realized(-25.0);
+        assert!((desk.equity() - (DESK_CAPITAL - 25.0)).abs() < 1e-9);
+        let flattened = desk.flatten_all();
+        assert_eq!(flattened.len(), 1);
+        assert_eq!(flattened[0].1.trade_ids.len(), 2);
+        assert!(desk.get("YUSDT/XUSDT").unwrap().position().is_none());
+    }
+
+    #[test]
+    fn test_backtest_trades_mean_reverting_spread_profitably() {
+        let prices = synthetic_pair(1500, 1.2, 0.9, 4);
+        for hedge_method in [HedgeRatioMethod::Ols, HedgeRatioMethod::Kalman] {
+            let config = PairsConfig { hedge_method, ..PairsConfig::default() };
+            let result = backtest_pair("YUSDT", "XUSDT", &prices, &config, 10_000.0).unwrap();
+            assert!(result.statistics.cointegrated);
+            assert_eq!(result.trading_bars, 1000);
+            assert!(result.trades.len() >= 10, "{:?}: {} trades", hedge_method, result.trades.len());
+            assert!(result.final_equity > 10_000.0, "{:?}: equity {}", hedge_method, result.final_equity);
+            assert!(result.win_rate > 0.5);
+            assert!(result.trades.iter().all(|t| t.costs > 0.0 && t.exit_time > t.entry_time));
+            assert!((0.0..1.0).contains(&result.max_drawdown) && result.exposure > 0.0);
+        }
+
+        let short = prices.slice(0..400);
+        assert!(backtest_pair("YUSDT", "XUSDT", &short, &PairsConfig::default(), 10_000.0).is_err());
+        let invalid = PairsConfig { exit_zscore: 3.0, ..PairsConfig::default() };
+        assert!(backtest_pair("YUSDT", "XUSDT", &prices, &invalid, 10_000.0).is_err());
+    }
+}
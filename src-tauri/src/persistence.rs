@@ -0,0 +1,66 @@
+// JSON persistence helpers for files under the application data directory
+
+use std::path::{Path, PathBuf};
+use serde::{de::DeserializeOwned, Serialize};
+use crate::errors::{TradingError, TradingResult};
+
+/// Application data directory (`./data`, matching the settings store)
+pub fn data_dir() -> TradingResult<PathBuf> {
+    std::env::current_dir()
+        .map(|dir| dir.join("data"))
+        .map_err(|e| TradingError::config_error(
+            "data_dir".to_string(),
+            format!("Failed to get current directory: {}", e)
+        ))
+}
+
+/// Path of a file inside the application data directory
+pub fn data_file(name: &str) -> TradingResult<PathBuf> {
+    Ok(data_dir()?.join(name))
+}
+
+/// Read a JSON file, returning `None` when it does not exist yet
+pub fn read_json<T: DeserializeOwned>(path: &Path) -> TradingResult<Option<T>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| TradingError::config_error(
+            "file_read".to_string(),
+            format!("Failed to read {}: {}", path.display(), e)
+        ))?;
+
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| TradingError::config_error(
+            "json_parse".to_string(),
+            format!("Failed to parse {}: {}", path.display(), e)
+        ))
+}
+
+/// Write a value as pretty JSON, creating parent directories as needed.
+/// The file is written to a temporary sibling first so a crash never leaves a truncated file.
+pub fn write_json<T: Serialize>(path: &Path, value: &T) -> TradingResult<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| TradingError::config_error(
+                "directory_create".to_string(),
+                format!("Failed to create directory {}: {}", parent.display(), e)
+            ))?;
+    }
+
+    let content = serde_json::to_string_pretty(value)
+        .map_err(|e| TradingError::config_error(
+            "json_serialize".to_string(),
+            format!("Failed to serialize {}: {}", path.display(), e)
+        ))?;
+
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::write(&temp_path, content)
+        .and_then(|_| std::fs::rename(&temp_path, path))
+        .map_err(|e| TradingError::config_error(
+            "file_write".to_string(),
+            format!("Failed to write {}: {}", path.display(), e)
+        ))
+}
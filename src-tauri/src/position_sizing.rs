@@ -0,0 +1,440 @@
+// Position Sizing Models
+// Fixed fractional, fractional Kelly, volatility targeting, fixed notional and
+// equal risk contribution sizing shared by the live bot and the backtester
+
+use rust_decimal::Decimal;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::advanced_risk_manager::KellyPositionSizer;
+use crate::models::PriceData;
+use crate::multi_timeframe::timeframe_duration;
+
+/// How a new position's notional is chosen
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SizingModel {
+    /// Risk a fixed percentage of equity between entry and stop
+    #[default]
+    FixedFractional,
+    /// Risk a fraction of the Kelly-optimal bet estimated from recent trade results
+    FractionalKelly,
+    /// Scale notional so the position's annualised volatility matches a target
+    VolatilityTarget,
+    /// Always trade the same notional
+    FixedNotional,
+    /// Split the volatility budget equally across position slots (inverse-volatility weights)
+    EqualRiskContribution,
+}
+
+impl SizingModel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SizingModel::FixedFractional => "Fixed Fractional",
+            SizingModel::FractionalKelly => "Fractional Kelly",
+            SizingModel::VolatilityTarget => "Volatility Target",
+            SizingModel::FixedNotional => "Fixed Notional",
+            SizingModel::EqualRiskContribution => "Equal Risk Contribution",
+        }
+    }
+}
+
+/// Volatility estimate used by the volatility-based models
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum VolatilityMeasure {
+    /// Average true range as a fraction of price
+    #[default]
+    Atr,
+    /// Standard deviation of close-to-close returns
+    Realized,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionSizing {
+    pub model: SizingModel,
+    /// Equity risked per trade for fixed fractional (and the Kelly fallback), in percent
+    pub risk_percent: f64,
+    /// Stop distance assumed when the caller has no stop, in percent
+    pub default_stop_percent: f64,
+    /// Fraction of full Kelly to use
+    pub kelly_fraction: f64,
+    /// Cap on the equity risked by a Kelly-sized trade, in percent
+    pub kelly_max_risk_percent: f64,
+    /// Trades required before Kelly is trusted; fixed fractional is used until then
+    pub kelly_min_trades: usize,
+    /// Annualised volatility target for volatility targeting and ERC, in percent
+    pub target_volatility_percent: f64,
+    pub volatility_measure: VolatilityMeasure,
+    pub volatility_lookback: usize,
+    pub fixed_notional: f64,
+    /// Number of equally-weighted risk slots for equal risk contribution
+    pub risk_slots: usize,
+    /// Scale the model's notional by signal strength
+    pub scale_by_signal_strength: bool,
+    /// Largest notional as a fraction of equity
+    pub max_equity_fraction: f64,
+    /// Smallest notional worth trading; smaller sizes are skipped
+    pub min_notional: f64,
+}
+
+impl Default for PositionSizing {
+    fn default() -> Self {
+        Self {
+            model: SizingModel::FixedFractional,
+            risk_percent: 1.0,
+            default_stop_percent: 2.0,
+            kelly_fraction: 0.25,
+            kelly_max_risk_percent: 2.0,
+            kelly_min_trades: 20,
+            target_volatility_percent: 40.0,
+            volatility_measure: VolatilityMeasure::Atr,
+            volatility_lookback: 14,
+            fixed_notional: 1000.0,
+            risk_slots: 4,
+            scale_by_signal_strength: true,
+            max_equity_fraction: 0.8,
+            min_notional: 10.0,
+        }
+    }
+}
+
+impl PositionSizing {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=10.0).contains(&self.risk_percent) || self.risk_percent == 0.0 {
+            return Err("Position sizing risk_percent must be between 0 and 10".to_string());
+        }
+        if self.default_stop_percent <= 0.0 || self.default_stop_percent > 50.0 {
+            return Err("Position sizing default_stop_percent must be between 0 and 50".to_string());
+        }
+        if self.kelly_fraction <= 0.0 || self.kelly_fraction > 1.0 {
+            return Err("Kelly fraction must be between 0 and 1".to_string());
+        }
+        if self.kelly_max_risk_percent <= 0.0 || self.kelly_max_risk_percent > 10.0 {
+            return Err("Kelly max risk must be between 0 and 10 percent".to_string());
+        }
+        if self.target_volatility_percent <= 0.0 || self.target_volatility_percent > 200.0 {
+            return Err("Target volatility must be between 0 and 200 percent".to_string());
+        }
+        if self.volatility_lookback < 2 {
+            return Err("Volatility lookback must be at least 2 bars".to_string());
+        }
+        if self.model == SizingModel::FixedNotional && self.fixed_notional <= 0.0 {
+            return Err("Fixed notional must be positive".to_string());
+        }
+        if self.risk_slots == 0 {
+            return Err("Equal risk contribution needs at least one risk slot".to_string());
+        }
+        if self.max_equity_fraction <= 0.0 || self.max_equity_fraction > 1.0 {
+            return Err("Max equity fraction must be between 0 and 1".to_string());
+        }
+        if self.min_notional < 0.0 {
+            return Err("Min notional cannot be negative".to_string());
+        }
+        Ok(())
+    }
+
+    /// Bars to keep for the volatility estimate
+    pub fn required_bars(&self) -> usize {
+        self.volatility_lookback + 1
+    }
+
+    /// Size a new position. `recent_bars` are the latest bars (oldest first) of `timeframe`.
+    pub fn size(&self, inputs: &SizingInputs, recent_bars: &[PriceData], timeframe: &str, kelly: &KellyPositionSizer) -> SizingDecision {
+        let equity = inputs.equity.to_f64().unwrap_or(0.0);
+        let price = inputs.price.to_f64().unwrap_or(0.0);
+        let stop_percent = inputs.stop_distance_percent
+            .filter(|s| *s > 0.0)
+            .unwrap_or(self.default_stop_percent);
+        let volatility = self.annualised_volatility(recent_bars, timeframe);
+
+        let mut decision = SizingDecision {
+            model: self.model,
+            equity,
+            price,
+            notional: 0.0,
+            quantity: Decimal::ZERO,
+            risk_amount: 0.0,
+            stop_distance_percent: stop_percent,
+            annualised_volatility: volatility,
+            kelly_fraction: None,
+            adjustments: Vec::new(),
+            explanation: String::new(),
+        };
+        if equity <= 0.0 || price <= 0.0 {
+            decision.explanation = "No equity or price available".to_string();
+            return decision;
+        }
+
+        let risk_notional = |risk_fraction: f64| equity * risk_fraction / (stop_percent / 100.0);
+        let (mut notional, rationale) = match self.model {
+            SizingModel::FixedFractional => (
+                risk_notional(self.risk_percent / 100.0),
+                format!("risk {:.2}% of equity over a {:.2}% stop", self.risk_percent, stop_percent),
+            ),
+            SizingModel::FractionalKelly => {
+                if kelly.trade_count() < self.kelly_min_trades {
+                    (
+                        risk_notional(self.risk_percent / 100.0),
+                        format!("{} of {} trades needed for Kelly, risk {:.2}% over a {:.2}% stop",
+                            kelly.trade_count(), self.kelly_min_trades, self.risk_percent, stop_percent),
+                    )
+                } else {
+                    // Kelly bet f* = p - q / b with payoff ratio b = avg win / avg loss
+                    let (win_rate, avg_win, avg_loss) = kelly.get_performance_metrics();
+                    let full_kelly = win_rate - (1.0 - win_rate) * avg_loss / avg_win.max(f64::EPSILON);
+                    let risk = (full_kelly * self.kelly_fraction).clamp(0.0, self.kelly_max_risk_percent / 100.0);
+                    decision.kelly_fraction = Some(risk);
+                    (
+                        risk_notional(risk),
+                        format!("{:.0}% Kelly on {:.0}% win rate ({:.2}% avg win / {:.2}% avg loss) risks {:.2}% over a {:.2}% stop",
+                            self.kelly_fraction * 100.0, win_rate * 100.0, avg_win, avg_loss, risk * 100.0, stop_percent),
+                    )
+                }
+            }
+            SizingModel::VolatilityTarget | SizingModel::EqualRiskContribution => match volatility {
+                Some(vol) if vol > 0.0 => {
+                    let slots = if self.model == SizingModel::EqualRiskContribution { self.risk_slots } else { 1 };
+                    let target = self.target_volatility_percent / 100.0 / slots as f64;
+                    (
+                        equity * target / vol,
+                        if slots > 1 {
+                            format!("{:.1}% volatility budget over {} slots against {:.1}% asset volatility",
+                                self.target_volatility_percent, slots, vol * 100.0)
+                        } else {
+                            format!("{:.1}% volatility target against {:.1}% asset volatility",
+                                self.target_volatility_percent, vol * 100.0)
+                        },
+                    )
+                }
+                _ => (
+                    risk_notional(self.risk_percent / 100.0),
+                    format!("not enough bars for volatility, risk {:.2}% over a {:.2}% stop", self.risk_percent, stop_percent),
+                ),
+            },
+            SizingModel::FixedNotional => (self.fixed_notional, format!("fixed notional of {:.2}", self.fixed_notional)),
+        };
+
+        if self.scale_by_signal_strength && inputs.signal_strength < 1.0 {
+            let strength = inputs.signal_strength.clamp(0.0, 1.0);
+            notional *= strength;
+            decision.adjustments.push(format!("x{:.2} signal strength", strength));
+        }
+        if (inputs.risk_multiplier - 1.0).abs() > f64::EPSILON {
+            notional *= inputs.risk_multiplier.max(0.0);
+            decision.adjustments.push(format!("x{:.2} {}", inputs.risk_multiplier, inputs.risk_multiplier_reason));
+        }
+
+        let cap = inputs.max_notional.map_or(f64::INFINITY, |m| m.to_f64().unwrap_or(f64::INFINITY))
+            .min(equity * self.max_equity_fraction);
+        if notional > cap {
+            notional = cap;
+            decision.adjustments.push(format!("capped at {:.2}", cap));
+        }
+        if notional < self.min_notional {
+            decision.adjustments.push(format!("below minimum notional {:.2}, skipped", self.min_notional));
+            notional = 0.0;
+        }
+
+        decision.notional = notional;
+        decision.quantity = Decimal::from_f64(notional / price).unwrap_or(Decimal::ZERO);
+        decision.risk_amount = notional * stop_percent / 100.0;
+        decision.explanation = format!("{}: {}{}{}", self.model.as_str(), rationale,
+            if decision.adjustments.is_empty() { "" } else { "; " },
+            decision.adjustments.join(", "));
+        decision
+    }
+
+    /// Annualised volatility of the instrument from recent bars
+    fn annualised_volatility(&self, bars: &[PriceData], timeframe: &str) -> Option<f64> {
+        let bars = &bars[bars.len().saturating_sub(self.required_bars())..];
+        if bars.len() < 3 {
+            return None;
+        }
+        let bars_per_year = timeframe_duration(timeframe)
+            .map(|d| 365.0 * 86_400.0 / d.num_seconds().max(1) as f64)?;
+
+        let per_bar = match self.volatility_measure {
+            VolatilityMeasure::Atr => {
+                let true_ranges: Vec<f64> = bars.windows(2)
+                    .filter_map(|pair| {
+                        let previous = pair[0].close.to_f64()?;
+                        let (high, low, close) = (pair[1].high.to_f64()?, pair[1].low.to_f64()?, pair[1].close.to_f64()?);
+                        let range = (high - low).max((high - previous).abs()).max((low - previous).abs());
+                        (close > 0.0).then_some(range / close)
+                    })
+                    .collect();
+                // Mean absolute move to standard deviation under normality
+                let atr = true_ranges.iter().sum::<f64>() / true_ranges.len().max(1) as f64;
+                atr * (std::f64::consts::PI / 2.0).sqrt() / 2.0_f64.sqrt()
+            }
+            VolatilityMeasure::Realized => {
+                let returns: Vec<f64> = bars.windows(2)
+                    .filter_map(|pair| {
+                        let (previous, current) = (pair[0].close.to_f64()?, pair[1].close.to_f64()?);
+                        (previous > 0.0).then(|| current / previous - 1.0)
+                    })
+                    .collect();
+                if returns.len() < 2 {
+                    return None;
+                }
+                let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+                (returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (returns.len() - 1) as f64).sqrt()
+            }
+        };
+
+        Some(per_bar * bars_per_year.sqrt())
+    }
+}
+
+/// Per-trade inputs to the sizing models
+#[derive(Debug, Clone)]
+pub struct SizingInputs {
+    pub equity: Decimal,
+    pub price: Decimal,
+    /// Distance from entry to stop in percent, if the strategy uses a stop
+    pub stop_distance_percent: Option<f64>,
+    pub signal_strength: f64,
+    /// External risk overlay (e.g. GPU risk assessment), 1.0 for none
+    pub risk_multiplier: f64,
+    pub risk_multiplier_reason: String,
+    pub max_notional: Option<Decimal>,
+}
+
+/// Sizing outcome recorded with the position
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizingDecision {
+    pub model: SizingModel,
+    pub equity: f64,
+    pub price: f64,
+    pub notional: f64,
+    pub quantity: Decimal,
+    /// Loss if the stop is hit
+    pub risk_amount: f64,
+    pub stop_distance_percent: f64,
+    pub annualised_volatility: Option<f64>,
+    /// Fraction of equity risked by the Kelly model
+    pub kelly_fraction: Option<f64>,
+    pub adjustments: Vec<String>,
+    pub explanation: String,
+}
+
+impl SizingDecision {
+    /// Shrink the decision by `factor` after sizing, e.g. to fit portfolio limits
+    pub fn scale(&mut self, factor: f64, reason: &str) {
+        let factor = factor.clamp(0.0, 1.0);
+        self.notional *= factor;
+        self.risk_amount *= factor;
+        self.quantity = if self.price > 0.0 {
+            Decimal::from_f64(self.notional / self.price).unwrap_or(Decimal::ZERO)
+        } else {
+            Decimal::ZERO
+        };
+        let adjustment = format!("x{:.2} {}", factor, reason);
+        self.explanation = format!("{}{}{}", self.explanation, if self.adjustments.is_empty() { "; " } else { ", " }, adjustment);
+        self.adjustments.push(adjustment);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn inputs() -> SizingInputs {
+        SizingInputs {
+            equity: Decimal::from(10_000),
+            price: Decimal::from(100),
+            stop_distance_percent: Some(2.0),
+            signal_strength: 1.0,
+            risk_multiplier: 1.0,
+            risk_multiplier_reason: String::new(),
+            max_notional: None,
+        }
+    }
+
+    /// Daily bars alternating +/-1% with a 2% high-low range
+    fn bars(count: usize) -> Vec<PriceData> {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut close = 100.0;
+        (0..count).map(|i| {
+            close *= if i % 2 == 0 { 1.01 } else { 0.99 };
+            let close_d = Decimal::from_f64(close).unwrap();
+            PriceData {
+                timestamp: start + Duration::days(i as i64),
+                open: close_d,
+                high: Decimal::from_f64(close * 1.01).unwrap(),
+                low: Decimal::from_f64(close * 0.99).unwrap(),
+                close: close_d,
+                volume: Decimal::ONE,
+            }
+        }).collect()
+    }
+
+    #[test]
+    fn test_fixed_fractional_and_caps() {
+        let sizing = PositionSizing::default();
+        let decision = sizing.size(&inputs(), &[], "1h", &KellyPositionSizer::new());
+        // 1% of 10k risked over a 2% stop
+        assert!((decision.notional - 5000.0).abs() < 1e-6);
+        assert_eq!(decision.quantity, Decimal::from(50));
+        assert!((decision.risk_amount - 100.0).abs() < 1e-6);
+
+        let capped = sizing.size(&SizingInputs { max_notional: Some(Decimal::from(1000)), ..inputs() }, &[], "1h", &KellyPositionSizer::new());
+        assert!((capped.notional - 1000.0).abs() < 1e-6);
+        assert!(capped.explanation.contains("capped"));
+
+        let skipped = sizing.size(&SizingInputs { risk_multiplier: 0.0, risk_multiplier_reason: "crisis".to_string(), ..inputs() }, &[], "1h", &KellyPositionSizer::new());
+        assert_eq!(skipped.quantity, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_kelly_needs_history_then_uses_it() {
+        let sizing = PositionSizing { model: SizingModel::FractionalKelly, kelly_min_trades: 10, ..Default::default() };
+        let mut kelly = KellyPositionSizer::new();
+        let fallback = sizing.size(&inputs(), &[], "1h", &kelly);
+        assert!(fallback.kelly_fraction.is_none());
+        assert!((fallback.notional - 5000.0).abs() < 1e-6);
+
+        // 60% winners of +2%, 40% losers of -1%: full Kelly 0.4, quarter Kelly 0.1, capped at 2% risk
+        for i in 0..10 {
+            if i < 6 { kelly.update_trade_result(true, 2.0) } else { kelly.update_trade_result(false, -1.0) }
+        }
+        let decision = sizing.size(&inputs(), &[], "1h", &kelly);
+        assert!((decision.kelly_fraction.unwrap() - 0.02).abs() < 1e-9);
+        assert!((decision.notional - 8000.0).abs() < 1e-6); // 2% over a 2% stop, capped at 80% of equity
+    }
+
+    #[test]
+    fn test_volatility_models() {
+        let history = bars(30);
+        let target = PositionSizing { model: SizingModel::VolatilityTarget, volatility_measure: VolatilityMeasure::Realized, ..Default::default() };
+        let decision = target.size(&inputs(), &history, "1d", &KellyPositionSizer::new());
+        let vol = decision.annualised_volatility.unwrap();
+        // +/-1% a day is roughly 19% annualised, so a 40% target wants more than the 80% equity cap
+        assert!(vol > 0.15 && vol < 0.25, "realized vol {}", vol);
+        assert!((decision.notional - 8000.0).abs() < 1e-6);
+
+        let erc = PositionSizing { model: SizingModel::EqualRiskContribution, ..target.clone() };
+        let split = erc.size(&inputs(), &history, "1d", &KellyPositionSizer::new());
+        assert!((split.notional - 10_000.0 * 0.1 / vol).abs() < 1e-6);
+
+        // Not enough bars falls back to fixed fractional
+        let fallback = target.size(&inputs(), &history[..2], "1d", &KellyPositionSizer::new());
+        assert!(fallback.annualised_volatility.is_none());
+        assert!((fallback.notional - 5000.0).abs() < 1e-6);
+
+        let atr = PositionSizing { volatility_measure: VolatilityMeasure::Atr, ..target };
+        assert!(atr.size(&inputs(), &history, "1d", &KellyPositionSizer::new()).annualised_volatility.unwrap() > 0.0);
+    }
+
+    #[test]
+    fn test_fixed_notional_and_validation() {
+        let sizing = PositionSizing { model: SizingModel::FixedNotional, fixed_notional: 250.0, ..Default::default() };
+        let decision = sizing.size(&SizingInputs { signal_strength: 0.5, ..inputs() }, &[], "1h", &KellyPositionSizer::new());
+        assert!((decision.notional - 125.0).abs() < 1e-6);
+        assert!(decision.explanation.starts_with("Fixed Notional"));
+
+        assert!(PositionSizing::default().validate().is_ok());
+        assert!(PositionSizing { risk_slots: 0, ..Default::default() }.validate().is_err());
+        assert!(PositionSizing { kelly_fraction: 1.5, ..Default::default() }.validate().is_err());
+    }
+}
@@ -0,0 +1,576 @@
+// Market Regime Detection
+// Clusters volatility, trend and autocorrelation features from stored klines
+// and switches strategy parameter sets per detected regime
+
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::errors::{TradingError, TradingResult};
+use crate::gpu_risk_manager::MarketRegime;
+use crate::models::PriceData;
+use crate::persistence;
+use crate::trading_strategy::{LROConfig, MarketPhase};
+
+const FEATURE_COUNT: usize = 3;
+const MIN_TRAINING_SAMPLES: usize = 50;
+const MAX_KMEANS_ITERATIONS: usize = 100;
+
+/// Market regimes recognised by the classifier
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Regime {
+    Trending,
+    Ranging,
+    Volatile,
+}
+
+impl Regime {
+    pub const ALL: [Regime; 3] = [Regime::Trending, Regime::Ranging, Regime::Volatile];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Regime::Trending => "Trending",
+            Regime::Ranging => "Ranging",
+            Regime::Volatile => "Volatile",
+        }
+    }
+
+    /// Equivalent strategy market phase
+    pub fn market_phase(&self) -> MarketPhase {
+        match self {
+            Regime::Trending => MarketPhase::Trending,
+            Regime::Ranging => MarketPhase::Ranging,
+            Regime::Volatile => MarketPhase::Breakout,
+        }
+    }
+
+    /// Equivalent risk-manager regime
+    pub fn market_regime(&self) -> MarketRegime {
+        match self {
+            Regime::Volatile => MarketRegime::Volatile,
+            Regime::Trending | Regime::Ranging => MarketRegime::Normal,
+        }
+    }
+}
+
+/// Features describing the behaviour of a price window
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RegimeFeatures {
+    /// Standard deviation of log returns per bar
+    pub volatility: f64,
+    /// Kaufman efficiency ratio: |net move| / path length (0 = noise, 1 = straight line)
+    pub trend_efficiency: f64,
+    /// Lag-1 autocorrelation of log returns
+    pub autocorrelation: f64,
+}
+
+impl RegimeFeatures {
+    /// Compute features from a window of closes (at least 10 prices)
+    pub fn from_closes(closes: &[f64]) -> Option<Self> {
+        if closes.len() < 10 || closes.iter().any(|c| !c.is_finite() || *c <= 0.0) {
+            return None;
+        }
+
+        let returns: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+        let n = returns.len() as f64;
+        let mean = returns.iter().sum::<f64>() / n;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+        let path_length: f64 = returns.iter().map(|r| r.abs()).sum();
+        let net_move = returns.iter().sum::<f64>().abs();
+        let trend_efficiency = if path_length > 0.0 { net_move / path_length } else { 0.0 };
+
+        let autocorrelation = if variance > 0.0 {
+            let covariance = returns.windows(2)
+                .map(|w| (w[0] - mean) * (w[1] - mean))
+                .sum::<f64>() / (n - 1.0);
+            (covariance / variance).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+
+        Some(Self {
+            volatility: variance.sqrt(),
+            trend_efficiency,
+            autocorrelation,
+        })
+    }
+
+    fn as_vector(&self) -> [f64; FEATURE_COUNT] {
+        // Volatility is log-scaled so calm and turbulent periods separate evenly
+        [(self.volatility + 1e-9).ln(), self.trend_efficiency, self.autocorrelation]
+    }
+}
+
+/// Regime posterior probabilities for the latest window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeProbabilities {
+    pub regime: Regime,
+    pub trending: f64,
+    pub ranging: f64,
+    pub volatile: f64,
+    pub features: RegimeFeatures,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl RegimeProbabilities {
+    pub fn probability(&self, regime: Regime) -> f64 {
+        match regime {
+            Regime::Trending => self.trending,
+            Regime::Ranging => self.ranging,
+            Regime::Volatile => self.volatile,
+        }
+    }
+
+    /// Probability of the most likely regime
+    pub fn confidence(&self) -> f64 {
+        self.probability(self.regime)
+    }
+}
+
+/// K-means regime model with Gaussian soft assignment for probabilities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeClassifier {
+    /// Number of bars in each feature window
+    pub window: usize,
+    feature_means: [f64; FEATURE_COUNT],
+    feature_stds: [f64; FEATURE_COUNT],
+    centroids: Vec<[f64; FEATURE_COUNT]>,
+    labels: Vec<Regime>,
+    weights: Vec<f64>,
+    variance: f64,
+    pub training_samples: usize,
+    pub trained_at: DateTime<Utc>,
+    pub symbol: Option<String>,
+    pub interval: Option<String>,
+}
+
+impl RegimeClassifier {
+    /// Fit the classifier on historical prices using rolling feature windows
+    pub fn fit(prices: &[PriceData], window: usize) -> TradingResult<Self> {
+        if !(10..200).contains(&window) {
+            return Err(TradingError::validation_error(
+                "window".to_string(),
+                "Regime window must be between 10 and 199 bars".to_string(),
+                Some(window.to_string())
+            ));
+        }
+
+        let closes: Vec<f64> = prices.iter().map(|p| p.close.to_f64().unwrap_or(0.0)).collect();
+        let samples: Vec<[f64; FEATURE_COUNT]> = closes
+            .windows(window + 1)
+            .filter_map(RegimeFeatures::from_closes)
+            .map(|f| f.as_vector())
+            .collect();
+
+        if samples.len() < MIN_TRAINING_SAMPLES {
+            return Err(TradingError::validation_error(
+                "historical_data".to_string(),
+                format!("Need at least {} feature windows to train, got {}", MIN_TRAINING_SAMPLES, samples.len()),
+                Some(prices.len().to_string())
+            ));
+        }
+
+        // Standardise features
+        let n = samples.len() as f64;
+        let mut feature_means = [0.0; FEATURE_COUNT];
+        let mut feature_stds = [0.0; FEATURE_COUNT];
+        for d in 0..FEATURE_COUNT {
+            feature_means[d] = samples.iter().map(|s| s[d]).sum::<f64>() / n;
+            let var = samples.iter().map(|s| (s[d] - feature_means[d]).powi(2)).sum::<f64>() / n;
+            feature_stds[d] = if var > 0.0 { var.sqrt() } else { 1.0 };
+        }
+        let standardized: Vec<[f64; FEATURE_COUNT]> = samples.iter()
+            .map(|s| standardize(s, &feature_means, &feature_stds))
+            .collect();
+
+        let (centroids, assignments) = kmeans(&standardized, Regime::ALL.len());
+
+        let mut weights = vec![0.0; centroids.len()];
+        let mut squared_error = 0.0;
+        for (sample, &cluster) in standardized.iter().zip(&assignments) {
+            weights[cluster] += 1.0;
+            squared_error += squared_distance(sample, &centroids[cluster]);
+        }
+        for weight in &mut weights {
+            *weight = (*weight / n).max(1e-6);
+        }
+        let variance = (squared_error / (n * FEATURE_COUNT as f64)).max(1e-3);
+
+        let labels = label_clusters(&centroids);
+
+        Ok(Self {
+            window,
+            feature_means,
+            feature_stds,
+            centroids,
+            labels,
+            weights,
+            variance,
+            training_samples: samples.len(),
+            trained_at: Utc::now(),
+            symbol: None,
+            interval: None,
+        })
+    }
+
+    /// Classify the most recent window of prices
+    pub fn classify(&self, prices: &[PriceData]) -> Option<RegimeProbabilities> {
+        if prices.len() < self.window + 1 {
+            return None;
+        }
+
+        let recent = &prices[prices.len() - self.window - 1..];
+        let closes: Vec<f64> = recent.iter().map(|p| p.close.to_f64().unwrap_or(0.0)).collect();
+        let features = RegimeFeatures::from_closes(&closes)?;
+        let timestamp = recent.last().map(|p| p.timestamp).unwrap_or_else(Utc::now);
+        Some(self.classify_features(features, timestamp))
+    }
+
+    /// Posterior regime probabilities for a feature vector
+    pub fn classify_features(&self, features: RegimeFeatures, timestamp: DateTime<Utc>) -> RegimeProbabilities {
+        let point = standardize(&features.as_vector(), &self.feature_means, &self.feature_stds);
+
+        let log_likelihoods: Vec<f64> = self.centroids.iter()
+            .zip(&self.weights)
+            .map(|(centroid, weight)| weight.ln() - squared_distance(&point, centroid) / (2.0 * self.variance))
+            .collect();
+        let max_ll = log_likelihoods.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let unnormalized: Vec<f64> = log_likelihoods.iter().map(|ll| (ll - max_ll).exp()).collect();
+        let total: f64 = unnormalized.iter().sum();
+
+        let mut probabilities = [0.0; 3];
+        for (cluster, p) in unnormalized.iter().enumerate() {
+            let index = Regime::ALL.iter().position(|r| *r == self.labels[cluster]).unwrap_or(0);
+            probabilities[index] += p / total;
+        }
+
+        let best = (0..probabilities.len())
+            .max_by(|&a, &b| probabilities[a].partial_cmp(&probabilities[b]).unwrap_or(std::cmp::Ordering::Equal))
+            .unwrap_or(0);
+
+        RegimeProbabilities {
+            regime: Regime::ALL[best],
+            trending: probabilities[0],
+            ranging: probabilities[1],
+            volatile: probabilities[2],
+            features,
+            timestamp,
+        }
+    }
+
+    /// Default model location (`data/regime_model.json`)
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("regime_model.json")
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        persistence::write_json(&Self::default_path()?, self)
+    }
+
+    /// Load the persisted model, if one has been trained
+    pub fn load() -> TradingResult<Option<Self>> {
+        persistence::read_json(&Self::default_path()?)
+    }
+}
+
+/// Strategy parameters applied while a regime is active
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeParameters {
+    pub overbought: f64,
+    pub oversold: f64,
+    pub stop_loss_percent: f64,
+    pub take_profit_percent: f64,
+    pub signal_strength_threshold: f64,
+    pub trailing_stop_enabled: bool,
+    pub trailing_stop_percent: f64,
+}
+
+impl RegimeParameters {
+    pub fn apply(&self, config: &mut LROConfig) {
+        config.overbought = self.overbought;
+        config.oversold = self.oversold;
+        config.stop_loss_percent = self.stop_loss_percent;
+        config.take_profit_percent = self.take_profit_percent;
+        config.signal_strength_threshold = self.signal_strength_threshold;
+        config.trailing_stop_enabled = self.trailing_stop_enabled;
+        config.trailing_stop_percent = self.trailing_stop_percent;
+    }
+
+    fn validate(&self, regime: Regime) -> Result<(), String> {
+        if self.overbought <= 0.0 || self.overbought >= 1.0 || self.oversold >= 0.0 || self.oversold <= -1.0 {
+            return Err(format!("{} regime thresholds must be within (-1, 0) and (0, 1)", regime.as_str()));
+        }
+        if self.stop_loss_percent <= 0.0 || self.stop_loss_percent > 20.0 {
+            return Err(format!("{} regime stop loss must be between 0.1% and 20%", regime.as_str()));
+        }
+        if self.take_profit_percent <= 0.0 || self.take_profit_percent > 50.0 {
+            return Err(format!("{} regime take profit must be between 0.1% and 50%", regime.as_str()));
+        }
+        if !(0.0..=1.0).contains(&self.signal_strength_threshold) {
+            return Err(format!("{} regime signal strength threshold must be between 0 and 1", regime.as_str()));
+        }
+        Ok(())
+    }
+}
+
+/// Per-regime parameter sets for the swing bot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegimeSwitching {
+    pub enabled: bool,
+    /// Minimum regime probability required before switching parameter sets
+    pub min_probability: f64,
+    pub trending: RegimeParameters,
+    pub ranging: RegimeParameters,
+    pub volatile: RegimeParameters,
+}
+
+impl Default for RegimeSwitching {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_probability: 0.6,
+            // Let winners run with a trailing stop
+            trending: RegimeParameters {
+                overbought: 0.85,
+                oversold: -0.85,
+                stop_loss_percent: 2.5,
+                take_profit_percent: 6.0,
+                signal_strength_threshold: 0.6,
+                trailing_stop_enabled: true,
+                trailing_stop_percent: 1.5,
+            },
+            // Mean reversion: tighter thresholds and targets
+            ranging: RegimeParameters {
+                overbought: 0.7,
+                oversold: -0.7,
+                stop_loss_percent: 1.5,
+                take_profit_percent: 3.0,
+                signal_strength_threshold: 0.55,
+                trailing_stop_enabled: false,
+                trailing_stop_percent: 1.0,
+            },
+            // Only the strongest signals, with wider stops
+            volatile: RegimeParameters {
+                overbought: 0.9,
+                oversold: -0.9,
+                stop_loss_percent: 3.0,
+                take_profit_percent: 6.0,
+                signal_strength_threshold: 0.75,
+                trailing_stop_enabled: true,
+                trailing_stop_percent: 2.0,
+            },
+        }
+    }
+}
+
+impl RegimeSwitching {
+    pub fn parameters_for(&self, regime: Regime) -> &RegimeParameters {
+        match regime {
+            Regime::Trending => &self.trending,
+            Regime::Ranging => &self.ranging,
+            Regime::Volatile => &self.volatile,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if !(0.34..=1.0).contains(&self.min_probability) {
+            return Err("Regime switching minimum probability must be between 0.34 and 1.0".to_string());
+        }
+        for regime in Regime::ALL {
+            self.parameters_for(regime).validate(regime)?;
+        }
+        Ok(())
+    }
+}
+
+fn standardize(
+    sample: &[f64; FEATURE_COUNT],
+    means: &[f64; FEATURE_COUNT],
+    stds: &[f64; FEATURE_COUNT],
+) -> [f64; FEATURE_COUNT] {
+    let mut out = [0.0; FEATURE_COUNT];
+    for d in 0..FEATURE_COUNT {
+        out[d] = (sample[d] - means[d]) / stds[d];
+    }
+    out
+}
+
+fn squared_distance(a: &[f64; FEATURE_COUNT], b: &[f64; FEATURE_COUNT]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+/// Lloyd's k-means with deterministic initialisation at volatility quantiles
+fn kmeans(samples: &[[f64; FEATURE_COUNT]], k: usize) -> (Vec<[f64; FEATURE_COUNT]>, Vec<usize>) {
+    let mut by_volatility: Vec<&[f64; FEATURE_COUNT]> = samples.iter().collect();
+    by_volatility.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap_or(std::cmp::Ordering::Equal));
+    let mut centroids: Vec<[f64; FEATURE_COUNT]> = (0..k)
+        .map(|i| *by_volatility[((2 * i + 1) * by_volatility.len()) / (2 * k)])
+        .collect();
+
+    let mut assignments = vec![0; samples.len()];
+    for _ in 0..MAX_KMEANS_ITERATIONS {
+        let mut changed = false;
+        for (sample, assignment) in samples.iter().zip(assignments.iter_mut()) {
+            let nearest = (0..k)
+                .min_by(|&a, &b| {
+                    squared_distance(sample, &centroids[a])
+                        .partial_cmp(&squared_distance(sample, &centroids[b]))
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .unwrap_or(0);
+            if *assignment != nearest {
+                *assignment = nearest;
+                changed = true;
+            }
+        }
+
+        for (cluster, centroid) in centroids.iter_mut().enumerate() {
+            let members: Vec<&[f64; FEATURE_COUNT]> = samples.iter()
+                .zip(&assignments)
+                .filter(|(_, &a)| a == cluster)
+                .map(|(s, _)| s)
+                .collect();
+            // Empty clusters keep their previous centroid
+            if !members.is_empty() {
+                for d in 0..FEATURE_COUNT {
+                    centroid[d] = members.iter().map(|m| m[d]).sum::<f64>() / members.len() as f64;
+                }
+            }
+        }
+
+        if !changed {
+            break;
+        }
+    }
+
+    (centroids, assignments)
+}
+
+/// Name clusters: highest volatility is Volatile, then the more efficient trend is Trending
+fn label_clusters(centroids: &[[f64; FEATURE_COUNT]]) -> Vec<Regime> {
+    let mut labels = vec![Regime::Ranging; centroids.len()];
+
+    let volatile = (0..centroids.len())
+        .max_by(|&a, &b| centroids[a][0].partial_cmp(&centroids[b][0]).unwrap_or(std::cmp::Ordering::Equal));
+    if let Some(volatile) = volatile {
+        labels[volatile] = Regime::Volatile;
+
+        let trending = (0..centroids.len())
+            .filter(|&c| c != volatile)
+            .max_by(|&a, &b| {
+                // Persistent (positively autocorrelated) efficient moves indicate trends
+                let score = |c: usize| centroids[c][1] + 0.5 * centroids[c][2];
+                score(a).partial_cmp(&score(b)).unwrap_or(std::cmp::Ordering::Equal)
+            });
+        if let Some(trending) = trending {
+            labels[trending] = Regime::Trending;
+        }
+    }
+
+    labels
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn prices_from(closes: &[f64]) -> Vec<PriceData> {
+        let start = Utc::now() - Duration::minutes(closes.len() as i64);
+        closes.iter().enumerate().map(|(i, &c)| {
+            let price = Decimal::from_f64(c).unwrap_or_default();
+            PriceData {
+                timestamp: start + Duration::minutes(i as i64),
+                open: price,
+                high: price,
+                low: price,
+                close: price,
+                volume: Decimal::from(1),
+            }
+        }).collect()
+    }
+
+    /// Deterministic pseudo-noise in [-1, 1]
+    fn noise(i: usize) -> f64 {
+        // splitmix64 hash of the index
+        let mut z = (i as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z as f64 / u64::MAX as f64) * 2.0 - 1.0
+    }
+
+    fn synthetic_regimes() -> Vec<f64> {
+        let mut closes = Vec::new();
+        let mut price = 100.0;
+        for block in 0..6 {
+            for i in 0..150 {
+                let step = block * 150 + i;
+                let ret = match block % 3 {
+                    0 => 0.002 + 0.0005 * noise(step),  // steady trend
+                    1 => 0.001 * noise(step),           // quiet range
+                    _ => 0.02 * noise(step),            // turbulent
+                };
+                price *= 1.0 + ret;
+                closes.push(price);
+            }
+        }
+        closes
+    }
+
+    #[test]
+    fn test_features_distinguish_trend_from_noise() {
+        let trend: Vec<f64> = (0..50).map(|i| 100.0 * 1.01f64.powi(i)).collect();
+        let noisy: Vec<f64> = (0..50).map(|i| 100.0 + noise(i)).collect();
+
+        let trend_features = RegimeFeatures::from_closes(&trend).unwrap();
+        let noisy_features = RegimeFeatures::from_closes(&noisy).unwrap();
+        assert!(trend_features.trend_efficiency > 0.99);
+        assert!(noisy_features.trend_efficiency < trend_features.trend_efficiency);
+        assert!(RegimeFeatures::from_closes(&[1.0, 2.0]).is_none());
+    }
+
+    #[test]
+    fn test_classifier_recognises_synthetic_regimes() {
+        let prices = prices_from(&synthetic_regimes());
+        let classifier = RegimeClassifier::fit(&prices, 30).unwrap();
+
+        let trend: Vec<f64> = (0..40).scan(100.0, |p, i| { *p *= 1.0 + 0.002 + 0.0005 * noise(i + 3); Some(*p) }).collect();
+        let turbulent: Vec<f64> = (0..40).scan(100.0, |p, i| { *p *= 1.0 + 0.02 * noise(i + 7); Some(*p) }).collect();
+
+        let trend_result = classifier.classify(&prices_from(&trend)).unwrap();
+        let turbulent_result = classifier.classify(&prices_from(&turbulent)).unwrap();
+        let quiet: Vec<f64> = (0..40).scan(100.0, |p, i| { *p *= 1.0 + 0.001 * noise(i + 11); Some(*p) }).collect();
+        let quiet_result = classifier.classify(&prices_from(&quiet)).unwrap();
+        assert_eq!(trend_result.regime, Regime::Trending);
+        assert_eq!(turbulent_result.regime, Regime::Volatile);
+        assert_eq!(quiet_result.regime, Regime::Ranging);
+
+        let total = trend_result.trending + trend_result.ranging + trend_result.volatile;
+        assert!((total - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fit_requires_enough_history() {
+        let prices = prices_from(&(0..40).map(|i| 100.0 + i as f64).collect::<Vec<_>>());
+        assert!(RegimeClassifier::fit(&prices, 30).is_err());
+        assert!(RegimeClassifier::fit(&prices, 5).is_err());
+    }
+
+    #[test]
+    fn test_parameter_sets_apply_to_config() {
+        let switching = RegimeSwitching { enabled: true, ..Default::default() };
+        assert!(switching.validate().is_ok());
+
+        let mut config = LROConfig::default();
+        switching.parameters_for(Regime::Volatile).apply(&mut config);
+        assert_eq!(config.signal_strength_threshold, 0.75);
+        assert!(config.trailing_stop_enabled);
+    }
+}
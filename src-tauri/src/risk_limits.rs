@@ -0,0 +1,455 @@
+// Risk Limit Profiles
+// Versioned per-account, per-bot and per-symbol overrides of the risk limits, loaded from
+// data/risk_limits.json, hot-reloaded when the file changes and audited on every change
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::advanced_trading::risk_engine::{ConcentrationLimits, GlobalRiskLimits};
+use crate::enhanced_risk_manager::RiskLimits as TradeRiskLimits;
+use crate::errors::{TradingError, TradingResult};
+use crate::gpu_risk_manager::GpuRiskThresholds;
+use crate::logging::LogCategory;
+use crate::persistence;
+use crate::validation::InputValidator;
+use crate::{log_info, log_warning};
+
+/// Schema version of the risk limit file understood by this build
+pub const RISK_LIMITS_SCHEMA_VERSION: u32 = 1;
+/// Audit entries kept on disk
+const MAX_AUDIT_ENTRIES: usize = 1000;
+
+/// Risk limit store shared by every bot and command
+pub type SharedRiskLimits = Arc<Mutex<RiskLimitStore>>;
+
+/// Partial profile, e.g. `{"global": {"max_drawdown_percent": 10.0}}`
+pub type RiskLimitOverrides = Map<String, Value>;
+
+/// Every configurable risk limit, resolved for one scope
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskLimitProfile {
+    /// Advanced trading engine portfolio limits
+    pub global: GlobalRiskLimits,
+    pub concentration: ConcentrationLimits,
+    /// Per-trade limits of the enhanced risk manager and swing bot
+    pub trade: TradeRiskLimits,
+    pub gpu: GpuRiskThresholds,
+}
+
+/// Versioned risk limit file. Overrides are applied over the built-in defaults in the order
+/// account, bot, symbol, so the most specific scope wins
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskLimitsConfig {
+    pub schema_version: u32,
+    /// Bumped on every saved change; updates must name the revision they were based on
+    #[serde(default)]
+    pub revision: u64,
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub updated_by: Option<String>,
+    #[serde(default)]
+    pub account: RiskLimitOverrides,
+    #[serde(default)]
+    pub bots: BTreeMap<String, RiskLimitOverrides>,
+    /// Keyed by upper-case symbol
+    #[serde(default)]
+    pub symbols: BTreeMap<String, RiskLimitOverrides>,
+}
+
+impl Default for RiskLimitsConfig {
+    fn default() -> Self {
+        Self {
+            schema_version: RISK_LIMITS_SCHEMA_VERSION,
+            revision: 0,
+            updated_at: None,
+            updated_by: None,
+            account: Map::new(),
+            bots: BTreeMap::new(),
+            symbols: BTreeMap::new(),
+        }
+    }
+}
+
+/// Limits resolved for one bot and symbol, with the scope each overridden value came from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveRiskLimits {
+    pub bot: Option<String>,
+    pub symbol: Option<String>,
+    pub revision: u64,
+    pub limits: RiskLimitProfile,
+    /// Field path to "account", "bot:<name>" or "symbol:<SYMBOL>"; unlisted fields are defaults
+    pub sources: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskLimitAuditEntry {
+    pub timestamp: DateTime<Utc>,
+    pub revision: u64,
+    /// User who saved the change, or "file" for an edit picked up by hot reload
+    pub actor: String,
+    /// Dotted path, e.g. "bots.swing_bot.global.max_drawdown_percent"
+    pub path: String,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+impl RiskLimitsConfig {
+    /// Resolve the limits for a bot and symbol (either may be omitted)
+    pub fn resolve(&self, bot: Option<&str>, symbol: Option<&str>) -> TradingResult<EffectiveRiskLimits> {
+        let symbol = symbol.map(|s| s.to_uppercase());
+        let mut layers = vec![("account".to_string(), &self.account)];
+        if let Some(overrides) = bot.and_then(|bot| self.bots.get(bot)) {
+            layers.push((format!("bot:{}", bot.unwrap_or_default()), overrides));
+        }
+        if let Some(overrides) = symbol.as_ref().and_then(|symbol| self.symbols.get(symbol)) {
+            layers.push((format!("symbol:{}", symbol.as_deref().unwrap_or_default()), overrides));
+        }
+
+        let mut resolved = profile_value(&RiskLimitProfile::default())?;
+        let mut sources = BTreeMap::new();
+        for (scope, overrides) in layers {
+            merge(&mut resolved, overrides);
+            let mut paths = BTreeMap::new();
+            flatten("", &Value::Object(overrides.clone()), &mut paths);
+            sources.extend(paths.into_keys().map(|path| (path, scope.clone())));
+        }
+
+        let limits = serde_json::from_value(resolved).map_err(|e| TradingError::config_error(
+            "risk_limits".to_string(),
+            format!("Invalid risk limit override: {}", e)
+        ))?;
+        Ok(EffectiveRiskLimits {
+            bot: bot.map(str::to_string),
+            symbol,
+            revision: self.revision,
+            limits,
+            sources,
+        })
+    }
+
+    /// Check the schema version, that every override names a known limit, and that every
+    /// scope resolves to limits `InputValidator` accepts
+    pub fn validate(&self, validator: &InputValidator) -> TradingResult<()> {
+        if self.schema_version != RISK_LIMITS_SCHEMA_VERSION {
+            return Err(TradingError::config_error(
+                "risk_limits.schema_version".to_string(),
+                format!("Unsupported risk limit schema version {} (expected {})", self.schema_version, RISK_LIMITS_SCHEMA_VERSION)
+            ));
+        }
+
+        let mut known = BTreeMap::new();
+        flatten("", &profile_value(&RiskLimitProfile::default())?, &mut known);
+        let scopes = std::iter::once(("account".to_string(), &self.account))
+            .chain(self.bots.iter().map(|(bot, o)| (format!("bot:{}", bot), o)))
+            .chain(self.symbols.iter().map(|(symbol, o)| (format!("symbol:{}", symbol), o)));
+        for (scope, overrides) in scopes {
+            let mut paths = BTreeMap::new();
+            flatten("", &Value::Object(overrides.clone()), &mut paths);
+            if let Some(unknown) = paths.keys().find(|path| !known.contains_key(*path)) {
+                return Err(TradingError::validation_error(
+                    format!("{}.{}", scope, unknown),
+                    "Unknown risk limit".to_string(),
+                    None
+                ));
+            }
+        }
+        if let Some(symbol) = self.symbols.keys().find(|symbol| **symbol != symbol.to_uppercase()) {
+            return Err(TradingError::validation_error(
+                "risk_limits.symbols".to_string(),
+                "Symbol keys must be upper case".to_string(),
+                Some(symbol.clone())
+            ));
+        }
+
+        validator.validate_risk_limit_profile("account", &self.resolve(None, None)?.limits)?;
+        for bot in self.bots.keys() {
+            validator.validate_risk_limit_profile(&format!("bot:{}", bot), &self.resolve(Some(bot), None)?.limits)?;
+            for symbol in self.symbols.keys() {
+                let scope = format!("bot:{}/symbol:{}", bot, symbol);
+                validator.validate_risk_limit_profile(&scope, &self.resolve(Some(bot), Some(symbol))?.limits)?;
+            }
+        }
+        for symbol in self.symbols.keys() {
+            validator.validate_risk_limit_profile(&format!("symbol:{}", symbol), &self.resolve(None, Some(symbol))?.limits)?;
+        }
+        Ok(())
+    }
+
+    /// Leaf-level differences between two configurations
+    fn diff(&self, other: &RiskLimitsConfig) -> Vec<(String, Option<Value>, Option<Value>)> {
+        let (mut before, mut after) = (BTreeMap::new(), BTreeMap::new());
+        flatten("", &self.overrides_value(), &mut before);
+        flatten("", &other.overrides_value(), &mut after);
+
+        let mut paths: Vec<&String> = before.keys().chain(after.keys()).collect();
+        paths.sort();
+        paths.dedup();
+        paths.into_iter()
+            .filter(|path| before.get(*path) != after.get(*path))
+            .map(|path| (path.clone(), before.get(path).cloned(), after.get(path).cloned()))
+            .collect()
+    }
+
+    fn overrides_value(&self) -> Value {
+        serde_json::json!({
+            "account": self.account,
+            "bots": self.bots,
+            "symbols": self.symbols,
+        })
+    }
+}
+
+/// Loaded risk limit file with hot reload and an audit trail
+#[derive(Debug, Default)]
+pub struct RiskLimitStore {
+    config: RiskLimitsConfig,
+    path: Option<PathBuf>,
+    modified: Option<SystemTime>,
+    audit: Vec<RiskLimitAuditEntry>,
+}
+
+impl RiskLimitStore {
+    /// In-memory store with the built-in defaults that is never written to disk
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("risk_limits.json")
+    }
+
+    /// Load and validate the risk limit file at `path`; the audit trail lives alongside it
+    pub fn open(path: PathBuf) -> TradingResult<Self> {
+        let config: RiskLimitsConfig = persistence::read_json(&path)?.unwrap_or_default();
+        config.validate(&InputValidator::new())?;
+        let audit = persistence::read_json(&Self::audit_path(&path))?.unwrap_or_default();
+        Ok(Self {
+            config,
+            modified: modified_time(&path),
+            path: Some(path),
+            audit,
+        })
+    }
+
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    pub fn config(&self) -> &RiskLimitsConfig {
+        &self.config
+    }
+
+    /// Limits for a bot and symbol as currently loaded
+    pub fn effective(&self, bot: Option<&str>, symbol: Option<&str>) -> TradingResult<EffectiveRiskLimits> {
+        self.config.resolve(bot, symbol)
+    }
+
+    /// Pick up external edits to the file, then resolve; an invalid edit keeps the previous limits
+    pub fn current(&mut self, bot: Option<&str>, symbol: Option<&str>) -> RiskLimitProfile {
+        if let Err(e) = self.reload_if_changed() {
+            log_warning!(LogCategory::Configuration, "Ignoring risk limit file change: {}", e);
+        }
+        self.effective(bot, symbol).map(|effective| effective.limits).unwrap_or_default()
+    }
+
+    /// Replace the configuration; `config.revision` must match the loaded revision
+    pub fn update(&mut self, mut config: RiskLimitsConfig, actor: &str) -> TradingResult<u64> {
+        if config.revision != self.config.revision {
+            return Err(TradingError::validation_error(
+                "risk_limits.revision".to_string(),
+                format!("Risk limits changed since revision {} was loaded (now {})", config.revision, self.config.revision),
+                Some(config.revision.to_string())
+            ));
+        }
+        config.validate(&InputValidator::new())?;
+
+        config.revision = self.config.revision + 1;
+        config.updated_at = Some(Utc::now());
+        config.updated_by = Some(actor.to_string());
+        let changes = self.config.diff(&config);
+        self.config = config;
+        if let Some(path) = self.path.as_ref() {
+            persistence::write_json(path, &self.config)?;
+            self.modified = modified_time(path);
+        }
+        self.record(changes, actor)?;
+        Ok(self.config.revision)
+    }
+
+    /// Reload the file if it changed on disk; returns whether new limits were applied
+    pub fn reload_if_changed(&mut self) -> TradingResult<bool> {
+        let path = match self.path.as_ref() {
+            Some(path) => path.clone(),
+            None => return Ok(false),
+        };
+        let modified = modified_time(&path);
+        if modified == self.modified {
+            return Ok(false);
+        }
+        // Remember the timestamp either way so a bad edit is reported once
+        self.modified = modified;
+
+        let config: RiskLimitsConfig = persistence::read_json(&path)?.unwrap_or_default();
+        config.validate(&InputValidator::new())?;
+        let changes = self.config.diff(&config);
+        self.config = config;
+        log_info!(LogCategory::Configuration, "Risk limits reloaded at revision {} ({} changes)", self.config.revision, changes.len());
+        self.record(changes, "file")?;
+        Ok(true)
+    }
+
+    /// Audit entries, newest first
+    pub fn audit(&self, limit: usize) -> Vec<RiskLimitAuditEntry> {
+        self.audit.iter().rev().take(limit).cloned().collect()
+    }
+
+    fn record(&mut self, changes: Vec<(String, Option<Value>, Option<Value>)>, actor: &str) -> TradingResult<()> {
+        let timestamp = Utc::now();
+        for (path, old_value, new_value) in changes {
+            log_info!(LogCategory::Configuration, "Risk limit {} changed by {}: {:?} -> {:?}", path, actor, old_value, new_value);
+            self.audit.push(RiskLimitAuditEntry {
+                timestamp,
+                revision: self.config.revision,
+                actor: actor.to_string(),
+                path,
+                old_value,
+                new_value,
+            });
+        }
+        if self.audit.len() > MAX_AUDIT_ENTRIES {
+            let excess = self.audit.len() - MAX_AUDIT_ENTRIES;
+            self.audit.drain(..excess);
+        }
+        match self.path.as_ref() {
+            Some(path) => persistence::write_json(&Self::audit_path(path), &self.audit),
+            None => Ok(()),
+        }
+    }
+
+    fn audit_path(path: &std::path::Path) -> PathBuf {
+        path.with_extension("audit.json")
+    }
+}
+
+fn modified_time(path: &std::path::Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+fn profile_value(profile: &RiskLimitProfile) -> TradingResult<Value> {
+    serde_json::to_value(profile).map_err(|e| TradingError::config_error(
+        "risk_limits".to_string(),
+        format!("Failed to serialize risk limits: {}", e)
+    ))
+}
+
+/// Deep-merge an override object into a resolved profile
+fn merge(target: &mut Value, overrides: &RiskLimitOverrides) {
+    for (key, value) in overrides {
+        match (target.get_mut(key), value) {
+            (Some(existing @ Value::Object(_)), Value::Object(nested)) => merge(existing, nested),
+            _ => {
+                if let Value::Object(map) = target {
+                    map.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+/// Dotted paths of every non-object value
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            for (key, nested) in map {
+                let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(&path, nested, out);
+            }
+        }
+        _ => {
+            out.insert(prefix.to_string(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn overrides(value: Value) -> RiskLimitOverrides {
+        value.as_object().cloned().unwrap()
+    }
+
+    fn sample_config() -> RiskLimitsConfig {
+        let mut config = RiskLimitsConfig {
+            account: overrides(json!({"global": {"max_drawdown_percent": 10.0}})),
+            ..Default::default()
+        };
+        config.bots.insert("swing_bot".to_string(), overrides(json!({"global": {"max_single_position_percent": 10.0}})));
+        config.symbols.insert("BTCUSDT".to_string(), overrides(json!({"global": {"max_single_position_percent": 5.0}})));
+        config
+    }
+
+    #[test]
+    fn test_most_specific_scope_wins() {
+        let config = sample_config();
+        let effective = config.resolve(Some("swing_bot"), Some("btcusdt")).unwrap();
+        assert_eq!(effective.limits.global.max_single_position_percent, 5.0);
+        assert_eq!(effective.limits.global.max_drawdown_percent, 10.0);
+        assert_eq!(effective.limits.global.leverage_limit, GlobalRiskLimits::default().leverage_limit);
+        assert_eq!(effective.sources["global.max_single_position_percent"], "symbol:BTCUSDT");
+        assert_eq!(effective.sources["global.max_drawdown_percent"], "account");
+        assert!(!effective.sources.contains_key("global.leverage_limit"));
+
+        let other_symbol = config.resolve(Some("swing_bot"), Some("ETHUSDT")).unwrap();
+        assert_eq!(other_symbol.limits.global.max_single_position_percent, 10.0);
+        assert_eq!(other_symbol.sources["global.max_single_position_percent"], "bot:swing_bot");
+    }
+
+    #[test]
+    fn test_update_validates_and_audits() {
+        let mut store = RiskLimitStore::new();
+        let revision = store.update(sample_config(), "alice").unwrap();
+        assert_eq!(revision, 1);
+        assert_eq!(store.audit(10).len(), 3);
+
+        // Stale revision and unknown limits are rejected
+        assert!(store.update(sample_config(), "alice").is_err());
+        let mut unknown = store.config().clone();
+        unknown.account = overrides(json!({"global": {"max_leverage": 2.0}}));
+        assert!(store.update(unknown, "alice").is_err());
+
+        let mut changed = store.config().clone();
+        changed.account = overrides(json!({"global": {"max_drawdown_percent": 12.0}}));
+        assert_eq!(store.update(changed, "bob").unwrap(), 2);
+        let latest = &store.audit(1)[0];
+        assert_eq!(latest.path, "account.global.max_drawdown_percent");
+        assert_eq!(latest.new_value, Some(json!(12.0)));
+        assert_eq!(latest.actor, "bob");
+    }
+
+    #[test]
+    fn test_hot_reload_from_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("risk_limits.json");
+        let mut store = RiskLimitStore::open(path.clone()).unwrap();
+        assert_eq!(store.current(Some("swing_bot"), None).global.max_single_position_percent, 25.0);
+
+        let mut edited = sample_config();
+        edited.revision = 7;
+        persistence::write_json(&path, &edited).unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + std::time::Duration::from_secs(5)).unwrap();
+
+        assert_eq!(store.current(Some("swing_bot"), None).global.max_single_position_percent, 10.0);
+        assert_eq!(store.config().revision, 7);
+        assert_eq!(store.audit(10)[0].actor, "file");
+        assert!(!store.reload_if_changed().unwrap());
+    }
+}
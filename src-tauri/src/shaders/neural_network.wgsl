@@ -0,0 +1,39 @@
+// GPU Compute Shader for Neural Network Inference
+// Forward pass of one dense layer over a batch of feature vectors
+
+struct LayerDims {
+    inputs: u32,
+    outputs: u32,
+    batch: u32,
+    activation: u32, // 0 = linear, 1 = tanh, 2 = relu
+}
+
+@group(0) @binding(0) var<uniform> dims: LayerDims;
+@group(0) @binding(1) var<storage, read> weights: array<f32>;  // outputs x inputs, row-major
+@group(0) @binding(2) var<storage, read> biases: array<f32>;
+@group(0) @binding(3) var<storage, read> layer_input: array<f32>;  // batch x inputs
+@group(0) @binding(4) var<storage, read_write> layer_output: array<f32>;  // batch x outputs
+
+@compute @workgroup_size(64, 1, 1)
+fn forward_pass(@builtin(global_invocation_id) global_id: vec3<u32>) {
+    let index = global_id.x;
+    if (index >= dims.outputs * dims.batch) {
+        return;
+    }
+
+    let row = index / dims.outputs;
+    let neuron = index % dims.outputs;
+
+    var sum = biases[neuron];
+    for (var i = 0u; i < dims.inputs; i = i + 1u) {
+        sum = sum + weights[neuron * dims.inputs + i] * layer_input[row * dims.inputs + i];
+    }
+
+    if (dims.activation == 1u) {
+        sum = tanh(sum);
+    } else if (dims.activation == 2u) {
+        sum = max(sum, 0.0);
+    }
+
+    layer_output[index] = sum;
+}
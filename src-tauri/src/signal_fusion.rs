@@ -0,0 +1,690 @@
+// Signal Fusion
+// Normalizes LRO, technical, order book, sentiment and risk readings onto a common
+// scale and combines them into one entry score, with each component's contribution
+
+use std::path::PathBuf;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::divergence::Divergence;
+use crate::errors::{TradingError, TradingResult};
+use crate::gpu_risk_manager::TradingRiskAssessment;
+use crate::market_intelligence::IntelligenceReading;
+use crate::models::{MarketDepthAnalysis, PriceData, TradeSide};
+use crate::persistence;
+
+/// Bars of history the price-derived components look back over
+pub const REQUIRED_BARS: usize = TREND_BARS + 1;
+const TREND_BARS: usize = 20;
+const RSI_PERIOD: usize = 14;
+const MIN_TRAINING_SAMPLES: usize = 30;
+/// Share of the newest samples held out to score the model
+const HOLDOUT_FRACTION: f64 = 0.2;
+const MIN_HOLDOUT_SAMPLES: usize = 10;
+const GRADIENT_STEPS: usize = 2000;
+const LEARNING_RATE: f64 = 0.1;
+
+/// Signal sources the fusion layer combines
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum SignalComponent {
+    /// LRO crossover strength
+    Lro,
+    /// Drift of the close over the trend lookback in units of its volatility
+    Trend,
+    /// RSI distance from 50
+    Momentum,
+    /// Net strength of recent bullish and bearish divergences
+    Divergence,
+    /// Order book depth imbalance
+    OrderBook,
+    /// Order book liquidity score
+    Liquidity,
+    /// Market intelligence score
+    Sentiment,
+    /// GPU risk assessment
+    Risk,
+}
+
+impl SignalComponent {
+    pub const ALL: [SignalComponent; 8] = [
+        SignalComponent::Lro,
+        SignalComponent::Trend,
+        SignalComponent::Momentum,
+        SignalComponent::Divergence,
+        SignalComponent::OrderBook,
+        SignalComponent::Liquidity,
+        SignalComponent::Sentiment,
+        SignalComponent::Risk,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SignalComponent::Lro => "LRO",
+            SignalComponent::Trend => "Trend",
+            SignalComponent::Momentum => "Momentum",
+            SignalComponent::Divergence => "Divergence",
+            SignalComponent::OrderBook => "Order Book",
+            SignalComponent::Liquidity => "Liquidity",
+            SignalComponent::Sentiment => "Sentiment",
+            SignalComponent::Risk => "Risk",
+        }
+    }
+}
+
+/// One component's reading on the common scale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentReading {
+    pub component: SignalComponent,
+    /// Value in the component's own units, None when its source had no data
+    pub raw: Option<f64>,
+    /// -1 (argues against the trade) to 1 (argues for it), 0 when unavailable
+    pub normalized: f64,
+}
+
+impl ComponentReading {
+    fn new(component: SignalComponent, raw: Option<f64>, normalize: impl FnOnce(f64) -> f64) -> Self {
+        let raw = raw.filter(|value| value.is_finite());
+        let normalized = raw.map(normalize)
+            .filter(|value| value.is_finite())
+            .map(|value| value.clamp(-1.0, 1.0))
+            .unwrap_or(0.0);
+        Self { component, raw, normalized }
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.raw.is_some()
+    }
+}
+
+/// Everything a fused entry decision is computed from
+#[derive(Debug, Clone)]
+pub struct FusionInputs<'a> {
+    pub side: TradeSide,
+    /// LRO signal strength, 0-1
+    pub lro_strength: f64,
+    /// Recent bars, oldest first
+    pub bars: &'a [PriceData],
+    /// Recent divergences, None when divergence detection is not running
+    pub divergences: Option<&'a [Divergence]>,
+    pub depth: Option<&'a MarketDepthAnalysis>,
+    pub intelligence: Option<&'a IntelligenceReading>,
+    pub risk: Option<&'a TradingRiskAssessment>,
+}
+
+impl FusionInputs<'_> {
+    /// Read every component, oriented so positive values favour `side`. Sources that are
+    /// not directional (liquidity, sentiment, risk) read the same for both sides
+    pub fn readings(&self) -> Vec<ComponentReading> {
+        let direction = match self.side {
+            TradeSide::Long | TradeSide::Buy => 1.0,
+            TradeSide::Short | TradeSide::Sell => -1.0,
+        };
+        let closes: Vec<f64> = self.bars.iter().map(|bar| bar.close.to_f64().unwrap_or(0.0)).collect();
+        let net_divergence = self.divergences.map(|divergences| divergences.iter()
+            .map(|d| if d.kind.is_bullish() { d.strength } else { -d.strength })
+            .sum::<f64>());
+
+        vec![
+            ComponentReading::new(SignalComponent::Lro, Some(self.lro_strength), |strength| 2.0 * strength - 1.0),
+            ComponentReading::new(SignalComponent::Trend, trend_zscore(&closes), |z| direction * (z / 2.0).tanh()),
+            ComponentReading::new(SignalComponent::Momentum, rsi(&closes, RSI_PERIOD), |rsi| direction * (rsi - 50.0) / 50.0),
+            ComponentReading::new(SignalComponent::Divergence, net_divergence, |net| direction * net),
+            ComponentReading::new(SignalComponent::OrderBook, self.depth.map(|d| d.depth_imbalance), |imbalance| direction * imbalance),
+            ComponentReading::new(SignalComponent::Liquidity, self.depth.map(|d| d.liquidity_score), |score| 2.0 * score - 1.0),
+            ComponentReading::new(SignalComponent::Sentiment, self.intelligence.map(|r| r.score), |score| (score - 50.0) / 50.0),
+            ComponentReading::new(SignalComponent::Risk, self.risk.map(|r| r.overall_risk as f64), |risk| 1.0 - 2.0 * risk),
+        ]
+    }
+}
+
+/// How component readings are combined
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum FusionMethod {
+    /// Weighted mean of the available readings
+    #[default]
+    Weighted,
+    /// Logistic model stacked on the readings, trained on backtest trade outcomes
+    Logistic,
+}
+
+/// Relative weights for weighted fusion. Unavailable components are left out and the
+/// rest rescaled, so only the ratios matter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionWeights {
+    pub lro: f64,
+    pub trend: f64,
+    pub momentum: f64,
+    pub divergence: f64,
+    pub order_book: f64,
+    pub liquidity: f64,
+    pub sentiment: f64,
+    pub risk: f64,
+}
+
+impl Default for FusionWeights {
+    fn default() -> Self {
+        Self {
+            lro: 0.4,
+            trend: 0.1,
+            momentum: 0.1,
+            divergence: 0.1,
+            order_book: 0.1,
+            liquidity: 0.05,
+            sentiment: 0.1,
+            risk: 0.05,
+        }
+    }
+}
+
+impl FusionWeights {
+    pub fn get(&self, component: SignalComponent) -> f64 {
+        match component {
+            SignalComponent::Lro => self.lro,
+            SignalComponent::Trend => self.trend,
+            SignalComponent::Momentum => self.momentum,
+            SignalComponent::Divergence => self.divergence,
+            SignalComponent::OrderBook => self.order_book,
+            SignalComponent::Liquidity => self.liquidity,
+            SignalComponent::Sentiment => self.sentiment,
+            SignalComponent::Risk => self.risk,
+        }
+    }
+}
+
+/// Bot entry gate on the fused score
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalFusionConfig {
+    pub enabled: bool,
+    pub method: FusionMethod,
+    pub weights: FusionWeights,
+    /// Fused strength (0-1) an entry must exceed, in place of `signal_strength_threshold`
+    pub entry_threshold: f64,
+}
+
+impl Default for SignalFusionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            method: FusionMethod::Weighted,
+            weights: FusionWeights::default(),
+            entry_threshold: 0.6,
+        }
+    }
+}
+
+impl SignalFusionConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let weights: Vec<f64> = SignalComponent::ALL.iter().map(|&c| self.weights.get(c)).collect();
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err("Signal fusion weights must be non-negative".to_string());
+        }
+        if weights.iter().sum::<f64>() <= 0.0 {
+            return Err("At least one signal fusion weight must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.entry_threshold) {
+            return Err("Signal fusion entry_threshold must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A component's part in one fused decision
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentContribution {
+    pub component: SignalComponent,
+    pub raw: Option<f64>,
+    pub normalized: f64,
+    /// Rescaled configured weight, or the model coefficient per standard deviation
+    pub weight: f64,
+    /// Amount added to the score; 0 for unavailable components
+    pub contribution: f64,
+}
+
+/// Fused entry decision with its attribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusedSignal {
+    pub timestamp: DateTime<Utc>,
+    pub side: TradeSide,
+    /// Method actually used; logistic fusion falls back to weights without a trained model
+    pub method: FusionMethod,
+    /// Weighted mean of the readings (-1 to 1), or the model's log-odds of a winning trade
+    pub score: f64,
+    /// 0-1 value compared with the entry threshold: the rescaled weighted score, or the
+    /// model's win probability
+    pub strength: f64,
+    /// Model log-odds before any component, 0 for weighted fusion
+    pub intercept: f64,
+    pub components: Vec<ComponentContribution>,
+    pub allowed: bool,
+}
+
+/// Combine readings with the configured method
+pub fn fuse(
+    config: &SignalFusionConfig,
+    model: Option<&FusionModel>,
+    side: TradeSide,
+    readings: &[ComponentReading],
+    timestamp: DateTime<Utc>,
+) -> FusedSignal {
+    let (method, score, strength, intercept, components) = match (config.method, model) {
+        (FusionMethod::Logistic, Some(model)) => {
+            let components = model.contributions(readings);
+            let score = model.intercept + components.iter().map(|c| c.contribution).sum::<f64>();
+            (FusionMethod::Logistic, score, sigmoid(score), model.intercept, components)
+        }
+        _ => {
+            let total: f64 = readings.iter()
+                .filter(|r| r.is_available())
+                .map(|r| config.weights.get(r.component))
+                .sum();
+            let components: Vec<ComponentContribution> = readings.iter()
+                .map(|reading| {
+                    let weight = if reading.is_available() && total > 0.0 {
+                        config.weights.get(reading.component) / total
+                    } else {
+                        0.0
+                    };
+                    ComponentContribution {
+                        component: reading.component,
+                        raw: reading.raw,
+                        normalized: reading.normalized,
+                        weight,
+                        contribution: weight * reading.normalized,
+                    }
+                })
+                .collect();
+            let score = components.iter().map(|c| c.contribution).sum::<f64>();
+            (FusionMethod::Weighted, score, (score + 1.0) / 2.0, 0.0, components)
+        }
+    };
+
+    FusedSignal {
+        timestamp,
+        side,
+        method,
+        score,
+        strength,
+        intercept,
+        components,
+        allowed: strength > config.entry_threshold,
+    }
+}
+
+/// Component readings at a trade's entry and whether the trade made money
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionSample {
+    pub timestamp: DateTime<Utc>,
+    pub components: Vec<ComponentReading>,
+    pub won: bool,
+}
+
+/// Out-of-sample scores of the model fitted without the newest samples
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HoldoutScore {
+    pub samples: usize,
+    pub accuracy: f64,
+    pub log_loss: f64,
+    /// Log loss of always predicting the training win rate
+    pub baseline_log_loss: f64,
+}
+
+/// L2-regularized logistic regression of trade outcome on standardized component readings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FusionModel {
+    pub components: Vec<SignalComponent>,
+    pub coefficients: Vec<f64>,
+    pub intercept: f64,
+    pub means: Vec<f64>,
+    /// 0 for components that never varied in training; they get no coefficient
+    pub std_devs: Vec<f64>,
+    pub l2: f64,
+    pub training_samples: usize,
+    pub win_rate: f64,
+    pub holdout: Option<HoldoutScore>,
+    pub trained_at: DateTime<Utc>,
+}
+
+impl FusionModel {
+    /// Fit on samples ordered by time. The newest fifth is first held out to score the
+    /// model out of sample, then the model is refitted on everything
+    pub fn fit(samples: &[FusionSample], l2: f64) -> TradingResult<Self> {
+        if !l2.is_finite() || l2 < 0.0 {
+            return Err(TradingError::validation_error(
+                "l2".to_string(),
+                "L2 penalty must be non-negative".to_string(),
+                Some(l2.to_string())
+            ));
+        }
+        if samples.len() < MIN_TRAINING_SAMPLES {
+            return Err(TradingError::validation_error(
+                "samples".to_string(),
+                format!("Need at least {} trades with recorded signal components", MIN_TRAINING_SAMPLES),
+                Some(samples.len().to_string())
+            ));
+        }
+        let wins = samples.iter().filter(|s| s.won).count();
+        if wins == 0 || wins == samples.len() {
+            return Err(TradingError::validation_error(
+                "samples".to_string(),
+                "Training trades must include both wins and losses".to_string(),
+                Some(format!("{} of {} won", wins, samples.len()))
+            ));
+        }
+
+        let mut ordered = samples.to_vec();
+        ordered.sort_by_key(|sample| sample.timestamp);
+
+        let holdout_count = (ordered.len() as f64 * HOLDOUT_FRACTION) as usize;
+        let (training, holdout) = ordered.split_at(ordered.len() - holdout_count);
+        let holdout = if holdout_count >= MIN_HOLDOUT_SAMPLES && training.iter().any(|s| s.won) && training.iter().any(|s| !s.won) {
+            let model = Self::fit_samples(training, l2);
+            Some(model.score(holdout))
+        } else {
+            None
+        };
+
+        let mut model = Self::fit_samples(&ordered, l2);
+        model.holdout = holdout;
+        Ok(model)
+    }
+
+    fn fit_samples(samples: &[FusionSample], l2: f64) -> Self {
+        let components = SignalComponent::ALL.to_vec();
+        let mut means = Vec::with_capacity(components.len());
+        let mut std_devs = Vec::with_capacity(components.len());
+        for &component in &components {
+            let values: Vec<f64> = samples.iter()
+                .filter_map(|sample| reading_for(&sample.components, component))
+                .map(|reading| reading.normalized)
+                .collect();
+            let (mean, std_dev) = mean_std(&values);
+            means.push(mean);
+            std_devs.push(std_dev);
+        }
+
+        let mut model = Self {
+            coefficients: vec![0.0; components.len()],
+            components,
+            intercept: 0.0,
+            means,
+            std_devs,
+            l2,
+            training_samples: samples.len(),
+            win_rate: samples.iter().filter(|s| s.won).count() as f64 / samples.len() as f64,
+            holdout: None,
+            trained_at: Utc::now(),
+        };
+
+        let features: Vec<Vec<f64>> = samples.iter().map(|sample| model.standardize(&sample.components)).collect();
+        let labels: Vec<f64> = samples.iter().map(|sample| if sample.won { 1.0 } else { 0.0 }).collect();
+        let n = samples.len() as f64;
+        model.intercept = logit(model.win_rate);
+        for _ in 0..GRADIENT_STEPS {
+            let mut gradient = vec![0.0; model.coefficients.len()];
+            let mut intercept_gradient = 0.0;
+            for (row, label) in features.iter().zip(&labels) {
+                let error = sigmoid(model.log_odds(row)) - label;
+                intercept_gradient += error;
+                for (g, x) in gradient.iter_mut().zip(row) {
+                    *g += error * x;
+                }
+            }
+            model.intercept -= LEARNING_RATE * intercept_gradient / n;
+            for (coefficient, g) in model.coefficients.iter_mut().zip(&gradient) {
+                *coefficient -= LEARNING_RATE * (g / n + l2 * *coefficient);
+            }
+        }
+        model
+    }
+
+    /// Standardized readings in coefficient order; unavailable and constant components are 0
+    fn standardize(&self, readings: &[ComponentReading]) -> Vec<f64> {
+        self.components.iter().enumerate()
+            .map(|(i, &component)| match reading_for(readings, component) {
+                Some(reading) if self.std_devs[i] > 0.0 => (reading.normalized - self.means[i]) / self.std_devs[i],
+                _ => 0.0,
+            })
+            .collect()
+    }
+
+    fn log_odds(&self, features: &[f64]) -> f64 {
+        self.intercept + self.coefficients.iter().zip(features).map(|(c, x)| c * x).sum::<f64>()
+    }
+
+    /// Probability that a trade entered on these readings wins
+    pub fn probability(&self, readings: &[ComponentReading]) -> f64 {
+        sigmoid(self.log_odds(&self.standardize(readings)))
+    }
+
+    fn contributions(&self, readings: &[ComponentReading]) -> Vec<ComponentContribution> {
+        let features = self.standardize(readings);
+        self.components.iter().enumerate()
+            .map(|(i, &component)| {
+                let reading = reading_for(readings, component);
+                ComponentContribution {
+                    component,
+                    raw: reading.and_then(|r| r.raw),
+                    normalized: reading.map_or(0.0, |r| r.normalized),
+                    weight: self.coefficients[i],
+                    contribution: self.coefficients[i] * features[i],
+                }
+            })
+            .collect()
+    }
+
+    fn score(&self, samples: &[FusionSample]) -> HoldoutScore {
+        let baseline = self.win_rate.clamp(1e-12, 1.0 - 1e-12);
+        let mut correct = 0;
+        let mut log_loss = 0.0;
+        let mut baseline_log_loss = 0.0;
+        for sample in samples {
+            let p = self.probability(&sample.components).clamp(1e-12, 1.0 - 1e-12);
+            if (p > 0.5) == sample.won {
+                correct += 1;
+            }
+            log_loss -= if sample.won { p.ln() } else { (1.0 - p).ln() };
+            baseline_log_loss -= if sample.won { baseline.ln() } else { (1.0 - baseline).ln() };
+        }
+        let n = samples.len() as f64;
+        HoldoutScore {
+            samples: samples.len(),
+            accuracy: correct as f64 / n,
+            log_loss: log_loss / n,
+            baseline_log_loss: baseline_log_loss / n,
+        }
+    }
+
+    /// Default model location (`data/signal_fusion_model.json`)
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("signal_fusion_model.json")
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        persistence::write_json(&Self::default_path()?, self)
+    }
+
+    /// Load the persisted model, if one has been trained
+    pub fn load() -> TradingResult<Option<Self>> {
+        persistence::read_json(&Self::default_path()?)
+    }
+}
+
+fn reading_for(readings: &[ComponentReading], component: SignalComponent) -> Option<&ComponentReading> {
+    readings.iter().find(|r| r.component == component && r.is_available())
+}
+
+/// Log change of the close over the trend lookback divided by the volatility expected over it
+fn trend_zscore(closes: &[f64]) -> Option<f64> {
+    if closes.len() < TREND_BARS + 1 || closes.iter().any(|c| *c <= 0.0) {
+        return None;
+    }
+    let recent = &closes[closes.len() - TREND_BARS - 1..];
+    let returns: Vec<f64> = recent.windows(2).map(|pair| (pair[1] / pair[0]).ln()).collect();
+    let (_, std_dev) = mean_std(&returns);
+    if std_dev == 0.0 {
+        return None;
+    }
+    Some(returns.iter().sum::<f64>() / (std_dev * (TREND_BARS as f64).sqrt()))
+}
+
+/// Wilder RSI of the last close
+fn rsi(closes: &[f64], period: usize) -> Option<f64> {
+    if closes.len() < period + 1 {
+        return None;
+    }
+    let changes: Vec<f64> = closes.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let mut gain = changes[..period].iter().map(|c| c.max(0.0)).sum::<f64>() / period as f64;
+    let mut loss = changes[..period].iter().map(|c| (-c).max(0.0)).sum::<f64>() / period as f64;
+    for change in &changes[period..] {
+        gain = (gain * (period - 1) as f64 + change.max(0.0)) / period as f64;
+        loss = (loss * (period - 1) as f64 + (-change).max(0.0)) / period as f64;
+    }
+    if gain + loss == 0.0 {
+        return Some(50.0);
+    }
+    Some(100.0 * gain / (gain + loss))
+}
+
+fn mean_std(values: &[f64]) -> (f64, f64) {
+    if values.len() < 2 {
+        return (values.first().copied().unwrap_or(0.0), 0.0);
+    }
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (values.len() - 1) as f64;
+    let std_dev = variance.sqrt();
+    (mean, if std_dev > 1e-9 { std_dev } else { 0.0 })
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn logit(p: f64) -> f64 {
+    let p = p.clamp(1e-6, 1.0 - 1e-6);
+    (p / (1.0 - p)).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn bars(closes: &[f64]) -> Vec<PriceData> {
+        let start = Utc::now() - Duration::hours(closes.len() as i64);
+        closes.iter().enumerate()
+            .map(|(i, &close)| {
+                let close = Decimal::from_f64(close).unwrap();
+                PriceData {
+                    timestamp: start + Duration::hours(i as i64),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    volume: Decimal::ONE,
+                }
+            })
+            .collect()
+    }
+
+    fn reading(component: SignalComponent, normalized: f64) -> ComponentReading {
+        ComponentReading { component, raw: Some(normalized), normalized }
+    }
+
+    #[test]
+    fn test_readings_are_oriented_to_the_side() {
+        let closes: Vec<f64> = (0..40).map(|i| 100.0 + i as f64 + if i % 2 == 0 { 0.3 } else { -0.3 }).collect();
+        let bars = bars(&closes);
+        let inputs = |side| FusionInputs {
+            side,
+            lro_strength: 0.8,
+            bars: &bars,
+            divergences: None,
+            depth: None,
+            intelligence: None,
+            risk: None,
+        };
+
+        let long = inputs(TradeSide::Long).readings();
+        let short = inputs(TradeSide::Short).readings();
+        let get = |readings: &[ComponentReading], component| reading_for(readings, component).map(|r| r.normalized);
+
+        assert!((get(&long, SignalComponent::Lro).unwrap() - 0.6).abs() < 1e-9);
+        assert!(get(&long, SignalComponent::Trend).unwrap() > 0.5);
+        assert!(get(&long, SignalComponent::Momentum).unwrap() > 0.0);
+        assert_eq!(get(&short, SignalComponent::Trend), get(&long, SignalComponent::Trend).map(|t| -t));
+        assert_eq!(get(&long, SignalComponent::OrderBook), None);
+        assert_eq!(long.len(), SignalComponent::ALL.len());
+    }
+
+    #[test]
+    fn test_weighted_fusion_rescales_over_available_components() {
+        let config = SignalFusionConfig { enabled: true, ..Default::default() };
+        let mut readings = vec![reading(SignalComponent::Lro, 0.5), reading(SignalComponent::Trend, -1.0)];
+        readings.push(ComponentReading { component: SignalComponent::OrderBook, raw: None, normalized: 0.0 });
+
+        let fused = fuse(&config, None, TradeSide::Long, &readings, Utc::now());
+        // Weights 0.4 and 0.1 rescale to 0.8 and 0.2
+        assert_eq!(fused.method, FusionMethod::Weighted);
+        assert!((fused.score - (0.8 * 0.5 - 0.2)).abs() < 1e-9);
+        assert!((fused.components.iter().map(|c| c.contribution).sum::<f64>() - fused.score).abs() < 1e-12);
+        assert_eq!(fused.components[2].weight, 0.0);
+        assert!((fused.strength - 0.6).abs() < 1e-9);
+        assert!(!fused.allowed);
+
+        // Logistic fusion without a model falls back to the weights
+        let logistic = SignalFusionConfig { method: FusionMethod::Logistic, ..config };
+        assert_eq!(fuse(&logistic, None, TradeSide::Long, &readings, Utc::now()).method, FusionMethod::Weighted);
+    }
+
+    #[test]
+    fn test_logistic_model_learns_informative_component() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let start = Utc::now() - Duration::days(300);
+        let samples: Vec<FusionSample> = (0..300)
+            .map(|i| {
+                let momentum: f64 = rng.gen_range(-1.0..1.0);
+                let noise: f64 = rng.gen_range(-1.0..1.0);
+                let won = rng.gen::<f64>() < sigmoid(3.0 * momentum);
+                FusionSample {
+                    timestamp: start + Duration::days(i),
+                    components: vec![
+                        reading(SignalComponent::Lro, noise),
+                        reading(SignalComponent::Momentum, momentum),
+                        ComponentReading { component: SignalComponent::Sentiment, raw: None, normalized: 0.0 },
+                    ],
+                    won,
+                }
+            })
+            .collect();
+
+        let model = FusionModel::fit(&samples, 0.01).unwrap();
+        let coefficient = |component| model.coefficients[model.components.iter().position(|c| *c == component).unwrap()];
+        assert!(coefficient(SignalComponent::Momentum) > 0.8);
+        assert!(coefficient(SignalComponent::Lro).abs() < coefficient(SignalComponent::Momentum) / 3.0);
+        assert_eq!(coefficient(SignalComponent::Sentiment), 0.0);
+
+        let holdout = model.holdout.as_ref().unwrap();
+        assert_eq!(holdout.samples, 60);
+        assert!(holdout.log_loss < holdout.baseline_log_loss);
+
+        let bullish = [reading(SignalComponent::Lro, 0.0), reading(SignalComponent::Momentum, 0.9)];
+        let bearish = [reading(SignalComponent::Lro, 0.0), reading(SignalComponent::Momentum, -0.9)];
+        assert!(model.probability(&bullish) > 0.7);
+        assert!(model.probability(&bearish) < 0.3);
+
+        let config = SignalFusionConfig { enabled: true, method: FusionMethod::Logistic, ..Default::default() };
+        let fused = fuse(&config, Some(&model), TradeSide::Long, &bullish, Utc::now());
+        assert_eq!(fused.method, FusionMethod::Logistic);
+        assert!((fused.intercept + fused.components.iter().map(|c| c.contribution).sum::<f64>() - fused.score).abs() < 1e-9);
+        assert!(fused.allowed);
+
+        assert!(FusionModel::fit(&samples[..10], 0.01).is_err());
+    }
+}
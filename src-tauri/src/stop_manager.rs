@@ -0,0 +1,527 @@
+// Stop Manager
+// Fixed %, ATR multiple, chandelier, Parabolic SAR, break-even-after-R and
+// MAE-derived stops with per-position state, shared by the bot and the backtester
+
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::advanced_risk_manager::{DynamicStopLoss, MAETracker, TradeMAE};
+use crate::models::PriceData;
+
+/// How the protective stop of a position is placed and moved
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum StopMethod {
+    /// Fixed percentage from entry, optionally trailing the best price by a percentage
+    #[default]
+    FixedPercent,
+    /// ATR multiple from entry, trailing by an ATR multiple once in profit
+    AtrMultiple,
+    /// Highest high (lowest low for shorts) of the lookback minus an ATR multiple
+    Chandelier,
+    /// Wilder's Parabolic SAR started at entry
+    ParabolicSar,
+    /// Fixed percentage stop moved to entry once the trade is up a given R multiple
+    BreakEvenAfterR,
+    /// Distance from the average adverse excursion of past losing trades
+    MaeDerived,
+}
+
+impl StopMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StopMethod::FixedPercent => "Fixed %",
+            StopMethod::AtrMultiple => "ATR Multiple",
+            StopMethod::Chandelier => "Chandelier",
+            StopMethod::ParabolicSar => "Parabolic SAR",
+            StopMethod::BreakEvenAfterR => "Break-even after R",
+            StopMethod::MaeDerived => "MAE Derived",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopManagerConfig {
+    pub method: StopMethod,
+    pub atr_period: usize,
+    /// ATR multiple for the initial ATR stop
+    pub atr_multiplier: f64,
+    /// ATR multiple the ATR stop trails by
+    pub atr_trailing_multiplier: f64,
+    /// Profit (percent) before the ATR stop starts trailing
+    pub atr_trailing_activation_percent: f64,
+    pub chandelier_period: usize,
+    pub chandelier_multiplier: f64,
+    pub sar_step: f64,
+    pub sar_max: f64,
+    /// R multiple of open profit that moves the stop to break-even
+    pub break_even_r: f64,
+    /// Profit locked in when moving to break-even, in percent of entry
+    pub break_even_offset_percent: f64,
+    /// Losing trades required before MAE stops are used; the fixed stop applies until then
+    pub mae_min_trades: usize,
+    /// Bounds on the initial distance of derived (non-fixed) stops, in percent
+    pub min_stop_percent: f64,
+    pub max_stop_percent: f64,
+}
+
+impl Default for StopManagerConfig {
+    fn default() -> Self {
+        Self {
+            method: StopMethod::FixedPercent,
+            atr_period: 14,
+            atr_multiplier: 2.0,
+            atr_trailing_multiplier: 1.5,
+            atr_trailing_activation_percent: 1.0,
+            chandelier_period: 22,
+            chandelier_multiplier: 3.0,
+            sar_step: 0.02,
+            sar_max: 0.2,
+            break_even_r: 1.0,
+            break_even_offset_percent: 0.1,
+            mae_min_trades: 10,
+            min_stop_percent: 0.5,
+            max_stop_percent: 10.0,
+        }
+    }
+}
+
+impl StopManagerConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.atr_period < 2 || self.chandelier_period < 2 {
+            return Err("Stop ATR and chandelier periods must be at least 2 bars".to_string());
+        }
+        if self.atr_multiplier <= 0.0 || self.atr_trailing_multiplier <= 0.0 || self.chandelier_multiplier <= 0.0 {
+            return Err("Stop ATR multipliers must be positive".to_string());
+        }
+        if self.sar_step <= 0.0 || self.sar_max < self.sar_step || self.sar_max > 1.0 {
+            return Err("Parabolic SAR requires 0 < step <= max <= 1".to_string());
+        }
+        if self.break_even_r <= 0.0 {
+            return Err("Break-even R multiple must be positive".to_string());
+        }
+        if self.min_stop_percent <= 0.0 || self.max_stop_percent < self.min_stop_percent || self.max_stop_percent > 50.0 {
+            return Err("Stop bounds must satisfy 0 < min <= max <= 50 percent".to_string());
+        }
+        Ok(())
+    }
+
+    /// Bars of history needed to place and move stops
+    pub fn required_bars(&self) -> usize {
+        self.atr_period.max(self.chandelier_period) + 1
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SarState {
+    pub sar: f64,
+    pub extreme_point: f64,
+    pub acceleration: f64,
+}
+
+/// Per-position stop state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StopState {
+    pub method: StopMethod,
+    pub is_long: bool,
+    pub entry_price: f64,
+    pub entry_time: DateTime<Utc>,
+    pub initial_stop: f64,
+    pub current_stop: f64,
+    /// Fixed-percent trailing distance, if trailing
+    pub trailing_percent: Option<f64>,
+    pub highest_price: f64,
+    pub lowest_price: f64,
+    pub break_even_reached: bool,
+    pub sar: Option<SarState>,
+    pub bars_held: usize,
+    /// Timestamp of the last bar applied, so a bar is only counted once
+    #[serde(default)]
+    pub last_bar_time: Option<DateTime<Utc>>,
+    pub description: String,
+}
+
+impl StopState {
+    pub fn stop_decimal(&self) -> Decimal {
+        Decimal::from_f64(self.current_stop).unwrap_or(Decimal::ZERO)
+    }
+
+    /// Initial stop distance as a percentage of entry
+    pub fn initial_distance_percent(&self) -> f64 {
+        (self.entry_price - self.initial_stop).abs() / self.entry_price * 100.0
+    }
+
+    /// Whether `price` is at or through the stop
+    pub fn is_hit(&self, price: f64) -> bool {
+        if self.is_long { price <= self.current_stop } else { price >= self.current_stop }
+    }
+
+    /// Fill price if the bar trades through the stop; gaps through the stop fill at the open
+    pub fn intrabar_fill(&self, bar: &PriceData) -> Option<f64> {
+        let (open, high, low) = (bar.open.to_f64()?, bar.high.to_f64()?, bar.low.to_f64()?);
+        if self.is_long && low <= self.current_stop {
+            Some(open.min(self.current_stop))
+        } else if !self.is_long && high >= self.current_stop {
+            Some(open.max(self.current_stop))
+        } else {
+            None
+        }
+    }
+
+    /// Open profit in multiples of the initial risk
+    pub fn r_multiple(&self, price: f64) -> f64 {
+        let risk = (self.entry_price - self.initial_stop).abs();
+        if risk <= 0.0 {
+            return 0.0;
+        }
+        if self.is_long { (price - self.entry_price) / risk } else { (self.entry_price - price) / risk }
+    }
+
+    /// Excursion record for the MAE tracker once the position closes
+    pub fn to_trade_mae(&self, exit_time: DateTime<Utc>, result_percent: f64) -> TradeMAE {
+        let (adverse, favorable) = if self.is_long {
+            (self.entry_price - self.lowest_price, self.highest_price - self.entry_price)
+        } else {
+            (self.highest_price - self.entry_price, self.entry_price - self.lowest_price)
+        };
+        TradeMAE {
+            entry_time: self.entry_time,
+            exit_time: Some(exit_time),
+            max_adverse_excursion: adverse.max(0.0) / self.entry_price * 100.0,
+            max_favorable_excursion: favorable.max(0.0) / self.entry_price * 100.0,
+            final_result: Some(result_percent),
+        }
+    }
+
+    fn ratchet(&mut self, candidate: f64) -> bool {
+        let tighter = if self.is_long { candidate > self.current_stop } else { candidate < self.current_stop };
+        if tighter && candidate.is_finite() {
+            self.current_stop = candidate;
+        }
+        tighter
+    }
+}
+
+/// The caller's fixed stop settings, used by the fixed methods and as the fallback
+/// when there is not enough history for a derived stop
+#[derive(Debug, Clone, Copy)]
+pub struct FixedStop {
+    pub stop_percent: f64,
+    pub trailing_percent: Option<f64>,
+}
+
+/// Places and moves stops according to a `StopManagerConfig`
+#[derive(Debug, Clone, Default)]
+pub struct StopManager {
+    config: StopManagerConfig,
+}
+
+impl StopManager {
+    pub fn new(config: StopManagerConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &StopManagerConfig {
+        &self.config
+    }
+
+    /// Place the initial stop for a new position
+    pub fn open(
+        &self,
+        entry_price: f64,
+        is_long: bool,
+        entry_time: DateTime<Utc>,
+        fixed_stop: FixedStop,
+        recent_bars: &[PriceData],
+        mae: &MAETracker,
+    ) -> StopState {
+        let FixedStop { stop_percent, trailing_percent } = fixed_stop;
+        let fixed = offset(entry_price, is_long, stop_percent);
+        let atr = self.atr(recent_bars);
+        let clamp = |stop: f64| {
+            let distance = (entry_price - stop).abs()
+                .max(entry_price * self.config.min_stop_percent / 100.0)
+                .min(entry_price * self.config.max_stop_percent / 100.0);
+            if is_long { entry_price - distance } else { entry_price + distance }
+        };
+
+        let mut sar = None;
+        let (stop, description) = match self.config.method {
+            StopMethod::FixedPercent => (fixed, match trailing_percent {
+                Some(trail) => format!("{:.2}% stop trailing by {:.2}%", stop_percent, trail),
+                None => format!("{:.2}% stop", stop_percent),
+            }),
+            StopMethod::BreakEvenAfterR => (fixed, format!("{:.2}% stop, break-even after {:.1}R", stop_percent, self.config.break_even_r)),
+            StopMethod::AtrMultiple => match &atr {
+                Some(dynamic) => {
+                    let stop = dynamic.calculate_initial_stop(Decimal::from_f64(entry_price).unwrap_or(Decimal::ZERO), is_long);
+                    (stop.to_f64().unwrap_or(fixed), format!("{:.1}x ATR ({:.4})", self.config.atr_multiplier, dynamic.current_atr()))
+                }
+                None => (fixed, format!("{:.2}% stop (not enough bars for ATR)", stop_percent)),
+            },
+            StopMethod::Chandelier => match (self.chandelier_level(recent_bars, is_long), &atr) {
+                (Some(level), Some(_)) => (clamp(level), format!("chandelier {}x{:.1} ATR", self.config.chandelier_period, self.config.chandelier_multiplier)),
+                _ => (fixed, format!("{:.2}% stop (not enough bars for chandelier)", stop_percent)),
+            },
+            StopMethod::ParabolicSar => {
+                let lookback = &recent_bars[recent_bars.len().saturating_sub(self.config.atr_period)..];
+                let start = if is_long {
+                    lookback.iter().filter_map(|b| b.low.to_f64()).fold(f64::INFINITY, f64::min)
+                } else {
+                    lookback.iter().filter_map(|b| b.high.to_f64()).fold(f64::NEG_INFINITY, f64::max)
+                };
+                let stop = if start.is_finite() { clamp(start) } else { fixed };
+                sar = Some(SarState { sar: stop, extreme_point: entry_price, acceleration: self.config.sar_step });
+                (stop, format!("Parabolic SAR step {:.2} max {:.2}", self.config.sar_step, self.config.sar_max))
+            }
+            StopMethod::MaeDerived => {
+                if mae.losing_trade_count() >= self.config.mae_min_trades {
+                    let percent = mae.suggest_stop_loss();
+                    (clamp(offset(entry_price, is_long, percent)), format!("{:.2}% from 1.2x average losing-trade MAE", percent))
+                } else {
+                    (fixed, format!("{:.2}% stop ({} of {} losing trades for MAE)", stop_percent, mae.losing_trade_count(), self.config.mae_min_trades))
+                }
+            }
+        };
+
+        StopState {
+            method: self.config.method,
+            is_long,
+            entry_price,
+            entry_time,
+            initial_stop: stop,
+            current_stop: stop,
+            trailing_percent: if self.config.method == StopMethod::FixedPercent { trailing_percent } else { None },
+            highest_price: entry_price,
+            lowest_price: entry_price,
+            break_even_reached: false,
+            sar,
+            bars_held: 0,
+            last_bar_time: None,
+            description,
+        }
+    }
+
+    /// Advance the stop with a closed bar. `recent_bars` ends with `bar`.
+    /// Returns the new stop when it moved; stops only ever tighten.
+    pub fn update(&self, state: &mut StopState, bar: &PriceData, recent_bars: &[PriceData]) -> Option<f64> {
+        if state.last_bar_time.is_some_and(|last| bar.timestamp <= last) {
+            return None;
+        }
+        state.last_bar_time = Some(bar.timestamp);
+        let (high, low, close) = (bar.high.to_f64()?, bar.low.to_f64()?, bar.close.to_f64()?);
+        let previous_extreme = if state.is_long { state.highest_price } else { state.lowest_price };
+        state.highest_price = state.highest_price.max(high);
+        state.lowest_price = state.lowest_price.min(low);
+        state.bars_held += 1;
+
+        let candidate = match state.method {
+            StopMethod::FixedPercent => state.trailing_percent.map(|trail| {
+                if state.is_long { state.highest_price * (1.0 - trail / 100.0) } else { state.lowest_price * (1.0 + trail / 100.0) }
+            }),
+            StopMethod::AtrMultiple => self.atr(recent_bars).and_then(|dynamic| {
+                dynamic.update_trailing_stop(
+                    Decimal::from_f64(close)?,
+                    Decimal::from_f64(state.entry_price)?,
+                    state.stop_decimal(),
+                    state.is_long,
+                )
+            }).and_then(|stop| stop.to_f64()),
+            StopMethod::Chandelier => self.chandelier_level(recent_bars, state.is_long),
+            StopMethod::ParabolicSar => {
+                let step = self.config.sar_step;
+                let max = self.config.sar_max;
+                let is_long = state.is_long;
+                let sar = state.sar.as_mut()?;
+                let mut next = sar.sar + sar.acceleration * (sar.extreme_point - sar.sar);
+                // SAR may not move into the previous two bars' range
+                let previous = &recent_bars[recent_bars.len().saturating_sub(3)..recent_bars.len().saturating_sub(1)];
+                for prior in previous {
+                    next = if is_long { next.min(prior.low.to_f64().unwrap_or(next)) } else { next.max(prior.high.to_f64().unwrap_or(next)) };
+                }
+                if (is_long && high > previous_extreme) || (!is_long && low < previous_extreme) {
+                    sar.extreme_point = if is_long { high } else { low };
+                    sar.acceleration = (sar.acceleration + step).min(max);
+                }
+                sar.sar = next;
+                Some(next)
+            }
+            StopMethod::BreakEvenAfterR => {
+                let favourable = if state.is_long { state.highest_price } else { state.lowest_price };
+                if !state.break_even_reached && state.r_multiple(favourable) >= self.config.break_even_r {
+                    state.break_even_reached = true;
+                    Some(offset(state.entry_price, !state.is_long, self.config.break_even_offset_percent))
+                } else {
+                    None
+                }
+            }
+            StopMethod::MaeDerived => None,
+        };
+
+        candidate.filter(|stop| state.ratchet(*stop)).map(|_| state.current_stop)
+    }
+
+    /// DynamicStopLoss primed with the ATR of `bars`
+    fn atr(&self, bars: &[PriceData]) -> Option<DynamicStopLoss> {
+        let bars = &bars[bars.len().saturating_sub(self.config.atr_period + 1)..];
+        if bars.len() < 2 {
+            return None;
+        }
+        let mut dynamic = DynamicStopLoss::with_params(
+            self.config.atr_period,
+            self.config.atr_multiplier,
+            self.config.min_stop_percent,
+            self.config.max_stop_percent,
+            self.config.atr_trailing_activation_percent,
+            self.config.atr_trailing_multiplier,
+        );
+        for pair in bars.windows(2) {
+            dynamic.update_atr(pair[1].high.to_f64()?, pair[1].low.to_f64()?, pair[0].close.to_f64()?);
+        }
+        Some(dynamic)
+    }
+
+    fn chandelier_level(&self, bars: &[PriceData], is_long: bool) -> Option<f64> {
+        let atr = self.atr(bars)?.current_atr();
+        let window = &bars[bars.len().saturating_sub(self.config.chandelier_period)..];
+        let distance = atr * self.config.chandelier_multiplier;
+        if is_long {
+            let highest = window.iter().filter_map(|b| b.high.to_f64()).fold(f64::NEG_INFINITY, f64::max);
+            highest.is_finite().then_some(highest - distance)
+        } else {
+            let lowest = window.iter().filter_map(|b| b.low.to_f64()).fold(f64::INFINITY, f64::min);
+            lowest.is_finite().then_some(lowest + distance)
+        }
+    }
+}
+
+/// Price `percent` away from `price` on the losing side of a long (or short) position
+fn offset(price: f64, is_long: bool, percent: f64) -> f64 {
+    if is_long { price * (1.0 - percent / 100.0) } else { price * (1.0 + percent / 100.0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn bar(i: i64, open: f64, high: f64, low: f64, close: f64) -> PriceData {
+        PriceData {
+            timestamp: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::hours(i),
+            open: Decimal::from_f64(open).unwrap(),
+            high: Decimal::from_f64(high).unwrap(),
+            low: Decimal::from_f64(low).unwrap(),
+            close: Decimal::from_f64(close).unwrap(),
+            volume: Decimal::ONE,
+        }
+    }
+
+    /// Flat bars with a 2-point range around 100
+    fn history(count: i64) -> Vec<PriceData> {
+        (0..count).map(|i| bar(i, 100.0, 101.0, 99.0, 100.0)).collect()
+    }
+
+    fn manager(method: StopMethod) -> StopManager {
+        StopManager::new(StopManagerConfig { method, ..Default::default() })
+    }
+
+    fn open(manager: &StopManager, bars: &[PriceData], trailing: Option<f64>) -> StopState {
+        manager.open(100.0, true, Utc::now(), FixedStop { stop_percent: 2.0, trailing_percent: trailing }, bars, &MAETracker::new())
+    }
+
+    /// Feed rising bars and return the stop after each
+    fn rally(manager: &StopManager, state: &mut StopState, bars: &mut Vec<PriceData>) -> Vec<f64> {
+        (0..5).map(|i| {
+            let price = 101.0 + i as f64 * 2.0;
+            let next = bar(bars.len() as i64, price - 1.0, price + 1.0, price - 1.0, price);
+            bars.push(next.clone());
+            manager.update(state, &next, bars);
+            state.current_stop
+        }).collect()
+    }
+
+    #[test]
+    fn test_fixed_percent_and_trailing() {
+        let mut bars = history(20);
+        let fixed = manager(StopMethod::FixedPercent);
+        let mut state = open(&fixed, &bars, None);
+        assert!((state.current_stop - 98.0).abs() < 1e-9);
+        assert_eq!(rally(&fixed, &mut state, &mut bars.clone()), vec![98.0; 5]);
+
+        let mut trailing = open(&fixed, &bars, Some(1.0));
+        let stops = rally(&fixed, &mut trailing, &mut bars);
+        // Trails 1% under the highest high (110 on the last bar)
+        assert!((stops[4] - 108.9).abs() < 1e-9);
+        assert!(stops.windows(2).all(|w| w[1] >= w[0]));
+    }
+
+    #[test]
+    fn test_atr_and_chandelier_stops() {
+        let mut bars = history(30);
+        // ATR of the flat history is 2, so a 2x ATR stop sits 4 below entry
+        let atr = manager(StopMethod::AtrMultiple);
+        let mut state = open(&atr, &bars, None);
+        assert!((state.current_stop - 96.0).abs() < 1e-9);
+        let stops = rally(&atr, &mut state, &mut bars.clone());
+        assert!(stops[4] > 96.0);
+
+        // Highest high 101 minus 3 x ATR 2
+        let chandelier = manager(StopMethod::Chandelier);
+        let mut state = open(&chandelier, &bars, None);
+        assert!((state.current_stop - 95.0).abs() < 1e-9);
+        let stops = rally(&chandelier, &mut state, &mut bars);
+        // Rally true ranges are 2, 3, 3, 3, 3 against nine flat bars of 2
+        assert!((stops[4] - (110.0 - 3.0 * 32.0 / 14.0)).abs() < 1e-9);
+        assert!(state.is_hit(103.0));
+    }
+
+    #[test]
+    fn test_parabolic_sar_accelerates() {
+        let mut bars = history(20);
+        let sar = manager(StopMethod::ParabolicSar);
+        let mut state = open(&sar, &bars, None);
+        assert!((state.current_stop - 99.0).abs() < 1e-9);
+
+        let stops = rally(&sar, &mut state, &mut bars);
+        assert!(stops.windows(2).all(|w| w[1] >= w[0]));
+        let sar_state = state.sar.as_ref().unwrap();
+        assert!((sar_state.acceleration - 0.12).abs() < 1e-9);
+        assert!((sar_state.extreme_point - 110.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_break_even_after_r() {
+        let mut bars = history(5);
+        let break_even = manager(StopMethod::BreakEvenAfterR);
+        let mut state = open(&break_even, &bars, None);
+        assert!((state.current_stop - 98.0).abs() < 1e-9);
+
+        // +1R is 102: the first rally bar's high of 102 arms break-even
+        let stops = rally(&break_even, &mut state, &mut bars);
+        assert!(state.break_even_reached);
+        assert!((stops[0] - 100.1).abs() < 1e-9);
+        assert!(stops.iter().all(|s| (s - 100.1).abs() < 1e-9));
+    }
+
+    #[test]
+    fn test_mae_derived_stop_and_intrabar_fill() {
+        let bars = history(5);
+        let mae_manager = manager(StopMethod::MaeDerived);
+        let mut tracker = MAETracker::new();
+        assert!((mae_manager.open(100.0, true, Utc::now(), FixedStop { stop_percent: 2.0, trailing_percent: None }, &bars, &tracker).current_stop - 98.0).abs() < 1e-9);
+
+        let entry = open(&mae_manager, &bars, None);
+        for _ in 0..10 {
+            tracker.record_trade(entry.to_trade_mae(Utc::now(), -1.0));
+            tracker.record_trade(TradeMAE { max_adverse_excursion: 2.5, ..entry.to_trade_mae(Utc::now(), -1.0) });
+        }
+        // Average losing MAE is 1.25% (0% and 2.5%), plus a 20% buffer
+        let state = mae_manager.open(100.0, true, Utc::now(), FixedStop { stop_percent: 2.0, trailing_percent: None }, &bars, &tracker);
+        assert!((state.current_stop - 98.5).abs() < 1e-9);
+
+        assert_eq!(state.intrabar_fill(&bar(0, 99.0, 99.5, 98.0, 99.0)), Some(98.5));
+        assert_eq!(state.intrabar_fill(&bar(0, 97.0, 99.5, 96.0, 99.0)), Some(97.0));
+        assert_eq!(state.intrabar_fill(&bar(0, 99.0, 99.5, 98.9, 99.0)), None);
+    }
+}
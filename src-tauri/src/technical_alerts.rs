@@ -0,0 +1,866 @@
+// Technical Alert System
+// Evaluates user-defined rules against live bars (price and indicator levels,
+// crossovers, candlestick patterns, volume spikes and divergences) with cooldowns
+// and one-shot or recurring triggers. Rules and alert history persist to disk.
+
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use crate::advanced_trading::technical_analysis::PatternType;
+use crate::divergence::{self, DivergenceDetector, OscillatorSource};
+use crate::errors::{TradingError, TradingResult};
+use crate::models::PriceData;
+use crate::multi_timeframe::{timeframe_duration, MultiTimeframeSeries};
+use crate::persistence;
+
+const MAX_BARS_PER_STREAM: usize = 300;
+const MAX_ALERT_HISTORY: usize = 1000;
+
+/// Candlestick patterns the alert engine can recognise on completed bars
+pub const SUPPORTED_PATTERNS: [PatternType; 7] = [
+    PatternType::Doji,
+    PatternType::Hammer,
+    PatternType::ShootingStar,
+    PatternType::Engulfing,
+    PatternType::Harami,
+    PatternType::MorningStar,
+    PatternType::EveningStar,
+];
+
+/// Value series an alert condition can watch
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AlertIndicator {
+    Price,
+    Volume,
+    Rsi { period: usize },
+    Lro { period: usize },
+    MacdHistogram { fast: usize, slow: usize, signal: usize },
+    Sma { period: usize },
+    Ema { period: usize },
+    Obv,
+}
+
+impl AlertIndicator {
+    pub fn name(&self) -> String {
+        match self {
+            AlertIndicator::Price => "Price".to_string(),
+            AlertIndicator::Volume => "Volume".to_string(),
+            AlertIndicator::Rsi { period } => format!("RSI({})", period),
+            AlertIndicator::Lro { period } => format!("LRO({})", period),
+            AlertIndicator::MacdHistogram { fast, slow, signal } => format!("MACD Histogram({},{},{})", fast, slow, signal),
+            AlertIndicator::Sma { period } => format!("SMA({})", period),
+            AlertIndicator::Ema { period } => format!("EMA({})", period),
+            AlertIndicator::Obv => "OBV".to_string(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let valid = match self {
+            AlertIndicator::Rsi { period } | AlertIndicator::Lro { period } => *period >= 2,
+            AlertIndicator::Sma { period } | AlertIndicator::Ema { period } => *period >= 1,
+            AlertIndicator::MacdHistogram { fast, slow, signal } => *fast > 0 && fast < slow && *signal > 0,
+            AlertIndicator::Price | AlertIndicator::Volume | AlertIndicator::Obv => true,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(format!("Invalid indicator parameters for {}", self.name()))
+        }
+    }
+
+    /// Indicator values aligned with `bars` (NaN during warm-up)
+    fn series(&self, bars: &[PriceData]) -> Vec<f64> {
+        let closes: Vec<f64> = bars.iter().map(|b| b.close.to_f64().unwrap_or(0.0)).collect();
+        match self {
+            AlertIndicator::Price => closes,
+            AlertIndicator::Volume => bars.iter().map(|b| b.volume.to_f64().unwrap_or(0.0)).collect(),
+            AlertIndicator::Rsi { period } => divergence::rsi_series(&closes, *period),
+            AlertIndicator::Lro { period } => divergence::lro_series(&closes, *period),
+            AlertIndicator::MacdHistogram { fast, slow, signal } => divergence::macd_histogram_series(&closes, *fast, *slow, *signal),
+            AlertIndicator::Sma { period } => sma_series(&closes, *period),
+            AlertIndicator::Ema { period } => ema_series(&closes, *period),
+            AlertIndicator::Obv => divergence::obv_series(bars),
+        }
+    }
+}
+
+/// Condition evaluated on each completed bar
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum AlertCondition {
+    Above { indicator: AlertIndicator, threshold: f64 },
+    Below { indicator: AlertIndicator, threshold: f64 },
+    CrossesAbove { indicator: AlertIndicator, threshold: f64 },
+    CrossesBelow { indicator: AlertIndicator, threshold: f64 },
+    /// `fast` crosses above `slow` (e.g. golden cross)
+    CrossoverAbove { fast: AlertIndicator, slow: AlertIndicator },
+    /// `fast` crosses below `slow` (e.g. death cross)
+    CrossoverBelow { fast: AlertIndicator, slow: AlertIndicator },
+    /// Volume exceeds `multiplier` times the average of the previous `lookback` bars
+    VolumeSpike { lookback: usize, multiplier: f64 },
+    PatternCompleted { pattern: PatternType },
+    DivergenceDetected { source: OscillatorSource },
+}
+
+/// Outcome of a condition that fired on the latest bar
+struct ConditionMatch {
+    current_value: f64,
+    threshold_value: f64,
+    message: String,
+}
+
+impl AlertCondition {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            AlertCondition::Above { indicator, threshold }
+            | AlertCondition::Below { indicator, threshold }
+            | AlertCondition::CrossesAbove { indicator, threshold }
+            | AlertCondition::CrossesBelow { indicator, threshold } => {
+                if !threshold.is_finite() {
+                    return Err("Alert threshold must be a finite number".to_string());
+                }
+                indicator.validate()
+            }
+            AlertCondition::CrossoverAbove { fast, slow } | AlertCondition::CrossoverBelow { fast, slow } => {
+                if fast == slow {
+                    return Err("Crossover alerts need two different indicators".to_string());
+                }
+                fast.validate()?;
+                slow.validate()
+            }
+            AlertCondition::VolumeSpike { lookback, multiplier } => {
+                if *lookback < 2 {
+                    return Err("Volume spike lookback must be at least 2 bars".to_string());
+                }
+                if !(*multiplier > 1.0 && multiplier.is_finite()) {
+                    return Err("Volume spike multiplier must be greater than 1".to_string());
+                }
+                Ok(())
+            }
+            AlertCondition::PatternCompleted { pattern } => {
+                if SUPPORTED_PATTERNS.contains(pattern) {
+                    Ok(())
+                } else {
+                    Err(format!("Pattern {:?} is not supported for alerts", pattern))
+                }
+            }
+            AlertCondition::DivergenceDetected { .. } => Ok(()),
+        }
+    }
+
+    fn evaluate(&self, bars: &[PriceData]) -> Option<ConditionMatch> {
+        match self {
+            AlertCondition::Above { indicator, threshold } => {
+                let current = last_value(&indicator.series(bars))?;
+                (current > *threshold).then(|| ConditionMatch {
+                    current_value: current,
+                    threshold_value: *threshold,
+                    message: format!("{} is above {} ({:.4})", indicator.name(), threshold, current),
+                })
+            }
+            AlertCondition::Below { indicator, threshold } => {
+                let current = last_value(&indicator.series(bars))?;
+                (current < *threshold).then(|| ConditionMatch {
+                    current_value: current,
+                    threshold_value: *threshold,
+                    message: format!("{} is below {} ({:.4})", indicator.name(), threshold, current),
+                })
+            }
+            AlertCondition::CrossesAbove { indicator, threshold } => {
+                let (previous, current) = last_two(&indicator.series(bars))?;
+                (previous <= *threshold && current > *threshold).then(|| ConditionMatch {
+                    current_value: current,
+                    threshold_value: *threshold,
+                    message: format!("{} crossed above {} ({:.4})", indicator.name(), threshold, current),
+                })
+            }
+            AlertCondition::CrossesBelow { indicator, threshold } => {
+                let (previous, current) = last_two(&indicator.series(bars))?;
+                (previous >= *threshold && current < *threshold).then(|| ConditionMatch {
+                    current_value: current,
+                    threshold_value: *threshold,
+                    message: format!("{} crossed below {} ({:.4})", indicator.name(), threshold, current),
+                })
+            }
+            AlertCondition::CrossoverAbove { fast, slow } => {
+                let (fast_previous, fast_current) = last_two(&fast.series(bars))?;
+                let (slow_previous, slow_current) = last_two(&slow.series(bars))?;
+                (fast_previous <= slow_previous && fast_current > slow_current).then(|| ConditionMatch {
+                    current_value: fast_current,
+                    threshold_value: slow_current,
+                    message: format!("{} crossed above {}", fast.name(), slow.name()),
+                })
+            }
+            AlertCondition::CrossoverBelow { fast, slow } => {
+                let (fast_previous, fast_current) = last_two(&fast.series(bars))?;
+                let (slow_previous, slow_current) = last_two(&slow.series(bars))?;
+                (fast_previous >= slow_previous && fast_current < slow_current).then(|| ConditionMatch {
+                    current_value: fast_current,
+                    threshold_value: slow_current,
+                    message: format!("{} crossed below {}", fast.name(), slow.name()),
+                })
+            }
+            AlertCondition::VolumeSpike { lookback, multiplier } => {
+                if bars.len() < lookback + 1 {
+                    return None;
+                }
+                let volumes: Vec<f64> = bars.iter().map(|b| b.volume.to_f64().unwrap_or(0.0)).collect();
+                let current = volumes[volumes.len() - 1];
+                let previous = &volumes[volumes.len() - 1 - lookback..volumes.len() - 1];
+                let average = previous.iter().sum::<f64>() / *lookback as f64;
+                let threshold = average * multiplier;
+                (average > 0.0 && current > threshold).then(|| ConditionMatch {
+                    current_value: current,
+                    threshold_value: threshold,
+                    message: format!("Volume spike: {:.2} is {:.1}x the {}-bar average", current, current / average, lookback),
+                })
+            }
+            AlertCondition::PatternCompleted { pattern } => {
+                let close = bars.last()?.close.to_f64().unwrap_or(0.0);
+                detect_candlestick_patterns(bars).contains(pattern).then(|| ConditionMatch {
+                    current_value: close,
+                    threshold_value: close,
+                    message: format!("{:?} pattern completed", pattern),
+                })
+            }
+            AlertCondition::DivergenceDetected { source } => {
+                let detector = DivergenceDetector::default();
+                let oscillator = detector.oscillator_series(bars, *source);
+                let last_index = bars.len().checked_sub(1)?;
+                // Only divergences confirmed on this bar so each one alerts once
+                let divergence = detector.scan_with(bars, &oscillator, *source)
+                    .into_iter()
+                    .rfind(|d| d.confirmed_index == last_index)?;
+                Some(ConditionMatch {
+                    current_value: divergence.strength,
+                    threshold_value: detector.config().min_strength,
+                    message: format!("{} divergence on {} (strength {:.2})",
+                        divergence.kind.as_str(), source.as_str(), divergence.strength),
+                })
+            }
+        }
+    }
+}
+
+/// Whether a rule disarms after its first trigger
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+pub enum AlertTrigger {
+    OneShot,
+    #[default]
+    Recurring,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertRule {
+    pub rule_id: String,
+    pub name: String,
+    pub symbol: String,
+    pub timeframe: String,
+    pub condition: AlertCondition,
+    pub trigger: AlertTrigger,
+    /// Minimum time between triggers of a recurring rule
+    pub cooldown_seconds: u64,
+    pub enabled: bool,
+    pub created_at: DateTime<Utc>,
+    pub last_triggered: Option<DateTime<Utc>>,
+    pub trigger_count: u64,
+}
+
+/// Rule definition as submitted from the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewAlertRule {
+    pub name: String,
+    pub symbol: String,
+    pub timeframe: String,
+    pub condition: AlertCondition,
+    #[serde(default)]
+    pub trigger: AlertTrigger,
+    #[serde(default)]
+    pub cooldown_seconds: u64,
+}
+
+impl AlertRule {
+    pub fn new(definition: NewAlertRule) -> Self {
+        Self {
+            rule_id: uuid::Uuid::new_v4().to_string(),
+            name: definition.name,
+            symbol: definition.symbol.to_uppercase(),
+            timeframe: definition.timeframe,
+            condition: definition.condition,
+            trigger: definition.trigger,
+            cooldown_seconds: definition.cooldown_seconds,
+            enabled: true,
+            created_at: Utc::now(),
+            last_triggered: None,
+            trigger_count: 0,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Alert rule name cannot be empty".to_string());
+        }
+        if self.symbol.trim().is_empty() {
+            return Err("Alert rule symbol cannot be empty".to_string());
+        }
+        if timeframe_duration(&self.timeframe).is_none() {
+            return Err(format!("Unsupported alert timeframe: {}", self.timeframe));
+        }
+        self.condition.validate()
+    }
+
+    fn in_cooldown(&self, now: DateTime<Utc>) -> bool {
+        match self.last_triggered {
+            Some(last) => now < last + Duration::seconds(self.cooldown_seconds as i64),
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TechnicalAlert {
+    pub alert_id: String,
+    pub symbol: String,
+    pub timeframe: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub message: String,
+    pub triggered_at: DateTime<Utc>,
+    pub current_value: f64,
+    pub threshold_value: f64,
+}
+
+/// On-disk layout of the alert file
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct AlertStore {
+    rules: Vec<AlertRule>,
+    history: Vec<TechnicalAlert>,
+}
+
+/// Technical alert system
+pub struct TechnicalAlertSystem {
+    alert_rules: Vec<AlertRule>,
+    active_alerts: VecDeque<TechnicalAlert>,
+    // Closed bars per (symbol, timeframe) that rules are evaluated on
+    bars: HashMap<(String, String), VecDeque<PriceData>>,
+    // Feed bar per (symbol, timeframe) that is still receiving updates
+    forming: HashMap<(String, String), PriceData>,
+    // Rule timeframes above the feed's, resampled per (symbol, rule timeframe)
+    resampled: HashMap<(String, String), MultiTimeframeSeries>,
+    path: Option<PathBuf>,
+}
+
+impl Default for TechnicalAlertSystem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TechnicalAlertSystem {
+    /// In-memory alert system that is never written to disk
+    pub fn new() -> Self {
+        Self {
+            alert_rules: Vec::new(),
+            active_alerts: VecDeque::new(),
+            bars: HashMap::new(),
+            forming: HashMap::new(),
+            resampled: HashMap::new(),
+            path: None,
+        }
+    }
+
+    pub fn default_path() -> TradingResult<PathBuf> {
+        persistence::data_file("technical_alerts.json")
+    }
+
+    /// Load rules and history from `path`; changes are saved back to the same file
+    pub fn open(path: PathBuf) -> TradingResult<Self> {
+        let store: AlertStore = persistence::read_json(&path)?.unwrap_or_default();
+        Ok(Self {
+            alert_rules: store.rules,
+            active_alerts: store.history.into_iter().collect(),
+            bars: HashMap::new(),
+            forming: HashMap::new(),
+            resampled: HashMap::new(),
+            path: Some(path),
+        })
+    }
+
+    pub fn open_default() -> TradingResult<Self> {
+        Self::open(Self::default_path()?)
+    }
+
+    pub fn save(&self) -> TradingResult<()> {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let store = AlertStore {
+            rules: self.alert_rules.clone(),
+            history: self.active_alerts.iter().cloned().collect(),
+        };
+        persistence::write_json(path, &store)
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.alert_rules
+    }
+
+    pub fn add_rule(&mut self, definition: NewAlertRule) -> TradingResult<AlertRule> {
+        let rule = AlertRule::new(definition);
+        rule.validate()
+            .map_err(|e| TradingError::validation_error("alert_rule".to_string(), e, None))?;
+        self.alert_rules.push(rule.clone());
+        self.save()?;
+        Ok(rule)
+    }
+
+    /// Delete a rule, returning whether it existed
+    pub fn remove_rule(&mut self, rule_id: &str) -> TradingResult<bool> {
+        let before = self.alert_rules.len();
+        self.alert_rules.retain(|rule| rule.rule_id != rule_id);
+        let removed = self.alert_rules.len() != before;
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Triggered alerts, newest first
+    pub fn history(&self, limit: usize) -> Vec<TechnicalAlert> {
+        self.active_alerts.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Record a live update of the feed's current bar for `symbol`/`timeframe`.
+    ///
+    /// An update with the same timestamp replaces the forming bar and older updates are
+    /// ignored. Rules see the forming bar only once a later bar shows it has closed.
+    pub fn on_feed_update(&mut self, symbol: &str, timeframe: &str, bar: &PriceData) -> Vec<TechnicalAlert> {
+        let key = (symbol.to_uppercase(), timeframe.to_string());
+        let closed = match self.forming.get(&key) {
+            Some(forming) if bar.timestamp < forming.timestamp => return Vec::new(),
+            Some(forming) if bar.timestamp > forming.timestamp => Some(forming.clone()),
+            _ => None,
+        };
+        self.forming.insert(key, bar.clone());
+
+        closed.map(|closed| self.on_bar(symbol, timeframe, &closed)).unwrap_or_default()
+    }
+
+    /// Record a closed feed bar for `symbol`/`timeframe` and evaluate the matching rules.
+    ///
+    /// Rules on longer timeframes are evaluated when the feed completes one of their bars.
+    /// Bars at or before the last recorded one are ignored. Cooldowns are measured in bar time.
+    pub fn on_bar(&mut self, symbol: &str, timeframe: &str, bar: &PriceData) -> Vec<TechnicalAlert> {
+        let symbol = symbol.to_uppercase();
+        let mut triggered = self.evaluate_rules(&symbol, timeframe, bar);
+
+        let mut rule_timeframes: Vec<String> = self.alert_rules.iter()
+            .filter(|rule| rule.enabled && rule.symbol == symbol && rule.timeframe != timeframe)
+            .map(|rule| rule.timeframe.clone())
+            .collect();
+        rule_timeframes.sort();
+        rule_timeframes.dedup();
+
+        for rule_timeframe in rule_timeframes {
+            let series = self.resampled.entry((symbol.clone(), rule_timeframe.clone()))
+                .or_insert_with(|| MultiTimeframeSeries::new(timeframe, std::slice::from_ref(&rule_timeframe)));
+            if series.base_timeframe() != timeframe {
+                *series = MultiTimeframeSeries::new(timeframe, std::slice::from_ref(&rule_timeframe));
+            }
+
+            let previous = series.last_completed(&rule_timeframe).map(|completed| completed.timestamp);
+            series.update(bar);
+            let completed = series.last_completed(&rule_timeframe)
+                .filter(|completed| Some(completed.timestamp) != previous)
+                .cloned();
+            if let Some(completed) = completed {
+                triggered.extend(self.evaluate_rules(&symbol, &rule_timeframe, &completed));
+            }
+        }
+
+        for alert in &triggered {
+            self.active_alerts.push_back(alert.clone());
+            if self.active_alerts.len() > MAX_ALERT_HISTORY {
+                self.active_alerts.pop_front();
+            }
+        }
+        triggered
+    }
+
+    /// Append a closed bar to the (symbol, timeframe) buffer and evaluate that timeframe's rules
+    fn evaluate_rules(&mut self, symbol: &str, timeframe: &str, bar: &PriceData) -> Vec<TechnicalAlert> {
+        let buffer = self.bars.entry((symbol.to_string(), timeframe.to_string())).or_default();
+        if buffer.back().is_some_and(|last| bar.timestamp <= last.timestamp) {
+            return Vec::new();
+        }
+        buffer.push_back(bar.clone());
+        if buffer.len() > MAX_BARS_PER_STREAM {
+            buffer.pop_front();
+        }
+        let bars: Vec<PriceData> = buffer.iter().cloned().collect();
+
+        let mut triggered = Vec::new();
+        for rule in &mut self.alert_rules {
+            if !rule.enabled || rule.symbol != symbol || rule.timeframe != timeframe || rule.in_cooldown(bar.timestamp) {
+                continue;
+            }
+
+            if let Some(matched) = rule.condition.evaluate(&bars) {
+                rule.last_triggered = Some(bar.timestamp);
+                rule.trigger_count += 1;
+                if rule.trigger == AlertTrigger::OneShot {
+                    rule.enabled = false;
+                }
+
+                triggered.push(TechnicalAlert {
+                    alert_id: uuid::Uuid::new_v4().to_string(),
+                    symbol: symbol.to_string(),
+                    timeframe: timeframe.to_string(),
+                    rule_id: rule.rule_id.clone(),
+                    rule_name: rule.name.clone(),
+                    message: format!("{} {}: {}", symbol, timeframe, matched.message),
+                    triggered_at: bar.timestamp,
+                    current_value: matched.current_value,
+                    threshold_value: matched.threshold_value,
+                });
+            }
+        }
+        triggered
+    }
+}
+
+/// Whether rules on `timeframe` can be evaluated from a feed of `feed_timeframe` bars:
+/// the same timeframe or a whole multiple of it
+pub fn timeframe_supported(timeframe: &str, feed_timeframe: &str) -> bool {
+    match (timeframe_duration(timeframe), timeframe_duration(feed_timeframe)) {
+        (Some(rule), Some(feed)) => rule == feed || (rule > feed && rule.num_seconds() % feed.num_seconds() == 0),
+        _ => false,
+    }
+}
+
+fn last_value(series: &[f64]) -> Option<f64> {
+    series.last().copied().filter(|v| v.is_finite())
+}
+
+/// Last two finite values of a series
+fn last_two(series: &[f64]) -> Option<(f64, f64)> {
+    match series {
+        [.., previous, current] if previous.is_finite() && current.is_finite() => Some((*previous, *current)),
+        _ => None,
+    }
+}
+
+fn sma_series(values: &[f64], period: usize) -> Vec<f64> {
+    let mut series = vec![f64::NAN; values.len()];
+    if period == 0 {
+        return series;
+    }
+    let mut sum = 0.0;
+    for i in 0..values.len() {
+        sum += values[i];
+        if i >= period {
+            sum -= values[i - period];
+        }
+        if i + 1 >= period {
+            series[i] = sum / period as f64;
+        }
+    }
+    series
+}
+
+/// EMA seeded with the SMA of the first `period` values
+fn ema_series(values: &[f64], period: usize) -> Vec<f64> {
+    let mut series = vec![f64::NAN; values.len()];
+    if period == 0 || values.len() < period {
+        return series;
+    }
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let mut ema = values[..period].iter().sum::<f64>() / period as f64;
+    series[period - 1] = ema;
+    for i in period..values.len() {
+        ema = alpha * values[i] + (1.0 - alpha) * ema;
+        series[i] = ema;
+    }
+    series
+}
+
+/// Candlestick patterns completed by the most recent bar
+pub fn detect_candlestick_patterns(bars: &[PriceData]) -> Vec<PatternType> {
+    let candles: Vec<Candle> = bars.iter().rev().take(5).rev().map(Candle::from).collect();
+    let mut patterns = Vec::new();
+    let current = match candles.last() {
+        Some(candle) => candle,
+        None => return patterns,
+    };
+
+    if current.range > 0.0 && current.body() <= current.range * 0.1 {
+        patterns.push(PatternType::Doji);
+    }
+
+    // Hammer and shooting star need the preceding bars to be trending into them
+    if candles.len() >= 4 {
+        let prior_move = candles[candles.len() - 2].close - candles[candles.len() - 4].close;
+        let body = current.body().max(current.range * 0.05);
+        if prior_move < 0.0 && current.lower_shadow() >= body * 2.0 && current.upper_shadow() <= current.range * 0.25 {
+            patterns.push(PatternType::Hammer);
+        }
+        if prior_move > 0.0 && current.upper_shadow() >= body * 2.0 && current.lower_shadow() <= current.range * 0.25 {
+            patterns.push(PatternType::ShootingStar);
+        }
+    }
+
+    if candles.len() >= 2 {
+        let previous = &candles[candles.len() - 2];
+        let opposite = previous.is_bullish() != current.is_bullish() && previous.body() > 0.0 && current.body() > 0.0;
+        if opposite && current.body_high() >= previous.body_high() && current.body_low() <= previous.body_low()
+            && current.body() > previous.body()
+        {
+            patterns.push(PatternType::Engulfing);
+        }
+        if opposite && current.body_high() < previous.body_high() && current.body_low() > previous.body_low() {
+            patterns.push(PatternType::Harami);
+        }
+    }
+
+    if candles.len() >= 3 {
+        let first = &candles[candles.len() - 3];
+        let middle = &candles[candles.len() - 2];
+        let first_midpoint = (first.open + first.close) / 2.0;
+        let small_middle = middle.body() <= first.body() * 0.3;
+        if !first.is_bullish() && small_middle && current.is_bullish()
+            && middle.body_high() <= first.close && current.close > first_midpoint
+        {
+            patterns.push(PatternType::MorningStar);
+        }
+        if first.is_bullish() && small_middle && !current.is_bullish()
+            && middle.body_low() >= first.close && current.close < first_midpoint
+        {
+            patterns.push(PatternType::EveningStar);
+        }
+    }
+
+    patterns
+}
+
+struct Candle {
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    range: f64,
+}
+
+impl From<&PriceData> for Candle {
+    fn from(bar: &PriceData) -> Self {
+        let high = bar.high.to_f64().unwrap_or(0.0);
+        let low = bar.low.to_f64().unwrap_or(0.0);
+        Self {
+            open: bar.open.to_f64().unwrap_or(0.0),
+            high,
+            low,
+            close: bar.close.to_f64().unwrap_or(0.0),
+            range: high - low,
+        }
+    }
+}
+
+impl Candle {
+    fn body(&self) -> f64 {
+        (self.close - self.open).abs()
+    }
+
+    fn body_high(&self) -> f64 {
+        self.open.max(self.close)
+    }
+
+    fn body_low(&self) -> f64 {
+        self.open.min(self.close)
+    }
+
+    fn upper_shadow(&self) -> f64 {
+        self.high - self.body_high()
+    }
+
+    fn lower_shadow(&self) -> f64 {
+        self.body_low() - self.low
+    }
+
+    fn is_bullish(&self) -> bool {
+        self.close > self.open
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn bar(minute: i64, open: f64, high: f64, low: f64, close: f64, volume: f64) -> PriceData {
+        let d = |v: f64| Decimal::from_f64(v).unwrap_or_default();
+        PriceData {
+            timestamp: DateTime::from_timestamp(1_700_000_000, 0).unwrap() + Duration::minutes(minute),
+            open: d(open),
+            high: d(high),
+            low: d(low),
+            close: d(close),
+            volume: d(volume),
+        }
+    }
+
+    fn flat(minute: i64, close: f64) -> PriceData {
+        bar(minute, close, close, close, close, 100.0)
+    }
+
+    fn rule(condition: AlertCondition, trigger: AlertTrigger, cooldown_seconds: u64) -> NewAlertRule {
+        NewAlertRule {
+            name: "test".to_string(),
+            symbol: "btcusdt".to_string(),
+            timeframe: "1m".to_string(),
+            condition,
+            trigger,
+            cooldown_seconds,
+        }
+    }
+
+    #[test]
+    fn test_price_cross_respects_cooldown() {
+        let mut system = TechnicalAlertSystem::new();
+        system.add_rule(rule(
+            AlertCondition::CrossesAbove { indicator: AlertIndicator::Price, threshold: 100.0 },
+            AlertTrigger::Recurring,
+            300,
+        )).unwrap();
+
+        let closes = [99.0, 101.0, 99.0, 101.0, 99.0, 99.0, 99.0, 99.0, 101.0];
+        let fired: Vec<usize> = closes.iter().enumerate()
+            .filter(|(i, &c)| !system.on_bar("BTCUSDT", "1m", &flat(*i as i64, c)).is_empty())
+            .map(|(i, _)| i)
+            .collect();
+
+        // The cross at minute 3 falls inside the 5 minute cooldown from minute 1
+        assert_eq!(fired, vec![1, 8]);
+        assert_eq!(system.rules()[0].trigger_count, 2);
+        assert_eq!(system.history(10).len(), 2);
+    }
+
+    #[test]
+    fn test_one_shot_rule_disarms() {
+        let mut system = TechnicalAlertSystem::new();
+        system.add_rule(rule(
+            AlertCondition::Above { indicator: AlertIndicator::Price, threshold: 100.0 },
+            AlertTrigger::OneShot,
+            0,
+        )).unwrap();
+
+        assert_eq!(system.on_bar("BTCUSDT", "1m", &flat(0, 105.0)).len(), 1);
+        assert!(system.on_bar("BTCUSDT", "1m", &flat(1, 106.0)).is_empty());
+        assert!(!system.rules()[0].enabled);
+        // Other streams never match the rule
+        assert!(system.on_bar("ETHUSDT", "1m", &flat(2, 106.0)).is_empty());
+    }
+
+    #[test]
+    fn test_volume_spike_and_crossover() {
+        let mut system = TechnicalAlertSystem::new();
+        system.add_rule(rule(AlertCondition::VolumeSpike { lookback: 5, multiplier: 3.0 }, AlertTrigger::Recurring, 0)).unwrap();
+        system.add_rule(rule(
+            AlertCondition::CrossoverAbove { fast: AlertIndicator::Sma { period: 2 }, slow: AlertIndicator::Sma { period: 5 } },
+            AlertTrigger::Recurring,
+            0,
+        )).unwrap();
+
+        for i in 0..6 {
+            assert!(system.on_bar("BTCUSDT", "1m", &flat(i, 100.0 - i as f64)).is_empty());
+        }
+        let alerts = system.on_bar("BTCUSDT", "1m", &bar(6, 95.0, 110.0, 95.0, 110.0, 500.0));
+        assert_eq!(alerts.len(), 2);
+        assert!(alerts.iter().any(|a| a.message.contains("Volume spike")));
+        assert!(alerts.iter().any(|a| a.message.contains("SMA(2) crossed above SMA(5)")));
+    }
+
+    #[test]
+    fn test_feed_updates_alert_on_closed_bars_only() {
+        let mut system = TechnicalAlertSystem::new();
+        system.add_rule(rule(
+            AlertCondition::Above { indicator: AlertIndicator::Price, threshold: 100.0 },
+            AlertTrigger::Recurring,
+            0,
+        )).unwrap();
+
+        // A forming candle that spikes above the threshold and falls back never alerts
+        assert!(system.on_feed_update("BTCUSDT", "1m", &flat(0, 99.0)).is_empty());
+        assert!(system.on_feed_update("BTCUSDT", "1m", &flat(0, 105.0)).is_empty());
+        assert!(system.on_feed_update("BTCUSDT", "1m", &flat(0, 99.0)).is_empty());
+        assert!(system.on_feed_update("BTCUSDT", "1m", &flat(1, 101.0)).is_empty());
+
+        // Minute 1 closed above the threshold once minute 2 arrives
+        let alerts = system.on_feed_update("BTCUSDT", "1m", &flat(2, 99.0));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].triggered_at, flat(1, 101.0).timestamp);
+    }
+
+    #[test]
+    fn test_rules_on_longer_timeframes_use_resampled_bars() {
+        let mut system = TechnicalAlertSystem::new();
+        let mut five_minute = rule(
+            AlertCondition::Above { indicator: AlertIndicator::Price, threshold: 100.0 },
+            AlertTrigger::Recurring,
+            0,
+        );
+        five_minute.timeframe = "5m".to_string();
+        system.add_rule(five_minute).unwrap();
+
+        // Minute 1 completes the first 5m bucket, minute 6 the second
+        let closes = [99.0, 99.0, 101.0, 101.0, 101.0, 101.0, 100.5];
+        let fired: Vec<usize> = closes.iter().enumerate()
+            .filter(|(i, &c)| !system.on_bar("BTCUSDT", "1m", &flat(*i as i64, c)).is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(fired, vec![6]);
+        assert_eq!(system.history(1)[0].timeframe, "5m");
+
+        assert!(timeframe_supported("1m", "1m"));
+        assert!(timeframe_supported("4h", "1m"));
+        assert!(!timeframe_supported("30s", "1m"));
+        assert!(!timeframe_supported("90s", "1m"));
+    }
+
+    #[test]
+    fn test_candlestick_patterns() {
+        let bullish_engulfing = vec![bar(0, 102.0, 102.5, 100.5, 101.0, 1.0), bar(1, 100.8, 103.0, 100.5, 102.8, 1.0)];
+        assert!(detect_candlestick_patterns(&bullish_engulfing).contains(&PatternType::Engulfing));
+
+        let hammer = vec![flat(0, 110.0), flat(1, 108.0), flat(2, 105.0), bar(3, 104.0, 104.6, 100.0, 104.5, 1.0)];
+        assert!(detect_candlestick_patterns(&hammer).contains(&PatternType::Hammer));
+
+        let invalid = rule(AlertCondition::PatternCompleted { pattern: PatternType::Triangle }, AlertTrigger::OneShot, 0);
+        assert!(TechnicalAlertSystem::new().add_rule(invalid).is_err());
+    }
+
+    #[test]
+    fn test_rules_and_history_persist() {
+        let path = std::env::temp_dir().join(format!("technical_alerts_{}.json", uuid::Uuid::new_v4()));
+        let mut system = TechnicalAlertSystem::open(path.clone()).unwrap();
+        let created = system.add_rule(rule(
+            AlertCondition::Below { indicator: AlertIndicator::Price, threshold: 50.0 },
+            AlertTrigger::Recurring,
+            60,
+        )).unwrap();
+        system.on_bar("BTCUSDT", "1m", &flat(0, 40.0));
+        system.save().unwrap();
+
+        let reloaded = TechnicalAlertSystem::open(path.clone()).unwrap();
+        assert_eq!(reloaded.rules().len(), 1);
+        assert_eq!(reloaded.rules()[0].rule_id, created.rule_id);
+        assert_eq!(reloaded.rules()[0].trigger_count, 1);
+        assert_eq!(reloaded.history(10).len(), 1);
+
+        let mut reloaded = reloaded;
+        assert!(reloaded.remove_rule(&created.rule_id).unwrap());
+        assert!(TechnicalAlertSystem::open(path.clone()).unwrap().rules().is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+}
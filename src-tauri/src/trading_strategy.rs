@@ -3,12 +3,28 @@ use rust_decimal::Decimal;
 use rust_decimal::prelude::{ToPrimitive, FromPrimitive};
 use std::str::FromStr;
 use chrono::{DateTime, Utc};
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use crate::models::{OrderBookDepth, MarketDepthAnalysis, LiquidityLevel, PriceData};
 use crate::logging::{LogLevel, LogCategory};
 use crate::{log_info, log_warning, log_error, log_debug};
 use crate::gpu_risk_manager::{GpuRiskManager, TradingRiskAssessment, MarketRegime};
 use crate::enhanced_lro::{EnhancedLRO, LROConfig as EnhancedLROConfig, LROSignal as EnhancedLROSignal, LROStatistics};
+use crate::multi_timeframe::{HigherTimeframeConfirmation, MultiTimeframeSeries};
+use crate::regime_detection::{Regime, RegimeClassifier, RegimeProbabilities, RegimeSwitching};
+use crate::lro_engine::{LroEngineKind, SignalDirection, from_optimized_signal, signal_line_cross, signal_line_strength};
+use crate::optimized_lro::OptimizedLRO;
+use crate::divergence::{Divergence, DivergenceDetector, DivergenceFilter, OscillatorSource};
+use crate::advanced_risk_manager::{HeatCheck, KellyPositionSizer, MAETracker, SharedHeatTracker};
+use crate::position_sizing::{PositionSizing, SizingDecision, SizingInputs};
+use crate::stop_manager::{FixedStop, StopManager, StopManagerConfig, StopState};
+use crate::drawdown_governor::SharedDrawdownGovernor;
+use crate::kill_switch::ClosedPosition;
+use crate::risk_limits::SharedRiskLimits;
+use crate::anomaly_detection::{Anomaly, AnomalyConfig, AnomalyDetector};
+use crate::market_intelligence::{IntelligenceFilter, IntelligenceReading};
+use crate::market_intelligence::fear_greed::{FearGreedFilter, FearGreedHistory, FearGreedReading};
+use crate::signal_fusion::{fuse, FusedSignal, FusionInputs, FusionModel, SignalFusionConfig};
+use crate::model_inference::{klines_from_bars, ModelPrediction, ModelSignalFilter, OnnxModel};
 
 /// Bot operational states - replaces simple boolean flags
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -53,6 +69,9 @@ pub struct PauseInfo {
     pub paused_at: DateTime<Utc>,
     pub auto_resume_at: Option<DateTime<Utc>>,
     pub conditions_for_resume: Vec<String>,
+    /// Market data anomaly that caused the pause, if any
+    #[serde(default)]
+    pub anomaly: Option<Anomaly>,
 }
 
 /// Auto-resume configuration settings
@@ -131,7 +150,7 @@ pub struct LROConfig {
     // Risk Management
     pub stop_loss_percent: f64,
     pub take_profit_percent: f64,
-    pub max_position_size: f64,
+    pub max_position_size: f64, // Largest position notional in quote currency, shared with the backtester
     pub max_daily_loss: f64,
     pub trailing_stop_enabled: bool,
     pub trailing_stop_percent: f64,
@@ -153,6 +172,39 @@ pub struct LROConfig {
     pub connection_resume_delay_minutes: u32,        // Minutes to wait before resuming after connection issues
     pub flash_crash_resume_delay_minutes: u32,       // Minutes to wait before resuming after flash crash
     pub max_auto_pause_duration_hours: u32,          // Max hours before requiring manual intervention
+    // Oscillator Engine
+    #[serde(default)]
+    pub lro_engine: LroEngineKind,
+    // Multi-Timeframe Confirmation
+    #[serde(default)]
+    pub higher_timeframe_confirmation: HigherTimeframeConfirmation,
+    // Regime-Based Parameter Sets
+    #[serde(default)]
+    pub regime_switching: RegimeSwitching,
+    // Divergence Entry Filter
+    #[serde(default)]
+    pub divergence_filter: DivergenceFilter,
+    // Position Sizing Model
+    #[serde(default)]
+    pub position_sizing: PositionSizing,
+    // Stop Placement - the fixed method uses stop_loss_percent and the trailing stop settings
+    #[serde(default)]
+    pub stop_management: StopManagerConfig,
+    // Market Data Anomaly Detection
+    #[serde(default)]
+    pub anomaly_detection: AnomalyConfig,
+    // Market Intelligence Entry Filter
+    #[serde(default)]
+    pub intelligence_filter: IntelligenceFilter,
+    // Signal Fusion Entry Gate
+    #[serde(default)]
+    pub signal_fusion: SignalFusionConfig,
+    // User-Supplied Model Entry Filter
+    #[serde(default)]
+    pub model_signal: ModelSignalFilter,
+    // Fear & Greed Entry Filter
+    #[serde(default)]
+    pub fear_greed_filter: FearGreedFilter,
 }
 
 impl Default for LROConfig {
@@ -192,6 +244,27 @@ impl Default for LROConfig {
             connection_resume_delay_minutes: 3,          // 3 minutes for connection issues
             flash_crash_resume_delay_minutes: 10,        // 10 minutes for flash crashes
             max_auto_pause_duration_hours: 2,            // Max 2 hours of auto-pause
+            // Enhanced LRO remains the default oscillator engine
+            lro_engine: LroEngineKind::Enhanced,
+            // Multi-timeframe confirmation disabled by default
+            higher_timeframe_confirmation: HigherTimeframeConfirmation::default(),
+            // Regime switching disabled until a classifier is trained
+            regime_switching: RegimeSwitching::default(),
+            // Divergence filter disabled by default
+            divergence_filter: DivergenceFilter::default(),
+            // Risk 1% of equity per trade against the configured stop
+            position_sizing: PositionSizing::default(),
+            // Fixed percentage stop from stop_loss_percent
+            stop_management: StopManagerConfig::default(),
+            // Robust anomaly detection on the live feed
+            anomaly_detection: AnomalyConfig::default(),
+            // Intelligence filter disabled until the intelligence engine is running
+            intelligence_filter: IntelligenceFilter::default(),
+            // Entries gated on LRO strength alone until fusion is enabled
+            signal_fusion: SignalFusionConfig::default(),
+            // No external model consulted until one is selected
+            model_signal: ModelSignalFilter::default(),
+            fear_greed_filter: FearGreedFilter::default(),
         }
     }
 }
@@ -388,6 +461,13 @@ pub enum SignalType {
     Hold,
 }
 
+/// Owner name of the bot in the shared portfolio heat tracker and drawdown governor
+const BOT_OWNER: &str = "swing_bot";
+
+fn default_bot_symbol() -> String {
+    "BTCUSDT".to_string() // Same as the default price feed symbol
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwingTradingBot {
     pub config: LROConfig,
@@ -396,6 +476,9 @@ pub struct SwingTradingBot {
     /// Information about current pause (if paused)
     pub pause_info: Option<PauseInfo>,
     pub current_position: Option<BotPosition>,
+    /// Symbol of the price feed, traded on entry and used for per-symbol readings
+    #[serde(default = "default_bot_symbol")]
+    pub symbol: String,
     pub price_history: VecDeque<PriceData>,
     pub lro_history: VecDeque<f64>,
     pub signal_history: VecDeque<LROSignal>,
@@ -404,6 +487,9 @@ pub struct SwingTradingBot {
     pub account_balance: Decimal,
     pub daily_loss_tracker: Decimal,
     pub daily_reset_time: DateTime<Utc>,
+    /// Losing trades in a row today, checked against the trade risk limits
+    #[serde(default)]
+    pub consecutive_losses: u32,
     pub max_position_hold_hours: u32,
     /// Deprecated - kept for backward compatibility, use state instead
     #[deprecated(note = "Use state field instead")]
@@ -421,6 +507,55 @@ pub struct SwingTradingBot {
     // Enhanced LRO calculator with 2025 improvements
     #[serde(skip)]
     enhanced_lro: Option<EnhancedLRO>,
+    // Kalman-smoothed incremental LRO (created when selected)
+    #[serde(skip)]
+    optimized_lro: Option<OptimizedLRO>,
+    // Higher-timeframe series resampled from the base feed
+    #[serde(skip)]
+    timeframe_series: MultiTimeframeSeries,
+    // Fitted regime model and the latest classification
+    #[serde(skip)]
+    regime_classifier: Option<RegimeClassifier>,
+    pub current_regime: Option<RegimeProbabilities>,
+    #[serde(skip)]
+    applied_regime: Option<Regime>,
+    // User config with the active regime parameter set applied; the user's config is never overwritten
+    #[serde(skip)]
+    regime_config: Option<LROConfig>,
+    // Divergences confirmed within the filter lookback
+    pub recent_divergences: Vec<Divergence>,
+    // Win/loss history feeding fractional-Kelly sizing
+    #[serde(skip)]
+    kelly_sizer: KellyPositionSizer,
+    // Adverse excursions of closed trades feeding MAE-derived stops
+    #[serde(skip)]
+    mae_tracker: MAETracker,
+    // Portfolio heat shared with the other bots
+    #[serde(skip)]
+    portfolio_heat: Option<SharedHeatTracker>,
+    // Account drawdown governor shared with the other bots
+    #[serde(skip)]
+    drawdown_governor: Option<SharedDrawdownGovernor>,
+    // Configured risk limit profiles shared with the other bots
+    #[serde(skip)]
+    risk_limits: Option<SharedRiskLimits>,
+    // Robust statistics over the feed and the record of anomalies found
+    #[serde(skip)]
+    anomaly_detector: AnomalyDetector,
+    // Latest market intelligence score per symbol, pushed by the refresh task
+    #[serde(skip)]
+    intelligence_readings: HashMap<String, IntelligenceReading>,
+    // Daily Fear & Greed readings, seeded from disk and extended by the refresh task
+    #[serde(skip)]
+    fear_greed_history: FearGreedHistory,
+    // Trained fusion model and the latest fused entry decision
+    #[serde(skip)]
+    fusion_model: Option<FusionModel>,
+    pub last_fused_signal: Option<FusedSignal>,
+    // ONNX model behind the model signal filter and its latest prediction
+    #[serde(skip)]
+    signal_model: Option<std::sync::Arc<OnnxModel>>,
+    pub last_model_prediction: Option<ModelPrediction>,
     // GPU-enhanced risk management
     #[serde(skip)]
     pub gpu_risk_manager: Option<std::sync::Arc<GpuRiskManager>>,
@@ -529,6 +664,12 @@ pub struct BotPosition {
     pub stop_loss: Option<Decimal>,
     pub take_profit: Option<Decimal>,
     pub entry_signal: LROSignal,
+    /// How the position size was chosen
+    #[serde(default)]
+    pub sizing: Option<SizingDecision>,
+    /// Protective stop state; `stop_loss` mirrors its current level
+    #[serde(default)]
+    pub stop_state: Option<StopState>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -610,6 +751,18 @@ impl SwingTradingBot {
             }
         }
         
+        // Validate higher-timeframe confirmation against the base timeframe
+        config.higher_timeframe_confirmation.validate(&config.timeframe)?;
+        config.regime_switching.validate()?;
+        config.divergence_filter.validate()?;
+        config.position_sizing.validate()?;
+        config.stop_management.validate()?;
+        config.anomaly_detection.validate()?;
+        config.intelligence_filter.validate()?;
+        config.signal_fusion.validate()?;
+        config.model_signal.validate()?;
+        config.fear_greed_filter.validate()?;
+        
         Ok(())
     }
 
@@ -629,12 +782,16 @@ impl SwingTradingBot {
         let overbought = config.overbought;
         let oversold = config.oversold;
         let adaptive_enabled = config.adaptive_enabled;
+        let signal_period = config.signal_period;
+        let timeframe = config.timeframe.clone();
+        let higher_timeframes = Self::series_timeframes(&config);
         
         Self {
             config,
             state: BotState::Stopped,
             pause_info: None,
             current_position: None,
+            symbol: default_bot_symbol(),
             price_history: VecDeque::with_capacity(200),
             lro_history: VecDeque::with_capacity(100),
             signal_history: VecDeque::with_capacity(50),
@@ -649,6 +806,7 @@ impl SwingTradingBot {
             },
             daily_loss_tracker: Decimal::ZERO,
             daily_reset_time: Utc::now(),
+            consecutive_losses: 0,
             max_position_hold_hours,
             #[allow(deprecated)]
             emergency_stop_triggered: false,
@@ -671,7 +829,29 @@ impl SwingTradingBot {
                 volatility_adjustment: adaptive_enabled,
                 multi_timeframe: true,
                 divergence_detection: true,
+                signal_period,
             })),
+            optimized_lro: None,
+            // Resampled higher timeframes for entry confirmation
+            timeframe_series: MultiTimeframeSeries::new(&timeframe, &higher_timeframes),
+            // Regime detection (classifier installed after training)
+            regime_classifier: None,
+            current_regime: None,
+            applied_regime: None,
+            regime_config: None,
+            recent_divergences: Vec::new(),
+            kelly_sizer: KellyPositionSizer::new(),
+            mae_tracker: MAETracker::new(),
+            portfolio_heat: None,
+            drawdown_governor: None,
+            risk_limits: None,
+            anomaly_detector: AnomalyDetector::new(),
+            intelligence_readings: HashMap::new(),
+            fear_greed_history: FearGreedHistory::new(),
+            fusion_model: None,
+            last_fused_signal: None,
+            signal_model: None,
+            last_model_prediction: None,
             // GPU risk management (initialized later)
             gpu_risk_manager: None,
             last_risk_assessment: None,
@@ -816,6 +996,7 @@ impl SwingTradingBot {
             paused_at: Utc::now(),
             auto_resume_at,
             conditions_for_resume,
+            anomaly: None,
         });
         
         // NOTE: Unlike emergency stop, we do NOT close positions during pause
@@ -871,8 +1052,9 @@ impl SwingTradingBot {
         }
     }
     
-    /// Stop the bot completely - closes positions and requires manual restart
-    pub fn stop_bot(&mut self, reason: &str) {
+    /// Stop the bot completely - closes positions and requires manual restart.
+    /// Returns the position that was closed, if any
+    pub fn stop_bot(&mut self, reason: &str) -> Option<ClosedPosition> {
         log_warning!(LogCategory::TradingLogic, "Bot stopped: {}", reason);
         
         self.state = BotState::Stopped;
@@ -881,9 +1063,7 @@ impl SwingTradingBot {
         { self.emergency_stop_triggered = true; }
         
         // Close any open position immediately
-        if self.current_position.is_some() {
-            self.exit_position(&format!("Bot Stopped: {}", reason));
-        }
+        self.exit_position(&format!("Bot Stopped: {}", reason))
     }
     
     /// Legacy method for backward compatibility
@@ -912,6 +1092,13 @@ impl SwingTradingBot {
             }
         }
         
+        // Pauses caused by a feed anomaly also wait for the detector to go quiet
+        if let Some(anomaly) = self.pause_info.as_ref().and_then(|info| info.anomaly.as_ref()) {
+            if !self.anomaly_detector.is_clear(&self.config.anomaly_detection, anomaly.kind, Utc::now()) {
+                return false;
+            }
+        }
+        
         // Strategy-aware resume logic
         match pause_reason {
             PauseReason::HighVolatility { threshold, .. } => {
@@ -1165,7 +1352,12 @@ impl SwingTradingBot {
             return false;
         }
         
-        // Check for any anomalous price jumps
+        // Robust z-scores once the detector has enough history
+        if let Some(consistent) = self.anomaly_detector.recent_returns_consistent(&self.config.anomaly_detection, 5) {
+            return consistent;
+        }
+        
+        // Until then, flag any anomalous price jump
         let recent_prices = self.price_history.iter().rev().take(5);
         let mut prev_price: Option<f64> = None;
         
@@ -1187,6 +1379,36 @@ impl SwingTradingBot {
         true
     }
     
+    /// Log new anomalies and pause on the most confident one that clears the pause threshold
+    fn handle_anomalies(&mut self, anomalies: Vec<Anomaly>) {
+        if anomalies.is_empty() {
+            return;
+        }
+        for anomaly in &anomalies {
+            log_warning!(LogCategory::DataProcessing, "Market data anomaly {:?}: {} (confidence {:.2})",
+                anomaly.kind, anomaly.description, anomaly.confidence);
+        }
+        
+        let pause_confidence = self.config.anomaly_detection.pause_confidence;
+        let strongest = anomalies.into_iter()
+            .filter(|anomaly| anomaly.confidence >= pause_confidence)
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence));
+        if let (Some(mut anomaly), true) = (strongest, self.is_running()) {
+            self.pause_bot(anomaly.pause_reason.clone());
+            self.anomaly_detector.mark_paused(&anomaly.id);
+            anomaly.paused = true;
+            if let Some(pause_info) = self.pause_info.as_mut() {
+                pause_info.conditions_for_resume.push(format!(
+                    "No related market data anomalies for {} minutes", self.config.anomaly_detection.quiet_minutes));
+                pause_info.anomaly = Some(anomaly);
+            }
+        }
+        
+        if let Err(e) = self.anomaly_detector.save() {
+            log_warning!(LogCategory::DataProcessing, "Failed to persist market data anomalies: {}", e);
+        }
+    }
+    
     /// Check if bot is running (not paused or stopped)
     pub fn is_running(&self) -> bool {
         self.state == BotState::Running
@@ -1235,6 +1457,9 @@ impl SwingTradingBot {
             return;
         }
         
+        let anomalies = self.anomaly_detector.observe_order_book(&self.config.anomaly_detection, &order_book);
+        self.handle_anomalies(anomalies);
+        
         // Store order book history
         self.order_book_history.push_back(order_book.clone());
         if self.order_book_history.len() > 50 {
@@ -1299,6 +1524,67 @@ impl SwingTradingBot {
         }
     }
     
+    /// Share the portfolio heat tracker that limits correlated exposure across bots
+    pub fn set_portfolio_heat(&mut self, tracker: SharedHeatTracker) {
+        self.portfolio_heat = Some(tracker);
+    }
+    
+    /// Share the drawdown governor that scales entries by account drawdown across bots
+    pub fn set_drawdown_governor(&mut self, governor: SharedDrawdownGovernor) {
+        self.drawdown_governor = Some(governor);
+    }
+    
+    /// Share the risk limit profiles, resolved for this bot and the traded symbol
+    pub fn set_risk_limits(&mut self, risk_limits: SharedRiskLimits) {
+        self.risk_limits = Some(risk_limits);
+    }
+    
+    /// Use an anomaly detector that keeps its records on disk
+    pub fn set_anomaly_detector(&mut self, detector: AnomalyDetector) {
+        self.anomaly_detector = detector;
+    }
+    
+    /// Recorded market data anomalies, newest first
+    pub fn anomalies(&self, limit: usize) -> Vec<Anomaly> {
+        self.anomaly_detector.records(limit)
+    }
+    
+    /// Follow the price feed's symbol. An open position keeps the symbol it was entered on
+    pub fn set_symbol(&mut self, symbol: &str) {
+        let symbol = symbol.trim().to_uppercase();
+        if !symbol.is_empty() {
+            self.symbol = symbol;
+        }
+    }
+    
+    /// Record the latest market intelligence score for a symbol
+    pub fn set_intelligence_reading(&mut self, reading: IntelligenceReading) {
+        self.intelligence_readings.insert(reading.symbol.clone(), reading);
+    }
+    
+    pub fn intelligence_reading(&self, symbol: &str) -> Option<&IntelligenceReading> {
+        self.intelligence_readings.get(symbol)
+    }
+    
+    /// Daily Fear & Greed readings consulted by the Fear & Greed filter
+    pub fn set_fear_greed_history(&mut self, history: FearGreedHistory) {
+        self.fear_greed_history = history;
+    }
+    
+    pub fn record_fear_greed(&mut self, reading: FearGreedReading) {
+        self.fear_greed_history.record(reading);
+    }
+    
+    /// Cross-check the streamed price against one fetched over REST, and flag the feed if
+    /// no bar has arrived recently. Returns the anomalies found
+    pub fn check_price_feed(&mut self, rest_price: Decimal, at: DateTime<Utc>) -> Vec<Anomaly> {
+        let config = &self.config.anomaly_detection;
+        let mut anomalies = self.anomaly_detector.check_stale(config, at);
+        anomalies.extend(self.anomaly_detector.observe_reference_price(config, rest_price.to_f64().unwrap_or(0.0), at));
+        self.handle_anomalies(anomalies.clone());
+        anomalies
+    }
+    
     /// Set GPU risk manager for enhanced analysis
     pub fn set_gpu_risk_manager(&mut self, gpu_manager: std::sync::Arc<GpuRiskManager>) {
         self.gpu_risk_manager = Some(gpu_manager);
@@ -1336,7 +1622,7 @@ impl SwingTradingBot {
             recommended_position_multiplier: 0.8, // Reduce position by 20%
             dynamic_stop_loss: None,
             execution_risk: 0.1,
-            market_regime: MarketRegime::Normal,
+            market_regime: self.classified_market_regime(),
             should_skip_trade: false,
         });
         
@@ -1374,17 +1660,40 @@ impl SwingTradingBot {
             }
         }
         
+        let anomalies = self.anomaly_detector.observe_bar(&self.config.anomaly_detection, &price);
+        self.handle_anomalies(anomalies);
+        
         // Store price for enhanced LRO before moving it
         let price_for_enhanced_lro = price.clone();
         
+        self.ensure_timeframe_series();
+        self.timeframe_series.update(&price);
+        
         self.price_history.push_back(price);
         if self.price_history.len() > 200 {
             self.price_history.pop_front();
         }
+        
+        self.update_regime();
+        self.report_equity();
 
-        // Calculate LRO using enhanced algorithm with 2025 improvements
+        // Calculate LRO using the selected oscillator engine
         if self.price_history.len() >= self.config.period {
-            let (lro_value, enhanced_signal) = if let Some(ref mut enhanced_lro) = self.enhanced_lro {
+            let (lro_value, enhanced_signal) = if self.config.lro_engine == LroEngineKind::LroCache {
+                // Legacy incremental regression with signal-line crossovers
+                (self.calculate_lro_incremental(), None)
+            } else if self.config.lro_engine == LroEngineKind::Optimized {
+                let period = self.config.period;
+                if self.optimized_lro.as_ref().map(|lro| lro.period()) != Some(period) {
+                    self.optimized_lro = Some(OptimizedLRO::new(period));
+                }
+                let optimized_lro = self.optimized_lro.get_or_insert_with(|| OptimizedLRO::new(period));
+                let close = price_for_enhanced_lro.close.to_f64().unwrap_or(0.0);
+                match optimized_lro.update(close).map(|signal| from_optimized_signal(&signal)) {
+                    Some(signal) => (signal.deviation(), Some(signal)),
+                    None => (self.calculate_lro_incremental(), None),
+                }
+            } else if let Some(ref mut enhanced_lro) = self.enhanced_lro {
                 // Use enhanced LRO with multi-timeframe analysis
                 if let Some(signal) = enhanced_lro.update(&price_for_enhanced_lro) {
                     let deviation = match signal {
@@ -1408,6 +1717,8 @@ impl SwingTradingBot {
             if self.lro_history.len() > 100 {
                 self.lro_history.pop_front();
             }
+            
+            self.update_divergences();
 
             // Generate trading signal (enhanced or fallback)
             let trading_signal = if let Some(enhanced_signal) = enhanced_signal {
@@ -1671,7 +1982,7 @@ impl SwingTradingBot {
         let (overbought, oversold) = if self.config.adaptive_enabled {
             self.calculate_adaptive_thresholds(&market_condition)
         } else {
-            (self.config.overbought, self.config.oversold)
+            (self.strategy_config().overbought, self.strategy_config().oversold)
         };
 
         // Generate signal based on LRO crossovers and thresholds
@@ -1960,6 +2271,10 @@ impl SwingTradingBot {
     }
 
     fn determine_market_phase(&self, trend_strength: f64, volatility: f64) -> MarketPhase {
+        if let Some(phase) = self.regime_market_phase() {
+            return phase;
+        }
+        
         let abs_trend = trend_strength.abs();
         
         if abs_trend > 0.7 && volatility < 0.3 {
@@ -1974,8 +2289,8 @@ impl SwingTradingBot {
     }
 
     fn calculate_adaptive_thresholds(&self, market_condition: &MarketCondition) -> (f64, f64) {
-        let base_overbought = self.config.overbought;
-        let base_oversold = self.config.oversold;
+        let base_overbought = self.strategy_config().overbought;
+        let base_oversold = self.strategy_config().oversold;
         
         // Get timeframe-specific volatility factor
         let timeframe_factor = self.config.get_timeframe_volatility_factor();
@@ -2000,36 +2315,30 @@ impl SwingTradingBot {
         oversold: f64,
         market_condition: &MarketCondition,
     ) -> SignalType {
-        // Fixed crossover detection: compare current vs previous LRO to current vs previous signal line
-        let (lro_cross_above, lro_cross_below) = if self.lro_history.len() > 1 && self.signal_line_history.len() > 0 {
-            let prev_lro = self.lro_history[self.lro_history.len() - 2];
-            let prev_signal = self.signal_line_history[self.signal_line_history.len() - 1];
-            
-            let cross_above = lro_value > signal_line && prev_lro <= prev_signal;
-            let cross_below = lro_value < signal_line && prev_lro >= prev_signal;
-            
-            (cross_above, cross_below)
-        } else {
-            (false, false)
+        // Crossover detection: compare current vs previous LRO to current vs previous signal line
+        let cross = match self.signal_line_history.back() {
+            Some(&prev_signal) if self.lro_history.len() > 1 => {
+                let prev_lro = self.lro_history[self.lro_history.len() - 2];
+                signal_line_cross((prev_lro, prev_signal), (lro_value, signal_line), overbought, oversold)
+            }
+            _ => SignalDirection::Neutral,
         };
 
         // Strong signals in trending markets
         if matches!(market_condition.market_phase, MarketPhase::Trending) {
-            if lro_value < oversold && lro_cross_above && market_condition.trend_strength > 0.0 {
+            if cross == SignalDirection::Bullish && market_condition.trend_strength > 0.0 {
                 return SignalType::StrongBuy;
             }
-            if lro_value > overbought && lro_cross_below && market_condition.trend_strength < 0.0 {
+            if cross == SignalDirection::Bearish && market_condition.trend_strength < 0.0 {
                 return SignalType::StrongSell;
             }
         }
 
         // Regular signals
-        if lro_value < oversold && lro_cross_above {
-            SignalType::Buy
-        } else if lro_value > overbought && lro_cross_below {
-            SignalType::Sell
-        } else {
-            SignalType::Hold
+        match cross {
+            SignalDirection::Bullish => SignalType::Buy,
+            SignalDirection::Bearish => SignalType::Sell,
+            _ => SignalType::Hold,
         }
     }
 
@@ -2039,12 +2348,10 @@ impl SwingTradingBot {
         signal_line: f64,
         market_condition: &MarketCondition,
     ) -> f64 {
-        let lro_distance = (lro_value - signal_line).abs();
-        let extreme_level = lro_value.abs();
         let volume_factor = market_condition.volume_profile.min(2.0) / 2.0;
         
         // Combine factors for signal strength
-        let base_strength = (lro_distance + extreme_level) / 2.0;
+        let base_strength = signal_line_strength(lro_value, signal_line);
         let adjusted_strength = base_strength * volume_factor;
         
         adjusted_strength.min(1.0)
@@ -2054,7 +2361,7 @@ impl SwingTradingBot {
         // Perform GPU risk assessment before processing any signals
         if let Some(ref risk_manager) = self.gpu_risk_manager {
             if let Some(latest_price) = self.price_history.back() {
-                let position_size = self.calculate_position_size(&signal);
+                let position_size = self.calculate_position_size(&signal, self.strategy_config().get_scaled_stop_loss()).quantity;
                 let price_data_slice = self.price_history.iter().collect::<Vec<_>>();
                 
                 // Perform async risk assessment (this is a synchronous context, so we'd need to restructure)
@@ -2120,7 +2427,13 @@ impl SwingTradingBot {
         
         match signal.signal_type {
             SignalType::Buy | SignalType::StrongBuy => {
-                if self.current_position.is_none() && signal.strength > self.config.signal_strength_threshold {
+                if self.current_position.is_none()
+                    && self.entry_strength_allows(&signal, &crate::models::TradeSide::Long)
+                    && self.higher_timeframes_confirm(&crate::models::TradeSide::Long)
+                    && self.divergences_allow(&crate::models::TradeSide::Long)
+                    && self.model_signal_allows(&crate::models::TradeSide::Long)
+                    && self.fear_greed_allows(&crate::models::TradeSide::Long)
+                {
                     // Enter long position
                     self.enter_position(signal, crate::models::TradeSide::Long);
                 }
@@ -2131,7 +2444,12 @@ impl SwingTradingBot {
                         // Exit long position
                         self.exit_position("LRO Signal");
                     }
-                } else if signal.strength > self.config.signal_strength_threshold {
+                } else if self.entry_strength_allows(&signal, &crate::models::TradeSide::Short)
+                    && self.higher_timeframes_confirm(&crate::models::TradeSide::Short)
+                    && self.divergences_allow(&crate::models::TradeSide::Short)
+                    && self.model_signal_allows(&crate::models::TradeSide::Short)
+                    && self.fear_greed_allows(&crate::models::TradeSide::Short)
+                {
                     // Enter short position
                     self.enter_position(signal, crate::models::TradeSide::Short);
                 }
@@ -2143,28 +2461,331 @@ impl SwingTradingBot {
         }
     }
 
+    /// Higher timeframes tracked for a config: the standard ladder plus any confirmation timeframes
+    fn series_timeframes(config: &LROConfig) -> Vec<String> {
+        let mut timeframes: Vec<String> = crate::multi_timeframe::DEFAULT_HIGHER_TIMEFRAMES
+            .iter()
+            .map(|tf| tf.to_string())
+            .collect();
+        for timeframe in &config.higher_timeframe_confirmation.timeframes {
+            if !timeframes.contains(timeframe) {
+                timeframes.push(timeframe.clone());
+            }
+        }
+        timeframes
+    }
+    
+    /// Rebuild the resampled series when the base or confirmation timeframes change
+    fn ensure_timeframe_series(&mut self) {
+        let tracked = self.timeframe_series.timeframes();
+        let missing = self.config.higher_timeframe_confirmation.timeframes
+            .iter()
+            .any(|tf| !tracked.contains(tf));
+        
+        if self.timeframe_series.base_timeframe() != self.config.timeframe || missing {
+            log_info!(LogCategory::DataProcessing, "Rebuilding multi-timeframe series for base timeframe {}", self.config.timeframe);
+            let mut series = MultiTimeframeSeries::new(&self.config.timeframe, &Self::series_timeframes(&self.config));
+            // Replay retained history so confirmation is available immediately
+            for price in &self.price_history {
+                series.update(price);
+            }
+            self.timeframe_series = series;
+        }
+    }
+    
+    /// Check the configured higher-timeframe confirmation rule for an entry
+    fn higher_timeframes_confirm(&self, side: &crate::models::TradeSide) -> bool {
+        let confirmation = &self.config.higher_timeframe_confirmation;
+        if !confirmation.enabled {
+            return true;
+        }
+        
+        let result = self.timeframe_series.confirm_entry(side, confirmation);
+        if !result.allowed {
+            log_info!(LogCategory::TradingLogic, "{:?} entry blocked by higher-timeframe confirmation (agreeing: {:?}, disagreeing: {:?}, insufficient data: {:?})",
+                side, result.agreeing_timeframes, result.disagreeing_timeframes, result.insufficient_data);
+        }
+        result.allowed
+    }
+    
+    /// Install (or remove) the fitted regime classifier
+    pub fn set_regime_classifier(&mut self, classifier: Option<RegimeClassifier>) {
+        self.regime_classifier = classifier;
+        self.current_regime = None;
+        self.clear_regime_parameters();
+    }
+    
+    /// Replace the user's configuration; the regime parameter set is re-derived on the next bar
+    pub fn set_config(&mut self, config: LROConfig) {
+        self.config = config;
+        self.clear_regime_parameters();
+    }
+    
+    /// Configuration the strategy trades with: the user's config, overlaid with the active regime parameter set
+    pub fn strategy_config(&self) -> &LROConfig {
+        self.regime_config.as_ref().unwrap_or(&self.config)
+    }
+    
+    fn clear_regime_parameters(&mut self) {
+        self.applied_regime = None;
+        self.regime_config = None;
+    }
+    
+    pub fn regime_classifier(&self) -> Option<&RegimeClassifier> {
+        self.regime_classifier.as_ref()
+    }
+    
+    /// Classify the current regime and switch parameter sets when it changes
+    fn update_regime(&mut self) {
+        let classifier = match self.regime_classifier.as_ref() {
+            Some(classifier) => classifier,
+            None => return,
+        };
+        
+        let prices: Vec<PriceData> = self.price_history.iter().cloned().collect();
+        let probabilities = match classifier.classify(&prices) {
+            Some(probabilities) => probabilities,
+            None => return,
+        };
+        
+        let regime = probabilities.regime;
+        let confidence = probabilities.confidence();
+        self.current_regime = Some(probabilities);
+        
+        let switching = &self.config.regime_switching;
+        if !switching.enabled {
+            self.clear_regime_parameters();
+            return;
+        }
+        if confidence < switching.min_probability || self.applied_regime == Some(regime) {
+            return;
+        }
+        
+        let parameters = switching.parameters_for(regime).clone();
+        let mut working = self.config.clone();
+        parameters.apply(&mut working);
+        self.regime_config = Some(working);
+        log_info!(LogCategory::TradingLogic, "Market regime switched to {} (p={:.2}); applied parameter set: overbought {:.2}, oversold {:.2}, stop loss {:.1}%, take profit {:.1}%",
+            regime.as_str(), confidence, parameters.overbought, parameters.oversold, parameters.stop_loss_percent, parameters.take_profit_percent);
+        self.applied_regime = Some(regime);
+    }
+    
+    /// Risk-manager regime from the regime classifier when it is confident enough
+    fn classified_market_regime(&self) -> MarketRegime {
+        match self.current_regime.as_ref() {
+            Some(probabilities) if probabilities.confidence() >= self.config.regime_switching.min_probability => {
+                probabilities.regime.market_regime()
+            }
+            _ => MarketRegime::Normal,
+        }
+    }
+    
+    /// Market phase from the regime classifier when it is confident enough
+    fn regime_market_phase(&self) -> Option<MarketPhase> {
+        let probabilities = self.current_regime.as_ref()?;
+        if probabilities.confidence() >= self.config.regime_switching.min_probability {
+            Some(probabilities.regime.market_phase())
+        } else {
+            None
+        }
+    }
+    
+    /// Refresh divergences between price and the configured oscillators
+    fn update_divergences(&mut self) {
+        let filter = &self.config.divergence_filter;
+        if !filter.enabled {
+            self.recent_divergences.clear();
+            return;
+        }
+        
+        let prices: Vec<PriceData> = self.price_history.iter().cloned().collect();
+        let detector = DivergenceDetector::new(filter.detection.clone());
+        let mut divergences = Vec::new();
+        
+        for &source in &filter.sources {
+            let oscillator = if source == OscillatorSource::Lro {
+                // Use the active engine's LRO values, aligned to the newest bars
+                let mut lro = vec![f64::NAN; prices.len().saturating_sub(self.lro_history.len())];
+                lro.extend(self.lro_history.iter().skip(self.lro_history.len().saturating_sub(prices.len())));
+                lro
+            } else {
+                detector.oscillator_series(&prices, source)
+            };
+            
+            for divergence in detector.scan_with(&prices, &oscillator, source) {
+                let bars_ago = prices.len() - 1 - divergence.confirmed_index;
+                if bars_ago == 0 {
+                    log_info!(LogCategory::TradingLogic, "{} divergence confirmed on {} (strength {:.2})",
+                        divergence.kind.as_str(), source.as_str(), divergence.strength);
+                }
+                if bars_ago <= filter.lookback_bars {
+                    divergences.push(divergence);
+                }
+            }
+        }
+        
+        self.recent_divergences = divergences;
+    }
+    
+    /// Install (or remove) the trained signal fusion model
+    pub fn set_fusion_model(&mut self, model: Option<FusionModel>) {
+        self.fusion_model = model;
+    }
+    
+    pub fn fusion_model(&self) -> Option<&FusionModel> {
+        self.fusion_model.as_ref()
+    }
+    
+    /// Install (or remove) the ONNX model consulted by the model signal filter
+    pub fn set_signal_model(&mut self, model: Option<std::sync::Arc<OnnxModel>>) {
+        self.signal_model = model;
+    }
+    
+    pub fn signal_model(&self) -> Option<&OnnxModel> {
+        self.signal_model.as_deref()
+    }
+    
+    /// Fuse a signal with the technical, order book, intelligence and risk readings for an entry on `side`
+    pub fn fuse_signal(&self, signal: &LROSignal, side: crate::models::TradeSide) -> FusedSignal {
+        let bars: Vec<PriceData> = self.recent_bars(crate::signal_fusion::REQUIRED_BARS);
+        let max_age = chrono::Duration::minutes(self.config.intelligence_filter.max_age_minutes as i64);
+        let intelligence = self.intelligence_readings.get(&self.symbol)
+            .filter(|reading| Utc::now() - reading.timestamp <= max_age);
+        let inputs = FusionInputs {
+            side: side.clone(),
+            lro_strength: signal.strength,
+            bars: &bars,
+            divergences: self.config.divergence_filter.enabled.then_some(self.recent_divergences.as_slice()),
+            depth: self.market_depth_analysis.as_ref().filter(|_| self.depth_analysis_enabled),
+            intelligence,
+            risk: self.last_risk_assessment.as_ref(),
+        };
+        fuse(&self.config.signal_fusion, self.fusion_model.as_ref(), side, &inputs.readings(), signal.timestamp)
+    }
+    
+    /// Check the entry strength: the fused score when signal fusion is enabled, the LRO strength otherwise
+    fn entry_strength_allows(&mut self, signal: &LROSignal, side: &crate::models::TradeSide) -> bool {
+        if !self.config.signal_fusion.enabled {
+            return signal.strength > self.strategy_config().signal_strength_threshold;
+        }
+        
+        let fused = self.fuse_signal(signal, side.clone());
+        let breakdown = fused.components.iter()
+            .filter(|c| c.contribution != 0.0)
+            .map(|c| format!("{} {:+.3}", c.component.as_str(), c.contribution))
+            .collect::<Vec<_>>()
+            .join(", ");
+        if fused.allowed {
+            log_info!(LogCategory::TradingLogic, "{:?} entry fused strength {:.2} ({:?}: {})", side, fused.strength, fused.method, breakdown);
+        } else {
+            log_info!(LogCategory::TradingLogic, "{:?} entry blocked by signal fusion: strength {:.2}, threshold {:.2} ({:?}: {})",
+                side, fused.strength, self.config.signal_fusion.entry_threshold, fused.method, breakdown);
+        }
+        let allowed = fused.allowed;
+        self.last_fused_signal = Some(fused);
+        allowed
+    }
+    
+    /// Check the divergence filter for an entry
+    fn divergences_allow(&self, side: &crate::models::TradeSide) -> bool {
+        let allowed = self.config.divergence_filter.allows_entry(side, &self.recent_divergences);
+        if !allowed {
+            log_info!(LogCategory::TradingLogic, "{:?} entry blocked by divergence filter ({} recent divergences)",
+                side, self.recent_divergences.len());
+        }
+        allowed
+    }
+    
+    /// Check the Fear & Greed index in force at the latest bar allows an entry
+    fn fear_greed_allows(&self, side: &crate::models::TradeSide) -> bool {
+        let at = match self.price_history.back() {
+            Some(bar) => bar.timestamp,
+            None => return true,
+        };
+        let allowed = self.config.fear_greed_filter.allows_at(side, &self.fear_greed_history, at);
+        if !allowed {
+            log_info!(LogCategory::TradingLogic, "{:?} entry blocked by Fear & Greed filter (index {:.1})",
+                side, self.fear_greed_history.index_at(at).unwrap_or_default());
+        }
+        allowed
+    }
+    
+    /// Check the user-supplied model agrees with an entry
+    fn model_signal_allows(&mut self, side: &crate::models::TradeSide) -> bool {
+        let bars = self.signal_model.as_ref().map(|model| self.recent_bars(model.required_bars())).unwrap_or_default();
+        let (allowed, prediction) = self.config.model_signal.evaluate(self.signal_model.as_deref(), &klines_from_bars(&bars), side);
+        if prediction.is_some() {
+            self.last_model_prediction = prediction;
+        }
+        allowed
+    }
+    
+    /// Multi-timeframe series built from the bot's price feed
+    pub fn timeframe_series(&self) -> &MultiTimeframeSeries {
+        &self.timeframe_series
+    }
+
     fn enter_position(&mut self, signal: LROSignal, side: crate::models::TradeSide) {
         if let Some(latest_price) = self.price_history.back() {
             let entry_price = latest_price.close;
-            let quantity = self.calculate_position_size(&signal);
+            let symbol = self.symbol.clone();
+            let stop_state = self.open_stop(entry_price, matches!(side, crate::models::TradeSide::Long));
+            let reading = self.intelligence_readings.get(&symbol);
+            if !self.config.intelligence_filter.allows_entry(reading, Utc::now()) {
+                let detail = reading
+                    .map(|r| format!("score {:.1} from {}, minimum {:.1}", r.score, r.timestamp.format("%H:%M"), self.config.intelligence_filter.min_score))
+                    .unwrap_or_else(|| format!("no score for {}", symbol));
+                log_info!(LogCategory::TradingLogic, "{:?} entry blocked by intelligence filter ({})", side, detail);
+                return;
+            }
+            let mut sizing = self.calculate_position_size(&signal, stop_state.initial_distance_percent());
+            if !self.apply_risk_limits(&symbol, &mut sizing) {
+                return;
+            }
+            
+            // Heat and correlated exposure limits shared across every bot
+            if let Some(check) = self.check_portfolio_heat(&symbol, &sizing) {
+                if check.blocked {
+                    log_warning!(LogCategory::RiskManagement, "Entry blocked - {}", check.explanation());
+                    return;
+                }
+                if check.scale < 1.0 {
+                    log_info!(LogCategory::RiskManagement, "{}", check.explanation());
+                    sizing.scale(check.scale, &format!("{} heat limits", check.cluster));
+                }
+            }
+            let quantity = sizing.quantity;
+            if quantity <= Decimal::ZERO {
+                log_info!(LogCategory::RiskManagement, "Entry skipped - {}", sizing.explanation);
+                return;
+            }
+            log_info!(LogCategory::RiskManagement, "Position sized: {}", sizing.explanation);
+            log_info!(LogCategory::RiskManagement, "Initial stop at {:.4}: {}", stop_state.initial_stop, stop_state.description);
             
-            // Calculate stop loss and take profit
-            let (stop_loss, take_profit) = self.calculate_risk_levels(entry_price, &side, &signal);
+            // Stop comes from the stop manager, take profit from the configured reward ratio
+            let (_, take_profit) = self.calculate_risk_levels(entry_price, &side, &signal);
             
             let side_clone = side.clone();
             let position = BotPosition {
-                symbol: "BTCUSDT".to_string(), // This should be configurable
+                symbol,
                 side,
                 entry_price,
                 quantity,
                 entry_time: Utc::now(),
-                stop_loss,
+                stop_loss: Some(stop_state.stop_decimal()),
                 take_profit,
                 entry_signal: signal,
+                sizing: Some(sizing),
+                stop_state: Some(stop_state),
             };
             
             // Only set position if we're in paper trading mode or if live trading is properly configured
             if self.config.paper_trading_enabled {
+                if let Some(ref tracker) = self.portfolio_heat {
+                    let stop_loss = position.stop_loss.unwrap_or(entry_price);
+                    tracker.lock().unwrap_or_else(|e| e.into_inner())
+                        .add_position(BOT_OWNER, &position.symbol, entry_price, stop_loss, entry_price * quantity, self.account_balance);
+                }
                 self.current_position = Some(position);
                 eprintln!("Paper trade entered: {:?} {} at ${}", side_clone, quantity, entry_price);
             } else {
@@ -2174,8 +2795,12 @@ impl SwingTradingBot {
         }
     }
 
-    fn exit_position(&mut self, reason: &str) {
+    fn exit_position(&mut self, reason: &str) -> Option<ClosedPosition> {
+        let mut closed = None;
         if let Some(position) = self.current_position.take() {
+            if let Some(ref tracker) = self.portfolio_heat {
+                tracker.lock().unwrap_or_else(|e| e.into_inner()).remove_position(BOT_OWNER, &position.symbol);
+            }
             if let Some(latest_price) = self.price_history.back() {
                 let exit_price = latest_price.close;
                 let hold_time = Utc::now().signed_duration_since(position.entry_time).num_minutes() as f64 / 60.0;
@@ -2185,9 +2810,24 @@ impl SwingTradingBot {
                     crate::models::TradeSide::Long => (exit_price - position.entry_price) * position.quantity,
                     crate::models::TradeSide::Short => (position.entry_price - exit_price) * position.quantity,
                 };
+                closed = Some(ClosedPosition::new(BOT_OWNER, &position.symbol, position.side.clone(), position.quantity, position.entry_price, exit_price));
                 
                 // Update performance stats and daily loss tracker
                 self.update_performance_stats(pnl, hold_time);
+                let entry_value = position.entry_price * position.quantity;
+                if entry_value > Decimal::ZERO {
+                    let pnl_percent = (pnl / entry_value).to_f64().unwrap_or(0.0) * 100.0;
+                    self.kelly_sizer.update_trade_result(pnl > Decimal::ZERO, pnl_percent);
+                    if let Some(ref stop_state) = position.stop_state {
+                        self.mae_tracker.record_trade(stop_state.to_trade_mae(Utc::now(), pnl_percent));
+                    }
+                }
+                
+                if pnl < Decimal::ZERO {
+                    self.consecutive_losses += 1;
+                } else {
+                    self.consecutive_losses = 0;
+                }
                 
                 // Atomic daily loss tracking with validation
                 if pnl < Decimal::ZERO {
@@ -2217,26 +2857,32 @@ impl SwingTradingBot {
                 }
             }
         }
+        closed
     }
 
-    fn calculate_position_size(&self, signal: &LROSignal) -> Decimal {
-        // GPU-enhanced position sizing with real-time risk assessment
-        let mut base_risk_percent = 0.02; // 2% base risk
+    fn calculate_position_size(&self, signal: &LROSignal, stop_distance_percent: f64) -> SizingDecision {
+        let sizing = &self.config.position_sizing;
+        let mut risk_multiplier = 1.0;
+        let mut risk_multiplier_reason = Vec::new();
         
-        // Apply GPU risk assessment if available
+        // The overlays below and the heat and risk limit checks at entry depend on live
+        // account state and are not replayed by the backtester
+        // GPU risk assessment overlays the configured sizing model
         if let Some(ref risk_assessment) = self.last_risk_assessment {
             // Reduce position size based on GPU-calculated risk factors
-            let risk_multiplier = risk_assessment.recommended_position_multiplier;
-            base_risk_percent *= risk_multiplier as f64;
+            risk_multiplier *= risk_assessment.recommended_position_multiplier as f64;
+            risk_multiplier_reason.push("GPU risk multiplier");
             
             // Additional safety checks based on market regime
             match risk_assessment.market_regime {
                 MarketRegime::Crisis => {
                     log_warning!(LogCategory::RiskManagement, "Crisis mode detected - disabling new positions");
-                    return Decimal::ZERO;
+                    risk_multiplier = 0.0;
+                    risk_multiplier_reason = vec!["crisis regime"];
                 },
                 MarketRegime::Volatile => {
-                    base_risk_percent *= 0.5; // Halve position size in volatile conditions
+                    risk_multiplier *= 0.5; // Halve position size in volatile conditions
+                    risk_multiplier_reason.push("volatile regime");
                     log_info!(LogCategory::RiskManagement, "Volatile market detected - reducing position size by 50%");
                 },
                 MarketRegime::Normal => {
@@ -2247,42 +2893,42 @@ impl SwingTradingBot {
             // Skip trade if GPU assessment indicates high risk
             if risk_assessment.should_skip_trade {
                 log_warning!(LogCategory::RiskManagement, "GPU risk assessment recommends skipping trade");
-                return Decimal::ZERO;
+                risk_multiplier = 0.0;
+                risk_multiplier_reason = vec!["GPU skip recommendation"];
             }
         }
         
-        let risk_percent = DecimalUtils::safe_from_f64_or_default(base_risk_percent, Decimal::from(2) / Decimal::from(100), "adjusted risk percentage");
-        let max_risk_amount = self.account_balance * risk_percent;
-        
-        // Get configured max position size from config
-        let max_position_from_config = DecimalUtils::safe_from_f64_or_default(
-            self.config.max_position_size, 
-            Decimal::from(1000), 
-            "max position size config"
-        );
-        
-        // Base position size on signal strength and market conditions
-        let strength_multiplier = DecimalUtils::safe_from_f64_or_default(
-            signal.strength, 
-            Decimal::ONE, 
-            "signal strength"
-        );
-        let volatility_adjustment = Decimal::ONE - DecimalUtils::safe_from_f64_or_default(
-            signal.market_condition.volatility * 0.5, 
-            Decimal::from_str("0.25").unwrap_or(Decimal::ZERO), 
-            "volatility adjustment"
-        );
-        
-        // Calculate position size
-        let calculated_size = max_risk_amount * strength_multiplier * volatility_adjustment;
+        // Account drawdown governor shared across bots
+        if let Some(ref governor) = self.drawdown_governor {
+            let governor = governor.lock().unwrap_or_else(|e| e.into_inner());
+            let multiplier = governor.size_multiplier();
+            if multiplier <= 0.0 {
+                log_warning!(LogCategory::RiskManagement, "Drawdown governor blocking new positions: {}", governor.status().reason);
+                risk_multiplier = 0.0;
+                risk_multiplier_reason = vec!["drawdown governor"];
+            } else if multiplier < 1.0 {
+                risk_multiplier *= multiplier;
+                risk_multiplier_reason.push("drawdown governor");
+            }
+        }
         
-        // Apply limits: minimum of risk-based size, configured max, and account balance
-        let final_size = calculated_size
-            .min(max_position_from_config)
-            .min(self.account_balance * Decimal::from_f64(0.8).unwrap_or(Decimal::ONE)); // Max 80% of balance
+        let price = self.price_history.back().map(|p| p.close).unwrap_or(Decimal::ZERO);
+        let inputs = SizingInputs {
+            equity: self.account_balance,
+            price,
+            stop_distance_percent: Some(stop_distance_percent),
+            signal_strength: signal.strength,
+            risk_multiplier,
+            risk_multiplier_reason: risk_multiplier_reason.join(" and "),
+            max_notional: Some(DecimalUtils::safe_from_f64_or_default(
+                self.config.max_position_size, 
+                Decimal::from(1000), 
+                "max position size config"
+            )),
+        };
+        let recent_bars = self.recent_bars(sizing.required_bars());
         
-        // Ensure minimum viable position size
-        final_size.max(Decimal::from(10)) // Minimum $10 position
+        sizing.size(&inputs, &recent_bars, &self.config.timeframe, &self.kelly_sizer)
     }
 
     fn calculate_risk_levels(
@@ -2292,8 +2938,8 @@ impl SwingTradingBot {
         signal: &LROSignal,
     ) -> (Option<Decimal>, Option<Decimal>) {
         // Use timeframe-aware risk parameters
-        let stop_loss = self.config.get_scaled_stop_loss();
-        let take_profit = self.config.get_scaled_take_profit();
+        let stop_loss = self.strategy_config().get_scaled_stop_loss();
+        let take_profit = self.strategy_config().get_scaled_take_profit();
         let risk_percent = DecimalUtils::safe_from_f64_or_default(
             stop_loss / 100.0, 
             Decimal::from(2) / Decimal::from(100), 
@@ -2319,7 +2965,105 @@ impl SwingTradingBot {
         }
     }
 
+    /// Check a sized entry against the shared heat limits, loading correlation history as needed
+    fn check_portfolio_heat(&self, symbol: &str, sizing: &SizingDecision) -> Option<HeatCheck> {
+        let mut tracker = self.portfolio_heat.as_ref()?.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = tracker.refresh_from_store(&[symbol.to_string()]) {
+            log_warning!(LogCategory::RiskManagement, "Could not load correlation history for {}: {}", symbol, e);
+        }
+        Some(tracker.check_entry(symbol, sizing.notional, sizing.risk_amount, sizing.equity))
+    }
+
+    /// Check a sized entry against the per-trade limits, cap it at the single-position limits
+    /// and refresh the GPU thresholds, picking up any edit to the risk limit file.
+    /// Returns false when the daily loss or consecutive loss limit blocks the entry
+    fn apply_risk_limits(&self, symbol: &str, sizing: &mut SizingDecision) -> bool {
+        let risk_limits = match self.risk_limits.as_ref() {
+            Some(risk_limits) => risk_limits,
+            None => return true,
+        };
+        let limits = risk_limits.lock().unwrap_or_else(|e| e.into_inner()).current(Some(BOT_OWNER), Some(symbol));
+        if let Some(ref gpu_manager) = self.gpu_risk_manager {
+            gpu_manager.set_thresholds(limits.gpu);
+        }
+
+        let balance = self.account_balance.to_f64().unwrap_or(0.0);
+        let daily_loss_percent = if balance > 0.0 {
+            self.daily_loss_tracker.abs().to_f64().unwrap_or(0.0) / balance * 100.0
+        } else {
+            0.0
+        };
+        if daily_loss_percent >= limits.trade.max_daily_loss_percent {
+            log_warning!(LogCategory::RiskManagement, "Entry blocked - daily loss {:.2}% reached the {}% limit",
+                daily_loss_percent, limits.trade.max_daily_loss_percent);
+            return false;
+        }
+        if self.consecutive_losses >= limits.trade.max_consecutive_losses {
+            log_warning!(LogCategory::RiskManagement, "Entry blocked - {} consecutive losses reached the limit of {}",
+                self.consecutive_losses, limits.trade.max_consecutive_losses);
+            return false;
+        }
+
+        let percent = limits.global.max_single_position_percent.min(limits.trade.max_position_size_percent);
+        let cap = sizing.equity * percent / 100.0;
+        if sizing.notional > cap && sizing.notional > 0.0 {
+            sizing.scale(cap / sizing.notional, "position limit");
+        }
+        true
+    }
+
+    /// Report balance plus realized and open P/L to the drawdown governor
+    fn report_equity(&self) {
+        let (governor, latest) = match (self.drawdown_governor.as_ref(), self.price_history.back()) {
+            (Some(governor), Some(latest)) => (governor, latest),
+            _ => return,
+        };
+        let unrealized = self.current_position.as_ref().map_or(Decimal::ZERO, |position| {
+            let move_since_entry = (latest.close - position.entry_price) * position.quantity;
+            match position.side {
+                crate::models::TradeSide::Short | crate::models::TradeSide::Sell => -move_since_entry,
+                _ => move_since_entry,
+            }
+        });
+        let equity = (self.account_balance + self.performance_stats.total_pnl + unrealized).to_f64().unwrap_or(0.0);
+        governor.lock().unwrap_or_else(|e| e.into_inner()).update_equity(BOT_OWNER, equity, latest.timestamp);
+    }
+
+    /// Last `count` bars of the price feed
+    fn recent_bars(&self, count: usize) -> Vec<PriceData> {
+        self.price_history.iter()
+            .skip(self.price_history.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+
+    /// Place the initial protective stop with the configured stop method
+    fn open_stop(&self, entry_price: Decimal, is_long: bool) -> StopState {
+        let manager = StopManager::new(self.config.stop_management.clone());
+        let fixed_stop = FixedStop {
+            stop_percent: self.strategy_config().get_scaled_stop_loss(),
+            trailing_percent: self.strategy_config().trailing_stop_enabled.then(|| self.strategy_config().get_scaled_trailing_stop()),
+        };
+        let recent_bars = self.recent_bars(manager.config().required_bars());
+        manager.open(entry_price.to_f64().unwrap_or(0.0), is_long, Utc::now(), fixed_stop, &recent_bars, &self.mae_tracker)
+    }
+
+    /// Advance the open position's stop with the latest bar
+    fn update_stop(&mut self) {
+        let manager = StopManager::new(self.config.stop_management.clone());
+        let recent_bars = self.recent_bars(manager.config().required_bars());
+        if let (Some(bar), Some(position)) = (recent_bars.last(), self.current_position.as_mut()) {
+            if let Some(state) = position.stop_state.as_mut() {
+                if let Some(stop) = manager.update(state, bar, &recent_bars) {
+                    position.stop_loss = Some(state.stop_decimal());
+                    log_debug!(LogCategory::RiskManagement, "{} stop moved to {:.4}", state.method.as_str(), stop);
+                }
+            }
+        }
+    }
+
     fn check_exit_conditions(&mut self) {
+        self.update_stop();
         if let Some(ref position) = self.current_position.clone() {
             if let Some(latest_price) = self.price_history.back() {
                 let current_price = latest_price.close;
@@ -2372,7 +3116,9 @@ impl SwingTradingBot {
                         }
                         
                         // Force exit regardless of slippage for safety
-                        self.exit_position("Stop Loss");
+                        let trailed = position.stop_state.as_ref()
+                            .is_some_and(|state| state.current_stop != state.initial_stop);
+                        self.exit_position(if trailed { "Trailing Stop" } else { "Stop Loss" });
                         return;
                     }
                 }
@@ -2391,11 +3137,6 @@ impl SwingTradingBot {
                     }
                 }
                 
-                // Trailing stop logic if enabled
-                if self.config.trailing_stop_enabled {
-                    self.check_trailing_stop(position, current_price);
-                }
-                
                 // Enhanced emergency stop: Check for catastrophic losses
                 let current_pnl = self.calculate_current_pnl(position, current_price);
                 let loss_percent = if position.entry_price > Decimal::ZERO && position.quantity > Decimal::ZERO {
@@ -2459,45 +3200,6 @@ impl SwingTradingBot {
         Decimal::ZERO
     }
     
-    fn check_trailing_stop(&mut self, position: &BotPosition, current_price: Decimal) {
-        if let Some(latest_price) = self.price_history.back() {
-            let trailing_percent = Decimal::from_f64(self.config.trailing_stop_percent / 100.0)
-                .unwrap_or_else(|| {
-                    log_warning!(LogCategory::Configuration, "Failed to convert trailing stop percent to decimal, using 1% default");
-                    Decimal::new(1, 2) // 0.01 (1%) as hardcoded fallback
-                });
-            
-            match position.side {
-                crate::models::TradeSide::Long => {
-                    // For long positions, trail below the highest price seen
-                    let highest_price = self.price_history.iter()
-                        .map(|p| p.high)
-                        .fold(position.entry_price.to_f64().unwrap_or(0.0), |a, b| a.max(b.to_f64().unwrap_or(0.0)));
-                    
-                    let trailing_stop = Decimal::from_f64(highest_price).unwrap_or(position.entry_price) * (Decimal::ONE - trailing_percent);
-                    
-                    if current_price <= trailing_stop {
-                        eprintln!("Trailing stop triggered for long position at ${} (trailing from ${})", current_price, highest_price);
-                        self.exit_position("Trailing Stop");
-                    }
-                },
-                crate::models::TradeSide::Short => {
-                    // For short positions, trail above the lowest price seen
-                    let lowest_price = self.price_history.iter()
-                        .map(|p| p.low)
-                        .fold(position.entry_price.to_f64().unwrap_or(f64::MAX), |a, b| a.min(b.to_f64().unwrap_or(f64::MAX)));
-                    
-                    let trailing_stop = Decimal::from_f64(lowest_price).unwrap_or(position.entry_price) * (Decimal::ONE + trailing_percent);
-                    
-                    if current_price >= trailing_stop {
-                        eprintln!("Trailing stop triggered for short position at ${} (trailing from ${})", current_price, lowest_price);
-                        self.exit_position("Trailing Stop");
-                    }
-                },
-            }
-        }
-    }
-
     fn update_performance_stats(&mut self, pnl: Decimal, hold_time: f64) {
         self.performance_stats.total_trades += 1;
         self.performance_stats.total_pnl += pnl;
@@ -2689,6 +3391,7 @@ impl SwingTradingBot {
         if now.date_naive() != self.daily_reset_time.date_naive() {
             eprintln!("Daily loss tracker reset for new day: {}", now.date_naive());
             self.daily_loss_tracker = Decimal::ZERO;
+            self.consecutive_losses = 0;
             self.daily_reset_time = now;
         }
         
@@ -2833,6 +3536,12 @@ impl SwingTradingBot {
             enhanced_lro.reset();
             log_info!(LogCategory::Configuration, "Enhanced LRO calculator reset successfully");
         }
+        self.optimized_lro = None;
+    }
+
+    /// Get optimized LRO statistics when that engine is active
+    pub fn get_optimized_lro_statistics(&self) -> Option<crate::optimized_lro::LROStatistics> {
+        self.optimized_lro.as_ref().map(|lro| lro.get_statistics())
     }
 
     /// Determine market condition based on LRO value
@@ -2845,7 +3554,9 @@ impl SwingTradingBot {
             lro_value.abs().min(1.0)
         };
         
-        let market_phase = if lro_value.abs() > self.config.overbought * 0.8 {
+        let market_phase = if let Some(phase) = self.regime_market_phase() {
+            phase
+        } else if lro_value.abs() > self.strategy_config().overbought * 0.8 {
             MarketPhase::Trending
         } else if lro_value.abs() < 0.2 {
             MarketPhase::Ranging
@@ -2874,4 +3585,41 @@ impl Default for BotPerformance {
             success_rate: 0.0,
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signal_fusion::SignalComponent;
+
+    fn reading(symbol: &str, score: f64) -> IntelligenceReading {
+        IntelligenceReading { symbol: symbol.to_string(), score, confidence: 0.8, timestamp: Utc::now() }
+    }
+
+    #[test]
+    fn test_fused_signal_uses_intelligence_for_feed_symbol() {
+        let mut bot = SwingTradingBot::new(LROConfig::default());
+        bot.set_symbol("ethusdt");
+        bot.set_intelligence_reading(reading("BTCUSDT", 10.0));
+        bot.set_intelligence_reading(reading("ETHUSDT", 80.0));
+        let signal = LROSignal {
+            timestamp: Utc::now(),
+            lro_value: -0.9,
+            signal_line: -0.95,
+            signal_type: SignalType::Buy,
+            strength: 0.7,
+            market_condition: MarketCondition {
+                trend_strength: 0.0,
+                volatility: 0.2,
+                volume_profile: 1.0,
+                market_phase: MarketPhase::Ranging,
+            },
+        };
+
+        let fused = bot.fuse_signal(&signal, crate::models::TradeSide::Long);
+        let sentiment = fused.components.iter()
+            .find(|c| c.component == SignalComponent::Sentiment)
+            .expect("sentiment component");
+        assert_eq!(bot.symbol, "ETHUSDT");
+        assert_eq!(sentiment.raw, Some(80.0));
+    }
+}
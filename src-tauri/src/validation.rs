@@ -320,12 +320,14 @@ impl InputValidator {
             ));
         }
 
-        // Validate position size
-        if config.max_position_size <= 0.0 || config.max_position_size > 1.0 {
+        // Validate position size; a legacy balance fraction must convert to a notional cap
+        let mut position_cap = config.clone();
+        position_cap.migrate_legacy_fields()?;
+        if position_cap.max_position_notional <= 0.0 {
             return Err(TradingError::validation_error(
-                "max_position_size".to_string(),
-                "Max position size must be between 0% and 100%".to_string(),
-                Some(config.max_position_size.to_string())
+                "max_position_notional".to_string(),
+                "Max position notional must be positive".to_string(),
+                Some(position_cap.max_position_notional.to_string())
             ));
         }
 
@@ -427,6 +429,48 @@ impl InputValidator {
         Ok(())
     }
 
+    /// Validate one resolved risk limit profile; `scope` names it in errors (e.g. "symbol:BTCUSDT")
+    pub fn validate_risk_limit_profile(&self, scope: &str, profile: &crate::risk_limits::RiskLimitProfile) -> TradingResult<()> {
+        let field = |name: &str| format!("{}.{}", scope, name);
+        let global = &profile.global;
+        if global.max_portfolio_var <= Decimal::ZERO {
+            return Err(TradingError::validation_error(
+                field("global.max_portfolio_var"),
+                "Maximum portfolio VaR must be greater than zero".to_string(),
+                Some(global.max_portfolio_var.to_string())
+            ));
+        }
+        self.validate_numeric_range(global.max_single_position_percent, 0.1, 100.0, &field("global.max_single_position_percent"))?;
+        self.validate_numeric_range(global.max_sector_concentration, 0.1, 100.0, &field("global.max_sector_concentration"))?;
+        self.validate_numeric_range(global.max_correlation_exposure, 0.1, 100.0, &field("global.max_correlation_exposure"))?;
+        self.validate_numeric_range(global.max_daily_loss_percent, 0.1, 100.0, &field("global.max_daily_loss_percent"))?;
+        self.validate_numeric_range(global.max_drawdown_percent, 0.1, 100.0, &field("global.max_drawdown_percent"))?;
+        self.validate_numeric_range(global.leverage_limit, 1.0, 125.0, &field("global.leverage_limit"))?;
+        self.validate_numeric_range(global.liquidity_requirement, 0.0, 1.0, &field("global.liquidity_requirement"))?;
+
+        let concentration = &profile.concentration;
+        self.validate_numeric_range(concentration.max_single_position, 0.1, 100.0, &field("concentration.max_single_position"))?;
+        self.validate_numeric_range(concentration.max_sector_exposure, 0.1, 100.0, &field("concentration.max_sector_exposure"))?;
+        self.validate_numeric_range(concentration.max_correlated_positions, 0.1, 100.0, &field("concentration.max_correlated_positions"))?;
+        self.validate_numeric_range(concentration.min_diversification_ratio, 0.0, 1.0, &field("concentration.min_diversification_ratio"))?;
+
+        let trade = &profile.trade;
+        self.validate_numeric_range(trade.max_position_size_percent, 0.1, 100.0, &field("trade.max_position_size_percent"))?;
+        self.validate_numeric_range(trade.max_daily_loss_percent, 0.1, 100.0, &field("trade.max_daily_loss_percent"))?;
+        self.validate_numeric_range(trade.max_drawdown_percent, 0.1, 100.0, &field("trade.max_drawdown_percent"))?;
+        self.validate_numeric_range(trade.max_consecutive_losses, 1, 100, &field("trade.max_consecutive_losses"))?;
+        self.validate_numeric_range(trade.min_risk_reward_ratio, 0.0, 20.0, &field("trade.min_risk_reward_ratio"))?;
+        self.validate_numeric_range(trade.max_correlation_exposure, 0.1, 100.0, &field("trade.max_correlation_exposure"))?;
+        self.validate_numeric_range(trade.volatility_threshold, 0.001, 1.0, &field("trade.volatility_threshold"))?;
+
+        let gpu = &profile.gpu;
+        self.validate_numeric_range(gpu.max_risk_percent, 0.001, 1.0, &field("gpu.max_risk_percent"))?;
+        self.validate_numeric_range(gpu.skip_overall_risk, 0.0, 1.0, &field("gpu.skip_overall_risk"))?;
+        self.validate_numeric_range(gpu.volatility_window, 2, 1000, &field("gpu.volatility_window"))?;
+
+        Ok(())
+    }
+
     /// Validate price data for completeness and sanity
     pub fn validate_price_data(&self, data: &crate::models::PriceData) -> TradingResult<()> {
         // Validate OHLC relationships